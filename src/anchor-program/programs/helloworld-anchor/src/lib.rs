@@ -0,0 +1,90 @@
+/*
+NOTES:
+- The greeting of src/program-rust written in Anchor, to read side by side with the
+native program: Greet stores the message and counts the greeting, and fails with
+MessageTooLong when the message doesn't fit.
+- What Anchor does for you here, and the native program does by hand:
+  - instruction dispatch: an 8 byte sighash of "global:greet" instead of the
+  HelloWorldInstruction tag, decoded into `greet`'s arguments
+  - account checks: `Account<GreetingAccount>` checks the owner and the 8 byte
+  discriminator the data opens with (native: check_greeting_account() and the
+  schema header of migrations.rs, from schema 6 on the same discriminator)
+  - creating the account: `init_if_needed` creates the PDA at [GREETING_SEED,
+  greeter] on the first Greet (native: the client creates it with
+  createAccountWithSeed before greeting)
+  - serializing: the account is written back when the instruction returns
+  (native: store_greeting())
+- Only the greeting itself is here, none of the config, fees, stake or logs Greet
+grew since. `tests/compare.rs` runs the same greetings against both programs.
+- Build with `npm run build:anchor-program` (`anchor build`, needs the Anchor CLI).
+*/
+use anchor_lang::prelude::*;
+
+// The address the Anchor program is deployed at
+// NOTE After your first deploy, replace this (and the one in Anchor.toml) with the
+// output of `anchor keys list`.
+declare_id!("Fm2w3MCyoc6n3onxCDa7qbpQtTf73Cz7Y3GMxh8i53tE");
+
+/// Seed of a greeter's greeting account, followed by the greeter's key
+pub const GREETING_SEED: &[u8] = b"greeting";
+
+/// Longest message, in bytes, a greeting account has room for
+pub const MAX_TXT_LEN: usize = 32;
+
+#[program]
+pub mod helloworld_anchor {
+    use super::*;
+
+    /// Store `txt` in the greeter's greeting account and count the greeting
+    pub fn greet(ctx: Context<Greet>, txt: String) -> Result<()> {
+        require!(txt.len() <= MAX_TXT_LEN, HelloWorldError::MessageTooLong);
+        msg!("Greeting passed to program is {:?}", txt);
+        let greeting = &mut ctx.accounts.greeting_account;
+        greeting.txt = txt;
+        greeting.counter = greeting.counter.saturating_add(1);
+        msg!("Was sent message {}!", greeting.txt);
+        msg!("Greeted {} time(s)!", greeting.counter);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Greet<'info> {
+    #[account(
+        init_if_needed,
+        payer = greeter,
+        space = GreetingAccount::SPACE,
+        seeds = [GREETING_SEED, greeter.key().as_ref()],
+        bump,
+    )]
+    pub greeting_account: Account<'info, GreetingAccount>,
+    /// Pays for the greeting account the first time
+    #[account(mut)]
+    pub greeter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Define the type of state stored in accounts, the fields of the native
+/// GreetingAccount the greeting itself uses
+#[account]
+pub struct GreetingAccount {
+    /// message string
+    pub txt: String,
+    /// number of greetings
+    pub counter: u64,
+}
+
+impl GreetingAccount {
+    /// Size of the account: the discriminator, the String's u32 length prefix and
+    /// MAX_TXT_LEN bytes, and the counter
+    pub const SPACE: usize = 8 + 4 + MAX_TXT_LEN + 8;
+}
+
+#[error_code]
+pub enum HelloWorldError {
+    // NOTE Anchor numbers these from 6000, as the native program does (see its
+    // error.rs), so this is Custom(6000) where the native MessageTooLong, its second
+    // error, is Custom(6001)
+    #[msg("Message does not fit in the greeting account")]
+    MessageTooLong,
+}
@@ -0,0 +1,151 @@
+// NOTE These run the same greetings against the native program and the Anchor one and
+// check they end in the same state. Both run as BPF, loaded from dist/program, so build
+// them first: `npm run test:anchor-program` does. The native program can't be linked
+// in here (it's pinned to solana-program 1.6.6), so its Greet is encoded by hand.
+use anchor_lang::{AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+// Account discriminator + Borsh String (u32 length prefix + 12 bytes of text) + u64
+// counter + calendar (u16 year, 46 byte day bitmap) + u64 epoch of the last update +
+// the two u64 origin counters, see src/program-rust/src/migrations.rs
+const NATIVE_GREETING_SIZE: usize = 8 + 4 + 12 + 8 + 2 + 46 + 8 + 16;
+
+/// One of the programs under test
+struct Greeter {
+    name: &'static str,
+    greeting_account: Pubkey,
+    /// Bytes ahead of the message and counter in the greeting account's data, the
+    /// account discriminator for both
+    header: usize,
+    /// Custom error code of MessageTooLong
+    message_too_long: u32,
+    greet: Box<dyn Fn(&str) -> Instruction>,
+}
+
+fn native(program_id: Pubkey, greeting_account: Pubkey) -> Greeter {
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    Greeter {
+        name: "native",
+        greeting_account,
+        header: 8,
+        message_too_long: 6001,
+        greet: Box::new(move |txt| {
+            // HelloWorldInstruction::Greet is tag 0, followed by the Borsh String
+            let mut data = vec![0];
+            data.extend_from_slice(&(txt.len() as u32).to_le_bytes());
+            data.extend_from_slice(txt.as_bytes());
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(greeting_account, false),
+                    AccountMeta::new_readonly(config, false),
+                ],
+            )
+        }),
+    }
+}
+
+fn anchor(greeter: Pubkey) -> Greeter {
+    let (greeting_account, _) = Pubkey::find_program_address(
+        &[helloworld_anchor::GREETING_SEED, greeter.as_ref()],
+        &helloworld_anchor::ID,
+    );
+    Greeter {
+        name: "anchor",
+        greeting_account,
+        header: 8,
+        message_too_long: 6000,
+        greet: Box::new(move |txt| Instruction {
+            program_id: helloworld_anchor::ID,
+            accounts: helloworld_anchor::accounts::Greet {
+                greeting_account,
+                greeter,
+                system_program: system_program::id(),
+            }
+            .to_account_metas(None),
+            data: helloworld_anchor::instruction::Greet {
+                txt: txt.to_string(),
+            }
+            .data(),
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_same_greetings() {
+    let native_id = Pubkey::new_unique();
+    let native_greeting = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("helloworld", native_id, None);
+    program_test.add_program("helloworld_anchor", helloworld_anchor::ID, None);
+    program_test.add_account(
+        native_greeting,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; NATIVE_GREETING_SIZE],
+            owner: native_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    let too_long = "Hello".repeat(8);
+    for greeter in [native(native_id, native_greeting), anchor(payer.pubkey())] {
+        for (txt, counter) in [("Hello1234567", 1), ("World7654321", 2)] {
+            let mut transaction =
+                Transaction::new_with_payer(&[(greeter.greet)(txt)], Some(&payer.pubkey()));
+            transaction.sign(
+                &[&payer],
+                banks_client.get_latest_blockhash().await.unwrap(),
+            );
+            banks_client.process_transaction(transaction).await.unwrap();
+
+            let account = banks_client
+                .get_account(greeter.greeting_account)
+                .await
+                .expect("get_account")
+                .expect("greeting account not found");
+            let greeting =
+                <(String, u64)>::deserialize(&mut &account.data[greeter.header..]).unwrap();
+            assert_eq!(greeting, (txt.to_string(), counter), "{}", greeter.name);
+            // Both open with sha256("account:GreetingAccount")[..8]
+            assert_eq!(
+                account.data[..8],
+                helloworld_anchor::GreetingAccount::DISCRIMINATOR,
+                "{}",
+                greeter.name
+            );
+        }
+
+        // Both refuse a message the account has no room for
+        let mut transaction =
+            Transaction::new_with_payer(&[(greeter.greet)(&too_long)], Some(&payer.pubkey()));
+        transaction.sign(
+            &[&payer],
+            banks_client.get_latest_blockhash().await.unwrap(),
+        );
+        assert_eq!(
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(greeter.message_too_long)
+            ),
+            "{}",
+            greeter.name
+        );
+    }
+}
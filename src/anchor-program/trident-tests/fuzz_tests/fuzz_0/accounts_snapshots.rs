@@ -0,0 +1,50 @@
+// NOTE The accounts of each instruction as check() sees them before and after it runs,
+// decoded with the program's own types. The greeting account is an Option: it doesn't
+// exist before a greeter's first Greet.
+use anchor_lang::prelude::*;
+use helloworld_anchor::GreetingAccount;
+use trident_client::fuzzing::{anchor_lang, FuzzingError};
+
+pub struct GreetSnapshot<'info> {
+    pub greeting_account: Option<Account<'info, GreetingAccount>>,
+    pub greeter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GreetSnapshot<'info> {
+    pub fn deserialize_option(
+        accounts: &'info mut [Option<AccountInfo<'info>>],
+    ) -> core::result::Result<Self, FuzzingError> {
+        let mut accounts_iter = accounts.iter();
+        let greeting_account: Option<Account<GreetingAccount>> = accounts_iter
+            .next()
+            .ok_or(FuzzingError::NotEnoughAccounts(
+                "greeting_account".to_string(),
+            ))?
+            .as_ref()
+            .map(Account::try_from)
+            .transpose()
+            .unwrap_or(None);
+        let greeter: Signer<'_> = accounts_iter
+            .next()
+            .ok_or(FuzzingError::NotEnoughAccounts("greeter".to_string()))?
+            .as_ref()
+            .map(Signer::try_from)
+            .ok_or(FuzzingError::AccountNotFound("greeter".to_string()))?
+            .map_err(|_| FuzzingError::CannotDeserializeAccount("greeter".to_string()))?;
+        let system_program: Program<System> = accounts_iter
+            .next()
+            .ok_or(FuzzingError::NotEnoughAccounts(
+                "system_program".to_string(),
+            ))?
+            .as_ref()
+            .map(Program::try_from)
+            .ok_or(FuzzingError::AccountNotFound("system_program".to_string()))?
+            .map_err(|_| FuzzingError::CannotDeserializeAccount("system_program".to_string()))?;
+        Ok(Self {
+            greeting_account,
+            greeter,
+            system_program,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+pub mod helloworld_anchor_fuzz_instructions {
+    use crate::accounts_snapshots::*;
+    use helloworld_anchor::{GREETING_SEED, MAX_TXT_LEN};
+    use trident_client::fuzzing::*;
+
+    /// Greeters a sequence picks from: few, so the same greeting account is greeted
+    /// again and again within one sequence
+    const GREETERS: u8 = 3;
+
+    #[derive(Arbitrary, DisplayIx, FuzzTestExecutor, FuzzDeserialize)]
+    pub enum FuzzInstruction {
+        Greet(Greet),
+    }
+
+    #[derive(Arbitrary, Debug)]
+    pub struct Greet {
+        pub accounts: GreetAccounts,
+        pub data: GreetData,
+    }
+
+    #[derive(Arbitrary, Debug)]
+    pub struct GreetAccounts {
+        pub greeter: AccountId,
+    }
+
+    #[derive(Arbitrary, Debug)]
+    pub struct GreetData {
+        /// Sometimes longer than MAX_TXT_LEN, to be refused
+        pub txt: String,
+    }
+
+    impl<'info> IxOps<'info> for Greet {
+        type IxData = helloworld_anchor::instruction::Greet;
+        type IxAccounts = FuzzAccounts;
+        type IxSnapshot = GreetSnapshot<'info>;
+
+        fn get_data(
+            &self,
+            _client: &mut impl FuzzClient,
+            _fuzz_accounts: &mut FuzzAccounts,
+        ) -> Result<Self::IxData, FuzzingError> {
+            Ok(helloworld_anchor::instruction::Greet {
+                txt: self.data.txt.clone(),
+            })
+        }
+
+        fn get_accounts(
+            &self,
+            client: &mut impl FuzzClient,
+            fuzz_accounts: &mut FuzzAccounts,
+        ) -> Result<(Vec<Keypair>, Vec<AccountMeta>), FuzzingError> {
+            let greeter = fuzz_accounts.greeter.get_or_create_account(
+                self.accounts.greeter % GREETERS,
+                client,
+                5 * LAMPORTS_PER_SOL,
+            );
+            let greeting_account = fuzz_accounts
+                .greeting_account
+                .get_or_create_account(
+                    self.accounts.greeter % GREETERS,
+                    &[GREETING_SEED, greeter.pubkey().as_ref()],
+                    &helloworld_anchor::ID,
+                )
+                .ok_or(FuzzingError::Custom(1))?
+                .pubkey();
+            let accounts = helloworld_anchor::accounts::Greet {
+                greeting_account,
+                greeter: greeter.pubkey(),
+                system_program: SYSTEM_PROGRAM_ID,
+            }
+            .to_account_metas(None);
+            Ok((vec![greeter], accounts))
+        }
+
+        /// The invariants, after every Greet that lands: the account holds the message
+        /// and its counter went up by exactly one. There's no instruction taking a
+        /// greeting back, so over a whole sequence a counter only ever increases and
+        /// ends at the number of Greets of its account that landed.
+        fn check(
+            &self,
+            pre_ix: Self::IxSnapshot,
+            post_ix: Self::IxSnapshot,
+            ix_data: Self::IxData,
+        ) -> Result<(), FuzzingErrorWithOrigin> {
+            let before = pre_ix
+                .greeting_account
+                .as_ref()
+                .map_or(0, |greeting| greeting.counter);
+            let after = post_ix
+                .greeting_account
+                .as_ref()
+                .ok_or(FuzzingError::Custom(2))?;
+            if after.counter != before + 1 {
+                return Err(FuzzingError::Custom(3).into());
+            }
+            if after.txt != ix_data.txt {
+                return Err(FuzzingError::Custom(4).into());
+            }
+            // A message the account has no room for has to be refused
+            if ix_data.txt.len() > MAX_TXT_LEN {
+                return Err(FuzzingError::Custom(5).into());
+            }
+            Ok(())
+        }
+
+        /// A Greet may only be refused for a message the account has no room for. The
+        /// transaction fails as a whole, so the account is left as it was.
+        fn tx_error_handler(
+            &self,
+            e: FuzzClientErrorWithOrigin,
+            ix_data: Self::IxData,
+            _pre_ix_acc_infos: &'info mut [Option<AccountInfo<'info>>],
+        ) -> Result<(), FuzzClientErrorWithOrigin> {
+            if ix_data.txt.len() > MAX_TXT_LEN {
+                Ok(())
+            } else {
+                Err(e)
+            }
+        }
+    }
+
+    /// Accounts a sequence has made, by AccountId
+    #[derive(Default)]
+    pub struct FuzzAccounts {
+        greeter: AccountsStorage<Keypair>,
+        greeting_account: AccountsStorage<PdaStore>,
+    }
+}
@@ -0,0 +1,39 @@
+// NOTE Trident feeds honggfuzz's input to Arbitrary, which turns it into a sequence of
+// instructions (FuzzInstruction, see fuzz_instructions.rs) with the greeters and
+// messages they use. Each iteration runs a sequence against a fresh program-test bank,
+// checking the invariants after every instruction that lands, so what's explored is
+// the state a sequence builds up rather than one call on its own. A broken invariant
+// is a crash, saved under trident-tests/fuzz_tests/fuzzing/hfuzz_workspace/fuzz_0 and
+// replayed with `trident fuzz run-debug fuzz_0 <CRASH_FILE>`.
+//
+// Run with `npm run fuzz:anchor-program` (`trident fuzz run fuzz_0`, needs the
+// Trident CLI and honggfuzz). Settings are in Trident.toml.
+use trident_client::fuzzing::*;
+mod accounts_snapshots;
+mod fuzz_instructions;
+
+use fuzz_instructions::helloworld_anchor_fuzz_instructions::FuzzInstruction as FuzzInstruction_helloworld_anchor;
+use helloworld_anchor::entry as entry_helloworld_anchor;
+use helloworld_anchor::ID as PROGRAM_ID_HELLOWORLD_ANCHOR;
+const PROGRAM_NAME_HELLOWORLD_ANCHOR: &str = "helloworld_anchor";
+
+struct MyFuzzData;
+
+impl FuzzDataBuilder<FuzzInstruction_helloworld_anchor> for MyFuzzData {}
+
+fn main() {
+    loop {
+        fuzz_trident!(fuzz_ix: FuzzInstruction_helloworld_anchor, |fuzz_data: MyFuzzData| {
+            let fuzzing_program_helloworld_anchor = FuzzingProgram::new(
+                PROGRAM_NAME_HELLOWORLD_ANCHOR,
+                &PROGRAM_ID_HELLOWORLD_ANCHOR,
+                processor!(convert_entry!(entry_helloworld_anchor)),
+            );
+            let mut client =
+                ProgramTestClientBlocking::new(&[fuzzing_program_helloworld_anchor]).unwrap();
+            // NOTE A broken invariant panics in there, the result only reports a
+            // transaction the program refused, which tx_error_handler has looked at
+            let _ = fuzz_data.run_with_runtime(PROGRAM_ID_HELLOWORLD_ANCHOR, &mut client);
+        });
+    }
+}
@@ -0,0 +1,167 @@
+// NOTE Run with `cargo bench --manifest-path=src/bench/Cargo.toml`. Criterion keeps the
+// last run under src/bench/target/criterion and reports each benchmark's change
+// against it, so bench the base branch first and then the refactor.
+//
+// Three paths per account type: Borsh decoding and encoding of the whole account, the
+// in-place reads and writes that skip Borsh (increment.rs), and the processors that
+// load, validate and write the accounts of a whole instruction.
+use borsh::BorshSerialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use helloworld::{
+    compact,
+    config::{Config, CONFIG_SIZE},
+    fixtures, increment,
+    instruction::HelloWorldInstruction,
+    migrations,
+    milestone::{self, GreetingSnapshot},
+    origin::Origin,
+    process_instruction,
+    receipt::{GreetingReceipt, RECEIPT_SIZE},
+    tags::{TagCounters, TAG_COUNTERS_SIZE},
+    test_utils::{self, EPOCH, NOW},
+    GreetingAccount, STATE_VERSION,
+};
+use helloworld_bench as values;
+
+fn greeting_account(c: &mut Criterion) {
+    let greeting = values::greeting_account();
+    let borsh = migrations::encode_as(STATE_VERSION, &greeting).unwrap();
+    let compact = compact::encode(&greeting);
+    let oldest = migrations::encode_as(1, &greeting).unwrap();
+
+    let mut group = c.benchmark_group("greeting_account");
+    group.bench_function("borsh/deserialize", |b| {
+        b.iter(|| GreetingAccount::unpack(black_box(&borsh)).unwrap())
+    });
+    group.bench_function("borsh/serialize", |b| {
+        b.iter(|| migrations::encode_as(STATE_VERSION, black_box(&greeting)).unwrap())
+    });
+    group.bench_function("borsh/deserialize_migrating_from_1", |b| {
+        b.iter(|| GreetingAccount::unpack(black_box(&oldest)).unwrap())
+    });
+    group.bench_function("compact/decode", |b| {
+        b.iter(|| compact::decode(black_box(&compact)).unwrap())
+    });
+    group.bench_function("compact/encode", |b| {
+        b.iter(|| compact::encode(black_box(&greeting)))
+    });
+    group.bench_function("in_place/counter", |b| {
+        b.iter(|| increment::counter(black_box(&borsh)).unwrap())
+    });
+    let mut data = borsh.clone();
+    group.bench_function("in_place/increment", |b| {
+        b.iter(|| {
+            increment::increment(black_box(&mut data), 1, 2, EPOCH, NOW, Some(Origin::Direct))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn config(c: &mut Criterion) {
+    let config = values::config();
+    let mut data = vec![0; CONFIG_SIZE];
+    config.pack(&mut data).unwrap();
+
+    let mut group = c.benchmark_group("config");
+    group.bench_function("deserialize", |b| {
+        b.iter(|| Config::unpack(black_box(&data)).unwrap())
+    });
+    let mut out = vec![0; CONFIG_SIZE];
+    group.bench_function("serialize", |b| {
+        b.iter(|| black_box(&config).pack(&mut out).unwrap())
+    });
+    group.finish();
+}
+
+fn tag_counters(c: &mut Criterion) {
+    let counters = values::tag_counters();
+    let mut data = vec![0; TAG_COUNTERS_SIZE];
+    counters.pack(&mut data).unwrap();
+
+    let mut group = c.benchmark_group("tag_counters");
+    group.bench_function("deserialize", |b| {
+        b.iter(|| TagCounters::unpack(black_box(&data)).unwrap())
+    });
+    let mut out = vec![0; TAG_COUNTERS_SIZE];
+    group.bench_function("serialize", |b| {
+        b.iter(|| black_box(&counters).pack(&mut out).unwrap())
+    });
+    group.finish();
+}
+
+fn receipt(c: &mut Criterion) {
+    let receipt = values::receipt();
+    let mut data = vec![0; RECEIPT_SIZE];
+    receipt.pack(&mut data);
+
+    let mut group = c.benchmark_group("receipt");
+    group.bench_function("deserialize", |b| {
+        b.iter(|| GreetingReceipt::unpack(black_box(&data)).unwrap())
+    });
+    let mut out = vec![0; RECEIPT_SIZE];
+    group.bench_function("serialize", |b| {
+        b.iter(|| black_box(&receipt).pack(&mut out))
+    });
+    group.finish();
+}
+
+fn snapshot(c: &mut Criterion) {
+    let snapshot = values::snapshot();
+    let size = milestone::snapshot_size(&snapshot.label);
+    let mut data = vec![0; size];
+    snapshot.pack(&mut data).unwrap();
+
+    let mut group = c.benchmark_group("snapshot");
+    group.bench_function("deserialize", |b| {
+        b.iter(|| GreetingSnapshot::unpack(black_box(&data)).unwrap())
+    });
+    let mut out = vec![0; size];
+    group.bench_function("serialize", |b| {
+        b.iter(|| black_box(&snapshot).pack(&mut out).unwrap())
+    });
+    group.finish();
+}
+
+/// The processors through process_instruction(), as the entrypoint calls them: decoding
+/// the instruction, loading and checking every account, then the write
+// NOTE CPIs do nothing off-chain, so only instructions that don't need one to succeed
+// are here
+fn processors(c: &mut Criterion) {
+    let program_id = fixtures::program_id();
+    let size = migrations::encode_as(STATE_VERSION, &values::greeting_account())
+        .unwrap()
+        .len();
+    let greeted = fixtures::greeting_account("greeted", size);
+    let config = fixtures::config_account(&Config::default());
+    let accounts = [greeted, config];
+    let greet = HelloWorldInstruction::Greet {
+        txt: "Hello1234567".to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+
+    // The processors log on every call, which would be most of what's measured
+    test_utils::without_logs(|| {
+        let mut group = c.benchmark_group("processors");
+        group.bench_function("greet", |b| {
+            b.iter(|| process_instruction(&program_id, &accounts, black_box(&greet)).unwrap())
+        });
+        group.bench_function("increment", |b| {
+            b.iter(|| process_instruction(&program_id, &accounts, black_box(&increment)).unwrap())
+        });
+        group.finish();
+    });
+}
+
+criterion_group!(
+    benches,
+    greeting_account,
+    config,
+    tag_counters,
+    receipt,
+    snapshot,
+    processors
+);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+//! The values the benchmarks in benches/ run on
+// NOTE Criterion needs newer crates than the solana 1.6 toolchain builds with, so the
+// benchmarks are a crate of their own, outside the workspace with their own lockfile
+// (like src/wasm). They run the program's code natively: a baseline for comparing a
+// refactor's before and after on the same machine, not compute units. The profiling
+// feature measures those on SBF.
+//
+// Each value is a busy account's, so the benchmarks take the longer paths: every
+// optional field present, a counter past the one-byte varints, every tag slot taken.
+use helloworld::{
+    calendar::DayBitmap,
+    config::Config,
+    milestone::GreetingSnapshot,
+    receipt::GreetingReceipt,
+    tags::{TagCount, TagCounters, MAX_TAGS},
+    GreetingAccount,
+};
+
+/// A greeting account with every field set
+pub fn greeting_account() -> GreetingAccount {
+    let mut greeted_days = DayBitmap::default();
+    for day in (0..366).step_by(3) {
+        greeted_days.set(day);
+    }
+    GreetingAccount {
+        txt: "Hello1234567".to_string(),
+        counter: 1_000_000,
+        calendar_year: 2024,
+        greeted_days,
+        last_update_epoch: 500,
+        direct_greets: 999_000,
+        cpi_greets: 1_000,
+    }
+}
+
+/// A config with every field set
+pub fn config() -> Config {
+    Config {
+        authority: [1; 32],
+        decay_half_life_epochs: 2,
+        crank_authority: [3; 32],
+        fee_usd_micros: 4,
+        max_price_age_slots: 5,
+        max_confidence_bps: 6,
+        price_feed: [7; 32],
+        fee_recipient: [8; 32],
+        stake_mint: [9; 32],
+        stake_unit: 10,
+        greeting_log: [11; 32],
+        paused: false,
+        max_message_len: 13,
+        cooldown_days: 14,
+        max_greet_many_accounts: 15,
+        claim_root: [16; 32],
+        greeting_receipts: true,
+        balance_mint: [18; 32],
+        balance_unit: 19,
+        epoch_bonus_slots: 20,
+    }
+}
+
+/// Tag counters with every tag slot taken
+pub fn tag_counters() -> TagCounters {
+    TagCounters {
+        greeting_account: [1; 32],
+        entries: (0..MAX_TAGS)
+            .map(|index| {
+                let mut tag = [0; 8];
+                tag[..4].copy_from_slice(b"tag-");
+                tag[4] = b'a' + index as u8;
+                TagCount {
+                    tag,
+                    count: 1_000 + index as u64,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// A receipt
+pub fn receipt() -> GreetingReceipt {
+    GreetingReceipt {
+        signer: [1; 32],
+        slot: 200_000,
+        unix_timestamp: 1_700_000_000,
+    }
+}
+
+/// A snapshot with the longest label
+pub fn snapshot() -> GreetingSnapshot {
+    GreetingSnapshot {
+        greeting_account: [1; 32],
+        recorder: [2; 32],
+        counter: 1_000_000,
+        slot: 200_000,
+        unix_timestamp: 1_700_000_000,
+        label: "l".repeat(helloworld::milestone::MAX_LABEL_LEN),
+    }
+}
@@ -0,0 +1,92 @@
+/**
+ * NOTES:
+ * - Compares the compute units of updating the greeting account's counter two ways:
+ * Greet, which decodes the whole account with Borsh and encodes it back, and
+ * Increment, which writes the counter, calendar and epoch fields in place through
+ * arrayref (see src/program-rust/src/increment.rs).
+ *   npm run bench:fields -- [--runs 5]
+ * - Both are only simulated, on the payer's greeting account, so nothing is sent and
+ * the account is the same for every run. Run `npm run start` once first so the account
+ * is on the current schema, which Increment needs.
+ * - The deployment must charge no fee and keep no greeting log or receipts, Increment
+ * refuses those. Greet's cost grows with the message, so it's run with the one main.ts
+ * saves.
+ */
+
+import {Connection, Transaction} from '@solana/web3.js';
+
+import {
+  checkProgram,
+  clientAccounts,
+  configAddress,
+  establishConnection,
+  establishPayer,
+  greetInstruction,
+  incrementInstruction,
+} from './hello_world';
+import {simulate} from './simulation';
+
+const MESSAGE = 'Hello1234567';
+
+async function main() {
+  const argv = process.argv.slice(2);
+  const runsIndex = argv.indexOf('--runs');
+  const runs = runsIndex >= 0 ? Number(argv[runsIndex + 1]) : 5;
+
+  await establishConnection();
+  await establishPayer();
+  await checkProgram();
+  const {rpcUrl, payer, programId, greeted} = clientAccounts();
+  const connection = new Connection(rpcUrl, 'confirmed');
+  const config = await configAddress(programId);
+
+  const cases: [string, () => Transaction][] = [
+    [
+      'Greet (Borsh)',
+      () =>
+        new Transaction().add(
+          greetInstruction(programId, greeted, config, MESSAGE),
+        ),
+    ],
+    [
+      'Increment (arrayref)',
+      () =>
+        new Transaction().add(incrementInstruction(programId, greeted, config)),
+    ],
+  ];
+  const means: number[] = [];
+  for (const [name, transaction] of cases) {
+    const units: number[] = [];
+    for (let run = 0; run < runs; run++) {
+      const report = await simulate(
+        connection,
+        transaction(),
+        payer,
+        programId,
+      );
+      if (!report.ok || report.unitsConsumed === null) {
+        const reason = report.error ? report.error.message : 'no units logged';
+        throw new Error(`${name} failed: ${reason}\n${report.logs.join('\n')}`);
+      }
+      units.push(report.unitsConsumed);
+    }
+    const mean = units.reduce((sum, cu) => sum + cu, 0) / units.length;
+    means.push(mean);
+    const range = `min ${Math.min(...units)}, max ${Math.max(...units)}`;
+    const cu = mean.toFixed(0).padStart(8);
+    console.log(`${name.padEnd(22)} ${cu} CU (${range})`);
+  }
+  const saved = means[0] - means[1];
+  const percent = ((100 * saved) / means[0]).toFixed(1);
+  console.log(
+    `Increment saves ${saved.toFixed(0)} CU a greeting (${percent}%)`,
+  );
+}
+
+main().then(
+  () => process.exit(),
+  err => {
+    console.error(err);
+    process.exit(-1);
+  },
+);
@@ -0,0 +1,247 @@
+/**
+ * NOTES:
+ * - Claims against a merkle root in the config: an allowlist, or a distribution of
+ * lamports paid out of the claim vault, each leaf once (see
+ * src/program-rust/src/claim.rs).
+ * - The list is a JSON file of `[{"claimant": "<PUBKEY>", "amount": "<LAMPORTS>"}]`,
+ * leaf i at index i. It's published as is; claimants rebuild their proof from it.
+ * - Usage:
+ *   npm run claim -- root <LIST_FILE>       print the list's root
+ *   npm run claim -- set-root <LIST_FILE>   set it as the config's claim root
+ *   npm run claim -- claim <LIST_FILE>      claim the payer's leaf
+ * - The payer signs, so it must be the config authority for set-root. Fund the vault
+ * with a plain SOL transfer to claimVaultAddress() before anyone claims.
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import {createHash} from 'crypto';
+import fs from 'mz/fs';
+
+import {instruction, setConfigInstruction} from './crank';
+import {nodeHash} from './counter_snapshot';
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {
+  CLAIMED_SEED,
+  CLAIMS_PER_BITMAP,
+  CLAIM_VAULT_SEED,
+  ClaimArgs,
+  HelloWorldInstruction,
+} from './generated';
+import {sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+export interface ClaimLeaf {
+  claimant: PublicKey;
+  amount: BN;
+}
+
+/**
+ * The leaf paying `amount` lamports to `claimant` at `index`
+ */
+export function claimLeafHash(
+  index: number,
+  claimant: PublicKey,
+  amount: BN,
+): Buffer {
+  const indexBytes = Buffer.alloc(4);
+  indexBytes.writeUInt32LE(index, 0);
+  return createHash('sha256')
+    .update(Buffer.from([0]))
+    .update(indexBytes)
+    .update(claimant.toBuffer())
+    .update(amount.toArrayLike(Buffer, 'le', 8))
+    .digest();
+}
+
+/**
+ * Every level of the tree over `leaves`, from the leaves up to the root, padded
+ * with all-zero leaves to a power of two
+ */
+function levels(leaves: ClaimLeaf[]): Buffer[][] {
+  let level = leaves.map(({claimant, amount}, index) =>
+    claimLeafHash(index, claimant, amount),
+  );
+  while (level.length & (level.length - 1)) {
+    level.push(Buffer.alloc(32));
+  }
+  const all = [level];
+  while (level.length > 1) {
+    const next = [];
+    for (let i = 0; i < level.length; i += 2) {
+      next.push(nodeHash(level[i], level[i + 1]));
+    }
+    level = next;
+    all.push(level);
+  }
+  return all;
+}
+
+/**
+ * The root of the list `leaves`, all zeros for an empty list
+ */
+export function claimRoot(leaves: ClaimLeaf[]): Buffer {
+  if (leaves.length === 0) {
+    return Buffer.alloc(32);
+  }
+  const all = levels(leaves);
+  return all[all.length - 1][0];
+}
+
+/**
+ * The siblings on the path from leaf `index` of `leaves` to the root, from the leaf
+ * up
+ */
+export function claimProof(leaves: ClaimLeaf[], index: number): Buffer[] {
+  if (index < 0 || index >= leaves.length) {
+    throw new Error(`No leaf ${index} in a list of ${leaves.length}`);
+  }
+  return levels(leaves)
+    .slice(0, -1)
+    .map((hashes, level) => hashes[(index >> level) ^ 1]);
+}
+
+/**
+ * Address of the claim vault, a PDA at seeds [CLAIM_VAULT_SEED]
+ */
+export async function claimVaultAddress(
+  programId: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(CLAIM_VAULT_SEED)],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Address of the claimed bitmap of `root` holding leaf `index`
+ */
+export async function claimedAddress(
+  programId: PublicKey,
+  root: Buffer,
+  index: number,
+): Promise<PublicKey> {
+  const number = Buffer.alloc(4);
+  number.writeUInt32LE(Math.floor(index / CLAIMS_PER_BITMAP), 0);
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(CLAIMED_SEED), root, number],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Build a Claim instruction paying `claimant` its leaf `index` of the list with
+ * `root`
+ */
+export async function claimInstruction(
+  programId: PublicKey,
+  claimant: PublicKey,
+  root: Buffer,
+  index: number,
+  amount: BN,
+  proof: Buffer[],
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  const claimed = await claimedAddress(programId, root, index);
+  const vault = await claimVaultAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: claimant, isSigner: true, isWritable: true},
+      {pubkey: claimed, isSigner: false, isWritable: true},
+      {pubkey: vault, isSigner: false, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.Claim,
+    new ClaimArgs({index, amount, proof}),
+  );
+}
+
+/**
+ * Read a list file
+ */
+export async function readClaimList(path: string): Promise<ClaimLeaf[]> {
+  const list = JSON.parse(await fs.readFile(path, 'utf8'));
+  return list.map(({claimant, amount}: {claimant: string; amount: string}) => ({
+    claimant: new PublicKey(claimant),
+    amount: new BN(amount),
+  }));
+}
+
+async function main() {
+  const [command, listFile] = process.argv.slice(2);
+  if (['root', 'set-root', 'claim'].indexOf(command) < 0 || !listFile) {
+    throw new Error(
+      'Usage: claim root <LIST_FILE>\n' +
+        '       claim set-root <LIST_FILE>\n' +
+        '       claim claim <LIST_FILE>',
+    );
+  }
+  const leaves = await readClaimList(listFile);
+  const root = claimRoot(leaves);
+  if (command === 'root') {
+    console.log(root.toString('hex'));
+    return;
+  }
+
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const payer = await getPayerSigner();
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  if (command === 'set-root') {
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await setConfigInstruction(programId, payer.publicKey, {
+          claim_root: root,
+        }),
+      ),
+      [payer],
+      programId,
+    );
+    console.log(`The claim root is now ${root.toString('hex')}`);
+  } else {
+    const index = leaves.findIndex(({claimant}) =>
+      claimant.equals(payer.publicKey),
+    );
+    if (index < 0) {
+      throw new Error(`${payer.publicKey.toBase58()} is not in the list`);
+    }
+    const {amount} = leaves[index];
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await claimInstruction(
+          programId,
+          payer.publicKey,
+          root,
+          index,
+          amount,
+          claimProof(leaves, index),
+        ),
+      ),
+      [payer],
+      programId,
+    );
+    console.log(`Claimed leaf ${index}, ${amount.toString()} lamports`);
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
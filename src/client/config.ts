@@ -0,0 +1,208 @@
+/**
+ * NOTES:
+ * - Named cluster profiles in ~/.config/helloworld/config.toml (or $HELLOWORLD_CONFIG),
+ * so switching between localnet, devnet and mainnet is one command instead of a set
+ * of flags and environment variables on every run:
+ *   npm run config -- use devnet
+ *   npm run config -- set program-id <PROGRAM_ID> [--profile devnet]
+ *   npm run config -- get [key] [--profile devnet]
+ * - A profile can set url, program_id, keypair (any signer source, see signer.ts) and
+ * commitment. Whatever it leaves out falls back to the usual places: the solana CLI
+ * config, dist/program/deployments.json and the program keypair file. RPC_URLS and
+ * PAYER_KEYPAIR still win over the profile.
+ * - The profile in use is HELLOWORLD_PROFILE if set, otherwise `active` in the file.
+ * Without a config file nothing changes; `set` and `use` create it with localnet,
+ * devnet and mainnet profiles.
+ * - Only the TOML this file writes is read back: `key = "string"` pairs, `[profiles.x]`
+ * tables and comments.
+ */
+
+import {Commitment} from '@solana/web3.js';
+import fs from 'mz/fs';
+import os from 'os';
+import path from 'path';
+
+export const CONFIG_PATH =
+  process.env.HELLOWORLD_CONFIG ||
+  path.resolve(os.homedir(), '.config', 'helloworld', 'config.toml');
+
+export interface Profile {
+  url?: string;
+  programId?: string;
+  keypair?: string;
+  commitment?: Commitment;
+}
+
+export interface Config {
+  active?: string;
+  profiles: Record<string, Profile>;
+}
+
+/**
+ * TOML keys of the profile fields
+ */
+const KEYS: Record<string, keyof Profile> = {
+  url: 'url',
+  program_id: 'programId',
+  keypair: 'keypair',
+  commitment: 'commitment',
+};
+
+const COMMITMENTS = ['processed', 'confirmed', 'finalized'];
+
+export const DEFAULT_CONFIG: Config = {
+  active: 'localnet',
+  profiles: {
+    localnet: {url: 'http://localhost:8899', commitment: 'confirmed'},
+    devnet: {url: 'https://api.devnet.solana.com', commitment: 'confirmed'},
+    mainnet: {
+      url: 'https://api.mainnet-beta.solana.com',
+      commitment: 'finalized',
+    },
+  },
+};
+
+function profileKey(key: string): keyof Profile {
+  const field = KEYS[key.replace(/-/g, '_')];
+  if (!field) {
+    throw new Error(
+      `Unknown setting ${key}, expected one of ${Object.keys(KEYS).join(', ')}`,
+    );
+  }
+  return field;
+}
+
+function setField(profile: Profile, key: string, value: string): void {
+  const field = profileKey(key);
+  if (field === 'commitment') {
+    if (COMMITMENTS.indexOf(value) < 0) {
+      throw new Error(`Commitment must be one of ${COMMITMENTS.join(', ')}`);
+    }
+    profile.commitment = value as Commitment;
+  } else {
+    profile[field] = value;
+  }
+}
+
+export function parseConfig(text: string): Config {
+  const config: Config = {profiles: {}};
+  let profile: Profile | undefined;
+  text.split('\n').forEach((raw, i) => {
+    const line = raw.trim();
+    if (line === '' || line.startsWith('#')) {
+      return;
+    }
+    const table = /^\[profiles\.([\w-]+)\]$/.exec(line);
+    if (table) {
+      profile = config.profiles[table[1]] = config.profiles[table[1]] || {};
+      return;
+    }
+    const pair = /^(\w+)\s*=\s*("(?:[^"\\]|\\.)*")\s*(#.*)?$/.exec(line);
+    if (!pair) {
+      throw new Error(`${CONFIG_PATH}:${i + 1}: can't parse ${line}`);
+    }
+    // NOTE TOML basic strings escape the way JSON strings do
+    const value = JSON.parse(pair[2]) as string;
+    if (profile) {
+      setField(profile, pair[1], value);
+    } else if (pair[1] === 'active') {
+      config.active = value;
+    } else {
+      throw new Error(`${CONFIG_PATH}:${i + 1}: unknown key ${pair[1]}`);
+    }
+  });
+  return config;
+}
+
+export function formatConfig(config: Config): string {
+  const lines = ['# helloworld client profiles, see src/client/config.ts'];
+  if (config.active) {
+    lines.push(`active = ${JSON.stringify(config.active)}`);
+  }
+  for (const name of Object.keys(config.profiles)) {
+    lines.push('', `[profiles.${name}]`);
+    for (const key of Object.keys(KEYS)) {
+      const value = config.profiles[name][KEYS[key]];
+      if (value !== undefined) {
+        lines.push(`${key} = ${JSON.stringify(value)}`);
+      }
+    }
+  }
+  return lines.join('\n') + '\n';
+}
+
+export async function readConfig(): Promise<Config | undefined> {
+  if (!fs.existsSync(CONFIG_PATH)) {
+    return undefined;
+  }
+  return parseConfig(await fs.readFile(CONFIG_PATH, {encoding: 'utf8'}));
+}
+
+async function writeConfig(config: Config): Promise<void> {
+  await fs.mkdir(path.dirname(CONFIG_PATH), {recursive: true});
+  await fs.writeFile(CONFIG_PATH, formatConfig(config));
+}
+
+/**
+ * The profile in use, empty without a config file
+ */
+export async function getProfile(): Promise<Profile> {
+  const config = await readConfig();
+  const name = process.env.HELLOWORLD_PROFILE || (config && config.active);
+  if (!config || !name) {
+    return {};
+  }
+  const profile = config.profiles[name];
+  if (!profile) {
+    throw new Error(`No profile ${name} in ${CONFIG_PATH}`);
+  }
+  return profile;
+}
+
+async function main() {
+  const args = process.argv.slice(2);
+  const profileIndex = args.indexOf('--profile');
+  const profileArg =
+    profileIndex >= 0 ? args.splice(profileIndex, 2)[1] : undefined;
+  const [command, key, value] = args;
+  const config = (await readConfig()) || DEFAULT_CONFIG;
+  const name = profileArg || process.env.HELLOWORLD_PROFILE || config.active;
+
+  if (command === 'use' && key) {
+    config.profiles[key] = config.profiles[key] || {};
+    config.active = key;
+    await writeConfig(config);
+    console.log(`Using profile ${key} (${CONFIG_PATH})`);
+  } else if (command === 'set' && key && value !== undefined && name) {
+    config.profiles[name] = config.profiles[name] || {};
+    setField(config.profiles[name], key, value);
+    await writeConfig(config);
+    console.log(`${name}: ${key} = ${value}`);
+  } else if (command === 'get' && name) {
+    const profile = config.profiles[name] || {};
+    if (key) {
+      console.log(profile[profileKey(key)] || '');
+    } else {
+      console.log(`Profile ${name} (${CONFIG_PATH})`);
+      for (const tomlKey of Object.keys(KEYS)) {
+        console.log(`  ${tomlKey}: ${profile[KEYS[tomlKey]] || '(not set)'}`);
+      }
+    }
+  } else {
+    throw new Error(
+      'Usage: config use <PROFILE>\n' +
+        '       config set <KEY> <VALUE> [--profile <PROFILE>]\n' +
+        '       config get [KEY] [--profile <PROFILE>]',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
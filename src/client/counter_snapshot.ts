@@ -0,0 +1,401 @@
+/**
+ * NOTES:
+ * - A counter snapshot is a merkle root over the counters of greeting accounts, kept
+ * up to date by the crank authority, for airdrops and off-chain proofs of greeting
+ * activity (see src/program-rust/src/counter_snapshot.rs).
+ * - Usage:
+ *   npm run counter-snapshot -- init <DEPTH>                   create a snapshot
+ *   npm run counter-snapshot -- update <SNAPSHOT> [ACCOUNT...]  snapshot counters
+ *   npm run counter-snapshot -- proof <SNAPSHOT> <ACCOUNT>     print a proof
+ * - The payer signs, so it must be the config authority for init and the crank
+ * authority for update. Without ACCOUNTs update snapshots every greeting account of
+ * the program.
+ * - The snapshot holds 2^DEPTH greeting accounts, at most 2^MAX_SNAPSHOT_DEPTH. It is
+ * allocated here, past the 10 KiB the program could allocate itself, so init pays
+ * the rent of the whole tree up front.
+ * - The program trusts the leaf index it's given for an account not in the tree yet,
+ * so update looks every account up first and only hands out the next free leaves.
+ * - A proof is JSON: the root and slot it was taken at, the account's leaf index and
+ * counter, and the siblings from the leaf up, checked by verifyProof().
+ */
+
+import {
+  Account,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import * as borsh from 'borsh';
+import BN from 'bn.js';
+import {createHash} from 'crypto';
+
+import {instruction} from './crank';
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {
+  CounterSnapshot,
+  COUNTER_SNAPSHOT_DISCRIMINATOR,
+  HelloWorldInstruction,
+  HelloWorldSchema,
+  InitCounterSnapshotArgs,
+  MAX_SNAPSHOT_DEPTH,
+  SNAPSHOT_HEADER_SIZE,
+  SNAPSHOT_LEAF_SIZE,
+  SnapshotCountersArgs,
+} from './generated';
+import {sendAndConfirm} from './signer';
+import {decodeGreeting} from './state';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Greeting accounts per SnapshotCounters, well within the transaction size limit and
+ * the compute budget of `depth` hashes each
+ */
+export const SNAPSHOT_BATCH = 16;
+
+export interface Leaf {
+  key: PublicKey;
+  counter: BN;
+}
+
+export interface Proof {
+  root: string;
+  slot: string;
+  account: string;
+  index: number;
+  counter: string;
+  siblings: string[];
+}
+
+/**
+ * Size of a snapshot account holding a tree `depth` deep
+ */
+export function counterSnapshotSize(depth: number): number {
+  const capacity = 2 ** depth;
+  const leaves = capacity * SNAPSHOT_LEAF_SIZE;
+  return SNAPSHOT_HEADER_SIZE + leaves + (2 * capacity - 2) * 32;
+}
+
+/**
+ * The leaf of a greeting account
+ */
+export function leafHash(key: PublicKey, counter: BN): Buffer {
+  return createHash('sha256')
+    .update(Buffer.from([0]))
+    .update(key.toBuffer())
+    .update(counter.toArrayLike(Buffer, 'le', 8))
+    .digest();
+}
+
+/**
+ * The node over two children
+ */
+export function nodeHash(left: Buffer, right: Buffer): Buffer {
+  return createHash('sha256')
+    .update(Buffer.from([1]))
+    .update(left)
+    .update(right)
+    .digest();
+}
+
+/**
+ * Decode a snapshot account's header and taken leaves
+ */
+export function decodeCounterSnapshot(
+  data: Buffer,
+): {header: CounterSnapshot; leaves: Leaf[]} {
+  if (
+    data.length < SNAPSHOT_HEADER_SIZE ||
+    !data.slice(0, 8).equals(COUNTER_SNAPSHOT_DISCRIMINATOR)
+  ) {
+    throw new Error('Not a counter snapshot');
+  }
+  const header = borsh.deserialize(
+    HelloWorldSchema,
+    CounterSnapshot,
+    data.slice(8, SNAPSHOT_HEADER_SIZE),
+  );
+  if (data.length !== counterSnapshotSize(header.depth)) {
+    throw new Error(`Not a counter snapshot of depth ${header.depth}`);
+  }
+  const leaves = [];
+  for (let i = 0; i < header.leaf_count; i++) {
+    const offset = SNAPSHOT_HEADER_SIZE + i * SNAPSHOT_LEAF_SIZE;
+    leaves.push({
+      key: new PublicKey(data.slice(offset, offset + 32)),
+      counter: new BN(
+        data.slice(offset + 32, offset + SNAPSHOT_LEAF_SIZE),
+        'le',
+      ),
+    });
+  }
+  return {header, leaves};
+}
+
+/**
+ * The siblings on the path from leaf `index` to the root of the tree `depth` deep
+ * over `leaves`, from the leaf up. Free leaves hash as all zeros.
+ */
+export function merkleProof(
+  leaves: Leaf[],
+  depth: number,
+  index: number,
+): Buffer[] {
+  let level = leaves.map(({key, counter}) => leafHash(key, counter));
+  let empty = Buffer.alloc(32);
+  const siblings = [];
+  for (let height = 0; height < depth; height++) {
+    const sibling = index ^ 1;
+    siblings.push(sibling < level.length ? level[sibling] : empty);
+    const next = [];
+    for (let i = 0; i < level.length; i += 2) {
+      const right = i + 1 < level.length ? level[i + 1] : empty;
+      next.push(nodeHash(level[i], right));
+    }
+    level = next;
+    empty = nodeHash(empty, empty);
+    index >>= 1;
+  }
+  return siblings;
+}
+
+/**
+ * Whether `key` held `counter` at leaf `index` of the tree with `root`
+ */
+export function verifyProof(
+  root: Buffer,
+  key: PublicKey,
+  counter: BN,
+  index: number,
+  siblings: Buffer[],
+): boolean {
+  if (siblings.length > MAX_SNAPSHOT_DEPTH || index >= 2 ** siblings.length) {
+    return false;
+  }
+  let hash = leafHash(key, counter);
+  for (const sibling of siblings) {
+    hash = index & 1 ? nodeHash(sibling, hash) : nodeHash(hash, sibling);
+    index >>= 1;
+  }
+  return hash.equals(root);
+}
+
+/**
+ * The proof that `account` is in the snapshot with `data`, null if it isn't
+ */
+export function snapshotProof(data: Buffer, account: PublicKey): Proof | null {
+  const {header, leaves} = decodeCounterSnapshot(data);
+  const index = leaves.findIndex(({key}) => key.equals(account));
+  if (index < 0) {
+    return null;
+  }
+  return {
+    root: Buffer.from(header.root).toString('hex'),
+    slot: header.slot.toString(),
+    account: account.toBase58(),
+    index,
+    counter: leaves[index].counter.toString(),
+    siblings: merkleProof(leaves, header.depth, index).map(sibling =>
+      sibling.toString('hex'),
+    ),
+  };
+}
+
+/**
+ * Build an InitCounterSnapshot instruction for the allocated account `snapshot`
+ */
+export async function initCounterSnapshotInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  snapshot: PublicKey,
+  depth: number,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: authority, isSigner: true, isWritable: false},
+      {pubkey: snapshot, isSigner: false, isWritable: true},
+    ],
+    HelloWorldInstruction.InitCounterSnapshot,
+    new InitCounterSnapshotArgs({depth}),
+  );
+}
+
+/**
+ * Build a SnapshotCounters instruction writing each of `greeted`, a greeting account
+ * and its leaf index, into `snapshot`
+ */
+export async function snapshotCountersInstruction(
+  programId: PublicKey,
+  crankAuthority: PublicKey,
+  snapshot: PublicKey,
+  greeted: [PublicKey, number][],
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: crankAuthority, isSigner: true, isWritable: false},
+      {pubkey: snapshot, isSigner: false, isWritable: true},
+      ...greeted.map(([pubkey]) => ({
+        pubkey,
+        isSigner: false,
+        isWritable: false,
+      })),
+    ],
+    HelloWorldInstruction.SnapshotCounters,
+    new SnapshotCountersArgs({indices: greeted.map(([, index]) => index)}),
+  );
+}
+
+/**
+ * Pair every account with its leaf in the snapshot with `data`: the one holding it,
+ * else the next free one
+ */
+export function assignLeaves(
+  data: Buffer,
+  accounts: PublicKey[],
+): [PublicKey, number][] {
+  const {header, leaves} = decodeCounterSnapshot(data);
+  const held = new Map(leaves.map(({key}, index) => [key.toBase58(), index]));
+  let next = header.leaf_count;
+  const assigned: [PublicKey, number][] = [];
+  for (const account of accounts) {
+    const key = account.toBase58();
+    if (!held.has(key)) {
+      held.set(key, next++);
+    }
+    assigned.push([account, held.get(key) as number]);
+  }
+  if (next > 2 ** header.depth) {
+    throw new Error(
+      `The snapshot holds ${2 ** header.depth} accounts, ${next} are needed`,
+    );
+  }
+  return assigned;
+}
+
+async function main() {
+  const [command, arg, ...rest] = process.argv.slice(2);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+
+  const fetchSnapshot = async (snapshot: PublicKey) => {
+    const info = await connection.getAccountInfo(snapshot);
+    if (info === null || !info.owner.equals(programId)) {
+      throw new Error(`No counter snapshot ${snapshot.toBase58()}`);
+    }
+    return info.data;
+  };
+
+  if (
+    command === 'init' &&
+    Number(arg) >= 1 &&
+    Number(arg) <= MAX_SNAPSHOT_DEPTH
+  ) {
+    const payer = await getPayerSigner();
+    const depth = Number(arg);
+    const snapshot = new Account();
+    const space = counterSnapshotSize(depth);
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: payer.publicKey,
+          newAccountPubkey: snapshot.publicKey,
+          lamports: await connection.getMinimumBalanceForRentExemption(space),
+          space,
+          programId,
+        }),
+        await initCounterSnapshotInstruction(
+          programId,
+          payer.publicKey,
+          snapshot.publicKey,
+          depth,
+        ),
+      ),
+      [payer, snapshot],
+      programId,
+    );
+    console.log(
+      `Created counter snapshot ${snapshot.publicKey.toBase58()}, up to`,
+      2 ** depth,
+    );
+  } else if (command === 'update' && arg) {
+    const payer = await getPayerSigner();
+    const snapshot = new PublicKey(arg);
+    let accounts = rest.map(account => new PublicKey(account));
+    if (accounts.length === 0) {
+      const configPubkey = await configAddress(programId);
+      const programAccounts = await connection.getProgramAccounts(programId);
+      accounts = programAccounts
+        .filter(({pubkey, account}) => {
+          const header = account.data.slice(0, 8);
+          if (
+            pubkey.equals(configPubkey) ||
+            header.equals(COUNTER_SNAPSHOT_DISCRIMINATOR)
+          ) {
+            return false;
+          }
+          try {
+            decodeGreeting(account.data);
+            return true;
+          } catch (err) {
+            return false;
+          }
+        })
+        .map(({pubkey}) => pubkey);
+    }
+    const greeted = assignLeaves(await fetchSnapshot(snapshot), accounts);
+    for (let start = 0; start < greeted.length; start += SNAPSHOT_BATCH) {
+      await sendAndConfirm(
+        connection,
+        new Transaction().add(
+          await snapshotCountersInstruction(
+            programId,
+            payer.publicKey,
+            snapshot,
+            greeted.slice(start, start + SNAPSHOT_BATCH),
+          ),
+        ),
+        [payer],
+        programId,
+      );
+    }
+    const {header} = decodeCounterSnapshot(await fetchSnapshot(snapshot));
+    console.log(
+      `Snapshot ${arg} holds ${header.leaf_count} account(s), root`,
+      Buffer.from(header.root).toString('hex'),
+    );
+  } else if (command === 'proof' && arg && rest.length === 1) {
+    const proof = snapshotProof(
+      await fetchSnapshot(new PublicKey(arg)),
+      new PublicKey(rest[0]),
+    );
+    if (proof === null) {
+      throw new Error(`${rest[0]} is not in snapshot ${arg}`);
+    }
+    console.log(JSON.stringify(proof, null, 2));
+  } else {
+    throw new Error(
+      'Usage: counter-snapshot init <DEPTH>\n' +
+        '       counter-snapshot update <SNAPSHOT> [ACCOUNT...]\n' +
+        '       counter-snapshot proof <SNAPSHOT> <ACCOUNT>',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,297 @@
+/**
+ * NOTES:
+ * - Keeper bot for the program's maintenance: once an epoch it sends Tick for every
+ * greeting account, folding the decay into the stored counters so they read right
+ * without anyone greeting them. Clockwork or a cron job can run `crank run --once`
+ * instead of keeping this process up.
+ * - Usage:
+ *   npm run crank -- init <HALF_LIFE_EPOCHS>   create the config account
+ *   npm run crank -- set-crank <PUBKEY>        name the crank authority
+ *   npm run crank -- run [--once]              tick every epoch
+ * - The payer signs everything, so it must be the config authority for init and
+ * set-crank, and the crank authority for run.
+ * - Tick is idempotent, an account already up to date for the epoch is left alone, so
+ * overlapping runs or retries after a timeout are harmless.
+ */
+
+import {
+  Account,
+  AccountMeta,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js';
+import * as borsh from 'borsh';
+import BN from 'bn.js';
+
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {decodeConfig} from './state';
+import {
+  ConfigPatch,
+  HelloWorldInstruction,
+  HelloWorldSchema,
+  InitConfigArgs,
+  SetConfigArgs,
+  SetCrankAuthorityArgs,
+} from './generated';
+import {getPayer, getRpcUrl, readAccountFromFile, sleep} from './utils';
+
+/**
+ * Greeting accounts per Tick, well within the transaction size limit
+ */
+export const TICK_BATCH = 20;
+
+/**
+ * How often `run` checks for a new epoch
+ */
+const POLL_MS = 60000;
+
+/**
+ * Build an instruction of the program from its tag and Borsh-encoded arguments
+ */
+export function instruction(
+  programId: PublicKey,
+  keys: AccountMeta[],
+  tag: HelloWorldInstruction,
+  args?: object,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data: Buffer.concat([
+      Buffer.from([tag]),
+      Buffer.from(args ? borsh.serialize(HelloWorldSchema, args) : []),
+    ]),
+  });
+}
+
+/**
+ * Build an InitConfig instruction making `authority` the config authority
+ */
+export async function initConfigInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  halfLifeEpochs: number,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.InitConfig,
+    new InitConfigArgs({decay_half_life_epochs: new BN(halfLifeEpochs)}),
+  );
+}
+
+/**
+ * Build a SetCrankAuthority instruction naming `crankAuthority`
+ */
+export async function setCrankAuthorityInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  crankAuthority: PublicKey,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: false},
+    ],
+    HelloWorldInstruction.SetCrankAuthority,
+    new SetCrankAuthorityArgs({crank_authority: crankAuthority.toBytes()}),
+  );
+}
+
+/**
+ * Parse `field=value` assignments into a SetConfig patch, e.g. `["paused=1"]`. Only
+ * the fields named are set, so a patch can change exactly one parameter.
+ */
+export function parseConfigPatch(assignments: string[]): Partial<ConfigPatch> {
+  const patch: Record<string, BN | Uint8Array | number> = {};
+  const fields = Object.keys(new ConfigPatch());
+  const u64s = [
+    'decay_half_life_epochs',
+    'fee_usd_micros',
+    'max_price_age_slots',
+    'stake_unit',
+    'balance_unit',
+    'epoch_bonus_slots',
+  ];
+  const keys = ['crank_authority', 'price_feed', 'fee_recipient', 'balance_mint'];
+  for (const assignment of assignments) {
+    const [field, value] = assignment.split('=');
+    if (fields.indexOf(field) < 0 || value === undefined) {
+      throw new Error(
+        `Expected <field>=<value> with a field of ${fields.join(', ')}`,
+      );
+    }
+    if (u64s.indexOf(field) >= 0) {
+      patch[field] = new BN(value);
+    } else if (keys.indexOf(field) >= 0) {
+      patch[field] = new PublicKey(value).toBytes();
+    } else if (field === 'claim_root') {
+      patch[field] = Buffer.from(value, 'hex');
+    } else if (value === 'true' || value === 'false') {
+      patch[field] = value === 'true' ? 1 : 0;
+    } else {
+      patch[field] = Number(value);
+    }
+  }
+  return patch as Partial<ConfigPatch>;
+}
+
+/**
+ * Build a SetConfig instruction changing the config's fields set in `patch`, the
+ * others keep their value (e.g. `{paused: 1}` to pause greetings)
+ */
+export async function setConfigInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  patch: Partial<ConfigPatch>,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: false},
+    ],
+    HelloWorldInstruction.SetConfig,
+    new SetConfigArgs({patch: Object.assign(new ConfigPatch(), patch)}),
+  );
+}
+
+/**
+ * Build a Tick instruction maintaining `greetedPubkeys`
+ */
+export async function tickInstruction(
+  programId: PublicKey,
+  crankAuthority: PublicKey,
+  greetedPubkeys: PublicKey[],
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: crankAuthority, isSigner: true, isWritable: false},
+      ...greetedPubkeys.map(pubkey => ({
+        pubkey,
+        isSigner: false,
+        isWritable: true,
+      })),
+    ],
+    HelloWorldInstruction.Tick,
+  );
+}
+
+/**
+ * Send Tick for every greeting account of the program, in batches
+ */
+export async function tickAll(
+  connection: Connection,
+  crank: Account,
+  programId: PublicKey,
+): Promise<number> {
+  const configPubkey = await configAddress(programId);
+  const accounts = await connection.getProgramAccounts(programId);
+  const greetedPubkeys = accounts
+    .map(({pubkey}) => pubkey)
+    .filter(pubkey => !pubkey.equals(configPubkey));
+  for (let start = 0; start < greetedPubkeys.length; start += TICK_BATCH) {
+    const batch = greetedPubkeys.slice(start, start + TICK_BATCH);
+    const transaction = new Transaction().add(
+      await tickInstruction(programId, crank.publicKey, batch),
+    );
+    await sendAndConfirmTransaction(connection, transaction, [crank]);
+  }
+  return greetedPubkeys.length;
+}
+
+async function run(
+  connection: Connection,
+  crank: Account,
+  programId: PublicKey,
+  once: boolean,
+): Promise<void> {
+  const configInfo = await connection.getAccountInfo(
+    await configAddress(programId),
+  );
+  if (configInfo === null || !configInfo.owner.equals(programId)) {
+    throw new Error('The config account does not exist, run `crank init`');
+  }
+  const crankAuthority = new PublicKey(
+    decodeConfig(configInfo.data).crank_authority,
+  );
+  if (!crankAuthority.equals(crank.publicKey)) {
+    throw new Error(
+      `The crank authority is ${crankAuthority.toBase58()}, not the payer`,
+    );
+  }
+  let lastEpoch = -1;
+  for (;;) {
+    const {epoch} = await connection.getEpochInfo();
+    if (epoch !== lastEpoch) {
+      const count = await tickAll(connection, crank, programId);
+      console.log(`Ticked ${count} greeting account(s) at epoch ${epoch}`);
+      lastEpoch = epoch;
+    }
+    if (once) {
+      return;
+    }
+    await sleep(POLL_MS);
+  }
+}
+
+async function main() {
+  const [command, arg] = process.argv.slice(2);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const payer = await getPayer();
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+
+  if (command === 'init' && Number(arg) >= 0) {
+    const transaction = new Transaction().add(
+      await initConfigInstruction(programId, payer.publicKey, Number(arg)),
+    );
+    await sendAndConfirmTransaction(connection, transaction, [payer]);
+    console.log(
+      `Created the config account, counters halve every ${arg} epoch(s)`,
+    );
+  } else if (command === 'set-crank' && arg) {
+    const transaction = new Transaction().add(
+      await setCrankAuthorityInstruction(
+        programId,
+        payer.publicKey,
+        new PublicKey(arg),
+      ),
+    );
+    await sendAndConfirmTransaction(connection, transaction, [payer]);
+    console.log(`The crank authority is now ${arg}`);
+  } else if (command === 'run' && (arg === undefined || arg === '--once')) {
+    await run(connection, payer, programId, arg === '--once');
+  } else {
+    throw new Error(
+      'Usage: crank init <HALF_LIFE_EPOCHS>\n' +
+        '       crank set-crank <PUBKEY>\n' +
+        '       crank run [--once]',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,261 @@
+/**
+ * NOTES:
+ * - `npm test` runs these with mocha. The send paths run against a stub Connection
+ * (and a local block engine for Jito bundles), so no validator is needed.
+ */
+
+import {
+  Account,
+  Connection,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import assert from 'assert';
+import http from 'http';
+import {AddressInfo} from 'net';
+
+import {HelloWorldTxError, helloWorldError, helloWorldTxError} from './errors';
+import {HelloWorldError} from './generated';
+import {sendAndConfirmBundle} from './jito';
+import {sendGreets} from './mass_greet';
+import {submit} from './offline';
+import {sendAndConfirm} from './signer';
+import {describeError} from './simulation';
+
+const programId = new Account().publicKey;
+const otherProgramId = new Account().publicKey;
+
+/** {"InstructionError": [index, {"Custom": code}]}, as a failed transaction reports it */
+function custom(index: number, code: number) {
+  return {InstructionError: [index, {Custom: code}]};
+}
+
+/** A transaction running `otherProgramId` then helloworld */
+function transaction(): Transaction {
+  return new Transaction().add(
+    new TransactionInstruction({keys: [], programId: otherProgramId}),
+    new TransactionInstruction({keys: [], programId, data: Buffer.from([0])}),
+  );
+}
+
+/** A Connection whose transactions fail with `simulated` and `confirmed` */
+function connection(simulated: unknown, confirmed: unknown): Connection {
+  const stub = {
+    getRecentBlockhash: () =>
+      Promise.resolve({blockhash: new Account().publicKey.toBase58()}),
+    getAccountInfo: () => Promise.resolve(null),
+    simulateTransaction: () =>
+      Promise.resolve({value: {err: simulated, logs: ['Program log: failed']}}),
+    sendRawTransaction: () => Promise.resolve('signature'),
+    confirmTransaction: () => Promise.resolve({value: {err: confirmed}}),
+  };
+  return stub as unknown as Connection;
+}
+
+/** `promise`'s rejection, failing when it resolves */
+async function rejection(promise: Promise<unknown>): Promise<Error> {
+  try {
+    await promise;
+  } catch (err) {
+    return err as Error;
+  }
+  throw new Error('Expected a rejection');
+}
+
+describe('helloWorldError', () => {
+  const programs = [otherProgramId, programId];
+
+  it('names the code of a failed helloworld instruction', () => {
+    assert.strictEqual(
+      helloWorldError(custom(1, 6001), programs, programId),
+      HelloWorldError.MessageTooLong,
+    );
+  });
+
+  it('ignores a failure at another instruction index', () => {
+    for (const index of [0, 2]) {
+      assert.strictEqual(
+        helloWorldError(custom(index, 6001), programs, programId),
+        null,
+      );
+    }
+  });
+
+  it("ignores another program's custom code", () => {
+    assert.strictEqual(
+      helloWorldError(custom(0, 6001), [otherProgramId], programId),
+      null,
+    );
+  });
+
+  it("ignores a code helloworld doesn't have", () => {
+    for (const code of [1, 9999]) {
+      assert.strictEqual(
+        helloWorldError(custom(1, code), programs, programId),
+        null,
+      );
+    }
+  });
+
+  it('ignores errors that are not a custom InstructionError', () => {
+    const notCustom = {InstructionError: [1, 'InvalidAccountData']};
+    assert.strictEqual(helloWorldError(notCustom, programs, programId), null);
+    assert.strictEqual(
+      helloWorldError('AccountNotFound', programs, programId),
+      null,
+    );
+    assert.strictEqual(helloWorldError(null, programs, programId), null);
+    assert.strictEqual(helloWorldError(custom(1, 6001), programs), null);
+  });
+});
+
+describe('helloWorldTxError', () => {
+  it('carries the error, instruction, signature and logs', () => {
+    const err = helloWorldTxError(
+      custom(1, 6001),
+      transaction(),
+      programId,
+      'signature',
+      ['log'],
+    );
+    assert.ok(err instanceof HelloWorldTxError);
+    assert.strictEqual(err.error, HelloWorldError.MessageTooLong);
+    assert.strictEqual(err.instruction, 1);
+    assert.strictEqual(err.signature, 'signature');
+    assert.deepStrictEqual(err.logs, ['log']);
+    assert.ok(err.message.startsWith('MessageTooLong: '));
+    assert.ok(err.message.endsWith('(instruction 1 of signature)'));
+  });
+
+  it("is null for anything but helloworld's errors", () => {
+    assert.strictEqual(
+      helloWorldTxError(custom(0, 6001), transaction(), programId, null),
+      null,
+    );
+    assert.strictEqual(
+      helloWorldTxError(custom(1, 1), transaction(), programId, null),
+      null,
+    );
+    assert.strictEqual(
+      helloWorldTxError('AccountNotFound', transaction(), programId, null),
+      null,
+    );
+    assert.strictEqual(
+      helloWorldTxError(custom(1, 6001), transaction(), undefined, null),
+      null,
+    );
+  });
+});
+
+describe('describeError', () => {
+  it('names a helloworld error and keeps the code of others', () => {
+    const programs = [otherProgramId, programId];
+    const failure = describeError(custom(1, 6001), programs, programId);
+    assert.strictEqual(failure.error, HelloWorldError.MessageTooLong);
+    assert.strictEqual(failure.name, 'MessageTooLong');
+    assert.strictEqual(failure.instruction, 1);
+
+    const other = describeError(custom(0, 6001), programs, programId);
+    assert.strictEqual(other.error, null);
+    assert.strictEqual(other.code, 6001);
+    assert.strictEqual(other.program, otherProgramId.toBase58());
+  });
+});
+
+describe('send paths', () => {
+  const payer = new Account();
+
+  /** sendAndConfirm() of transaction() on connection(simulated, confirmed) */
+  const send = (simulated: unknown, confirmed: unknown) =>
+    sendAndConfirm(
+      connection(simulated, confirmed),
+      transaction(),
+      [payer],
+      programId,
+    );
+
+  it('sendAndConfirm throws a HelloWorldTxError on a failed simulation', async () => {
+    const err = await rejection(send(custom(1, 6001), null));
+    assert.ok(err instanceof HelloWorldTxError);
+    assert.strictEqual(err.error, HelloWorldError.MessageTooLong);
+    assert.strictEqual(err.signature, null);
+    assert.deepStrictEqual(err.logs, ['Program log: failed']);
+  });
+
+  it('sendAndConfirm throws a HelloWorldTxError on a failed transaction', async () => {
+    const err = await rejection(send(null, custom(1, 6003)));
+    assert.ok(err instanceof HelloWorldTxError);
+    assert.strictEqual(err.error, HelloWorldError.Unauthorized);
+    assert.strictEqual(err.signature, 'signature');
+  });
+
+  it("sendAndConfirm keeps a plain error for another program's code", async () => {
+    const simulated = await rejection(send(custom(0, 6001), null));
+    assert.strictEqual(simulated.name, 'SimulationError');
+    const confirmed = await rejection(send(null, custom(0, 6001)));
+    assert.ok(!(confirmed instanceof HelloWorldTxError));
+  });
+
+  it('offline submit throws a HelloWorldTxError', async () => {
+    const signed = (failure: unknown) => {
+      const tx = transaction();
+      tx.recentBlockhash = new Account().publicKey.toBase58();
+      tx.sign(payer);
+      return submit(connection(null, failure), tx, programId);
+    };
+    const err = await rejection(signed(custom(1, 6001)));
+    assert.ok(err instanceof HelloWorldTxError);
+    assert.strictEqual(err.signature, 'signature');
+    const other = await rejection(signed(custom(0, 6001)));
+    assert.ok(!(other instanceof HelloWorldTxError));
+    assert.strictEqual(await signed(null), 'signature');
+  });
+
+  it("mass_greet reports a greet's HelloWorldTxError", async () => {
+    const {results} = await sendGreets(
+      connection(null, custom(0, 6001)),
+      payer,
+      programId,
+      [new Account().publicKey],
+      {count: 1, accounts: 1, concurrency: 1, rate: 1000},
+    );
+    assert.strictEqual(results.length, 1);
+    assert.strictEqual(results[0].ok, false);
+    const {error} = results[0];
+    assert.ok(error && error.startsWith('MessageTooLong: '));
+  });
+
+  it('a Jito bundle throws a HelloWorldTxError', async function () {
+    this.timeout(10000);
+    const server = http.createServer((req, res) => {
+      let body = '';
+      req.on('data', (chunk: Buffer) => (body += chunk.toString()));
+      req.on('end', () => {
+        const {method} = JSON.parse(body) as {method: string};
+        const result =
+          method === 'sendBundle'
+            ? 'bundle'
+            : {value: [{err: custom(1, 6001), confirmation_status: null}]};
+        res.end(JSON.stringify({jsonrpc: '2.0', id: 1, result}));
+      });
+    });
+    await new Promise<void>(resolve => server.listen(0, '127.0.0.1', resolve));
+    const {port} = server.address() as AddressInfo;
+    try {
+      const err = await rejection(
+        sendAndConfirmBundle(
+          connection(null, null),
+          transaction(),
+          [payer],
+          `http://127.0.0.1:${port}`,
+          programId,
+        ),
+      );
+      assert.ok(err instanceof HelloWorldTxError);
+      assert.strictEqual(err.error, HelloWorldError.MessageTooLong);
+      assert.strictEqual(err.signature, 'bundle');
+    } finally {
+      server.close();
+    }
+  });
+});
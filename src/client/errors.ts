@@ -0,0 +1,92 @@
+/**
+ * NOTES:
+ * - A failed instruction reaches the client as {"InstructionError": [index, {"Custom": n}]}.
+ * When the failing instruction is helloworld's, helloWorldTxError() turns that back
+ * into a HelloWorldTxError holding the HelloWorldError variant, so callers can check
+ * `err.error === HelloWorldError.MessageTooLong` instead of matching numbers.
+ * - Every send path maps its failures this way: sendAndConfirm() (both the simulation
+ * and the confirmed transaction), Jito bundles, mass_greet and `offline submit`.
+ * Other failures keep their plain Error.
+ * - HelloWorldError and its messages are generated from the Rust program (generated.ts),
+ * whose tests check that every code is mapped.
+ * - errors.test.ts (`npm test`) checks the mapping and each send path against a
+ * stub Connection.
+ */
+
+import {PublicKey, Transaction} from '@solana/web3.js';
+
+import {HELLO_WORLD_ERRORS, HelloWorldError} from './generated';
+
+export class HelloWorldTxError extends Error {
+  constructor(
+    readonly error: HelloWorldError,
+    readonly instruction: number,
+    readonly signature: string | null,
+    readonly logs: string[] = [],
+  ) {
+    super(
+      `${HELLO_WORLD_ERRORS[error].name}: ${HELLO_WORLD_ERRORS[error].msg}` +
+        ` (instruction ${instruction}${signature ? ` of ${signature}` : ''})`,
+    );
+    this.name = 'HelloWorldTxError';
+  }
+}
+
+/**
+ * The [index, error] pair of an InstructionError, null for other errors
+ */
+export function instructionError(err: unknown): [number, unknown] | null {
+  const pair =
+    err && typeof err === 'object'
+      ? (err as {InstructionError?: [number, unknown]}).InstructionError
+      : undefined;
+  return pair || null;
+}
+
+/**
+ * The HelloWorldError behind `err`, if the failing instruction is `programId`'s
+ * and its custom code is one of ours. `programs` are the program ids of the
+ * transaction's instructions.
+ */
+export function helloWorldError(
+  err: unknown,
+  programs: PublicKey[],
+  programId?: PublicKey,
+): HelloWorldError | null {
+  const pair = instructionError(err);
+  if (!pair || !programId) {
+    return null;
+  }
+  const [index, detail] = pair;
+  const program = programs[index];
+  const code =
+    detail && typeof detail === 'object'
+      ? (detail as {Custom?: number}).Custom
+      : undefined;
+  if (!program || !program.equals(programId) || code === undefined) {
+    return null;
+  }
+  return HELLO_WORLD_ERRORS[code] ? (code as HelloWorldError) : null;
+}
+
+/**
+ * A HelloWorldTxError for `err` returned by `transaction`, or null when it
+ * isn't one of helloworld's errors
+ */
+export function helloWorldTxError(
+  err: unknown,
+  transaction: Transaction,
+  programId: PublicKey | undefined,
+  signature: string | null,
+  logs: string[] = [],
+): HelloWorldTxError | null {
+  const error = helloWorldError(
+    err,
+    transaction.instructions.map(instruction => instruction.programId),
+    programId,
+  );
+  const pair = instructionError(err);
+  return error !== null && pair
+    ? new HelloWorldTxError(error, pair[0], signature, logs)
+    : null;
+}
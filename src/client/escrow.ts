@@ -0,0 +1,306 @@
+/**
+ * NOTES:
+ * - Client of the escrow program (src/escrow), a second example program swapping
+ * two SPL tokens without either side trusting the other.
+ * - Usage:
+ *   npm run escrow -- offer <SOURCE> <AMOUNT> <RECEIVE_ACCOUNT> <EXPECTED_AMOUNT>
+ *   npm run escrow -- take <INITIALIZER> <SOURCE> <DESTINATION>
+ *   npm run escrow -- cancel <DESTINATION>
+ * - Accounts are token accounts, amounts in base units. offer and cancel are
+ * signed by the payer as the initializer; offer creates the vault, a token account
+ * of the offered mint owned by the escrow, in the same transaction. take pays the
+ * expected tokens from SOURCE and receives the offered ones into DESTINATION.
+ */
+
+import {
+  Account,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import path from 'path';
+import * as borsh from 'borsh';
+
+import {ESCROW_SEED, Escrow, HelloWorldSchema} from './generated';
+import {sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Path to the keypair of the deployed escrow program
+ */
+const ESCROW_KEYPAIR_PATH = path.resolve(
+  __dirname,
+  '../../dist/program/helloworld_escrow-keypair.json',
+);
+
+/**
+ * The SPL Token program, and the size of its token accounts
+ */
+const TOKEN_PROGRAM_ID = new PublicKey(
+  'TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA',
+);
+const TOKEN_ACCOUNT_SIZE = 165;
+
+/**
+ * EscrowInstruction tags
+ */
+enum EscrowInstruction {
+  InitEscrow = 0,
+  Exchange = 1,
+  Cancel = 2,
+}
+
+/**
+ * Address of `initializer`'s escrow
+ */
+export async function escrowAddress(
+  escrowProgramId: PublicKey,
+  initializer: PublicKey,
+): Promise<PublicKey> {
+  const [escrow] = await PublicKey.findProgramAddress(
+    [Buffer.from(ESCROW_SEED), initializer.toBuffer()],
+    escrowProgramId,
+  );
+  return escrow;
+}
+
+/**
+ * Instruction data of `tag` followed by the amounts, as u64s
+ */
+function escrowData(tag: EscrowInstruction, ...amounts: number[]): Buffer {
+  return Buffer.concat([
+    Buffer.from([tag]),
+    ...amounts.map(amount => new BN(amount).toArrayLike(Buffer, 'le', 8)),
+  ]);
+}
+
+/**
+ * Build the instructions creating `vault`, a token account of `mint` owned by
+ * the escrow, and offering `offeredAmount` tokens from `source` for
+ * `expectedAmount` paid into `receiveAccount`
+ */
+export async function initEscrowInstructions(
+  connection: Connection,
+  escrowProgramId: PublicKey,
+  initializer: PublicKey,
+  source: PublicKey,
+  mint: PublicKey,
+  vault: PublicKey,
+  receiveAccount: PublicKey,
+  offeredAmount: number,
+  expectedAmount: number,
+): Promise<TransactionInstruction[]> {
+  const escrow = await escrowAddress(escrowProgramId, initializer);
+  return [
+    SystemProgram.createAccount({
+      fromPubkey: initializer,
+      newAccountPubkey: vault,
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        TOKEN_ACCOUNT_SIZE,
+      ),
+      space: TOKEN_ACCOUNT_SIZE,
+      programId: TOKEN_PROGRAM_ID,
+    }),
+    // The token program's InitializeAccount
+    new TransactionInstruction({
+      programId: TOKEN_PROGRAM_ID,
+      keys: [
+        {pubkey: vault, isSigner: false, isWritable: true},
+        {pubkey: mint, isSigner: false, isWritable: false},
+        {pubkey: escrow, isSigner: false, isWritable: false},
+        {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+      ],
+      data: Buffer.from([1]),
+    }),
+    new TransactionInstruction({
+      programId: escrowProgramId,
+      keys: [
+        {pubkey: escrow, isSigner: false, isWritable: true},
+        {pubkey: initializer, isSigner: true, isWritable: true},
+        {pubkey: source, isSigner: false, isWritable: true},
+        {pubkey: vault, isSigner: false, isWritable: true},
+        {pubkey: receiveAccount, isSigner: false, isWritable: false},
+        {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+        {
+          pubkey: SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+      ],
+      data: escrowData(
+        EscrowInstruction.InitEscrow,
+        offeredAmount,
+        expectedAmount,
+      ),
+    }),
+  ];
+}
+
+/**
+ * Build an Exchange instruction taking `initializer`'s offer, at the terms of
+ * `escrow`
+ */
+export async function exchangeInstruction(
+  escrowProgramId: PublicKey,
+  initializer: PublicKey,
+  escrow: Escrow,
+  taker: PublicKey,
+  takerSource: PublicKey,
+  takerDestination: PublicKey,
+): Promise<TransactionInstruction> {
+  return new TransactionInstruction({
+    programId: escrowProgramId,
+    keys: [
+      {
+        pubkey: await escrowAddress(escrowProgramId, initializer),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: taker, isSigner: true, isWritable: false},
+      {pubkey: takerSource, isSigner: false, isWritable: true},
+      {pubkey: takerDestination, isSigner: false, isWritable: true},
+      {
+        pubkey: new PublicKey(escrow.receive_account),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: new PublicKey(escrow.vault), isSigner: false, isWritable: true},
+      {pubkey: initializer, isSigner: false, isWritable: true},
+      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+    ],
+    data: escrowData(
+      EscrowInstruction.Exchange,
+      escrow.offered_amount.toNumber(),
+      escrow.expected_amount.toNumber(),
+    ),
+  });
+}
+
+/**
+ * Build a Cancel instruction withdrawing `initializer`'s offer, returning the
+ * tokens to `destination`
+ */
+export async function cancelInstruction(
+  escrowProgramId: PublicKey,
+  initializer: PublicKey,
+  escrow: Escrow,
+  destination: PublicKey,
+): Promise<TransactionInstruction> {
+  return new TransactionInstruction({
+    programId: escrowProgramId,
+    keys: [
+      {
+        pubkey: await escrowAddress(escrowProgramId, initializer),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: initializer, isSigner: true, isWritable: true},
+      {pubkey: new PublicKey(escrow.vault), isSigner: false, isWritable: true},
+      {pubkey: destination, isSigner: false, isWritable: true},
+      {pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false},
+    ],
+    data: escrowData(EscrowInstruction.Cancel),
+  });
+}
+
+/**
+ * Fetch `initializer`'s open escrow, or null if there's none
+ */
+export async function getEscrow(
+  connection: Connection,
+  escrowProgramId: PublicKey,
+  initializer: PublicKey,
+): Promise<Escrow | null> {
+  const info = await connection.getAccountInfo(
+    await escrowAddress(escrowProgramId, initializer),
+  );
+  if (info === null || !info.owner.equals(escrowProgramId)) {
+    return null;
+  }
+  return borsh.deserialize(HelloWorldSchema, Escrow, info.data);
+}
+
+async function main() {
+  const [command, ...args] = process.argv.slice(2);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const escrowProgramId = (await readAccountFromFile(ESCROW_KEYPAIR_PATH))
+    .publicKey;
+  const payer = await getPayerSigner();
+
+  if (command === 'offer' && args.length === 4) {
+    const source = new PublicKey(args[0]);
+    const info = await connection.getAccountInfo(source);
+    if (info === null || !info.owner.equals(TOKEN_PROGRAM_ID)) {
+      throw new Error(`${args[0]} is not a token account`);
+    }
+    const vault = new Account();
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        ...(await initEscrowInstructions(
+          connection,
+          escrowProgramId,
+          payer.publicKey,
+          source,
+          new PublicKey(info.data.slice(0, 32)),
+          vault.publicKey,
+          new PublicKey(args[2]),
+          Number(args[1]),
+          Number(args[3]),
+        )),
+      ),
+      [payer, vault],
+    );
+    console.log(`Offered ${args[1]} for ${args[3]}, vault ${vault.publicKey}`);
+    return;
+  }
+
+  if (
+    (command === 'take' && args.length === 3) ||
+    (command === 'cancel' && args.length === 1)
+  ) {
+    const initializer =
+      command === 'take' ? new PublicKey(args[0]) : payer.publicKey;
+    const escrow = await getEscrow(connection, escrowProgramId, initializer);
+    if (escrow === null) {
+      throw new Error(`${initializer.toBase58()} has no open escrow`);
+    }
+    const ix =
+      command === 'take'
+        ? await exchangeInstruction(
+            escrowProgramId,
+            initializer,
+            escrow,
+            payer.publicKey,
+            new PublicKey(args[1]),
+            new PublicKey(args[2]),
+          )
+        : await cancelInstruction(
+            escrowProgramId,
+            initializer,
+            escrow,
+            new PublicKey(args[0]),
+          );
+    await sendAndConfirm(connection, new Transaction().add(ix), [payer]);
+    console.log(command === 'take' ? 'Took the offer' : 'Withdrew the offer');
+    return;
+  }
+
+  throw new Error(
+    'Usage: escrow offer <SOURCE> <AMOUNT> <RECEIVE_ACCOUNT> <EXPECTED_AMOUNT> | take <INITIALIZER> <SOURCE> <DESTINATION> | cancel <DESTINATION>',
+  );
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,1632 @@
+// This file is generated from the Rust program by
+// `cargo run -p helloworld-schema --bin ts -- src/client/generated.ts`. Do not edit.
+import BN from 'bn.js';
+
+export class GreetingAccount {
+  txt = '';
+  counter: BN = new BN(0);
+  calendar_year = 0;
+  greeted_days: Uint8Array = new Uint8Array(46);
+  last_update_epoch: BN = new BN(0);
+  direct_greets: BN = new BN(0);
+  cpi_greets: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          txt: string;
+          counter: BN;
+          calendar_year: number;
+          greeted_days: Uint8Array;
+          last_update_epoch: BN;
+          direct_greets: BN;
+          cpi_greets: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.txt = fields.txt;
+      this.counter = fields.counter;
+      this.calendar_year = fields.calendar_year;
+      this.greeted_days = fields.greeted_days;
+      this.last_update_epoch = fields.last_update_epoch;
+      this.direct_greets = fields.direct_greets;
+      this.cpi_greets = fields.cpi_greets;
+    }
+  }
+}
+
+export class GreetingAccountV4 {
+  txt = '';
+  counter: BN = new BN(0);
+  calendar_year = 0;
+  greeted_days: Uint8Array = new Uint8Array(46);
+  last_update_epoch: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          txt: string;
+          counter: BN;
+          calendar_year: number;
+          greeted_days: Uint8Array;
+          last_update_epoch: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.txt = fields.txt;
+      this.counter = fields.counter;
+      this.calendar_year = fields.calendar_year;
+      this.greeted_days = fields.greeted_days;
+      this.last_update_epoch = fields.last_update_epoch;
+    }
+  }
+}
+
+export class GreetingAccountV3 {
+  txt = '';
+  counter: BN = new BN(0);
+  calendar_year = 0;
+  greeted_days: Uint8Array = new Uint8Array(46);
+  constructor(
+    fields:
+      | {
+          txt: string;
+          counter: BN;
+          calendar_year: number;
+          greeted_days: Uint8Array;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.txt = fields.txt;
+      this.counter = fields.counter;
+      this.calendar_year = fields.calendar_year;
+      this.greeted_days = fields.greeted_days;
+    }
+  }
+}
+
+export class GreetingAccountV2 {
+  txt = '';
+  counter: BN = new BN(0);
+  constructor(fields: {txt: string; counter: BN} | undefined = undefined) {
+    if (fields) {
+      this.txt = fields.txt;
+      this.counter = fields.counter;
+    }
+  }
+}
+
+export class GreetingAccountV1 {
+  txt = '';
+  constructor(fields: {txt: string} | undefined = undefined) {
+    if (fields) {
+      this.txt = fields.txt;
+    }
+  }
+}
+
+export class Config {
+  authority: Uint8Array = new Uint8Array(32);
+  decay_half_life_epochs: BN = new BN(0);
+  crank_authority: Uint8Array = new Uint8Array(32);
+  fee_usd_micros: BN = new BN(0);
+  max_price_age_slots: BN = new BN(0);
+  max_confidence_bps = 0;
+  price_feed: Uint8Array = new Uint8Array(32);
+  fee_recipient: Uint8Array = new Uint8Array(32);
+  stake_mint: Uint8Array = new Uint8Array(32);
+  stake_unit: BN = new BN(0);
+  greeting_log: Uint8Array = new Uint8Array(32);
+  paused = 0;
+  max_message_len = 0;
+  cooldown_days = 0;
+  max_greet_many_accounts = 0;
+  claim_root: Uint8Array = new Uint8Array(32);
+  greeting_receipts = 0;
+  balance_mint: Uint8Array = new Uint8Array(32);
+  balance_unit: BN = new BN(0);
+  epoch_bonus_slots: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          authority: Uint8Array;
+          decay_half_life_epochs: BN;
+          crank_authority: Uint8Array;
+          fee_usd_micros: BN;
+          max_price_age_slots: BN;
+          max_confidence_bps: number;
+          price_feed: Uint8Array;
+          fee_recipient: Uint8Array;
+          stake_mint: Uint8Array;
+          stake_unit: BN;
+          greeting_log: Uint8Array;
+          paused: number;
+          max_message_len: number;
+          cooldown_days: number;
+          max_greet_many_accounts: number;
+          claim_root: Uint8Array;
+          greeting_receipts: number;
+          balance_mint: Uint8Array;
+          balance_unit: BN;
+          epoch_bonus_slots: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.authority = fields.authority;
+      this.decay_half_life_epochs = fields.decay_half_life_epochs;
+      this.crank_authority = fields.crank_authority;
+      this.fee_usd_micros = fields.fee_usd_micros;
+      this.max_price_age_slots = fields.max_price_age_slots;
+      this.max_confidence_bps = fields.max_confidence_bps;
+      this.price_feed = fields.price_feed;
+      this.fee_recipient = fields.fee_recipient;
+      this.stake_mint = fields.stake_mint;
+      this.stake_unit = fields.stake_unit;
+      this.greeting_log = fields.greeting_log;
+      this.paused = fields.paused;
+      this.max_message_len = fields.max_message_len;
+      this.cooldown_days = fields.cooldown_days;
+      this.max_greet_many_accounts = fields.max_greet_many_accounts;
+      this.claim_root = fields.claim_root;
+      this.greeting_receipts = fields.greeting_receipts;
+      this.balance_mint = fields.balance_mint;
+      this.balance_unit = fields.balance_unit;
+      this.epoch_bonus_slots = fields.epoch_bonus_slots;
+    }
+  }
+}
+
+export class LuckTicket {
+  greeter: Uint8Array = new Uint8Array(32);
+  randomness: Uint8Array = new Uint8Array(32);
+  seed_slot: BN = new BN(0);
+  counter: BN = new BN(0);
+  last_update_epoch: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          greeter: Uint8Array;
+          randomness: Uint8Array;
+          seed_slot: BN;
+          counter: BN;
+          last_update_epoch: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.greeter = fields.greeter;
+      this.randomness = fields.randomness;
+      this.seed_slot = fields.seed_slot;
+      this.counter = fields.counter;
+      this.last_update_epoch = fields.last_update_epoch;
+    }
+  }
+}
+
+export class StakePosition {
+  owner: Uint8Array = new Uint8Array(32);
+  mint: Uint8Array = new Uint8Array(32);
+  amount: BN = new BN(0);
+  last_deposit_slot: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          owner: Uint8Array;
+          mint: Uint8Array;
+          amount: BN;
+          last_deposit_slot: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.owner = fields.owner;
+      this.mint = fields.mint;
+      this.amount = fields.amount;
+      this.last_deposit_slot = fields.last_deposit_slot;
+    }
+  }
+}
+
+export class Challenge {
+  challenger: Uint8Array = new Uint8Array(32);
+  challengee: Uint8Array = new Uint8Array(32);
+  greeting_account: Uint8Array = new Uint8Array(32);
+  target_counter: BN = new BN(0);
+  deadline_slot: BN = new BN(0);
+  amount: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          challenger: Uint8Array;
+          challengee: Uint8Array;
+          greeting_account: Uint8Array;
+          target_counter: BN;
+          deadline_slot: BN;
+          amount: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.challenger = fields.challenger;
+      this.challengee = fields.challengee;
+      this.greeting_account = fields.greeting_account;
+      this.target_counter = fields.target_counter;
+      this.deadline_slot = fields.deadline_slot;
+      this.amount = fields.amount;
+    }
+  }
+}
+
+export class ForeignGreeter {
+  chain = 0;
+  sender: Uint8Array = new Uint8Array(32);
+  counter: BN = new BN(0);
+  last_sequence: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          chain: number;
+          sender: Uint8Array;
+          counter: BN;
+          last_sequence: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.chain = fields.chain;
+      this.sender = fields.sender;
+      this.counter = fields.counter;
+      this.last_sequence = fields.last_sequence;
+    }
+  }
+}
+
+export class GreetingLeaf {
+  greeting_account: Uint8Array = new Uint8Array(32);
+  counter: BN = new BN(0);
+  slot: BN = new BN(0);
+  txt = '';
+  constructor(
+    fields:
+      | {
+          greeting_account: Uint8Array;
+          counter: BN;
+          slot: BN;
+          txt: string;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.greeting_account = fields.greeting_account;
+      this.counter = fields.counter;
+      this.slot = fields.slot;
+      this.txt = fields.txt;
+    }
+  }
+}
+
+export class TransferGreetings {
+  mint: Uint8Array = new Uint8Array(32);
+  counter: BN = new BN(0);
+  volume: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          mint: Uint8Array;
+          counter: BN;
+          volume: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.mint = fields.mint;
+      this.counter = fields.counter;
+      this.volume = fields.volume;
+    }
+  }
+}
+
+export class SessionToken {
+  authority: Uint8Array = new Uint8Array(32);
+  session_key: Uint8Array = new Uint8Array(32);
+  valid_until: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          authority: Uint8Array;
+          session_key: Uint8Array;
+          valid_until: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.authority = fields.authority;
+      this.session_key = fields.session_key;
+      this.valid_until = fields.valid_until;
+    }
+  }
+}
+
+export class Escrow {
+  initializer: Uint8Array = new Uint8Array(32);
+  vault: Uint8Array = new Uint8Array(32);
+  receive_account: Uint8Array = new Uint8Array(32);
+  offered_amount: BN = new BN(0);
+  expected_amount: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          initializer: Uint8Array;
+          vault: Uint8Array;
+          receive_account: Uint8Array;
+          offered_amount: BN;
+          expected_amount: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.initializer = fields.initializer;
+      this.vault = fields.vault;
+      this.receive_account = fields.receive_account;
+      this.offered_amount = fields.offered_amount;
+      this.expected_amount = fields.expected_amount;
+    }
+  }
+}
+
+export class ConfigPatch {
+  decay_half_life_epochs: BN | null = null;
+  crank_authority: Uint8Array | null = null;
+  fee_usd_micros: BN | null = null;
+  max_price_age_slots: BN | null = null;
+  max_confidence_bps: number | null = null;
+  price_feed: Uint8Array | null = null;
+  fee_recipient: Uint8Array | null = null;
+  stake_unit: BN | null = null;
+  paused: number | null = null;
+  max_message_len: number | null = null;
+  cooldown_days: number | null = null;
+  max_greet_many_accounts: number | null = null;
+  claim_root: Uint8Array | null = null;
+  greeting_receipts: number | null = null;
+  balance_mint: Uint8Array | null = null;
+  balance_unit: BN | null = null;
+  epoch_bonus_slots: BN | null = null;
+  constructor(
+    fields:
+      | {
+          decay_half_life_epochs: BN | null;
+          crank_authority: Uint8Array | null;
+          fee_usd_micros: BN | null;
+          max_price_age_slots: BN | null;
+          max_confidence_bps: number | null;
+          price_feed: Uint8Array | null;
+          fee_recipient: Uint8Array | null;
+          stake_unit: BN | null;
+          paused: number | null;
+          max_message_len: number | null;
+          cooldown_days: number | null;
+          max_greet_many_accounts: number | null;
+          claim_root: Uint8Array | null;
+          greeting_receipts: number | null;
+          balance_mint: Uint8Array | null;
+          balance_unit: BN | null;
+          epoch_bonus_slots: BN | null;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.decay_half_life_epochs = fields.decay_half_life_epochs;
+      this.crank_authority = fields.crank_authority;
+      this.fee_usd_micros = fields.fee_usd_micros;
+      this.max_price_age_slots = fields.max_price_age_slots;
+      this.max_confidence_bps = fields.max_confidence_bps;
+      this.price_feed = fields.price_feed;
+      this.fee_recipient = fields.fee_recipient;
+      this.stake_unit = fields.stake_unit;
+      this.paused = fields.paused;
+      this.max_message_len = fields.max_message_len;
+      this.cooldown_days = fields.cooldown_days;
+      this.max_greet_many_accounts = fields.max_greet_many_accounts;
+      this.claim_root = fields.claim_root;
+      this.greeting_receipts = fields.greeting_receipts;
+      this.balance_mint = fields.balance_mint;
+      this.balance_unit = fields.balance_unit;
+      this.epoch_bonus_slots = fields.epoch_bonus_slots;
+    }
+  }
+}
+
+export class CounterSnapshot {
+  depth = 0;
+  leaf_count = 0;
+  slot: BN = new BN(0);
+  root: Uint8Array = new Uint8Array(32);
+  constructor(
+    fields:
+      | {
+          depth: number;
+          leaf_count: number;
+          slot: BN;
+          root: Uint8Array;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.depth = fields.depth;
+      this.leaf_count = fields.leaf_count;
+      this.slot = fields.slot;
+      this.root = fields.root;
+    }
+  }
+}
+
+export class GreetingReceipt {
+  signer: Uint8Array = new Uint8Array(32);
+  slot: BN = new BN(0);
+  unix_timestamp: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          signer: Uint8Array;
+          slot: BN;
+          unix_timestamp: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.signer = fields.signer;
+      this.slot = fields.slot;
+      this.unix_timestamp = fields.unix_timestamp;
+    }
+  }
+}
+
+export class GreetingSnapshot {
+  greeting_account: Uint8Array = new Uint8Array(32);
+  recorder: Uint8Array = new Uint8Array(32);
+  counter: BN = new BN(0);
+  slot: BN = new BN(0);
+  unix_timestamp: BN = new BN(0);
+  label = '';
+  constructor(
+    fields:
+      | {
+          greeting_account: Uint8Array;
+          recorder: Uint8Array;
+          counter: BN;
+          slot: BN;
+          unix_timestamp: BN;
+          label: string;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.greeting_account = fields.greeting_account;
+      this.recorder = fields.recorder;
+      this.counter = fields.counter;
+      this.slot = fields.slot;
+      this.unix_timestamp = fields.unix_timestamp;
+      this.label = fields.label;
+    }
+  }
+}
+
+export class TagCount {
+  tag: Uint8Array = new Uint8Array(8);
+  count: BN = new BN(0);
+  constructor(fields: {tag: Uint8Array; count: BN} | undefined = undefined) {
+    if (fields) {
+      this.tag = fields.tag;
+      this.count = fields.count;
+    }
+  }
+}
+
+export class TagCounters {
+  greeting_account: Uint8Array = new Uint8Array(32);
+  entries: TagCount[] = [];
+  constructor(
+    fields:
+      | {
+          greeting_account: Uint8Array;
+          entries: TagCount[];
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.greeting_account = fields.greeting_account;
+      this.entries = fields.entries;
+    }
+  }
+}
+
+export class GreetArgs {
+  txt = '';
+  constructor(fields: {txt: string} | undefined = undefined) {
+    if (fields) {
+      this.txt = fields.txt;
+    }
+  }
+}
+
+export class InitConfigArgs {
+  decay_half_life_epochs: BN = new BN(0);
+  constructor(fields: {decay_half_life_epochs: BN} | undefined = undefined) {
+    if (fields) {
+      this.decay_half_life_epochs = fields.decay_half_life_epochs;
+    }
+  }
+}
+
+export class SetDecayArgs {
+  half_life_epochs: BN = new BN(0);
+  constructor(fields: {half_life_epochs: BN} | undefined = undefined) {
+    if (fields) {
+      this.half_life_epochs = fields.half_life_epochs;
+    }
+  }
+}
+
+export class SetCrankAuthorityArgs {
+  crank_authority: Uint8Array = new Uint8Array(32);
+  constructor(fields: {crank_authority: Uint8Array} | undefined = undefined) {
+    if (fields) {
+      this.crank_authority = fields.crank_authority;
+    }
+  }
+}
+
+export class SetFeeArgs {
+  fee_usd_micros: BN = new BN(0);
+  max_price_age_slots: BN = new BN(0);
+  max_confidence_bps = 0;
+  price_feed: Uint8Array = new Uint8Array(32);
+  fee_recipient: Uint8Array = new Uint8Array(32);
+  constructor(
+    fields:
+      | {
+          fee_usd_micros: BN;
+          max_price_age_slots: BN;
+          max_confidence_bps: number;
+          price_feed: Uint8Array;
+          fee_recipient: Uint8Array;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.fee_usd_micros = fields.fee_usd_micros;
+      this.max_price_age_slots = fields.max_price_age_slots;
+      this.max_confidence_bps = fields.max_confidence_bps;
+      this.price_feed = fields.price_feed;
+      this.fee_recipient = fields.fee_recipient;
+    }
+  }
+}
+
+export class SetAuthorityArgs {
+  new_authority: Uint8Array = new Uint8Array(32);
+  constructor(fields: {new_authority: Uint8Array} | undefined = undefined) {
+    if (fields) {
+      this.new_authority = fields.new_authority;
+    }
+  }
+}
+
+export class SetStakeArgs {
+  stake_mint: Uint8Array = new Uint8Array(32);
+  stake_unit: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          stake_mint: Uint8Array;
+          stake_unit: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.stake_mint = fields.stake_mint;
+      this.stake_unit = fields.stake_unit;
+    }
+  }
+}
+
+export class DepositArgs {
+  amount: BN = new BN(0);
+  constructor(fields: {amount: BN} | undefined = undefined) {
+    if (fields) {
+      this.amount = fields.amount;
+    }
+  }
+}
+
+export class WithdrawStakeArgs {
+  amount: BN = new BN(0);
+  constructor(fields: {amount: BN} | undefined = undefined) {
+    if (fields) {
+      this.amount = fields.amount;
+    }
+  }
+}
+
+export class CreateChallengeArgs {
+  greet_count: BN = new BN(0);
+  deadline_slot: BN = new BN(0);
+  amount: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          greet_count: BN;
+          deadline_slot: BN;
+          amount: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.greet_count = fields.greet_count;
+      this.deadline_slot = fields.deadline_slot;
+      this.amount = fields.amount;
+    }
+  }
+}
+
+export class InitGreetingLogArgs {
+  max_depth = 0;
+  max_buffer_size = 0;
+  constructor(
+    fields:
+      | {
+          max_depth: number;
+          max_buffer_size: number;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.max_depth = fields.max_depth;
+      this.max_buffer_size = fields.max_buffer_size;
+    }
+  }
+}
+
+export class CreateSessionArgs {
+  duration_seconds: BN = new BN(0);
+  top_up_lamports: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          duration_seconds: BN;
+          top_up_lamports: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.duration_seconds = fields.duration_seconds;
+      this.top_up_lamports = fields.top_up_lamports;
+    }
+  }
+}
+
+export class GreetManyArgs {
+  txt = '';
+  constructor(fields: {txt: string} | undefined = undefined) {
+    if (fields) {
+      this.txt = fields.txt;
+    }
+  }
+}
+
+export class GreetForArgs {
+  txt = '';
+  constructor(fields: {txt: string} | undefined = undefined) {
+    if (fields) {
+      this.txt = fields.txt;
+    }
+  }
+}
+
+export class SetConfigArgs {
+  patch: ConfigPatch = new ConfigPatch();
+  constructor(fields: {patch: ConfigPatch} | undefined = undefined) {
+    if (fields) {
+      this.patch = fields.patch;
+    }
+  }
+}
+
+export class InitCounterSnapshotArgs {
+  depth = 0;
+  constructor(fields: {depth: number} | undefined = undefined) {
+    if (fields) {
+      this.depth = fields.depth;
+    }
+  }
+}
+
+export class SnapshotCountersArgs {
+  indices: number[] = [];
+  constructor(fields: {indices: number[]} | undefined = undefined) {
+    if (fields) {
+      this.indices = fields.indices;
+    }
+  }
+}
+
+export class ClaimArgs {
+  index = 0;
+  amount: BN = new BN(0);
+  proof: Uint8Array[] = [];
+  constructor(
+    fields:
+      | {
+          index: number;
+          amount: BN;
+          proof: Uint8Array[];
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.index = fields.index;
+      this.amount = fields.amount;
+      this.proof = fields.proof;
+    }
+  }
+}
+
+export class SnapshotArgs {
+  label = '';
+  constructor(fields: {label: string} | undefined = undefined) {
+    if (fields) {
+      this.label = fields.label;
+    }
+  }
+}
+
+export class CountTagArgs {
+  tag: Uint8Array = new Uint8Array(8);
+  constructor(fields: {tag: Uint8Array} | undefined = undefined) {
+    if (fields) {
+      this.tag = fields.tag;
+    }
+  }
+}
+
+export class CheckRecencyArgs {
+  slot: BN = new BN(0);
+  slot_hash: Uint8Array = new Uint8Array(32);
+  max_age_slots: BN = new BN(0);
+  constructor(
+    fields:
+      | {
+          slot: BN;
+          slot_hash: Uint8Array;
+          max_age_slots: BN;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.slot = fields.slot;
+      this.slot_hash = fields.slot_hash;
+      this.max_age_slots = fields.max_age_slots;
+    }
+  }
+}
+
+export class RegisterForeignGreeterArgs {
+  chain = 0;
+  sender: Uint8Array = new Uint8Array(32);
+  constructor(
+    fields:
+      | {
+          chain: number;
+          sender: Uint8Array;
+        }
+      | undefined = undefined,
+  ) {
+    if (fields) {
+      this.chain = fields.chain;
+      this.sender = fields.sender;
+    }
+  }
+}
+
+/**
+ * Borsh schema for every class above
+ */
+export const HelloWorldSchema = new Map<any, any>([
+  [
+    GreetingAccount,
+    {
+      kind: 'struct',
+      fields: [
+        ['txt', 'string'],
+        ['counter', 'u64'],
+        ['calendar_year', 'u16'],
+        ['greeted_days', [46]],
+        ['last_update_epoch', 'u64'],
+        ['direct_greets', 'u64'],
+        ['cpi_greets', 'u64'],
+      ],
+    },
+  ],
+  [
+    GreetingAccountV4,
+    {
+      kind: 'struct',
+      fields: [
+        ['txt', 'string'],
+        ['counter', 'u64'],
+        ['calendar_year', 'u16'],
+        ['greeted_days', [46]],
+        ['last_update_epoch', 'u64'],
+      ],
+    },
+  ],
+  [
+    GreetingAccountV3,
+    {
+      kind: 'struct',
+      fields: [
+        ['txt', 'string'],
+        ['counter', 'u64'],
+        ['calendar_year', 'u16'],
+        ['greeted_days', [46]],
+      ],
+    },
+  ],
+  [
+    GreetingAccountV2,
+    {kind: 'struct', fields: [['txt', 'string'], ['counter', 'u64']]},
+  ],
+  [GreetingAccountV1, {kind: 'struct', fields: [['txt', 'string']]}],
+  [
+    Config,
+    {
+      kind: 'struct',
+      fields: [
+        ['authority', [32]],
+        ['decay_half_life_epochs', 'u64'],
+        ['crank_authority', [32]],
+        ['fee_usd_micros', 'u64'],
+        ['max_price_age_slots', 'u64'],
+        ['max_confidence_bps', 'u16'],
+        ['price_feed', [32]],
+        ['fee_recipient', [32]],
+        ['stake_mint', [32]],
+        ['stake_unit', 'u64'],
+        ['greeting_log', [32]],
+        ['paused', 'u8'],
+        ['max_message_len', 'u32'],
+        ['cooldown_days', 'u16'],
+        ['max_greet_many_accounts', 'u8'],
+        ['claim_root', [32]],
+        ['greeting_receipts', 'u8'],
+        ['balance_mint', [32]],
+        ['balance_unit', 'u64'],
+        ['epoch_bonus_slots', 'u64'],
+      ],
+    },
+  ],
+  [
+    LuckTicket,
+    {
+      kind: 'struct',
+      fields: [
+        ['greeter', [32]],
+        ['randomness', [32]],
+        ['seed_slot', 'u64'],
+        ['counter', 'u64'],
+        ['last_update_epoch', 'u64'],
+      ],
+    },
+  ],
+  [
+    StakePosition,
+    {
+      kind: 'struct',
+      fields: [
+        ['owner', [32]],
+        ['mint', [32]],
+        ['amount', 'u64'],
+        ['last_deposit_slot', 'u64'],
+      ],
+    },
+  ],
+  [
+    Challenge,
+    {
+      kind: 'struct',
+      fields: [
+        ['challenger', [32]],
+        ['challengee', [32]],
+        ['greeting_account', [32]],
+        ['target_counter', 'u64'],
+        ['deadline_slot', 'u64'],
+        ['amount', 'u64'],
+      ],
+    },
+  ],
+  [
+    ForeignGreeter,
+    {
+      kind: 'struct',
+      fields: [
+        ['chain', 'u16'],
+        ['sender', [32]],
+        ['counter', 'u64'],
+        ['last_sequence', 'u64'],
+      ],
+    },
+  ],
+  [
+    GreetingLeaf,
+    {
+      kind: 'struct',
+      fields: [
+        ['greeting_account', [32]],
+        ['counter', 'u64'],
+        ['slot', 'u64'],
+        ['txt', 'string'],
+      ],
+    },
+  ],
+  [
+    TransferGreetings,
+    {
+      kind: 'struct',
+      fields: [
+        ['mint', [32]],
+        ['counter', 'u64'],
+        ['volume', 'u64'],
+      ],
+    },
+  ],
+  [
+    SessionToken,
+    {
+      kind: 'struct',
+      fields: [
+        ['authority', [32]],
+        ['session_key', [32]],
+        ['valid_until', 'u64'],
+      ],
+    },
+  ],
+  [
+    Escrow,
+    {
+      kind: 'struct',
+      fields: [
+        ['initializer', [32]],
+        ['vault', [32]],
+        ['receive_account', [32]],
+        ['offered_amount', 'u64'],
+        ['expected_amount', 'u64'],
+      ],
+    },
+  ],
+  [
+    ConfigPatch,
+    {
+      kind: 'struct',
+      fields: [
+        ['decay_half_life_epochs', {kind: 'option', type: 'u64'}],
+        ['crank_authority', {kind: 'option', type: [32]}],
+        ['fee_usd_micros', {kind: 'option', type: 'u64'}],
+        ['max_price_age_slots', {kind: 'option', type: 'u64'}],
+        ['max_confidence_bps', {kind: 'option', type: 'u16'}],
+        ['price_feed', {kind: 'option', type: [32]}],
+        ['fee_recipient', {kind: 'option', type: [32]}],
+        ['stake_unit', {kind: 'option', type: 'u64'}],
+        ['paused', {kind: 'option', type: 'u8'}],
+        ['max_message_len', {kind: 'option', type: 'u32'}],
+        ['cooldown_days', {kind: 'option', type: 'u16'}],
+        ['max_greet_many_accounts', {kind: 'option', type: 'u8'}],
+        ['claim_root', {kind: 'option', type: [32]}],
+        ['greeting_receipts', {kind: 'option', type: 'u8'}],
+        ['balance_mint', {kind: 'option', type: [32]}],
+        ['balance_unit', {kind: 'option', type: 'u64'}],
+        ['epoch_bonus_slots', {kind: 'option', type: 'u64'}],
+      ],
+    },
+  ],
+  [
+    CounterSnapshot,
+    {
+      kind: 'struct',
+      fields: [
+        ['depth', 'u8'],
+        ['leaf_count', 'u32'],
+        ['slot', 'u64'],
+        ['root', [32]],
+      ],
+    },
+  ],
+  [
+    GreetingReceipt,
+    {
+      kind: 'struct',
+      fields: [
+        ['signer', [32]],
+        ['slot', 'u64'],
+        ['unix_timestamp', 'u64'],
+      ],
+    },
+  ],
+  [
+    GreetingSnapshot,
+    {
+      kind: 'struct',
+      fields: [
+        ['greeting_account', [32]],
+        ['recorder', [32]],
+        ['counter', 'u64'],
+        ['slot', 'u64'],
+        ['unix_timestamp', 'u64'],
+        ['label', 'string'],
+      ],
+    },
+  ],
+  [TagCount, {kind: 'struct', fields: [['tag', [8]], ['count', 'u64']]}],
+  [
+    TagCounters,
+    {
+      kind: 'struct',
+      fields: [
+        ['greeting_account', [32]],
+        ['entries', [TagCount]],
+      ],
+    },
+  ],
+  [GreetArgs, {kind: 'struct', fields: [['txt', 'string']]}],
+  [
+    InitConfigArgs,
+    {kind: 'struct', fields: [['decay_half_life_epochs', 'u64']]},
+  ],
+  [SetDecayArgs, {kind: 'struct', fields: [['half_life_epochs', 'u64']]}],
+  [
+    SetCrankAuthorityArgs,
+    {kind: 'struct', fields: [['crank_authority', [32]]]},
+  ],
+  [
+    SetFeeArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['fee_usd_micros', 'u64'],
+        ['max_price_age_slots', 'u64'],
+        ['max_confidence_bps', 'u16'],
+        ['price_feed', [32]],
+        ['fee_recipient', [32]],
+      ],
+    },
+  ],
+  [SetAuthorityArgs, {kind: 'struct', fields: [['new_authority', [32]]]}],
+  [
+    SetStakeArgs,
+    {kind: 'struct', fields: [['stake_mint', [32]], ['stake_unit', 'u64']]},
+  ],
+  [DepositArgs, {kind: 'struct', fields: [['amount', 'u64']]}],
+  [WithdrawStakeArgs, {kind: 'struct', fields: [['amount', 'u64']]}],
+  [
+    CreateChallengeArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['greet_count', 'u64'],
+        ['deadline_slot', 'u64'],
+        ['amount', 'u64'],
+      ],
+    },
+  ],
+  [
+    InitGreetingLogArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['max_depth', 'u32'],
+        ['max_buffer_size', 'u32'],
+      ],
+    },
+  ],
+  [
+    CreateSessionArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['duration_seconds', 'u64'],
+        ['top_up_lamports', 'u64'],
+      ],
+    },
+  ],
+  [GreetManyArgs, {kind: 'struct', fields: [['txt', 'string']]}],
+  [GreetForArgs, {kind: 'struct', fields: [['txt', 'string']]}],
+  [SetConfigArgs, {kind: 'struct', fields: [['patch', ConfigPatch]]}],
+  [InitCounterSnapshotArgs, {kind: 'struct', fields: [['depth', 'u8']]}],
+  [SnapshotCountersArgs, {kind: 'struct', fields: [['indices', ['u32']]]}],
+  [
+    ClaimArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['index', 'u32'],
+        ['amount', 'u64'],
+        ['proof', [[32]]],
+      ],
+    },
+  ],
+  [SnapshotArgs, {kind: 'struct', fields: [['label', 'string']]}],
+  [CountTagArgs, {kind: 'struct', fields: [['tag', [8]]]}],
+  [
+    CheckRecencyArgs,
+    {
+      kind: 'struct',
+      fields: [
+        ['slot', 'u64'],
+        ['slot_hash', [32]],
+        ['max_age_slots', 'u64'],
+      ],
+    },
+  ],
+  [
+    RegisterForeignGreeterArgs,
+    {kind: 'struct', fields: [['chain', 'u16'], ['sender', [32]]]},
+  ],
+]);
+
+/**
+ * Schema version of GreetingAccount. From version 2 on, account data opens with
+ * STATE_MAGIC and the version byte; version 1 accounts have no header.
+ */
+export const STATE_VERSION = 6;
+export const STATE_MAGIC = 'HWS';
+
+/**
+ * Anchor discriminator of GreetingAccount, sha256("account:GreetingAccount")[..8].
+ * From DISCRIMINATOR_VERSION on it opens the data in place of STATE_MAGIC and the
+ * version byte.
+ */
+export const GREETING_DISCRIMINATOR = Buffer.from([190, 16, 56, 57, 246, 26, 112, 24]);
+export const DISCRIMINATOR_VERSION = 6;
+
+/**
+ * Discriminator of compactly encoded accounts (varints, bitpacked flags), in place
+ * of STATE_MAGIC. See src/program-rust/src/compact.rs.
+ */
+export const COMPACT_MAGIC = 'HWC';
+export const COMPACT_FLAG_COUNTER = 1;
+export const COMPACT_FLAG_CALENDAR = 2;
+export const COMPACT_FLAG_LAST_UPDATE = 4;
+export const COMPACT_FLAG_ORIGIN = 8;
+
+/**
+ * Bytes of the greeted_days bitmap, one bit per day of the year
+ */
+export const DAY_BITMAP_SIZE = 46;
+
+/**
+ * The most greeting accounts one GreetMany takes
+ */
+export const MAX_GREET_MANY_ACCOUNTS = 24;
+
+/**
+ * Seed of the config account's address, a PDA of the program
+ */
+export const CONFIG_SEED = 'config';
+
+/**
+ * Seed of the tip vault's address, a PDA of the program
+ */
+export const VAULT_SEED = 'vault';
+
+/**
+ * Seed of the claim vault's address, a PDA of the program
+ */
+export const CLAIM_VAULT_SEED = 'claim-vault';
+
+/**
+ * Seed of a claimed bitmap, followed by the claim root and the bitmap's number
+ */
+export const CLAIMED_SEED = 'claimed';
+
+/**
+ * Anchor discriminator of a claimed bitmap, sha256("account:ClaimedBitmap")[..8]
+ */
+export const CLAIMED_BITMAP_DISCRIMINATOR = Buffer.from([42, 242, 64, 97, 154, 187, 151, 183]);
+
+/**
+ * Size of a claimed bitmap's data, the discriminator included
+ */
+export const CLAIMED_BITMAP_SIZE = 1032;
+
+/**
+ * Leaves one claimed bitmap covers
+ */
+export const CLAIMS_PER_BITMAP = 8192;
+
+/**
+ * Seed of a greeting account's ticket, followed by the greeting account's key
+ */
+export const TICKET_SEED = 'ticket';
+
+/**
+ * Seed of a staker's stake position, followed by the staker's key
+ */
+export const STAKE_SEED = 'stake';
+
+/**
+ * Seed of the stake authority, the owner of the stake vault
+ */
+export const STAKE_AUTHORITY_SEED = 'stake-authority';
+
+/**
+ * Seed of a challenge, followed by the challenger's and the challengee's keys
+ */
+export const CHALLENGE_SEED = 'challenge';
+
+/**
+ * Seed of a foreign greeter, followed by its Wormhole chain id (big-endian u16) and
+ * emitter address
+ */
+export const FOREIGN_GREETER_SEED = 'foreign-greeter';
+
+/**
+ * Seed of the log authority, the authority of the greeting log's tree
+ */
+export const LOG_AUTHORITY_SEED = 'greeting-log';
+
+/**
+ * Seed of a mint's extra account metas in the transfer-hook program, followed by
+ * the mint's key
+ */
+export const EXTRA_ACCOUNT_METAS_SEED = 'extra-account-metas';
+
+/**
+ * Seed of a mint's transfer greetings in the transfer-hook program, followed by the
+ * mint's key
+ */
+export const TRANSFER_GREETINGS_SEED = 'transfer-greetings';
+
+/**
+ * Seed of a session token, followed by the authority's and the session key's keys
+ */
+export const SESSION_SEED = 'session';
+
+/**
+ * Seed of a target's greeting account (GreetFor), followed by the target's key
+ */
+export const TARGET_GREETING_SEED = 'greeting';
+
+/**
+ * Longest message GreetFor saves, in bytes
+ */
+export const MAX_TARGET_MESSAGE_LEN = 32;
+
+/**
+ * Size of a target's greeting account
+ */
+export const TARGET_GREETING_SIZE = 124;
+
+/**
+ * Seed of an escrow in the escrow program, followed by the initializer's key
+ */
+export const ESCROW_SEED = 'escrow';
+
+/**
+ * Anchor discriminator of CounterSnapshot, sha256("account:CounterSnapshot")[..8]
+ */
+export const COUNTER_SNAPSHOT_DISCRIMINATOR = Buffer.from([244, 186, 213, 70, 106, 244, 18, 229]);
+
+/**
+ * Bytes of a counter snapshot's discriminator and CounterSnapshot header
+ */
+export const SNAPSHOT_HEADER_SIZE = 53;
+
+/**
+ * Bytes of a counter snapshot's leaf, a greeting account key and counter
+ */
+export const SNAPSHOT_LEAF_SIZE = 40;
+
+/**
+ * Deepest tree a counter snapshot can hold
+ */
+export const MAX_SNAPSHOT_DEPTH = 16;
+
+/**
+ * Seed of a greeting receipt, followed by the greeting account's key and counter
+ */
+export const RECEIPT_SEED = 'receipt';
+
+/**
+ * Anchor discriminator of GreetingReceipt, sha256("account:GreetingReceipt")[..8]
+ */
+export const RECEIPT_DISCRIMINATOR = Buffer.from([131, 162, 12, 227, 214, 137, 135, 68]);
+
+/**
+ * Size of a greeting receipt's data, the discriminator included
+ */
+export const RECEIPT_SIZE = 56;
+
+/**
+ * Seed of a snapshot, followed by the greeting account's and the recorder's keys and the label
+ */
+export const SNAPSHOT_SEED = 'snapshot';
+
+/**
+ * Anchor discriminator of GreetingSnapshot, sha256("account:GreetingSnapshot")[..8]
+ */
+export const SNAPSHOT_DISCRIMINATOR = Buffer.from([234, 107, 107, 176, 155, 117, 216, 16]);
+
+/**
+ * Longest snapshot label in bytes
+ */
+export const MAX_LABEL_LEN = 32;
+
+/**
+ * Seed of the tag counters, followed by the greeting account's key
+ */
+export const TAGS_SEED = 'tags';
+
+/**
+ * Anchor discriminator of TagCounters, sha256("account:TagCounters")[..8]
+ */
+export const TAG_COUNTERS_DISCRIMINATOR = Buffer.from([235, 100, 236, 95, 73, 212, 182, 97]);
+
+/**
+ * Most tags one greeting account counts
+ */
+export const MAX_TAGS = 16;
+
+/**
+ * Size of the tag counters' data, the discriminator included
+ */
+export const TAG_COUNTERS_SIZE = 300;
+
+/**
+ * Slots the SlotHashes sysvar keeps, the oldest a CheckRecency can name
+ */
+export const SLOT_HASHES_MAX_ENTRIES = 512;
+
+/**
+ * Prefix of the log lines carrying a greeting account's state hash
+ */
+export const STATE_HASH_PREFIX = 'helloworld-state: ';
+
+/**
+ * Bytes of the sha256 of the account data kept in a state hash
+ */
+export const STATE_HASH_SIZE = 8;
+
+/**
+ * Instruction tags, the first byte of every instruction's data
+ */
+export enum HelloWorldInstruction {
+  Greet = 0,
+  Version = 1,
+  ExportState = 2,
+  ImportState = 3,
+  InitConfig = 4,
+  SetDecay = 5,
+  SetCrankAuthority = 6,
+  Tick = 7,
+  SetFee = 8,
+  CommitLuck = 9,
+  RevealLuck = 10,
+  SetAuthority = 11,
+  SetStake = 12,
+  Deposit = 13,
+  WithdrawStake = 14,
+  CreateChallenge = 15,
+  ClaimChallenge = 16,
+  RefundChallenge = 17,
+  ReceiveCrossChainGreet = 18,
+  InitGreetingLog = 19,
+  CreateSession = 20,
+  RevokeSession = 21,
+  Increment = 22,
+  GreetMany = 23,
+  GreetFor = 24,
+  SetConfig = 25,
+  InitCounterSnapshot = 26,
+  SnapshotCounters = 27,
+  Claim = 28,
+  Snapshot = 29,
+  CountTag = 30,
+  CheckRecency = 31,
+  RegisterForeignGreeter = 32,
+}
+
+/**
+ * Program errors, numbered by their `Custom(code)` error code
+ */
+export enum HelloWorldError {
+  ExecutableAccount = 6000,
+  MessageTooLong = 6001,
+  MigrationRequired = 6002,
+  Unauthorized = 6003,
+  InvalidConfig = 6004,
+  UnauthorizedCrank = 6005,
+  InvalidPriceFeed = 6006,
+  StalePrice = 6007,
+  UncertainPrice = 6008,
+  InvalidRandomness = 6009,
+  RandomnessNotRevealed = 6010,
+  NoNewGreeting = 6011,
+  InvalidStakeVault = 6012,
+  InsufficientStake = 6013,
+  ChallengeNotMet = 6014,
+  ChallengeExpired = 6015,
+  ChallengeNotExpired = 6016,
+  InvalidVaa = 6017,
+  VaaAlreadyReceived = 6018,
+  InvalidGreetingLog = 6019,
+  InvalidSession = 6020,
+  Reentrancy = 6021,
+  Paused = 6022,
+  OnCooldown = 6023,
+  InvalidConfigValue = 6024,
+  InvalidSnapshot = 6025,
+  InvalidSnapshotIndex = 6026,
+  InvalidClaimProof = 6027,
+  AlreadyClaimed = 6028,
+  ReceiptExists = 6029,
+  LabelTaken = 6030,
+  TagsFull = 6031,
+  InvalidBalanceAccount = 6032,
+  StaleSlotHash = 6033,
+  UnregisteredEmitter = 6034,
+  NotAGreeting = 6035,
+}
+
+/**
+ * Program error codes, as returned in `Custom(code)` errors
+ */
+export const HELLO_WORLD_ERRORS: {
+  [code: number]: {name: string; msg: string};
+} = {
+  6000: {
+    name: 'ExecutableAccount',
+    msg: 'Greeting account must not be executable',
+  },
+  6001: {
+    name: 'MessageTooLong',
+    msg: 'Message does not fit in the greeting account',
+  },
+  6002: {
+    name: 'MigrationRequired',
+    msg: 'Account must be writable to migrate it to the current schema version',
+  },
+  6003: {
+    name: 'Unauthorized',
+    msg: 'Signer is not the config authority',
+  },
+  6004: {
+    name: 'InvalidConfig',
+    msg: 'Account is not the config account of this program',
+  },
+  6005: {
+    name: 'UnauthorizedCrank',
+    msg: 'Signer is not the crank authority',
+  },
+  6006: {
+    name: 'InvalidPriceFeed',
+    msg: 'Account is not the configured Pyth price feed',
+  },
+  6007: {
+    name: 'StalePrice',
+    msg: 'Price feed is not trading or its price is too old',
+  },
+  6008: {
+    name: 'UncertainPrice',
+    msg: 'Price confidence interval is too wide',
+  },
+  6009: {
+    name: 'InvalidRandomness',
+    msg: 'Account is not freshly seeded Switchboard randomness',
+  },
+  6010: {
+    name: 'RandomnessNotRevealed',
+    msg: 'Randomness is not revealed in this slot',
+  },
+  6011: {
+    name: 'NoNewGreeting',
+    msg: 'No greeting to draw for since the last draw',
+  },
+  6012: {
+    name: 'InvalidStakeVault',
+    msg: 'Account is not the stake vault',
+  },
+  6013: {
+    name: 'InsufficientStake',
+    msg: 'Withdrawal exceeds the stake deposited',
+  },
+  6014: {
+    name: 'ChallengeNotMet',
+    msg: 'Greeting account has not reached the challenge\'s counter',
+  },
+  6015: {
+    name: 'ChallengeExpired',
+    msg: 'Challenge deadline has passed',
+  },
+  6016: {
+    name: 'ChallengeNotExpired',
+    msg: 'Challenge deadline has not passed yet',
+  },
+  6017: {
+    name: 'InvalidVaa',
+    msg: 'Account is not a posted Wormhole VAA',
+  },
+  6018: {
+    name: 'VaaAlreadyReceived',
+    msg: 'VAA is not newer than the last one received from its emitter',
+  },
+  6019: {
+    name: 'InvalidGreetingLog',
+    msg: 'Account is not the config\'s greeting log',
+  },
+  6020: {
+    name: 'InvalidSession',
+    msg: 'Session token is expired or was not issued to the signer',
+  },
+  6021: {
+    name: 'Reentrancy',
+    msg: 'Greeting account is already being greeted further up the call stack',
+  },
+  6022: {
+    name: 'Paused',
+    msg: 'Greetings are paused',
+  },
+  6023: {
+    name: 'OnCooldown',
+    msg: 'Greeting account was greeted too recently',
+  },
+  6024: {
+    name: 'InvalidConfigValue',
+    msg: 'Config value is out of range',
+  },
+  6025: {
+    name: 'InvalidSnapshot',
+    msg: 'Account is not a counter snapshot of this program',
+  },
+  6026: {
+    name: 'InvalidSnapshotIndex',
+    msg: 'Snapshot leaf holds another account or is not the next free one',
+  },
+  6027: {
+    name: 'InvalidClaimProof',
+    msg: 'Claim is not under the config\'s claim root',
+  },
+  6028: {
+    name: 'AlreadyClaimed',
+    msg: 'Leaf was claimed already',
+  },
+  6029: {
+    name: 'ReceiptExists',
+    msg: 'Greeting receipt exists already',
+  },
+  6030: {
+    name: 'LabelTaken',
+    msg: 'Snapshot label is taken already',
+  },
+  6031: {
+    name: 'TagsFull',
+    msg: 'Greeting account counts all the tags it has room for',
+  },
+  6032: {
+    name: 'InvalidBalanceAccount',
+    msg: 'Token account isn\'t the greeter\'s of the balance mint',
+  },
+  6033: {
+    name: 'StaleSlotHash',
+    msg: 'Slot hash is unknown or too old',
+  },
+  6034: {
+    name: 'UnregisteredEmitter',
+    msg: 'VAA emitter is not a registered foreign greeter',
+  },
+  6035: {
+    name: 'NotAGreeting',
+    msg: 'VAA payload is not a greeting',
+  },
+};
@@ -0,0 +1,325 @@
+/**
+ * NOTES:
+ * - Writes the client's encodings of the program's golden values to
+ * src/program-rust/golden/js, for the program's unit tests to decode and compare with
+ * its own (test_js_vectors in src/program-rust/src/golden.rs). A field laid out
+ * differently by generated.ts than by the program fails those tests.
+ * - Usage:
+ *   npm run golden && cargo test --manifest-path=src/program-rust/Cargo.toml golden
+ * - The values here are golden.rs's, field for field: a new instruction or account
+ * there needs its entry here, or test_js_vectors fails on the missing file.
+ * - The instructions are built with crank.ts's instruction() and the accounts with the
+ * schema the state.ts decoders read, the client's own paths.
+ */
+
+import {SystemProgram} from '@solana/web3.js';
+import BN from 'bn.js';
+import * as borsh from 'borsh';
+import fs from 'mz/fs';
+import path from 'path';
+
+import {instruction} from './crank';
+import {
+  CheckRecencyArgs,
+  RegisterForeignGreeterArgs,
+  ClaimArgs,
+  Config,
+  ConfigPatch,
+  CountTagArgs,
+  CreateChallengeArgs,
+  CreateSessionArgs,
+  DepositArgs,
+  GREETING_DISCRIMINATOR,
+  GreetArgs,
+  GreetForArgs,
+  GreetManyArgs,
+  GreetingAccount,
+  GreetingReceipt,
+  GreetingSnapshot,
+  HelloWorldInstruction,
+  HelloWorldSchema,
+  InitConfigArgs,
+  InitCounterSnapshotArgs,
+  InitGreetingLogArgs,
+  RECEIPT_DISCRIMINATOR,
+  SNAPSHOT_DISCRIMINATOR,
+  SetAuthorityArgs,
+  SetConfigArgs,
+  SetCrankAuthorityArgs,
+  SetDecayArgs,
+  SetFeeArgs,
+  SetStakeArgs,
+  SnapshotArgs,
+  SnapshotCountersArgs,
+  TAG_COUNTERS_DISCRIMINATOR,
+  TagCount,
+  TagCounters,
+  WithdrawStakeArgs,
+} from './generated';
+import {tag} from './tags';
+
+/**
+ * Where the program's unit tests look for the client's encodings
+ */
+const GOLDEN_DIR = path.resolve(__dirname, '../../src/program-rust/golden/js');
+
+/**
+ * 32 bytes of `byte`, a key or hash
+ */
+function bytes32(byte: number): Uint8Array {
+  return new Uint8Array(32).fill(byte);
+}
+
+/**
+ * Account data: `discriminator` and the Borsh encoding of `value`
+ */
+function accountData(discriminator: Buffer, value: object): Buffer {
+  return Buffer.concat([
+    discriminator,
+    Buffer.from(borsh.serialize(HelloWorldSchema, value)),
+  ]);
+}
+
+/**
+ * One instruction of every kind and its arguments, by vector name
+ */
+function instructions(): [string, HelloWorldInstruction, object?][] {
+  const txt = 'Hello1234567';
+  const I = HelloWorldInstruction;
+  return [
+    ['greet', I.Greet, new GreetArgs({txt})],
+    ['version', I.Version],
+    ['export_state', I.ExportState],
+    ['import_state', I.ImportState],
+    [
+      'init_config',
+      I.InitConfig,
+      new InitConfigArgs({decay_half_life_epochs: new BN(2)}),
+    ],
+    ['set_decay', I.SetDecay, new SetDecayArgs({half_life_epochs: new BN(3)})],
+    [
+      'set_crank_authority',
+      I.SetCrankAuthority,
+      new SetCrankAuthorityArgs({crank_authority: bytes32(3)}),
+    ],
+    ['tick', I.Tick],
+    [
+      'set_fee',
+      I.SetFee,
+      new SetFeeArgs({
+        fee_usd_micros: new BN(4),
+        max_price_age_slots: new BN(5),
+        max_confidence_bps: 6,
+        price_feed: bytes32(7),
+        fee_recipient: bytes32(8),
+      }),
+    ],
+    ['commit_luck', I.CommitLuck],
+    ['reveal_luck', I.RevealLuck],
+    [
+      'set_authority',
+      I.SetAuthority,
+      new SetAuthorityArgs({new_authority: bytes32(1)}),
+    ],
+    [
+      'set_stake',
+      I.SetStake,
+      new SetStakeArgs({stake_mint: bytes32(9), stake_unit: new BN(10)}),
+    ],
+    ['deposit', I.Deposit, new DepositArgs({amount: new BN(11)})],
+    [
+      'withdraw_stake',
+      I.WithdrawStake,
+      new WithdrawStakeArgs({amount: new BN(12)}),
+    ],
+    [
+      'create_challenge',
+      I.CreateChallenge,
+      new CreateChallengeArgs({
+        greet_count: new BN(13),
+        deadline_slot: new BN(14),
+        amount: new BN(15),
+      }),
+    ],
+    ['claim_challenge', I.ClaimChallenge],
+    ['refund_challenge', I.RefundChallenge],
+    ['receive_cross_chain_greet', I.ReceiveCrossChainGreet],
+    [
+      'init_greeting_log',
+      I.InitGreetingLog,
+      new InitGreetingLogArgs({max_depth: 14, max_buffer_size: 64}),
+    ],
+    [
+      'create_session',
+      I.CreateSession,
+      new CreateSessionArgs({
+        duration_seconds: new BN(3600),
+        top_up_lamports: new BN(5000),
+      }),
+    ],
+    ['revoke_session', I.RevokeSession],
+    ['increment', I.Increment],
+    ['greet_many', I.GreetMany, new GreetManyArgs({txt})],
+    ['greet_for', I.GreetFor, new GreetForArgs({txt})],
+    [
+      'set_config',
+      I.SetConfig,
+      new SetConfigArgs({
+        patch: new ConfigPatch({
+          decay_half_life_epochs: new BN(2),
+          crank_authority: bytes32(3),
+          fee_usd_micros: new BN(4),
+          max_price_age_slots: new BN(5),
+          max_confidence_bps: 6,
+          price_feed: bytes32(7),
+          fee_recipient: bytes32(8),
+          stake_unit: new BN(10),
+          paused: 1,
+          max_message_len: 13,
+          cooldown_days: 14,
+          max_greet_many_accounts: 15,
+          claim_root: bytes32(16),
+          greeting_receipts: 1,
+          balance_mint: bytes32(18),
+          balance_unit: new BN(19),
+          epoch_bonus_slots: new BN(20),
+        }),
+      }),
+    ],
+    [
+      'set_config_empty',
+      I.SetConfig,
+      new SetConfigArgs({patch: new ConfigPatch()}),
+    ],
+    [
+      'init_counter_snapshot',
+      I.InitCounterSnapshot,
+      new InitCounterSnapshotArgs({depth: 4}),
+    ],
+    [
+      'snapshot_counters',
+      I.SnapshotCounters,
+      new SnapshotCountersArgs({indices: [0, 1, 65536]}),
+    ],
+    [
+      'claim',
+      I.Claim,
+      new ClaimArgs({
+        index: 17,
+        amount: new BN(18),
+        proof: [bytes32(19), bytes32(20)],
+      }),
+    ],
+    ['snapshot', I.Snapshot, new SnapshotArgs({label: 'launch'})],
+    ['count_tag', I.CountTag, new CountTagArgs({tag: tag('gm')})],
+    [
+      'check_recency',
+      I.CheckRecency,
+      new CheckRecencyArgs({
+        slot: new BN(200000),
+        slot_hash: bytes32(21),
+        max_age_slots: new BN(150),
+      }),
+    ],
+    [
+      'register_foreign_greeter',
+      I.RegisterForeignGreeter,
+      new RegisterForeignGreeterArgs({chain: 2, sender: bytes32(22)}),
+    ],
+  ];
+}
+
+/**
+ * The accounts' data, by vector name
+ */
+function accounts(): [string, Buffer][] {
+  const greetedDays = new Uint8Array(46);
+  for (const day of [0, 100, 365]) {
+    greetedDays[day >> 3] |= 1 << (day & 7);
+  }
+  const greeting = new GreetingAccount({
+    txt: 'Hello1234567',
+    counter: new BN(42),
+    calendar_year: 2024,
+    greeted_days: greetedDays,
+    last_update_epoch: new BN(500),
+    direct_greets: new BN(40),
+    cpi_greets: new BN(2),
+  });
+  const config = new Config({
+    authority: bytes32(1),
+    decay_half_life_epochs: new BN(2),
+    crank_authority: bytes32(3),
+    fee_usd_micros: new BN(4),
+    max_price_age_slots: new BN(5),
+    max_confidence_bps: 6,
+    price_feed: bytes32(7),
+    fee_recipient: bytes32(8),
+    stake_mint: bytes32(9),
+    stake_unit: new BN(10),
+    greeting_log: bytes32(11),
+    paused: 1,
+    max_message_len: 13,
+    cooldown_days: 14,
+    max_greet_many_accounts: 15,
+    claim_root: bytes32(16),
+    greeting_receipts: 1,
+    balance_mint: bytes32(18),
+    balance_unit: new BN(19),
+    epoch_bonus_slots: new BN(20),
+  });
+  const receipt = new GreetingReceipt({
+    signer: bytes32(1),
+    slot: new BN(200000),
+    unix_timestamp: new BN(1700000000),
+  });
+  const snapshot = new GreetingSnapshot({
+    greeting_account: bytes32(1),
+    recorder: bytes32(2),
+    counter: new BN(42),
+    slot: new BN(200000),
+    unix_timestamp: new BN(1700000000),
+    label: 'launch',
+  });
+  const tagCounters = new TagCounters({
+    greeting_account: bytes32(1),
+    entries: [
+      new TagCount({tag: tag('gm'), count: new BN(3)}),
+      new TagCount({tag: tag('gn'), count: new BN(1)}),
+    ],
+  });
+  return [
+    ['greeting', accountData(GREETING_DISCRIMINATOR, greeting)],
+    ['config', Buffer.from(borsh.serialize(HelloWorldSchema, config))],
+    ['receipt', accountData(RECEIPT_DISCRIMINATOR, receipt)],
+    ['snapshot', accountData(SNAPSHOT_DISCRIMINATOR, snapshot)],
+    ['tag_counters', accountData(TAG_COUNTERS_DISCRIMINATOR, tagCounters)],
+  ];
+}
+
+async function write(name: string, data: Buffer): Promise<void> {
+  const file = path.join(GOLDEN_DIR, name);
+  await fs.mkdir(path.dirname(file), {recursive: true});
+  await fs.writeFile(file, data);
+}
+
+async function main() {
+  for (const [name, kind, args] of instructions()) {
+    const {data} = instruction(SystemProgram.programId, [], kind, args);
+    await write(`instruction/${name}.bin`, data);
+  }
+  for (const [name, data] of accounts()) {
+    await write(`account/${name}.bin`, data);
+  }
+  console.log(`Wrote the client's encodings to ${GOLDEN_DIR}`);
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
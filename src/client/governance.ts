@@ -0,0 +1,342 @@
+/**
+ * NOTES:
+ * - Lets a DAO own the deployment. With spl-governance the config authority is a
+ * governance's native treasury, a PDA the governance program signs for when a
+ * proposal that passed is executed. Every admin action (SetConfig, SetDecay,
+ * SetFee, SetCrankAuthority, SetAuthority) then has to go through a vote.
+ * - Hand the config over once, signed by the current authority (the payer):
+ *   npm run governance -- treasury <GOVERNANCE>
+ *   npm run governance -- hand-over <GOVERNANCE>
+ * - Then add admin actions to a draft proposal, created in the Realms app, as its
+ * owner (or their delegate, the payer):
+ *   npm run governance -- propose-set-decay <PROPOSAL> <HALF_LIFE_EPOCHS> [--index N]
+ *   npm run governance -- propose-set-config <PROPOSAL> <FIELD>=<VALUE>... [--index N]
+ *   npm run governance -- propose <PROPOSAL> <INSTRUCTIONS_JSON> [--index N]
+ * where INSTRUCTIONS_JSON holds instructions in the deployer's format (squads.ts)
+ * signed by the treasury. --index is the proposal's next transaction index, 0 for
+ * its first. Sign off, vote and execute in Realms.
+ * - propose-set-config changes only the config fields it names (SetConfig's patch),
+ * so a proposal to change one parameter can't reset another voted on meanwhile.
+ * - Only InsertTransaction is encoded here, by hand as governance.rs in the program
+ * crate does: GovernanceInstruction tag 9 followed by its Borsh fields.
+ * The governance account and token owner record are read out of the proposal (the
+ * ProposalV2 layout opens with the account type, governance, governing token mint,
+ * state and token owner record).
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import fs from 'mz/fs';
+
+import {instruction, parseConfigPatch, setConfigInstruction} from './crank';
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {HelloWorldInstruction, SetAuthorityArgs, SetDecayArgs} from './generated';
+import {Signer, sendAndConfirm} from './signer';
+import {parseInstructions} from './squads';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+export const GOVERNANCE_PROGRAM_ID = new PublicKey(
+  'GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw',
+);
+
+/**
+ * GovernanceInstruction tag of InsertTransaction
+ */
+const INSERT_TRANSACTION = 9;
+
+/**
+ * Offsets of the governance and token owner record keys in a ProposalV2 account
+ */
+const PROPOSAL_GOVERNANCE_OFFSET = 1;
+const PROPOSAL_TOKEN_OWNER_RECORD_OFFSET = 1 + 32 + 32 + 1;
+
+/**
+ * The accounts of a draft proposal to add transactions to
+ */
+export interface Proposal {
+  governanceProgramId: PublicKey;
+  governance: PublicKey;
+  proposal: PublicKey;
+  tokenOwnerRecord: PublicKey;
+}
+
+function u16(value: number): Buffer {
+  const buffer = Buffer.alloc(2);
+  buffer.writeUInt16LE(value);
+  return buffer;
+}
+
+function u32(value: number): Buffer {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32LE(value);
+  return buffer;
+}
+
+/**
+ * The native treasury of `governance`, the key to make the config authority
+ */
+export async function nativeTreasuryAddress(
+  governance: PublicKey,
+  governanceProgramId = GOVERNANCE_PROGRAM_ID,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from('native-treasury'), governance.toBuffer()],
+    governanceProgramId,
+  );
+  return address;
+}
+
+/**
+ * Address of the `index`th transaction of option `optionIndex` of a proposal
+ */
+export async function proposalTransactionAddress(
+  proposal: Proposal,
+  optionIndex: number,
+  index: number,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from('governance'),
+      proposal.proposal.toBuffer(),
+      Buffer.from([optionIndex]),
+      u16(index),
+    ],
+    proposal.governanceProgramId,
+  );
+  return address;
+}
+
+/**
+ * Read the governance and token owner record out of a proposal account
+ */
+export async function loadProposal(
+  connection: Connection,
+  proposal: PublicKey,
+): Promise<Proposal> {
+  const info = await connection.getAccountInfo(proposal);
+  if (info === null) {
+    throw new Error(`Proposal ${proposal.toBase58()} does not exist`);
+  }
+  const key = (offset: number) =>
+    new PublicKey(info.data.slice(offset, offset + 32));
+  return {
+    governanceProgramId: info.owner,
+    governance: key(PROPOSAL_GOVERNANCE_OFFSET),
+    proposal,
+    tokenOwnerRecord: key(PROPOSAL_TOKEN_OWNER_RECORD_OFFSET),
+  };
+}
+
+/**
+ * Build an InsertTransaction instruction adding `instructions` to the proposal as
+ * its `index`th transaction for option `optionIndex` (0 for a yes/no proposal)
+ */
+export async function insertTransactionInstruction(
+  proposal: Proposal,
+  governanceAuthority: PublicKey,
+  payer: PublicKey,
+  index: number,
+  instructions: TransactionInstruction[],
+  optionIndex = 0,
+): Promise<TransactionInstruction> {
+  const encoded = instructions.map(ix =>
+    Buffer.concat([
+      ix.programId.toBuffer(),
+      u32(ix.keys.length),
+      ...ix.keys.map(key =>
+        Buffer.concat([
+          key.pubkey.toBuffer(),
+          Buffer.from([key.isSigner ? 1 : 0, key.isWritable ? 1 : 0]),
+        ]),
+      ),
+      u32(ix.data.length),
+      ix.data,
+    ]),
+  );
+  const transaction = await proposalTransactionAddress(
+    proposal,
+    optionIndex,
+    index,
+  );
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: proposal.governance, isSigner: false, isWritable: false},
+      {pubkey: proposal.proposal, isSigner: false, isWritable: true},
+      {pubkey: proposal.tokenOwnerRecord, isSigner: false, isWritable: false},
+      {pubkey: governanceAuthority, isSigner: true, isWritable: false},
+      {pubkey: transaction, isSigner: false, isWritable: true},
+      {pubkey: payer, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+      {pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false},
+    ],
+    programId: proposal.governanceProgramId,
+    data: Buffer.concat([
+      Buffer.from([INSERT_TRANSACTION, optionIndex]),
+      u16(index),
+      u32(0), // hold_up_time before spl-governance 3.1, ignored since
+      u32(encoded.length),
+      ...encoded,
+    ]),
+  });
+}
+
+/**
+ * Build a SetAuthority instruction handing the config to `newAuthority`
+ */
+export async function setAuthorityInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  newAuthority: PublicKey,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: false},
+    ],
+    HelloWorldInstruction.SetAuthority,
+    new SetAuthorityArgs({new_authority: newAuthority.toBytes()}),
+  );
+}
+
+/**
+ * Build a SetDecay instruction changing the half-life to `halfLifeEpochs`
+ */
+export async function setDecayInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  halfLifeEpochs: number,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: false},
+    ],
+    HelloWorldInstruction.SetDecay,
+    new SetDecayArgs({half_life_epochs: new BN(halfLifeEpochs)}),
+  );
+}
+
+async function propose(
+  connection: Connection,
+  payer: Signer,
+  proposal: Proposal,
+  index: number,
+  instructions: TransactionInstruction[],
+): Promise<void> {
+  const treasury = await nativeTreasuryAddress(
+    proposal.governance,
+    proposal.governanceProgramId,
+  );
+  for (const ix of instructions) {
+    for (const key of ix.keys) {
+      if (key.isSigner && !key.pubkey.equals(treasury)) {
+        throw new Error(
+          `Only the treasury can sign, not ${key.pubkey.toBase58()}`,
+        );
+      }
+    }
+  }
+  await sendAndConfirm(
+    connection,
+    new Transaction().add(
+      await insertTransactionInstruction(
+        proposal,
+        payer.publicKey,
+        payer.publicKey,
+        index,
+        instructions,
+      ),
+    ),
+    [payer],
+  );
+  console.log(
+    `Added transaction ${index} to proposal ${proposal.proposal.toBase58()}`,
+  );
+}
+
+async function main() {
+  const [command, ...rest] = process.argv.slice(2);
+  const option = (name: string) => {
+    const index = rest.indexOf(name);
+    return index >= 0 ? rest.splice(index, 2)[1] : undefined;
+  };
+  const index = Number(option('--index') || 0);
+  const [first, second] = rest;
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+
+  if (command === 'treasury' && first) {
+    console.log((await nativeTreasuryAddress(new PublicKey(first))).toBase58());
+  } else if (command === 'hand-over' && first) {
+    const payer = await getPayerSigner();
+    const treasury = await nativeTreasuryAddress(new PublicKey(first));
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await setAuthorityInstruction(programId, payer.publicKey, treasury),
+      ),
+      [payer],
+    );
+    console.log(`The config authority is now the treasury ${treasury}`);
+  } else if (command === 'propose-set-decay' && first && Number(second) >= 0) {
+    const payer = await getPayerSigner();
+    const proposal = await loadProposal(connection, new PublicKey(first));
+    const treasury = await nativeTreasuryAddress(
+      proposal.governance,
+      proposal.governanceProgramId,
+    );
+    await propose(connection, payer, proposal, index, [
+      await setDecayInstruction(programId, treasury, Number(second)),
+    ]);
+  } else if (command === 'propose-set-config' && first && second) {
+    const payer = await getPayerSigner();
+    const proposal = await loadProposal(connection, new PublicKey(first));
+    const treasury = await nativeTreasuryAddress(
+      proposal.governance,
+      proposal.governanceProgramId,
+    );
+    const patch = parseConfigPatch(rest.slice(1));
+    await propose(connection, payer, proposal, index, [
+      await setConfigInstruction(programId, treasury, patch),
+    ]);
+  } else if (command === 'propose' && first && second) {
+    const payer = await getPayerSigner();
+    const proposal = await loadProposal(connection, new PublicKey(first));
+    const instructions = parseInstructions(
+      await fs.readFile(second, {encoding: 'utf8'}),
+    );
+    await propose(connection, payer, proposal, index, instructions);
+  } else {
+    throw new Error(
+      'Usage: governance treasury <GOVERNANCE>\n' +
+        '       governance hand-over <GOVERNANCE>\n' +
+        '       governance propose-set-decay <PROPOSAL> <HALF_LIFE_EPOCHS> ' +
+        '[--index N]\n' +
+        '       governance propose-set-config <PROPOSAL> <FIELD>=<VALUE>... ' +
+        '[--index N]\n' +
+        '       governance propose <PROPOSAL> <INSTRUCTIONS_JSON> [--index N]',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
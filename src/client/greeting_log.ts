@@ -0,0 +1,137 @@
+/**
+ * NOTES:
+ * - Starts the greeting log: a concurrent merkle tree of spl-account-compression that
+ * every Greet appends a leaf to from then on (see src/program-rust/src/compression.rs).
+ * - Usage, signed by the payer, which must be the config authority:
+ *   npm run greeting-log -- init [MAX_DEPTH] [MAX_BUFFER_SIZE]
+ * - The tree holds 2^MAX_DEPTH greetings (14 by default, about 16k). MAX_BUFFER_SIZE
+ * (64 by default) is how many appends can land between a proof being fetched and it
+ * being checked. Once the tree is full Greet fails until the authority starts a new
+ * log with another init.
+ * - Only the leaf hashes are stored. The leaves themselves (GreetingLeaf in
+ * generated.ts) are the data of the spl-noop instructions in each Greet's inner
+ * instructions, for an indexer to keep.
+ */
+
+import {
+  Account,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+
+import {instruction} from './crank';
+import {
+  ACCOUNT_COMPRESSION_PROGRAM_ID,
+  NOOP_PROGRAM_ID,
+  PROGRAM_KEYPAIR_PATH,
+  configAddress,
+  logAuthorityAddress,
+} from './hello_world';
+import {HelloWorldInstruction, InitGreetingLogArgs} from './generated';
+import {sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Size of the tree account of a tree `maxDepth` deep keeping `maxBufferSize`
+ * changes, without a canopy
+ */
+export function merkleTreeAccountSize(
+  maxDepth: number,
+  maxBufferSize: number,
+): number {
+  const header = 56;
+  const changeLog = 32 + 32 * maxDepth + 4 + 4;
+  const rightmostProof = 32 * maxDepth + 32 + 4 + 4;
+  return header + 8 + 8 + 8 + maxBufferSize * changeLog + rightmostProof;
+}
+
+/**
+ * Build an InitGreetingLog instruction starting the greeting log in `tree`
+ */
+export async function initGreetingLogInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  tree: PublicKey,
+  maxDepth: number,
+  maxBufferSize: number,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  const logAuthority = await logAuthorityAddress(programId);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: true},
+      {pubkey: authority, isSigner: true, isWritable: false},
+      {pubkey: tree, isSigner: false, isWritable: true},
+      {pubkey: logAuthority, isSigner: false, isWritable: false},
+      {
+        pubkey: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: NOOP_PROGRAM_ID, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.InitGreetingLog,
+    new InitGreetingLogArgs({
+      max_depth: maxDepth,
+      max_buffer_size: maxBufferSize,
+    }),
+  );
+}
+
+async function main() {
+  const [command, depth, buffer] = process.argv.slice(2);
+  if (command !== 'init') {
+    throw new Error('Usage: greeting-log init [MAX_DEPTH] [MAX_BUFFER_SIZE]');
+  }
+  const maxDepth = Number(depth || 14);
+  const maxBufferSize = Number(buffer || 64);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const payer = await getPayerSigner();
+
+  // NOTE The tree is too big for a PDA to be created by CPI (10 KiB at most), so it's
+  // a fresh keypair, allocated to the compression program in the same transaction
+  const tree = new Account();
+  const space = merkleTreeAccountSize(maxDepth, maxBufferSize);
+  const lamports = await connection.getMinimumBalanceForRentExemption(space);
+  await sendAndConfirm(
+    connection,
+    new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: payer.publicKey,
+        newAccountPubkey: tree.publicKey,
+        lamports,
+        space,
+        programId: ACCOUNT_COMPRESSION_PROGRAM_ID,
+      }),
+      await initGreetingLogInstruction(
+        programId,
+        payer.publicKey,
+        tree.publicKey,
+        maxDepth,
+        maxBufferSize,
+      ),
+    ),
+    [payer, tree],
+    programId,
+  );
+  console.log(
+    `Greetings are now logged to ${tree.publicKey.toBase58()}, up to`,
+    2 ** maxDepth,
+  );
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -2,35 +2,71 @@
 /* eslint-disable @typescript-eslint/no-unsafe-member-access */
 
 import {
-  Account,
+  Commitment,
   Connection,
   PublicKey,
   LAMPORTS_PER_SOL,
   SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   TransactionInstruction,
   Transaction,
-  sendAndConfirmTransaction,
 } from '@solana/web3.js';
+import BN from 'bn.js';
 import fs from 'mz/fs';
 import path from 'path';
 import * as borsh from 'borsh';
 
 import {
-  getPayer,
-  getRpcUrl,
+  getPayerSigner,
+  getRpcUrls,
   newAccountWithLamports,
   readAccountFromFile,
+  requestAirdropWithRetry,
 } from './utils';
+import {getProfile} from './config';
+import {jitoBlockEngineUrl, sendAndConfirmBundle} from './jito';
+import {checkRecencyInstruction, recentSlotHash} from './recency';
+import {RpcPool} from './rpc_pool';
+import {Signer, offlineOptions, sendAndConfirm} from './signer';
+import {
+  decodeConfig,
+  decodeGreeting,
+  effectiveCounter,
+  greetedDays,
+  greetingSize,
+} from './state';
+// NOTE GreetingAccount (the state of a greeting account), the instruction argument
+// classes, the Borsh schema and the instruction tags are all generated from the Rust
+// program so the two sides can't drift. See src/schema/src/bin/ts.rs.
+import {
+  CONFIG_SEED,
+  GreetArgs,
+  GreetForArgs,
+  GreetManyArgs,
+  GreetingAccount,
+  HelloWorldInstruction,
+  HelloWorldSchema,
+  LOG_AUTHORITY_SEED,
+  RECEIPT_SEED,
+  SESSION_SEED,
+  STAKE_SEED,
+  TARGET_GREETING_SEED,
+} from './generated';
 
 /**
  * Connection to the network
  */
 let connection: Connection;
 
+/**
+ * The RPC endpoints to fail over between
+ */
+let rpcPool: RpcPool;
+
 /**
  * Connection to the network
  */
-let payerAccount: Account;
+let payerAccount: Signer;
 
 /**
  * Hello world's program id
@@ -42,6 +78,16 @@ let programId: PublicKey;
  */
 let greetedPubkey: PublicKey;
 
+/**
+ * The program's config account
+ */
+let configPubkey: PublicKey;
+
+/**
+ * Seed of the payer's greeting account, derived from the payer and the program id
+ */
+export const GREETING_SEED = 'hello';
+
 /**
  * Path to program files
  */
@@ -59,42 +105,31 @@ const PROGRAM_SO_PATH = path.join(PROGRAM_PATH, 'helloworld.so');
  * Path to the keypair of the deployed program.
  * This file is created when running `solana program deploy dist/program/helloworld.so`
  */
-const PROGRAM_KEYPAIR_PATH = path.join(PROGRAM_PATH, 'helloworld-keypair.json');
-
-/**
- * The state of a greeting account managed by the hello world program
- */
-class GreetingAccount {
-  // // NOTE This class is analogous to Rust struct type (pub struct GreetingAccount)
-  // counter = 0;
-  // // NOTE The borsh library requires that we use a constructor like below
-  // constructor(fields: {counter: number} | undefined = undefined) {
-  //   // Any new class properties would need to be set within this scope
-  //   if (fields) {
-  //     this.counter = fields.counter;
-  //     // this.newField = fields.newField;
-  //   }
-  // }
-  // === Sending a string message ===
-  txt = '';
-  constructor(fields: {txt: string} | undefined = undefined) {
-    if (fields) {
-      this.txt = fields.txt;
-    }
-  }
-}
+export const PROGRAM_KEYPAIR_PATH = path.join(
+  PROGRAM_PATH,
+  'helloworld-keypair.json',
+);
+
+/**
+ * Program ids recorded per RPC url by the deploy tool (`npm run deploy:program-rust`)
+ */
+const DEPLOYMENTS_PATH = path.join(PROGRAM_PATH, 'deployments.json');
 
 /**
- * Borsh schema definition for greeting accounts
+ * Program id the deploy tool recorded for `rpcUrl`, if any
  */
-const GreetingSchema = new Map([
-  // NOTE Borsh needs additional metadata in order to do a Mapping.
-  // It needs the client side Type (GreetingAccount), and it also needs
-  // metadata from our Rust program. This is all provided to the
-  // borsh.serialize() method below (see GREETING_SIZE).
-  // [GreetingAccount, {kind: 'struct', fields: [['counter', 'u32']]}],
-  [GreetingAccount, {kind: 'struct', fields: [['txt', 'String']]}],
-]);
+async function deployedProgramId(rpcUrl: string): Promise<PublicKey | null> {
+  if (!fs.existsSync(DEPLOYMENTS_PATH)) {
+    return null;
+  }
+  const deployments = JSON.parse(
+    await fs.readFile(DEPLOYMENTS_PATH, {encoding: 'utf8'}),
+  ) as Record<string, {programId?: string} | undefined>;
+  const deployment = deployments[rpcUrl];
+  return deployment && deployment.programId
+    ? new PublicKey(deployment.programId)
+    : null;
+}
 
 /**
  * The expected size of each greeting account.
@@ -107,23 +142,20 @@ const sampleGreeter = new GreetingAccount();
 sampleGreeter.txt = '000000000000';
 // NOTE This serializes (encode) to the destination data type (Uint8Array which is an
 // Array of 8 bytes) and it's taking out the length (size). This is how it knows how
-// much data size is required.
-const GREETING_SIZE = borsh.serialize(
-  GreetingSchema,
-  // new GreetingAccount(),
-  // UPDATE Replace the type with our sampleGreeter
-  sampleGreeter,
-).length;
+// much data size is required. greetingSize() adds the 8 byte account discriminator.
+export const GREETING_SIZE = greetingSize(sampleGreeter);
 console.log('Greeting account size:', GREETING_SIZE);
 
 /**
  * Establish a connection to the cluster
  */
 export async function establishConnection(): Promise<void> {
-  const rpcUrl = await getRpcUrl();
-  connection = new Connection(rpcUrl, 'confirmed');
+  // NOTE Pass several urls with RPC_URLS=url1,url2 to fail over between them
+  const {commitment} = await getProfile();
+  rpcPool = new RpcPool(await getRpcUrls(), commitment);
+  connection = await rpcPool.select();
   const version = await connection.getVersion();
-  console.log('Connection to cluster established:', rpcUrl, version);
+  console.log('Connection to cluster established:', rpcPool.url, version);
 }
 
 /**
@@ -155,7 +187,7 @@ export async function establishPayer(): Promise<void> {
     try {
       // Get payer from cli config
       // NOTE Again, this is creating the initial keypair, NOT the actual Solana account
-      payerAccount = await getPayer();
+      payerAccount = await getPayerSigner();
     } catch (err) {
       // Fund a new payer via airdrop
       // NOTE This is the helper method from utils.ts
@@ -167,11 +199,11 @@ export async function establishPayer(): Promise<void> {
   const lamports = await connection.getBalance(payerAccount.publicKey);
   if (lamports < fees) {
     // This should only happen when using cli config keypair
-    const sig = await connection.requestAirdrop(
+    await requestAirdropWithRetry(
+      connection,
       payerAccount.publicKey,
       fees - lamports,
     );
-    await connection.confirmTransaction(sig);
   }
 
   console.log(
@@ -187,10 +219,15 @@ export async function establishPayer(): Promise<void> {
  * Check if the hello world BPF program has been deployed
  */
 export async function checkProgram(): Promise<void> {
-  // Read program id from keypair file
+  // Read program id from the profile, or the deploy tool's record for this
+  // cluster, falling back to the keypair file
+  const profile = await getProfile();
   try {
-    const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
-    programId = programAccount.publicKey;
+    const deployed = profile.programId
+      ? new PublicKey(profile.programId)
+      : await deployedProgramId(rpcPool.url);
+    programId =
+      deployed || (await readAccountFromFile(PROGRAM_KEYPAIR_PATH)).publicKey;
   } catch (err) {
     const errMsg = (err as Error).message;
     throw new Error(
@@ -217,9 +254,9 @@ export async function checkProgram(): Promise<void> {
     throw new Error(`Program is not executable`);
   }
   console.log(`Using program ${programId.toBase58()}`);
+  configPubkey = await configAddress(programId);
 
   // Derive the address of a greeting account from the program so that it's easy to find later.
-  const GREETING_SEED = 'hello';
   // NOTE This is creating a seed in order to prepare for the actual AccountInfo object creation
   greetedPubkey = await PublicKey.createWithSeed(
     payerAccount.publicKey,
@@ -260,71 +297,767 @@ export async function checkProgram(): Promise<void> {
         programId, // The programId that will own, access, control and update this account
       }),
     );
-    await sendAndConfirmTransaction(connection, transaction, [payerAccount]);
+    await sendAndConfirm(connection, transaction, [payerAccount]);
   }
 }
 
 /**
- * Say hello
+ * Address of the program's config account, a PDA at seeds [CONFIG_SEED]
  */
-export async function sayHello(msg: string): Promise<void> {
-  // Get the account affected by our program
-  console.log('Saying hello to', greetedPubkey.toBase58());
-  // Create a new GreetingAccount instance to ensure data structure aligns for Borsh
-  const messageAccount = new GreetingAccount();
-  // Set txt property to equal the msg argument
-  messageAccount.txt = msg;
-  // Create a new transaction instruction that we'll add to transaction
-  const instruction = new TransactionInstruction({
-    keys: [{pubkey: greetedPubkey, isSigner: false, isWritable: true}],
+export async function configAddress(programId: PublicKey): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(CONFIG_SEED)],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * The accounts a Greet needs when the config charges a fee
+ */
+export interface FeeAccounts {
+  payer: PublicKey;
+  priceFeed: PublicKey;
+  feeRecipient: PublicKey;
+}
+
+/**
+ * The fee accounts for `payer` to greet with, or undefined while greetings are free
+ */
+export async function feeAccounts(
+  connection: Connection,
+  programId: PublicKey,
+  payer: PublicKey,
+): Promise<FeeAccounts | undefined> {
+  const configInfo = await connection.getAccountInfo(
+    await configAddress(programId),
+  );
+  if (configInfo === null || !configInfo.owner.equals(programId)) {
+    return undefined;
+  }
+  const config = decodeConfig(configInfo.data);
+  if (config.fee_usd_micros.isZero()) {
+    return undefined;
+  }
+  return {
+    payer,
+    priceFeed: new PublicKey(config.price_feed),
+    feeRecipient: new PublicKey(config.fee_recipient),
+  };
+}
+
+/**
+ * The spl-account-compression program, which holds the greeting log's merkle tree
+ */
+export const ACCOUNT_COMPRESSION_PROGRAM_ID = new PublicKey(
+  'cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK',
+);
+
+/**
+ * The spl-noop program, which greetings are logged through
+ */
+export const NOOP_PROGRAM_ID = new PublicKey(
+  'noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNkWnvV',
+);
+
+/**
+ * Address of the log authority, the authority of the greeting log's merkle tree
+ */
+export async function logAuthorityAddress(
+  programId: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(LOG_AUTHORITY_SEED)],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * The accounts a Greet needs when the config sets a greeting log
+ */
+export interface GreetingLogAccounts {
+  tree: PublicKey;
+  logAuthority: PublicKey;
+}
+
+/**
+ * The greeting log accounts to greet with, or undefined while greetings aren't logged
+ */
+export async function greetingLogAccounts(
+  connection: Connection,
+  programId: PublicKey,
+): Promise<GreetingLogAccounts | undefined> {
+  const configInfo = await connection.getAccountInfo(
+    await configAddress(programId),
+  );
+  if (configInfo === null || !configInfo.owner.equals(programId)) {
+    return undefined;
+  }
+  const config = decodeConfig(configInfo.data);
+  if (config.greeting_log.every(byte => byte === 0)) {
+    return undefined;
+  }
+  return {
+    tree: new PublicKey(config.greeting_log),
+    logAuthority: await logAuthorityAddress(programId),
+  };
+}
+
+/**
+ * Address of the receipt of the greeting of `greetedPubkey` whose counter read
+ * `counter` before it
+ */
+export async function receiptAddress(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  counter: BN,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from(RECEIPT_SEED),
+      greetedPubkey.toBuffer(),
+      counter.toArrayLike(Buffer, 'le', 8),
+    ],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * The accounts a Greet needs when the config sets greeting receipts
+ */
+export interface ReceiptAccounts {
+  receipt: PublicKey;
+  signer: PublicKey;
+}
+
+/**
+ * The receipt accounts for `signer` to greet `greetedPubkey` with, or undefined while
+ * the config keeps no receipts. The receipt is the next greeting's, so build the
+ * Greet right after.
+ */
+export async function receiptAccounts(
+  connection: Connection,
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  signer: PublicKey,
+): Promise<ReceiptAccounts | undefined> {
+  const configInfo = await connection.getAccountInfo(
+    await configAddress(programId),
+  );
+  if (configInfo === null || !configInfo.owner.equals(programId)) {
+    return undefined;
+  }
+  if (!decodeConfig(configInfo.data).greeting_receipts) {
+    return undefined;
+  }
+  const greeted = await connection.getAccountInfo(greetedPubkey);
+  const counter = greeted ? decodeGreeting(greeted.data).counter : new BN(0);
+  return {
+    receipt: await receiptAddress(programId, greetedPubkey, counter),
+    signer,
+  };
+}
+
+/**
+ * The accounts a Greet needs when the config sets a balance mint: the greeter's
+ * token account of the mint, signed for by its owner
+ */
+export interface BalanceAccounts {
+  tokenAccount: PublicKey;
+  owner: PublicKey;
+}
+
+/**
+ * The balance accounts for `owner` to greet with, or undefined while the config
+ * weighs no balance. Takes the first token account of the config's balance mint
+ * `owner` has.
+ */
+export async function balanceAccounts(
+  connection: Connection,
+  programId: PublicKey,
+  owner: PublicKey,
+): Promise<BalanceAccounts | undefined> {
+  const configInfo = await connection.getAccountInfo(
+    await configAddress(programId),
+  );
+  if (configInfo === null || !configInfo.owner.equals(programId)) {
+    return undefined;
+  }
+  const config = decodeConfig(configInfo.data);
+  if (config.balance_mint.every(byte => byte === 0)) {
+    return undefined;
+  }
+  const mint = new PublicKey(config.balance_mint);
+  const {value} = await connection.getTokenAccountsByOwner(owner, {mint});
+  if (value.length === 0) {
+    throw new Error(`${owner.toBase58()} has no account of ${mint.toBase58()}`);
+  }
+  return {tokenAccount: value[0].pubkey, owner};
+}
+
+/**
+ * Address of `staker`'s stake position
+ */
+export async function stakePositionAddress(
+  programId: PublicKey,
+  staker: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(STAKE_SEED), staker.toBuffer()],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Address of `target`'s greeting account, the one GreetFor saves into
+ */
+export async function greetingAddress(
+  programId: PublicKey,
+  target: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(TARGET_GREETING_SEED), target.toBuffer()],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Address of `authority`'s session token for `sessionKey`
+ */
+export async function sessionAddress(
+  programId: PublicKey,
+  authority: PublicKey,
+  sessionKey: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(SESSION_SEED), authority.toBuffer(), sessionKey.toBuffer()],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * The accounts a Greet weighted by stake needs: the staker's position, signed for
+ * by the staker, or by a session key of theirs along with its session token
+ */
+export interface StakeAccounts {
+  position: PublicKey;
+  signer: PublicKey;
+  sessionToken?: PublicKey;
+}
+
+/**
+ * Build a Greet instruction saving `txt` into `greetedPubkey`, see configAddress()
+ * for `configPubkey`. A deployment charging a fee also needs `fee`, see
+ * feeAccounts(), one logging greetings `log`, see greetingLogAccounts(), and one
+ * keeping receipts `receipt`, see receiptAccounts(). Pass `stake` to weight the
+ * greeting by stake, or `balance` when the config weighs balances, see
+ * balanceAccounts().
+ */
+export function greetInstruction(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  configPubkey: PublicKey,
+  txt: string,
+  fee?: FeeAccounts,
+  log?: GreetingLogAccounts,
+  stake?: StakeAccounts,
+  receipt?: ReceiptAccounts,
+  balance?: BalanceAccounts,
+): TransactionInstruction {
+  // Create the Greet instruction's arguments so the data structure aligns for Borsh
+  const greetArgs = new GreetArgs({txt});
+  const keys = [
+    {pubkey: greetedPubkey, isSigner: false, isWritable: true},
+    {pubkey: configPubkey, isSigner: false, isWritable: false},
+  ];
+  if (fee) {
+    keys.push(
+      {pubkey: fee.payer, isSigner: true, isWritable: true},
+      {pubkey: fee.priceFeed, isSigner: false, isWritable: false},
+      {pubkey: fee.feeRecipient, isSigner: false, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    );
+  }
+  if (log) {
+    keys.push(
+      {pubkey: log.tree, isSigner: false, isWritable: true},
+      {pubkey: log.logAuthority, isSigner: false, isWritable: false},
+      {
+        pubkey: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        isSigner: false,
+        isWritable: false,
+      },
+      {pubkey: NOOP_PROGRAM_ID, isSigner: false, isWritable: false},
+    );
+  }
+  if (receipt) {
+    keys.push(
+      {pubkey: receipt.receipt, isSigner: false, isWritable: true},
+      {pubkey: receipt.signer, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    );
+  }
+  if (balance) {
+    keys.push(
+      {pubkey: balance.tokenAccount, isSigner: false, isWritable: false},
+      {pubkey: balance.owner, isSigner: true, isWritable: false},
+    );
+  }
+  if (stake) {
+    keys.push(
+      {pubkey: stake.position, isSigner: false, isWritable: false},
+      {pubkey: stake.signer, isSigner: true, isWritable: false},
+    );
+    if (stake.sessionToken) {
+      keys.push({
+        pubkey: stake.sessionToken,
+        isSigner: false,
+        isWritable: false,
+      });
+    }
+  }
+  return new TransactionInstruction({
+    keys,
     programId, // The controlling program
-    // data: Buffer.alloc(0), // Any data sent over (none in this example). All instructions are hellos
-    // NOTE 'data' (below). All metadata from GreetingAccount and GreetingSchema MUST to be passed
+    // NOTE 'data' (below). All metadata from GreetArgs and HelloWorldSchema MUST to be passed
     // to the program (Rust) in their correct form, so that's why this Borsh serialization
     // is necessary.
     // NOTE We're wrapping in a Node Buffer so it can go over to the program as a blob.
     // Then, in the program, it can be deserialized (if needed) and then inserted into
     // the account's data (which is a bytearray &[u8]) via:
     // data[..instruction_data.len()].copy_from_slice(&instruction_data);
-    data: Buffer.from(borsh.serialize(GreetingSchema, messageAccount)),
+    // NOTE The instruction data is the Greet tag byte followed by its arguments.
+    data: Buffer.concat([
+      Buffer.from([HelloWorldInstruction.Greet]),
+      Buffer.from(borsh.serialize(HelloWorldSchema, greetArgs)),
+    ]),
+  });
+}
+
+/**
+ * Build an Increment instruction counting a greeting of `greetedPubkey` without
+ * changing its message. Only for deployments with no fee, greeting log or greeting
+ * receipts, and accounts already on the current schema.
+ */
+export function incrementInstruction(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  configPubkey: PublicKey,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: greetedPubkey, isSigner: false, isWritable: true},
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+    ],
+    programId,
+    data: Buffer.from([HelloWorldInstruction.Increment]),
+  });
+}
+
+/**
+ * Build a GreetMany instruction saving `txt` into every account of `greetedPubkeys`,
+ * at most MAX_GREET_MANY_ACCOUNTS of them. Accounts that can't be greeted are
+ * skipped, each with a "GreetMany skipped <index> <pubkey>: <error>" log line. Only
+ * for deployments with no fee, greeting log or greeting receipts.
+ */
+export function greetManyInstruction(
+  programId: PublicKey,
+  greetedPubkeys: PublicKey[],
+  configPubkey: PublicKey,
+  txt: string,
+): TransactionInstruction {
+  const keys = [{pubkey: configPubkey, isSigner: false, isWritable: false}];
+  for (const pubkey of greetedPubkeys) {
+    keys.push({pubkey, isSigner: false, isWritable: true});
+  }
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data: Buffer.concat([
+      Buffer.from([HelloWorldInstruction.GreetMany]),
+      Buffer.from(borsh.serialize(HelloWorldSchema, new GreetManyArgs({txt}))),
+    ]),
   });
-  await sendAndConfirmTransaction(
-    connection, // Run on same network
-    // Create transaction and add our instruction (above)
-    new Transaction().add(instruction),
-    [payerAccount], // The transaction payer
+}
+
+/**
+ * Build a GreetFor instruction saving `txt` into `target`'s greeting account, paid
+ * for by `payer`, who signs. The target doesn't sign. The account is created, at the
+ * payer's expense, the first time the target is greeted. When the config charges a
+ * fee, pass `fee` with the price feed and recipient it names.
+ */
+export async function greetForInstruction(
+  programId: PublicKey,
+  payer: PublicKey,
+  target: PublicKey,
+  txt: string,
+  fee?: {priceFeed: PublicKey; feeRecipient: PublicKey},
+): Promise<TransactionInstruction> {
+  const keys = [
+    {
+      pubkey: await greetingAddress(programId, target),
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: await configAddress(programId),
+      isSigner: false,
+      isWritable: false,
+    },
+    {pubkey: payer, isSigner: true, isWritable: true},
+    {pubkey: target, isSigner: false, isWritable: false},
+    {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+  ];
+  if (fee) {
+    keys.push(
+      {pubkey: fee.priceFeed, isSigner: false, isWritable: false},
+      {pubkey: fee.feeRecipient, isSigner: false, isWritable: true},
+    );
+  }
+  return new TransactionInstruction({
+    keys,
+    programId,
+    data: Buffer.concat([
+      Buffer.from([HelloWorldInstruction.GreetFor]),
+      Buffer.from(borsh.serialize(HelloWorldSchema, new GreetForArgs({txt}))),
+    ]),
+  });
+}
+
+/**
+ * Append the instructions sysvar to a Greet, Increment, GreetMany or GreetFor
+ * instruction, so the program counts the greeting in direct_greets or cpi_greets
+ * by whether it came straight from the transaction or through another program.
+ * Call it last: the sysvar goes after every other account.
+ */
+export function withInstructionsSysvar(
+  instruction: TransactionInstruction,
+): TransactionInstruction {
+  instruction.keys.push({
+    pubkey: SYSVAR_INSTRUCTIONS_PUBKEY,
+    isSigner: false,
+    isWritable: false,
+  });
+  return instruction;
+}
+
+/**
+ * The cluster and accounts in use, once established
+ */
+export function clientAccounts(): {
+  rpcUrl: string;
+  payer: PublicKey;
+  programId: PublicKey;
+  greeted: PublicKey;
+} {
+  return {
+    rpcUrl: rpcPool.url,
+    payer: payerAccount.publicKey,
+    programId,
+    greeted: greetedPubkey,
+  };
+}
+
+/**
+ * Say hello. Returns the transaction signature, or null when it wasn't sent
+ * directly (through Jito, or printed for offline signing).
+ */
+export async function sayHello(msg: string): Promise<string | null> {
+  // Get the account affected by our program
+  console.log('Saying hello to', greetedPubkey.toBase58());
+  const fee = await rpcPool.run(connection =>
+    feeAccounts(connection, programId, payerAccount.publicKey),
+  );
+  const log = await rpcPool.run(connection =>
+    greetingLogAccounts(connection, programId),
   );
+  const receipt = await rpcPool.run(connection =>
+    receiptAccounts(
+      connection,
+      programId,
+      greetedPubkey,
+      payerAccount.publicKey,
+    ),
+  );
+  const balance = await rpcPool.run(connection =>
+    balanceAccounts(connection, programId, payerAccount.publicKey),
+  );
+  // Create a new transaction instruction that we'll add to transaction
+  const instruction = withInstructionsSysvar(
+    greetInstruction(
+      programId,
+      greetedPubkey,
+      configPubkey,
+      msg,
+      fee,
+      log,
+      undefined,
+      receipt,
+      balance,
+    ),
+  );
+  const {signOnly, dumpTransaction, maxAgeSlots} = offlineOptions();
+  const transaction = new Transaction();
+  // NOTE With --max-age-slots the greeting can't land once the slot hash is stale,
+  // for a greeting signed now and sent later
+  if (maxAgeSlots !== undefined) {
+    const slotHash = await rpcPool.run(connection => recentSlotHash(connection));
+    transaction.add(checkRecencyInstruction(programId, slotHash, maxAgeSlots));
+  }
+  transaction.add(instruction);
+  // NOTE Optionally go through Jito's block engine for inclusion during congestion
+  const blockEngineUrl = jitoBlockEngineUrl();
+  if (blockEngineUrl && !signOnly && !dumpTransaction) {
+    await sendAndConfirmBundle(
+      connection,
+      transaction,
+      [payerAccount],
+      blockEngineUrl,
+      programId,
+    );
+    return null;
+  }
+  const signature = await rpcPool.run(connection =>
+    sendAndConfirm(
+      connection, // Run on same network
+      transaction, // Our instruction (above), after the recency check if any
+      [payerAccount], // The transaction payer
+      programId, // Name the program's errors if the simulation fails
+    ),
+  );
+  return signature || null;
 }
 
 /**
- * Report the number of times the greeted account has been said hello to
+ * Copy the payer's greeting saved under the program deployed at `oldProgramId`
+ * into its greeting account of the current program. The old deployment logs the
+ * state it hands over first (ExportState), unless `exportFirst` is false for
+ * deployments that predate it. Only the config authority can import, so the payer
+ * has to be it. Returns the transaction signature.
  */
-export async function reportGreetings(): Promise<void> {
+export async function importGreeting(
+  oldProgramId: PublicKey,
+  exportFirst = true,
+): Promise<string> {
+  const sourcePubkey = await PublicKey.createWithSeed(
+    payerAccount.publicKey,
+    GREETING_SEED,
+    oldProgramId,
+  );
+  const source = await rpcPool.run(connection =>
+    connection.getAccountInfo(sourcePubkey),
+  );
+  if (source === null || !source.owner.equals(oldProgramId)) {
+    throw new Error(
+      `No greeting account ${sourcePubkey.toBase58()} under ${oldProgramId.toBase58()}`,
+    );
+  }
+  console.log(
+    'Importing',
+    sourcePubkey.toBase58(),
+    'into',
+    greetedPubkey.toBase58(),
+  );
+  const transaction = new Transaction();
+  if (exportFirst) {
+    transaction.add(
+      new TransactionInstruction({
+        keys: [{pubkey: sourcePubkey, isSigner: false, isWritable: false}],
+        programId: oldProgramId,
+        data: Buffer.from([HelloWorldInstruction.ExportState]),
+      }),
+    );
+  }
+  transaction.add(
+    new TransactionInstruction({
+      keys: [
+        {pubkey: greetedPubkey, isSigner: false, isWritable: true},
+        {pubkey: sourcePubkey, isSigner: false, isWritable: false},
+        {pubkey: configPubkey, isSigner: false, isWritable: false},
+        {pubkey: payerAccount.publicKey, isSigner: true, isWritable: false},
+      ],
+      programId,
+      data: Buffer.from([HelloWorldInstruction.ImportState]),
+    }),
+  );
+  return rpcPool.run(connection =>
+    sendAndConfirm(connection, transaction, [payerAccount], programId),
+  );
+}
+
+/**
+ * Report the version of the deployed program
+ */
+export async function reportVersion(): Promise<void> {
+  // NOTE The program logs its versions rather than returning them, so we only
+  // need to simulate the transaction (no fees) and read the log line back.
+  const transaction = new Transaction().add(
+    new TransactionInstruction({
+      keys: [],
+      programId,
+      data: Buffer.from([HelloWorldInstruction.Version]),
+    }),
+  );
+  // NOTE Simulation doesn't check signatures, so a Ledger payer isn't asked to sign
+  transaction.feePayer = payerAccount.publicKey;
+  const {value} = await connection.simulateTransaction(transaction);
+  const prefix = 'Program log: helloworld-version: ';
+  const line = (value.logs || []).find(log => log.startsWith(prefix));
+  if (!line) {
+    throw new Error('Program did not report a version');
+  }
+  const [version, schema] = line.slice(prefix.length).split(' schema: ');
+  console.log('Program version', version, 'with state schema', schema);
+}
+
+/**
+ * A value read from the cluster, with the slot the node had reached when it answered
+ * (at the commitment asked for). Compare slots to tell how stale a read is.
+ */
+export interface SlotRead<T> {
+  slot: number;
+  value: T;
+}
+
+/**
+ * Fetch and decode the greeting account `pubkey` at `commitment` (the connection's
+ * own when left out). The value is null when the account doesn't exist, isn't owned
+ * by `programId` or doesn't decode.
+ */
+export async function getGreeting(
+  connection: Connection,
+  programId: PublicKey,
+  pubkey: PublicKey,
+  commitment?: Commitment,
+): Promise<SlotRead<GreetingAccount | null>> {
+  const {context, value} = await connection.getAccountInfoAndContext(
+    pubkey,
+    commitment,
+  );
+  let greeting = null;
+  if (value !== null && value.owner.equals(programId)) {
+    try {
+      greeting = decodeGreeting(value.data);
+    } catch (err) {
+      // Not a greeting account (e.g. the config account)
+    }
+  }
+  return {slot: context.slot, value: greeting};
+}
+
+/**
+ * The most accounts one getMultipleAccounts request may ask for
+ */
+export const MAX_MULTIPLE_ACCOUNTS = 100;
+
+/**
+ * Fetch and decode the greeting accounts of `pubkeys` at `commitment`, one
+ * getMultipleAccounts request per MAX_MULTIPLE_ACCOUNTS of them. Keyed by base58
+ * address; accounts that don't exist, aren't owned by `programId` or don't decode are
+ * left out. With several requests the slot is the oldest one answered at.
+ */
+// NOTE web3.js 1.12 has getMultipleAccountsInfo() but no variant returning the
+// context, so the request goes through the connection's RPC call directly.
+export async function getGreetings(
+  connection: Connection,
+  programId: PublicKey,
+  pubkeys: PublicKey[],
+  commitment: Commitment = connection.commitment || 'confirmed',
+): Promise<SlotRead<Map<string, GreetingAccount>>> {
+  const greetings = new Map<string, GreetingAccount>();
+  let slot = Infinity;
+  for (let start = 0; start < pubkeys.length; start += MAX_MULTIPLE_ACCOUNTS) {
+    const chunk = pubkeys.slice(start, start + MAX_MULTIPLE_ACCOUNTS);
+    const response = await (connection as any)._rpcRequest(
+      'getMultipleAccounts',
+      [chunk.map(key => key.toBase58()), {encoding: 'base64', commitment}],
+    );
+    if (response.error) {
+      throw new Error(
+        `getMultipleAccounts failed: ${String(response.error.message)}`,
+      );
+    }
+    slot = Math.min(slot, Number(response.result.context.slot));
+    const infos: ({owner: string; data: [string, string]} | null)[] =
+      response.result.value;
+    infos.forEach((info, i) => {
+      if (info === null || info.owner !== programId.toBase58()) {
+        return;
+      }
+      try {
+        const data = Buffer.from(info.data[0], 'base64');
+        greetings.set(chunk[i].toBase58(), decodeGreeting(data));
+      } catch (err) {
+        // Not a greeting account (e.g. the config account)
+      }
+    });
+  }
+  return {slot: slot === Infinity ? 0 : slot, value: greetings};
+}
+
+/**
+ * Report the number of times the greeted account has been said hello to, read at
+ * `commitment` (the RPC pool's own when left out)
+ */
+export async function reportGreetings(
+  commitment?: Commitment,
+): Promise<void> {
   console.log('Retrieving message from greeting account');
   // Retrieve the greetedAccount/AccountInfo
   // NOTE Borsh serialize/deserialize is very similar to how it works in our
   // Rust program as well.
-  const accountInfo = await connection.getAccountInfo(greetedPubkey);
-  if (accountInfo === null) {
+  // NOTE decodeGreeting() (through getGreeting()) reads accounts of every schema
+  // version, including the ones the program hasn't migrated yet
+  const {slot, value: greeting} = await rpcPool.run(connection =>
+    getGreeting(connection, programId, greetedPubkey, commitment),
+  );
+  if (greeting === null) {
     throw 'Error: cannot find the greeted account';
   }
-  // Retrieve data from this account on the network
-  // Perform some deserializations (decode) (binary -> data type)
-  // so that we have a JS type we can work with in JS.
-  // NOTE This will error if our data size doesn't match between client/program
-  const greeting: GreetingAccount = borsh.deserialize(
-    GreetingSchema,
-    GreetingAccount,
-    accountInfo.data,
-  );
+  console.log('Read at slot', slot);
   // Last, we now have a GreetingAccount class/object instance,
-  // so we can attempt to display the counter (data) value
-  // console.log(
-  //   greetedPubkey.toBase58(),
-  //   'has been greeted',
-  //   greeting.counter,
-  //   'time(s)',
-  // );
+  // so we can display the counter (data) value
+  console.log(
+    greetedPubkey.toBase58(),
+    'has been greeted',
+    greeting.counter.toString(),
+    'time(s)',
+  );
+  // NOTE The stored counter is the raw one, decayed lazily by the program. Work out
+  // what it has decayed to by now when the config sets a half-life.
+  const configInfo = await rpcPool.run(connection =>
+    connection.getAccountInfo(configPubkey),
+  );
+  if (configInfo !== null && configInfo.owner.equals(programId)) {
+    const config = decodeConfig(configInfo.data);
+    const halfLife = config.decay_half_life_epochs.toNumber();
+    if (halfLife > 0) {
+      const {epoch} = await rpcPool.run(connection =>
+        connection.getEpochInfo(),
+      );
+      console.log(
+        'Counter halves every',
+        halfLife,
+        'epoch(s), down to',
+        effectiveCounter(greeting, halfLife, epoch).toString(),
+        'at epoch',
+        epoch,
+      );
+    }
+  }
+  if (greeting.calendar_year !== 0) {
+    console.log(
+      'Greeted on',
+      greetedDays(greeting).length,
+      'day(s) of',
+      greeting.calendar_year,
+    );
+  }
   console.log(
     'Account',
     greetedPubkey.toBase58(),
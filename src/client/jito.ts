@@ -0,0 +1,129 @@
+/* eslint-disable @typescript-eslint/no-unsafe-assignment */
+/* eslint-disable @typescript-eslint/no-unsafe-member-access */
+/* eslint-disable @typescript-eslint/no-unsafe-return */
+
+/**
+ * NOTES:
+ * - During congestion a plain sendTransaction can sit in the queue and expire. Jito's
+ * block engine instead auctions "bundles" (up to 5 transactions executed in order and
+ * all-or-nothing) to Jito-Solana validators, and including a tip buys priority.
+ * - The tip is just a SystemProgram transfer to one of Jito's tip accounts. It goes in
+ * the same transaction as the greet, so the tip is only paid if the greet lands.
+ * - Bundles are only processed on clusters running Jito validators (mainnet-beta, and
+ * testnet with the testnet block engine). There's no block engine on localnet.
+ * - Set JITO_BLOCK_ENGINE_URL (e.g. https://mainnet.block-engine.jito.wtf) and optionally
+ * JITO_TIP_LAMPORTS to have sayHello() send through Jito.
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from '@solana/web3.js';
+import {helloWorldTxError} from './errors';
+import {Signer, signTransaction} from './signer';
+import {postJson, sleep} from './utils';
+
+/**
+ * Jito's mainnet tip accounts. Any one of them works, picking at random spreads
+ * the write lock contention between bundles.
+ */
+const TIP_ACCOUNTS = [
+  '96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5',
+  'HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe',
+  'Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY',
+  'ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49',
+  'DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh',
+  'ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt',
+  'DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL',
+  '3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT',
+];
+
+/**
+ * Block engines drop bundles tipping less than this
+ */
+export const MIN_TIP_LAMPORTS = 1000;
+
+/**
+ * Block engine URL from JITO_BLOCK_ENGINE_URL, if Jito sending is enabled
+ */
+export function jitoBlockEngineUrl(): string | undefined {
+  return process.env.JITO_BLOCK_ENGINE_URL;
+}
+
+async function rpc(
+  blockEngineUrl: string,
+  method: string,
+  params: any[],
+): Promise<any> {
+  const response = await postJson(`${blockEngineUrl}/api/v1/bundles`, {
+    jsonrpc: '2.0',
+    id: 1,
+    method,
+    params,
+  });
+  if (response.error) {
+    throw new Error(`${method} failed: ${JSON.stringify(response.error)}`);
+  }
+  return response.result;
+}
+
+/**
+ * Send `transaction` as a single-transaction Jito bundle with a tip appended,
+ * and wait until the bundle is confirmed. Returns the bundle id. A failure that is
+ * one of `programId`'s errors throws a HelloWorldTxError (see errors.ts).
+ */
+export async function sendAndConfirmBundle(
+  connection: Connection,
+  transaction: Transaction,
+  signers: Signer[],
+  blockEngineUrl: string,
+  programId?: PublicKey,
+  tipLamports = Number(process.env.JITO_TIP_LAMPORTS || MIN_TIP_LAMPORTS),
+  timeoutMs = 60000,
+): Promise<string> {
+  const tipAccount =
+    TIP_ACCOUNTS[Math.floor(Math.random() * TIP_ACCOUNTS.length)];
+  transaction.add(
+    SystemProgram.transfer({
+      fromPubkey: signers[0].publicKey,
+      toPubkey: new PublicKey(tipAccount),
+      lamports: Math.max(tipLamports, MIN_TIP_LAMPORTS),
+    }),
+  );
+  const {blockhash} = await connection.getRecentBlockhash();
+  transaction.recentBlockhash = blockhash;
+  await signTransaction(transaction, signers);
+
+  const encoded = transaction.serialize().toString('base64');
+  const bundleId: string = await rpc(blockEngineUrl, 'sendBundle', [
+    [encoded],
+    {encoding: 'base64'},
+  ]);
+  console.log('Sent bundle', bundleId, 'tipping', tipAccount);
+
+  // NOTE A bundle that loses the auction just never shows up, so poll until the
+  // blockhash would have expired anyway
+  const deadline = Date.now() + timeoutMs;
+  while (Date.now() < deadline) {
+    await sleep(2000);
+    const statuses = await rpc(blockEngineUrl, 'getBundleStatuses', [
+      [bundleId],
+    ]);
+    const status = statuses && statuses.value && statuses.value[0];
+    if (status && status.err && status.err.Ok === undefined) {
+      throw (
+        helloWorldTxError(status.err, transaction, programId, bundleId) ||
+        new Error(`Bundle ${bundleId} failed: ${JSON.stringify(status.err)}`)
+      );
+    }
+    if (
+      status &&
+      ['confirmed', 'finalized'].includes(status.confirmation_status)
+    ) {
+      return bundleId;
+    }
+  }
+  throw new Error(`Bundle ${bundleId} was not confirmed in ${timeoutMs}ms`);
+}
@@ -0,0 +1,138 @@
+/**
+ * NOTES:
+ * - Measures how the program performs under load so changes to it can be quantified:
+ * end-to-end TPS, confirmation latency percentiles and compute units per transaction.
+ * - Start a fresh validator first (`solana-test-validator --reset`), deploy the program
+ * and point the CLI config at localhost, then:
+ *   npm run loadtest -- [--count 1000] [--accounts 50] [--concurrency 32] [--rate 200]
+ *     [--out loadtest]
+ * This writes <out>.json (summary) and <out>.csv (one row per transaction).
+ * - Sending reuses mass_greet.ts. Compute units come from the "consumed N of M compute
+ * units" log line of each confirmed transaction, fetched after the run so the lookups
+ * don't skew the timing.
+ */
+
+import {Connection} from '@solana/web3.js';
+import fs from 'mz/fs';
+
+import {PROGRAM_KEYPAIR_PATH} from './hello_world';
+import {
+  Result,
+  ensureGreetingAccounts,
+  parseOptions,
+  sendGreets,
+} from './mass_greet';
+import {computeUnits} from './simulation';
+import {getPayer, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * The p-th percentile (0-100) of sorted values
+ */
+export function percentile(sorted: number[], p: number): number {
+  if (sorted.length === 0) {
+    return 0;
+  }
+  const index = Math.ceil((p / 100) * sorted.length) - 1;
+  return sorted[Math.min(sorted.length - 1, Math.max(0, index))];
+}
+
+async function main() {
+  const argv = process.argv.slice(2);
+  const outIndex = argv.indexOf('--out');
+  const out = outIndex >= 0 ? argv.splice(outIndex, 2)[1] : 'loadtest';
+  const options = parseOptions(argv, {
+    count: 1000,
+    accounts: 50,
+    concurrency: 32,
+    rate: 200,
+  });
+
+  const rpcUrl = await getRpcUrl();
+  if (!/localhost|127\.0\.0\.1/.test(rpcUrl)) {
+    console.warn(`Load testing ${rpcUrl}, which is not a local validator`);
+  }
+  const connection = new Connection(rpcUrl, 'confirmed');
+  const payer = await getPayer();
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const accounts = await ensureGreetingAccounts(
+    connection,
+    payer,
+    programId,
+    options.accounts,
+  );
+
+  console.log('Sending', options.count, 'greets');
+  const {results, elapsedMs} = await sendGreets(
+    connection,
+    payer,
+    programId,
+    accounts,
+    options,
+  );
+
+  console.log('Fetching compute units');
+  const rows: (Result & {computeUnits: number | null})[] = [];
+  for (const result of results) {
+    let cu: number | null = null;
+    if (result.signature) {
+      const transaction = await connection.getConfirmedTransaction(
+        result.signature,
+      );
+      const meta = transaction && transaction.meta;
+      cu = computeUnits((meta && meta.logMessages) || [], programId.toBase58());
+    }
+    rows.push({...result, computeUnits: cu});
+  }
+
+  const confirmed = rows.filter(row => row.ok);
+  const latencies = confirmed.map(row => row.latencyMs).sort((a, b) => a - b);
+  const units = confirmed
+    .map(row => row.computeUnits)
+    .filter((cu): cu is number => cu !== null)
+    .sort((a, b) => a - b);
+  const summary = {
+    rpcUrl,
+    options,
+    sent: rows.length,
+    confirmed: confirmed.length,
+    failed: rows.length - confirmed.length,
+    elapsedMs,
+    tps: (confirmed.length * 1000) / elapsedMs,
+    latencyMs: {
+      p50: percentile(latencies, 50),
+      p90: percentile(latencies, 90),
+      p99: percentile(latencies, 99),
+      max: percentile(latencies, 100),
+    },
+    computeUnits: {
+      min: percentile(units, 0),
+      p50: percentile(units, 50),
+      max: percentile(units, 100),
+      mean: units.reduce((sum, cu) => sum + cu, 0) / (units.length || 1),
+    },
+  };
+
+  const csv = ['signature,ok,latency_ms,compute_units,error'];
+  for (const row of rows) {
+    const error = (row.error || '').replace(/"/g, '""');
+    csv.push(
+      `${row.signature || ''},${row.ok},${row.latencyMs},` +
+        `${row.computeUnits === null ? '' : row.computeUnits},"${error}"`,
+    );
+  }
+  await fs.writeFile(`${out}.json`, JSON.stringify(summary, null, 2) + '\n');
+  await fs.writeFile(`${out}.csv`, csv.join('\n') + '\n');
+  console.log(JSON.stringify(summary, null, 2));
+  console.log(`Wrote ${out}.json and ${out}.csv`);
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -10,9 +10,11 @@ import {
   establishConnection,
   establishPayer,
   checkProgram,
+  reportVersion,
   sayHello,
   reportGreetings,
 } from './hello_world';
+import {offlineOptions} from './signer';
 
 async function main() {
   console.log("Let's say hello to a Solana account...");
@@ -26,10 +28,19 @@ async function main() {
   // Check if the program has been deployed
   await checkProgram();
 
+  // Find out which version of the program is deployed
+  await reportVersion();
+
   // Say hello to an account
   // NOTE msg must be same length as account data for borsh!
   await sayHello('Hello1234567');
 
+  // NOTE With --sign-only/--dump-transaction nothing was sent (see offline.ts)
+  const {signOnly, dumpTransaction} = offlineOptions();
+  if (signOnly || dumpTransaction) {
+    return;
+  }
+
   // Find out how many times that account has been greeted
   await reportGreetings();
 
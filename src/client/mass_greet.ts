@@ -0,0 +1,246 @@
+/* eslint-disable @typescript-eslint/no-unsafe-assignment */
+/* eslint-disable @typescript-eslint/no-unsafe-member-access */
+
+/**
+ * NOTES:
+ * - Sends a batch of greet transactions across many greeting accounts, to load-test
+ * the program on localnet (or to see how a cluster behaves under a burst).
+ * - Usage:
+ *   npm run mass-greet -- [--count 100] [--accounts 10] [--concurrency 8] [--rate 20]
+ * - The greeting accounts are derived from the payer with seeds hello-0, hello-1, ...
+ * and created on the first run. They're the same size as the main client's account,
+ * so every message is at most 12 characters. Each message includes its index, which
+ * keeps two greets to the same account from being identical (duplicate) transactions.
+ * - `--rate` caps how many transactions start per second across all workers, and
+ * `--concurrency` how many are in flight at once. A background task refreshes the
+ * blockhash so long runs don't hit "Blockhash not found".
+ */
+
+import {
+  Account,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from '@solana/web3.js';
+
+import {helloWorldTxError} from './errors';
+import {
+  GREETING_SIZE,
+  PROGRAM_KEYPAIR_PATH,
+  configAddress,
+  feeAccounts,
+  greetInstruction,
+  greetingLogAccounts,
+} from './hello_world';
+import {getPayer, getRpcUrl, readAccountFromFile, sleep} from './utils';
+
+export interface Options {
+  count: number;
+  accounts: number;
+  concurrency: number;
+  rate: number;
+}
+
+export function parseOptions(
+  argv: string[],
+  options: Options = {count: 100, accounts: 10, concurrency: 8, rate: 20},
+): Options {
+  for (let i = 0; i < argv.length; i += 2) {
+    const name = argv[i].replace(/^--/, '');
+    const value = Number(argv[i + 1]);
+    if (!(name in options) || !(value > 0)) {
+      throw new Error(`Invalid option ${argv[i]} ${argv[i + 1]}`);
+    }
+    options[name as keyof Options] = value;
+  }
+  return options;
+}
+
+/**
+ * Derive the greeting accounts and create the ones that don't exist yet
+ */
+export async function ensureGreetingAccounts(
+  connection: Connection,
+  payer: Account,
+  programId: PublicKey,
+  count: number,
+): Promise<PublicKey[]> {
+  const seeds = Array.from({length: count}, (_, i) => `hello-${i}`);
+  const pubkeys = await Promise.all(
+    seeds.map(seed =>
+      PublicKey.createWithSeed(payer.publicKey, seed, programId),
+    ),
+  );
+  const lamports = await connection.getMinimumBalanceForRentExemption(
+    GREETING_SIZE,
+  );
+  const missing: number[] = [];
+  for (let i = 0; i < count; i++) {
+    if ((await connection.getAccountInfo(pubkeys[i])) === null) {
+      missing.push(i);
+    }
+  }
+  // NOTE A handful of createAccountWithSeed instructions fit in one transaction
+  for (let start = 0; start < missing.length; start += 5) {
+    const transaction = new Transaction();
+    for (const i of missing.slice(start, start + 5)) {
+      transaction.add(
+        SystemProgram.createAccountWithSeed({
+          fromPubkey: payer.publicKey,
+          basePubkey: payer.publicKey,
+          seed: seeds[i],
+          newAccountPubkey: pubkeys[i],
+          lamports,
+          space: GREETING_SIZE,
+          programId,
+        }),
+      );
+    }
+    const signature = await connection.sendTransaction(transaction, [payer]);
+    await connection.confirmTransaction(signature);
+  }
+  console.log(
+    `Using ${count} greeting accounts (${missing.length} newly created)`,
+  );
+  return pubkeys;
+}
+
+export interface Result {
+  ok: boolean;
+  latencyMs: number;
+  signature?: string;
+  error?: string;
+}
+
+function report(results: Result[], elapsedMs: number): void {
+  const confirmed = results.filter(result => result.ok);
+  const errors = new Map<string, number>();
+  for (const result of results) {
+    if (result.error) {
+      errors.set(result.error, (errors.get(result.error) || 0) + 1);
+    }
+  }
+  const latencies = confirmed
+    .map(result => result.latencyMs)
+    .sort((a, b) => a - b);
+  const median = latencies[Math.floor(latencies.length / 2)] || 0;
+  console.log('Sent', results.length, 'greets in', elapsedMs / 1000, 's');
+  console.log('  confirmed:', confirmed.length);
+  console.log('  failed:   ', results.length - confirmed.length);
+  console.log('  confirmed per second:', (confirmed.length * 1000) / elapsedMs);
+  console.log('  median confirmation latency:', median, 'ms');
+  errors.forEach((count, error) => console.log(`  ${count}x ${error}`));
+}
+
+/**
+ * Send `options.count` greets spread over `accounts`, returning one result per greet
+ * and the total run time
+ */
+export async function sendGreets(
+  connection: Connection,
+  payer: Account,
+  programId: PublicKey,
+  accounts: PublicKey[],
+  options: Options,
+): Promise<{results: Result[]; elapsedMs: number}> {
+  const configPubkey = await configAddress(programId);
+  const fee = await feeAccounts(connection, programId, payer.publicKey);
+  const log = await greetingLogAccounts(connection, programId);
+  // Keep a fresh blockhash around, they expire after ~150 slots (about a minute)
+  let {blockhash} = await connection.getRecentBlockhash();
+  let running = true;
+  const refreshBlockhash = async () => {
+    while (running) {
+      await sleep(10000);
+      ({blockhash} = await connection.getRecentBlockhash());
+    }
+  };
+  refreshBlockhash().catch(err =>
+    console.warn('Blockhash refresh failed', err),
+  );
+
+  // Rate limit: each send reserves the next free start time
+  let nextStart = Date.now();
+  const interval = 1000 / options.rate;
+  let next = 0;
+  const results: Result[] = [];
+  const started = Date.now();
+
+  const worker = async () => {
+    while (next < options.count) {
+      const i = next++;
+      const now = Date.now();
+      const start = Math.max(now, nextStart);
+      nextStart = start + interval;
+      await sleep(start - now);
+
+      const transaction = new Transaction().add(
+        greetInstruction(
+          programId,
+          accounts[i % accounts.length],
+          configPubkey,
+          `mass-${i}`.slice(0, 12),
+          fee,
+          log,
+        ),
+      );
+      // NOTE Sign ourselves rather than with sendTransaction(), which would
+      // fetch a blockhash of its own for every transaction
+      transaction.recentBlockhash = blockhash;
+      transaction.sign(payer);
+      const sent = Date.now();
+      try {
+        const signature = await connection.sendRawTransaction(
+          transaction.serialize(),
+        );
+        const {value} = await connection.confirmTransaction(signature);
+        if (value.err) {
+          throw (
+            helloWorldTxError(value.err, transaction, programId, signature) ||
+            new Error(JSON.stringify(value.err))
+          );
+        }
+        results.push({ok: true, latencyMs: Date.now() - sent, signature});
+      } catch (err) {
+        const error = (err as Error).message || String(err);
+        results.push({ok: false, latencyMs: Date.now() - sent, error});
+      }
+    }
+  };
+  await Promise.all(Array.from({length: options.concurrency}, worker));
+  running = false;
+  return {results, elapsedMs: Date.now() - started};
+}
+
+async function main() {
+  const options = parseOptions(process.argv.slice(2));
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const payer = await getPayer();
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const accounts = await ensureGreetingAccounts(
+    connection,
+    payer,
+    programId,
+    options.accounts,
+  );
+  const {results, elapsedMs} = await sendGreets(
+    connection,
+    payer,
+    programId,
+    accounts,
+    options,
+  );
+  report(results, elapsedMs);
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
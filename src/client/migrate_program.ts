@@ -0,0 +1,53 @@
+/**
+ * NOTES:
+ * - Moves the payer's greeting to a redeployment of the program under a new program
+ * id, so nothing is lost when the program can't be upgraded in place:
+ *   npm run migrate-program -- <OLD_PROGRAM_ID> [--skip-export]
+ * - The new program is the one the client uses (profile, deployments.json or the
+ * program keypair). Its greeting account is created if needed, then one transaction
+ * has the old program log the state (ExportState, a permanent record of what moved)
+ * and the new one copy it over (ImportState).
+ * - Deployments older than ExportState reject it, pass --skip-export for those.
+ * - Greeting accounts derive from the payer, so each payer migrates its own.
+ * - The import carries the counter over, so the payer has to be the new program's
+ * config authority.
+ */
+
+import {PublicKey} from '@solana/web3.js';
+
+import {
+  checkProgram,
+  establishConnection,
+  establishPayer,
+  importGreeting,
+  reportGreetings,
+} from './hello_world';
+
+async function main() {
+  const args = process.argv.slice(2);
+  const skipExport = args.indexOf('--skip-export') >= 0;
+  const oldProgramId = args.filter(arg => !arg.startsWith('--'))[0];
+  if (!oldProgramId) {
+    throw new Error('Usage: migrate-program <OLD_PROGRAM_ID> [--skip-export]');
+  }
+
+  await establishConnection();
+  await establishPayer();
+  await checkProgram();
+  const signature = await importGreeting(
+    new PublicKey(oldProgramId),
+    !skipExport,
+  );
+  console.log('Migrated in', signature);
+  await reportGreetings();
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
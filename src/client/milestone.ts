@@ -0,0 +1,174 @@
+/**
+ * NOTES:
+ * - Snapshot records a greeting account's counter and the time under a label, in a
+ * PDA of the recorder's, so milestones stay readable after the counter moves on (see
+ * src/program-rust/src/milestone.rs).
+ * - Usage:
+ *   npm run milestone -- record <GREETING_ACCOUNT> <LABEL>   snapshot the counter
+ *   npm run milestone -- show <GREETING_ACCOUNT> <LABEL>     print the payer's snapshot
+ *   npm run milestone -- list <GREETING_ACCOUNT>             print every snapshot of it
+ * - The payer is the recorder and pays the rent. A label is taken once per recorder
+ * and greeting account, record under a new one for the next milestone.
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+
+import {instruction} from './crank';
+import {
+  GreetingSnapshot,
+  HelloWorldInstruction,
+  MAX_LABEL_LEN,
+  SNAPSHOT_DISCRIMINATOR,
+  SNAPSHOT_SEED,
+  SnapshotArgs,
+} from './generated';
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {sendAndConfirm} from './signer';
+import {decodeSnapshot} from './state';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Address of `recorder`'s snapshot of `greetedPubkey` labeled `label`
+ */
+export async function snapshotAddress(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  recorder: PublicKey,
+  label: string,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [
+      Buffer.from(SNAPSHOT_SEED),
+      greetedPubkey.toBuffer(),
+      recorder.toBuffer(),
+      Buffer.from(label),
+    ],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Build a Snapshot instruction recording `greetedPubkey`'s counter under `label`, at
+ * `recorder`'s expense
+ */
+export async function snapshotInstruction(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  recorder: PublicKey,
+  label: string,
+): Promise<TransactionInstruction> {
+  const length = Buffer.from(label).length;
+  if (length === 0 || length > MAX_LABEL_LEN) {
+    throw new Error(`A label is 1 to ${MAX_LABEL_LEN} bytes, not ${length}`);
+  }
+  const configPubkey = await configAddress(programId);
+  const snapshot = await snapshotAddress(
+    programId,
+    greetedPubkey,
+    recorder,
+    label,
+  );
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: greetedPubkey, isSigner: false, isWritable: false},
+      {pubkey: recorder, isSigner: true, isWritable: true},
+      {pubkey: snapshot, isSigner: false, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.Snapshot,
+    new SnapshotArgs({label}),
+  );
+}
+
+/**
+ * Every snapshot of `greetedPubkey`, by any recorder
+ */
+export async function listSnapshots(
+  connection: Connection,
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+): Promise<GreetingSnapshot[]> {
+  const accounts = await connection.getProgramAccounts(programId);
+  const prefix = Buffer.concat([SNAPSHOT_DISCRIMINATOR, greetedPubkey.toBuffer()]);
+  return accounts
+    .filter(({account}) => account.data.slice(0, prefix.length).equals(prefix))
+    .map(({account}) => decodeSnapshot(account.data));
+}
+
+function print(snapshot: GreetingSnapshot) {
+  const time = new Date(snapshot.unix_timestamp.toNumber() * 1000);
+  console.log(`${snapshot.label}: ${snapshot.counter.toString()}`);
+  console.log(`  recorder: ${new PublicKey(snapshot.recorder).toBase58()}`);
+  console.log(`  slot: ${snapshot.slot.toString()}`);
+  console.log(`  time: ${time.toISOString()}`);
+}
+
+async function main() {
+  const [command, account, label] = process.argv.slice(2);
+  if (
+    ['record', 'show', 'list'].indexOf(command) < 0 ||
+    !account ||
+    (command !== 'list' && !label)
+  ) {
+    throw new Error(
+      'Usage: milestone record <GREETING_ACCOUNT> <LABEL>\n' +
+        '       milestone show <GREETING_ACCOUNT> <LABEL>\n' +
+        '       milestone list <GREETING_ACCOUNT>',
+    );
+  }
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const greetedPubkey = new PublicKey(account);
+  if (command === 'list') {
+    (await listSnapshots(connection, programId, greetedPubkey)).forEach(print);
+    return;
+  }
+
+  const payer = await getPayerSigner();
+  if (command === 'record') {
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await snapshotInstruction(
+          programId,
+          greetedPubkey,
+          payer.publicKey,
+          label,
+        ),
+      ),
+      [payer],
+      programId,
+    );
+  }
+  const address = await snapshotAddress(
+    programId,
+    greetedPubkey,
+    payer.publicKey,
+    label,
+  );
+  const info = await connection.getAccountInfo(address);
+  if (info === null || !info.owner.equals(programId)) {
+    throw new Error(`No snapshot labeled ${label} at ${address.toBase58()}`);
+  }
+  print(decodeSnapshot(info.data));
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
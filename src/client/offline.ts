@@ -0,0 +1,128 @@
+/**
+ * NOTES:
+ * - Lets an authority key stay on an air-gapped machine. Any client command that sends
+ * transactions (`npm start`, `npm run squads -- propose`) takes the solana CLI's
+ * offline flags:
+ *   --dump-transaction   print the unsigned transaction instead of sending it
+ *   --sign-only          sign with the local signers and print it instead of sending
+ *   --blockhash <HASH>   use this blockhash instead of fetching one
+ * - The printed transaction (base64) is then carried across, signed and broadcast:
+ *   npm start -- --dump-transaction                      (online)
+ *   npm run offline -- sign <TX> [--signer <SOURCE>]     (offline, no RPC needed)
+ *   npm run offline -- submit <TX> [--program-id <ID>]   (online)
+ * <TX> is the base64 transaction or a file holding it. `sign` adds the signature of
+ * the payer, or of --signer (any signer source, including usb://ledger).
+ * - A transaction is only valid for ~150 slots (about a minute) after its blockhash,
+ * so the round trip has to be quick. Durable nonces would lift that limit; with
+ * `npm start -- --max-age-slots <N>` the Greet also checks a slot hash at most N
+ * slots old (see recency.ts), so it expires whatever its blockhash.
+ * - `npm start` stops after the greet in these modes: the greeting account is only
+ * updated once the transactions are submitted. On a first run the account creation is
+ * printed too, submit it before the greet.
+ */
+
+import {Connection, PublicKey, Transaction} from '@solana/web3.js';
+import fs from 'mz/fs';
+
+import {getProfile} from './config';
+import {helloWorldTxError} from './errors';
+import {LedgerSigner, encodeTransaction, resolveAnySigner} from './signer';
+import {getPayerSigner, getRpcUrl} from './utils';
+
+/**
+ * Read a transaction given as base64 or as a file holding the base64
+ */
+async function readTransaction(arg: string): Promise<Transaction> {
+  const text = fs.existsSync(arg)
+    ? await fs.readFile(arg, {encoding: 'utf8'})
+    : arg;
+  return Transaction.from(Buffer.from(text.trim(), 'base64'));
+}
+
+/**
+ * Public keys whose signature the transaction still needs
+ */
+export function missingSigners(transaction: Transaction): string[] {
+  return transaction.signatures
+    .filter(({signature}) => signature === null)
+    .map(({publicKey}) => publicKey.toBase58());
+}
+
+/**
+ * Broadcast the fully signed `transaction` and wait until it's confirmed, naming
+ * `programId`'s errors with a HelloWorldTxError (see errors.ts)
+ */
+export async function submit(
+  connection: Connection,
+  transaction: Transaction,
+  programId?: PublicKey,
+): Promise<string> {
+  const signature = await connection.sendRawTransaction(
+    transaction.serialize(),
+  );
+  const {value} = await connection.confirmTransaction(signature);
+  if (value.err) {
+    throw (
+      helloWorldTxError(value.err, transaction, programId, signature) ||
+      new Error(`Transaction ${signature} failed: ${JSON.stringify(value.err)}`)
+    );
+  }
+  return signature;
+}
+
+async function main() {
+  const [command, txArg, ...rest] = process.argv.slice(2);
+  if (!txArg || (command !== 'sign' && command !== 'submit')) {
+    throw new Error(
+      'Usage: offline sign <TX> [--signer <SOURCE>]\n' +
+        '       offline submit <TX> [--program-id <PROGRAM_ID>]',
+    );
+  }
+  const transaction = await readTransaction(txArg);
+
+  if (command === 'sign') {
+    const signerIndex = rest.indexOf('--signer');
+    const signer =
+      signerIndex >= 0
+        ? await resolveAnySigner(rest[signerIndex + 1])
+        : await getPayerSigner();
+    // NOTE signTransaction() would make the signer the fee payer, keep the one the
+    // transaction was built with
+    if (signer instanceof LedgerSigner) {
+      await signer.sign(transaction);
+    } else {
+      transaction.partialSign(signer);
+    }
+    console.log('Signed by', signer.publicKey.toBase58());
+    const missing = missingSigners(transaction);
+    if (missing.length > 0) {
+      console.log('Still needs signatures from', missing.join(', '));
+    }
+    console.log(encodeTransaction(transaction));
+    return;
+  }
+
+  const missing = missingSigners(transaction);
+  if (missing.length > 0) {
+    throw new Error(`Missing signatures from ${missing.join(', ')}`);
+  }
+  // NOTE Name helloworld's errors for the program in --program-id or the profile
+  const programIdIndex = rest.indexOf('--program-id');
+  const programIdArg =
+    programIdIndex >= 0
+      ? rest[programIdIndex + 1]
+      : (await getProfile()).programId;
+  const programId = programIdArg ? new PublicKey(programIdArg) : undefined;
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  console.log('Confirmed', await submit(connection, transaction, programId));
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,82 @@
+/**
+ * NOTES:
+ * - Everything between "the program is deployed" and "I see my greeting" in one
+ * command, for devnet (or localnet):
+ *   npm run quickstart -- [message]
+ * It funds the payer from the faucet (backing off while rate limited), creates the
+ * greeting account, sends one greet, reads the count back and prints explorer links.
+ * - The payer is the usual one (profile, CLI config or PAYER_KEYPAIR). Without any
+ * keypair a throwaway one is generated and funded, which is fine for a first look.
+ * - The greeting account holds exactly 12 characters, so the message must be 12
+ * characters long.
+ */
+
+import {PublicKey} from '@solana/web3.js';
+
+import {
+  checkProgram,
+  clientAccounts,
+  establishConnection,
+  establishPayer,
+  reportGreetings,
+  sayHello,
+} from './hello_world';
+
+const MESSAGE_LENGTH = 12;
+
+/**
+ * Solana Explorer link for an address or transaction on the cluster at `rpcUrl`
+ */
+export function explorerUrl(
+  kind: 'address' | 'tx',
+  value: string,
+  rpcUrl: string,
+): string {
+  const base = `https://explorer.solana.com/${kind}/${value}`;
+  if (rpcUrl.indexOf('mainnet') >= 0) {
+    return base;
+  }
+  for (const cluster of ['devnet', 'testnet']) {
+    if (rpcUrl.indexOf(cluster) >= 0) {
+      return `${base}?cluster=${cluster}`;
+    }
+  }
+  return `${base}?cluster=custom&customUrl=${encodeURIComponent(rpcUrl)}`;
+}
+
+async function main() {
+  const message = process.argv[2] || 'Hello1234567';
+  if (message.length !== MESSAGE_LENGTH) {
+    throw new Error(`The message must be ${MESSAGE_LENGTH} characters long`);
+  }
+
+  await establishConnection();
+  if (clientAccounts().rpcUrl.indexOf('mainnet') >= 0) {
+    throw new Error('quickstart relies on airdrops, use devnet or localnet');
+  }
+  await establishPayer();
+  await checkProgram();
+  const signature = await sayHello(message);
+  await reportGreetings();
+
+  const {rpcUrl, payer, programId, greeted} = clientAccounts();
+  const address = (pubkey: PublicKey) =>
+    explorerUrl('address', pubkey.toBase58(), rpcUrl);
+  console.log('');
+  console.log('Payer:           ', address(payer));
+  console.log('Program:         ', address(programId));
+  console.log('Greeting account:', address(greeted));
+  if (signature) {
+    console.log('Greet:           ', explorerUrl('tx', signature, rpcUrl));
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
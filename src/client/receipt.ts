@@ -0,0 +1,52 @@
+/**
+ * NOTES:
+ * - With the config's greeting_receipts set, every Greet leaves a receipt PDA behind
+ * recording who signed for it and when (see src/program-rust/src/receipt.rs).
+ * - Usage:
+ *   npm run receipt -- <GREETING_ACCOUNT> <COUNTER>   print a greeting's receipt
+ * - COUNTER is the greeting account's counter before the greeting, so the first
+ * greeting's receipt is at 0. Receipts are turned on by a SetConfig setting
+ * greeting_receipts, e.g. `npm run governance -- propose-set-config <PROPOSAL>
+ * greeting_receipts=true`.
+ */
+
+import {Connection, PublicKey} from '@solana/web3.js';
+import BN from 'bn.js';
+
+import {PROGRAM_KEYPAIR_PATH, receiptAddress} from './hello_world';
+import {decodeReceipt} from './state';
+import {getRpcUrl, readAccountFromFile} from './utils';
+
+async function main() {
+  const [account, counter] = process.argv.slice(2);
+  if (!account || !/^\d+$/.test(counter || '')) {
+    throw new Error('Usage: receipt <GREETING_ACCOUNT> <COUNTER>');
+  }
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const address = await receiptAddress(
+    programId,
+    new PublicKey(account),
+    new BN(counter),
+  );
+  const info = await connection.getAccountInfo(address);
+  if (info === null || !info.owner.equals(programId)) {
+    throw new Error(`No receipt of greeting ${counter} at ${address.toBase58()}`);
+  }
+  const receipt = decodeReceipt(info.data);
+  console.log(`Receipt ${address.toBase58()}`);
+  console.log(`  signer: ${new PublicKey(receipt.signer).toBase58()}`);
+  console.log(`  slot: ${receipt.slot.toString()}`);
+  console.log(
+    `  time: ${new Date(receipt.unix_timestamp.toNumber() * 1000).toISOString()}`,
+  );
+}
+
+main().then(
+  () => process.exit(),
+  err => {
+    console.error(err);
+    process.exit(-1);
+  },
+);
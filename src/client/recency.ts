@@ -0,0 +1,97 @@
+/**
+ * NOTES:
+ * - CheckRecency fails a transaction unless it names a slot hash from the SlotHashes
+ * sysvar no more than some slots old, so a pre-signed Greet can't be sent once it's
+ * stale (see src/program-rust/src/recency.rs).
+ * - Usage:
+ *   npm run recency                                   print the newest slot hash
+ *   npm start -- --sign-only --max-age-slots <N>      pre-sign a Greet valid N slots
+ * - The hashes are the bank hashes kept in the sysvar, not blockhashes: read them from
+ * the sysvar account, as recentSlotHash() does.
+ */
+
+import {Connection, PublicKey, TransactionInstruction} from '@solana/web3.js';
+import BN from 'bn.js';
+import * as borsh from 'borsh';
+
+import {
+  CheckRecencyArgs,
+  HelloWorldInstruction,
+  HelloWorldSchema,
+} from './generated';
+import {getRpcUrl} from './utils';
+
+/**
+ * Address of the SlotHashes sysvar
+ */
+export const SYSVAR_SLOT_HASHES_PUBKEY = new PublicKey(
+  'SysvarS1otHashes111111111111111111111111111',
+);
+
+/**
+ * A slot and its hash, as the SlotHashes sysvar keeps them
+ */
+export interface SlotHash {
+  slot: BN;
+  hash: Buffer;
+}
+
+/**
+ * The newest slot hash in the SlotHashes sysvar
+ */
+export async function recentSlotHash(connection: Connection): Promise<SlotHash> {
+  const info = await connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+  // NOTE A bincode Vec<(Slot, Hash)>: a u64 length, then the entries newest first
+  if (info === null || info.data.length < 8 + 40) {
+    throw new Error('The SlotHashes sysvar holds no slot hash');
+  }
+  return {
+    slot: new BN(info.data.slice(8, 16), 'le'),
+    hash: info.data.slice(16, 48),
+  };
+}
+
+/**
+ * Build a CheckRecency instruction failing unless `slotHash` is at most `maxAgeSlots`
+ * old when the transaction lands
+ */
+export function checkRecencyInstruction(
+  programId: PublicKey,
+  slotHash: SlotHash,
+  maxAgeSlots: number,
+): TransactionInstruction {
+  // NOTE Built here rather than with crank.ts's instruction(), which hello_world.ts
+  // can't import without a cycle
+  const args = new CheckRecencyArgs({
+    slot: slotHash.slot,
+    slot_hash: slotHash.hash,
+    max_age_slots: new BN(maxAgeSlots),
+  });
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false},
+    ],
+    programId,
+    data: Buffer.concat([
+      Buffer.from([HelloWorldInstruction.CheckRecency]),
+      Buffer.from(borsh.serialize(HelloWorldSchema, args)),
+    ]),
+  });
+}
+
+async function main() {
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const {slot, hash} = await recentSlotHash(connection);
+  console.log(`slot: ${slot.toString()}`);
+  console.log(`hash: ${hash.toString('hex')}`);
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,115 @@
+/* eslint-disable @typescript-eslint/no-unsafe-assignment */
+/* eslint-disable @typescript-eslint/no-unsafe-member-access */
+
+/**
+ * NOTES:
+ * - Public RPC endpoints rate limit, fall behind and go down, so a script talking to a
+ * single url breaks regularly. RpcPool takes several urls (RPC_URLS=url1,url2,...),
+ * picks the best one and moves to the next when a request fails.
+ * - An endpoint is usable when `getHealth` says "ok" and its slot is within
+ * MAX_SLOT_LAG of the most advanced endpoint. getHealth alone isn't enough: a node
+ * reports healthy relative to its own known validators, while being far behind the
+ * cluster. Of the usable endpoints the fastest to answer wins.
+ * - run() retries the operation on the next endpoint when it throws. Only wrap
+ * operations that are safe to repeat: re-sending a greet just writes the same message.
+ */
+
+import {Commitment, Connection} from '@solana/web3.js';
+
+import {postJson} from './utils';
+
+/**
+ * How many slots an endpoint may trail the most advanced one before it's skipped
+ */
+export const MAX_SLOT_LAG = 50;
+
+interface EndpointStatus {
+  url: string;
+  healthy: boolean;
+  slot: number;
+  latencyMs: number;
+}
+
+async function checkEndpoint(url: string): Promise<EndpointStatus> {
+  const start = Date.now();
+  try {
+    const [health, slot] = await Promise.all([
+      postJson(url, {jsonrpc: '2.0', id: 1, method: 'getHealth'}),
+      postJson(url, {jsonrpc: '2.0', id: 2, method: 'getSlot'}),
+    ]);
+    return {
+      url,
+      healthy: health.result === 'ok' && typeof slot.result === 'number',
+      slot: Number(slot.result) || 0,
+      latencyMs: Date.now() - start,
+    };
+  } catch (err) {
+    return {url, healthy: false, slot: 0, latencyMs: Infinity};
+  }
+}
+
+/**
+ * Usable endpoints (healthy and caught up), fastest first
+ */
+export async function rankEndpoints(
+  urls: string[],
+  maxSlotLag = MAX_SLOT_LAG,
+): Promise<string[]> {
+  const statuses = await Promise.all(urls.map(checkEndpoint));
+  const healthy = statuses.filter(status => status.healthy);
+  const tip = Math.max(0, ...healthy.map(status => status.slot));
+  return healthy
+    .filter(status => tip - status.slot <= maxSlotLag)
+    .sort((a, b) => a.latencyMs - b.latencyMs)
+    .map(status => status.url);
+}
+
+export class RpcPool {
+  url = '';
+  connection: Connection | undefined;
+
+  constructor(
+    readonly urls: string[],
+    readonly commitment: Commitment = 'confirmed',
+  ) {}
+
+  /**
+   * Switch to the best endpoint not in `exclude`
+   */
+  async select(exclude: Set<string> = new Set()): Promise<Connection> {
+    const candidates = this.urls.filter(url => !exclude.has(url));
+    const [best] = await rankEndpoints(candidates);
+    if (!best) {
+      throw new Error(
+        `No healthy RPC endpoint among ${candidates.join(', ') || 'none'}`,
+      );
+    }
+    this.url = best;
+    this.connection = new Connection(best, this.commitment);
+    return this.connection;
+  }
+
+  /**
+   * Run `operation`, failing over to the next best endpoint each time it throws
+   */
+  async run<T>(operation: (connection: Connection) => Promise<T>): Promise<T> {
+    const failed = new Set<string>();
+    let connection = this.connection || (await this.select());
+    for (;;) {
+      try {
+        return await operation(connection);
+      } catch (err) {
+        failed.add(this.url);
+        if (failed.size === this.urls.length) {
+          throw err;
+        }
+        const previous = this.url;
+        connection = await this.select(failed).catch(() => {
+          throw err;
+        });
+        const message = (err as Error).message;
+        console.warn(`RPC ${previous} failed (${message}), using ${this.url}`);
+      }
+    }
+  }
+}
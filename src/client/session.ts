@@ -0,0 +1,216 @@
+/**
+ * NOTES:
+ * - Session keys: approve a temporary key once and it greets for you until the
+ * session runs out, without the wallet signing again (see
+ * src/program-rust/src/session.rs).
+ * - Usage:
+ *   npm run session -- start [MINUTES]   (signed by the payer, 60 minutes by default)
+ *   npm run session -- greet <MESSAGE>   (signed by the session key alone)
+ *   npm run session -- revoke            (signed by the payer)
+ * - start generates the session key into dist/session-keypair.json and tops it up
+ * with 0.01 SOL for its transaction fees. greet pays from it and, when the payer has
+ * a stake position, weights the greeting by the payer's stake through the session
+ * token.
+ */
+
+import {
+  Account,
+  Connection,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import BN from 'bn.js';
+import fs from 'mz/fs';
+import path from 'path';
+
+import {instruction} from './crank';
+import {
+  GREETING_SEED,
+  PROGRAM_KEYPAIR_PATH,
+  configAddress,
+  feeAccounts,
+  greetInstruction,
+  greetingLogAccounts,
+  sessionAddress,
+  stakePositionAddress,
+} from './hello_world';
+import {CreateSessionArgs, HelloWorldInstruction} from './generated';
+import {sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Where the session key is kept between commands
+ */
+const SESSION_KEYPAIR_PATH = path.resolve(
+  __dirname,
+  '../../dist/session-keypair.json',
+);
+
+/**
+ * Lamports the session key is topped up with for its transaction fees
+ */
+const TOP_UP_LAMPORTS = LAMPORTS_PER_SOL / 100;
+
+/**
+ * Build a CreateSession instruction letting `sessionKey` greet for `authority`
+ * for `durationSeconds`
+ */
+export async function createSessionInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  sessionKey: PublicKey,
+  durationSeconds: number,
+  topUpLamports: number,
+): Promise<TransactionInstruction> {
+  return instruction(
+    programId,
+    [
+      {
+        pubkey: await sessionAddress(programId, authority, sessionKey),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: authority, isSigner: true, isWritable: true},
+      {pubkey: sessionKey, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.CreateSession,
+    new CreateSessionArgs({
+      duration_seconds: new BN(durationSeconds),
+      top_up_lamports: new BN(topUpLamports),
+    }),
+  );
+}
+
+/**
+ * Build a RevokeSession instruction ending `authority`'s session for `sessionKey`
+ */
+export async function revokeSessionInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  sessionKey: PublicKey,
+): Promise<TransactionInstruction> {
+  return instruction(
+    programId,
+    [
+      {
+        pubkey: await sessionAddress(programId, authority, sessionKey),
+        isSigner: false,
+        isWritable: true,
+      },
+      {pubkey: authority, isSigner: true, isWritable: true},
+    ],
+    HelloWorldInstruction.RevokeSession,
+  );
+}
+
+async function main() {
+  const [command, arg] = process.argv.slice(2);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programId = (await readAccountFromFile(PROGRAM_KEYPAIR_PATH))
+    .publicKey;
+  const payer = await getPayerSigner();
+
+  if (command === 'start') {
+    const minutes = Number(arg || 60);
+    const sessionKey = new Account();
+    await fs.writeFile(
+      SESSION_KEYPAIR_PATH,
+      JSON.stringify(Array.from(sessionKey.secretKey)),
+    );
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await createSessionInstruction(
+          programId,
+          payer.publicKey,
+          sessionKey.publicKey,
+          minutes * 60,
+          TOP_UP_LAMPORTS,
+        ),
+      ),
+      [payer, sessionKey],
+      programId,
+    );
+    console.log(
+      `${sessionKey.publicKey.toBase58()} greets for you for`,
+      minutes,
+      'minute(s)',
+    );
+    return;
+  }
+
+  const sessionKey = await readAccountFromFile(SESSION_KEYPAIR_PATH);
+  if (command === 'revoke') {
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await revokeSessionInstruction(
+          programId,
+          payer.publicKey,
+          sessionKey.publicKey,
+        ),
+      ),
+      [payer],
+      programId,
+    );
+    console.log('Revoked the session of', sessionKey.publicKey.toBase58());
+    return;
+  }
+  if (command !== 'greet' || !arg) {
+    throw new Error(
+      'Usage: session start [MINUTES] | greet <MESSAGE> | revoke',
+    );
+  }
+
+  // NOTE Only the payer's key is needed here, not its signature
+  const greeted = await PublicKey.createWithSeed(
+    payer.publicKey,
+    GREETING_SEED,
+    programId,
+  );
+  const position = await stakePositionAddress(programId, payer.publicKey);
+  const staked = await connection.getAccountInfo(position);
+  const stake =
+    staked && staked.owner.equals(programId)
+      ? {
+          position,
+          signer: sessionKey.publicKey,
+          sessionToken: await sessionAddress(
+            programId,
+            payer.publicKey,
+            sessionKey.publicKey,
+          ),
+        }
+      : undefined;
+  await sendAndConfirm(
+    connection,
+    new Transaction().add(
+      greetInstruction(
+        programId,
+        greeted,
+        await configAddress(programId),
+        arg,
+        await feeAccounts(connection, programId, sessionKey.publicKey),
+        await greetingLogAccounts(connection, programId),
+        stake,
+      ),
+    ),
+    [sessionKey],
+    programId,
+  );
+  console.log(`Greeted ${greeted.toBase58()} with the session key`);
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
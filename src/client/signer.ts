@@ -0,0 +1,404 @@
+/**
+ * NOTES:
+ * - Resolves a keypair from the same kinds of "signer source" the solana CLI accepts,
+ * so the keypair_path in ~/.config/solana/cli/config.yml (or PAYER_KEYPAIR) can be any of:
+ *   /path/to/keypair.json, file:///path/to/keypair.json  JSON keyfile (solana-keygen)
+ *   prompt://, prompt://?key=0/0, prompt://?full-path=m/44/501/0/0
+ *                                   seed phrase (and passphrase) typed at a prompt
+ *   ASK                             legacy spelling of prompt://
+ *   stdin                           JSON keyfile contents piped on stdin
+ *   env://NAME                      JSON keyfile contents in environment variable NAME
+ *   usb://ledger, usb://ledger?key=0/0
+ *                                   Ledger hardware wallet (resolveAnySigner() only)
+ * - Seed phrases follow BIP39 (PBKDF2-SHA512 over the NFKD-normalized phrase, salt
+ * "mnemonic" + passphrase). With ?key=A[/C] the key is derived along m/44'/501'/A'[/C']
+ * using SLIP-0010 (all levels hardened, like the CLI and wallets). Without a derivation
+ * path the first 32 bytes of the seed are used, which is what `solana-keygen new` does.
+ * - The phrase's checksum isn't verified (that needs the BIP39 word list): a typo gives
+ * a different, valid key. Check the printed public key before funding it.
+ * - A Ledger never hands out its key, so it can't be an Account. LedgerSigner signs
+ * through the ledger-sign binary (src/ledger, `npm run build:ledger`), and
+ * sendAndConfirm() takes Accounts and LedgerSigners alike.
+ */
+
+import {
+  Account,
+  Connection,
+  PublicKey,
+  Transaction,
+} from '@solana/web3.js';
+import {spawn} from 'child_process';
+import {
+  createHmac,
+  createPrivateKey,
+  createPublicKey,
+  pbkdf2Sync,
+} from 'crypto';
+import fs from 'mz/fs';
+import path from 'path';
+import readline from 'readline';
+import {Writable} from 'stream';
+
+import {HelloWorldTxError, helloWorldTxError} from './errors';
+import {SimulationError, simulate} from './simulation';
+
+/**
+ * DER prefix of a PKCS#8 Ed25519 private key, followed by the 32 byte seed
+ */
+const PKCS8_ED25519_PREFIX = Buffer.from(
+  '302e020100300506032b657004220420',
+  'hex',
+);
+
+const HARDENED = 0x80000000;
+
+/**
+ * The ledger-sign binary, overridable with LEDGER_SIGN
+ */
+const LEDGER_SIGN =
+  process.env.LEDGER_SIGN ||
+  path.resolve(__dirname, '../ledger/target/release/ledger-sign');
+
+/**
+ * Create an Account from keyfile contents (a JSON array of 64 bytes)
+ */
+export function accountFromJson(json: string): Account {
+  return new Account(Buffer.from(JSON.parse(json) as number[]));
+}
+
+/**
+ * Ed25519 keypair for a 32 byte seed
+ */
+export function accountFromSeed(seed: Buffer): Account {
+  const privateKey = createPrivateKey({
+    key: Buffer.concat([PKCS8_ED25519_PREFIX, seed]),
+    format: 'der',
+    type: 'pkcs8',
+  });
+  const publicKey = createPublicKey(privateKey)
+    .export({format: 'der', type: 'spki'})
+    .slice(-32);
+  return new Account(Buffer.concat([seed, publicKey]));
+}
+
+/**
+ * SLIP-0010 Ed25519 key derivation, every index hardened
+ */
+export function deriveSeed(seed: Buffer, indexes: number[]): Buffer {
+  let digest = createHmac('sha512', 'ed25519 seed').update(seed).digest();
+  for (const index of indexes) {
+    const indexBytes = Buffer.alloc(4);
+    indexBytes.writeUInt32BE((index | HARDENED) >>> 0);
+    const data = Buffer.concat([
+      Buffer.alloc(1),
+      digest.slice(0, 32),
+      indexBytes,
+    ]);
+    digest = createHmac('sha512', digest.slice(32)).update(data).digest();
+  }
+  return digest.slice(0, 32);
+}
+
+/**
+ * Derivation path from a prompt:// query: ?key=A[/C] or ?full-path=m/44/501/A[/C]
+ */
+export function derivationPath(query: string): number[] | undefined {
+  const params = new URLSearchParams(query);
+  const key = params.get('key');
+  const fullPath = params.get('full-path');
+  const parse = (parts: string[]) =>
+    parts.map(part => {
+      const index = Number(part.replace(/'$/, ''));
+      if (!Number.isInteger(index) || index < 0 || index >= HARDENED) {
+        throw new Error(`Invalid derivation path index ${part}`);
+      }
+      return index;
+    });
+  if (fullPath !== null) {
+    const [root, ...parts] = fullPath.split('/');
+    if (root !== 'm') {
+      throw new Error(`Derivation path ${fullPath} must start with m/`);
+    }
+    return parse(parts);
+  }
+  if (key !== null) {
+    return [44, 501, ...parse(key.split('/').filter(part => part !== ''))];
+  }
+  return undefined;
+}
+
+/**
+ * Keypair for a BIP39 seed phrase, derived along `derivation` when given
+ */
+export function accountFromSeedPhrase(
+  phrase: string,
+  passphrase = '',
+  derivation?: number[],
+): Account {
+  const words = phrase.trim().split(/\s+/);
+  if (words.length < 12 || words.length > 24 || words.length % 3 !== 0) {
+    throw new Error(`Seed phrases have 12 to 24 words, not ${words.length}`);
+  }
+  const seed = pbkdf2Sync(
+    words.join(' ').normalize('NFKD'),
+    `mnemonic${passphrase}`.normalize('NFKD'),
+    2048,
+    64,
+    'sha512',
+  );
+  const derived = derivation ? deriveSeed(seed, derivation) : seed.slice(0, 32);
+  return accountFromSeed(derived);
+}
+
+/**
+ * Ask a question on the terminal, without echoing the answer when `hidden`
+ */
+function prompt(question: string, hidden: boolean): Promise<string> {
+  let muted = false;
+  const output = new Writable({
+    write(chunk: Buffer, _encoding, callback) {
+      if (!muted) {
+        process.stdout.write(chunk);
+      }
+      callback();
+    },
+  });
+  const rl = readline.createInterface({
+    input: process.stdin,
+    output,
+    terminal: true,
+  });
+  return new Promise(resolve => {
+    rl.question(question, answer => {
+      rl.close();
+      if (hidden) {
+        process.stdout.write('\n');
+      }
+      resolve(answer);
+    });
+    muted = hidden;
+  });
+}
+
+function readStdin(): Promise<string> {
+  return new Promise((resolve, reject) => {
+    let text = '';
+    process.stdin.on('data', (chunk: Buffer) => (text += chunk.toString()));
+    process.stdin.on('end', () => resolve(text));
+    process.stdin.on('error', reject);
+  });
+}
+
+/**
+ * Resolve a signer source (see NOTES) to an Account
+ */
+export async function resolveSigner(source: string): Promise<Account> {
+  if (source === 'ASK' || source.startsWith('prompt:')) {
+    const query = source.includes('?') ? source.slice(source.indexOf('?')) : '';
+    const derivation = derivationPath(query);
+    const phrase = await prompt('Seed phrase: ', true);
+    const passphrase = await prompt(
+      'BIP39 passphrase (empty for none): ',
+      true,
+    );
+    const account = accountFromSeedPhrase(phrase, passphrase, derivation);
+    console.log('Recovered', account.publicKey.toBase58());
+    return account;
+  }
+  if (source === 'stdin') {
+    return accountFromJson(await readStdin());
+  }
+  if (source.startsWith('env://')) {
+    const name = source.slice('env://'.length);
+    const value = process.env[name];
+    if (!value) {
+      throw new Error(`Environment variable ${name} is not set`);
+    }
+    return accountFromJson(value);
+  }
+  if (source.startsWith('usb://')) {
+    throw new Error(
+      `${source} is a hardware wallet, use resolveAnySigner() for it`,
+    );
+  }
+  const filePath = source.startsWith('file://')
+    ? source.slice('file://'.length)
+    : source;
+  return accountFromJson(await fs.readFile(filePath, {encoding: 'utf8'}));
+}
+
+/**
+ * Run ledger-sign, returning its output. The device prompts go to our stderr.
+ */
+function ledgerSign(args: string[], input = ''): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const child = spawn(LEDGER_SIGN, args, {
+      stdio: ['pipe', 'pipe', 'inherit'],
+    });
+    let out = '';
+    child.stdout.on('data', (chunk: Buffer) => (out += chunk.toString()));
+    child.on('error', err =>
+      reject(
+        new Error(
+          `Failed to run ${LEDGER_SIGN} (${err.message}), ` +
+            'build it with `npm run build:ledger`',
+        ),
+      ),
+    );
+    child.on('close', code =>
+      code === 0
+        ? resolve(out.trim())
+        : reject(new Error(`ledger-sign exited with ${String(code)}`)),
+    );
+    child.stdin.end(input);
+  });
+}
+
+/**
+ * A key held on a Ledger
+ */
+export class LedgerSigner {
+  constructor(readonly source: string, readonly publicKey: PublicKey) {}
+
+  static async connect(source: string): Promise<LedgerSigner> {
+    const pubkey = await ledgerSign(['pubkey', source]);
+    return new LedgerSigner(source, new PublicKey(pubkey));
+  }
+
+  /**
+   * Add this key's signature, once approved on the device
+   */
+  async sign(transaction: Transaction): Promise<void> {
+    const message = transaction.serializeMessage().toString('base64');
+    const signature = await ledgerSign(['sign', this.source], message);
+    transaction.addSignature(this.publicKey, Buffer.from(signature, 'base64'));
+  }
+}
+
+export type Signer = Account | LedgerSigner;
+
+/**
+ * Resolve any signer source, including `usb://ledger`
+ */
+export function resolveAnySigner(source: string): Promise<Signer> {
+  return source.startsWith('usb://')
+    ? LedgerSigner.connect(source)
+    : resolveSigner(source);
+}
+
+/**
+ * Sign `transaction` (which needs a recent blockhash) with keypairs and Ledgers.
+ * The first signer pays the fees.
+ */
+export async function signTransaction(
+  transaction: Transaction,
+  signers: Signer[],
+): Promise<void> {
+  transaction.feePayer = signers[0].publicKey;
+  const accounts = signers.filter(
+    (signer): signer is Account => signer instanceof Account,
+  );
+  if (accounts.length > 0) {
+    transaction.partialSign(...accounts);
+  }
+  for (const signer of signers) {
+    if (signer instanceof LedgerSigner) {
+      await signer.sign(transaction);
+    }
+  }
+}
+
+export interface OfflineOptions {
+  /** Sign with the local signers and print the transaction, don't send it */
+  signOnly: boolean;
+  /** Print the unsigned transaction instead of sending it */
+  dumpTransaction: boolean;
+  /** Blockhash to use instead of fetching one */
+  blockhash?: string;
+  /** Slots a greeting stays valid for, checked by a CheckRecency (see recency.ts) */
+  maxAgeSlots?: number;
+}
+
+/**
+ * The solana CLI's offline signing flags, from the command line
+ */
+export function offlineOptions(argv = process.argv): OfflineOptions {
+  const blockhashIndex = argv.indexOf('--blockhash');
+  const maxAgeIndex = argv.indexOf('--max-age-slots');
+  return {
+    signOnly: argv.indexOf('--sign-only') >= 0,
+    dumpTransaction: argv.indexOf('--dump-transaction') >= 0,
+    blockhash: blockhashIndex >= 0 ? argv[blockhashIndex + 1] : undefined,
+    maxAgeSlots: maxAgeIndex >= 0 ? Number(argv[maxAgeIndex + 1]) : undefined,
+  };
+}
+
+/**
+ * Base64 wire format of a transaction, whether or not all signatures are there
+ */
+export function encodeTransaction(transaction: Transaction): string {
+  return transaction
+    .serialize({requireAllSignatures: false, verifySignatures: false})
+    .toString('base64');
+}
+
+/**
+ * sendAndConfirmTransaction() for any mix of keypairs and Ledgers. With --sign-only
+ * or --dump-transaction the transaction is printed instead of sent (see offline.ts)
+ * and there's no signature to return.
+ * The transaction is simulated first and a SimulationError thrown if it would fail.
+ * With `programId`, helloworld's errors throw a HelloWorldTxError instead, whether
+ * the simulation or the sent transaction fails (see errors.ts).
+ */
+export async function sendAndConfirm(
+  connection: Connection,
+  transaction: Transaction,
+  signers: Signer[],
+  programId?: PublicKey,
+): Promise<string> {
+  const offline = offlineOptions();
+  if (offline.signOnly || offline.dumpTransaction) {
+    transaction.recentBlockhash =
+      offline.blockhash || (await connection.getRecentBlockhash()).blockhash;
+    transaction.feePayer = signers[0].publicKey;
+    if (offline.signOnly) {
+      await signTransaction(transaction, signers);
+    }
+    const label = offline.signOnly ? 'Signed' : 'Unsigned';
+    console.log(`${label} transaction:`, encodeTransaction(transaction));
+    return '';
+  }
+
+  const report = await simulate(
+    connection,
+    transaction,
+    signers[0].publicKey,
+    programId,
+  );
+  if (report.error && report.error.error !== null) {
+    throw new HelloWorldTxError(
+      report.error.error,
+      report.error.instruction || 0,
+      null,
+      report.logs,
+    );
+  }
+  if (!report.ok) {
+    throw new SimulationError(report);
+  }
+
+  const {blockhash} = await connection.getRecentBlockhash();
+  transaction.recentBlockhash = blockhash;
+  await signTransaction(transaction, signers);
+  const signature = await connection.sendRawTransaction(
+    transaction.serialize(),
+  );
+  const {value} = await connection.confirmTransaction(signature);
+  if (value.err) {
+    throw (
+      helloWorldTxError(value.err, transaction, programId, signature) ||
+      new Error(`Transaction ${signature} failed: ${JSON.stringify(value.err)}`)
+    );
+  }
+  return signature;
+}
@@ -0,0 +1,137 @@
+/**
+ * NOTES:
+ * - sendAndConfirm() simulates every transaction before sending it, so one that would
+ * fail costs no fees and comes back with the reason instead of a bare error code.
+ * - The simulation is summed up in a SimulationReport: the logs, the compute units the
+ * program used, the greets it logged and, on failure, which instruction failed and
+ * why. Custom error codes from helloworld are mapped to their HelloWorldError (see
+ * errors.ts).
+ * - The program has no structured events; a successful greet ends with the log line
+ * "Was sent message <txt>!", which is what `greetings` collects.
+ */
+
+import {Connection, PublicKey, Transaction} from '@solana/web3.js';
+
+import {helloWorldError, instructionError} from './errors';
+import {HELLO_WORLD_ERRORS, HelloWorldError} from './generated';
+
+export interface SimulationFailure {
+  /** Index of the failing instruction, null if the whole transaction failed */
+  instruction: number | null;
+  /** Program that failed, when known */
+  program: string | null;
+  /** Custom error code, for `Custom(n)` errors */
+  code: number | null;
+  /** The code as a HelloWorldError, when the failing program is helloworld */
+  error: HelloWorldError | null;
+  /** HelloWorldError variant, or the runtime's error name */
+  name: string;
+  message: string;
+}
+
+export interface SimulationReport {
+  ok: boolean;
+  logs: string[];
+  unitsConsumed: number | null;
+  greetings: string[];
+  error: SimulationFailure | null;
+}
+
+/**
+ * Compute units the program consumed, from a transaction's logs
+ */
+export function computeUnits(
+  logs: string[],
+  programId: string,
+): number | null {
+  const prefix = `Program ${programId} consumed `;
+  const line = logs.find(log => log.startsWith(prefix));
+  return line ? Number(line.slice(prefix.length).split(' ')[0]) : null;
+}
+
+/**
+ * Describe a TransactionError such as {"InstructionError": [0, {"Custom": 6001}]}.
+ * `programs` are the program ids of the transaction's instructions.
+ */
+export function describeError(
+  err: unknown,
+  programs: PublicKey[],
+  programId?: PublicKey,
+): SimulationFailure {
+  const failure: SimulationFailure = {
+    instruction: null,
+    program: null,
+    code: null,
+    error: null,
+    name: typeof err === 'string' ? err : JSON.stringify(err),
+    message: 'Transaction failed',
+  };
+  const pair = instructionError(err);
+  if (!pair) {
+    return failure;
+  }
+  const [index, detail] = pair;
+  const program = programs[index];
+  failure.instruction = index;
+  failure.program = program ? program.toBase58() : null;
+  failure.message = `Instruction ${index} failed`;
+  if (typeof detail === 'string') {
+    failure.name = detail;
+    return failure;
+  }
+  const custom = (detail as {Custom?: number}).Custom;
+  failure.name = JSON.stringify(detail);
+  failure.code = typeof custom === 'number' ? custom : null;
+  failure.error = helloWorldError(err, programs, programId);
+  if (failure.error !== null) {
+    failure.name = HELLO_WORLD_ERRORS[failure.error].name;
+    failure.message = HELLO_WORLD_ERRORS[failure.error].msg;
+  }
+  return failure;
+}
+
+/**
+ * Simulate `transaction` paid by `feePayer` (signatures aren't checked), reporting
+ * errors in terms of `programId` when given
+ */
+export async function simulate(
+  connection: Connection,
+  transaction: Transaction,
+  feePayer: PublicKey,
+  programId?: PublicKey,
+): Promise<SimulationReport> {
+  transaction.feePayer = feePayer;
+  const {value} = await connection.simulateTransaction(transaction);
+  const logs = value.logs || [];
+  const prefix = 'Program log: Was sent message ';
+  return {
+    ok: !value.err,
+    logs,
+    unitsConsumed: programId ? computeUnits(logs, programId.toBase58()) : null,
+    greetings: logs
+      .filter(log => log.startsWith(prefix) && log.endsWith('!'))
+      .map(log => log.slice(prefix.length, -1)),
+    error: value.err
+      ? describeError(
+          value.err,
+          transaction.instructions.map(instruction => instruction.programId),
+          programId,
+        )
+      : null,
+  };
+}
+
+/**
+ * Thrown by sendAndConfirm() when the simulation fails with anything but a
+ * HelloWorldError (those throw a HelloWorldTxError), carrying the report
+ */
+export class SimulationError extends Error {
+  constructor(readonly report: SimulationReport) {
+    super(
+      `Simulation failed: ${report.error ? report.error.name : 'unknown'}` +
+        (report.error ? ` (${report.error.message})` : '') +
+        `\n${report.logs.join('\n')}`,
+    );
+    this.name = 'SimulationError';
+  }
+}
@@ -0,0 +1,185 @@
+/**
+ * NOTES:
+ * - Saves an account as it is on chain, to compare before and after a migration, a
+ * realloc or a program upgrade:
+ *   npm run snapshot -- save <ACCOUNT> <FILE>
+ *   npm run snapshot -- diff <BEFORE> <AFTER>
+ * - A snapshot is JSON holding the slot, the account's owner, lamports and size, its
+ * raw data (base64) and, when the data is a GreetingAccount, the decoded fields.
+ * - `diff` compares field by field: account metadata, decoded fields and the byte
+ * ranges of the raw data that changed. Data that no longer decodes (e.g. after a
+ * layout change) is reported as such, the raw diff still shows what moved.
+ * - Only greeting accounts are decoded. Other accounts, the program's config account
+ * included, are saved raw.
+ */
+
+import {Connection, PublicKey} from '@solana/web3.js';
+import fs from 'mz/fs';
+
+import {decodeGreeting, greetedDays, isCompact, storedVersion} from './state';
+import {getRpcUrl} from './utils';
+
+export interface Snapshot {
+  address: string;
+  slot: number;
+  owner: string;
+  lamports: number;
+  executable: boolean;
+  size: number;
+  data: string;
+  decoded: {type: string; fields: Record<string, unknown>} | null;
+}
+
+/**
+ * Decode account data as a GreetingAccount, null if it isn't one
+ */
+export function decodeAccount(data: Buffer): Snapshot['decoded'] {
+  try {
+    const greeting = decodeGreeting(data);
+    return {
+      type: 'GreetingAccount',
+      fields: {
+        version: storedVersion(data),
+        encoding: isCompact(data) ? 'compact' : 'borsh',
+        txt: greeting.txt,
+        counter: greeting.counter.toString(),
+        calendar_year: greeting.calendar_year,
+        greeted_days: greetedDays(greeting),
+        last_update_epoch: greeting.last_update_epoch.toString(),
+        direct_greets: greeting.direct_greets.toString(),
+        cpi_greets: greeting.cpi_greets.toString(),
+      },
+    };
+  } catch (err) {
+    return null;
+  }
+}
+
+export async function takeSnapshot(
+  connection: Connection,
+  address: PublicKey,
+): Promise<Snapshot> {
+  const {context, value} = await connection.getAccountInfoAndContext(address);
+  if (value === null) {
+    throw new Error(`Account ${address.toBase58()} does not exist`);
+  }
+  return {
+    address: address.toBase58(),
+    slot: context.slot,
+    owner: value.owner.toBase58(),
+    lamports: value.lamports,
+    executable: value.executable,
+    size: value.data.length,
+    data: value.data.toString('base64'),
+    decoded: decodeAccount(value.data),
+  };
+}
+
+/**
+ * Byte ranges [start, end) where `a` and `b` differ, including any bytes only
+ * one of them has
+ */
+export function changedRanges(a: Buffer, b: Buffer): [number, number][] {
+  const ranges: [number, number][] = [];
+  const length = Math.max(a.length, b.length);
+  let start = -1;
+  for (let i = 0; i <= length; i++) {
+    const differs =
+      i < length && (i >= a.length || i >= b.length || a[i] !== b[i]);
+    if (differs && start < 0) {
+      start = i;
+    } else if (!differs && start >= 0) {
+      ranges.push([start, i]);
+      start = -1;
+    }
+  }
+  return ranges;
+}
+
+/**
+ * One line per difference between two snapshots, empty when they match
+ */
+export function diffSnapshots(before: Snapshot, after: Snapshot): string[] {
+  const lines: string[] = [];
+  if (before.address !== after.address) {
+    lines.push(`address: ${before.address} -> ${after.address}`);
+  }
+  const fields: (keyof Snapshot)[] = [
+    'owner',
+    'lamports',
+    'executable',
+    'size',
+  ];
+  for (const field of fields) {
+    const a = String(before[field]);
+    const b = String(after[field]);
+    if (a !== b) {
+      lines.push(`${field}: ${a} -> ${b}`);
+    }
+  }
+
+  const describe = (snapshot: Snapshot) =>
+    snapshot.decoded ? snapshot.decoded.type : 'undecodable data';
+  if (!before.decoded || !after.decoded) {
+    if (describe(before) !== describe(after)) {
+      lines.push(`decoded: ${describe(before)} -> ${describe(after)}`);
+    }
+  } else {
+    const names = Object.keys(before.decoded.fields);
+    for (const name of Object.keys(after.decoded.fields)) {
+      if (names.indexOf(name) < 0) {
+        names.push(name);
+      }
+    }
+    for (const name of names) {
+      const a = JSON.stringify(before.decoded.fields[name]);
+      const b = JSON.stringify(after.decoded.fields[name]);
+      if (a !== b) {
+        lines.push(`${name}: ${a || '(missing)'} -> ${b || '(missing)'}`);
+      }
+    }
+  }
+
+  const a = Buffer.from(before.data, 'base64');
+  const b = Buffer.from(after.data, 'base64');
+  for (const [start, end] of changedRanges(a, b)) {
+    const hex = (data: Buffer) => data.slice(start, end).toString('hex') || '-';
+    lines.push(`data[${start}..${end}]: ${hex(a)} -> ${hex(b)}`);
+  }
+  return lines;
+}
+
+async function readSnapshot(file: string): Promise<Snapshot> {
+  return JSON.parse(await fs.readFile(file, {encoding: 'utf8'})) as Snapshot;
+}
+
+async function main() {
+  const [command, first, second] = process.argv.slice(2);
+  if (command === 'save' && first && second) {
+    const connection = new Connection(await getRpcUrl(), 'confirmed');
+    const snapshot = await takeSnapshot(connection, new PublicKey(first));
+    await fs.writeFile(second, JSON.stringify(snapshot, null, 2) + '\n');
+    console.log(`Saved ${first} at slot ${snapshot.slot} to ${second}`);
+  } else if (command === 'diff' && first && second) {
+    const before = await readSnapshot(first);
+    const after = await readSnapshot(second);
+    console.log(`Slot ${before.slot} -> ${after.slot}`);
+    const lines = diffSnapshots(before, after);
+    console.log(lines.length > 0 ? lines.join('\n') : 'No differences');
+  } else {
+    throw new Error(
+      'Usage: snapshot save <ACCOUNT> <FILE>\n' +
+        '       snapshot diff <BEFORE> <AFTER>',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,160 @@
+/* eslint-disable @typescript-eslint/no-unsafe-assignment */
+/* eslint-disable @typescript-eslint/no-unsafe-member-access */
+
+/**
+ * NOTES:
+ * - Solana Pay "transaction requests" let a mobile wallet greet an account by scanning a
+ * QR code. The code holds `solana:<link>`, where <link> is an https URL we serve:
+ *   - GET <link> returns a label and icon the wallet shows before asking the user.
+ *   - POST <link> with {account: <wallet pubkey>} returns a base64 transaction, unsigned,
+ *   with the wallet as fee payer. The wallet signs and sends it.
+ * - The greeting account and message ride along as query parameters of <link>, so one
+ * server can hand out codes for any account. The greeting account doesn't need to sign,
+ * only the wallet paying the fee does.
+ * - Usage:
+ *   npm run pay -- url <link> <greeting account> <message> [qr.png]
+ *   npm run pay -- serve [port]
+ * The QR code PNG is rendered with `qrencode` if it's installed. The link must be public
+ * https in production (e.g. put `serve` behind a reverse proxy or ngrok).
+ */
+
+import {Connection, PublicKey, Transaction} from '@solana/web3.js';
+import {execFile} from 'child_process';
+import http from 'http';
+import {promisify} from 'util';
+
+import {
+  PROGRAM_KEYPAIR_PATH,
+  configAddress,
+  feeAccounts,
+  greetInstruction,
+  greetingLogAccounts,
+} from './hello_world';
+import {getRpcUrl, readAccountFromFile} from './utils';
+
+const LABEL = 'Solana Hello World';
+const ICON =
+  process.env.SOLANA_PAY_ICON ||
+  'https://solana.com/src/img/branding/solanaLogoMark.svg';
+
+/**
+ * Solana Pay transaction-request URL for greeting `greetedPubkey` with `txt`
+ */
+export function greetTransactionRequestUrl(
+  link: string,
+  greetedPubkey: PublicKey,
+  txt: string,
+): string {
+  const url = new URL(link);
+  url.searchParams.set('account', greetedPubkey.toBase58());
+  url.searchParams.set('txt', txt);
+  // NOTE The spec requires the link to be URL-encoded when it has query parameters
+  return `solana:${encodeURIComponent(url.toString())}`;
+}
+
+/**
+ * Base64 greet transaction for `wallet` to sign, as returned by the POST request
+ */
+export async function createGreetTransaction(
+  connection: Connection,
+  programId: PublicKey,
+  wallet: PublicKey,
+  greetedPubkey: PublicKey,
+  txt: string,
+): Promise<string> {
+  const transaction = new Transaction().add(
+    greetInstruction(
+      programId,
+      greetedPubkey,
+      await configAddress(programId),
+      txt,
+      await feeAccounts(connection, programId, wallet),
+      await greetingLogAccounts(connection, programId),
+    ),
+  );
+  transaction.feePayer = wallet;
+  const {blockhash} = await connection.getRecentBlockhash();
+  transaction.recentBlockhash = blockhash;
+  // NOTE Unsigned: the wallet adds the only signature this transaction needs
+  return transaction
+    .serialize({requireAllSignatures: false, verifySignatures: false})
+    .toString('base64');
+}
+
+function readBody(req: http.IncomingMessage): Promise<string> {
+  return new Promise((resolve, reject) => {
+    let body = '';
+    req.on('data', (chunk: Buffer) => (body += chunk.toString()));
+    req.on('end', () => resolve(body));
+    req.on('error', reject);
+  });
+}
+
+function reply(res: http.ServerResponse, status: number, body: object): void {
+  res.writeHead(status, {
+    'Content-Type': 'application/json',
+    // Wallets fetch from other origins
+    'Access-Control-Allow-Origin': '*',
+  });
+  res.end(JSON.stringify(body));
+}
+
+/**
+ * Serve transaction requests for links made with greetTransactionRequestUrl()
+ */
+export async function serve(port: number): Promise<void> {
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+
+  const server = http.createServer((req, res) => {
+    const handle = async () => {
+      if (req.method === 'GET') {
+        return reply(res, 200, {label: LABEL, icon: ICON});
+      }
+      if (req.method !== 'POST') {
+        return reply(res, 405, {error: 'Method not allowed'});
+      }
+      const params = new URL(req.url || '/', 'http://localhost').searchParams;
+      const {account} = JSON.parse(await readBody(req));
+      const txt = params.get('txt') || '';
+      const transaction = await createGreetTransaction(
+        connection,
+        programId,
+        new PublicKey(account),
+        new PublicKey(params.get('account') || ''),
+        txt,
+      );
+      reply(res, 200, {transaction, message: `Say "${txt}"`});
+    };
+    handle().catch((err: Error) => reply(res, 400, {error: err.message}));
+  });
+  server.listen(port);
+  console.log(`Serving Solana Pay transaction requests on port ${port}`);
+}
+
+async function main() {
+  const [command, ...args] = process.argv.slice(2);
+  if (command === 'url' && args.length >= 3) {
+    const [link, greeted, txt, qrPath] = args;
+    const url = greetTransactionRequestUrl(link, new PublicKey(greeted), txt);
+    console.log(url);
+    if (qrPath) {
+      await promisify(execFile)('qrencode', ['-o', qrPath, url]);
+      console.log('QR code written to', qrPath);
+    }
+  } else if (command === 'serve') {
+    await serve(Number(args[0] || 8080));
+  } else {
+    throw new Error(
+      'Usage: solana_pay url <link> <greeting account> <message> [qr.png] | serve [port]',
+    );
+  }
+}
+
+if (require.main === module) {
+  main().catch(err => {
+    console.error(err);
+    process.exit(-1);
+  });
+}
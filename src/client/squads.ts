@@ -0,0 +1,398 @@
+/**
+ * NOTES:
+ * - In production the authority over a program shouldn't be one hot keypair. With a
+ * Squads v4 multisig the authority is the multisig's vault PDA, and anything the vault
+ * signs must first be proposed as a "vault transaction" and approved by enough members.
+ * - proposeVaultTransaction() wraps any instructions signed by the vault into a vault
+ * transaction, opens a proposal for it and casts the creator's approval, all in one
+ * transaction. Remaining members approve and execute in the Squads app or CLI.
+ * - The only authority-gated instruction today is the loader's Upgrade (the program
+ * itself has no admin instructions such as Pause, SetFeeConfig or Withdraw yet). The
+ * deployer prints it as JSON after `write-buffer --buffer-authority <VAULT>`:
+ *   npm run squads -- vault <MULTISIG> [--vault-index 0]
+ *   npm run deploy:program-rust -- write-buffer --url devnet --buffer-authority <VAULT>
+ *   npm run squads -- propose <MULTISIG> upgrade.json [--vault-index 0] [--memo text]
+ * where upgrade.json holds the printed instruction (or an array of instructions).
+ * The member signing the proposal is the payer, which can be a Ledger (usb://ledger).
+ * - Squads is an Anchor program: instruction data starts with the first 8 bytes of
+ * sha256("global:<instruction name>"), followed by the Borsh-encoded arguments. The
+ * inner message uses Squads' compact encoding (u8 lengths, u16 for instruction data).
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {createHash} from 'crypto';
+import fs from 'mz/fs';
+
+import {Signer, offlineOptions, sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl} from './utils';
+
+export const SQUADS_PROGRAM_ID = new PublicKey(
+  'SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf',
+);
+
+/**
+ * Offset of `transaction_index` (u64) in the Multisig account: discriminator,
+ * create_key, config_authority, threshold (u16) and time_lock (u32)
+ */
+const TRANSACTION_INDEX_OFFSET = 8 + 32 + 32 + 2 + 4;
+
+function discriminator(name: string): Buffer {
+  return createHash('sha256').update(`global:${name}`).digest().slice(0, 8);
+}
+
+function u8(value: number): Buffer {
+  return Buffer.from([value]);
+}
+
+function u32(value: number): Buffer {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32LE(value);
+  return buffer;
+}
+
+function u64(value: number): Buffer {
+  const buffer = Buffer.alloc(8);
+  buffer.writeUInt32LE(value % 2 ** 32);
+  buffer.writeUInt32LE(Math.floor(value / 2 ** 32), 4);
+  return buffer;
+}
+
+function optionString(value?: string): Buffer {
+  if (value === undefined) {
+    return u8(0);
+  }
+  const bytes = Buffer.from(value, 'utf8');
+  return Buffer.concat([u8(1), u32(bytes.length), bytes]);
+}
+
+async function pda(seeds: Buffer[]): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from('multisig'), ...seeds],
+    SQUADS_PROGRAM_ID,
+  );
+  return address;
+}
+
+export function vaultPda(
+  multisig: PublicKey,
+  vaultIndex = 0,
+): Promise<PublicKey> {
+  return pda([multisig.toBuffer(), Buffer.from('vault'), u8(vaultIndex)]);
+}
+
+export function transactionPda(
+  multisig: PublicKey,
+  index: number,
+): Promise<PublicKey> {
+  return pda([multisig.toBuffer(), Buffer.from('transaction'), u64(index)]);
+}
+
+export function proposalPda(
+  multisig: PublicKey,
+  index: number,
+): Promise<PublicKey> {
+  return pda([
+    multisig.toBuffer(),
+    Buffer.from('transaction'),
+    u64(index),
+    Buffer.from('proposal'),
+  ]);
+}
+
+interface AccountMeta {
+  pubkey: PublicKey;
+  isSigner: boolean;
+  isWritable: boolean;
+}
+
+/**
+ * Compile instructions into Squads' TransactionMessage, with the vault as payer.
+ * Account keys are ordered writable signers, readonly signers, writable non-signers,
+ * readonly non-signers, like a regular Solana message.
+ */
+export function compileVaultMessage(
+  vault: PublicKey,
+  instructions: TransactionInstruction[],
+): Buffer {
+  const metas: AccountMeta[] = [];
+  const add = (pubkey: PublicKey, isSigner: boolean, isWritable: boolean) => {
+    const meta = metas.find(existing => existing.pubkey.equals(pubkey));
+    if (meta) {
+      meta.isSigner = meta.isSigner || isSigner;
+      meta.isWritable = meta.isWritable || isWritable;
+    } else {
+      metas.push({pubkey, isSigner, isWritable});
+    }
+  };
+  add(vault, true, true);
+  for (const instruction of instructions) {
+    for (const key of instruction.keys) {
+      if (key.isSigner && !key.pubkey.equals(vault)) {
+        throw new Error(
+          `Only the vault can sign, not ${key.pubkey.toBase58()}`,
+        );
+      }
+      add(key.pubkey, key.isSigner, key.isWritable);
+    }
+    add(instruction.programId, false, false);
+  }
+
+  const group = (isSigner: boolean, isWritable: boolean) =>
+    metas.filter(
+      meta => meta.isSigner === isSigner && meta.isWritable === isWritable,
+    );
+  const writableSigners = group(true, true);
+  const readonlySigners = group(true, false);
+  const writable = group(false, true);
+  const keys = [
+    ...writableSigners,
+    ...readonlySigners,
+    ...writable,
+    ...group(false, false),
+  ].map(meta => meta.pubkey);
+  const index = (pubkey: PublicKey) =>
+    keys.findIndex(key => key.equals(pubkey));
+
+  const parts = [
+    u8(writableSigners.length + readonlySigners.length),
+    u8(writableSigners.length),
+    u8(writable.length),
+    u8(keys.length),
+    ...keys.map(key => key.toBuffer()),
+    u8(instructions.length),
+  ];
+  for (const instruction of instructions) {
+    const dataLength = Buffer.alloc(2);
+    dataLength.writeUInt16LE(instruction.data.length);
+    parts.push(
+      u8(index(instruction.programId)),
+      u8(instruction.keys.length),
+      Buffer.from(instruction.keys.map(key => index(key.pubkey))),
+      dataLength,
+      instruction.data,
+    );
+  }
+  // No address lookup tables
+  parts.push(u8(0));
+  return Buffer.concat(parts);
+}
+
+function vaultTransactionCreateInstruction(
+  multisig: PublicKey,
+  transaction: PublicKey,
+  creator: PublicKey,
+  vaultIndex: number,
+  message: Buffer,
+  memo?: string,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: multisig, isSigner: false, isWritable: true},
+      {pubkey: transaction, isSigner: false, isWritable: true},
+      {pubkey: creator, isSigner: true, isWritable: false},
+      {pubkey: creator, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    programId: SQUADS_PROGRAM_ID,
+    data: Buffer.concat([
+      discriminator('vault_transaction_create'),
+      u8(vaultIndex),
+      u8(0), // ephemeral signers
+      u32(message.length),
+      message,
+      optionString(memo),
+    ]),
+  });
+}
+
+function proposalCreateInstruction(
+  multisig: PublicKey,
+  proposal: PublicKey,
+  creator: PublicKey,
+  index: number,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: multisig, isSigner: false, isWritable: false},
+      {pubkey: proposal, isSigner: false, isWritable: true},
+      {pubkey: creator, isSigner: true, isWritable: false},
+      {pubkey: creator, isSigner: true, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    programId: SQUADS_PROGRAM_ID,
+    data: Buffer.concat([
+      discriminator('proposal_create'),
+      u64(index),
+      u8(0), // not a draft
+    ]),
+  });
+}
+
+function proposalApproveInstruction(
+  multisig: PublicKey,
+  proposal: PublicKey,
+  member: PublicKey,
+): TransactionInstruction {
+  return new TransactionInstruction({
+    keys: [
+      {pubkey: multisig, isSigner: false, isWritable: false},
+      {pubkey: member, isSigner: true, isWritable: true},
+      {pubkey: proposal, isSigner: false, isWritable: true},
+    ],
+    programId: SQUADS_PROGRAM_ID,
+    data: Buffer.concat([discriminator('proposal_approve'), optionString()]),
+  });
+}
+
+/**
+ * Propose `instructions` as a vault transaction of `multisig` and approve it as
+ * `member`, who needs the Initiate and Vote permissions. Returns the proposal address.
+ */
+export async function proposeVaultTransaction(
+  connection: Connection,
+  member: Signer,
+  multisig: PublicKey,
+  instructions: TransactionInstruction[],
+  vaultIndex = 0,
+  memo?: string,
+): Promise<PublicKey> {
+  const multisigInfo = await connection.getAccountInfo(multisig);
+  if (!multisigInfo || !multisigInfo.owner.equals(SQUADS_PROGRAM_ID)) {
+    throw new Error(`${multisig.toBase58()} is not a Squads v4 multisig`);
+  }
+  const data = multisigInfo.data;
+  const index =
+    data.readUInt32LE(TRANSACTION_INDEX_OFFSET) +
+    data.readUInt32LE(TRANSACTION_INDEX_OFFSET + 4) * 2 ** 32 +
+    1;
+
+  const vault = await vaultPda(multisig, vaultIndex);
+  const transaction = await transactionPda(multisig, index);
+  const proposal = await proposalPda(multisig, index);
+  const message = compileVaultMessage(vault, instructions);
+  await sendAndConfirm(
+    connection,
+    new Transaction().add(
+      vaultTransactionCreateInstruction(
+        multisig,
+        transaction,
+        member.publicKey,
+        vaultIndex,
+        message,
+        memo,
+      ),
+      proposalCreateInstruction(multisig, proposal, member.publicKey, index),
+      proposalApproveInstruction(multisig, proposal, member.publicKey),
+    ),
+    [member],
+  );
+  return proposal;
+}
+
+const BASE58_ALPHABET =
+  '123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz';
+
+function decodeBase58(text: string): Buffer {
+  const bytes: number[] = [];
+  for (const char of text) {
+    let carry = BASE58_ALPHABET.indexOf(char);
+    if (carry < 0) {
+      throw new Error(`Invalid base58 character ${char}`);
+    }
+    for (let i = 0; i < bytes.length; i++) {
+      carry += bytes[i] * 58;
+      bytes[i] = carry & 0xff;
+      carry >>= 8;
+    }
+    for (; carry > 0; carry >>= 8) {
+      bytes.push(carry & 0xff);
+    }
+  }
+  const zeros = text.length - text.replace(/^1+/, '').length;
+  const leading = new Array<number>(zeros).fill(0);
+  return Buffer.from([...leading, ...bytes.reverse()]);
+}
+
+interface InstructionJson {
+  programId: string;
+  accounts: {pubkey: string; isSigner: boolean; isWritable: boolean}[];
+  data: string;
+}
+
+/**
+ * Parse instructions in the deployer's JSON format (data in base58)
+ */
+export function parseInstructions(json: string): TransactionInstruction[] {
+  const parsed = JSON.parse(json) as InstructionJson | InstructionJson[];
+  return (Array.isArray(parsed) ? parsed : [parsed]).map(
+    instruction =>
+      new TransactionInstruction({
+        programId: new PublicKey(instruction.programId),
+        keys: instruction.accounts.map(meta => ({
+          pubkey: new PublicKey(meta.pubkey),
+          isSigner: meta.isSigner,
+          isWritable: meta.isWritable,
+        })),
+        data: decodeBase58(instruction.data),
+      }),
+  );
+}
+
+async function main() {
+  const [command, multisigArg, ...rest] = process.argv.slice(2);
+  const option = (name: string) => {
+    const index = rest.indexOf(name);
+    return index >= 0 ? rest.splice(index, 2)[1] : undefined;
+  };
+  const vaultIndex = Number(option('--vault-index') || 0);
+  const memo = option('--memo');
+  if (!multisigArg || !['vault', 'propose'].includes(command)) {
+    throw new Error(
+      'Usage: squads vault <MULTISIG> [--vault-index N]\n' +
+        '       squads propose <MULTISIG> <INSTRUCTIONS_JSON> ' +
+        '[--vault-index N] [--memo TEXT]',
+    );
+  }
+  const multisig = new PublicKey(multisigArg);
+  if (command === 'vault') {
+    console.log((await vaultPda(multisig, vaultIndex)).toBase58());
+    return;
+  }
+
+  const instructions = parseInstructions(
+    await fs.readFile(rest[0], {encoding: 'utf8'}),
+  );
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const member = await getPayerSigner();
+  const proposal = await proposeVaultTransaction(
+    connection,
+    member,
+    multisig,
+    instructions,
+    vaultIndex,
+    memo,
+  );
+  const {signOnly, dumpTransaction} = offlineOptions();
+  if (signOnly || dumpTransaction) {
+    console.log('Submitting it creates proposal', proposal.toBase58());
+    return;
+  }
+  console.log('Proposal', proposal.toBase58(), 'created and approved');
+  console.log('Other members can now approve and execute it in Squads');
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,364 @@
+/**
+ * NOTES:
+ * - Reads greeting accounts of every schema version. Version 1 accounts hold the bare
+ * Borsh GreetingAccountV1; from version 2 on the data opens with STATE_MAGIC and the
+ * version byte, and from version 6 on with Anchor's 8-byte account discriminator
+ * instead (see src/program-rust/src/migrations.rs).
+ * - The program upgrades accounts lazily on the next Greet, so old accounts stay
+ * around. They decode as the current GreetingAccount with the fields their version
+ * lacks (counter, calendar, last_update_epoch, the origin counters) empty, like the
+ * program's migration would leave them.
+ * - Accounts are allocated at a fixed size and borsh-js rejects trailing bytes, so
+ * the data is cut to the exact length of the state before deserializing.
+ * - Accounts opening with COMPACT_MAGIC use the compact encoding (varints, bitpacked
+ * flags) instead of Borsh, see src/program-rust/src/compact.rs.
+ * - With a decay half-life in the config account the stored counter is the raw one as
+ * of last_update_epoch; effectiveCounter() works out what it has decayed to since, the
+ * way the program does (see src/program-rust/src/decay.rs).
+ * - Config accounts created before a field was added are shorter; decodeConfig()
+ * reads the missing fields as zeros (see src/program-rust/src/config.rs).
+ */
+
+import * as borsh from 'borsh';
+import BN from 'bn.js';
+
+import {
+  COMPACT_FLAG_CALENDAR,
+  COMPACT_FLAG_COUNTER,
+  COMPACT_FLAG_LAST_UPDATE,
+  COMPACT_FLAG_ORIGIN,
+  COMPACT_MAGIC,
+  Config,
+  DAY_BITMAP_SIZE,
+  DISCRIMINATOR_VERSION,
+  GREETING_DISCRIMINATOR,
+  GreetingAccount,
+  GreetingAccountV1,
+  GreetingAccountV2,
+  GreetingAccountV3,
+  GreetingAccountV4,
+  GreetingReceipt,
+  GreetingSnapshot,
+  HelloWorldSchema,
+  RECEIPT_DISCRIMINATOR,
+  RECEIPT_SIZE,
+  SNAPSHOT_DISCRIMINATOR,
+  TAG_COUNTERS_DISCRIMINATOR,
+  STATE_MAGIC,
+  STATE_VERSION,
+  TagCounters,
+} from './generated';
+
+/**
+ * Size of the STATE_MAGIC + version header
+ */
+export const HEADER_SIZE = STATE_MAGIC.length + 1;
+
+/**
+ * Whether `data` opens with GreetingAccount's Anchor discriminator
+ */
+function hasDiscriminator(data: Buffer): boolean {
+  return (
+    data.length >= GREETING_DISCRIMINATOR.length &&
+    data.slice(0, GREETING_DISCRIMINATOR.length).equals(GREETING_DISCRIMINATOR)
+  );
+}
+
+/**
+ * Whether `data` holds a compactly encoded greeting
+ */
+export function isCompact(data: Buffer): boolean {
+  return (
+    data.length >= HEADER_SIZE &&
+    data.slice(0, COMPACT_MAGIC.length).toString() === COMPACT_MAGIC
+  );
+}
+
+/**
+ * Schema version of the state in `data`
+ */
+export function storedVersion(data: Buffer): number {
+  if (hasDiscriminator(data)) {
+    return DISCRIMINATOR_VERSION;
+  }
+  const magic = data.slice(0, STATE_MAGIC.length).toString();
+  if (
+    data.length >= HEADER_SIZE &&
+    (magic === STATE_MAGIC || magic === COMPACT_MAGIC) &&
+    data[STATE_MAGIC.length] >= 2
+  ) {
+    return data[STATE_MAGIC.length];
+  }
+  return 1;
+}
+
+/**
+ * The current GreetingAccount holding the fields of an older schema version
+ */
+function upgrade(
+  txt: string,
+  counter: BN,
+  calendarYear = 0,
+  greetedDays = new Uint8Array(DAY_BITMAP_SIZE),
+  lastUpdateEpoch = new BN(0),
+): GreetingAccount {
+  return new GreetingAccount({
+    txt,
+    counter,
+    calendar_year: calendarYear,
+    greeted_days: greetedDays,
+    last_update_epoch: lastUpdateEpoch,
+    direct_greets: new BN(0),
+    cpi_greets: new BN(0),
+  });
+}
+
+/**
+ * Days of the greeting's calendar_year it was greeted on (0 is January 1st), for
+ * drawing a contribution graph
+ */
+export function greetedDays(greeting: GreetingAccount): number[] {
+  const days: number[] = [];
+  for (let day = 0; day < DAY_BITMAP_SIZE * 8; day++) {
+    if ((greeting.greeted_days[day >> 3] & (1 << (day & 7))) !== 0) {
+      days.push(day);
+    }
+  }
+  return days;
+}
+
+/**
+ * The greeting's counter decayed to `epoch` under a half-life of `halfLifeEpochs`
+ * (0 for no decay), the one the program builds on at the next Greet. Accounts not
+ * stamped with an epoch yet (last_update_epoch 0) don't decay.
+ */
+export function effectiveCounter(
+  greeting: GreetingAccount,
+  halfLifeEpochs: number,
+  epoch: number,
+): BN {
+  const lastUpdate = greeting.last_update_epoch.toNumber();
+  if (halfLifeEpochs === 0 || lastUpdate === 0 || epoch <= lastUpdate) {
+    return greeting.counter;
+  }
+  const halvings = Math.floor((epoch - lastUpdate) / halfLifeEpochs);
+  return halvings >= 64 ? new BN(0) : greeting.counter.shrn(halvings);
+}
+
+/**
+ * Size of the config account's data
+ */
+export const CONFIG_SIZE = borsh.serialize(HelloWorldSchema, new Config())
+  .length;
+
+/**
+ * Decode the config account's data, whatever size it was created with
+ */
+export function decodeConfig(data: Buffer): Config {
+  const padded = Buffer.alloc(CONFIG_SIZE);
+  data.copy(padded, 0, 0, CONFIG_SIZE);
+  return borsh.deserialize(HelloWorldSchema, Config, padded) as Config;
+}
+
+/**
+ * Decode a greeting receipt's data, see src/program-rust/src/receipt.rs
+ */
+export function decodeReceipt(data: Buffer): GreetingReceipt {
+  if (
+    data.length !== RECEIPT_SIZE ||
+    !data.slice(0, RECEIPT_DISCRIMINATOR.length).equals(RECEIPT_DISCRIMINATOR)
+  ) {
+    throw new Error('Not a greeting receipt');
+  }
+  return borsh.deserialize(
+    HelloWorldSchema,
+    GreetingReceipt,
+    data.slice(RECEIPT_DISCRIMINATOR.length),
+  ) as GreetingReceipt;
+}
+
+/**
+ * Account data size needed to hold `greeting` at the current schema version
+ */
+export function greetingSize(greeting: GreetingAccount): number {
+  return (
+    GREETING_DISCRIMINATOR.length +
+    borsh.serialize(HelloWorldSchema, greeting).length
+  );
+}
+
+/**
+ * Read an LEB128 varint from `data` at `offset`
+ */
+function readVarint(data: Buffer, offset: number): {value: BN; end: number} {
+  const value = new BN(0);
+  for (let i = 0; i < 10; i++) {
+    if (offset + i >= data.length) {
+      throw new Error('Unexpected end of varint');
+    }
+    const byte = data[offset + i];
+    value.ior(new BN(byte & 0x7f).shln(7 * i));
+    if ((byte & 0x80) === 0) {
+      return {value, end: offset + i + 1};
+    }
+  }
+  throw new Error('Varint overflows a u64');
+}
+
+/**
+ * Decode a compactly encoded greeting account
+ */
+function decodeCompact(data: Buffer): GreetingAccount {
+  let offset = HEADER_SIZE;
+  const flags = data[offset++];
+  const known =
+    COMPACT_FLAG_COUNTER |
+    COMPACT_FLAG_CALENDAR |
+    COMPACT_FLAG_LAST_UPDATE |
+    COMPACT_FLAG_ORIGIN;
+  if ((flags & ~known) !== 0) {
+    throw new Error(`Unknown compact flags ${flags}`);
+  }
+  const len = readVarint(data, offset);
+  const txtEnd = len.end + len.value.toNumber();
+  if (txtEnd > data.length) {
+    throw new Error('Message runs past the end of the account');
+  }
+  const txt = data.slice(len.end, txtEnd).toString('utf8');
+  let end = txtEnd;
+  const greeting = upgrade(txt, new BN(0));
+  if ((flags & COMPACT_FLAG_COUNTER) !== 0) {
+    const counter = readVarint(data, end);
+    greeting.counter = counter.value;
+    end = counter.end;
+  }
+  if ((flags & COMPACT_FLAG_CALENDAR) !== 0) {
+    const year = readVarint(data, end);
+    if (year.end + DAY_BITMAP_SIZE > data.length) {
+      throw new Error('Calendar runs past the end of the account');
+    }
+    greeting.calendar_year = year.value.toNumber();
+    greeting.greeted_days = new Uint8Array(
+      data.slice(year.end, year.end + DAY_BITMAP_SIZE),
+    );
+    end = year.end + DAY_BITMAP_SIZE;
+  }
+  if ((flags & COMPACT_FLAG_LAST_UPDATE) !== 0) {
+    const lastUpdate = readVarint(data, end);
+    greeting.last_update_epoch = lastUpdate.value;
+    end = lastUpdate.end;
+  }
+  if ((flags & COMPACT_FLAG_ORIGIN) !== 0) {
+    const direct = readVarint(data, end);
+    greeting.direct_greets = direct.value;
+    greeting.cpi_greets = readVarint(data, direct.end).value;
+  }
+  return greeting;
+}
+
+/**
+ * Decode a greeting account's data, whatever its schema version and encoding
+ */
+export function decodeGreeting(data: Buffer): GreetingAccount {
+  const version = storedVersion(data);
+  if (version > STATE_VERSION) {
+    throw new Error(
+      `Schema version ${version} is newer than this client's ${STATE_VERSION}`,
+    );
+  }
+  if (isCompact(data)) {
+    return decodeCompact(data);
+  }
+  const header = hasDiscriminator(data)
+    ? GREETING_DISCRIMINATOR.length
+    : version === 1
+    ? 0
+    : HEADER_SIZE;
+  const body = data.slice(header);
+  // u32 length prefix + message, then the u64 counter from version 2 on, the
+  // calendar (u16 year, day bitmap) from version 3 on, the u64 last_update_epoch
+  // from version 4 on and the u64 direct_greets and cpi_greets from version 5 on
+  // (version 6 only changed the header)
+  const txtEnd = 4 + body.readUInt32LE(0);
+  if (version === 1) {
+    const greeting = borsh.deserialize(
+      HelloWorldSchema,
+      GreetingAccountV1,
+      body.slice(0, txtEnd),
+    ) as GreetingAccountV1;
+    return upgrade(greeting.txt, new BN(0));
+  }
+  if (version === 2) {
+    const greeting = borsh.deserialize(
+      HelloWorldSchema,
+      GreetingAccountV2,
+      body.slice(0, txtEnd + 8),
+    ) as GreetingAccountV2;
+    return upgrade(greeting.txt, greeting.counter);
+  }
+  if (version === 3) {
+    const greeting = borsh.deserialize(
+      HelloWorldSchema,
+      GreetingAccountV3,
+      body.slice(0, txtEnd + 8 + 2 + DAY_BITMAP_SIZE),
+    ) as GreetingAccountV3;
+    return upgrade(
+      greeting.txt,
+      greeting.counter,
+      greeting.calendar_year,
+      greeting.greeted_days,
+    );
+  }
+  if (version === 4) {
+    const greeting = borsh.deserialize(
+      HelloWorldSchema,
+      GreetingAccountV4,
+      body.slice(0, txtEnd + 8 + 2 + DAY_BITMAP_SIZE + 8),
+    ) as GreetingAccountV4;
+    return upgrade(
+      greeting.txt,
+      greeting.counter,
+      greeting.calendar_year,
+      greeting.greeted_days,
+      greeting.last_update_epoch,
+    );
+  }
+  return borsh.deserialize(
+    HelloWorldSchema,
+    GreetingAccount,
+    body.slice(0, txtEnd + 8 + 2 + DAY_BITMAP_SIZE + 8 + 16),
+  ) as GreetingAccount;
+}
+
+/**
+ * Decode a snapshot's data, see src/program-rust/src/milestone.rs
+ */
+export function decodeSnapshot(data: Buffer): GreetingSnapshot {
+  if (
+    !data.slice(0, SNAPSHOT_DISCRIMINATOR.length).equals(SNAPSHOT_DISCRIMINATOR)
+  ) {
+    throw new Error('Not a greeting snapshot');
+  }
+  return borsh.deserialize(
+    HelloWorldSchema,
+    GreetingSnapshot,
+    data.slice(SNAPSHOT_DISCRIMINATOR.length),
+  ) as GreetingSnapshot;
+}
+
+/**
+ * Decode a greeting account's tag counters, see src/program-rust/src/tags.rs
+ */
+export function decodeTagCounters(data: Buffer): TagCounters {
+  const discriminator = TAG_COUNTERS_DISCRIMINATOR.length;
+  if (!data.slice(0, discriminator).equals(TAG_COUNTERS_DISCRIMINATOR)) {
+    throw new Error('Not tag counters');
+  }
+  // NOTE The account has room for MAX_TAGS entries, cut it after the ones in use
+  const entries = data.readUInt32LE(discriminator + 32);
+  return borsh.deserialize(
+    HelloWorldSchema,
+    TagCounters,
+    data.slice(discriminator, discriminator + 32 + 4 + entries * 16),
+  ) as TagCounters;
+}
@@ -0,0 +1,123 @@
+/**
+ * NOTES:
+ * - Every instruction writing a greeting account logs
+ * "helloworld-state: <account> <hash>", both in hex: the first STATE_HASH_SIZE bytes
+ * of the sha256 of the account's whole data as the instruction leaves it (see
+ * src/program-rust/src/state_hash.rs). A verifier keeping its own copy of the
+ * account's bytes (replayed from transactions, an indexer, a Geyser stream) checks it
+ * against the logs of the transaction, without fetching the account.
+ * - A transaction writing an account twice logs it twice; the last line counts.
+ * - Usage:
+ *   npm run verify-state -- <SIGNATURE> <ACCOUNT> [DATA_FILE]
+ *   checks the raw account bytes in DATA_FILE against the state hash the transaction
+ *   logged for ACCOUNT. Without DATA_FILE the account is fetched, which only matches
+ *   when nothing wrote to it since.
+ */
+
+import {Connection, PublicKey} from '@solana/web3.js';
+import {createHash} from 'crypto';
+import fs from 'mz/fs';
+
+import {STATE_HASH_PREFIX, STATE_HASH_SIZE} from './generated';
+import {getRpcUrl} from './utils';
+
+/**
+ * The state hash of an account holding `data`
+ */
+export function stateHash(data: Buffer): Buffer {
+  return createHash('sha256').update(data).digest().slice(0, STATE_HASH_SIZE);
+}
+
+/**
+ * The account and state hash a log line carries, null for any other line
+ */
+export function parseStateHash(
+  line: string,
+): {account: PublicKey; hash: Buffer} | null {
+  const start = line.indexOf(STATE_HASH_PREFIX);
+  if (start === -1) {
+    return null;
+  }
+  const match = /^([0-9a-f]{64}) ([0-9a-f]+)$/.exec(
+    line.slice(start + STATE_HASH_PREFIX.length),
+  );
+  if (!match || match[2].length !== 2 * STATE_HASH_SIZE) {
+    return null;
+  }
+  return {
+    account: new PublicKey(Buffer.from(match[1], 'hex')),
+    hash: Buffer.from(match[2], 'hex'),
+  };
+}
+
+/**
+ * The last state hash `logs` carry for `account`, null if the logs don't write it
+ */
+export function lastStateHash(
+  logs: string[],
+  account: PublicKey,
+): Buffer | null {
+  let last = null;
+  for (const line of logs) {
+    const parsed = parseStateHash(line);
+    if (parsed && parsed.account.equals(account)) {
+      last = parsed.hash;
+    }
+  }
+  return last;
+}
+
+/**
+ * Whether `data` is what the transaction that logged `logs` left in `account`, null
+ * if the logs don't write it
+ */
+export function verifyStateHash(
+  logs: string[],
+  account: PublicKey,
+  data: Buffer,
+): boolean | null {
+  const hash = lastStateHash(logs, account);
+  return hash && hash.equals(stateHash(data));
+}
+
+async function main() {
+  const [signature, account, dataFile] = process.argv.slice(2);
+  if (!signature || !account) {
+    throw new Error('Usage: verify-state <SIGNATURE> <ACCOUNT> [DATA_FILE]');
+  }
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const pubkey = new PublicKey(account);
+  const transaction = await connection.getConfirmedTransaction(signature);
+  const meta = transaction && transaction.meta;
+  if (!meta || !meta.logMessages) {
+    throw new Error(`No logs for transaction ${signature}`);
+  }
+  let data;
+  if (dataFile) {
+    data = await fs.readFile(dataFile);
+  } else {
+    const info = await connection.getAccountInfo(pubkey);
+    if (info === null) {
+      throw new Error(`No account ${account}`);
+    }
+    data = info.data;
+  }
+  const verified = verifyStateHash(meta.logMessages, pubkey, data);
+  if (verified === null) {
+    throw new Error(`Transaction ${signature} didn't write ${account}`);
+  }
+  console.log(verified ? 'State matches' : 'State differs', 'for', account);
+  if (!verified) {
+    process.exitCode = 1;
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
@@ -0,0 +1,138 @@
+/**
+ * NOTES:
+ * - CountTag counts a greeting of a greeting account under a tag like "gm" or "gn",
+ * so one account tracks several categories. The counts are kept in the account's tag
+ * counters, a PDA created on the first tagged greeting with room for MAX_TAGS tags
+ * (see src/program-rust/src/tags.rs).
+ * - Usage:
+ *   npm run tags -- count <GREETING_ACCOUNT> <TAG>   count a greeting under TAG
+ *   npm run tags -- show <GREETING_ACCOUNT>          print the account's counts
+ * - A tag is 1 to 8 bytes. The payer pays for the tag counters if they're new.
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+
+import {instruction} from './crank';
+import {CountTagArgs, HelloWorldInstruction, TAGS_SEED} from './generated';
+import {PROGRAM_KEYPAIR_PATH, configAddress} from './hello_world';
+import {sendAndConfirm} from './signer';
+import {decodeTagCounters} from './state';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * The tag named `name`, padded with zeros to 8 bytes
+ */
+export function tag(name: string): Buffer {
+  const bytes = Buffer.from(name);
+  if (bytes.length === 0 || bytes.length > 8) {
+    throw new Error(`A tag is 1 to 8 bytes, not ${bytes.length}`);
+  }
+  return Buffer.concat([bytes, Buffer.alloc(8 - bytes.length)]);
+}
+
+/**
+ * The name of the tag `bytes`, without its padding
+ */
+export function tagName(bytes: Uint8Array): string {
+  const end = bytes.indexOf(0);
+  return Buffer.from(bytes.slice(0, end < 0 ? bytes.length : end)).toString();
+}
+
+/**
+ * Address of `greetedPubkey`'s tag counters
+ */
+export async function tagCountersAddress(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+): Promise<PublicKey> {
+  const [address] = await PublicKey.findProgramAddress(
+    [Buffer.from(TAGS_SEED), greetedPubkey.toBuffer()],
+    programId,
+  );
+  return address;
+}
+
+/**
+ * Build a CountTag instruction counting a greeting of `greetedPubkey` under the tag
+ * named `name`, `payer` paying for the tag counters if they're new
+ */
+export async function countTagInstruction(
+  programId: PublicKey,
+  greetedPubkey: PublicKey,
+  payer: PublicKey,
+  name: string,
+): Promise<TransactionInstruction> {
+  const configPubkey = await configAddress(programId);
+  const tagCounters = await tagCountersAddress(programId, greetedPubkey);
+  return instruction(
+    programId,
+    [
+      {pubkey: configPubkey, isSigner: false, isWritable: false},
+      {pubkey: greetedPubkey, isSigner: false, isWritable: false},
+      {pubkey: payer, isSigner: true, isWritable: true},
+      {pubkey: tagCounters, isSigner: false, isWritable: true},
+      {pubkey: SystemProgram.programId, isSigner: false, isWritable: false},
+    ],
+    HelloWorldInstruction.CountTag,
+    new CountTagArgs({tag: tag(name)}),
+  );
+}
+
+async function main() {
+  const [command, account, name] = process.argv.slice(2);
+  if (
+    ['count', 'show'].indexOf(command) < 0 ||
+    !account ||
+    (command === 'count' && !name)
+  ) {
+    throw new Error(
+      'Usage: tags count <GREETING_ACCOUNT> <TAG>\n' +
+        '       tags show <GREETING_ACCOUNT>',
+    );
+  }
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const programAccount = await readAccountFromFile(PROGRAM_KEYPAIR_PATH);
+  const programId = programAccount.publicKey;
+  const greetedPubkey = new PublicKey(account);
+  if (command === 'count') {
+    const payer = await getPayerSigner();
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        await countTagInstruction(
+          programId,
+          greetedPubkey,
+          payer.publicKey,
+          name,
+        ),
+      ),
+      [payer],
+      programId,
+    );
+  }
+  const address = await tagCountersAddress(programId, greetedPubkey);
+  const info = await connection.getAccountInfo(address);
+  if (info === null || !info.owner.equals(programId)) {
+    console.log(`${account} has no tagged greetings`);
+    return;
+  }
+  for (const entry of decodeTagCounters(info.data).entries) {
+    console.log(`${tagName(entry.tag)}: ${entry.count.toString()}`);
+  }
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
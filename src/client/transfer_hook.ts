@@ -0,0 +1,184 @@
+/**
+ * NOTES:
+ * - Client of the transfer-hook program (src/transfer-hook), which counts every
+ * transfer of a Token-2022 mint as a greeting of that mint.
+ * - Usage:
+ *   npm run transfer-hook -- init <MINT>    (signed by the payer, the mint authority)
+ *   npm run transfer-hook -- count <MINT>
+ * - The mint has to be created with its TransferHook extension pointing at the
+ * program (`spl-token create-token --program-id TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+ * --transfer-hook <HOOK_PROGRAM_ID>`). init then creates the accounts Token-2022
+ * reads the hook's extra accounts from and the counter, funding both first since
+ * the hook has no payer to charge.
+ */
+
+import {
+  Connection,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import path from 'path';
+import * as borsh from 'borsh';
+
+import {
+  EXTRA_ACCOUNT_METAS_SEED,
+  HelloWorldSchema,
+  TRANSFER_GREETINGS_SEED,
+  TransferGreetings,
+} from './generated';
+import {sendAndConfirm} from './signer';
+import {getPayerSigner, getRpcUrl, readAccountFromFile} from './utils';
+
+/**
+ * Path to the keypair of the deployed transfer-hook program
+ */
+const HOOK_KEYPAIR_PATH = path.resolve(
+  __dirname,
+  '../../dist/program/helloworld_transfer_hook-keypair.json',
+);
+
+/**
+ * Sizes of the extra account metas and transfer greetings accounts
+ */
+const EXTRA_ACCOUNT_METAS_SIZE = 51;
+const TRANSFER_GREETINGS_SIZE = 48;
+
+/**
+ * Sighash of the interface's InitializeExtraAccountMetaList
+ */
+const INITIALIZE_EXTRA_ACCOUNT_METAS = Buffer.from([
+  43, 34, 13, 49, 167, 88, 235, 235,
+]);
+
+/**
+ * Addresses of `mint`'s extra account metas and transfer greetings
+ */
+export async function transferHookAddresses(
+  hookProgramId: PublicKey,
+  mint: PublicKey,
+): Promise<{extraAccountMetas: PublicKey; transferGreetings: PublicKey}> {
+  const [extraAccountMetas] = await PublicKey.findProgramAddress(
+    [Buffer.from(EXTRA_ACCOUNT_METAS_SEED), mint.toBuffer()],
+    hookProgramId,
+  );
+  const [transferGreetings] = await PublicKey.findProgramAddress(
+    [Buffer.from(TRANSFER_GREETINGS_SEED), mint.toBuffer()],
+    hookProgramId,
+  );
+  return {extraAccountMetas, transferGreetings};
+}
+
+/**
+ * Build the instructions funding and initializing the hook's accounts for `mint`
+ */
+export async function initializeTransferHookInstructions(
+  connection: Connection,
+  hookProgramId: PublicKey,
+  mint: PublicKey,
+  mintAuthority: PublicKey,
+): Promise<TransactionInstruction[]> {
+  const {extraAccountMetas, transferGreetings} = await transferHookAddresses(
+    hookProgramId,
+    mint,
+  );
+  return [
+    SystemProgram.transfer({
+      fromPubkey: mintAuthority,
+      toPubkey: extraAccountMetas,
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        EXTRA_ACCOUNT_METAS_SIZE,
+      ),
+    }),
+    SystemProgram.transfer({
+      fromPubkey: mintAuthority,
+      toPubkey: transferGreetings,
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        TRANSFER_GREETINGS_SIZE,
+      ),
+    }),
+    new TransactionInstruction({
+      programId: hookProgramId,
+      keys: [
+        {pubkey: extraAccountMetas, isSigner: false, isWritable: true},
+        {pubkey: mint, isSigner: false, isWritable: false},
+        {pubkey: mintAuthority, isSigner: true, isWritable: false},
+        {
+          pubkey: SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+        {pubkey: transferGreetings, isSigner: false, isWritable: true},
+      ],
+      data: INITIALIZE_EXTRA_ACCOUNT_METAS,
+    }),
+  ];
+}
+
+/**
+ * Fetch `mint`'s transfer greetings, or null before init
+ */
+export async function getTransferGreetings(
+  connection: Connection,
+  hookProgramId: PublicKey,
+  mint: PublicKey,
+): Promise<TransferGreetings | null> {
+  const {transferGreetings} = await transferHookAddresses(hookProgramId, mint);
+  const info = await connection.getAccountInfo(transferGreetings);
+  if (info === null || !info.owner.equals(hookProgramId)) {
+    return null;
+  }
+  return borsh.deserialize(HelloWorldSchema, TransferGreetings, info.data);
+}
+
+async function main() {
+  const [command, mintArg] = process.argv.slice(2);
+  if ((command !== 'init' && command !== 'count') || !mintArg) {
+    throw new Error('Usage: transfer-hook init|count <MINT>');
+  }
+  const mint = new PublicKey(mintArg);
+  const connection = new Connection(await getRpcUrl(), 'confirmed');
+  const hookProgramId = (await readAccountFromFile(HOOK_KEYPAIR_PATH))
+    .publicKey;
+
+  if (command === 'init') {
+    const payer = await getPayerSigner();
+    await sendAndConfirm(
+      connection,
+      new Transaction().add(
+        ...(await initializeTransferHookInstructions(
+          connection,
+          hookProgramId,
+          mint,
+          payer.publicKey,
+        )),
+      ),
+      [payer],
+    );
+    console.log(`Transfers of ${mint.toBase58()} now count as greetings`);
+    return;
+  }
+
+  const greetings = await getTransferGreetings(connection, hookProgramId, mint);
+  if (greetings === null) {
+    throw new Error(`The hook isn't initialized for ${mint.toBase58()}`);
+  }
+  console.log(
+    `${mint.toBase58()} greeted`,
+    greetings.counter.toString(),
+    'time(s) by transfer, moving',
+    greetings.volume.toString(),
+    'base units',
+  );
+}
+
+if (require.main === module) {
+  main().then(
+    () => process.exit(),
+    err => {
+      console.error(err);
+      process.exit(-1);
+    },
+  );
+}
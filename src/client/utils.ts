@@ -6,7 +6,22 @@ import os from 'os';
 import fs from 'mz/fs';
 import path from 'path';
 import yaml from 'yaml';
-import {Account, Connection} from '@solana/web3.js';
+import http from 'http';
+import https from 'https';
+import {
+  Account,
+  Connection,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+} from '@solana/web3.js';
+
+import {getProfile} from './config';
+import {
+  Signer,
+  accountFromJson,
+  resolveAnySigner,
+  resolveSigner,
+} from './signer';
 
 // Allows for a pause/sleep before continuing the execution
 export function sleep(ms: number): Promise<void> {
@@ -21,14 +36,43 @@ export async function newAccountWithLamports(
   // which is an Object that represents the Private & Public Keys for an
   // account that will be created later.
   const account = new Account();
-  const signature = await connection.requestAirdrop(
-    account.publicKey,
-    lamports,
-  );
-  await connection.confirmTransaction(signature);
+  await requestAirdropWithRetry(connection, account.publicKey, lamports);
   return account;
 }
 
+/**
+ * Airdrop `lamports`, backing off and retrying while the faucet rate limits us.
+ * Public faucets cap each request, so amounts over 1 SOL are split up.
+ */
+export async function requestAirdropWithRetry(
+  connection: Connection,
+  pubkey: PublicKey,
+  lamports: number,
+  attempts = 5,
+): Promise<void> {
+  for (let remaining = lamports; remaining > 0; ) {
+    const amount = Math.min(remaining, LAMPORTS_PER_SOL);
+    for (let attempt = 1; ; attempt++) {
+      try {
+        const signature = await connection.requestAirdrop(pubkey, amount);
+        await connection.confirmTransaction(signature);
+        break;
+      } catch (err) {
+        if (attempt >= attempts) {
+          throw err;
+        }
+        const delayMs = 1000 * 2 ** attempt;
+        console.warn(
+          `Airdrop failed (${(err as Error).message}),`,
+          `retrying in ${delayMs / 1000}s`,
+        );
+        await sleep(delayMs);
+      }
+    }
+    remaining -= amount;
+  }
+}
+
 /**
  * @private
  */
@@ -46,9 +90,14 @@ async function getConfig(): Promise<any> {
 }
 
 /**
- * Load and parse the Solana CLI config file to determine which RPC url to use
+ * Load and parse the Solana CLI config file to determine which RPC url to use,
+ * unless the helloworld profile (config.ts) sets one
  */
 export async function getRpcUrl(): Promise<string> {
+  const {url} = await getProfile();
+  if (url) {
+    return url;
+  }
   try {
     const config = await getConfig();
     if (!config.json_rpc_url) throw new Error('Missing RPC URL');
@@ -62,28 +111,62 @@ export async function getRpcUrl(): Promise<string> {
 }
 
 /**
- * Load and parse the Solana CLI config file to determine which payer to use
+ * RPC urls to fail over between: the comma-separated RPC_URLS environment variable,
+ * or just the CLI config's url
  */
-export async function getPayer(): Promise<Account> {
-  // NOTE Configure/Create a new keypair (NOT an account!) that represents the account
-  // from which monies/lamports will come out in order to pay for all the
-  // transactions that we're about to execute. Again, every transaction you
-  // request requires money to pay for the transaction. Creating the keypair is
-  // one of the first steps into creating an account.
+export async function getRpcUrls(): Promise<string[]> {
+  const urls = (process.env.RPC_URLS || '')
+    .split(',')
+    .map(url => url.trim())
+    .filter(url => url.length > 0);
+  return urls.length > 0 ? urls : [await getRpcUrl()];
+}
+
+/**
+ * Load and parse the Solana CLI config file to determine which payer to use.
+ * PAYER_KEYPAIR and then the helloworld profile override it; all take any signer
+ * source from signer.ts.
+ */
+async function getPayerSource(): Promise<string | undefined> {
+  if (process.env.PAYER_KEYPAIR) {
+    return process.env.PAYER_KEYPAIR;
+  }
+  const {keypair} = await getProfile();
+  if (keypair) {
+    return keypair;
+  }
   try {
     const config = await getConfig();
     // NOTE In this example, you can use your own wallet's private/public keypair
     // in order to generate the account. You don't have to do this if you don't want.
     if (!config.keypair_path) throw new Error('Missing keypair path');
-    return readAccountFromFile(config.keypair_path);
+    return config.keypair_path;
   } catch (err) {
     console.warn(
       'Failed to read keypair from CLI config file, falling back to new random keypair',
     );
-    return new Account();
+    return undefined;
   }
 }
 
+export async function getPayer(): Promise<Account> {
+  // NOTE Configure/Create a new keypair (NOT an account!) that represents the account
+  // from which monies/lamports will come out in order to pay for all the
+  // transactions that we're about to execute. Again, every transaction you
+  // request requires money to pay for the transaction. Creating the keypair is
+  // one of the first steps into creating an account.
+  const source = await getPayerSource();
+  return source ? resolveSigner(source) : new Account();
+}
+
+/**
+ * Like getPayer(), but the payer may also be a Ledger (usb://ledger)
+ */
+export async function getPayerSigner(): Promise<Signer> {
+  const source = await getPayerSource();
+  return source ? resolveAnySigner(source) : new Account();
+}
+
 /**
  * Create an Account from a keypair file
  */
@@ -92,6 +175,34 @@ export async function readAccountFromFile(filePath: string): Promise<Account> {
   // and returns/generates a "real" keypair object that will be later used to create a
   // REAL Solana Account existing on the network. A little confusing...
   const keypairString = await fs.readFile(filePath, {encoding: 'utf8'});
-  const keypairBuffer = Buffer.from(JSON.parse(keypairString));
-  return new Account(keypairBuffer);
+  return accountFromJson(keypairString);
+}
+
+/**
+ * POST a JSON body and parse the JSON response
+ */
+export function postJson(url: string, body: object): Promise<any> {
+  const data = JSON.stringify(body);
+  const request: typeof http.request = url.startsWith('https:')
+    ? https.request
+    : http.request;
+  return new Promise((resolve, reject) => {
+    const req = request(
+      url,
+      {method: 'POST', headers: {'Content-Type': 'application/json'}},
+      res => {
+        let text = '';
+        res.on('data', (chunk: Buffer) => (text += chunk.toString()));
+        res.on('end', () => {
+          try {
+            resolve(JSON.parse(text));
+          } catch (err) {
+            reject(new Error(`${url} returned ${text}`));
+          }
+        });
+      },
+    );
+    req.on('error', reject);
+    req.end(data);
+  });
 }
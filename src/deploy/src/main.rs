@@ -0,0 +1,434 @@
+/*
+NOTES:
+- One command for what otherwise takes four: build the BPF program, deploy it (or
+upgrade it in place, if the program id already exists) with the solana CLI, record the
+result in dist/program/deployments.json and check that the bytes on chain match the
+build we just made.
+- deployments.json is keyed by RPC url, so one checkout can track localnet, devnet and
+mainnet deploys side by side. The JS client looks up the url it's connected to there
+before falling back to dist/program/helloworld-keypair.json.
+- The upgrade authority and buffer are keypair paths passed straight through to
+`solana program deploy`. Passing a buffer keypair lets an interrupted deploy be
+resumed (`solana program deploy --buffer <same keypair>`) instead of re-uploading.
+- Upgrading a live program is safer split in two, so the new bytes can be reviewed
+before anything changes on chain:
+  1. `deploy write-buffer --buffer-authority <PUBKEY>` uploads the build into a buffer
+  account, checks the buffer against the local build, then hands the buffer to the
+  program's upgrade authority. Once handed over, only that authority can use (or close)
+  the buffer, so nobody can swap the bytes between review and upgrade.
+  2. `deploy upgrade --buffer <BUFFER>` swaps the program to the buffer's bytes, signed
+  by the upgrade authority, then records and verifies like a plain deploy.
+- When the upgrade authority is a Squads multisig vault, step 2 can't be signed here.
+Hand the buffer to the vault instead; write-buffer prints the Upgrade instruction (as
+JSON, data in base58) for the multisig to propose, approve and execute. The buffer's
+rent goes to the spill account, which defaults to the new authority.
+- Run with `cargo run -p helloworld-deploy -- [write-buffer|upgrade] --url devnet [options]`.
+*/
+use helloworld_verify::{dump_program, executable_hash};
+use serde_json::{json, Value};
+use solana_program::{bpf_loader_upgradeable, instruction::Instruction, pubkey::Pubkey};
+use std::{
+    env, fs,
+    process::{self, Command},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const USAGE: &str = "Usage: deploy [write-buffer|upgrade] --url <URL> [options]
+
+Commands:
+  (none)         Build, deploy or upgrade in place, record and verify
+  write-buffer   Build, write the program to a buffer and hand it to --buffer-authority
+  upgrade        Upgrade the program from a written --buffer, record and verify
+
+Options:
+  --url <URL>                    RPC url or moniker (localhost, devnet, testnet, mainnet-beta)
+  --program-id <KEYPAIR|PUBKEY>  Program (defaults to dist/program/helloworld-keypair.json)
+  --upgrade-authority <KEYPAIR>  Upgrade authority (defaults to the CLI config keypair)
+  --buffer <KEYPAIR|PUBKEY>      Buffer account to write through or upgrade from
+  --buffer-authority <PUBKEY>    write-buffer: hand the buffer to this authority
+  --spill <PUBKEY>               write-buffer: receiver of the buffer's rent on upgrade
+  --skip-build                   Use the existing dist/program/helloworld.so";
+
+const SO_PATH: &str = "dist/program/helloworld.so";
+const DEPLOYMENTS_PATH: &str = "dist/program/deployments.json";
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Deploy,
+    WriteBuffer,
+    Upgrade,
+}
+
+struct Args {
+    mode: Mode,
+    url: String,
+    program_id: String,
+    upgrade_authority: Option<String>,
+    buffer: Option<String>,
+    buffer_authority: Option<Pubkey>,
+    spill: Option<Pubkey>,
+    skip_build: bool,
+}
+
+fn parse_pubkey(value: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(value).map_err(|err| format!("Invalid pubkey {}: {:?}", value, err))
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut url = None;
+    let mut args = Args {
+        mode: Mode::Deploy,
+        url: String::new(),
+        program_id: "dist/program/helloworld-keypair.json".to_string(),
+        upgrade_authority: None,
+        buffer: None,
+        buffer_authority: None,
+        spill: None,
+        skip_build: false,
+    };
+    let mut iter = env::args().skip(1).peekable();
+    match iter.peek().map(String::as_str) {
+        Some("write-buffer") => args.mode = Mode::WriteBuffer,
+        Some("upgrade") => args.mode = Mode::Upgrade,
+        _ => {}
+    }
+    if args.mode != Mode::Deploy {
+        iter.next();
+    }
+    while let Some(flag) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "--url" | "-u" => url = Some(value()?),
+            "--program-id" => args.program_id = value()?,
+            "--upgrade-authority" => args.upgrade_authority = Some(value()?),
+            "--buffer" => args.buffer = Some(value()?),
+            "--buffer-authority" => args.buffer_authority = Some(parse_pubkey(&value()?)?),
+            "--spill" => args.spill = Some(parse_pubkey(&value()?)?),
+            "--skip-build" => args.skip_build = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument {}", flag)),
+        }
+    }
+    args.url = rpc_url(&url.ok_or("Missing --url")?);
+    if args.mode == Mode::Upgrade && args.buffer.is_none() {
+        return Err("upgrade needs the --buffer to upgrade from".to_string());
+    }
+    Ok(args)
+}
+
+/// Expand the solana CLI's cluster monikers, so deployments are keyed by the same
+/// url the client reads from the CLI config
+fn rpc_url(url: &str) -> String {
+    match url {
+        "localhost" | "l" => "http://localhost:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+/// Run a command, returning its stdout
+fn run_command(cmd: &mut Command) -> Result<String, String> {
+    let output = cmd
+        .output()
+        .map_err(|err| format!("Failed to run {:?}: {}", cmd, err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{:?} exited with {}\n{}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Address of a pubkey argument, or of the keypair file it names
+fn resolve_pubkey(value: &str) -> Result<Pubkey, String> {
+    if let Ok(pubkey) = Pubkey::from_str(value) {
+        return Ok(pubkey);
+    }
+    let out = run_command(Command::new("solana-keygen").arg("pubkey").arg(value))?;
+    parse_pubkey(out.trim())
+}
+
+/// Read a `programId`/`buffer` style field from the CLI's `--output json`
+fn json_pubkey(out: &str, field: &str) -> Result<Pubkey, String> {
+    let value = serde_json::from_str::<Value>(out)
+        .ok()
+        .and_then(|output| output[field].as_str().map(str::to_string))
+        .ok_or_else(|| format!("No {} in solana CLI output: {}", field, out))?;
+    parse_pubkey(&value)
+}
+
+fn build() -> Result<(), String> {
+    println!("Building {}", SO_PATH);
+    run_command(Command::new("cargo").args([
+        "build-bpf",
+        "--manifest-path=src/program-rust/Cargo.toml",
+        "--bpf-out-dir=dist/program",
+    ]))
+    .map(|_| ())
+}
+
+fn deploy(args: &Args) -> Result<Pubkey, String> {
+    let mut cmd = Command::new("solana");
+    cmd.args(["program", "deploy", "--output", "json", "--url", &args.url])
+        .args(["--program-id", &args.program_id]);
+    if let Some(authority) = &args.upgrade_authority {
+        cmd.args(["--upgrade-authority", authority]);
+    }
+    if let Some(buffer) = &args.buffer {
+        cmd.args(["--buffer", buffer]);
+    }
+    // NOTE Without a program file, `solana program deploy` upgrades from the buffer
+    if args.mode == Mode::Deploy {
+        cmd.arg(SO_PATH);
+        println!("Deploying to {}", args.url);
+    } else {
+        println!("Upgrading on {} from buffer", args.url);
+    }
+    json_pubkey(&run_command(&mut cmd)?, "programId")
+}
+
+/// Write the build to a buffer account, returning the buffer's address
+fn write_buffer(args: &Args) -> Result<Pubkey, String> {
+    let mut cmd = Command::new("solana");
+    cmd.args([
+        "program",
+        "write-buffer",
+        "--output",
+        "json",
+        "--url",
+        &args.url,
+    ]);
+    if let Some(buffer) = &args.buffer {
+        cmd.args(["--buffer", buffer]);
+    }
+    cmd.arg(SO_PATH);
+    println!("Writing buffer on {}", args.url);
+    json_pubkey(&run_command(&mut cmd)?, "buffer")
+}
+
+fn set_buffer_authority(url: &str, buffer: &Pubkey, authority: &Pubkey) -> Result<(), String> {
+    run_command(Command::new("solana").args([
+        "program",
+        "set-buffer-authority",
+        "--url",
+        url,
+        &buffer.to_string(),
+        "--new-buffer-authority",
+        &authority.to_string(),
+    ]))
+    .map(|_| ())
+}
+
+/// The Upgrade instruction as JSON, for a multisig upgrade authority to propose
+fn instruction_json(instruction: &Instruction) -> Value {
+    json!({
+        "programId": instruction.program_id.to_string(),
+        "accounts": instruction
+            .accounts
+            .iter()
+            .map(|meta| json!({
+                "pubkey": meta.pubkey.to_string(),
+                "isSigner": meta.is_signer,
+                "isWritable": meta.is_writable,
+            }))
+            .collect::<Vec<_>>(),
+        "data": bs58::encode(&instruction.data).into_string(),
+    })
+}
+
+/// Record the deploy for `url` in deployments.json, keeping other clusters' entries
+fn record_deployment(deployments: &mut Value, url: &str, deployment: Value) {
+    if !deployments.is_object() {
+        *deployments = json!({});
+    }
+    deployments[url] = deployment;
+}
+
+fn read_so() -> Result<Vec<u8>, String> {
+    fs::read(SO_PATH).map_err(|err| format!("Failed to read {}: {}", SO_PATH, err))
+}
+
+/// Build, write and check the buffer, then hand it over. Returns whether the buffer
+/// matches the local build.
+fn run_write_buffer(args: &Args) -> Result<bool, String> {
+    let local_hash = executable_hash(&read_so()?);
+    let buffer = write_buffer(args)?;
+    println!("Buffer: {}", buffer);
+
+    let buffer_hash = executable_hash(&dump_program(Some(&args.url), &buffer)?);
+    println!("Local build: {}", local_hash);
+    println!("Buffer:      {}", buffer_hash);
+    if buffer_hash != local_hash {
+        return Ok(false);
+    }
+
+    let program_id = resolve_pubkey(&args.program_id)?;
+    match args.buffer_authority {
+        Some(authority) => {
+            set_buffer_authority(&args.url, &buffer, &authority)?;
+            println!("Buffer authority handed to {}", authority);
+            let spill = args.spill.unwrap_or(authority);
+            let upgrade = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &authority, &spill);
+            println!(
+                "Upgrade instruction, for an upgrade authority that can't sign here:\n{}",
+                serde_json::to_string_pretty(&instruction_json(&upgrade)).unwrap()
+            );
+        }
+        None => println!("Buffer authority unchanged (the CLI config keypair)"),
+    }
+    println!(
+        "Upgrade with: deploy upgrade --url {} --program-id {} --buffer {}",
+        args.url, program_id, buffer
+    );
+    Ok(true)
+}
+
+/// Deploy or upgrade, record the deployment and check the program against the local
+/// build
+fn run_deploy(args: &Args) -> Result<bool, String> {
+    let local = read_so()?;
+    let program_id = deploy(args)?;
+    println!("Program id: {}", program_id);
+
+    let upgrade_authority = match &args.upgrade_authority {
+        Some(authority) => Some(resolve_pubkey(authority)?.to_string()),
+        None => None,
+    };
+    let deployed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let local_hash = executable_hash(&local);
+
+    let mut deployments = fs::read_to_string(DEPLOYMENTS_PATH)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_else(|| json!({}));
+    record_deployment(
+        &mut deployments,
+        &args.url,
+        json!({
+            "programId": program_id.to_string(),
+            "upgradeAuthority": upgrade_authority,
+            "hash": local_hash,
+            "deployedAt": deployed_at,
+        }),
+    );
+    let text = serde_json::to_string_pretty(&deployments).unwrap();
+    fs::write(DEPLOYMENTS_PATH, text + "\n")
+        .map_err(|err| format!("Failed to write {}: {}", DEPLOYMENTS_PATH, err))?;
+    println!("Recorded in {}", DEPLOYMENTS_PATH);
+
+    let on_chain_hash = executable_hash(&dump_program(Some(&args.url), &program_id)?);
+    println!("Local build:    {}", local_hash);
+    println!("On-chain build: {}", on_chain_hash);
+    Ok(local_hash == on_chain_hash)
+}
+
+fn run() -> Result<bool, String> {
+    let args = parse_args()?;
+    // NOTE An upgrade is checked against the build the buffer was written from
+    if !args.skip_build && args.mode != Mode::Upgrade {
+        build()?;
+    }
+    match args.mode {
+        Mode::WriteBuffer => run_write_buffer(&args),
+        Mode::Deploy | Mode::Upgrade => run_deploy(&args),
+    }
+}
+
+fn main() {
+    match run() {
+        Ok(true) => println!("Verified: on-chain bytes match the local build"),
+        Ok(false) => {
+            eprintln!("Mismatch: on-chain bytes do NOT match the local build");
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rpc_url_expands_monikers() {
+        assert_eq!(rpc_url("devnet"), "https://api.devnet.solana.com");
+        assert_eq!(rpc_url("l"), "http://localhost:8899");
+        assert_eq!(
+            rpc_url("https://rpc.example.com"),
+            "https://rpc.example.com"
+        );
+    }
+
+    #[test]
+    fn test_record_deployment_keeps_other_clusters() {
+        let mut deployments = json!({ "http://localhost:8899": { "programId": "a" } });
+        record_deployment(
+            &mut deployments,
+            "https://api.devnet.solana.com",
+            json!({ "programId": "b" }),
+        );
+        assert_eq!(deployments["http://localhost:8899"]["programId"], "a");
+        assert_eq!(
+            deployments["https://api.devnet.solana.com"]["programId"],
+            "b"
+        );
+
+        let mut corrupt = json!([]);
+        record_deployment(&mut corrupt, "u", json!({}));
+        assert!(corrupt.is_object());
+    }
+
+    #[test]
+    fn test_upgrade_instruction_json() {
+        let program_id = Pubkey::new_unique();
+        let buffer = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let upgrade = bpf_loader_upgradeable::upgrade(&program_id, &buffer, &vault, &vault);
+        let value = instruction_json(&upgrade);
+
+        assert_eq!(value["programId"], bpf_loader_upgradeable::id().to_string());
+        // Upgrade is variant 3 of the loader's bincode-encoded instruction enum
+        assert_eq!(
+            bs58::decode(value["data"].as_str().unwrap())
+                .into_vec()
+                .unwrap(),
+            vec![3, 0, 0, 0]
+        );
+        let accounts = value["accounts"].as_array().unwrap();
+        assert_eq!(accounts[2]["pubkey"], buffer.to_string());
+        let signers: Vec<_> = accounts
+            .iter()
+            .filter(|meta| meta["isSigner"] == true)
+            .collect();
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0]["pubkey"], vault.to_string());
+    }
+
+    #[test]
+    fn test_json_pubkey() {
+        let buffer = Pubkey::new_unique();
+        let out = format!("{{\"buffer\": \"{}\"}}", buffer);
+        assert_eq!(json_pubkey(&out, "buffer"), Ok(buffer));
+        assert!(json_pubkey(&out, "programId").is_err());
+        assert!(json_pubkey("Program Id: abc", "programId").is_err());
+    }
+}
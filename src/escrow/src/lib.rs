@@ -0,0 +1,469 @@
+/*
+NOTES:
+- A second example program, the classic escrow: someone offers tokens of one mint for
+tokens of another and whoever takes the offer gets them, without either side having to
+trust the other.
+- Written the way helloworld is, on the same crate: the layouts and builders are in
+src/program-rust/src/escrow.rs, token accounts are read with helloworld::token, program
+accounts checked with validate_program_account() and accounts closed with
+close_account(). The tests use helloworld's test_utils and fixtures (the `test-utils`
+feature).
+- Build with `npm run build:escrow` and deploy the .so from dist/program; the client is
+`npm run escrow`.
+*/
+use borsh::{BorshDeserialize, BorshSerialize};
+use helloworld::{
+    close_account,
+    escrow::{self, Escrow, EscrowInstruction, ESCROW_SEED, ESCROW_SIZE},
+    token::{self, TokenAccount},
+    validate_program_account,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+
+// The address the escrow program is deployed at
+// NOTE After your first deploy, replace this with the output of
+// `solana address -k dist/program/helloworld_escrow-keypair.json`.
+solana_program::declare_id!("6RtyWdYwJAdLSoWUzdjftWuAkSGQLoeBw8yqW4o6eDTi");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = EscrowInstruction::try_from_slice(instruction_data).map_err(|err| {
+        msg!("Can't read the instruction, {}", err);
+        ProgramError::InvalidInstructionData
+    })?;
+    match instruction {
+        EscrowInstruction::InitEscrow {
+            offered_amount,
+            expected_amount,
+        } => process_init_escrow(program_id, accounts, offered_amount, expected_amount),
+        EscrowInstruction::Exchange {
+            offered_amount,
+            expected_amount,
+        } => process_exchange(program_id, accounts, offered_amount, expected_amount),
+        EscrowInstruction::Cancel => process_cancel(program_id, accounts),
+    }
+}
+
+/// Read an SPL token account, or fail naming it `what`
+fn load_token_account(account: &AccountInfo, what: &str) -> Result<TokenAccount, ProgramError> {
+    let parsed = if *account.owner == token::id() {
+        TokenAccount::parse(&account.data.borrow())
+    } else {
+        None
+    };
+    parsed.ok_or_else(|| {
+        msg!("{} is not a token account ({})", account.key, what);
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Read an escrow, along with its bump seed
+fn load_escrow(program_id: &Pubkey, account: &AccountInfo) -> Result<(Escrow, u8), ProgramError> {
+    let escrow = if account.owner == program_id {
+        Escrow::try_from_slice(&account.data.borrow()).ok()
+    } else {
+        None
+    };
+    if let Some(escrow) = escrow {
+        let initializer = Pubkey::new_from_array(escrow.initializer);
+        let (address, bump) = escrow::escrow_address(program_id, &initializer);
+        if *account.key == address {
+            return Ok((escrow, bump));
+        }
+    }
+    msg!("{} is not an escrow", account.key);
+    Err(ProgramError::InvalidSeeds)
+}
+
+/// Move all of the vault's tokens to `destination` and close the vault, signed by the
+/// escrow
+fn empty_vault<'a>(
+    escrow_account: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    rent_recipient: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> Result<u64, ProgramError> {
+    // NOTE All of it rather than the amount offered, since tokens sent to the vault on
+    // top of the offer would otherwise keep it from closing
+    let amount = load_token_account(vault, "the vault")?.amount;
+    invoke_signed(
+        &token::transfer(vault.key, destination.key, escrow_account.key, amount),
+        &[
+            vault.clone(),
+            destination.clone(),
+            escrow_account.clone(),
+            token_program.clone(),
+        ],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &token::close_account(vault.key, rent_recipient.key, escrow_account.key),
+        &[
+            vault.clone(),
+            rent_recipient.clone(),
+            escrow_account.clone(),
+            token_program.clone(),
+        ],
+        &[seeds],
+    )?;
+    Ok(amount)
+}
+
+/// Offer tokens for tokens of another mint
+pub fn process_init_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    offered_amount: u64,
+    expected_amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow_account = next_account_info(accounts_iter)?;
+    let initializer = next_account_info(accounts_iter)?;
+    let source = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let receive_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    if !initializer.is_signer {
+        msg!("The initializer must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if offered_amount == 0 || expected_amount == 0 {
+        msg!("An escrow offers and expects at least one token");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (address, bump) = escrow::escrow_address(program_id, initializer.key);
+    if *escrow_account.key != address {
+        msg!(
+            "{} is not the escrow of {}",
+            escrow_account.key,
+            initializer.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if escrow_account.owner == program_id {
+        msg!("{} already has an open escrow", initializer.key);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    validate_program_account(token_program, &token::id())?;
+    validate_program_account(system_program, &system_program::id())?;
+    let offered = load_token_account(source, "the source")?;
+    let vault_account = load_token_account(vault, "the vault")?;
+    if vault_account.owner != address.to_bytes() || vault_account.mint != offered.mint {
+        msg!(
+            "{} is not a token account of the offered mint owned by the escrow",
+            vault.key
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if load_token_account(receive_account, "the receive account")?.owner
+        != initializer.key.to_bytes()
+    {
+        msg!("{} is not the initializer's", receive_account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            escrow_account.key,
+            Rent::get()?.minimum_balance(ESCROW_SIZE),
+            ESCROW_SIZE as u64,
+            program_id,
+        ),
+        &[
+            initializer.clone(),
+            escrow_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[ESCROW_SEED, initializer.key.as_ref(), &[bump]]],
+    )?;
+    invoke(
+        &token::transfer(source.key, vault.key, initializer.key, offered_amount),
+        &[
+            source.clone(),
+            vault.clone(),
+            initializer.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    let escrow = Escrow {
+        initializer: initializer.key.to_bytes(),
+        vault: vault.key.to_bytes(),
+        receive_account: receive_account.key.to_bytes(),
+        offered_amount,
+        expected_amount,
+    };
+    escrow
+        .serialize(&mut &mut escrow_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            msg!("Can't write the escrow, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    msg!("Offered {} for {}", offered_amount, expected_amount);
+    Ok(())
+}
+
+/// Take an offer
+pub fn process_exchange(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    offered_amount: u64,
+    expected_amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow_account = next_account_info(accounts_iter)?;
+    let taker = next_account_info(accounts_iter)?;
+    let taker_source = next_account_info(accounts_iter)?;
+    let taker_destination = next_account_info(accounts_iter)?;
+    let receive_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let initializer = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    if !taker.is_signer {
+        msg!("The taker must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (escrow, bump) = load_escrow(program_id, escrow_account)?;
+    if offered_amount != escrow.offered_amount || expected_amount != escrow.expected_amount {
+        msg!(
+            "The offer is {} for {}",
+            escrow.offered_amount,
+            escrow.expected_amount
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if receive_account.key.to_bytes() != escrow.receive_account
+        || vault.key.to_bytes() != escrow.vault
+        || initializer.key.to_bytes() != escrow.initializer
+    {
+        msg!("Not the escrow's receive account, vault and initializer");
+        return Err(ProgramError::InvalidArgument);
+    }
+    validate_program_account(token_program, &token::id())?;
+
+    invoke(
+        &token::transfer(
+            taker_source.key,
+            receive_account.key,
+            taker.key,
+            expected_amount,
+        ),
+        &[
+            taker_source.clone(),
+            receive_account.clone(),
+            taker.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    let amount = empty_vault(
+        escrow_account,
+        vault,
+        taker_destination,
+        initializer,
+        token_program,
+        &[ESCROW_SEED, initializer.key.as_ref(), &[bump]],
+    )?;
+    close_account(escrow_account, initializer)?;
+    msg!("{} took {} for {}", taker.key, amount, expected_amount);
+    Ok(())
+}
+
+/// Withdraw an offer
+pub fn process_cancel(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let escrow_account = next_account_info(accounts_iter)?;
+    let initializer = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    if !initializer.is_signer {
+        msg!("The initializer must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (escrow, bump) = load_escrow(program_id, escrow_account)?;
+    if initializer.key.to_bytes() != escrow.initializer || vault.key.to_bytes() != escrow.vault {
+        msg!("Not the escrow's initializer and vault");
+        return Err(ProgramError::InvalidArgument);
+    }
+    validate_program_account(token_program, &token::id())?;
+
+    let amount = empty_vault(
+        escrow_account,
+        vault,
+        destination,
+        initializer,
+        token_program,
+        &[ESCROW_SEED, initializer.key.as_ref(), &[bump]],
+    )?;
+    close_account(escrow_account, initializer)?;
+    msg!("Returned {} to {}", amount, destination.key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helloworld::{fixtures::account, test_utils::use_test_sysvars, token::token_account};
+    use solana_program::bpf_loader;
+
+    #[test]
+    fn test_escrow_lifecycle() {
+        let program_id = id();
+        let (offered_mint, expected_mint) = ([1; 32], [2; 32]);
+        let (initializer_key, taker_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (escrow_key, _) = escrow::escrow_address(&program_id, &initializer_key);
+        let system = system_program::id();
+        let token_program_id = token::id();
+        let token_data =
+            |mint, owner: &Pubkey, amount| token_account(mint, owner.to_bytes(), amount);
+        let new_token_account = |mint, owner: &Pubkey, amount| {
+            account(
+                Pubkey::new_unique(),
+                false,
+                2_000,
+                token_data(mint, owner, amount),
+                token_program_id,
+            )
+        };
+        // NOTE The create_account and token CPIs are no-ops in unit tests, so the escrow
+        // starts out allocated and no tokens move
+        let escrow_account = account(escrow_key, false, 1_000, vec![0; ESCROW_SIZE], system);
+        let initializer = account(initializer_key, true, 0, vec![], system);
+        let source = new_token_account(offered_mint, &initializer_key, 500);
+        let vault = new_token_account(offered_mint, &escrow_key, 0);
+        let receive = new_token_account(expected_mint, &initializer_key, 0);
+        let token_program = account(token_program_id, false, 0, vec![], bpf_loader::id());
+        let system_program = account(system, false, 0, vec![], bpf_loader::id());
+
+        use_test_sysvars();
+        let init = |offered_amount, expected_amount| {
+            EscrowInstruction::InitEscrow {
+                offered_amount,
+                expected_amount,
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let init_accounts = vec![
+            escrow_account.clone(),
+            initializer.clone(),
+            source.clone(),
+            vault.clone(),
+            receive.clone(),
+            token_program.clone(),
+            system_program,
+        ];
+        assert_eq!(
+            process_instruction(&program_id, &init_accounts, &init(0, 2)),
+            Err(ProgramError::InvalidArgument)
+        );
+        // The vault has to be the escrow's and hold the offered mint
+        let mut not_vault = init_accounts.clone();
+        not_vault[3] = new_token_account(offered_mint, &initializer_key, 0);
+        assert_eq!(
+            process_instruction(&program_id, &not_vault, &init(500, 2)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        not_vault[3] = new_token_account(expected_mint, &escrow_key, 0);
+        assert_eq!(
+            process_instruction(&program_id, &not_vault, &init(500, 2)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        process_instruction(&program_id, &init_accounts, &init(500, 2)).unwrap();
+        let escrow = Escrow::try_from_slice(&escrow_account.data.borrow()).unwrap();
+        assert_eq!(
+            escrow,
+            Escrow {
+                initializer: initializer_key.to_bytes(),
+                vault: vault.key.to_bytes(),
+                receive_account: receive.key.to_bytes(),
+                offered_amount: 500,
+                expected_amount: 2,
+            }
+        );
+
+        let mut escrow_account = escrow_account;
+        escrow_account.owner = &ID;
+        let mut init_accounts = init_accounts;
+        init_accounts[0] = escrow_account.clone();
+        assert_eq!(
+            process_instruction(&program_id, &init_accounts, &init(500, 2)),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        let exchange = |offered_amount, expected_amount| {
+            EscrowInstruction::Exchange {
+                offered_amount,
+                expected_amount,
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let taker = account(taker_key, true, 0, vec![], system);
+        let exchange_accounts = vec![
+            escrow_account.clone(),
+            taker,
+            new_token_account(expected_mint, &taker_key, 2),
+            new_token_account(offered_mint, &taker_key, 0),
+            receive,
+            vault.clone(),
+            initializer.clone(),
+            token_program.clone(),
+        ];
+        // Not at other terms than the offer's
+        assert_eq!(
+            process_instruction(&program_id, &exchange_accounts, &exchange(500, 1)),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut other_vault = exchange_accounts.clone();
+        other_vault[5] = new_token_account(offered_mint, &escrow_key, 500);
+        assert_eq!(
+            process_instruction(&program_id, &other_vault, &exchange(500, 2)),
+            Err(ProgramError::InvalidArgument)
+        );
+        let cancel = EscrowInstruction::Cancel.try_to_vec().unwrap();
+        let cancel_accounts = vec![
+            escrow_account.clone(),
+            initializer.clone(),
+            vault,
+            source,
+            token_program,
+        ];
+        let mut not_initializer = cancel_accounts.clone();
+        not_initializer[1] = exchange_accounts[1].clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_initializer, &cancel),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        process_instruction(&program_id, &exchange_accounts, &exchange(500, 2)).unwrap();
+        // The escrow is closed, its rent back with the initializer
+        assert_eq!(
+            (escrow_account.lamports(), initializer.lamports()),
+            (0, 1_000)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &cancel_accounts, &cancel),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// Regenerate include/helloworld.h from the extern "C" functions in src/lib.rs
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("Unable to generate C bindings")
+        .write_to_file(format!("{}/include/helloworld.h", crate_dir));
+}
@@ -0,0 +1,412 @@
+#ifndef HELLOWORLD_H
+#define HELLOWORLD_H
+
+/* Generated by cbindgen from src/ffi/src/lib.rs. Do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Success
+#define HELLOWORLD_OK 0
+
+// A required pointer argument was null
+#define HELLOWORLD_ERR_NULL_POINTER -1
+
+// The input bytes are not a valid account / the input string is not valid UTF-8
+#define HELLOWORLD_ERR_INVALID_DATA -2
+
+// The output buffer is too small; `*written` holds the size needed
+#define HELLOWORLD_ERR_BUFFER_TOO_SMALL -3
+
+// Decode a greeting account's data and copy its message (UTF-8, not NUL-terminated)
+// into `txt_out`. Trailing zero padding in the account data is ignored.
+//
+// # Safety
+// `data` must point to `data_len` readable bytes, `txt_out` to `txt_out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_decode_greeting_account(const uint8_t *data,
+                                           uintptr_t data_len,
+                                           uint8_t *txt_out,
+                                           uintptr_t txt_out_len,
+                                           uintptr_t *written);
+
+// Build the instruction data for Greet with the NUL-terminated UTF-8 message `txt`.
+// Accounts: 0. `[writable]` the greeting account, 1. `[]` the config account
+// (optional, without it the counter doesn't decay).
+//
+// # Safety
+// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_greet_instruction_data(const char *txt,
+                                          uint8_t *out,
+                                          uintptr_t out_len,
+                                          uintptr_t *written);
+
+// Build the instruction data for Version. Accounts: none.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_version_instruction_data(uint8_t *out, uintptr_t out_len, uintptr_t *written);
+
+// Build the instruction data for ExportState.
+// Accounts: 0. `[]` the greeting account.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_export_state_instruction_data(uint8_t *out,
+                                                 uintptr_t out_len,
+                                                 uintptr_t *written);
+
+// Build the instruction data for ImportState.
+// Accounts: 0. `[writable]` the greeting account, 1. `[]` the account to import from.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_import_state_instruction_data(uint8_t *out,
+                                                 uintptr_t out_len,
+                                                 uintptr_t *written);
+
+// Build the instruction data for InitConfig.
+// Accounts: 0. `[writable]` the config account, 1. `[signer, writable]` the authority,
+// 2. `[]` the system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_init_config_instruction_data(uint64_t decay_half_life_epochs,
+                                                uint8_t *out,
+                                                uintptr_t out_len,
+                                                uintptr_t *written);
+
+// Build the instruction data for SetDecay.
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_set_decay_instruction_data(uint64_t half_life_epochs,
+                                              uint8_t *out,
+                                              uintptr_t out_len,
+                                              uintptr_t *written);
+
+// Build the instruction data for SetCrankAuthority, naming the 32-byte key at
+// `crank_authority` (all zeros for none).
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+//
+// # Safety
+// `crank_authority` must point to 32 readable bytes, `out` to `out_len` writable bytes
+// and `written` to a writable `size_t`.
+int32_t helloworld_set_crank_authority_instruction_data(const uint8_t *crank_authority,
+                                                        uint8_t *out,
+                                                        uintptr_t out_len,
+                                                        uintptr_t *written);
+
+// Build the instruction data for SetFee, charging `fee_usd_micros` millionths of a
+// dollar per Greet at the price of the Pyth SOL/USD account `price_feed`, paid to
+// `fee_recipient` (both 32-byte keys).
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+//
+// # Safety
+// `price_feed` and `fee_recipient` must point to 32 readable bytes each, `out` to
+// `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_set_fee_instruction_data(uint64_t fee_usd_micros,
+                                            uint64_t max_price_age_slots,
+                                            uint16_t max_confidence_bps,
+                                            const uint8_t *price_feed,
+                                            const uint8_t *fee_recipient,
+                                            uint8_t *out,
+                                            uintptr_t out_len,
+                                            uintptr_t *written);
+
+// Build the instruction data for Tick.
+// Accounts: 0. `[]` the config account, 1. `[signer]` the crank authority, then
+// `[writable]` each greeting account to maintain.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_tick_instruction_data(uint8_t *out, uintptr_t out_len, uintptr_t *written);
+
+// Build the instruction data for SetAuthority, handing the config to the 32-byte key
+// at `new_authority`.
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+//
+// # Safety
+// `new_authority` must point to 32 readable bytes, `out` to `out_len` writable bytes
+// and `written` to a writable `size_t`.
+int32_t helloworld_set_authority_instruction_data(const uint8_t *new_authority,
+                                                  uint8_t *out,
+                                                  uintptr_t out_len,
+                                                  uintptr_t *written);
+
+// Build the instruction data for CommitLuck.
+// Accounts: 0. `[]` the greeting account, 1. `[writable]` its ticket, 2. `[]` the
+// Switchboard randomness account, 3. `[signer, writable]` the greeter, 4. `[]` the
+// system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_commit_luck_instruction_data(uint8_t *out,
+                                                uintptr_t out_len,
+                                                uintptr_t *written);
+
+// Build the instruction data for RevealLuck.
+// Accounts: 0. `[]` the greeting account, 1. `[writable]` its ticket, 2. `[]` the
+// randomness account committed to, 3. `[writable]` the tip vault, 4. `[writable]` the
+// greeter, 5. `[]` the system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_reveal_luck_instruction_data(uint8_t *out,
+                                                uintptr_t out_len,
+                                                uintptr_t *written);
+
+// Build the instruction data for SetStake, weighting greetings by stake of the mint
+// whose 32-byte key is at `stake_mint`, 1 more for every `stake_unit` base units.
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+//
+// # Safety
+// `stake_mint` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+// `written` to a writable `size_t`.
+int32_t helloworld_set_stake_instruction_data(const uint8_t *stake_mint,
+                                              uint64_t stake_unit,
+                                              uint8_t *out,
+                                              uintptr_t out_len,
+                                              uintptr_t *written);
+
+// Build the instruction data for Deposit, staking `amount` tokens.
+// Accounts: 0. `[]` the config account, 1. `[writable]` the stake position, 2.
+// `[signer, writable]` the staker, 3. `[writable]` the token account to take from, 4.
+// `[writable]` the stake vault, 5. `[]` the token program, 6. `[]` the system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_deposit_instruction_data(uint64_t amount,
+                                            uint8_t *out,
+                                            uintptr_t out_len,
+                                            uintptr_t *written);
+
+// Build the instruction data for WithdrawStake, paying back `amount` staked tokens.
+// Accounts: 0. `[writable]` the stake position, 1. `[signer]` the staker, 2.
+// `[writable]` the stake vault, 3. `[writable]` the token account to pay to, 4. `[]`
+// the stake authority, 5. `[]` the token program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_withdraw_stake_instruction_data(uint64_t amount,
+                                                   uint8_t *out,
+                                                   uintptr_t out_len,
+                                                   uintptr_t *written);
+
+// Build the instruction data for CreateChallenge, escrowing `amount` lamports for the
+// challengee to greet `greet_count` more times before `deadline_slot`.
+// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challenger, 2.
+// `[]` the challengee, 3. `[]` the greeting account, 4. `[]` the system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_create_challenge_instruction_data(uint64_t greet_count,
+                                                     uint64_t deadline_slot,
+                                                     uint64_t amount,
+                                                     uint8_t *out,
+                                                     uintptr_t out_len,
+                                                     uintptr_t *written);
+
+// Build the instruction data for ClaimChallenge.
+// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challengee, 2.
+// `[]` the challenge's greeting account.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_claim_challenge_instruction_data(uint8_t *out,
+                                                    uintptr_t out_len,
+                                                    uintptr_t *written);
+
+// Build the instruction data for RefundChallenge.
+// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challenger.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_refund_challenge_instruction_data(uint8_t *out,
+                                                     uintptr_t out_len,
+                                                     uintptr_t *written);
+
+// Build the instruction data for ReceiveCrossChainGreet.
+// Accounts: 0. `[]` the posted VAA, 1. `[writable]` the emitter's foreign greeter.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_receive_cross_chain_greet_instruction_data(uint8_t *out,
+                                                              uintptr_t out_len,
+                                                              uintptr_t *written);
+
+// Build the instruction data for InitGreetingLog, starting a greeting log `max_depth`
+// deep that keeps `max_buffer_size` changes.
+// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority, 2.
+// `[writable]` the merkle tree, 3. `[]` the log authority, 4. `[]` the
+// spl-account-compression program, 5. `[]` the spl-noop program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_init_greeting_log_instruction_data(uint32_t max_depth,
+                                                      uint32_t max_buffer_size,
+                                                      uint8_t *out,
+                                                      uintptr_t out_len,
+                                                      uintptr_t *written);
+
+// Build the instruction data for CreateSession, letting a session key greet for the
+// authority for `duration_seconds` and topping it up with `top_up_lamports`.
+// Accounts: 0. `[writable]` the session token, 1. `[signer, writable]` the authority,
+// 2. `[signer, writable]` the session key, 3. `[]` the system program.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_create_session_instruction_data(uint64_t duration_seconds,
+                                                   uint64_t top_up_lamports,
+                                                   uint8_t *out,
+                                                   uintptr_t out_len,
+                                                   uintptr_t *written);
+
+// Build the instruction data for RevokeSession.
+// Accounts: 0. `[writable]` the session token, 1. `[signer, writable]` the authority.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_revoke_session_instruction_data(uint8_t *out,
+                                                   uintptr_t out_len,
+                                                   uintptr_t *written);
+
+// Build the instruction data for Increment, counting a greeting without changing the
+// message.
+// Accounts: 0. `[writable]` the greeting account, 1. `[]` the config account.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_increment_instruction_data(uint8_t *out, uintptr_t out_len, uintptr_t *written);
+
+// Build the instruction data for GreetMany with the NUL-terminated UTF-8 message `txt`,
+// saved into every greeting account passed.
+// Accounts: 0. `[]` the config account, 1.. `[writable]` the greeting accounts, at most
+// MAX_GREET_MANY_ACCOUNTS (24) of them.
+//
+// # Safety
+// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_greet_many_instruction_data(const char *txt,
+                                               uint8_t *out,
+                                               uintptr_t out_len,
+                                               uintptr_t *written);
+
+// Build the instruction data for GreetFor with the NUL-terminated UTF-8 message `txt`,
+// saved into the target's greeting account at the payer's expense.
+// Accounts: 0. `[writable]` the target's greeting account, 1. `[]` the config account,
+// 2. `[signer, writable]` the payer, 3. `[]` the target, 4. `[]` the system program.
+//
+// # Safety
+// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_greet_for_instruction_data(const char *txt,
+                                              uint8_t *out,
+                                              uintptr_t out_len,
+                                              uintptr_t *written);
+
+// Build the instruction data for InitCounterSnapshot, making the client-allocated
+// snapshot account a merkle tree `depth` levels deep.
+// Accounts: 0. `[]` the config account, 1. `[signer]` the config authority, 2.
+// `[writable]` the snapshot.
+//
+// # Safety
+// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+int32_t helloworld_init_counter_snapshot_instruction_data(uint8_t depth,
+                                                          uint8_t *out,
+                                                          uintptr_t out_len,
+                                                          uintptr_t *written);
+
+// Build the instruction data for SnapshotCounters, writing the counter of each greeting
+// account passed into the snapshot leaf at the same position in the `count` `indices`.
+// Accounts: 0. `[]` the config account, 1. `[signer]` the crank authority, 2.
+// `[writable]` the snapshot, 3.. `[]` the greeting accounts.
+//
+// # Safety
+// `indices` must point to `count` readable `uint32_t`, `out` to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_snapshot_counters_instruction_data(const uint32_t *indices,
+                                                      uintptr_t count,
+                                                      uint8_t *out,
+                                                      uintptr_t out_len,
+                                                      uintptr_t *written);
+
+// Build the instruction data for Claim, paying out leaf `index` of `amount` lamports of
+// the config's claim root. `proof` is the `proof_len` siblings from the leaf up, 32 bytes
+// each, one after the other.
+// Accounts: 0. `[]` the config account, 1. `[signer, writable]` the claimant, 2.
+// `[writable]` the leaf's claimed bitmap, 3. `[writable]` the claim vault, 4. `[]` the
+// system program.
+//
+// # Safety
+// `proof` must point to `proof_len` * 32 readable bytes, `out` to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_claim_instruction_data(uint32_t index,
+                                          uint64_t amount,
+                                          const uint8_t *proof,
+                                          uintptr_t proof_len,
+                                          uint8_t *out,
+                                          uintptr_t out_len,
+                                          uintptr_t *written);
+
+// Build the instruction data for Snapshot, recording the greeting account's counter
+// under the NUL-terminated UTF-8 `label`, 1 to MAX_LABEL_LEN bytes.
+// Accounts: 0. `[]` the config account, 1. `[]` the greeting account, 2. `[signer,
+// writable]` the recorder, 3. `[writable]` the snapshot, 4. `[]` the system program.
+//
+// # Safety
+// `label` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_snapshot_instruction_data(const char *label,
+                                             uint8_t *out,
+                                             uintptr_t out_len,
+                                             uintptr_t *written);
+
+// Build the instruction data for CountTag, counting a greeting under the tag named by
+// the NUL-terminated UTF-8 `tag`, 1 to 8 bytes.
+// Accounts: 0. `[]` the config account, 1. `[]` the greeting account, 2. `[signer,
+// writable]` the payer, 3. `[writable]` the tag counters, 4. `[]` the system program.
+//
+// # Safety
+// `tag` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+// bytes, and `written` to a writable `size_t`.
+int32_t helloworld_count_tag_instruction_data(const char *tag,
+                                              uint8_t *out,
+                                              uintptr_t out_len,
+                                              uintptr_t *written);
+
+// Build the instruction data for CheckRecency, failing unless the 32 bytes at
+// `slot_hash` are the hash of `slot`, at most `max_age_slots` old.
+// Accounts: 0. `[]` the SlotHashes sysvar.
+//
+// # Safety
+// `slot_hash` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+// `written` to a writable `size_t`.
+int32_t helloworld_check_recency_instruction_data(uint64_t slot,
+                                                  const uint8_t *slot_hash,
+                                                  uint64_t max_age_slots,
+                                                  uint8_t *out,
+                                                  uintptr_t out_len,
+                                                  uintptr_t *written);
+
+// Build the instruction data for RegisterForeignGreeter, letting the emitter at the
+// 32-byte universal address `sender` on Wormhole chain `chain` greet.
+// Accounts: 0. `[]` the config account, 1. `[signer, writable]` the config authority,
+// 2. `[writable]` the emitter's foreign greeter, 3. `[]` the system program.
+//
+// # Safety
+// `sender` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+// `written` to a writable `size_t`.
+int32_t helloworld_register_foreign_greeter_instruction_data(uint16_t chain,
+                                                             const uint8_t *sender,
+                                                             uint8_t *out,
+                                                             uintptr_t out_len,
+                                                             uintptr_t *written);
+
+// Human-readable message for a `Custom(code)` program error, or NULL for unknown codes.
+// The returned string is static; do not free it.
+const char *helloworld_error_message(uint32_t code);
+
+#endif /* HELLOWORLD_H */
@@ -0,0 +1,1319 @@
+/*
+NOTES:
+- A C ABI over the program's account and instruction layouts, so mobile and native
+wallets can integrate without a Rust toolchain. Link against the static or shared
+library and include include/helloworld.h (regenerated by cbindgen on every build).
+- Every function returns one of the HELLOWORLD_* status codes. Output is always written
+into a caller-provided buffer so ownership never crosses the FFI boundary, except for
+helloworld_error_message() which returns a pointer to a static string.
+- Instruction data is just the bytes: the caller builds the transaction with its own
+Solana SDK using the program id and the account order documented in instruction.rs.
+*/
+use borsh::BorshSerialize;
+use helloworld::{
+    error::{HelloWorldError, ERROR_MESSAGES},
+    instruction::HelloWorldInstruction,
+    milestone::is_valid_label,
+    tags, GreetingAccount,
+};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr, slice,
+    sync::OnceLock,
+};
+
+/// Success
+pub const HELLOWORLD_OK: i32 = 0;
+/// A required pointer argument was null
+pub const HELLOWORLD_ERR_NULL_POINTER: i32 = -1;
+/// The input bytes are not a valid account / the input string is not valid UTF-8
+pub const HELLOWORLD_ERR_INVALID_DATA: i32 = -2;
+/// The output buffer is too small; `*written` holds the size needed
+pub const HELLOWORLD_ERR_BUFFER_TOO_SMALL: i32 = -3;
+
+/// Copy `bytes` into the caller's buffer, reporting the length through `written`
+unsafe fn write_out(bytes: &[u8], out: *mut u8, out_len: usize, written: *mut usize) -> i32 {
+    *written = bytes.len();
+    if bytes.len() > out_len {
+        return HELLOWORLD_ERR_BUFFER_TOO_SMALL;
+    }
+    if !bytes.is_empty() {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    }
+    HELLOWORLD_OK
+}
+
+unsafe fn instruction_data(
+    instruction: HelloWorldInstruction,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    match instruction.try_to_vec() {
+        Ok(data) => write_out(&data, out, out_len, written),
+        Err(_) => HELLOWORLD_ERR_INVALID_DATA,
+    }
+}
+
+/// Decode a greeting account's data and copy its message (UTF-8, not NUL-terminated)
+/// into `txt_out`. Trailing zero padding in the account data is ignored.
+///
+/// # Safety
+/// `data` must point to `data_len` readable bytes, `txt_out` to `txt_out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_decode_greeting_account(
+    data: *const u8,
+    data_len: usize,
+    txt_out: *mut u8,
+    txt_out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if data.is_null() || txt_out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let data = slice::from_raw_parts(data, data_len);
+    match GreetingAccount::unpack(data) {
+        Ok(account) => write_out(account.txt.as_bytes(), txt_out, txt_out_len, written),
+        Err(_) => HELLOWORLD_ERR_INVALID_DATA,
+    }
+}
+
+/// Build the instruction data for Greet with the NUL-terminated UTF-8 message `txt`.
+/// Accounts: 0. `[writable]` the greeting account, 1. `[]` the config account
+/// (optional, without it the counter doesn't decay).
+///
+/// # Safety
+/// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_greet_instruction_data(
+    txt: *const c_char,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if txt.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let txt = match CStr::from_ptr(txt).to_str() {
+        Ok(txt) => txt.to_string(),
+        Err(_) => return HELLOWORLD_ERR_INVALID_DATA,
+    };
+    instruction_data(HelloWorldInstruction::Greet { txt }, out, out_len, written)
+}
+
+/// Build the instruction data for Version. Accounts: none.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_version_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::Version, out, out_len, written)
+}
+
+/// Build the instruction data for ExportState.
+/// Accounts: 0. `[]` the greeting account.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_export_state_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::ExportState, out, out_len, written)
+}
+
+/// Build the instruction data for ImportState.
+/// Accounts: 0. `[writable]` the greeting account, 1. `[]` the account to import from.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_import_state_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::ImportState, out, out_len, written)
+}
+
+/// Build the instruction data for InitConfig.
+/// Accounts: 0. `[writable]` the config account, 1. `[signer, writable]` the authority,
+/// 2. `[]` the system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_init_config_instruction_data(
+    decay_half_life_epochs: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::InitConfig {
+            decay_half_life_epochs,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for SetDecay.
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_set_decay_instruction_data(
+    half_life_epochs: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::SetDecay { half_life_epochs },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for SetCrankAuthority, naming the 32-byte key at
+/// `crank_authority` (all zeros for none).
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+///
+/// # Safety
+/// `crank_authority` must point to 32 readable bytes, `out` to `out_len` writable bytes
+/// and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_set_crank_authority_instruction_data(
+    crank_authority: *const u8,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if crank_authority.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(slice::from_raw_parts(crank_authority, 32));
+    instruction_data(
+        HelloWorldInstruction::SetCrankAuthority {
+            crank_authority: key,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for SetFee, charging `fee_usd_micros` millionths of a
+/// dollar per Greet at the price of the Pyth SOL/USD account `price_feed`, paid to
+/// `fee_recipient` (both 32-byte keys).
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+///
+/// # Safety
+/// `price_feed` and `fee_recipient` must point to 32 readable bytes each, `out` to
+/// `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn helloworld_set_fee_instruction_data(
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: *const u8,
+    fee_recipient: *const u8,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if price_feed.is_null() || fee_recipient.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let (mut feed, mut recipient) = ([0u8; 32], [0u8; 32]);
+    feed.copy_from_slice(slice::from_raw_parts(price_feed, 32));
+    recipient.copy_from_slice(slice::from_raw_parts(fee_recipient, 32));
+    instruction_data(
+        HelloWorldInstruction::SetFee {
+            fee_usd_micros,
+            max_price_age_slots,
+            max_confidence_bps,
+            price_feed: feed,
+            fee_recipient: recipient,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for Tick.
+/// Accounts: 0. `[]` the config account, 1. `[signer]` the crank authority, then
+/// `[writable]` each greeting account to maintain.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_tick_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::Tick, out, out_len, written)
+}
+
+/// Build the instruction data for SetAuthority, handing the config to the 32-byte key
+/// at `new_authority`.
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+///
+/// # Safety
+/// `new_authority` must point to 32 readable bytes, `out` to `out_len` writable bytes
+/// and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_set_authority_instruction_data(
+    new_authority: *const u8,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if new_authority.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(slice::from_raw_parts(new_authority, 32));
+    instruction_data(
+        HelloWorldInstruction::SetAuthority { new_authority: key },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for CommitLuck.
+/// Accounts: 0. `[]` the greeting account, 1. `[writable]` its ticket, 2. `[]` the
+/// Switchboard randomness account, 3. `[signer, writable]` the greeter, 4. `[]` the
+/// system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_commit_luck_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::CommitLuck, out, out_len, written)
+}
+
+/// Build the instruction data for RevealLuck.
+/// Accounts: 0. `[]` the greeting account, 1. `[writable]` its ticket, 2. `[]` the
+/// randomness account committed to, 3. `[writable]` the tip vault, 4. `[writable]` the
+/// greeter, 5. `[]` the system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_reveal_luck_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::RevealLuck, out, out_len, written)
+}
+
+/// Build the instruction data for SetStake, weighting greetings by stake of the mint
+/// whose 32-byte key is at `stake_mint`, 1 more for every `stake_unit` base units.
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority.
+///
+/// # Safety
+/// `stake_mint` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+/// `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_set_stake_instruction_data(
+    stake_mint: *const u8,
+    stake_unit: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if stake_mint.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(slice::from_raw_parts(stake_mint, 32));
+    instruction_data(
+        HelloWorldInstruction::SetStake {
+            stake_mint: key,
+            stake_unit,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for Deposit, staking `amount` tokens.
+/// Accounts: 0. `[]` the config account, 1. `[writable]` the stake position, 2.
+/// `[signer, writable]` the staker, 3. `[writable]` the token account to take from, 4.
+/// `[writable]` the stake vault, 5. `[]` the token program, 6. `[]` the system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_deposit_instruction_data(
+    amount: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::Deposit { amount },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for WithdrawStake, paying back `amount` staked tokens.
+/// Accounts: 0. `[writable]` the stake position, 1. `[signer]` the staker, 2.
+/// `[writable]` the stake vault, 3. `[writable]` the token account to pay to, 4. `[]`
+/// the stake authority, 5. `[]` the token program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_withdraw_stake_instruction_data(
+    amount: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::WithdrawStake { amount },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for CreateChallenge, escrowing `amount` lamports for the
+/// challengee to greet `greet_count` more times before `deadline_slot`.
+/// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challenger, 2.
+/// `[]` the challengee, 3. `[]` the greeting account, 4. `[]` the system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_create_challenge_instruction_data(
+    greet_count: u64,
+    deadline_slot: u64,
+    amount: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::CreateChallenge {
+            greet_count,
+            deadline_slot,
+            amount,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for ClaimChallenge.
+/// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challengee, 2.
+/// `[]` the challenge's greeting account.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_claim_challenge_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::ClaimChallenge, out, out_len, written)
+}
+
+/// Build the instruction data for RefundChallenge.
+/// Accounts: 0. `[writable]` the challenge, 1. `[signer, writable]` the challenger.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_refund_challenge_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::RefundChallenge,
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for ReceiveCrossChainGreet.
+/// Accounts: 0. `[]` the posted VAA, 1. `[writable]` the emitter's foreign greeter.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_receive_cross_chain_greet_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::ReceiveCrossChainGreet,
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for InitGreetingLog, starting a greeting log `max_depth`
+/// deep that keeps `max_buffer_size` changes.
+/// Accounts: 0. `[writable]` the config account, 1. `[signer]` the config authority, 2.
+/// `[writable]` the merkle tree, 3. `[]` the log authority, 4. `[]` the
+/// spl-account-compression program, 5. `[]` the spl-noop program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_init_greeting_log_instruction_data(
+    max_depth: u32,
+    max_buffer_size: u32,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::InitGreetingLog {
+            max_depth,
+            max_buffer_size,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for CreateSession, letting a session key greet for the
+/// authority for `duration_seconds` and topping it up with `top_up_lamports`.
+/// Accounts: 0. `[writable]` the session token, 1. `[signer, writable]` the authority,
+/// 2. `[signer, writable]` the session key, 3. `[]` the system program.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_create_session_instruction_data(
+    duration_seconds: u64,
+    top_up_lamports: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::CreateSession {
+            duration_seconds,
+            top_up_lamports,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for RevokeSession.
+/// Accounts: 0. `[writable]` the session token, 1. `[signer, writable]` the authority.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_revoke_session_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::RevokeSession, out, out_len, written)
+}
+
+/// Build the instruction data for Increment, counting a greeting without changing the
+/// message.
+/// Accounts: 0. `[writable]` the greeting account, 1. `[]` the config account.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_increment_instruction_data(
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(HelloWorldInstruction::Increment, out, out_len, written)
+}
+
+/// Build the instruction data for GreetMany with the NUL-terminated UTF-8 message `txt`,
+/// saved into every greeting account passed.
+/// Accounts: 0. `[]` the config account, 1.. `[writable]` the greeting accounts, at most
+/// MAX_GREET_MANY_ACCOUNTS (24) of them.
+///
+/// # Safety
+/// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_greet_many_instruction_data(
+    txt: *const c_char,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if txt.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let txt = match CStr::from_ptr(txt).to_str() {
+        Ok(txt) => txt.to_string(),
+        Err(_) => return HELLOWORLD_ERR_INVALID_DATA,
+    };
+    instruction_data(
+        HelloWorldInstruction::GreetMany { txt },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for GreetFor with the NUL-terminated UTF-8 message `txt`,
+/// saved into the target's greeting account at the payer's expense.
+/// Accounts: 0. `[writable]` the target's greeting account, 1. `[]` the config account,
+/// 2. `[signer, writable]` the payer, 3. `[]` the target, 4. `[]` the system program.
+///
+/// # Safety
+/// `txt` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_greet_for_instruction_data(
+    txt: *const c_char,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if txt.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let txt = match CStr::from_ptr(txt).to_str() {
+        Ok(txt) => txt.to_string(),
+        Err(_) => return HELLOWORLD_ERR_INVALID_DATA,
+    };
+    instruction_data(
+        HelloWorldInstruction::GreetFor { txt },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for InitCounterSnapshot, making the client-allocated
+/// snapshot account a merkle tree `depth` levels deep.
+/// Accounts: 0. `[]` the config account, 1. `[signer]` the config authority, 2.
+/// `[writable]` the snapshot.
+///
+/// # Safety
+/// `out` must point to `out_len` writable bytes and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_init_counter_snapshot_instruction_data(
+    depth: u8,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    instruction_data(
+        HelloWorldInstruction::InitCounterSnapshot { depth },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for SnapshotCounters, writing the counter of each greeting
+/// account passed into the snapshot leaf at the same position in the `count` `indices`.
+/// Accounts: 0. `[]` the config account, 1. `[signer]` the crank authority, 2.
+/// `[writable]` the snapshot, 3.. `[]` the greeting accounts.
+///
+/// # Safety
+/// `indices` must point to `count` readable `uint32_t`, `out` to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_snapshot_counters_instruction_data(
+    indices: *const u32,
+    count: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if indices.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let indices = std::slice::from_raw_parts(indices, count).to_vec();
+    instruction_data(
+        HelloWorldInstruction::SnapshotCounters { indices },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for Claim, paying out leaf `index` of `amount` lamports of
+/// the config's claim root. `proof` is the `proof_len` siblings from the leaf up, 32 bytes
+/// each, one after the other.
+/// Accounts: 0. `[]` the config account, 1. `[signer, writable]` the claimant, 2.
+/// `[writable]` the leaf's claimed bitmap, 3. `[writable]` the claim vault, 4. `[]` the
+/// system program.
+///
+/// # Safety
+/// `proof` must point to `proof_len` * 32 readable bytes, `out` to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_claim_instruction_data(
+    index: u32,
+    amount: u64,
+    proof: *const u8,
+    proof_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if proof.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let proof = std::slice::from_raw_parts(proof, proof_len * 32)
+        .chunks(32)
+        .map(|sibling| {
+            let mut hash = [0; 32];
+            hash.copy_from_slice(sibling);
+            hash
+        })
+        .collect();
+    instruction_data(
+        HelloWorldInstruction::Claim {
+            index,
+            amount,
+            proof,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for Snapshot, recording the greeting account's counter
+/// under the NUL-terminated UTF-8 `label`, 1 to MAX_LABEL_LEN bytes.
+/// Accounts: 0. `[]` the config account, 1. `[]` the greeting account, 2. `[signer,
+/// writable]` the recorder, 3. `[writable]` the snapshot, 4. `[]` the system program.
+///
+/// # Safety
+/// `label` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_snapshot_instruction_data(
+    label: *const c_char,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if label.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let label = match CStr::from_ptr(label).to_str() {
+        Ok(label) if is_valid_label(label) => label.to_string(),
+        _ => return HELLOWORLD_ERR_INVALID_DATA,
+    };
+    instruction_data(
+        HelloWorldInstruction::Snapshot { label },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for CountTag, counting a greeting under the tag named by
+/// the NUL-terminated UTF-8 `tag`, 1 to 8 bytes.
+/// Accounts: 0. `[]` the config account, 1. `[]` the greeting account, 2. `[signer,
+/// writable]` the payer, 3. `[writable]` the tag counters, 4. `[]` the system program.
+///
+/// # Safety
+/// `tag` must be a valid NUL-terminated string, `out` must point to `out_len` writable
+/// bytes, and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_count_tag_instruction_data(
+    tag: *const c_char,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if tag.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let tag = match CStr::from_ptr(tag).to_str().ok().and_then(tags::tag) {
+        Some(tag) => tag,
+        None => return HELLOWORLD_ERR_INVALID_DATA,
+    };
+    instruction_data(
+        HelloWorldInstruction::CountTag { tag },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for CheckRecency, failing unless the 32 bytes at
+/// `slot_hash` are the hash of `slot`, at most `max_age_slots` old.
+/// Accounts: 0. `[]` the SlotHashes sysvar.
+///
+/// # Safety
+/// `slot_hash` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+/// `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_check_recency_instruction_data(
+    slot: u64,
+    slot_hash: *const u8,
+    max_age_slots: u64,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if slot_hash.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(slice::from_raw_parts(slot_hash, 32));
+    instruction_data(
+        HelloWorldInstruction::CheckRecency {
+            slot,
+            slot_hash: hash,
+            max_age_slots,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Build the instruction data for RegisterForeignGreeter, letting the emitter at the
+/// 32-byte universal address `sender` on Wormhole chain `chain` greet.
+/// Accounts: 0. `[]` the config account, 1. `[signer, writable]` the config authority,
+/// 2. `[writable]` the emitter's foreign greeter, 3. `[]` the system program.
+///
+/// # Safety
+/// `sender` must point to 32 readable bytes, `out` to `out_len` writable bytes and
+/// `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn helloworld_register_foreign_greeter_instruction_data(
+    chain: u16,
+    sender: *const u8,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> i32 {
+    if sender.is_null() || out.is_null() || written.is_null() {
+        return HELLOWORLD_ERR_NULL_POINTER;
+    }
+    let mut address = [0u8; 32];
+    address.copy_from_slice(slice::from_raw_parts(sender, 32));
+    instruction_data(
+        HelloWorldInstruction::RegisterForeignGreeter {
+            chain,
+            sender: address,
+        },
+        out,
+        out_len,
+        written,
+    )
+}
+
+/// Human-readable message for a `Custom(code)` program error, or NULL for unknown codes.
+/// The returned string is static; do not free it.
+#[no_mangle]
+pub extern "C" fn helloworld_error_message(code: u32) -> *const c_char {
+    static MESSAGES: OnceLock<Vec<(u32, CString)>> = OnceLock::new();
+    let messages = MESSAGES.get_or_init(|| {
+        ERROR_MESSAGES
+            .iter()
+            .map(|(code, message)| (*code, CString::new(*message).unwrap()))
+            .collect()
+    });
+    match HelloWorldError::from_code(code) {
+        Some(error) => messages
+            .iter()
+            .find(|(code, _)| *code == error.code())
+            .map_or(ptr::null(), |(_, message)| message.as_ptr()),
+        None => ptr::null(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_greet_round_trip() {
+        let txt = CString::new("Hello1234567").unwrap();
+        let mut data = [0u8; 32];
+        let mut written = 0;
+        let status = unsafe {
+            helloworld_greet_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(written, 1 + 4 + 12);
+        assert_eq!(data[0], 0);
+
+        // The Greet arguments are laid out exactly like the account, decode them back
+        let mut txt_out = [0u8; 12];
+        let status = unsafe {
+            helloworld_decode_greeting_account(
+                data[1..].as_ptr(),
+                4 + 12,
+                txt_out.as_mut_ptr(),
+                txt_out.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&txt_out[..written], b"Hello1234567");
+    }
+
+    #[test]
+    fn test_buffer_too_small_reports_needed_size() {
+        let mut data = [0u8; 1];
+        let mut written = 0;
+        let txt = CString::new("hi").unwrap();
+        let status = unsafe {
+            helloworld_greet_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(written, 1 + 4 + 2);
+
+        let status =
+            unsafe { helloworld_version_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(data, [1]);
+
+        let status =
+            unsafe { helloworld_import_state_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(data, [3]);
+
+        let mut data = [0u8; 9];
+        let status = unsafe {
+            helloworld_init_config_instruction_data(10, data.as_mut_ptr(), 8, &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(written, 1 + 8);
+        let status = unsafe {
+            helloworld_set_decay_instruction_data(20, data.as_mut_ptr(), data.len(), &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(data, [5, 20, 0, 0, 0, 0, 0, 0, 0]);
+
+        let crank = [7u8; 32];
+        let mut data = [0u8; 33];
+        let status = unsafe {
+            helloworld_set_crank_authority_instruction_data(
+                crank.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(data[0], 6);
+        assert_eq!(data[1..], crank);
+        let status =
+            unsafe { helloworld_tick_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (7, 1));
+
+        let mut data = [0u8; 83];
+        let status = unsafe {
+            helloworld_set_fee_instruction_data(
+                10_000,
+                25,
+                100,
+                crank.as_ptr(),
+                crank.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (8, data.len()));
+
+        let status =
+            unsafe { helloworld_commit_luck_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (9, 1));
+        let status =
+            unsafe { helloworld_reveal_luck_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (10, 1));
+
+        let mut data = [0u8; 33];
+        let status = unsafe {
+            helloworld_set_authority_instruction_data(
+                crank.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], &data[1..]), (11, &crank[..]));
+
+        let mut data = [0u8; 41];
+        let status = unsafe {
+            helloworld_set_stake_instruction_data(
+                crank.as_ptr(),
+                1_000,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], &data[1..33]), (12, &crank[..]));
+        assert_eq!(&data[33..], &1_000u64.to_le_bytes());
+        let status = unsafe {
+            helloworld_deposit_instruction_data(500, data.as_mut_ptr(), data.len(), &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (13, 9));
+        let status = unsafe {
+            helloworld_withdraw_stake_instruction_data(
+                500,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (14, 9));
+
+        let mut data = [0u8; 25];
+        let status = unsafe {
+            helloworld_create_challenge_instruction_data(
+                3,
+                1_000,
+                500,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (15, 25));
+        assert_eq!(&data[17..], &500u64.to_le_bytes());
+        let status = unsafe {
+            helloworld_claim_challenge_instruction_data(data.as_mut_ptr(), 1, &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (16, 1));
+        let status = unsafe {
+            helloworld_refund_challenge_instruction_data(data.as_mut_ptr(), 1, &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (17, 1));
+        let status = unsafe {
+            helloworld_receive_cross_chain_greet_instruction_data(
+                data.as_mut_ptr(),
+                1,
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (18, 1));
+
+        let mut data = [0u8; 9];
+        let status = unsafe {
+            helloworld_init_greeting_log_instruction_data(
+                14,
+                64,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(data, [19, 14, 0, 0, 0, 64, 0, 0, 0]);
+        assert_eq!(written, 9);
+
+        let mut data = [0u8; 17];
+        let status = unsafe {
+            helloworld_create_session_instruction_data(
+                3_600,
+                500,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (20, 17));
+        assert_eq!(&data[9..], &500u64.to_le_bytes());
+        let status = unsafe {
+            helloworld_revoke_session_instruction_data(data.as_mut_ptr(), 1, &mut written)
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (21, 1));
+        let status =
+            unsafe { helloworld_increment_instruction_data(data.as_mut_ptr(), 1, &mut written) };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], written), (22, 1));
+        let txt = CString::new("hi").unwrap();
+        let status = unsafe {
+            helloworld_greet_many_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[23, 2, 0, 0, 0, b'h', b'i']);
+        let status = unsafe {
+            helloworld_greet_for_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[24, 2, 0, 0, 0, b'h', b'i']);
+        let status = unsafe {
+            helloworld_init_counter_snapshot_instruction_data(
+                4,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[26, 4]);
+        let indices = [0u32, 1];
+        let status = unsafe {
+            helloworld_snapshot_counters_instruction_data(
+                indices.as_ptr(),
+                indices.len(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[27, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+        let proof = [7u8; 32];
+        let mut claim = [0u8; 1 + 4 + 8 + 4 + 32];
+        let status = unsafe {
+            helloworld_claim_instruction_data(
+                3,
+                500,
+                proof.as_ptr(),
+                1,
+                claim.as_mut_ptr(),
+                claim.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((claim[0], written), (28, claim.len()));
+        assert_eq!(&claim[13..17], &1u32.to_le_bytes());
+        let status = unsafe {
+            helloworld_snapshot_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[29, 2, 0, 0, 0, b'h', b'i']);
+        let empty = CString::new("").unwrap();
+        let status = unsafe {
+            helloworld_snapshot_instruction_data(
+                empty.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_ERR_INVALID_DATA);
+        let status = unsafe {
+            helloworld_count_tag_instruction_data(
+                txt.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(&data[..written], &[30, b'h', b'i', 0, 0, 0, 0, 0, 0]);
+        let slot_hash = [9u8; 32];
+        let mut data = [0u8; 1 + 8 + 32 + 8];
+        let status = unsafe {
+            helloworld_check_recency_instruction_data(
+                7,
+                slot_hash.as_ptr(),
+                150,
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!((data[0], &data[1..9]), (31, &7u64.to_le_bytes()[..]));
+        assert_eq!(&data[9..41], &slot_hash);
+        assert_eq!(
+            (&data[41..], written),
+            (&150u64.to_le_bytes()[..], data.len())
+        );
+        let sender = [7u8; 32];
+        let mut data = [0u8; 1 + 2 + 32];
+        let status = unsafe {
+            helloworld_register_foreign_greeter_instruction_data(
+                2,
+                sender.as_ptr(),
+                data.as_mut_ptr(),
+                data.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_OK);
+        assert_eq!(
+            (data[0], &data[1..3], written),
+            (32, &[2, 0][..], data.len())
+        );
+        assert_eq!(&data[3..], &sender);
+    }
+
+    #[test]
+    fn test_invalid_account_and_null_pointers() {
+        let garbage = [0xffu8; 4];
+        let mut txt_out = [0u8; 8];
+        let mut written = 0;
+        let status = unsafe {
+            helloworld_decode_greeting_account(
+                garbage.as_ptr(),
+                garbage.len(),
+                txt_out.as_mut_ptr(),
+                txt_out.len(),
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_ERR_INVALID_DATA);
+
+        let status = unsafe {
+            helloworld_decode_greeting_account(
+                ptr::null(),
+                0,
+                txt_out.as_mut_ptr(),
+                8,
+                &mut written,
+            )
+        };
+        assert_eq!(status, HELLOWORLD_ERR_NULL_POINTER);
+    }
+
+    #[test]
+    fn test_error_message() {
+        let message = unsafe { CStr::from_ptr(helloworld_error_message(6001)) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            HelloWorldError::MessageTooLong.message()
+        );
+        assert!(helloworld_error_message(1).is_null());
+    }
+}
@@ -0,0 +1,340 @@
+/*
+NOTES:
+- Websocket accountSubscribe is fine for a demo, but it drops updates under load and has
+no replay. Production indexers read straight from a validator's Geyser plugin instead,
+most commonly through Yellowstone gRPC (Triton, Helius, QuickNode, or your own node
+running yellowstone-grpc-geyser).
+- This binary subscribes to every account owned by the program, decodes each update
+into a GreetingAccount and prints it as it lands.
+- Filters run on the server, so only matching accounts cross the wire. We filter on the
+owner, and optionally on the exact data size (every greeting account is allocated at
+the same size by the client, so this skips anything else the program might own).
+- With --events it also streams the program's successful transactions and prints the
+config and authority events they log (see event.rs in the program crate), the changes
+to alert on since only the config authority can make them.
+- The server sends a Ping every few seconds; answering it keeps load balancers from
+closing an idle stream.
+- Run with `cargo run --manifest-path src/geyser/Cargo.toml -- --program-id <PUBKEY>`.
+*/
+use futures::{SinkExt, StreamExt};
+use helloworld::{event::Event, GreetingAccount};
+use std::{collections::HashMap, env, io, process};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::{
+    prelude::{
+        subscribe_request_filter_accounts_filter::Filter, subscribe_update::UpdateOneof,
+        CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterTransactions,
+        SubscribeRequestPing, SubscribeUpdateAccountInfo, SubscribeUpdateTransactionInfo,
+    },
+    tonic::transport::ClientTlsConfig,
+};
+
+const USAGE: &str = "Usage: geyser --program-id <PUBKEY> [--endpoint <URL>] [--x-token <TOKEN>]
+              [--commitment <LEVEL>] [--data-size <BYTES>] [--events]
+
+Options:
+  --program-id <PUBKEY>  Program whose accounts to stream
+  --endpoint <URL>       Yellowstone gRPC endpoint (defaults to http://127.0.0.1:10000)
+  --x-token <TOKEN>      Access token, also read from GEYSER_X_TOKEN
+  --commitment <LEVEL>   processed, confirmed (default) or finalized
+  --data-size <BYTES>    Only stream accounts with exactly this much data
+  --events               Also stream the program's transactions, printing the config
+                         and authority changes they log";
+
+struct Args {
+    endpoint: String,
+    x_token: Option<String>,
+    program_id: String,
+    commitment: CommitmentLevel,
+    data_size: Option<u64>,
+    events: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut endpoint = "http://127.0.0.1:10000".to_string();
+    let mut x_token = env::var("GEYSER_X_TOKEN").ok();
+    let mut program_id = None;
+    let mut commitment = CommitmentLevel::Confirmed;
+    let mut data_size = None;
+    let mut events = false;
+    let mut iter = env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "--endpoint" => endpoint = value()?,
+            "--x-token" => x_token = Some(value()?),
+            "--program-id" => {
+                let value = value()?;
+                match bs58::decode(&value).into_vec() {
+                    Ok(bytes) if bytes.len() == 32 => program_id = Some(value),
+                    _ => return Err(format!("Invalid program id {}", value)),
+                }
+            }
+            "--commitment" => {
+                let value = value()?;
+                commitment = match value.as_str() {
+                    "processed" => CommitmentLevel::Processed,
+                    "confirmed" => CommitmentLevel::Confirmed,
+                    "finalized" => CommitmentLevel::Finalized,
+                    _ => return Err(format!("Unknown commitment {}", value)),
+                };
+            }
+            "--data-size" => {
+                let value = value()?;
+                data_size = Some(
+                    value
+                        .parse()
+                        .map_err(|err| format!("Invalid data size {}: {}", value, err))?,
+                );
+            }
+            "--events" => events = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument {}", flag)),
+        }
+    }
+    Ok(Args {
+        endpoint,
+        x_token,
+        program_id: program_id.ok_or("Missing --program-id")?,
+        commitment,
+        data_size,
+        events,
+    })
+}
+
+/// Subscribe to the program's accounts, and with --events its successful transactions:
+/// no slots or blocks
+fn subscribe_request(args: &Args) -> SubscribeRequest {
+    let filters = args
+        .data_size
+        .map(|size| SubscribeRequestFilterAccountsFilter {
+            filter: Some(Filter::Datasize(size)),
+        })
+        .into_iter()
+        .collect();
+    let mut accounts = HashMap::new();
+    // NOTE The key is just a label, the server echoes it back in update.filters
+    accounts.insert(
+        "helloworld".to_string(),
+        SubscribeRequestFilterAccounts {
+            owner: vec![args.program_id.clone()],
+            filters,
+            ..Default::default()
+        },
+    );
+    let mut transactions = HashMap::new();
+    if args.events {
+        transactions.insert(
+            "helloworld".to_string(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                account_include: vec![args.program_id.clone()],
+                ..Default::default()
+            },
+        );
+    }
+    SubscribeRequest {
+        accounts,
+        transactions,
+        commitment: Some(args.commitment as i32),
+        ..Default::default()
+    }
+}
+
+/// (account address, decoded message) for one account update
+fn decode_account(account: &SubscribeUpdateAccountInfo) -> (String, Result<String, String>) {
+    let pubkey = bs58::encode(&account.pubkey).into_string();
+    // Accounts are allocated at a fixed size, unpack() ignores the zeroed tail
+    let txt = GreetingAccount::unpack(&account.data)
+        .map(|greeting| greeting.txt)
+        .map_err(|err| err.to_string());
+    (pubkey, txt)
+}
+
+/// (transaction signature, the events it logged) for one transaction update
+fn decode_events(transaction: &SubscribeUpdateTransactionInfo) -> (String, Vec<io::Result<Event>>) {
+    let signature = bs58::encode(&transaction.signature).into_string();
+    let events = transaction
+        .meta
+        .as_ref()
+        .map(|meta| helloworld::event::events(&meta.log_messages))
+        .unwrap_or_default();
+    (signature, events)
+}
+
+async fn run() -> Result<(), String> {
+    let args = parse_args()?;
+
+    let mut builder = GeyserGrpcClient::build_from_shared(args.endpoint.clone())
+        .and_then(|builder| builder.x_token(args.x_token.clone()))
+        .map_err(|err| format!("Invalid endpoint {}: {}", args.endpoint, err))?;
+    if args.endpoint.starts_with("https://") {
+        builder = builder
+            .tls_config(ClientTlsConfig::new().with_native_roots())
+            .map_err(|err| format!("Failed to configure TLS: {}", err))?;
+    }
+    let mut client = builder
+        .connect()
+        .await
+        .map_err(|err| format!("Failed to connect to {}: {}", args.endpoint, err))?;
+
+    let (mut sink, mut stream) = client
+        .subscribe_with_request(Some(subscribe_request(&args)))
+        .await
+        .map_err(|err| format!("Failed to subscribe: {}", err))?;
+    println!(
+        "Streaming accounts owned by {} from {}",
+        args.program_id, args.endpoint
+    );
+
+    while let Some(update) = stream.next().await {
+        let update = update.map_err(|err| format!("Stream error: {}", err))?;
+        match update.update_oneof {
+            Some(UpdateOneof::Account(update)) => {
+                let account = match update.account {
+                    Some(account) => account,
+                    None => continue,
+                };
+                match decode_account(&account) {
+                    (pubkey, Ok(txt)) => println!("slot {} {} {:?}", update.slot, pubkey, txt),
+                    (pubkey, Err(err)) => {
+                        eprintln!(
+                            "slot {} {} is not a greeting account: {}",
+                            update.slot, pubkey, err
+                        )
+                    }
+                }
+            }
+            Some(UpdateOneof::Transaction(update)) => {
+                let transaction = match update.transaction {
+                    Some(transaction) => transaction,
+                    None => continue,
+                };
+                let (signature, events) = decode_events(&transaction);
+                for event in events {
+                    match event {
+                        Ok(event) => println!("slot {} {} {:?}", update.slot, signature, event),
+                        Err(err) => eprintln!(
+                            "slot {} {} logged an unreadable event: {}",
+                            update.slot, signature, err
+                        ),
+                    }
+                }
+            }
+            Some(UpdateOneof::Ping(_)) => {
+                let ping = SubscribeRequest {
+                    ping: Some(SubscribeRequestPing { id: 1 }),
+                    ..Default::default()
+                };
+                sink.send(ping)
+                    .await
+                    .map_err(|err| format!("Failed to answer ping: {}", err))?;
+            }
+            _ => {}
+        }
+    }
+    Err("Stream closed by the server".to_string())
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run().await {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::BorshSerialize;
+    use helloworld::{calendar::DayBitmap, migrations, STATE_VERSION};
+    use yellowstone_grpc_proto::prelude::TransactionStatusMeta;
+
+    #[test]
+    fn test_subscribe_request_filters_on_owner_and_size() {
+        let args = Args {
+            endpoint: String::new(),
+            x_token: None,
+            program_id: "8PNFh5ugkVBAFT1Si8XsjXs4FgP3eDFuZSh8uTW6sDjc".to_string(),
+            commitment: CommitmentLevel::Finalized,
+            data_size: Some(16),
+            events: false,
+        };
+        let request = subscribe_request(&args);
+        let filter = &request.accounts["helloworld"];
+        assert_eq!(filter.owner, vec![args.program_id.clone()]);
+        assert_eq!(filter.filters[0].filter, Some(Filter::Datasize(16)));
+        assert_eq!(request.commitment, Some(CommitmentLevel::Finalized as i32));
+        assert!(request.transactions.is_empty());
+
+        let request = subscribe_request(&Args {
+            events: true,
+            ..args
+        });
+        let filter = &request.transactions["helloworld"];
+        assert_eq!(filter.account_include, request.accounts["helloworld"].owner);
+        assert_eq!((filter.vote, filter.failed), (Some(false), Some(false)));
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let event = Event::authority_changed([1; 32], [2; 32]);
+        let transaction = SubscribeUpdateTransactionInfo {
+            signature: vec![0; 64],
+            meta: Some(TransactionStatusMeta {
+                log_messages: vec![
+                    "Program log: Config authority changed".to_string(),
+                    format!("Program log: {}", event.to_log()),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (signature, events) = decode_events(&transaction);
+        assert_eq!(signature, bs58::encode([0; 64]).into_string());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].as_ref().unwrap(), &event);
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let mut data = migrations::encode(
+            STATE_VERSION,
+            &GreetingAccount {
+                txt: "Hello1234567".to_string(),
+                counter: 1,
+                calendar_year: 2024,
+                greeted_days: DayBitmap::default(),
+                last_update_epoch: 0,
+                direct_greets: 0,
+                cpi_greets: 0,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        data.resize(112, 0);
+        let account = SubscribeUpdateAccountInfo {
+            pubkey: vec![0; 32],
+            data,
+            ..Default::default()
+        };
+        let (pubkey, txt) = decode_account(&account);
+        assert_eq!(pubkey, "11111111111111111111111111111111");
+        assert_eq!(txt.unwrap(), "Hello1234567");
+
+        let garbage = SubscribeUpdateAccountInfo {
+            data: vec![0xff; 4],
+            ..Default::default()
+        };
+        assert!(decode_account(&garbage).1.is_err());
+    }
+}
@@ -0,0 +1,102 @@
+/*
+NOTES:
+- Signs for the JS client with a Ledger, so the payer (and admin authorities such as a
+Squads member) never has to exist as a keypair file. Node has no USB access of its own,
+so for a `usb://ledger` signer source the client runs this binary:
+  ledger-sign pubkey usb://ledger?key=0/0
+  ledger-sign sign usb://ledger?key=0/0 < message.b64
+`sign` reads a base64 serialized transaction message on stdin, waits for it to be
+approved on the device and prints the base64 signature.
+- Built on solana-remote-wallet, which is what the solana CLI uses for `usb://`
+signers, so locators and derivation paths (?key=A/C, ?full-path=m/44/501/A/C) mean the
+same thing in both. Without a query the key is m/44'/501', like the CLI.
+- The Solana app on the Ledger must be open, with blind signing enabled for
+instructions it can't display (such as greet).
+- Build with `npm run build:ledger`.
+*/
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_derivation_path::DerivationPath;
+use solana_remote_wallet::{
+    locator::Locator, remote_keypair::generate_remote_keypair, remote_wallet::maybe_wallet_manager,
+};
+use solana_signer::Signer;
+use std::{
+    convert::TryFrom,
+    env,
+    io::{self, Read},
+    process,
+};
+use uriparse::URIReference;
+
+const USAGE: &str = "Usage: ledger-sign pubkey <usb://ledger[?key=A/C]>
+       ledger-sign sign <usb://ledger[?key=A/C]> < message.b64";
+
+/// Split a `usb://` signer source into the device locator and the derivation path
+fn parse_source(source: &str) -> Result<(Locator, DerivationPath), String> {
+    let uri = URIReference::try_from(source)
+        .map_err(|err| format!("Invalid signer {}: {}", source, err))?;
+    let locator =
+        Locator::new_from_uri(&uri).map_err(|err| format!("Invalid signer {}: {}", source, err))?;
+    let path = DerivationPath::from_uri_any_query(&uri)
+        .map_err(|err| format!("Invalid derivation path in {}: {}", source, err))?
+        .unwrap_or_default();
+    Ok((locator, path))
+}
+
+fn run() -> Result<String, String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, source) = match args.as_slice() {
+        [command, source] if command == "pubkey" || command == "sign" => (command, source),
+        _ => return Err("Expected a command and a signer".to_string()),
+    };
+    let (locator, path) = parse_source(source)?;
+    let wallet_manager = maybe_wallet_manager()
+        .map_err(|err| err.to_string())?
+        .ok_or("No Ledger found, is it connected and unlocked?")?;
+    let keypair = generate_remote_keypair(locator, path, &wallet_manager, false, "payer")
+        .map_err(|err| err.to_string())?;
+    if command == "pubkey" {
+        return Ok(keypair.pubkey.to_string());
+    }
+
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|err| format!("Failed to read the message: {}", err))?;
+    let message = STANDARD
+        .decode(input.trim())
+        .map_err(|err| format!("Invalid base64 message: {}", err))?;
+    eprintln!("Approve the transaction on the Ledger");
+    let signature = keypair
+        .try_sign_message(&message)
+        .map_err(|err| err.to_string())?;
+    Ok(STANDARD.encode(signature))
+}
+
+fn main() {
+    match run() {
+        Ok(out) => println!("{}", out),
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        let (locator, path) = parse_source("usb://ledger?key=1/0").unwrap();
+        assert_eq!(locator, Locator::new_from_path("usb://ledger").unwrap());
+        assert_eq!(path, DerivationPath::new_bip44(Some(1), Some(0)));
+
+        let (_, path) = parse_source("usb://ledger").unwrap();
+        assert_eq!(path, DerivationPath::default());
+
+        assert!(parse_source("usb://ledger?key=a").is_err());
+        assert!(parse_source("prompt://?key=0/0").is_err());
+    }
+}
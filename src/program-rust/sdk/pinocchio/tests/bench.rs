@@ -0,0 +1,105 @@
+// NOTE Increment's compute units and the binary's size on pinocchio
+// (helloworld_pinocchio.so) next to the program built against solana-program 1.18
+// (helloworld.so), the same instruction on the same account. `cargo xtask bench-sdk`
+// builds both into dist/sdk and points SBF_OUT_DIR at it, so this is ignored under a
+// plain `cargo test`, like tests/size.rs. It prints the numbers rather than holding
+// either build to them: the point is to see what the SDK costs.
+use borsh::BorshSerialize;
+use helloworld_pinocchio::{
+    calendar::DayBitmap, config::CONFIG_SEED, migrations, GreetingAccount, STATE_VERSION,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+use std::{env, fs, path::Path};
+
+/// Increment's data, its tag
+const INCREMENT: u8 = helloworld_pinocchio::instruction::tag::INCREMENT as u8;
+
+/// The compute units an Increment of a fresh greeting account takes on the program
+/// built as `name`
+async fn increment_units(name: &str) -> u64 {
+    let program_id = Pubkey::new_unique();
+    let greeted = Pubkey::new_unique();
+    let greeting = GreetingAccount {
+        txt: "Hello1234567".to_string(),
+        counter: 0,
+        calendar_year: 0,
+        greeted_days: DayBitmap::default(),
+        last_update_epoch: 0,
+        direct_greets: 0,
+        cpi_greets: 0,
+    };
+    let mut program_test = ProgramTest::new(name, program_id, None);
+    program_test.prefer_bpf(true);
+    program_test.add_account(
+        greeted,
+        Account {
+            lamports: 1_000_000_000,
+            data: migrations::encode(STATE_VERSION, &greeting.try_to_vec().unwrap()),
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let (config, _) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[INCREMENT],
+        vec![
+            AccountMeta::new(greeted, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    let details = simulation.simulation_details.unwrap();
+    assert!(
+        matches!(simulation.result, Some(Ok(()))),
+        "{} failed: {:?}\n{}",
+        name,
+        simulation.result,
+        details.logs.join("\n")
+    );
+    details.units_consumed
+}
+
+#[tokio::test]
+#[ignore = "measures the builds of cargo xtask bench-sdk"]
+async fn bench_pinocchio_against_solana_program() {
+    let out_dir = env::var("SBF_OUT_DIR")
+        .unwrap_or_else(|_| panic!("No SBF builds to measure, run `cargo xtask bench-sdk`"));
+    let mut rows = Vec::new();
+    for name in ["helloworld", "helloworld_pinocchio"] {
+        let program = Path::new(&out_dir).join(format!("{}.so", name));
+        let size = fs::metadata(&program)
+            .unwrap_or_else(|err| panic!("Can't read {}: {}", program.display(), err))
+            .len();
+        rows.push((name, increment_units(name).await, size));
+    }
+    println!("{:<22} {:>10} {:>10}", "build", "CU", "bytes");
+    for (name, units, size) in &rows {
+        println!("{:<22} {:>10} {:>10}", name, units, size);
+    }
+    let (_, sdk_units, sdk_size) = rows[0];
+    let (_, units, size) = rows[1];
+    println!(
+        "pinocchio: {} CU and {} bytes fewer",
+        sdk_units as i64 - units as i64,
+        sdk_size as i64 - size as i64
+    );
+}
@@ -0,0 +1,515 @@
+//! Where each account sits in an instruction's account list, and a view of the list
+//! checked once up front
+// NOTE The orderings are the ones in instruction::INSTRUCTION_ACCOUNTS (and the
+// "Accounts expected" docs); the constants name the positions so the processors don't
+// count next_account_info() calls to know which account is which. Accounts::load()
+// checks in one pass that every account the instruction can't run without is there.
+// Signatures, owners, keys and PDAs are checked right after, by each instruction's
+// InstructionAccounts in lib.rs (see Context there), which knows what error to return
+// for each.
+#[cfg(feature = "program")]
+use crate::instruction::AccountDescription;
+#[cfg(feature = "program")]
+use solana_program::{account_info::AccountInfo, program_error::ProgramError};
+
+/// Greet's (and Increment's) greeting account
+pub const GREETING_ACCOUNT_IDX: usize = 0;
+/// Greet's (and Increment's) config account
+pub const CONFIG_IDX: usize = 1;
+/// The payer of Greet's fee, when the config sets one
+pub const PAYER_IDX: usize = 2;
+/// The Pyth price account Greet's fee is priced at
+pub const PRICE_FEED_IDX: usize = 3;
+/// The recipient of Greet's fee
+pub const FEE_RECIPIENT_IDX: usize = 4;
+/// The system program Greet pays its fee through
+pub const SYSTEM_PROGRAM_IDX: usize = 5;
+/// Where the greeting log (then the stake accounts) start when the config charges no
+/// fee. With a fee they start right after the fee accounts.
+pub const FEE_ACCOUNTS_END: usize = SYSTEM_PROGRAM_IDX + 1;
+
+/// The accounts of the instructions run by the config authority: InitConfig, the
+/// Set* instructions (SetConfig included) and InitGreetingLog
+pub mod admin {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The config authority, signing
+    pub const AUTHORITY_IDX: usize = 1;
+    /// The system program, for InitConfig to create the config account
+    pub const SYSTEM_PROGRAM_IDX: usize = 2;
+    /// The greeting log's merkle tree, the first of InitGreetingLog's log accounts
+    pub const GREETING_LOG_IDX: usize = 2;
+}
+
+/// The accounts of InitCounterSnapshot and SnapshotCounters
+pub mod snapshot {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The config authority (InitCounterSnapshot) or the crank authority
+    /// (SnapshotCounters), signing
+    pub const SIGNER_IDX: usize = 1;
+    /// The counter snapshot
+    pub const SNAPSHOT_IDX: usize = 2;
+    /// SnapshotCounters' first greeting account, the rest follow it
+    pub const GREETING_ACCOUNTS_IDX: usize = 3;
+}
+
+/// Claim's accounts
+pub mod claim {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The claimant, signing
+    pub const CLAIMANT_IDX: usize = 1;
+    /// The claimed bitmap of the leaf
+    pub const CLAIMED_IDX: usize = 2;
+    /// The claim vault
+    pub const VAULT_IDX: usize = 3;
+    /// The system program, to create the claimed bitmap and pay out through
+    pub const SYSTEM_PROGRAM_IDX: usize = 4;
+}
+
+/// Snapshot's accounts
+pub mod milestone {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The greeting account whose counter is recorded
+    pub const GREETING_ACCOUNT_IDX: usize = 1;
+    /// The recorder, signing
+    pub const RECORDER_IDX: usize = 2;
+    /// The snapshot
+    pub const SNAPSHOT_IDX: usize = 3;
+    /// The system program, to create the snapshot
+    pub const SYSTEM_PROGRAM_IDX: usize = 4;
+}
+
+/// CountTag's accounts
+pub mod tags {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The greeting account counted
+    pub const GREETING_ACCOUNT_IDX: usize = 1;
+    /// The payer, signing
+    pub const PAYER_IDX: usize = 2;
+    /// The tag counters
+    pub const TAG_COUNTERS_IDX: usize = 3;
+    /// The system program, to create the tag counters
+    pub const SYSTEM_PROGRAM_IDX: usize = 4;
+}
+
+/// CheckRecency's accounts
+pub mod recency {
+    /// The SlotHashes sysvar
+    pub const SLOT_HASHES_IDX: usize = 0;
+}
+
+/// GreetMany's accounts
+pub mod greet_many {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The first greeting account, the rest follow it
+    pub const GREETING_ACCOUNTS_IDX: usize = 1;
+}
+
+/// GreetFor's accounts
+pub mod greet_for {
+    /// The target's greeting account
+    pub const GREETING_ACCOUNT_IDX: usize = 0;
+    /// The config account
+    pub const CONFIG_IDX: usize = 1;
+    /// The payer, signing
+    pub const PAYER_IDX: usize = 2;
+    /// The account credited with the greeting
+    pub const TARGET_IDX: usize = 3;
+    /// The system program, to create the greeting account and pay the fee through
+    pub const SYSTEM_PROGRAM_IDX: usize = 4;
+    /// The Pyth price account the fee is priced at, when there's a fee. Without one
+    /// the greeting log's accounts start here.
+    pub const PRICE_FEED_IDX: usize = 5;
+    /// The recipient of the fee
+    pub const FEE_RECIPIENT_IDX: usize = 6;
+    /// Where the greeting log's accounts start when there's a fee
+    pub const FEE_ACCOUNTS_END: usize = FEE_RECIPIENT_IDX + 1;
+}
+
+/// ExportState's and ImportState's accounts
+pub mod state {
+    /// The greeting account exported or imported into
+    pub const GREETING_ACCOUNT_IDX: usize = 0;
+    /// ImportState's source account
+    pub const SOURCE_IDX: usize = 1;
+    /// ImportState's config account
+    pub const CONFIG_IDX: usize = 2;
+    /// The config authority, signing ImportState
+    pub const AUTHORITY_IDX: usize = 3;
+}
+
+/// Tick's accounts
+pub mod tick {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The crank authority, signing
+    pub const CRANK_AUTHORITY_IDX: usize = 1;
+    /// The first greeting account, the rest follow it
+    pub const GREETING_ACCOUNTS_IDX: usize = 2;
+}
+
+/// CommitLuck's and RevealLuck's accounts
+pub mod luck {
+    /// The greeting account
+    pub const GREETING_ACCOUNT_IDX: usize = 0;
+    /// The luck ticket
+    pub const TICKET_IDX: usize = 1;
+    /// The Switchboard randomness account
+    pub const RANDOMNESS_IDX: usize = 2;
+    /// CommitLuck's greeter, signing
+    pub const COMMIT_GREETER_IDX: usize = 3;
+    /// CommitLuck's system program, to create the ticket
+    pub const COMMIT_SYSTEM_PROGRAM_IDX: usize = 4;
+    /// RevealLuck's prize vault
+    pub const VAULT_IDX: usize = 3;
+    /// RevealLuck's greeter, receiving the prize and the ticket's rent
+    pub const REVEAL_GREETER_IDX: usize = 4;
+    /// RevealLuck's system program, to pay the prize through
+    pub const REVEAL_SYSTEM_PROGRAM_IDX: usize = 5;
+}
+
+/// Deposit's accounts
+pub mod deposit {
+    /// The config account
+    pub const CONFIG_IDX: usize = 0;
+    /// The stake position
+    pub const POSITION_IDX: usize = 1;
+    /// The staker, signing
+    pub const STAKER_IDX: usize = 2;
+    /// The staker's token account paid from
+    pub const SOURCE_IDX: usize = 3;
+    /// The stake vault
+    pub const VAULT_IDX: usize = 4;
+    /// The token program
+    pub const TOKEN_PROGRAM_IDX: usize = 5;
+    /// The system program, to create the stake position
+    pub const SYSTEM_PROGRAM_IDX: usize = 6;
+}
+
+/// WithdrawStake's accounts
+pub mod withdraw_stake {
+    /// The stake position
+    pub const POSITION_IDX: usize = 0;
+    /// The staker, signing
+    pub const STAKER_IDX: usize = 1;
+    /// The stake vault
+    pub const VAULT_IDX: usize = 2;
+    /// The token account paid to
+    pub const DESTINATION_IDX: usize = 3;
+    /// The stake authority, the vault's owner
+    pub const STAKE_AUTHORITY_IDX: usize = 4;
+    /// The token program
+    pub const TOKEN_PROGRAM_IDX: usize = 5;
+}
+
+/// The accounts of CreateChallenge, ClaimChallenge and RefundChallenge
+pub mod challenge {
+    /// The challenge
+    pub const CHALLENGE_IDX: usize = 0;
+    /// CreateChallenge's and RefundChallenge's challenger, signing
+    pub const CHALLENGER_IDX: usize = 1;
+    /// CreateChallenge's challengee
+    pub const CREATE_CHALLENGEE_IDX: usize = 2;
+    /// CreateChallenge's greeting account of the challengee
+    pub const CREATE_GREETING_ACCOUNT_IDX: usize = 3;
+    /// CreateChallenge's system program, to create the challenge
+    pub const SYSTEM_PROGRAM_IDX: usize = 4;
+    /// ClaimChallenge's challengee, signing
+    pub const CLAIM_CHALLENGEE_IDX: usize = 1;
+    /// ClaimChallenge's greeting account of the challengee
+    pub const CLAIM_GREETING_ACCOUNT_IDX: usize = 2;
+}
+
+/// ReceiveCrossChainGreet's and RegisterForeignGreeter's accounts
+pub mod cross_chain {
+    /// ReceiveCrossChainGreet's posted VAA
+    pub const POSTED_VAA_IDX: usize = 0;
+    /// ReceiveCrossChainGreet's foreign greeter
+    pub const FOREIGN_GREETER_IDX: usize = 1;
+    /// RegisterForeignGreeter's config account
+    pub const REGISTER_CONFIG_IDX: usize = 0;
+    /// RegisterForeignGreeter's config authority, signing
+    pub const REGISTER_AUTHORITY_IDX: usize = 1;
+    /// RegisterForeignGreeter's foreign greeter
+    pub const REGISTER_FOREIGN_GREETER_IDX: usize = 2;
+    /// RegisterForeignGreeter's system program, to create the foreign greeter
+    pub const REGISTER_SYSTEM_PROGRAM_IDX: usize = 3;
+}
+
+/// CreateSession's and RevokeSession's accounts
+pub mod session {
+    /// The session token
+    pub const SESSION_TOKEN_IDX: usize = 0;
+    /// The authority the session key stands in for, signing
+    pub const AUTHORITY_IDX: usize = 1;
+    /// CreateSession's session key
+    pub const SESSION_KEY_IDX: usize = 2;
+    /// CreateSession's system program, to create the session token
+    pub const SYSTEM_PROGRAM_IDX: usize = 3;
+}
+
+/// An instruction's accounts, with the ones it can't run without checked to be there
+#[cfg(feature = "program")]
+pub struct Accounts<'a, 'b> {
+    accounts: &'a [AccountInfo<'b>],
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> Accounts<'a, 'b> {
+    /// Check `accounts` has every account `expected` doesn't mark optional, returning
+    /// NotEnoughAccountKeys if one is missing. Pass the instruction's entry of
+    /// INSTRUCTION_ACCOUNTS.
+    pub fn load(
+        accounts: &'a [AccountInfo<'b>],
+        expected: &[AccountDescription],
+    ) -> Result<Self, ProgramError> {
+        let required = expected
+            .iter()
+            .filter(|description| !description.optional)
+            .count();
+        if accounts.len() < required {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(Self { accounts })
+    }
+
+    /// The account at `idx`, or NotEnoughAccountKeys for an optional one left out
+    pub fn get(&self, idx: usize) -> Result<&'a AccountInfo<'b>, ProgramError> {
+        self.accounts
+            .get(idx)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    }
+
+    /// The accounts from `idx` on, for the variable part of the list
+    pub fn iter_from(&self, idx: usize) -> std::slice::Iter<'a, AccountInfo<'b>> {
+        self.accounts.get(idx..).unwrap_or(&[]).iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        fixtures,
+        instruction::{tag, INSTRUCTION_ACCOUNTS},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_indexes_match_instruction_accounts() {
+        let name = |tag: usize, idx: usize| INSTRUCTION_ACCOUNTS[tag][idx].name;
+        for tag in [tag::GREET, tag::INCREMENT] {
+            assert_eq!(name(tag, GREETING_ACCOUNT_IDX), "greetingAccount");
+            assert_eq!(name(tag, CONFIG_IDX), "config");
+        }
+        assert_eq!(name(tag::GREET, PAYER_IDX), "payer");
+        assert_eq!(name(tag::GREET, PRICE_FEED_IDX), "priceFeed");
+        assert_eq!(name(tag::GREET, FEE_RECIPIENT_IDX), "feeRecipient");
+        assert_eq!(name(tag::GREET, SYSTEM_PROGRAM_IDX), "systemProgram");
+        assert_eq!(name(tag::GREET, FEE_ACCOUNTS_END), "greetingLog");
+        for tag in [
+            tag::INIT_CONFIG,
+            tag::SET_DECAY,
+            tag::SET_CRANK_AUTHORITY,
+            tag::SET_FEE,
+            tag::SET_AUTHORITY,
+            tag::SET_STAKE,
+            tag::INIT_GREETING_LOG,
+            tag::SET_CONFIG,
+        ] {
+            assert_eq!(name(tag, admin::CONFIG_IDX), "config");
+            assert_eq!(name(tag, admin::AUTHORITY_IDX), "authority");
+        }
+        assert_eq!(
+            name(tag::INIT_CONFIG, admin::SYSTEM_PROGRAM_IDX),
+            "systemProgram"
+        );
+        let greet_for = |idx: usize| name(tag::GREET_FOR, idx);
+        assert_eq!(
+            greet_for(greet_for::GREETING_ACCOUNT_IDX),
+            "greetingAccount"
+        );
+        assert_eq!(greet_for(greet_for::CONFIG_IDX), "config");
+        assert_eq!(greet_for(greet_for::PAYER_IDX), "payer");
+        assert_eq!(greet_for(greet_for::TARGET_IDX), "target");
+        assert_eq!(greet_for(greet_for::SYSTEM_PROGRAM_IDX), "systemProgram");
+        assert_eq!(greet_for(greet_for::PRICE_FEED_IDX), "priceFeed");
+        assert_eq!(greet_for(greet_for::FEE_RECIPIENT_IDX), "feeRecipient");
+        assert_eq!(greet_for(greet_for::FEE_ACCOUNTS_END), "greetingLog");
+        assert_eq!(name(tag::GREET_MANY, greet_many::CONFIG_IDX), "config");
+        assert_eq!(
+            name(tag::GREET_MANY, greet_many::GREETING_ACCOUNTS_IDX),
+            "greetingAccount"
+        );
+        assert_eq!(
+            name(tag::INIT_GREETING_LOG, admin::GREETING_LOG_IDX),
+            "merkleTree"
+        );
+        let init = |idx: usize| name(tag::INIT_COUNTER_SNAPSHOT, idx);
+        assert_eq!(init(snapshot::CONFIG_IDX), "config");
+        assert_eq!(init(snapshot::SIGNER_IDX), "authority");
+        assert_eq!(init(snapshot::SNAPSHOT_IDX), "snapshot");
+        let counters = |idx: usize| name(tag::SNAPSHOT_COUNTERS, idx);
+        assert_eq!(counters(snapshot::CONFIG_IDX), "config");
+        assert_eq!(counters(snapshot::SIGNER_IDX), "crankAuthority");
+        assert_eq!(counters(snapshot::SNAPSHOT_IDX), "snapshot");
+        assert_eq!(
+            INSTRUCTION_ACCOUNTS[tag::SNAPSHOT_COUNTERS].len(),
+            snapshot::GREETING_ACCOUNTS_IDX
+        );
+        let claim_name = |idx: usize| name(tag::CLAIM, idx);
+        assert_eq!(claim_name(claim::CONFIG_IDX), "config");
+        assert_eq!(claim_name(claim::CLAIMANT_IDX), "claimant");
+        assert_eq!(claim_name(claim::CLAIMED_IDX), "claimedBitmap");
+        assert_eq!(claim_name(claim::VAULT_IDX), "claimVault");
+        assert_eq!(claim_name(claim::SYSTEM_PROGRAM_IDX), "systemProgram");
+        let milestone_name = |idx: usize| name(tag::SNAPSHOT, idx);
+        assert_eq!(milestone_name(milestone::CONFIG_IDX), "config");
+        assert_eq!(
+            milestone_name(milestone::GREETING_ACCOUNT_IDX),
+            "greetingAccount"
+        );
+        assert_eq!(milestone_name(milestone::RECORDER_IDX), "recorder");
+        assert_eq!(milestone_name(milestone::SNAPSHOT_IDX), "snapshot");
+        assert_eq!(
+            milestone_name(milestone::SYSTEM_PROGRAM_IDX),
+            "systemProgram"
+        );
+        let tags_name = |idx: usize| name(tag::COUNT_TAG, idx);
+        assert_eq!(tags_name(tags::CONFIG_IDX), "config");
+        assert_eq!(tags_name(tags::GREETING_ACCOUNT_IDX), "greetingAccount");
+        assert_eq!(tags_name(tags::PAYER_IDX), "payer");
+        assert_eq!(tags_name(tags::TAG_COUNTERS_IDX), "tagCounters");
+        assert_eq!(tags_name(tags::SYSTEM_PROGRAM_IDX), "systemProgram");
+        assert_eq!(
+            name(tag::CHECK_RECENCY, recency::SLOT_HASHES_IDX),
+            "slotHashes"
+        );
+        let export = |idx: usize| name(tag::EXPORT_STATE, idx);
+        assert_eq!(export(state::GREETING_ACCOUNT_IDX), "greetingAccount");
+        let import = |idx: usize| name(tag::IMPORT_STATE, idx);
+        assert_eq!(import(state::GREETING_ACCOUNT_IDX), "greetingAccount");
+        assert_eq!(import(state::SOURCE_IDX), "sourceAccount");
+        assert_eq!(import(state::CONFIG_IDX), "config");
+        assert_eq!(import(state::AUTHORITY_IDX), "authority");
+        assert_eq!(name(tag::TICK, tick::CONFIG_IDX), "config");
+        assert_eq!(name(tag::TICK, tick::CRANK_AUTHORITY_IDX), "crankAuthority");
+        assert_eq!(
+            INSTRUCTION_ACCOUNTS[tag::TICK].len(),
+            tick::GREETING_ACCOUNTS_IDX
+        );
+        for tag in [tag::COMMIT_LUCK, tag::REVEAL_LUCK] {
+            assert_eq!(name(tag, luck::GREETING_ACCOUNT_IDX), "greetingAccount");
+            assert_eq!(name(tag, luck::TICKET_IDX), "ticket");
+            assert_eq!(name(tag, luck::RANDOMNESS_IDX), "randomness");
+        }
+        let commit = |idx: usize| name(tag::COMMIT_LUCK, idx);
+        assert_eq!(commit(luck::COMMIT_GREETER_IDX), "greeter");
+        assert_eq!(commit(luck::COMMIT_SYSTEM_PROGRAM_IDX), "systemProgram");
+        let reveal = |idx: usize| name(tag::REVEAL_LUCK, idx);
+        assert_eq!(reveal(luck::VAULT_IDX), "vault");
+        assert_eq!(reveal(luck::REVEAL_GREETER_IDX), "greeter");
+        assert_eq!(reveal(luck::REVEAL_SYSTEM_PROGRAM_IDX), "systemProgram");
+        let deposit_name = |idx: usize| name(tag::DEPOSIT, idx);
+        assert_eq!(deposit_name(deposit::CONFIG_IDX), "config");
+        assert_eq!(deposit_name(deposit::POSITION_IDX), "stakePosition");
+        assert_eq!(deposit_name(deposit::STAKER_IDX), "staker");
+        assert_eq!(deposit_name(deposit::SOURCE_IDX), "source");
+        assert_eq!(deposit_name(deposit::VAULT_IDX), "vault");
+        assert_eq!(deposit_name(deposit::TOKEN_PROGRAM_IDX), "tokenProgram");
+        assert_eq!(deposit_name(deposit::SYSTEM_PROGRAM_IDX), "systemProgram");
+        let withdraw = |idx: usize| name(tag::WITHDRAW_STAKE, idx);
+        assert_eq!(withdraw(withdraw_stake::POSITION_IDX), "stakePosition");
+        assert_eq!(withdraw(withdraw_stake::STAKER_IDX), "staker");
+        assert_eq!(withdraw(withdraw_stake::VAULT_IDX), "vault");
+        assert_eq!(withdraw(withdraw_stake::DESTINATION_IDX), "destination");
+        assert_eq!(
+            withdraw(withdraw_stake::STAKE_AUTHORITY_IDX),
+            "stakeAuthority"
+        );
+        assert_eq!(withdraw(withdraw_stake::TOKEN_PROGRAM_IDX), "tokenProgram");
+        for tag in [
+            tag::CREATE_CHALLENGE,
+            tag::CLAIM_CHALLENGE,
+            tag::REFUND_CHALLENGE,
+        ] {
+            assert_eq!(name(tag, challenge::CHALLENGE_IDX), "challenge");
+        }
+        let create = |idx: usize| name(tag::CREATE_CHALLENGE, idx);
+        assert_eq!(create(challenge::CHALLENGER_IDX), "challenger");
+        assert_eq!(create(challenge::CREATE_CHALLENGEE_IDX), "challengee");
+        assert_eq!(
+            create(challenge::CREATE_GREETING_ACCOUNT_IDX),
+            "greetingAccount"
+        );
+        assert_eq!(create(challenge::SYSTEM_PROGRAM_IDX), "systemProgram");
+        let claim_challenge = |idx: usize| name(tag::CLAIM_CHALLENGE, idx);
+        assert_eq!(
+            claim_challenge(challenge::CLAIM_CHALLENGEE_IDX),
+            "challengee"
+        );
+        assert_eq!(
+            claim_challenge(challenge::CLAIM_GREETING_ACCOUNT_IDX),
+            "greetingAccount"
+        );
+        assert_eq!(
+            name(tag::REFUND_CHALLENGE, challenge::CHALLENGER_IDX),
+            "challenger"
+        );
+        let receive = |idx: usize| name(tag::RECEIVE_CROSS_CHAIN_GREET, idx);
+        assert_eq!(receive(cross_chain::POSTED_VAA_IDX), "postedVaa");
+        assert_eq!(receive(cross_chain::FOREIGN_GREETER_IDX), "foreignGreeter");
+        let register = |idx: usize| name(tag::REGISTER_FOREIGN_GREETER, idx);
+        assert_eq!(register(cross_chain::REGISTER_CONFIG_IDX), "config");
+        assert_eq!(register(cross_chain::REGISTER_AUTHORITY_IDX), "authority");
+        assert_eq!(
+            register(cross_chain::REGISTER_FOREIGN_GREETER_IDX),
+            "foreignGreeter"
+        );
+        assert_eq!(
+            register(cross_chain::REGISTER_SYSTEM_PROGRAM_IDX),
+            "systemProgram"
+        );
+        for tag in [tag::CREATE_SESSION, tag::REVOKE_SESSION] {
+            assert_eq!(name(tag, session::SESSION_TOKEN_IDX), "sessionToken");
+            assert_eq!(name(tag, session::AUTHORITY_IDX), "authority");
+        }
+        let create_session = |idx: usize| name(tag::CREATE_SESSION, idx);
+        assert_eq!(create_session(session::SESSION_KEY_IDX), "sessionKey");
+        assert_eq!(create_session(session::SYSTEM_PROGRAM_IDX), "systemProgram");
+    }
+
+    #[test]
+    fn test_load() {
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let accounts: Vec<_> = (0..3)
+            .map(|_| fixtures::account(key, false, 0, vec![], owner))
+            .collect();
+        let greet = INSTRUCTION_ACCOUNTS[tag::GREET];
+
+        // Greet runs with just its greeting and config accounts
+        let loaded = Accounts::load(&accounts[..2], greet).unwrap();
+        assert!(loaded.get(CONFIG_IDX).is_ok());
+        assert_eq!(
+            loaded.get(PAYER_IDX).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+        assert_eq!(loaded.iter_from(PAYER_IDX).count(), 0);
+        assert_eq!(
+            Accounts::load(&accounts[..1], greet).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+        let loaded = Accounts::load(&accounts, greet).unwrap();
+        assert_eq!(loaded.iter_from(PAYER_IDX).count(), 1);
+        assert_eq!(loaded.iter_from(FEE_ACCOUNTS_END).count(), 0);
+        assert_eq!(
+            Accounts::load(&accounts[..2], INSTRUCTION_ACCOUNTS[tag::INIT_CONFIG]).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+}
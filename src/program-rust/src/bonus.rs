@@ -0,0 +1,73 @@
+//! Greetings that count double at the start of every epoch
+// NOTE With epoch_bonus_slots set in the config (see config.rs), a greeting in the
+// first epoch_bonus_slots slots of an epoch counts BONUS_MULTIPLIER times what it
+// would have otherwise. The Clock sysvar has the current slot and epoch but not where
+// the epoch started, so that comes from the EpochSchedule sysvar: slot / slots_per_epoch
+// would be wrong on a cluster with warmup, whose first epochs are shorter.
+//
+// The EpochSchedule is only read with the bonus on, it costs a syscall every greeting.
+// A bonus of slots_per_epoch or more makes every greeting count double.
+use solana_program::{clock::Slot, epoch_schedule::EpochSchedule};
+
+/// How many times a greeting in the bonus slots counts
+pub const BONUS_MULTIPLIER: u64 = 2;
+
+/// Slots `slot` is into its epoch under `schedule`, 0 on an epoch's first slot
+pub fn slot_index(schedule: &EpochSchedule, slot: Slot) -> u64 {
+    schedule.get_epoch_and_slot_index(slot).1
+}
+
+/// Whether a greeting at `slot` is in the first `bonus_slots` slots of its epoch
+pub fn is_bonus_slot(schedule: &EpochSchedule, slot: Slot, bonus_slots: u64) -> bool {
+    slot_index(schedule, slot) < bonus_slots
+}
+
+/// What a greeting of `weight` at `slot` counts for, with the first `bonus_slots` slots
+/// of every epoch counting double. A `bonus_slots` of 0 turns the bonus off.
+pub fn apply(schedule: &EpochSchedule, slot: Slot, bonus_slots: u64, weight: u64) -> u64 {
+    if is_bonus_slot(schedule, slot, bonus_slots) {
+        weight.saturating_mul(BONUS_MULTIPLIER)
+    } else {
+        weight
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bonus_at_the_start_of_every_epoch() {
+        let schedule = EpochSchedule::custom(1_000, 1_000, false);
+        // Epoch 7 runs from slot 7_000 to 7_999
+        assert_eq!(apply(&schedule, 7_000, 10, 1), 2);
+        assert_eq!(apply(&schedule, 7_009, 10, 1), 2);
+        assert_eq!(apply(&schedule, 7_010, 10, 1), 1);
+        assert_eq!(apply(&schedule, 7_999, 10, 1), 1);
+        assert_eq!(apply(&schedule, 8_000, 10, 1), 2);
+        // Weights other than 1 double too, and the bonus can be off
+        assert_eq!(apply(&schedule, 8_000, 10, 3), 6);
+        assert_eq!(apply(&schedule, 8_000, 10, 0), 0);
+        assert_eq!(apply(&schedule, 8_000, 0, 3), 3);
+        assert_eq!(apply(&schedule, 8_000, 10, u64::MAX), u64::MAX);
+        // A bonus as long as the epoch covers all of it
+        assert_eq!(apply(&schedule, 8_999, 1_000, 1), 2);
+    }
+
+    #[test]
+    fn test_bonus_during_warmup() {
+        // With warmup the epochs start at 32 slots and double until slots_per_epoch
+        let schedule = EpochSchedule::custom(1_024, 1_024, true);
+        assert_eq!(schedule.get_first_slot_in_epoch(1), 32);
+        assert_eq!(schedule.get_first_slot_in_epoch(2), 96);
+        assert_eq!(slot_index(&schedule, 96), 0);
+        assert!(is_bonus_slot(&schedule, 96, 4));
+        assert!(is_bonus_slot(&schedule, 99, 4));
+        assert!(!is_bonus_slot(&schedule, 100, 4));
+        // slot / slots_per_epoch would put slot 96 well into epoch 0
+        assert!(!is_bonus_slot(&schedule, 95, 4));
+        let first_normal_slot = schedule.first_normal_slot;
+        assert!(is_bonus_slot(&schedule, first_normal_slot + 1_024, 4));
+        assert!(!is_bonus_slot(&schedule, first_normal_slot + 1_028, 4));
+    }
+}
@@ -0,0 +1,205 @@
+//! Which days of the year an account was greeted on
+// NOTE Greet marks the current day (UTC, from the Clock sysvar) in a 366 bit bitmap,
+// one bit per day of the year with January 1st as bit 0, enough to draw a GitHub-style
+// contribution graph. The bitmap only covers calendar_year: the first greeting of a new
+// year clears it. A clock that runs backwards into an earlier year (it can drift a
+// little between validators) leaves the bitmap alone.
+use borsh::{
+    schema::{BorshSchema, Declaration, Definition},
+    BorshDeserialize, BorshSerialize,
+};
+use std::{collections::HashMap, io};
+
+/// Days in the longest year
+pub const DAYS_IN_YEAR: usize = 366;
+
+/// Bytes the day bitmap takes, one bit per day rounded up
+pub const DAY_BITMAP_SIZE: usize = 46;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// One bit per day of the year, bit `day % 8` of byte `day / 8`
+// NOTE Borsh only implements arrays of some lengths, so the (de)serialization and
+// schema are written out: the layout is a plain [u8; 46].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayBitmap(pub [u8; DAY_BITMAP_SIZE]);
+
+impl Default for DayBitmap {
+    fn default() -> Self {
+        DayBitmap([0; DAY_BITMAP_SIZE])
+    }
+}
+
+impl DayBitmap {
+    /// Whether day `day` (0 is January 1st) is marked
+    pub fn is_set(&self, day: u16) -> bool {
+        let day = day as usize;
+        day < DAYS_IN_YEAR && self.0[day / 8] & (1 << (day % 8)) != 0
+    }
+
+    /// Mark day `day` (0 is January 1st), ignoring days past the end of the year
+    pub fn set(&mut self, day: u16) {
+        let day = day as usize;
+        if day < DAYS_IN_YEAR {
+            self.0[day / 8] |= 1 << (day % 8);
+        }
+    }
+
+    /// Number of days marked
+    pub fn count(&self) -> u32 {
+        self.0.iter().map(|byte| byte.count_ones()).sum()
+    }
+}
+
+impl BorshSerialize for DayBitmap {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl BorshDeserialize for DayBitmap {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        if buf.len() < DAY_BITMAP_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected end of day bitmap",
+            ));
+        }
+        let mut days = [0; DAY_BITMAP_SIZE];
+        days.copy_from_slice(&buf[..DAY_BITMAP_SIZE]);
+        *buf = &buf[DAY_BITMAP_SIZE..];
+        Ok(DayBitmap(days))
+    }
+}
+
+impl BorshSchema for DayBitmap {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        Self::add_definition(
+            Self::declaration(),
+            Definition::Array {
+                length: DAY_BITMAP_SIZE as u32,
+                elements: u8::declaration(),
+            },
+            definitions,
+        );
+    }
+
+    fn declaration() -> Declaration {
+        format!("Array<u8, {}>", DAY_BITMAP_SIZE)
+    }
+}
+
+/// UTC calendar year and day of the year (0 is January 1st) of a unix timestamp
+pub fn year_and_day(unix_timestamp: i64) -> (i64, u16) {
+    // Civil-from-days (Howard Hinnant), with years starting on March 1st so the
+    // leap day falls at the end
+    let days = unix_timestamp.div_euclid(SECONDS_PER_DAY) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_march_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // January and February belong to the next civil year
+    let january = day_of_march_year >= 306;
+    let year = year_of_era + era * 400 + if january { 1 } else { 0 };
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let day = if january {
+        day_of_march_year - 306
+    } else {
+        day_of_march_year + 31 + 28 + if leap { 1 } else { 0 }
+    };
+    (year, day as u16)
+}
+
+/// Whether `days` of `year` has a day marked in the `window` days up to and including
+/// the day of `unix_timestamp`. Days of other years aren't in the bitmap.
+pub fn greeted_within(year: u16, days: &DayBitmap, unix_timestamp: i64, window: u16) -> bool {
+    let (greeted_year, day) = year_and_day(unix_timestamp);
+    if window == 0 || greeted_year != i64::from(year) {
+        return false;
+    }
+    (day.saturating_sub(window - 1)..=day).any(|day| days.is_set(day))
+}
+
+/// Mark the day of `unix_timestamp` in `days`, moving `year` on (and clearing the
+/// bitmap) on the first greeting of a new year. Returns false, leaving both alone,
+/// when the timestamp falls before `year` or outside the years a u16 holds.
+pub fn mark_greeted(year: &mut u16, days: &mut DayBitmap, unix_timestamp: i64) -> bool {
+    let (greeted_year, day) = year_and_day(unix_timestamp);
+    if greeted_year < i64::from(*year) || greeted_year > i64::from(u16::MAX) {
+        return false;
+    }
+    if greeted_year > i64::from(*year) {
+        *year = greeted_year as u16;
+        *days = DayBitmap::default();
+    }
+    days.set(day);
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2024-01-01T00:00:00Z, a leap year
+    const JAN_1_2024: i64 = 1_704_067_200;
+
+    #[test]
+    fn test_year_and_day() {
+        assert_eq!(year_and_day(0), (1970, 0));
+        assert_eq!(year_and_day(JAN_1_2024), (2024, 0));
+        assert_eq!(year_and_day(JAN_1_2024 - 1), (2023, 364));
+        // Leap day and New Year's Eve of a leap year
+        assert_eq!(year_and_day(JAN_1_2024 + 59 * SECONDS_PER_DAY), (2024, 59));
+        assert_eq!(
+            year_and_day(JAN_1_2024 + 365 * SECONDS_PER_DAY),
+            (2024, 365)
+        );
+        assert_eq!(year_and_day(JAN_1_2024 + 366 * SECONDS_PER_DAY), (2025, 0));
+        // March 1st of a common year
+        assert_eq!(year_and_day(1_740_787_200), (2025, 59));
+        assert_eq!(year_and_day(-1), (1969, 364));
+    }
+
+    #[test]
+    fn test_mark_greeted_rolls_over_years() {
+        let mut year = 0;
+        let mut days = DayBitmap::default();
+        assert!(mark_greeted(&mut year, &mut days, JAN_1_2024 - 1));
+        assert_eq!(year, 2023);
+        assert!(days.is_set(364));
+
+        assert!(mark_greeted(
+            &mut year,
+            &mut days,
+            JAN_1_2024 + 365 * SECONDS_PER_DAY
+        ));
+        assert!(mark_greeted(&mut year, &mut days, JAN_1_2024 + 10));
+        assert_eq!(year, 2024);
+        assert!(days.is_set(0) && days.is_set(365));
+        assert_eq!(days.count(), 2);
+
+        // Greeted twice the same day
+        assert!(mark_greeted(&mut year, &mut days, JAN_1_2024 + 20));
+        assert_eq!(days.count(), 2);
+
+        // The clock going back a year changes nothing
+        assert!(!mark_greeted(&mut year, &mut days, JAN_1_2024 - 1));
+        assert_eq!((year, days.count()), (2024, 2));
+    }
+
+    #[test]
+    fn test_day_bitmap_layout() {
+        let mut days = DayBitmap::default();
+        days.set(9);
+        days.set(365);
+        // Past the end of the year
+        days.set(DAYS_IN_YEAR as u16);
+        let data = days.try_to_vec().unwrap();
+        assert_eq!(data.len(), 46);
+        assert_eq!(data[1], 0b0000_0010);
+        assert_eq!(data[45], 0b0010_0000);
+        assert_eq!(DayBitmap::try_from_slice(&data).unwrap(), days);
+        assert!(DayBitmap::try_from_slice(&data[1..]).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! Greeting challenges: lamports escrowed for whoever greets enough before a deadline
+// NOTE A challenger escrows lamports in a challenge account, a PDA of this program at
+// [CHALLENGE_SEED, challenger, challengee] (so one open challenge per pair), daring the
+// challengee to get a greeting account's counter up by `greet_count` before
+// `deadline_slot`. Greet doesn't take a signer, so the program can't tell who greeted:
+// the challenge counts greetings of the named account, whoever sends them, and only
+// the challengee can claim. Before the deadline the challengee can claim the escrow
+// once the counter is there; from the deadline on only the challenger can take it
+// back, even if the counter got there but nobody claimed in time. Either way the
+// challenge account is closed and its rent goes with the escrow.
+//
+// The counter the challenge is measured against is the stored one, so on a decaying
+// deployment greetings have to come faster than the decay, and staked greetings count
+// for their weight. The target is the counter at creation plus `greet_count`, and only
+// this program's greetings and the config authority's ImportState move the counter, so
+// the challengee can't set it themselves: somebody, anybody, has to greet it there.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of a challenge, followed by the challenger's and the challengee's keys
+pub const CHALLENGE_SEED: &[u8] = b"challenge";
+
+/// An open challenge
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct Challenge {
+    /// key of who escrowed the lamports, refunded after the deadline
+    pub challenger: [u8; 32],
+    /// key of who is challenged, paid on success
+    pub challengee: [u8; 32],
+    /// key of the greeting account to greet
+    pub greeting_account: [u8; 32],
+    /// counter the greeting account has to reach
+    pub target_counter: u64,
+    /// first slot the challenge can no longer be claimed in
+    pub deadline_slot: u64,
+    /// lamports escrowed, on top of the account's rent
+    pub amount: u64,
+}
+
+/// Size of a challenge account's data
+pub const CHALLENGE_SIZE: usize = 32 + 32 + 32 + 8 + 8 + 8;
+
+impl Challenge {
+    /// Whether the deadline has passed at `slot`
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot >= self.deadline_slot
+    }
+
+    /// Whether a greeting account at `counter` completes the challenge
+    pub fn is_met(&self, counter: u64) -> bool {
+        counter >= self.target_counter
+    }
+}
+
+/// Address and bump seed of `challenger`'s challenge to `challengee`
+#[cfg(feature = "program")]
+pub fn challenge_address(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    challengee: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CHALLENGE_SEED, challenger.as_ref(), challengee.as_ref()],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_challenge() {
+        let challenge = Challenge {
+            target_counter: 15,
+            deadline_slot: 100,
+            ..Challenge::default()
+        };
+        assert_eq!(challenge.try_to_vec().unwrap().len(), CHALLENGE_SIZE);
+        assert!(!challenge.is_met(14));
+        assert!(challenge.is_met(15));
+        assert!(!challenge.is_expired(99));
+        assert!(challenge.is_expired(100));
+    }
+}
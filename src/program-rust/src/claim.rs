@@ -0,0 +1,230 @@
+//! Claims against a merkle root in the config: an allowlist, or a distribution of
+//! lamports, each leaf paid out to its claimant once
+// NOTE The config authority publishes the list off chain and sets its root with
+// SetConfig's claim_root; the all-zero root means there's nothing to claim. A leaf is
+// sha256(0 || index as u32 LE || claimant || amount as u64 LE), and the tree is built
+// like a counter snapshot's (see counter_snapshot.rs): nodes are sha256(1 || left ||
+// right), a leaf's index picks the side at each level, and the list is padded with
+// all-zero leaves to a power of two. claim_root() and claim_proof() build both from
+// the list.
+//
+// Claim pays the leaf's amount out of the claim vault, a PDA at [CLAIM_VAULT_SEED]
+// left owned by the system program like the tip vault (see luck.rs): funding it is a
+// plain SOL transfer. An amount of 0 makes the leaf a pure allowlist entry.
+//
+// Which leaves were claimed is kept in bitmaps, PDAs at [CLAIMED_SEED, root, bitmap
+// number as u32 LE] of CLAIMS_PER_BITMAP bits each, created by the first claimant of
+// their range. The root is in the seeds, so a new root starts over with fresh bitmaps,
+// while setting a root again finds its old ones: what was claimed stays claimed. A
+// bitmap opens with Anchor's account discriminator, CLAIMED_BITMAP_DISCRIMINATOR, so
+// it can't pass for a greeting account (whose schema 1 reads any zeroed data) and be
+// greeted clear.
+use crate::counter_snapshot::node_hash;
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+/// Seed of the claim vault's address
+pub const CLAIM_VAULT_SEED: &[u8] = b"claim-vault";
+
+/// Seed of a claimed bitmap's address, followed by the root and the bitmap's number
+pub const CLAIMED_SEED: &[u8] = b"claimed";
+
+/// Anchor discriminator of a claimed bitmap: sha256("account:ClaimedBitmap")[..8]
+pub const CLAIMED_BITMAP_DISCRIMINATOR: [u8; 8] = [42, 242, 64, 97, 154, 187, 151, 183];
+
+/// Bytes of bits in a claimed bitmap, after the discriminator
+const CLAIMED_BITS_SIZE: usize = 1024;
+
+/// Size of a claimed bitmap's data, the discriminator included
+pub const CLAIMED_BITMAP_SIZE: usize = CLAIMED_BITMAP_DISCRIMINATOR.len() + CLAIMED_BITS_SIZE;
+
+/// Leaves one claimed bitmap covers
+pub const CLAIMS_PER_BITMAP: u32 = CLAIMED_BITS_SIZE as u32 * 8;
+
+/// Longest proof accepted: a u32 index reaches 32 levels down
+pub const MAX_CLAIM_PROOF_LEN: usize = 32;
+
+/// The leaf paying `amount` lamports to `claimant` at `index`
+pub fn leaf_hash(index: u32, claimant: &[u8; 32], amount: u64) -> [u8; 32] {
+    hashv(&[&[0], &index.to_le_bytes(), claimant, &amount.to_le_bytes()]).to_bytes()
+}
+
+/// Whether the leaf paying `amount` to `claimant` at `index` is under `root`, by the
+/// siblings from the leaf up
+pub fn verify(
+    root: &[u8; 32],
+    index: u32,
+    claimant: &[u8; 32],
+    amount: u64,
+    proof: &[[u8; 32]],
+) -> bool {
+    if *root == [0; 32] || proof.len() > MAX_CLAIM_PROOF_LEN || (index as u64) >> proof.len() != 0 {
+        return false;
+    }
+    let mut hash = leaf_hash(index, claimant, amount);
+    let mut index = index;
+    for sibling in proof {
+        hash = if index & 1 == 0 {
+            node_hash(&hash, sibling)
+        } else {
+            node_hash(sibling, &hash)
+        };
+        index >>= 1;
+    }
+    hash == *root
+}
+
+/// Every level of the tree over `leaves` (claimant, amount), from the leaves up to
+/// the root
+fn levels(leaves: &[([u8; 32], u64)]) -> Vec<Vec<[u8; 32]>> {
+    let mut level: Vec<_> = leaves
+        .iter()
+        .enumerate()
+        .map(|(index, (claimant, amount))| leaf_hash(index as u32, claimant, *amount))
+        .collect();
+    level.resize(leaves.len().next_power_of_two(), [0; 32]);
+    let mut levels = vec![level];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| node_hash(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// The root of the list `leaves` of (claimant, amount), leaf i at index i
+pub fn claim_root(leaves: &[([u8; 32], u64)]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    levels(leaves).pop().unwrap_or_default()[0]
+}
+
+/// The proof of leaf `index` of the list `leaves`, None past its end
+pub fn claim_proof(leaves: &[([u8; 32], u64)], index: u32) -> Option<Vec<[u8; 32]>> {
+    if index as usize >= leaves.len() {
+        return None;
+    }
+    let levels = levels(leaves);
+    let depth = levels.len() - 1;
+    Some(
+        levels[..depth]
+            .iter()
+            .enumerate()
+            .map(|(level, hashes)| hashes[(index as usize >> level) ^ 1])
+            .collect(),
+    )
+}
+
+/// Number of the claimed bitmap holding leaf `index`
+pub fn bitmap_number(index: u32) -> u32 {
+    index / CLAIMS_PER_BITMAP
+}
+
+/// Whether `data` is a claimed bitmap's
+pub fn is_bitmap(data: &[u8]) -> bool {
+    data.len() == CLAIMED_BITMAP_SIZE && data.starts_with(&CLAIMED_BITMAP_DISCRIMINATOR)
+}
+
+/// Write the discriminator into a new claimed bitmap's data
+pub fn init_bitmap(data: &mut [u8]) {
+    data[..CLAIMED_BITMAP_DISCRIMINATOR.len()].copy_from_slice(&CLAIMED_BITMAP_DISCRIMINATOR);
+}
+
+/// Byte and bit of leaf `index` in a claimed bitmap's data
+fn bit_of(index: u32) -> (usize, u8) {
+    let bit = (index % CLAIMS_PER_BITMAP) as usize;
+    (CLAIMED_BITMAP_DISCRIMINATOR.len() + bit / 8, 1 << (bit % 8))
+}
+
+/// Whether the claimed bitmap `data` marks leaf `index` claimed
+pub fn is_claimed(data: &[u8], index: u32) -> bool {
+    let (byte, mask) = bit_of(index);
+    matches!(data.get(byte), Some(byte) if byte & mask != 0)
+}
+
+/// Mark leaf `index` claimed in the claimed bitmap `data`
+pub fn set_claimed(data: &mut [u8], index: u32) {
+    let (byte, mask) = bit_of(index);
+    if let Some(byte) = data.get_mut(byte) {
+        *byte |= mask;
+    }
+}
+
+/// Address and bump seed of the claim vault of the program at `program_id`
+pub fn claim_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_VAULT_SEED], program_id)
+}
+
+/// Address and bump seed of the claimed bitmap of `root` holding leaf `index`
+pub fn claimed_address(program_id: &Pubkey, root: &[u8; 32], index: u32) -> (Pubkey, u8) {
+    let number = bitmap_number(index).to_le_bytes();
+    Pubkey::find_program_address(&[CLAIMED_SEED, root, &number], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_every_leaf_proves_against_the_root() {
+        let leaves: Vec<_> = (0..5u8).map(|i| ([i + 1; 32], i as u64 * 100)).collect();
+        let root = claim_root(&leaves);
+        for (index, (claimant, amount)) in leaves.iter().enumerate() {
+            let index = index as u32;
+            let proof = claim_proof(&leaves, index).unwrap();
+            assert_eq!(proof.len(), 3);
+            assert!(verify(&root, index, claimant, *amount, &proof));
+            assert!(!verify(&root, index, claimant, amount + 1, &proof));
+            assert!(!verify(&root, index ^ 1, claimant, *amount, &proof));
+            assert!(!verify(&root, index, &[9; 32], *amount, &proof));
+            assert!(!verify(&root, index + 8, claimant, *amount, &proof));
+        }
+        assert_eq!(claim_proof(&leaves, 5), None);
+
+        // A list of one is its own root, and nothing proves against no root
+        let one = [([1; 32], 10)];
+        assert_eq!(claim_root(&one), leaf_hash(0, &[1; 32], 10));
+        assert!(verify(&claim_root(&one), 0, &[1; 32], 10, &[]));
+        assert_eq!(claim_root(&[]), [0; 32]);
+        assert!(!verify(&[0; 32], 0, &[1; 32], 10, &[]));
+    }
+
+    #[test]
+    fn test_claimed_bitmap() {
+        let mut data = [0; CLAIMED_BITMAP_SIZE];
+        assert!(!is_bitmap(&data));
+        init_bitmap(&mut data);
+        assert!(is_bitmap(&data));
+        assert!(!is_bitmap(&data[..CLAIMED_BITMAP_SIZE - 1]));
+        let index = CLAIMS_PER_BITMAP + 13;
+        assert_eq!(bitmap_number(index), 1);
+        assert!(!is_claimed(&data, index));
+        set_claimed(&mut data, index);
+        assert!(is_claimed(&data, index));
+        assert!(!is_claimed(&data, index + 1));
+        assert_eq!(data[9], 1 << 5);
+        assert_eq!(data[..8], CLAIMED_BITMAP_DISCRIMINATOR);
+        set_claimed(&mut data, CLAIMS_PER_BITMAP - 1);
+        assert_eq!(data[CLAIMED_BITMAP_SIZE - 1], 1 << 7);
+
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            claimed_address(&program_id, &[1; 32], 0),
+            claimed_address(&program_id, &[1; 32], CLAIMS_PER_BITMAP - 1)
+        );
+        assert_ne!(
+            claimed_address(&program_id, &[1; 32], 0),
+            claimed_address(&program_id, &[2; 32], 0)
+        );
+    }
+
+    #[test]
+    fn test_claimed_bitmap_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:ClaimedBitmap");
+        assert_eq!(hash.to_bytes()[..8], CLAIMED_BITMAP_DISCRIMINATOR);
+        // Not a schema 1 message length, whose top byte is zero
+        assert_ne!(CLAIMED_BITMAP_DISCRIMINATOR[3], 0);
+    }
+}
@@ -0,0 +1,331 @@
+//! Compact encoding of the greeting account state
+// NOTE The Borsh layout spends 4 bytes on the message length and 8 on the counter
+// whatever their values. The compact encoding stores both as varints (LEB128, 7 bits
+// per byte) and leaves out fields holding their default value, recording which ones
+// are present in a bitpacked flags byte. A 12 character message greeted fewer than
+// 128 times takes 19 bytes instead of 28 (schema 2, before the calendar), and with
+// it the rent-exempt minimum drops.
+// The price is a few more compute units to decode, and an account whose encoding
+// grows as the counter does (one more byte at 128, 16384, ...).
+//
+// The encoding is selected by the discriminator the data opens with: COMPACT_MAGIC
+// instead of migrations::STATE_MAGIC, followed by the schema version byte. The program
+// keeps an account in the encoding it finds it in, and picks the compact one for a
+// new account only when the Borsh layout doesn't fit.
+//
+// Since the flags say which fields are stored, new fields only need a new flag, and
+// older compact data reads as the current state with those fields at their default.
+use crate::{
+    calendar::{DayBitmap, DAY_BITMAP_SIZE},
+    migrations, GreetingAccount, STATE_VERSION,
+};
+use std::io;
+
+/// First bytes of the data of every compact-encoded account
+pub const COMPACT_MAGIC: [u8; 3] = *b"HWC";
+
+/// Flag set when the counter is stored (it's left out while it's 0)
+pub const FLAG_COUNTER: u8 = 0b0000_0001;
+
+/// Flag set when the calendar is stored: the year as a varint, then the day bitmap
+/// (it's left out until the first greeting recording a day)
+pub const FLAG_CALENDAR: u8 = 0b0000_0010;
+
+/// Flag set when last_update_epoch is stored, as a varint (it's left out while the
+/// account isn't stamped with an epoch)
+pub const FLAG_LAST_UPDATE: u8 = 0b0000_0100;
+
+/// Flag set when the greetings are counted by origin: direct_greets then cpi_greets,
+/// as varints (left out while both are 0)
+pub const FLAG_ORIGIN: u8 = 0b0000_1000;
+
+/// Every flag this version knows about, any other bit set means the data is invalid
+const KNOWN_FLAGS: u8 = FLAG_COUNTER | FLAG_CALENDAR | FLAG_LAST_UPDATE | FLAG_ORIGIN;
+
+/// How a greeting account's state is laid out
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// Fixed-width Borsh, see `migrations`
+    Borsh,
+    /// Varints and bitpacked flags
+    Compact,
+}
+
+/// Encoding of the state in `data`, told apart by its discriminator
+pub fn encoding(data: &[u8]) -> Encoding {
+    if data.len() >= migrations::HEADER_SIZE && data[..COMPACT_MAGIC.len()] == COMPACT_MAGIC {
+        Encoding::Compact
+    } else {
+        Encoding::Borsh
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Append `value` to `out` as an LEB128 varint
+pub fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read an LEB128 varint from the front of `input`, advancing it
+pub fn read_varint(input: &mut &[u8]) -> io::Result<u64> {
+    let mut value = 0u64;
+    for (i, byte) in input.iter().enumerate() {
+        let bits = u64::from(byte & 0x7f);
+        // A u64 has room for 9 full groups of 7 bits and 1 more bit
+        if i == 9 && bits > 1 {
+            return Err(invalid("varint overflows a u64"));
+        }
+        value |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            *input = &input[i + 1..];
+            return Ok(value);
+        }
+        if i == 9 {
+            return Err(invalid("varint overflows a u64"));
+        }
+    }
+    Err(invalid("unexpected end of varint"))
+}
+
+/// Account data holding `account` in the compact encoding
+pub fn encode(account: &GreetingAccount) -> Vec<u8> {
+    let mut data = Vec::with_capacity(migrations::HEADER_SIZE + 2 + account.txt.len() + 1);
+    data.extend_from_slice(&COMPACT_MAGIC);
+    data.push(STATE_VERSION);
+    let mut flags = 0;
+    if account.counter != 0 {
+        flags |= FLAG_COUNTER;
+    }
+    if account.calendar_year != 0 {
+        flags |= FLAG_CALENDAR;
+    }
+    if account.last_update_epoch != 0 {
+        flags |= FLAG_LAST_UPDATE;
+    }
+    if account.direct_greets != 0 || account.cpi_greets != 0 {
+        flags |= FLAG_ORIGIN;
+    }
+    data.push(flags);
+    write_varint(account.txt.len() as u64, &mut data);
+    data.extend_from_slice(account.txt.as_bytes());
+    if flags & FLAG_COUNTER != 0 {
+        write_varint(account.counter, &mut data);
+    }
+    if flags & FLAG_CALENDAR != 0 {
+        write_varint(u64::from(account.calendar_year), &mut data);
+        data.extend_from_slice(&account.greeted_days.0);
+    }
+    if flags & FLAG_LAST_UPDATE != 0 {
+        write_varint(account.last_update_epoch, &mut data);
+    }
+    if flags & FLAG_ORIGIN != 0 {
+        write_varint(account.direct_greets, &mut data);
+        write_varint(account.cpi_greets, &mut data);
+    }
+    data
+}
+
+/// Decode compact-encoded account data. Only the front of the data is read, like
+/// the program leaves it.
+pub fn decode(data: &[u8]) -> io::Result<GreetingAccount> {
+    if encoding(data) != Encoding::Compact {
+        return Err(invalid("not a compact greeting account"));
+    }
+    // NOTE The compact encoding arrived with schema 2
+    let version = data[COMPACT_MAGIC.len()];
+    if !(2..=STATE_VERSION).contains(&version) {
+        return Err(invalid("unsupported compact schema version"));
+    }
+    let mut input = &data[migrations::HEADER_SIZE..];
+    let (&flags, rest) = input
+        .split_first()
+        .ok_or_else(|| invalid("missing compact flags"))?;
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(invalid("unknown compact flags"));
+    }
+    input = rest;
+    let len = read_varint(&mut input)? as usize;
+    if len > input.len() {
+        return Err(invalid("message runs past the end of the account"));
+    }
+    let txt =
+        String::from_utf8(input[..len].to_vec()).map_err(|_| invalid("message isn't UTF-8"))?;
+    input = &input[len..];
+    let counter = if flags & FLAG_COUNTER != 0 {
+        read_varint(&mut input)?
+    } else {
+        0
+    };
+    let mut account = GreetingAccount {
+        txt,
+        counter,
+        calendar_year: 0,
+        greeted_days: DayBitmap::default(),
+        last_update_epoch: 0,
+        direct_greets: 0,
+        cpi_greets: 0,
+    };
+    if flags & FLAG_CALENDAR != 0 {
+        let year = read_varint(&mut input)?;
+        if year > u64::from(u16::MAX) || input.len() < DAY_BITMAP_SIZE {
+            return Err(invalid("invalid compact calendar"));
+        }
+        account.calendar_year = year as u16;
+        account
+            .greeted_days
+            .0
+            .copy_from_slice(&input[..DAY_BITMAP_SIZE]);
+        input = &input[DAY_BITMAP_SIZE..];
+    }
+    if flags & FLAG_LAST_UPDATE != 0 {
+        account.last_update_epoch = read_varint(&mut input)?;
+    }
+    if flags & FLAG_ORIGIN != 0 {
+        account.direct_greets = read_varint(&mut input)?;
+        account.cpi_greets = read_varint(&mut input)?;
+    }
+    Ok(account)
+}
+
+/// Convert Borsh account data, of any schema version, to the compact encoding
+pub fn to_compact(data: &[u8]) -> io::Result<Vec<u8>> {
+    GreetingAccount::unpack(data).map(|account| encode(&account))
+}
+
+/// Convert compact account data to the Borsh layout at STATE_VERSION
+pub fn to_borsh(data: &[u8]) -> io::Result<Vec<u8>> {
+    use borsh::BorshSerialize;
+    let account = decode(data)?;
+    Ok(migrations::encode(STATE_VERSION, &account.try_to_vec()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    fn greeting(txt: &str, counter: u64) -> GreetingAccount {
+        GreetingAccount {
+            txt: txt.to_string(),
+            counter,
+            calendar_year: 0,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: 0,
+            direct_greets: 0,
+            cpi_greets: 0,
+        }
+    }
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in &[
+            0,
+            1,
+            127,
+            128,
+            300,
+            16_383,
+            16_384,
+            u64::from(u32::MAX),
+            u64::MAX,
+        ] {
+            let mut out = vec![];
+            write_varint(*value, &mut out);
+            let mut input = &out[..];
+            assert_eq!(read_varint(&mut input).unwrap(), *value);
+            assert!(input.is_empty());
+        }
+        let mut out = vec![];
+        write_varint(300, &mut out);
+        assert_eq!(out, vec![0xac, 0x02]);
+        write_varint(u64::MAX, &mut out);
+        assert_eq!(out.len(), 2 + 10);
+
+        assert!(read_varint(&mut &[0x80][..]).is_err());
+        assert!(read_varint(&mut &[0xff; 10][..]).is_err());
+        assert!(read_varint(
+            &mut &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02][..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_compact_is_smaller() {
+        let account = greeting("Hello1234567", 1);
+        let borsh = migrations::encode(STATE_VERSION, &account.try_to_vec().unwrap());
+        let compact = encode(&account);
+        assert_eq!(borsh.len(), 104);
+        assert_eq!(compact.len(), 19);
+        // The counter is left out while it's 0, and grows a byte every 7 bits
+        assert_eq!(encode(&greeting("Hello1234567", 0)).len(), 18);
+        assert_eq!(encode(&greeting("Hello1234567", 128)).len(), 20);
+        assert_eq!(encode(&greeting("Hello1234567", u64::MAX)).len(), 28);
+        // So is the calendar until a day is recorded
+        let mut dated = greeting("Hello1234567", 1);
+        dated.calendar_year = 2024;
+        dated.greeted_days.set(59);
+        assert_eq!(encode(&dated).len(), 19 + 2 + 46);
+        assert_eq!(decode(&encode(&dated)).unwrap(), dated);
+        // And the epoch stamp until the account is greeted with the config account
+        dated.last_update_epoch = 500;
+        assert_eq!(encode(&dated).len(), 19 + 2 + 46 + 2);
+        assert_eq!(decode(&encode(&dated)).unwrap(), dated);
+        // And the origin counters until a greeting is counted as direct or CPI
+        dated.direct_greets = 1;
+        assert_eq!(encode(&dated).len(), 19 + 2 + 46 + 2 + 2);
+        assert_eq!(decode(&encode(&dated)).unwrap(), dated);
+    }
+
+    #[test]
+    fn test_converters_round_trip() {
+        let account = greeting("Hello1234567", 300);
+        let borsh = migrations::encode(STATE_VERSION, &account.try_to_vec().unwrap());
+        let mut compact = to_compact(&borsh).unwrap();
+        assert_eq!(encoding(&compact), Encoding::Compact);
+        assert_eq!(encoding(&borsh), Encoding::Borsh);
+        assert_eq!(to_borsh(&compact).unwrap(), borsh);
+
+        // Accounts are allocated at a fixed size, the zeroed tail is ignored
+        compact.resize(64, 0);
+        assert_eq!(decode(&compact).unwrap(), account);
+        assert_eq!(GreetingAccount::unpack(&compact).unwrap(), account);
+
+        // Schema 1 accounts convert too, starting the counter at 0
+        let v1 = crate::GreetingAccountV1 {
+            txt: "Hello1234567".to_string(),
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(
+            decode(&to_compact(&v1).unwrap()).unwrap(),
+            greeting("Hello1234567", 0)
+        );
+        assert!(to_borsh(&v1).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_data() {
+        let data = encode(&greeting("Hello", 1));
+        // Truncated message
+        assert!(decode(&data[..8]).is_err());
+        // Unknown flag
+        let mut flagged = data.clone();
+        flagged[migrations::HEADER_SIZE] |= 0b1000_0000;
+        assert!(decode(&flagged).is_err());
+        // Calendar flag without the calendar
+        let mut calendar = data.clone();
+        calendar[migrations::HEADER_SIZE] |= FLAG_CALENDAR;
+        assert!(decode(&calendar).is_err());
+        // Schema version this program doesn't know
+        let mut newer = data;
+        newer[COMPACT_MAGIC.len()] = STATE_VERSION + 1;
+        assert!(decode(&newer).is_err());
+    }
+}
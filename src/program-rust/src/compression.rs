@@ -0,0 +1,193 @@
+//! The greeting log: every greeting appended to an spl-account-compression merkle tree
+// NOTE spl-account-compression needs a newer solana-program than the one pinned here,
+// so, like governance.rs, this encodes the two instructions it takes by hand. They're
+// Anchor instructions: the first 8 bytes of sha256("global:<name>") followed by the
+// Borsh fields.
+//
+// The log is a concurrent merkle tree account owned by the compression program, with
+// the log authority, a PDA of this program at [LOG_AUTHORITY_SEED], as its authority,
+// so only this program can append to it. The client allocates the tree (its size
+// depends on the depth and buffer picked, see merkle_tree_account_size()) and
+// InitGreetingLog initializes it and records it in the config. From then on every Greet
+// appends the hash of a GreetingLeaf, after logging the leaf itself through the noop
+// program, where indexers pick it up from the transaction's inner instructions. Only the
+// 32-byte leaf hashes are stored on-chain; whoever holds a leaf can prove it against the
+// tree's root.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of the log authority's address
+pub const LOG_AUTHORITY_SEED: &[u8] = b"greeting-log";
+
+/// Anchor discriminator of InitEmptyMerkleTree
+const INIT_EMPTY_MERKLE_TREE: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+
+/// Anchor discriminator of Append
+const APPEND: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+
+/// Size of the tree account's header (ConcurrentMerkleTreeHeader, V1)
+const HEADER_SIZE: usize = 56;
+
+/// The spl-account-compression program, at the same address on every cluster
+#[cfg(feature = "program")]
+pub mod account_compression {
+    solana_program::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+
+/// The spl-noop program, the compression program and Greet log through
+#[cfg(feature = "program")]
+pub mod noop {
+    solana_program::declare_id!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNkWnvV");
+}
+
+/// What's logged for a greeting
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct GreetingLeaf {
+    /// key of the greeting account greeted
+    pub greeting_account: [u8; 32],
+    /// its counter after the greeting
+    pub counter: u64,
+    /// slot of the greeting
+    pub slot: u64,
+    /// the greeting's message
+    pub txt: String,
+}
+
+impl GreetingLeaf {
+    /// The leaf appended to the tree, the sha256 hash of the Borsh-encoded leaf
+    #[cfg(feature = "program")]
+    pub fn hash(&self) -> [u8; 32] {
+        solana_program::hash::hashv(&[&self.to_bytes()]).to_bytes()
+    }
+
+    /// The Borsh-encoded leaf
+    // NOTE Encoding into a Vec can't fail, and unwrap() would panic through the Debug
+    // formatting of the io::Error
+    #[cfg(feature = "program")]
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let _ = self.serialize(&mut bytes);
+        bytes
+    }
+}
+
+/// Size of the tree account of a tree `max_depth` deep keeping `max_buffer_size`
+/// changes, without a canopy
+pub fn merkle_tree_account_size(max_depth: u32, max_buffer_size: u32) -> usize {
+    let depth = max_depth as usize;
+    // Sequence number, active index and buffer size
+    let tree = 8 + 8 + 8
+        // The change logs: a root, a path, the index and padding each
+        + max_buffer_size as usize * (32 + 32 * depth + 4 + 4)
+        // The rightmost proof, leaf, index and padding
+        + 32 * depth + 32 + 4 + 4;
+    HEADER_SIZE + tree
+}
+
+/// Instruction data of InitEmptyMerkleTree
+pub fn init_empty_merkle_tree_data(max_depth: u32, max_buffer_size: u32) -> Vec<u8> {
+    let mut data = INIT_EMPTY_MERKLE_TREE.to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+    data
+}
+
+/// Instruction data of Append
+pub fn append_data(leaf: &[u8; 32]) -> Vec<u8> {
+    let mut data = APPEND.to_vec();
+    data.extend_from_slice(leaf);
+    data
+}
+
+/// Address and bump seed of the log authority of the program at `program_id`
+#[cfg(feature = "program")]
+pub fn log_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOG_AUTHORITY_SEED], program_id)
+}
+
+/// Build an InitEmptyMerkleTree of `tree`, with `authority` as its authority
+#[cfg(feature = "program")]
+pub fn init_empty_merkle_tree(
+    tree: &Pubkey,
+    authority: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    Instruction::new_with_bytes(
+        account_compression::id(),
+        &init_empty_merkle_tree_data(max_depth, max_buffer_size),
+        vec![
+            AccountMeta::new(*tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(noop::id(), false),
+        ],
+    )
+}
+
+/// Build an Append of `leaf` to `tree`, signed by its authority
+#[cfg(feature = "program")]
+pub fn append(
+    tree: &Pubkey,
+    authority: &Pubkey,
+    leaf: &[u8; 32],
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    Instruction::new_with_bytes(
+        account_compression::id(),
+        &append_data(leaf),
+        vec![
+            AccountMeta::new(*tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(noop::id(), false),
+        ],
+    )
+}
+
+/// Build a noop instruction logging `leaf`
+#[cfg(feature = "program")]
+pub fn log_leaf(leaf: &GreetingLeaf) -> solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction::new_with_bytes(noop::id(), &leaf.to_bytes(), vec![])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instruction_data() {
+        assert_eq!(
+            init_empty_merkle_tree_data(14, 64),
+            vec![191, 11, 119, 7, 180, 107, 220, 110, 14, 0, 0, 0, 64, 0, 0, 0]
+        );
+        let data = append_data(&[7; 32]);
+        assert_eq!(&data[..8], &APPEND);
+        assert_eq!(&data[8..], &[7; 32]);
+    }
+
+    #[test]
+    fn test_merkle_tree_account_size() {
+        // What spl-account-compression's getConcurrentMerkleTreeAccountSize() gives
+        assert_eq!(merkle_tree_account_size(14, 64), 31_800);
+        assert_eq!(merkle_tree_account_size(3, 8), 1_304);
+    }
+
+    #[test]
+    fn test_leaf_hash() {
+        let leaf = GreetingLeaf {
+            greeting_account: [1; 32],
+            counter: 3,
+            slot: 42,
+            txt: "hello".to_string(),
+        };
+        let bytes = leaf.try_to_vec().unwrap();
+        assert_eq!(bytes.len(), 32 + 8 + 8 + 4 + 5);
+        assert_eq!(GreetingLeaf::try_from_slice(&bytes).unwrap(), leaf);
+        let other = GreetingLeaf {
+            counter: 4,
+            ..leaf.clone()
+        };
+        assert_ne!(leaf.hash(), other.hash());
+    }
+}
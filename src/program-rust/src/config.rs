@@ -0,0 +1,787 @@
+//! Program-wide settings, held in one account per deployment
+// NOTE The config account is a PDA of this program at seeds [CONFIG_SEED], so clients
+// and the program find it from the program id alone. InitConfig creates it with the
+// signer as its authority, the only key allowed to change it afterwards. Whoever calls
+// InitConfig first becomes the authority, so run it right after deploying.
+//
+// The authority can name a crank authority, the key a keeper bot (Clockwork, a cron
+// job, ...) signs Tick with to do the maintenance nobody else would pay for. Keys are
+// stored as raw bytes so this module builds without solana-program; the all-zero key
+// (the system program's id, which never signs) means there is none.
+//
+// With a fee set the authority charges for every Greet, an amount of USD paid in
+// lamports at the SOL/USD price of a Pyth feed (see pyth.rs). The authority picks the
+// feed and how fresh and certain its price has to be.
+//
+// With a stake mint set, greeters who staked it count for more (see stake.rs).
+//
+// With a greeting log set, every greeting is appended to it (see compression.rs).
+//
+// With a claim root set, Claim pays out the leaves of the list it's the root of (see
+// claim.rs).
+//
+// With greeting receipts on, every Greet leaves a receipt PDA behind (see receipt.rs).
+//
+// With a balance mint set, a Greet counts for the greeter's balance of it at greet
+// time instead, floored to whole balance units: a greeter holding 2.7 units adds 2 to
+// the counter, one holding less than a unit adds nothing.
+//
+// With epoch bonus slots set, a greeting in the first that many slots of an epoch
+// counts double, whatever it would have counted otherwise (see bonus.rs).
+//
+// The limits on greeting (paused, message length, cooldown, GreetMany's quota) are all
+// off at their zero value. SetConfig changes any of the plain tunables (the claim root,
+// greeting receipts, balance weighting and the epoch bonus included) at once, each
+// field of its ConfigPatch left None keeps its value; the keys with more to them
+// (authority, stake mint, greeting log) keep their own instructions.
+//
+// solana-program 1.6 can't grow an account, so fields are only ever appended, and a
+// config account created before a field reads as holding its zero value. See unpack().
+use crate::{
+    calendar::{self, DayBitmap, DAYS_IN_YEAR},
+    instruction::MAX_GREET_MANY_ACCOUNTS,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+use std::io;
+
+/// Seed of the config account's address
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Settings shared by every greeting account of the deployment
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// key allowed to change the config
+    pub authority: [u8; 32],
+    /// epochs it takes a greeting counter to halve, 0 for counters that don't decay
+    pub decay_half_life_epochs: u64,
+    /// key allowed to Tick, all zeros for none
+    pub crank_authority: [u8; 32],
+    /// fee per Greet in millionths of a dollar, 0 for free greetings
+    pub fee_usd_micros: u64,
+    /// slots the price may be behind the clock
+    pub max_price_age_slots: u64,
+    /// widest confidence interval accepted, in basis points of the price
+    pub max_confidence_bps: u16,
+    /// key of the Pyth SOL/USD price account
+    pub price_feed: [u8; 32],
+    /// key the fees are paid to
+    pub fee_recipient: [u8; 32],
+    /// key of the mint greeters stake, all zeros for no staking
+    pub stake_mint: [u8; 32],
+    /// base units of stake that add 1 to the weight of a greeting
+    pub stake_unit: u64,
+    /// key of the merkle tree greetings are logged to, all zeros for no log
+    pub greeting_log: [u8; 32],
+    /// whether greetings are refused, to stop the program during an incident
+    pub paused: bool,
+    /// longest message a greeting may save in bytes, 0 for as long as the account holds
+    pub max_message_len: u32,
+    /// days a greeting account waits after a greeting to be greeted again, 0 for none
+    pub cooldown_days: u16,
+    /// most greeting accounts one GreetMany takes, 0 for MAX_GREET_MANY_ACCOUNTS
+    pub max_greet_many_accounts: u8,
+    /// root of the merkle tree Claim pays out, all zeros for nothing to claim
+    pub claim_root: [u8; 32],
+    /// whether every Greet creates a receipt, see receipt.rs
+    pub greeting_receipts: bool,
+    /// key of the mint whose balance a Greet counts for, all zeros for none
+    pub balance_mint: [u8; 32],
+    /// base units of balance_mint that count 1, 0 for 1
+    pub balance_unit: u64,
+    /// slots at the start of every epoch whose greetings count double, 0 for none
+    pub epoch_bonus_slots: u64,
+}
+
+/// Size of the config account's data
+pub const CONFIG_SIZE: usize = CONFIG_SIZE_V9 + 8;
+
+/// Size of the config accounts created before the crank authority
+pub const CONFIG_SIZE_V1: usize = 32 + 8;
+
+/// Size of the config accounts created before the fee
+pub const CONFIG_SIZE_V2: usize = CONFIG_SIZE_V1 + 32;
+
+/// Size of the config accounts created before staking
+pub const CONFIG_SIZE_V3: usize = CONFIG_SIZE_V2 + 8 + 8 + 2 + 32 + 32;
+
+/// Size of the config accounts created before the greeting log
+pub const CONFIG_SIZE_V4: usize = CONFIG_SIZE_V3 + 32 + 8;
+
+/// Size of the config accounts created before the limits on greeting
+pub const CONFIG_SIZE_V5: usize = CONFIG_SIZE_V4 + 32;
+
+/// Size of the config accounts created before the claim root
+pub const CONFIG_SIZE_V6: usize = CONFIG_SIZE_V5 + 1 + 4 + 2 + 1;
+
+/// Size of the config accounts created before greeting receipts
+pub const CONFIG_SIZE_V7: usize = CONFIG_SIZE_V6 + 32;
+
+/// Size of the config accounts created before balance weighting
+pub const CONFIG_SIZE_V8: usize = CONFIG_SIZE_V7 + 1;
+
+/// Size of the config accounts created before the epoch bonus
+pub const CONFIG_SIZE_V9: usize = CONFIG_SIZE_V8 + 32 + 8;
+
+/// Longest max_message_len accepted: a message has to fit in an account
+pub const MAX_MESSAGE_LEN_LIMIT: u32 = 10 * 1024;
+
+/// A partial update of the config, see SetConfig. Each field set replaces the
+/// config's, the ones left None are kept.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct ConfigPatch {
+    pub decay_half_life_epochs: Option<u64>,
+    pub crank_authority: Option<[u8; 32]>,
+    pub fee_usd_micros: Option<u64>,
+    pub max_price_age_slots: Option<u64>,
+    pub max_confidence_bps: Option<u16>,
+    pub price_feed: Option<[u8; 32]>,
+    pub fee_recipient: Option<[u8; 32]>,
+    pub stake_unit: Option<u64>,
+    pub paused: Option<bool>,
+    pub max_message_len: Option<u32>,
+    pub cooldown_days: Option<u16>,
+    pub max_greet_many_accounts: Option<u8>,
+    pub claim_root: Option<[u8; 32]>,
+    pub greeting_receipts: Option<bool>,
+    pub balance_mint: Option<[u8; 32]>,
+    pub balance_unit: Option<u64>,
+    pub epoch_bonus_slots: Option<u64>,
+}
+
+impl ConfigPatch {
+    /// Check every field set is in range, returning the name of the first that isn't
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if matches!(self.max_confidence_bps, Some(bps) if bps > 10_000) {
+            return Err("max_confidence_bps");
+        }
+        if matches!(self.max_message_len, Some(len) if len > MAX_MESSAGE_LEN_LIMIT) {
+            return Err("max_message_len");
+        }
+        if matches!(self.cooldown_days, Some(days) if days as usize > DAYS_IN_YEAR) {
+            return Err("cooldown_days");
+        }
+        if matches!(
+            self.max_greet_many_accounts,
+            Some(max) if max as usize > MAX_GREET_MANY_ACCOUNTS
+        ) {
+            return Err("max_greet_many_accounts");
+        }
+        if self.stake_unit == Some(0) {
+            return Err("stake_unit");
+        }
+        if self.balance_unit == Some(0) {
+            return Err("balance_unit");
+        }
+        Ok(())
+    }
+
+    /// validate() the patch, and check `config` patched with it keeps receipts off
+    /// while counters decay (see receipt.rs). Returns the name of the first field that
+    /// doesn't fit.
+    pub fn validate_for(&self, config: &Config) -> Result<(), &'static str> {
+        self.validate()?;
+        let receipts = self.greeting_receipts.unwrap_or(config.greeting_receipts);
+        let half_life = self
+            .decay_half_life_epochs
+            .unwrap_or(config.decay_half_life_epochs);
+        if receipts && half_life > 0 {
+            return Err(if self.greeting_receipts == Some(true) {
+                "greeting_receipts"
+            } else {
+                "decay_half_life_epochs"
+            });
+        }
+        Ok(())
+    }
+
+    /// Write the fields set into `config`
+    pub fn apply(&self, config: &mut Config) {
+        fn set<T: Copy>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        set(
+            &mut config.decay_half_life_epochs,
+            self.decay_half_life_epochs,
+        );
+        set(&mut config.crank_authority, self.crank_authority);
+        set(&mut config.fee_usd_micros, self.fee_usd_micros);
+        set(&mut config.max_price_age_slots, self.max_price_age_slots);
+        set(&mut config.max_confidence_bps, self.max_confidence_bps);
+        set(&mut config.price_feed, self.price_feed);
+        set(&mut config.fee_recipient, self.fee_recipient);
+        set(&mut config.stake_unit, self.stake_unit);
+        set(&mut config.paused, self.paused);
+        set(&mut config.max_message_len, self.max_message_len);
+        set(&mut config.cooldown_days, self.cooldown_days);
+        set(
+            &mut config.max_greet_many_accounts,
+            self.max_greet_many_accounts,
+        );
+        set(&mut config.claim_root, self.claim_root);
+        set(&mut config.greeting_receipts, self.greeting_receipts);
+        set(&mut config.balance_mint, self.balance_mint);
+        set(&mut config.balance_unit, self.balance_unit);
+        set(&mut config.epoch_bonus_slots, self.epoch_bonus_slots);
+    }
+}
+
+impl Config {
+    /// Read the config from a config account's data, of any size it was created with
+    pub fn unpack(data: &[u8]) -> io::Result<Self> {
+        if [
+            CONFIG_SIZE_V1,
+            CONFIG_SIZE_V2,
+            CONFIG_SIZE_V3,
+            CONFIG_SIZE_V4,
+            CONFIG_SIZE_V5,
+            CONFIG_SIZE_V6,
+            CONFIG_SIZE_V7,
+            CONFIG_SIZE_V8,
+            CONFIG_SIZE_V9,
+        ]
+        .contains(&data.len())
+        {
+            let mut padded = data.to_vec();
+            padded.resize(CONFIG_SIZE, 0);
+            return Self::try_from_slice(&padded);
+        }
+        Self::try_from_slice(data)
+    }
+
+    /// Write the config into a config account's data. An account created before a
+    /// field can't hold it, so setting that field fails.
+    pub fn pack(&self, data: &mut [u8]) -> io::Result<()> {
+        let bytes = self.try_to_vec()?;
+        let len = bytes.len().min(data.len());
+        if bytes[len..].iter().any(|byte| *byte != 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "config account is too old to hold this setting",
+            ));
+        }
+        data[..len].copy_from_slice(&bytes[..len]);
+        Ok(())
+    }
+
+    /// Whether `key` is the crank authority
+    pub fn is_crank_authority(&self, key: &[u8; 32]) -> bool {
+        self.crank_authority != [0; 32] && self.crank_authority == *key
+    }
+
+    /// Whether greetings cost a fee
+    pub fn charges_fee(&self) -> bool {
+        self.fee_usd_micros > 0
+    }
+
+    /// Whether greeters can stake
+    pub fn has_staking(&self) -> bool {
+        self.stake_mint != [0; 32]
+    }
+
+    /// Whether a Greet counts for the greeter's balance of the balance mint
+    pub fn weighs_balance(&self) -> bool {
+        self.balance_mint != [0; 32]
+    }
+
+    /// How much a Greet by a greeter holding `amount` of the balance mint counts for
+    pub fn balance_weight(&self, amount: u64) -> u64 {
+        amount / self.balance_unit.max(1)
+    }
+
+    /// Whether greetings are logged
+    pub fn has_greeting_log(&self) -> bool {
+        self.greeting_log != [0; 32]
+    }
+
+    /// Whether a message of `len` bytes is within max_message_len
+    pub fn allows_message_len(&self, len: usize) -> bool {
+        self.max_message_len == 0 || len <= self.max_message_len as usize
+    }
+
+    /// Whether an account with this calendar was greeted too recently to be greeted at
+    /// `unix_timestamp`. Only the calendar's year is known, so the cooldown starts over
+    /// on January 1st.
+    pub fn on_cooldown(&self, year: u16, days: &DayBitmap, unix_timestamp: i64) -> bool {
+        calendar::greeted_within(year, days, unix_timestamp, self.cooldown_days)
+    }
+
+    /// Whether there's a list to claim
+    pub fn has_claims(&self) -> bool {
+        self.claim_root != [0; 32]
+    }
+
+    /// Most greeting accounts one GreetMany takes
+    pub fn greet_many_limit(&self) -> usize {
+        match self.max_greet_many_accounts {
+            0 => MAX_GREET_MANY_ACCOUNTS,
+            max => max as usize,
+        }
+    }
+}
+
+/// Address and bump seed of the config account of the program at `program_id`
+#[cfg(feature = "program")]
+pub fn config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_size() {
+        let config = Config {
+            authority: [7; 32],
+            decay_half_life_epochs: 10,
+            crank_authority: [8; 32],
+            fee_usd_micros: 10_000,
+            max_price_age_slots: 25,
+            max_confidence_bps: 100,
+            price_feed: [9; 32],
+            fee_recipient: [10; 32],
+            stake_mint: [11; 32],
+            stake_unit: 1_000,
+            greeting_log: [12; 32],
+            paused: true,
+            max_message_len: 64,
+            cooldown_days: 2,
+            max_greet_many_accounts: 4,
+            claim_root: [13; 32],
+            greeting_receipts: true,
+            balance_mint: [14; 32],
+            balance_unit: 100,
+            epoch_bonus_slots: 1_000,
+        };
+        let data = config.try_to_vec().unwrap();
+        assert_eq!(data.len(), CONFIG_SIZE);
+        assert!(config.charges_fee());
+        assert!(config.weighs_balance());
+        assert_eq!(config.balance_weight(299), 2);
+        assert!(config.has_staking());
+        assert!(config.has_greeting_log());
+        assert!(config.has_claims());
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(config.is_crank_authority(&[8; 32]));
+        assert!(!config.is_crank_authority(&[7; 32]));
+        assert!(!Config::default().is_crank_authority(&[0; 32]));
+    }
+
+    #[test]
+    fn test_config_created_before_the_crank_authority() {
+        let mut data = vec![0; CONFIG_SIZE_V1];
+        let mut config = Config {
+            authority: [7; 32],
+            decay_half_life_epochs: 10,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        config.crank_authority = [8; 32];
+        assert!(config.pack(&mut data).is_err());
+        assert!(Config::unpack(&data[..CONFIG_SIZE_V1 - 1]).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_the_fee() {
+        let mut data = vec![0; CONFIG_SIZE_V2];
+        let mut config = Config {
+            authority: [7; 32],
+            crank_authority: [8; 32],
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        config.fee_usd_micros = 10_000;
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_staking() {
+        let mut data = vec![0; CONFIG_SIZE_V3];
+        let mut config = Config {
+            authority: [7; 32],
+            fee_usd_micros: 10_000,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.has_staking());
+        config.stake_mint = [11; 32];
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_the_greeting_log() {
+        let mut data = vec![0; CONFIG_SIZE_V4];
+        let mut config = Config {
+            authority: [7; 32],
+            stake_mint: [11; 32],
+            stake_unit: 1_000,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.has_greeting_log());
+        config.greeting_log = [12; 32];
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_the_limits() {
+        let mut data = vec![0; CONFIG_SIZE_V5];
+        let mut config = Config {
+            authority: [7; 32],
+            greeting_log: [12; 32],
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.paused);
+        assert_eq!(config.greet_many_limit(), MAX_GREET_MANY_ACCOUNTS);
+        config.paused = true;
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_the_claim_root() {
+        let mut data = vec![0; CONFIG_SIZE_V6];
+        let mut config = Config {
+            authority: [7; 32],
+            paused: true,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.has_claims());
+        config.claim_root = [13; 32];
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_greeting_receipts() {
+        let mut data = vec![0; CONFIG_SIZE_V7];
+        let mut config = Config {
+            authority: [7; 32],
+            claim_root: [13; 32],
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.greeting_receipts);
+        config.greeting_receipts = true;
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_balance_weighting() {
+        let mut data = vec![0; CONFIG_SIZE_V8];
+        let mut config = Config {
+            authority: [7; 32],
+            greeting_receipts: true,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert!(!config.weighs_balance());
+        // Without a unit every base unit counts
+        assert_eq!(config.balance_weight(5), 5);
+        config.balance_mint = [14; 32];
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_created_before_epoch_bonus() {
+        let mut data = vec![0; CONFIG_SIZE_V9];
+        let mut config = Config {
+            authority: [7; 32],
+            balance_mint: [14; 32],
+            balance_unit: 100,
+            ..Config::default()
+        };
+        config.pack(&mut data).unwrap();
+        assert_eq!(Config::unpack(&data).unwrap(), config);
+        assert_eq!(config.epoch_bonus_slots, 0);
+        config.epoch_bonus_slots = 1_000;
+        assert!(config.pack(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_config_patch() {
+        let mut config = Config {
+            authority: [7; 32],
+            fee_usd_micros: 10_000,
+            ..Config::default()
+        };
+        let patch = ConfigPatch {
+            paused: Some(true),
+            max_message_len: Some(32),
+            ..ConfigPatch::default()
+        };
+        assert_eq!(patch.validate(), Ok(()));
+        patch.apply(&mut config);
+        assert!(config.paused);
+        assert_eq!(config.fee_usd_micros, 10_000);
+        assert!(config.allows_message_len(32));
+        assert!(!config.allows_message_len(33));
+
+        let invalid = |patch: ConfigPatch| patch.validate().unwrap_err();
+        assert_eq!(
+            invalid(ConfigPatch {
+                max_confidence_bps: Some(10_001),
+                ..ConfigPatch::default()
+            }),
+            "max_confidence_bps"
+        );
+        assert_eq!(
+            invalid(ConfigPatch {
+                max_greet_many_accounts: Some(MAX_GREET_MANY_ACCOUNTS as u8 + 1),
+                ..ConfigPatch::default()
+            }),
+            "max_greet_many_accounts"
+        );
+        assert_eq!(
+            invalid(ConfigPatch {
+                cooldown_days: Some(DAYS_IN_YEAR as u16 + 1),
+                ..ConfigPatch::default()
+            }),
+            "cooldown_days"
+        );
+    }
+
+    #[test]
+    fn test_config_patch_keeps_receipts_off_with_decay() {
+        let decaying = Config {
+            decay_half_life_epochs: 10,
+            ..Config::default()
+        };
+        let receipts = ConfigPatch {
+            greeting_receipts: Some(true),
+            ..ConfigPatch::default()
+        };
+        assert_eq!(receipts.validate(), Ok(()));
+        assert_eq!(receipts.validate_for(&decaying), Err("greeting_receipts"));
+        assert_eq!(receipts.validate_for(&Config::default()), Ok(()));
+        // Turning decay off in the same patch
+        let receipts_without_decay = ConfigPatch {
+            decay_half_life_epochs: Some(0),
+            ..receipts.clone()
+        };
+        assert_eq!(receipts_without_decay.validate_for(&decaying), Ok(()));
+
+        let with_receipts = Config {
+            greeting_receipts: true,
+            ..Config::default()
+        };
+        let decay = ConfigPatch {
+            decay_half_life_epochs: Some(10),
+            ..ConfigPatch::default()
+        };
+        assert_eq!(
+            decay.validate_for(&with_receipts),
+            Err("decay_half_life_epochs")
+        );
+        // Out of range fields are still named first
+        let out_of_range = ConfigPatch {
+            max_confidence_bps: Some(10_001),
+            ..receipts
+        };
+        assert_eq!(
+            out_of_range.validate_for(&decaying),
+            Err("max_confidence_bps")
+        );
+    }
+
+    #[test]
+    fn test_patch_of_one_field_leaves_the_rest_byte_for_byte() {
+        let config = Config {
+            authority: [1; 32],
+            decay_half_life_epochs: 2,
+            crank_authority: [3; 32],
+            fee_usd_micros: 4,
+            max_price_age_slots: 5,
+            max_confidence_bps: 6,
+            price_feed: [7; 32],
+            fee_recipient: [8; 32],
+            stake_mint: [9; 32],
+            stake_unit: 10,
+            greeting_log: [11; 32],
+            paused: false,
+            max_message_len: 12,
+            cooldown_days: 13,
+            max_greet_many_accounts: 14,
+            claim_root: [15; 32],
+            greeting_receipts: false,
+            balance_mint: [16; 32],
+            balance_unit: 17,
+            epoch_bonus_slots: 18,
+        };
+        let before = config.try_to_vec().unwrap();
+        let none = ConfigPatch::default;
+        // Each patch changes one field, and where that field's bytes are
+        let patches = [
+            (
+                ConfigPatch {
+                    decay_half_life_epochs: Some(20),
+                    ..none()
+                },
+                32..40,
+            ),
+            (
+                ConfigPatch {
+                    crank_authority: Some([30; 32]),
+                    ..none()
+                },
+                40..72,
+            ),
+            (
+                ConfigPatch {
+                    fee_usd_micros: Some(40),
+                    ..none()
+                },
+                72..80,
+            ),
+            (
+                ConfigPatch {
+                    max_price_age_slots: Some(50),
+                    ..none()
+                },
+                80..88,
+            ),
+            (
+                ConfigPatch {
+                    max_confidence_bps: Some(60),
+                    ..none()
+                },
+                88..90,
+            ),
+            (
+                ConfigPatch {
+                    price_feed: Some([70; 32]),
+                    ..none()
+                },
+                90..122,
+            ),
+            (
+                ConfigPatch {
+                    fee_recipient: Some([80; 32]),
+                    ..none()
+                },
+                122..154,
+            ),
+            (
+                ConfigPatch {
+                    stake_unit: Some(100),
+                    ..none()
+                },
+                186..194,
+            ),
+            (
+                ConfigPatch {
+                    paused: Some(true),
+                    ..none()
+                },
+                226..227,
+            ),
+            (
+                ConfigPatch {
+                    max_message_len: Some(120),
+                    ..none()
+                },
+                227..231,
+            ),
+            (
+                ConfigPatch {
+                    cooldown_days: Some(130),
+                    ..none()
+                },
+                231..233,
+            ),
+            (
+                ConfigPatch {
+                    max_greet_many_accounts: Some(24),
+                    ..none()
+                },
+                233..234,
+            ),
+            (
+                ConfigPatch {
+                    claim_root: Some([150; 32]),
+                    ..none()
+                },
+                234..266,
+            ),
+            (
+                ConfigPatch {
+                    greeting_receipts: Some(true),
+                    ..none()
+                },
+                266..267,
+            ),
+            (
+                ConfigPatch {
+                    balance_mint: Some([160; 32]),
+                    ..none()
+                },
+                267..299,
+            ),
+            (
+                ConfigPatch {
+                    balance_unit: Some(170),
+                    ..none()
+                },
+                299..307,
+            ),
+            (
+                ConfigPatch {
+                    epoch_bonus_slots: Some(180),
+                    ..none()
+                },
+                307..CONFIG_SIZE,
+            ),
+        ];
+        for (patch, field) in patches.iter() {
+            assert_eq!(patch.validate(), Ok(()));
+            let mut data = before.clone();
+            let mut patched = Config::unpack(&data).unwrap();
+            patch.apply(&mut patched);
+            patched.pack(&mut data).unwrap();
+            assert_ne!(data[field.clone()], before[field.clone()], "{:?}", patch);
+            for (i, (new, old)) in data.iter().zip(&before).enumerate() {
+                assert!(
+                    field.contains(&i) || new == old,
+                    "{:?} changed byte {}",
+                    patch,
+                    i
+                );
+            }
+        }
+
+        // And an empty patch changes nothing
+        let mut patched = config.clone();
+        none().apply(&mut patched);
+        assert_eq!(patched.try_to_vec().unwrap(), before);
+    }
+
+    #[test]
+    fn test_cooldown() {
+        // 2024-03-01T12:00:00Z, day 60 of 2024
+        const NOW: i64 = 1_709_294_400;
+        const DAY: i64 = 86_400;
+        let mut days = DayBitmap::default();
+        days.set(59);
+        let config = Config {
+            cooldown_days: 2,
+            ..Config::default()
+        };
+        assert!(config.on_cooldown(2024, &days, NOW));
+        assert!(!config.on_cooldown(2024, &days, NOW + DAY));
+        // A calendar of another year doesn't count
+        assert!(!config.on_cooldown(2023, &days, NOW));
+        assert!(!Config::default().on_cooldown(2024, &days, NOW - DAY));
+    }
+}
@@ -0,0 +1,370 @@
+//! A merkle root over the counters of greeting accounts, for airdrops and off-chain
+//! proofs of greeting activity
+// NOTE A counter snapshot is an account of this program the client allocates with
+// account_size() bytes (past the 10KiB a program can allocate itself) and the config
+// authority initializes with InitCounterSnapshot, so only the authority's snapshots
+// exist. Then the crank authority keeps it up to date with SnapshotCounters, passing
+// greeting accounts and the leaf each goes to: the one already holding the account, or
+// the next free one. Leaves are taken left to right and an account keeps its leaf.
+//
+// The data is the CounterSnapshot header, then the (key, counter) of every leaf, then
+// the hashes of every level of the tree below the root, from the leaves up. With every
+// level stored, changing a leaf rehashes only its path to the root: `depth` hashes, not
+// the whole tree. Nodes whose leaves are all still free aren't written and read as the
+// hash of an empty subtree instead, so a new snapshot needs no initializing.
+//
+// A leaf is sha256(0 || key || counter as u64 LE) and a node sha256(1 || left || right),
+// the prefix keeping a leaf from passing for a node. The counter is the effective one,
+// with the decay up to the epoch of the snapshot folded in (see decay.rs).
+//
+// Looking an account up by key would cost a pass over every leaf, so the program trusts
+// the index it's given as far as it can check it: a taken leaf must hold the account.
+// Passing an account already in the tree at the next free leaf would put it in twice,
+// which only the crank authority can do; the client looks the index up first.
+use crate::error::HelloWorldError;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::hash::hashv;
+
+/// Anchor discriminator of CounterSnapshot: sha256("account:CounterSnapshot")[..8]
+pub const COUNTER_SNAPSHOT_DISCRIMINATOR: [u8; 8] = [244, 186, 213, 70, 106, 244, 18, 229];
+
+/// Deepest tree a snapshot can hold, 65536 greeting accounts in under 7MB
+pub const MAX_SNAPSHOT_DEPTH: u8 = 16;
+
+/// Bytes of a leaf's greeting account key and counter
+pub const LEAF_SIZE: usize = 32 + 8;
+
+/// Bytes of the discriminator and the CounterSnapshot header
+pub const HEADER_SIZE: usize = 8 + 1 + 4 + 8 + 32;
+
+/// The header of a counter snapshot account
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct CounterSnapshot {
+    /// depth of the tree, which holds up to 2^depth greeting accounts
+    pub depth: u8,
+    /// leaves taken, leaves 0 to leaf_count - 1
+    pub leaf_count: u32,
+    /// slot of the last SnapshotCounters
+    pub slot: u64,
+    /// root of the tree
+    pub root: [u8; 32],
+}
+
+impl CounterSnapshot {
+    /// Read the header of a counter snapshot account's data, None if it isn't one
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE || data[..8] != COUNTER_SNAPSHOT_DISCRIMINATOR {
+            return None;
+        }
+        let snapshot = Self::try_from_slice(&data[8..HEADER_SIZE]).ok()?;
+        if snapshot.depth == 0
+            || snapshot.depth > MAX_SNAPSHOT_DEPTH
+            || data.len() != account_size(snapshot.depth)
+            || snapshot.leaf_count > snapshot.capacity()
+        {
+            return None;
+        }
+        Some(snapshot)
+    }
+
+    /// Write the header into a counter snapshot account's data
+    pub fn pack(&self, data: &mut [u8]) {
+        data[..8].copy_from_slice(&COUNTER_SNAPSHOT_DISCRIMINATOR);
+        // NOTE Encoding into a slice of the exact size can't fail
+        let _ = self.serialize(&mut &mut data[8..HEADER_SIZE]);
+    }
+
+    /// Leaves the tree has
+    pub fn capacity(&self) -> u32 {
+        1 << self.depth
+    }
+}
+
+/// Size of a counter snapshot account holding a tree `depth` deep
+pub fn account_size(depth: u8) -> usize {
+    let capacity = 1usize << depth;
+    HEADER_SIZE + capacity * LEAF_SIZE + (2 * capacity - 2) * 32
+}
+
+/// The leaf of a greeting account
+pub fn leaf_hash(key: &[u8; 32], counter: u64) -> [u8; 32] {
+    hashv(&[&[0], key, &counter.to_le_bytes()]).to_bytes()
+}
+
+/// The node over two children
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[1], left, right]).to_bytes()
+}
+
+/// The hash of an empty subtree at every level from the leaves (all zeros) to `depth`
+pub fn empty_hashes(depth: u8) -> Vec<[u8; 32]> {
+    let mut empty = vec![[0; 32]];
+    for level in 0..depth as usize {
+        let node = node_hash(&empty[level], &empty[level]);
+        empty.push(node);
+    }
+    empty
+}
+
+/// Start a snapshot of a tree `depth` deep in `data`, which must be account_size()
+/// bytes and all zeros
+pub fn init(data: &mut [u8], depth: u8) -> Result<(), HelloWorldError> {
+    if depth == 0
+        || depth > MAX_SNAPSHOT_DEPTH
+        || data.len() != account_size(depth)
+        || data.iter().any(|byte| *byte != 0)
+    {
+        return Err(HelloWorldError::InvalidSnapshot);
+    }
+    let snapshot = CounterSnapshot {
+        depth,
+        leaf_count: 0,
+        slot: 0,
+        root: empty_hashes(depth)[depth as usize],
+    };
+    snapshot.pack(data);
+    Ok(())
+}
+
+/// The greeting account key and counter at leaf `index`, None for a free leaf
+pub fn leaf(data: &[u8], index: u32) -> Option<([u8; 32], u64)> {
+    let snapshot = CounterSnapshot::unpack(data)?;
+    if index >= snapshot.leaf_count {
+        return None;
+    }
+    let offset = HEADER_SIZE + index as usize * LEAF_SIZE;
+    let mut key = [0; 32];
+    key.copy_from_slice(&data[offset..offset + 32]);
+    let mut counter = [0; 8];
+    counter.copy_from_slice(&data[offset + 32..offset + LEAF_SIZE]);
+    Some((key, u64::from_le_bytes(counter)))
+}
+
+/// Where the node `index` of `level` (0 for the leaves) starts
+fn node_offset(depth: u8, level: usize, index: u32) -> usize {
+    let capacity = 1usize << depth;
+    HEADER_SIZE
+        + capacity * LEAF_SIZE
+        + (2 * capacity - 2 * (capacity >> level) + index as usize) * 32
+}
+
+/// The node `index` of `level`, the empty subtree's hash when its leaves are all free
+fn node(
+    data: &[u8],
+    snapshot: &CounterSnapshot,
+    empty: &[[u8; 32]],
+    level: usize,
+    index: u32,
+) -> [u8; 32] {
+    if (index as u64) << level >= snapshot.leaf_count as u64 {
+        return empty[level];
+    }
+    let offset = node_offset(snapshot.depth, level, index);
+    let mut node = [0; 32];
+    node.copy_from_slice(&data[offset..offset + 32]);
+    node
+}
+
+/// Set leaf `index` to `key` holding `counter` and update the root. The leaf must
+/// hold `key` already or be the next free one. `empty` is empty_hashes() of the
+/// snapshot's depth. Returns whether anything changed.
+pub fn set_counter(
+    data: &mut [u8],
+    empty: &[[u8; 32]],
+    index: u32,
+    key: &[u8; 32],
+    counter: u64,
+) -> Result<bool, HelloWorldError> {
+    let mut snapshot = CounterSnapshot::unpack(data).ok_or(HelloWorldError::InvalidSnapshot)?;
+    match leaf(data, index) {
+        Some((held, held_counter)) if held == *key => {
+            if held_counter == counter {
+                return Ok(false);
+            }
+        }
+        None if index == snapshot.leaf_count && index < snapshot.capacity() => {
+            snapshot.leaf_count += 1;
+        }
+        _ => return Err(HelloWorldError::InvalidSnapshotIndex),
+    }
+    let offset = HEADER_SIZE + index as usize * LEAF_SIZE;
+    data[offset..offset + 32].copy_from_slice(key);
+    data[offset + 32..offset + LEAF_SIZE].copy_from_slice(&counter.to_le_bytes());
+
+    let mut hash = leaf_hash(key, counter);
+    let mut index = index;
+    for level in 0..snapshot.depth as usize {
+        let offset = node_offset(snapshot.depth, level, index);
+        data[offset..offset + 32].copy_from_slice(&hash);
+        let sibling = node(data, &snapshot, empty, level, index ^ 1);
+        hash = if index & 1 == 0 {
+            node_hash(&hash, &sibling)
+        } else {
+            node_hash(&sibling, &hash)
+        };
+        index >>= 1;
+    }
+    snapshot.root = hash;
+    snapshot.pack(data);
+    Ok(true)
+}
+
+/// The siblings on the path from leaf `index` to the root, from the leaf up, None for
+/// a free leaf
+pub fn proof(data: &[u8], index: u32) -> Option<Vec<[u8; 32]>> {
+    let snapshot = CounterSnapshot::unpack(data)?;
+    if index >= snapshot.leaf_count {
+        return None;
+    }
+    let empty = empty_hashes(snapshot.depth);
+    let mut index = index;
+    let mut siblings = vec![];
+    for level in 0..snapshot.depth as usize {
+        siblings.push(node(data, &snapshot, &empty, level, index ^ 1));
+        index >>= 1;
+    }
+    Some(siblings)
+}
+
+/// Whether `key` held `counter` at leaf `index` of the tree with `root`, by the
+/// siblings of proof()
+pub fn verify_proof(
+    root: &[u8; 32],
+    key: &[u8; 32],
+    counter: u64,
+    index: u32,
+    siblings: &[[u8; 32]],
+) -> bool {
+    if siblings.len() > MAX_SNAPSHOT_DEPTH as usize || (index as u64) >> siblings.len() != 0 {
+        return false;
+    }
+    let mut hash = leaf_hash(key, counter);
+    let mut index = index;
+    for sibling in siblings {
+        hash = if index & 1 == 0 {
+            node_hash(&hash, sibling)
+        } else {
+            node_hash(sibling, &hash)
+        };
+        index >>= 1;
+    }
+    hash == *root
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The root of every leaf in `leaves` hashed level by level, the slow way
+    fn full_root(depth: u8, leaves: &[([u8; 32], u64)]) -> [u8; 32] {
+        let mut level: Vec<_> = (0..1usize << depth)
+            .map(|i| {
+                leaves
+                    .get(i)
+                    .map_or([0; 32], |(key, counter)| leaf_hash(key, *counter))
+            })
+            .collect();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| node_hash(&pair[0], &pair[1]))
+                .collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn test_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:CounterSnapshot");
+        assert_eq!(hash.to_bytes()[..8], COUNTER_SNAPSHOT_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_set_counter_keeps_the_root_of_the_whole_tree() {
+        let depth = 3;
+        let mut data = vec![0; account_size(depth)];
+        init(&mut data, depth).unwrap();
+        let empty = empty_hashes(depth);
+        assert_eq!(
+            CounterSnapshot::unpack(&data).unwrap().root,
+            full_root(depth, &[])
+        );
+
+        let mut leaves = vec![];
+        for i in 0..5u8 {
+            let key = [i + 1; 32];
+            assert_eq!(
+                set_counter(&mut data, &empty, i as u32, &key, i as u64),
+                Ok(true)
+            );
+            leaves.push((key, i as u64));
+            let snapshot = CounterSnapshot::unpack(&data).unwrap();
+            assert_eq!(snapshot.leaf_count, leaves.len() as u32);
+            assert_eq!(snapshot.root, full_root(depth, &leaves));
+        }
+        // A counter changes in place; the same counter again changes nothing
+        assert_eq!(set_counter(&mut data, &empty, 1, &[2; 32], 40), Ok(true));
+        leaves[1].1 = 40;
+        assert_eq!(set_counter(&mut data, &empty, 1, &[2; 32], 40), Ok(false));
+        let root = CounterSnapshot::unpack(&data).unwrap().root;
+        assert_eq!(root, full_root(depth, &leaves));
+
+        for (index, (key, counter)) in leaves.iter().enumerate() {
+            let siblings = proof(&data, index as u32).unwrap();
+            assert_eq!(siblings.len(), depth as usize);
+            assert!(verify_proof(&root, key, *counter, index as u32, &siblings));
+            assert!(!verify_proof(
+                &root,
+                key,
+                counter + 1,
+                index as u32,
+                &siblings
+            ));
+            assert!(!verify_proof(
+                &root,
+                key,
+                *counter,
+                index as u32 ^ 1,
+                &siblings
+            ));
+        }
+        assert_eq!(proof(&data, 5), None);
+    }
+
+    #[test]
+    fn test_set_counter_checks_the_index() {
+        let depth = 1;
+        let mut data = vec![0; account_size(depth)];
+        init(&mut data, depth).unwrap();
+        let empty = empty_hashes(depth);
+        // Not the next free leaf
+        assert_eq!(
+            set_counter(&mut data, &empty, 1, &[1; 32], 1),
+            Err(HelloWorldError::InvalidSnapshotIndex)
+        );
+        set_counter(&mut data, &empty, 0, &[1; 32], 1).unwrap();
+        // Taken by another account
+        assert_eq!(
+            set_counter(&mut data, &empty, 0, &[2; 32], 1),
+            Err(HelloWorldError::InvalidSnapshotIndex)
+        );
+        set_counter(&mut data, &empty, 1, &[2; 32], 1).unwrap();
+        // Full
+        assert_eq!(
+            set_counter(&mut data, &empty, 2, &[3; 32], 1),
+            Err(HelloWorldError::InvalidSnapshotIndex)
+        );
+
+        // Only a zeroed account of the right size starts a snapshot
+        assert_eq!(
+            init(&mut data, depth),
+            Err(HelloWorldError::InvalidSnapshot)
+        );
+        let mut short = vec![0; account_size(depth) - 1];
+        assert_eq!(
+            init(&mut short, depth),
+            Err(HelloWorldError::InvalidSnapshot)
+        );
+        assert_eq!(CounterSnapshot::unpack(&short), None);
+    }
+}
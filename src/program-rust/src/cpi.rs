@@ -0,0 +1,54 @@
+//! Typed helpers for other programs calling into hello world through CPI
+// NOTE Pull this crate in with `features = ["no-entrypoint"]` and call these instead of
+// building the instruction and `invoke`-ing it by hand. Anchor programs can use
+// `declare_program!` with the IDL from `cargo run -p helloworld-schema --bin idl -- --anchor`.
+use crate::{instruction, validate_program_account};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, program::invoke};
+
+/// Save `txt` into `greeting_account` through the hello world program. `config` is
+/// the hello world config account, see `config::config_address()`, and
+/// `instructions_sysvar` the instructions sysvar, which has the greeting counted in
+/// the account's `cpi_greets`. A deployment charging a fee also needs the fee
+/// accounts, see `instruction::greet_with_fee()`.
+pub fn greet<'a>(
+    helloworld_program: &AccountInfo<'a>,
+    greeting_account: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    instructions_sysvar: &AccountInfo<'a>,
+    txt: &str,
+) -> ProgramResult {
+    validate_program_account(helloworld_program, &crate::id())?;
+    let ix = instruction::greet(helloworld_program.key, greeting_account.key, txt);
+    invoke(
+        &instruction::add_instructions_sysvar(ix),
+        &[
+            greeting_account.clone(),
+            config.clone(),
+            instructions_sysvar.clone(),
+            helloworld_program.clone(),
+        ],
+    )
+}
+
+/// Have the hello world program log its version
+pub fn version<'a>(helloworld_program: &AccountInfo<'a>) -> ProgramResult {
+    validate_program_account(helloworld_program, &crate::id())?;
+    let ix = instruction::version(helloworld_program.key);
+    invoke(&ix, std::slice::from_ref(helloworld_program))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+    #[test]
+    fn test_rejects_wrong_program() {
+        let not_helloworld = fixtures::read_only(fixtures::program(Pubkey::new_unique()));
+        assert_eq!(
+            version(&not_helloworld),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Greeting counters that halve every so many epochs
+// NOTE With a half-life set in the config account (see config.rs) a greeting counter
+// counts recent greetings more than old ones: it halves every `half_life_epochs`
+// epochs. Nothing walks the accounts each epoch to do that. The account stores the raw
+// counter as of `last_update_epoch`, and the effective counter is worked out from the
+// two whenever the account is read or written. A write folds the decay in: the counter
+// becomes the effective one and last_update_epoch moves on by the whole half-lives
+// applied, so the epochs into the current half-life still count next time.
+//
+// A last_update_epoch of 0 means the account hasn't been stamped yet (it was written
+// before schema 4, or by a Greet from when the config account was optional): it reads
+// its raw counter until the next Greet or Tick stamps it with the current epoch.
+
+/// Counter and last_update_epoch once `counter`, last updated at `last_update_epoch`,
+/// has decayed to `epoch`. A `half_life_epochs` of 0 turns decay off.
+pub fn decay(
+    counter: u64,
+    last_update_epoch: u64,
+    half_life_epochs: u64,
+    epoch: u64,
+) -> (u64, u64) {
+    if half_life_epochs == 0 || last_update_epoch == 0 || epoch <= last_update_epoch {
+        return (counter, last_update_epoch);
+    }
+    let halvings = (epoch - last_update_epoch) / half_life_epochs;
+    let counter = if halvings >= 64 {
+        0
+    } else {
+        counter >> halvings
+    };
+    (counter, last_update_epoch + halvings * half_life_epochs)
+}
+
+/// decay() for an account being written at `epoch`, stamping it with `epoch` when it
+/// isn't stamped yet or decay is off
+pub fn touch(
+    counter: u64,
+    last_update_epoch: u64,
+    half_life_epochs: u64,
+    epoch: u64,
+) -> (u64, u64) {
+    let (counter, last_update_epoch) = decay(counter, last_update_epoch, half_life_epochs, epoch);
+    if half_life_epochs == 0 || last_update_epoch == 0 {
+        (counter, epoch)
+    } else {
+        (counter, last_update_epoch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decay_halves_every_half_life() {
+        assert_eq!(decay(100, 10, 5, 14), (100, 10));
+        assert_eq!(decay(100, 10, 5, 15), (50, 15));
+        // The epochs into the current half-life carry over
+        assert_eq!(decay(100, 10, 5, 27), (12, 25));
+        assert_eq!(decay(12, 25, 5, 27), (12, 25));
+        assert_eq!(decay(u64::MAX, 1, 1, 100), (0, 100));
+    }
+
+    #[test]
+    fn test_no_decay_when_off_or_unstamped() {
+        assert_eq!(decay(100, 10, 0, 1_000), (100, 10));
+        assert_eq!(decay(100, 0, 5, 1_000), (100, 0));
+        // An epoch before the last update
+        assert_eq!(decay(100, 10, 5, 3), (100, 10));
+    }
+
+    #[test]
+    fn test_touch_stamps_the_account() {
+        assert_eq!(touch(100, 0, 5, 42), (100, 42));
+        assert_eq!(touch(100, 10, 0, 42), (100, 42));
+        assert_eq!(touch(100, 10, 5, 42), (1, 40));
+    }
+}
@@ -0,0 +1,348 @@
+//! Errors specific to the hello world program
+// NOTE The runtime only sees a ProgramError, and our errors reach the client as
+// ProgramError::Custom(code) i.e. `InstructionError(0, Custom(6001))`. That number is
+// all the client gets, so ERROR_MESSAGES lets it turn the code back into something
+// readable.
+//
+// Codes are the variant's index plus ERROR_CODE_OFFSET, the same 6000 Anchor numbers a
+// program's own errors from, so a code can't be mistaken for one of the small numbers
+// other programs (the system and token programs, a CPI callee) return. Variants are
+// only ever appended: a code keeps its meaning across versions. The processor logs the
+// error it fails with as `Error: <name> (<code>)`, see log().
+#[cfg(feature = "program")]
+use solana_program::{msg, program_error::ProgramError};
+use std::fmt;
+
+/// What every error's index is offset by in its code
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HelloWorldError {
+    /// The greeting account is an executable (program) account
+    ExecutableAccount = 0,
+    /// The message does not fit in the greeting account's data
+    MessageTooLong = 1,
+    /// The account is on an old schema version and was passed read-only, so it
+    /// can't be upgraded in place
+    MigrationRequired = 2,
+    /// The signer isn't the config authority
+    Unauthorized = 3,
+    /// The account passed as the config account isn't this program's config PDA
+    InvalidConfig = 4,
+    /// The signer isn't the crank authority
+    UnauthorizedCrank = 5,
+    /// The price feed isn't the one in the config or isn't a Pyth price account
+    InvalidPriceFeed = 6,
+    /// The price isn't trading or is older than the config allows
+    StalePrice = 7,
+    /// The price's confidence interval is wider than the config allows
+    UncertainPrice = 8,
+    /// The account isn't a Switchboard randomness account seeded in the last slot
+    InvalidRandomness = 9,
+    /// The randomness wasn't revealed in the current slot
+    RandomnessNotRevealed = 10,
+    /// The greeting account wasn't greeted since its last draw, or a draw is pending
+    NoNewGreeting = 11,
+    /// The account isn't a token account of the stake mint owned by the stake authority
+    InvalidStakeVault = 12,
+    /// A withdrawal is larger than the stake deposited
+    InsufficientStake = 13,
+    /// The greeting account's counter hasn't reached the challenge's target
+    ChallengeNotMet = 14,
+    /// The challenge's deadline has passed, so it can only be refunded
+    ChallengeExpired = 15,
+    /// The challenge's deadline hasn't passed, so it can't be refunded yet
+    ChallengeNotExpired = 16,
+    /// The account isn't a VAA posted to the Wormhole core bridge
+    InvalidVaa = 17,
+    /// The VAA's sequence isn't past the last one received from its emitter
+    VaaAlreadyReceived = 18,
+    /// The account isn't the config's greeting log, or the log authority doesn't own it
+    InvalidGreetingLog = 19,
+    /// The session token isn't this program's, has run out or wasn't issued to the signer
+    InvalidSession = 20,
+    /// The greeting account is being greeted by an instruction further up the call stack
+    Reentrancy = 21,
+    /// The config pauses greetings
+    Paused = 22,
+    /// The greeting account was greeted within the config's cooldown
+    OnCooldown = 23,
+    /// A SetConfig field is out of range
+    InvalidConfigValue = 24,
+    /// The account isn't a counter snapshot of this program, or one of the wrong size
+    InvalidSnapshot = 25,
+    /// The snapshot's leaf holds another greeting account, or isn't the next free one
+    InvalidSnapshotIndex = 26,
+    /// The proof doesn't put the claimed leaf under the config's claim root
+    InvalidClaimProof = 27,
+    /// The leaf was claimed already
+    AlreadyClaimed = 28,
+    /// The greeting's receipt exists already, the counter came back to a value it held
+    ReceiptExists = 29,
+    /// The recorder took a snapshot of the account under this label already
+    LabelTaken = 30,
+    /// The greeting account counts MAX_TAGS tags already, none can be added
+    TagsFull = 31,
+    /// The token account isn't the greeter's of the config's balance mint
+    InvalidBalanceAccount = 32,
+    /// The slot hash isn't in the SlotHashes sysvar, or its slot is older than allowed
+    StaleSlotHash = 33,
+    /// The VAA's emitter has no foreign greeter, see RegisterForeignGreeter
+    UnregisteredEmitter = 34,
+    /// The VAA's payload isn't a greeting, see `wormhole::decode_greeting()`
+    NotAGreeting = 35,
+}
+
+/// Every error code the program can return with its human-readable message
+pub const ERROR_MESSAGES: &[(u32, &str)] = &[
+    (
+        HelloWorldError::ExecutableAccount.code(),
+        "Greeting account must not be executable",
+    ),
+    (
+        HelloWorldError::MessageTooLong.code(),
+        "Message does not fit in the greeting account",
+    ),
+    (
+        HelloWorldError::MigrationRequired.code(),
+        "Account must be writable to migrate it to the current schema version",
+    ),
+    (
+        HelloWorldError::Unauthorized.code(),
+        "Signer is not the config authority",
+    ),
+    (
+        HelloWorldError::InvalidConfig.code(),
+        "Account is not the config account of this program",
+    ),
+    (
+        HelloWorldError::UnauthorizedCrank.code(),
+        "Signer is not the crank authority",
+    ),
+    (
+        HelloWorldError::InvalidPriceFeed.code(),
+        "Account is not the configured Pyth price feed",
+    ),
+    (
+        HelloWorldError::StalePrice.code(),
+        "Price feed is not trading or its price is too old",
+    ),
+    (
+        HelloWorldError::UncertainPrice.code(),
+        "Price confidence interval is too wide",
+    ),
+    (
+        HelloWorldError::InvalidRandomness.code(),
+        "Account is not freshly seeded Switchboard randomness",
+    ),
+    (
+        HelloWorldError::RandomnessNotRevealed.code(),
+        "Randomness is not revealed in this slot",
+    ),
+    (
+        HelloWorldError::NoNewGreeting.code(),
+        "No greeting to draw for since the last draw",
+    ),
+    (
+        HelloWorldError::InvalidStakeVault.code(),
+        "Account is not the stake vault",
+    ),
+    (
+        HelloWorldError::InsufficientStake.code(),
+        "Withdrawal exceeds the stake deposited",
+    ),
+    (
+        HelloWorldError::ChallengeNotMet.code(),
+        "Greeting account has not reached the challenge's counter",
+    ),
+    (
+        HelloWorldError::ChallengeExpired.code(),
+        "Challenge deadline has passed",
+    ),
+    (
+        HelloWorldError::ChallengeNotExpired.code(),
+        "Challenge deadline has not passed yet",
+    ),
+    (
+        HelloWorldError::InvalidVaa.code(),
+        "Account is not a posted Wormhole VAA",
+    ),
+    (
+        HelloWorldError::VaaAlreadyReceived.code(),
+        "VAA is not newer than the last one received from its emitter",
+    ),
+    (
+        HelloWorldError::InvalidGreetingLog.code(),
+        "Account is not the config's greeting log",
+    ),
+    (
+        HelloWorldError::InvalidSession.code(),
+        "Session token is expired or was not issued to the signer",
+    ),
+    (
+        HelloWorldError::Reentrancy.code(),
+        "Greeting account is already being greeted further up the call stack",
+    ),
+    (HelloWorldError::Paused.code(), "Greetings are paused"),
+    (
+        HelloWorldError::OnCooldown.code(),
+        "Greeting account was greeted too recently",
+    ),
+    (
+        HelloWorldError::InvalidConfigValue.code(),
+        "Config value is out of range",
+    ),
+    (
+        HelloWorldError::InvalidSnapshot.code(),
+        "Account is not a counter snapshot of this program",
+    ),
+    (
+        HelloWorldError::InvalidSnapshotIndex.code(),
+        "Snapshot leaf holds another account or is not the next free one",
+    ),
+    (
+        HelloWorldError::InvalidClaimProof.code(),
+        "Claim is not under the config's claim root",
+    ),
+    (
+        HelloWorldError::AlreadyClaimed.code(),
+        "Leaf was claimed already",
+    ),
+    (
+        HelloWorldError::ReceiptExists.code(),
+        "Greeting receipt exists already",
+    ),
+    (
+        HelloWorldError::LabelTaken.code(),
+        "Snapshot label is taken already",
+    ),
+    (
+        HelloWorldError::TagsFull.code(),
+        "Greeting account counts all the tags it has room for",
+    ),
+    (
+        HelloWorldError::InvalidBalanceAccount.code(),
+        "Token account isn't the greeter's of the balance mint",
+    ),
+    (
+        HelloWorldError::StaleSlotHash.code(),
+        "Slot hash is unknown or too old",
+    ),
+    (
+        HelloWorldError::UnregisteredEmitter.code(),
+        "VAA emitter is not a registered foreign greeter",
+    ),
+    (
+        HelloWorldError::NotAGreeting.code(),
+        "VAA payload is not a greeting",
+    ),
+];
+
+impl HelloWorldError {
+    /// Map a `ProgramError::Custom` code back to the error, if it is one of ours
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code.checked_sub(ERROR_CODE_OFFSET)? {
+            0 => Some(HelloWorldError::ExecutableAccount),
+            1 => Some(HelloWorldError::MessageTooLong),
+            2 => Some(HelloWorldError::MigrationRequired),
+            3 => Some(HelloWorldError::Unauthorized),
+            4 => Some(HelloWorldError::InvalidConfig),
+            5 => Some(HelloWorldError::UnauthorizedCrank),
+            6 => Some(HelloWorldError::InvalidPriceFeed),
+            7 => Some(HelloWorldError::StalePrice),
+            8 => Some(HelloWorldError::UncertainPrice),
+            9 => Some(HelloWorldError::InvalidRandomness),
+            10 => Some(HelloWorldError::RandomnessNotRevealed),
+            11 => Some(HelloWorldError::NoNewGreeting),
+            12 => Some(HelloWorldError::InvalidStakeVault),
+            13 => Some(HelloWorldError::InsufficientStake),
+            14 => Some(HelloWorldError::ChallengeNotMet),
+            15 => Some(HelloWorldError::ChallengeExpired),
+            16 => Some(HelloWorldError::ChallengeNotExpired),
+            17 => Some(HelloWorldError::InvalidVaa),
+            18 => Some(HelloWorldError::VaaAlreadyReceived),
+            19 => Some(HelloWorldError::InvalidGreetingLog),
+            20 => Some(HelloWorldError::InvalidSession),
+            21 => Some(HelloWorldError::Reentrancy),
+            22 => Some(HelloWorldError::Paused),
+            23 => Some(HelloWorldError::OnCooldown),
+            24 => Some(HelloWorldError::InvalidConfigValue),
+            25 => Some(HelloWorldError::InvalidSnapshot),
+            26 => Some(HelloWorldError::InvalidSnapshotIndex),
+            27 => Some(HelloWorldError::InvalidClaimProof),
+            28 => Some(HelloWorldError::AlreadyClaimed),
+            29 => Some(HelloWorldError::ReceiptExists),
+            30 => Some(HelloWorldError::LabelTaken),
+            31 => Some(HelloWorldError::TagsFull),
+            32 => Some(HelloWorldError::InvalidBalanceAccount),
+            33 => Some(HelloWorldError::StaleSlotHash),
+            34 => Some(HelloWorldError::UnregisteredEmitter),
+            35 => Some(HelloWorldError::NotAGreeting),
+            _ => None,
+        }
+    }
+
+    /// The numeric code sent to clients inside `ProgramError::Custom`
+    pub const fn code(self) -> u32 {
+        ERROR_CODE_OFFSET + self as u32
+    }
+
+    /// Human-readable description of the error
+    pub fn message(self) -> &'static str {
+        ERROR_MESSAGES
+            .iter()
+            .find(|(code, _)| *code == self.code())
+            .map(|(_, message)| *message)
+            .unwrap_or("Unknown error")
+    }
+
+    /// Log the error as `Error: <name> (<code>)`
+    // NOTE msg!, not log!, so the line is there without the "log" feature too: it's
+    // what to grep a validator's logs for
+    #[cfg(feature = "program")]
+    pub fn log(self) {
+        msg!("Error: {:?} ({})", self, self.code());
+    }
+}
+
+impl fmt::Display for HelloWorldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for HelloWorldError {}
+
+#[cfg(feature = "program")]
+impl From<HelloWorldError> for ProgramError {
+    fn from(e: HelloWorldError) -> Self {
+        ProgramError::Custom(e.code())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_code_round_trips_every_catalog_entry() {
+        for (code, message) in ERROR_MESSAGES {
+            let error = HelloWorldError::from_code(*code).unwrap();
+            assert_eq!(error.code(), *code);
+            assert_eq!(error.message(), *message);
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(*code));
+        }
+        assert_eq!(
+            HelloWorldError::from_code(ERROR_CODE_OFFSET + ERROR_MESSAGES.len() as u32),
+            None
+        );
+        assert_eq!(HelloWorldError::from_code(1), None);
+    }
+
+    #[test]
+    fn test_codes_are_offset() {
+        assert_eq!(HelloWorldError::ExecutableAccount.code(), 6000);
+        assert_eq!(HelloWorldError::MessageTooLong.code(), 6001);
+        assert_eq!(HelloWorldError::InvalidConfigValue.code(), 6024);
+    }
+}
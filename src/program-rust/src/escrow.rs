@@ -0,0 +1,258 @@
+//! The interface of the escrow program (src/escrow), a second example program swapping
+//! two SPL tokens without either side trusting the other
+// NOTE Module-wide for EscrowInstruction's BorshSchema derive, see instruction.rs
+#![allow(dead_code)]
+// NOTE The escrow program is a separate program built on this crate, like the transfer
+// hook, so its layouts and builders live here with helloworld's and clients need only
+// the one interface crate.
+//
+// The initializer offers `offered_amount` of one token for `expected_amount` of
+// another. InitEscrow creates the escrow, a PDA of the escrow program at [ESCROW_SEED,
+// initializer] (so one open escrow per initializer), and moves the offered tokens into
+// the vault, a token account the client creates beforehand with the escrow as its
+// owner. Exchange lets anyone take the offer: the taker pays the expected tokens
+// straight into the initializer's receive account and the escrow signs the offered
+// tokens out of the vault to the taker. Cancel gives the initializer the offered tokens
+// back. Both close the vault and the escrow, refunding their rent to the initializer.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+/// Seed of an escrow, followed by the initializer's key
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// An open offer
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct Escrow {
+    /// key of who made the offer
+    pub initializer: [u8; 32],
+    /// key of the token account holding the offered tokens, owned by the escrow
+    pub vault: [u8; 32],
+    /// key of the initializer's token account the expected tokens are paid into
+    pub receive_account: [u8; 32],
+    /// tokens offered, in the vault
+    pub offered_amount: u64,
+    /// tokens asked for in exchange
+    pub expected_amount: u64,
+}
+
+/// Size of an escrow account's data
+pub const ESCROW_SIZE: usize = 32 + 32 + 32 + 8 + 8;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub enum EscrowInstruction {
+    /// Offer tokens from the initializer's token account for tokens of another mint
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The escrow, see `escrow_address()`
+    /// 1. `[signer, writable]` The initializer, paying for the escrow
+    /// 2. `[writable]` The initializer's token account to take the offered tokens from
+    /// 3. `[writable]` The vault, a token account of the offered mint owned by the escrow
+    /// 4. `[]` The initializer's token account of the expected mint
+    /// 5. `[]` The SPL Token program
+    /// 6. `[]` The system program
+    InitEscrow {
+        /// Tokens offered
+        offered_amount: u64,
+        /// Tokens asked for in exchange
+        expected_amount: u64,
+    },
+
+    /// Take the offer
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The escrow
+    /// 1. `[signer]` The taker
+    /// 2. `[writable]` The taker's token account to pay the expected tokens from
+    /// 3. `[writable]` The taker's token account to receive the offered tokens
+    /// 4. `[writable]` The initializer's receive account
+    /// 5. `[writable]` The vault
+    /// 6. `[writable]` The initializer, refunded the escrow's and the vault's rent
+    /// 7. `[]` The SPL Token program
+    Exchange {
+        /// Tokens the taker expects to get, so a changed offer fails rather than
+        /// trading at terms the taker never saw
+        offered_amount: u64,
+        /// Tokens the taker expects to pay
+        expected_amount: u64,
+    },
+
+    /// Withdraw the offer
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The escrow
+    /// 1. `[signer, writable]` The initializer, refunded the escrow's and the vault's rent
+    /// 2. `[writable]` The vault
+    /// 3. `[writable]` The initializer's token account to return the offered tokens to
+    /// 4. `[]` The SPL Token program
+    Cancel,
+}
+
+/// Address and bump seed of `initializer`'s escrow
+#[cfg(feature = "program")]
+pub fn escrow_address(escrow_program_id: &Pubkey, initializer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED, initializer.as_ref()], escrow_program_id)
+}
+
+/// Build an `InitEscrow` instruction offering `offered_amount` tokens from `source`
+/// for `expected_amount` paid into `receive_account`
+#[cfg(feature = "program")]
+pub fn init_escrow(
+    escrow_program_id: &Pubkey,
+    initializer: &Pubkey,
+    source: &Pubkey,
+    vault: &Pubkey,
+    receive_account: &Pubkey,
+    offered_amount: u64,
+    expected_amount: u64,
+) -> Instruction {
+    let data = EscrowInstruction::InitEscrow {
+        offered_amount,
+        expected_amount,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (escrow, _) = escrow_address(escrow_program_id, initializer);
+    Instruction::new_with_bytes(
+        *escrow_program_id,
+        &data,
+        vec![
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(*initializer, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(*receive_account, false),
+            AccountMeta::new_readonly(crate::token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build an `Exchange` instruction taking `escrow`'s offer
+#[cfg(feature = "program")]
+pub fn exchange(
+    escrow_program_id: &Pubkey,
+    escrow: &Escrow,
+    taker: &Pubkey,
+    taker_source: &Pubkey,
+    taker_destination: &Pubkey,
+) -> Instruction {
+    let data = EscrowInstruction::Exchange {
+        offered_amount: escrow.offered_amount,
+        expected_amount: escrow.expected_amount,
+    }
+    .try_to_vec()
+    .unwrap();
+    let initializer = Pubkey::new_from_array(escrow.initializer);
+    let (escrow_key, _) = escrow_address(escrow_program_id, &initializer);
+    Instruction::new_with_bytes(
+        *escrow_program_id,
+        &data,
+        vec![
+            AccountMeta::new(escrow_key, false),
+            AccountMeta::new_readonly(*taker, true),
+            AccountMeta::new(*taker_source, false),
+            AccountMeta::new(*taker_destination, false),
+            AccountMeta::new(Pubkey::new_from_array(escrow.receive_account), false),
+            AccountMeta::new(Pubkey::new_from_array(escrow.vault), false),
+            AccountMeta::new(initializer, false),
+            AccountMeta::new_readonly(crate::token::id(), false),
+        ],
+    )
+}
+
+/// Build a `Cancel` instruction withdrawing `initializer`'s offer, returning the tokens
+/// in `vault` to `destination`
+#[cfg(feature = "program")]
+pub fn cancel(
+    escrow_program_id: &Pubkey,
+    initializer: &Pubkey,
+    vault: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let data = EscrowInstruction::Cancel.try_to_vec().unwrap();
+    let (escrow, _) = escrow_address(escrow_program_id, initializer);
+    Instruction::new_with_bytes(
+        *escrow_program_id,
+        &data,
+        vec![
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(*initializer, true),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(crate::token::id(), false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escrow() {
+        assert_eq!(Escrow::default().try_to_vec().unwrap().len(), ESCROW_SIZE);
+        let data = EscrowInstruction::InitEscrow {
+            offered_amount: 500,
+            expected_amount: 2,
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(
+            data,
+            vec![0, 244, 1, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(EscrowInstruction::Cancel.try_to_vec().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_builders() {
+        let program_id = Pubkey::new_unique();
+        let (initializer, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (escrow_key, _) = escrow_address(&program_id, &initializer);
+        let ix = init_escrow(
+            &program_id,
+            &initializer,
+            &Pubkey::new_unique(),
+            &vault,
+            &Pubkey::new_unique(),
+            500,
+            2,
+        );
+        assert_eq!((ix.accounts.len(), ix.accounts[0].pubkey), (7, escrow_key));
+        assert!(ix.accounts[1].is_signer);
+        let escrow = Escrow {
+            initializer: initializer.to_bytes(),
+            vault: vault.to_bytes(),
+            receive_account: [3; 32],
+            offered_amount: 500,
+            expected_amount: 2,
+        };
+        let taker = Pubkey::new_unique();
+        let ix = exchange(
+            &program_id,
+            &escrow,
+            &taker,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        );
+        assert_eq!(ix.accounts.len(), 8);
+        assert_eq!(ix.accounts[0].pubkey, escrow_key);
+        assert_eq!(ix.accounts[5].pubkey, vault);
+        assert_eq!(ix.accounts[6].pubkey, initializer);
+        assert_eq!(
+            EscrowInstruction::try_from_slice(&ix.data).unwrap(),
+            EscrowInstruction::Exchange {
+                offered_amount: 500,
+                expected_amount: 2,
+            }
+        );
+        let ix = cancel(&program_id, &initializer, &vault, &Pubkey::new_unique());
+        assert_eq!(ix.accounts.len(), 5);
+        assert!(ix.accounts[1].is_signer && ix.accounts[1].is_writable);
+    }
+}
@@ -0,0 +1,246 @@
+//! Structured events the program logs when an admin changes the config, for monitoring
+//! to alert on privileged operations
+// NOTE Events are encoded the way Anchor's emit! encodes them: the 8-byte discriminator
+// sha256("event:<Name>")[..8] followed by the Borsh encoding of the event's struct, so
+// a parser reading the Anchor IDL (`--anchor` in the schema crate's idl binary) decodes
+// them as they are. An event is logged as one "helloworld-event: <hex>" line of that
+// encoding. The runtime's own channel for this, sol_log_data() (the "Program data:
+// <base64>" lines Anchor tooling reads), arrived in solana-program 1.8: emit() goes
+// through sdk::log_data(), which uses it when built with "sdk-1-18" and logs the hex
+// line on the pinned 1.6. from_log() reads both. Like the helloworld-version line it's
+// logged with or without the "log" feature.
+// Works on the logs of a transaction (getTransaction, a Geyser stream) or of
+// solana-program-test.
+use crate::config::Config;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use std::io;
+
+/// Prefix of the lines carrying an event
+pub const EVENT_PREFIX: &str = "helloworld-event: ";
+
+/// SetConfig, SetDecay, SetCrankAuthority, SetFee, SetStake or InitGreetingLog changed
+/// the config
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct ConfigChanged {
+    /// the config before the change
+    pub old: Config,
+    /// the config after it
+    pub new: Config,
+}
+
+/// Anchor discriminator of ConfigChanged: sha256("event:ConfigChanged")[..8]
+pub const CONFIG_CHANGED: [u8; 8] = [147, 25, 86, 98, 98, 77, 78, 192];
+
+/// SetAuthority handed the config from `old` to `new`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct AuthorityChanged {
+    /// key of the authority handing the config over
+    pub old: [u8; 32],
+    /// key of the new authority
+    pub new: [u8; 32],
+}
+
+/// Anchor discriminator of AuthorityChanged: sha256("event:AuthorityChanged")[..8]
+pub const AUTHORITY_CHANGED: [u8; 8] = [31, 19, 174, 152, 4, 82, 215, 226];
+
+/// A privileged change, with the values before and after it
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// Boxed to keep the BPF stack frame small
+    ConfigChanged(Box<ConfigChanged>),
+    AuthorityChanged(AuthorityChanged),
+}
+
+impl Event {
+    /// ConfigChanged from `old` to `new`
+    pub fn config_changed(old: &Config, new: &Config) -> Self {
+        Event::ConfigChanged(Box::new(ConfigChanged {
+            old: old.clone(),
+            new: new.clone(),
+        }))
+    }
+
+    /// AuthorityChanged from `old` to `new`
+    pub fn authority_changed(old: [u8; 32], new: [u8; 32]) -> Self {
+        Event::AuthorityChanged(AuthorityChanged { old, new })
+    }
+
+    /// The discriminator followed by the event's fields
+    pub fn to_vec(&self) -> Vec<u8> {
+        let (discriminator, fields) = match self {
+            Event::ConfigChanged(event) => (CONFIG_CHANGED, event.try_to_vec()),
+            Event::AuthorityChanged(event) => (AUTHORITY_CHANGED, event.try_to_vec()),
+        };
+        [&discriminator[..], &fields.unwrap()].concat()
+    }
+
+    /// Decode what to_vec() encodes
+    pub fn from_slice(data: &[u8]) -> io::Result<Self> {
+        if data.len() < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "missing event discriminator",
+            ));
+        }
+        let (discriminator, fields) = data.split_at(8);
+        if discriminator == CONFIG_CHANGED {
+            let event = ConfigChanged::try_from_slice(fields)?;
+            Ok(Event::ConfigChanged(Box::new(event)))
+        } else if discriminator == AUTHORITY_CHANGED {
+            let event = AuthorityChanged::try_from_slice(fields)?;
+            Ok(Event::AuthorityChanged(event))
+        } else {
+            Err(io::Error::new(io::ErrorKind::InvalidData, "unknown event"))
+        }
+    }
+
+    /// The log line carrying the event
+    pub fn to_log(&self) -> String {
+        let data = self.to_vec();
+        let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}{}", EVENT_PREFIX, hex)
+    }
+
+    /// The event a log line carries, None for any other line. Takes the line as the
+    /// program logged it or as the runtime reports it, after "Program log: ", or the
+    /// "Program data: " line sol_log_data() writes. Data lines are other programs'
+    /// too, so only those starting with one of the discriminators are events.
+    pub fn from_log(line: &str) -> Option<io::Result<Self>> {
+        if let Some(base64) = line.strip_prefix("Program data: ") {
+            let data = decode_base64(base64).ok()?;
+            let discriminator = data.get(..8)?;
+            if discriminator != CONFIG_CHANGED && discriminator != AUTHORITY_CHANGED {
+                return None;
+            }
+            return Some(Self::from_slice(&data));
+        }
+        let line = line.strip_prefix("Program log: ").unwrap_or(line);
+        let hex = line.strip_prefix(EVENT_PREFIX)?;
+        Some(decode_hex(hex).and_then(|data| Self::from_slice(&data)))
+    }
+
+    /// Log the event
+    #[cfg(feature = "program")]
+    pub fn emit(&self) {
+        crate::sdk::log_data(EVENT_PREFIX, &self.to_vec());
+    }
+}
+
+/// Every event in `logs`, in the order they were logged
+pub fn events<S: AsRef<str>>(logs: &[S]) -> Vec<io::Result<Event>> {
+    logs.iter()
+        .filter_map(|line| Event::from_log(line.as_ref()))
+        .collect()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid hex");
+    if hex.len() & 1 != 0 {
+        return Err(invalid());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+fn decode_base64(base64: &str) -> io::Result<Vec<u8>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid base64");
+    let sextet = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let base64 = base64.trim_end_matches('=').as_bytes();
+    if base64.len() % 4 == 1 {
+        return Err(invalid());
+    }
+    let mut data = Vec::with_capacity(base64.len() * 3 / 4);
+    for chunk in base64.chunks(4) {
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            bits |= u32::from(sextet(c).ok_or_else(invalid)?) << (18 - 6 * i);
+        }
+        data.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_discriminators_are_anchors() {
+        let discriminator = |name: &str| {
+            let hash = solana_program::hash::hash(format!("event:{}", name).as_bytes());
+            hash.to_bytes()[..8].to_vec()
+        };
+        assert_eq!(discriminator("ConfigChanged"), CONFIG_CHANGED);
+        assert_eq!(discriminator("AuthorityChanged"), AUTHORITY_CHANGED);
+    }
+
+    #[test]
+    fn test_event_round_trips_through_a_log_line() {
+        let event = Event::config_changed(
+            &Config::default(),
+            &Config {
+                paused: true,
+                ..Config::default()
+            },
+        );
+        let line = format!("Program log: {}", event.to_log());
+        assert_eq!(Event::from_log(&line).unwrap().unwrap(), event);
+        // Anchor's layout: the discriminator and the struct, no enum tag
+        let data = event.to_vec();
+        assert_eq!(data[..8], CONFIG_CHANGED);
+        assert!(
+            ConfigChanged::try_from_slice(&data[8..])
+                .unwrap()
+                .new
+                .paused
+        );
+
+        let logs = [
+            "Program log: Config updated".to_string(),
+            Event::authority_changed([1; 32], [2; 32]).to_log(),
+            format!("{}zz", EVENT_PREFIX),
+            format!("{}{}", EVENT_PREFIX, "00".repeat(8)),
+        ];
+        let found = events(&logs);
+        assert_eq!(found.len(), 3);
+        assert_eq!(
+            found[0].as_ref().unwrap(),
+            &Event::authority_changed([1; 32], [2; 32])
+        );
+        assert!(found[1].is_err());
+        assert!(found[2].is_err());
+    }
+
+    #[test]
+    fn test_event_parses_from_a_data_line() {
+        use crate::test_utils::encode_base64 as base64;
+
+        // AuthorityChanged([1; 32], [2; 32]) as sol_log_data() logs it, base64 of
+        // its discriminator and fields
+        let event = Event::authority_changed([1; 32], [2; 32]);
+        let data = event.to_vec();
+        assert_eq!(decode_base64(&base64(&data)).unwrap(), data);
+        let line = format!("Program data: {}", base64(&data));
+        assert_eq!(Event::from_log(&line).unwrap().unwrap(), event);
+
+        // Another program's data, and data too short to be an event
+        assert!(Event::from_log(&format!("Program data: {}", base64(&[7; 40]))).is_none());
+        assert!(Event::from_log("Program data: AQID").is_none());
+        assert!(Event::from_log("Program data: !!!!").is_none());
+        assert_eq!(decode_base64("aGk=").unwrap(), b"hi");
+        assert!(decode_base64("a").is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Keys, addresses and accounts for tests that are the same on every run, shared with
+//! the program-test suite and the other programs' tests through the `test-utils`
+//! feature
+// NOTE Pubkey::new_unique() hands out keys in the order tests ask for them, which
+// changes with the tests picked and the threads they land on, so a failure names a
+// different address every run. A fixture key is derived from a name instead:
+// key("greeter") is the same in the unit tests, tests/lib.rs and the escrow's tests,
+// and a failing run can be replayed and its logs searched for the address.
+//
+// solana-program can't sign, so a keypair is handed out as its seed: suites with
+// solana-sdk make the Keypair with keypair_from_seed(&fixtures::seed(name)). Its public
+// key is then the keypair's, not key(name); key() is for accounts that only sign in
+// unit tests, where a signature is the is_signer flag.
+use crate::{
+    config::{self, Config},
+    increment::FIELDS_SIZE,
+    migrations::GREETING_DISCRIMINATOR,
+};
+use solana_program::{
+    account_info::AccountInfo, bpf_loader, clock::Epoch, hash::hashv, pubkey::Pubkey,
+    system_program,
+};
+
+/// Lamports a funded fixture account holds, 10 SOL
+pub const FUNDED_LAMPORTS: u64 = 10_000_000_000;
+
+/// Longest message a fixture greeting account of GREETING_SIZE has room for
+pub const MAX_TXT_LEN: usize = 12;
+
+/// Size of a greeting account in the current layout: the discriminator, a Borsh String
+/// (u32 length prefix + up to MAX_TXT_LEN bytes of text) and the fields after it
+pub const GREETING_SIZE: usize = GREETING_DISCRIMINATOR.len() + 4 + MAX_TXT_LEN + FIELDS_SIZE;
+
+/// 32 bytes derived from `name` alone: a keypair seed, or the bytes of key(name)
+pub fn seed(name: &str) -> [u8; 32] {
+    hashv(&[b"helloworld-fixture:", name.as_bytes()]).to_bytes()
+}
+
+/// The key named `name`
+pub fn key(name: &str) -> Pubkey {
+    Pubkey::new_from_array(seed(name))
+}
+
+/// The program id tests run the program under
+pub fn program_id() -> Pubkey {
+    key("program")
+}
+
+/// Address of the PDA of program_id() at `seeds`
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &program_id()).0
+}
+
+/// Address of program_id()'s config account
+pub fn config_address() -> Pubkey {
+    config::config_address(&program_id()).0
+}
+
+/// A writable account living until the end of the test run, executable when it's a
+/// program (owned by the BPF loader)
+// NOTE Leaked, so tests can keep cloning it into account lists without the borrow of
+// locals for every field that AccountInfo::new() takes
+pub fn account(
+    key: Pubkey,
+    is_signer: bool,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        true,
+        Box::leak(Box::new(lamports)),
+        Box::leak(data.into_boxed_slice()),
+        Box::leak(Box::new(owner)),
+        owner == bpf_loader::id(),
+        Epoch::default(),
+    )
+}
+
+/// `account` made read-only
+pub fn read_only(mut account: AccountInfo<'static>) -> AccountInfo<'static> {
+    account.is_writable = false;
+    account
+}
+
+/// `account` marked executable or not whatever its owner, for the checks refusing a
+/// program where state belongs or an account posing as a program
+pub fn with_executable(
+    mut account: AccountInfo<'static>,
+    executable: bool,
+) -> AccountInfo<'static> {
+    account.executable = executable;
+    account
+}
+
+/// `account` under `key`, sharing its lamports and data
+pub fn with_key(mut account: AccountInfo<'static>, key: Pubkey) -> AccountInfo<'static> {
+    account.key = Box::leak(Box::new(key));
+    account
+}
+
+/// `account` owned by `owner`, sharing its lamports and data
+pub fn with_owner(mut account: AccountInfo<'static>, owner: Pubkey) -> AccountInfo<'static> {
+    account.owner = Box::leak(Box::new(owner));
+    account
+}
+
+/// The signer named `name`, a system account holding FUNDED_LAMPORTS
+pub fn signer(name: &str) -> AccountInfo<'static> {
+    account(
+        key(name),
+        true,
+        FUNDED_LAMPORTS,
+        vec![],
+        system_program::id(),
+    )
+}
+
+/// The greeting account named `name`: `size` zeroed bytes owned by program_id(), as
+/// the client creates it
+pub fn greeting_account(name: &str, size: usize) -> AccountInfo<'static> {
+    account(
+        key(name),
+        false,
+        FUNDED_LAMPORTS,
+        vec![0; size],
+        program_id(),
+    )
+}
+
+/// program_id()'s config account holding `config`
+pub fn config_account(config: &Config) -> AccountInfo<'static> {
+    let mut data = vec![0; config::CONFIG_SIZE];
+    config.pack(&mut data).unwrap();
+    account(config_address(), false, FUNDED_LAMPORTS, data, program_id())
+}
+
+/// The program at `program_id`, for the processors that check a program account
+pub fn program(program_id: Pubkey) -> AccountInfo<'static> {
+    account(program_id, false, 0, vec![], bpf_loader::id())
+}
+
+/// A seeded xorshift64* generator, for randomized tests a failing case of can be
+/// replayed from its seed
+pub struct Rng(u64);
+
+impl Rng {
+    /// The generator starting from `seed`, which mustn't be 0
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift never leaves 0");
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in 0..n
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_u64() as u8).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_keys_are_stable() {
+        // Pinned, so a failure names the same addresses on every machine
+        let pinned = "6PgjM8mh93gkj7L75EAEJummk8DfUmd8EUaRPUKHRmHf";
+        assert_eq!(program_id(), Pubkey::from_str(pinned).unwrap());
+        assert_ne!(key("greeter"), key("greeted"));
+        assert_eq!(config_address(), pda(&[config::CONFIG_SEED]));
+        assert_eq!(seed("crank"), key("crank").to_bytes());
+
+        let config = config_account(&Config {
+            authority: key("authority").to_bytes(),
+            ..Config::default()
+        });
+        assert_eq!(
+            Config::unpack(&config.data.borrow()).unwrap().authority,
+            key("authority").to_bytes()
+        );
+        assert!(signer("greeter").is_signer);
+        assert_eq!(signer("greeter").lamports(), FUNDED_LAMPORTS);
+        assert!(program(system_program::id()).executable);
+        assert!(!greeting_account("greeted", 8).executable);
+    }
+
+    #[test]
+    fn test_greeting_size_fits_the_longest_message() {
+        let greeting = crate::GreetingAccount {
+            txt: "x".repeat(MAX_TXT_LEN),
+            counter: u64::MAX,
+            calendar_year: 2024,
+            greeted_days: crate::calendar::DayBitmap::default(),
+            last_update_epoch: u64::MAX,
+            direct_greets: u64::MAX,
+            cpi_greets: u64::MAX,
+        };
+        let data = crate::migrations::encode_as(crate::STATE_VERSION, &greeting).unwrap();
+        assert_eq!(data.len(), GREETING_SIZE);
+    }
+}
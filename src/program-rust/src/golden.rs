@@ -0,0 +1,441 @@
+//! Golden vectors: the exact bytes of every instruction, of the accounts clients read
+//! and of the events, checked in under golden/
+// NOTE These files are the encoding contract for anything outside this crate that
+// reads or writes the program's data: the JS client, the Python and wasm bindings,
+// third-party integrators. Each one is built here from fixed values and compared byte
+// for byte with the file, and the file is decoded back to the same value. A change that
+// moves a byte fails the test, whether it's meant to or not.
+//
+// When a change to a layout is meant (a new instruction, a field appended to the
+// config), rewrite the files with `UPDATE_GOLDEN=1 cargo test golden` and commit them
+// with it, so the diff shows which encodings moved. An existing file changing is a
+// breaking change for every client: only files for new values should appear.
+//
+// golden/js/ holds the same values encoded by the TypeScript client instead, written by
+// `npm run golden` (src/client/golden.ts) and not checked in. test_js_vectors() decodes
+// them here, so a field the client's schema lays out differently from the program's
+// fails it. Without the directory there's nothing to check, as under a plain `cargo
+// test`; CI writes it first.
+//
+// The values set every field to something other than its default, and different
+// fields to different bytes, so fields swapping places or a field left out changes the
+// bytes too.
+use crate::{
+    calendar::DayBitmap,
+    compact,
+    config::{self, Config, ConfigPatch},
+    event::Event,
+    instruction::HelloWorldInstruction,
+    migrations,
+    milestone::{self, GreetingSnapshot},
+    receipt::{self, GreetingReceipt},
+    tags::{self, TagCount, TagCounters},
+    GreetingAccount, STATE_VERSION,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::{env, fs, path::PathBuf};
+
+/// Directory of the vectors, next to the manifest building the crate or above it for
+/// the ones under sdk/
+fn golden_dir() -> PathBuf {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("golden"))
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| manifest_dir.join("golden"))
+}
+
+/// Compare `data` with the vector at golden/`name`, or write it with UPDATE_GOLDEN set
+fn check(name: &str, data: &[u8]) {
+    let path = golden_dir().join(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, data).unwrap();
+        return;
+    }
+    let expected = fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "{}: {}, write it with UPDATE_GOLDEN=1 cargo test golden",
+            path.display(),
+            err
+        )
+    });
+    assert!(
+        expected == data,
+        "{} changed, the encoding is no longer compatible with existing clients\n\
+         expected {:02x?}\n     got {:02x?}",
+        name,
+        expected,
+        data
+    );
+}
+
+/// A greeting account with every field set
+fn greeting_account() -> GreetingAccount {
+    let mut greeted_days = DayBitmap::default();
+    greeted_days.set(0);
+    greeted_days.set(100);
+    greeted_days.set(365);
+    GreetingAccount {
+        txt: "Hello1234567".to_string(),
+        counter: 42,
+        calendar_year: 2024,
+        greeted_days,
+        last_update_epoch: 500,
+        direct_greets: 40,
+        cpi_greets: 2,
+    }
+}
+
+/// A config with every field set
+fn config() -> Config {
+    Config {
+        authority: [1; 32],
+        decay_half_life_epochs: 2,
+        crank_authority: [3; 32],
+        fee_usd_micros: 4,
+        max_price_age_slots: 5,
+        max_confidence_bps: 6,
+        price_feed: [7; 32],
+        fee_recipient: [8; 32],
+        stake_mint: [9; 32],
+        stake_unit: 10,
+        greeting_log: [11; 32],
+        paused: true,
+        max_message_len: 13,
+        cooldown_days: 14,
+        max_greet_many_accounts: 15,
+        claim_root: [16; 32],
+        greeting_receipts: true,
+        balance_mint: [18; 32],
+        balance_unit: 19,
+        epoch_bonus_slots: 20,
+    }
+}
+
+/// A patch setting every field
+fn config_patch() -> ConfigPatch {
+    ConfigPatch {
+        decay_half_life_epochs: Some(2),
+        crank_authority: Some([3; 32]),
+        fee_usd_micros: Some(4),
+        max_price_age_slots: Some(5),
+        max_confidence_bps: Some(6),
+        price_feed: Some([7; 32]),
+        fee_recipient: Some([8; 32]),
+        stake_unit: Some(10),
+        paused: Some(true),
+        max_message_len: Some(13),
+        cooldown_days: Some(14),
+        max_greet_many_accounts: Some(15),
+        claim_root: Some([16; 32]),
+        greeting_receipts: Some(true),
+        balance_mint: Some([18; 32]),
+        balance_unit: Some(19),
+        epoch_bonus_slots: Some(20),
+    }
+}
+
+/// A receipt with every field set
+fn receipt() -> GreetingReceipt {
+    GreetingReceipt {
+        signer: [1; 32],
+        slot: 200_000,
+        unix_timestamp: 1_700_000_000,
+    }
+}
+
+/// A snapshot with every field set
+fn snapshot() -> GreetingSnapshot {
+    GreetingSnapshot {
+        greeting_account: [1; 32],
+        recorder: [2; 32],
+        counter: 42,
+        slot: 200_000,
+        unix_timestamp: 1_700_000_000,
+        label: "launch".to_string(),
+    }
+}
+
+/// Tag counters with two tags
+fn tag_counters() -> TagCounters {
+    TagCounters {
+        greeting_account: [1; 32],
+        entries: vec![
+            TagCount {
+                tag: tags::tag("gm").unwrap(),
+                count: 3,
+            },
+            TagCount {
+                tag: tags::tag("gn").unwrap(),
+                count: 1,
+            },
+        ],
+    }
+}
+
+/// One instruction of every kind, with the name of its vector
+fn instructions() -> Vec<(&'static str, HelloWorldInstruction)> {
+    use HelloWorldInstruction::*;
+    let txt = || "Hello1234567".to_string();
+    vec![
+        ("greet", Greet { txt: txt() }),
+        ("version", Version),
+        ("export_state", ExportState),
+        ("import_state", ImportState),
+        (
+            "init_config",
+            InitConfig {
+                decay_half_life_epochs: 2,
+            },
+        ),
+        (
+            "set_decay",
+            SetDecay {
+                half_life_epochs: 3,
+            },
+        ),
+        (
+            "set_crank_authority",
+            SetCrankAuthority {
+                crank_authority: [3; 32],
+            },
+        ),
+        ("tick", Tick),
+        (
+            "set_fee",
+            SetFee {
+                fee_usd_micros: 4,
+                max_price_age_slots: 5,
+                max_confidence_bps: 6,
+                price_feed: [7; 32],
+                fee_recipient: [8; 32],
+            },
+        ),
+        ("commit_luck", CommitLuck),
+        ("reveal_luck", RevealLuck),
+        (
+            "set_authority",
+            SetAuthority {
+                new_authority: [1; 32],
+            },
+        ),
+        (
+            "set_stake",
+            SetStake {
+                stake_mint: [9; 32],
+                stake_unit: 10,
+            },
+        ),
+        ("deposit", Deposit { amount: 11 }),
+        ("withdraw_stake", WithdrawStake { amount: 12 }),
+        (
+            "create_challenge",
+            CreateChallenge {
+                greet_count: 13,
+                deadline_slot: 14,
+                amount: 15,
+            },
+        ),
+        ("claim_challenge", ClaimChallenge),
+        ("refund_challenge", RefundChallenge),
+        ("receive_cross_chain_greet", ReceiveCrossChainGreet),
+        (
+            "init_greeting_log",
+            InitGreetingLog {
+                max_depth: 14,
+                max_buffer_size: 64,
+            },
+        ),
+        (
+            "create_session",
+            CreateSession {
+                duration_seconds: 3_600,
+                top_up_lamports: 5_000,
+            },
+        ),
+        ("revoke_session", RevokeSession),
+        ("increment", Increment),
+        ("greet_many", GreetMany { txt: txt() }),
+        ("greet_for", GreetFor { txt: txt() }),
+        (
+            "set_config",
+            SetConfig {
+                patch: Box::new(config_patch()),
+            },
+        ),
+        (
+            "set_config_empty",
+            SetConfig {
+                patch: Box::default(),
+            },
+        ),
+        ("init_counter_snapshot", InitCounterSnapshot { depth: 4 }),
+        (
+            "snapshot_counters",
+            SnapshotCounters {
+                indices: vec![0, 1, 65_536],
+            },
+        ),
+        (
+            "claim",
+            Claim {
+                index: 17,
+                amount: 18,
+                proof: vec![[19; 32], [20; 32]],
+            },
+        ),
+        (
+            "snapshot",
+            Snapshot {
+                label: "launch".to_string(),
+            },
+        ),
+        (
+            "count_tag",
+            CountTag {
+                tag: tags::tag("gm").unwrap(),
+            },
+        ),
+        (
+            "check_recency",
+            CheckRecency {
+                slot: 200_000,
+                slot_hash: [21; 32],
+                max_age_slots: 150,
+            },
+        ),
+        (
+            "register_foreign_greeter",
+            RegisterForeignGreeter {
+                chain: 2,
+                sender: [22; 32],
+            },
+        ),
+    ]
+}
+
+#[test]
+fn test_instruction_vectors() {
+    let instructions = instructions();
+    // One vector per instruction: a new one needs its vector
+    let tags: Vec<u8> = instructions
+        .iter()
+        .map(|(_, instruction)| instruction.try_to_vec().unwrap()[0])
+        .collect();
+    let last = *tags.last().unwrap() as usize;
+    assert!((0..=last).all(|tag| tags.contains(&(tag as u8))));
+    assert_eq!(
+        HelloWorldInstruction::try_from_slice(&[last as u8 + 1]).ok(),
+        None
+    );
+
+    for (name, instruction) in instructions {
+        let data = instruction.try_to_vec().unwrap();
+        check(&format!("instruction/{}.bin", name), &data);
+        assert_eq!(
+            HelloWorldInstruction::try_from_slice(&data).unwrap(),
+            instruction
+        );
+    }
+}
+
+#[test]
+fn test_greeting_account_vectors() {
+    let account = greeting_account();
+    for version in 1..=STATE_VERSION {
+        let data = migrations::encode_as(version, &account).unwrap();
+        check(&format!("account/greeting_v{}.bin", version), &data);
+        assert_eq!(migrations::stored_version(&data).unwrap(), version);
+    }
+    let data = migrations::encode_as(STATE_VERSION, &account).unwrap();
+    assert_eq!(GreetingAccount::unpack(&data).unwrap(), account);
+
+    let data = compact::encode(&account);
+    check("account/greeting_compact.bin", &data);
+    assert_eq!(GreetingAccount::unpack(&data).unwrap(), account);
+}
+
+#[test]
+fn test_account_vectors() {
+    let mut data = vec![0; config::CONFIG_SIZE];
+    config().pack(&mut data).unwrap();
+    check("account/config.bin", &data);
+    assert_eq!(Config::unpack(&data).unwrap(), config());
+
+    let mut data = vec![0; receipt::RECEIPT_SIZE];
+    receipt().pack(&mut data);
+    check("account/receipt.bin", &data);
+    assert_eq!(GreetingReceipt::unpack(&data).unwrap(), receipt());
+
+    let mut data = vec![0; milestone::snapshot_size(&snapshot().label)];
+    snapshot().pack(&mut data).unwrap();
+    check("account/snapshot.bin", &data);
+    assert_eq!(GreetingSnapshot::unpack(&data).unwrap(), snapshot());
+
+    let mut data = vec![0; tags::TAG_COUNTERS_SIZE];
+    tag_counters().pack(&mut data).unwrap();
+    check("account/tag_counters.bin", &data);
+    assert_eq!(TagCounters::unpack(&data).unwrap(), tag_counters());
+}
+
+#[test]
+fn test_event_vectors() {
+    let events = [
+        (
+            "config_changed",
+            Event::config_changed(&Config::default(), &config()),
+        ),
+        (
+            "authority_changed",
+            Event::authority_changed([1; 32], [2; 32]),
+        ),
+    ];
+    for (name, event) in events.iter() {
+        let data = event.to_vec();
+        check(&format!("event/{}.bin", name), &data);
+        assert_eq!(&Event::from_slice(&data).unwrap(), event);
+    }
+}
+
+#[test]
+fn test_js_vectors() {
+    let js_dir = golden_dir().join("js");
+    if !js_dir.is_dir() {
+        println!("No JS vectors to decode, run `npm run golden`");
+        return;
+    }
+    let read = |name: &str| {
+        let path = js_dir.join(name);
+        fs::read(&path).unwrap_or_else(|err| {
+            panic!(
+                "{}: {}, add the value to src/client/golden.ts",
+                path.display(),
+                err
+            )
+        })
+    };
+
+    for (name, instruction) in instructions() {
+        let data = read(&format!("instruction/{}.bin", name));
+        assert_eq!(
+            HelloWorldInstruction::try_from_slice(&data).ok().as_ref(),
+            Some(&instruction),
+            "{}",
+            name
+        );
+        assert_eq!(data, instruction.try_to_vec().unwrap(), "{}", name);
+    }
+    let data = read("account/greeting.bin");
+    assert_eq!(GreetingAccount::unpack(&data).unwrap(), greeting_account());
+    let data = read("account/config.bin");
+    assert_eq!(data.len(), config::CONFIG_SIZE);
+    assert_eq!(Config::unpack(&data).unwrap(), config());
+    let data = read("account/receipt.bin");
+    assert_eq!(GreetingReceipt::unpack(&data).unwrap(), receipt());
+    let data = read("account/snapshot.bin");
+    assert_eq!(GreetingSnapshot::unpack(&data).unwrap(), snapshot());
+    let data = read("account/tag_counters.bin");
+    assert_eq!(TagCounters::unpack(&data).unwrap(), tag_counters());
+}
@@ -0,0 +1,216 @@
+//! Client builders for running the deployment through spl-governance
+// NOTE spl-governance needs a newer solana-program than the one pinned here, so this
+// encodes the one instruction it takes, InsertTransaction, by hand (GovernanceInstruction
+// tag 9 of spl-governance 3.x).
+//
+// A DAO owns the deployment once the config authority is its governance's native
+// treasury, a PDA only the governance program signs for: hand the config over with
+// SetAuthority. From then on an admin action (SetDecay, SetFee, ...) is built with the
+// treasury as its authority, as usual, and wrapped into a proposal transaction with
+// insert_transaction(). When the proposal passes, ExecuteTransaction runs it signed by
+// the treasury. The governance account itself signs too, so it can be the authority
+// instead; the treasury also holds SOL, so it can pay for InitConfig.
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+// The spl-governance program deployed by the Solana Program Library. Realms can also
+// run their own deployment of it, so every builder takes its id.
+solana_program::declare_id!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Seed of the PDAs of spl-governance's proposal transactions
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+/// Seed of a governance's native treasury, followed by the governance account's key
+pub const NATIVE_TREASURY_SEED: &[u8] = b"native-treasury";
+
+/// GovernanceInstruction tag of InsertTransaction
+const INSERT_TRANSACTION: u8 = 9;
+
+// NOTE solana-program's Pubkey implements a different borsh version's traits than the
+// one this crate uses, so keys are written as their bytes (the same encoding)
+
+/// spl-governance's AccountMetaData
+#[derive(BorshSerialize)]
+struct AccountMetaData {
+    pubkey: [u8; 32],
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// spl-governance's InstructionData, an instruction stored in a proposal
+#[derive(BorshSerialize)]
+struct InstructionData {
+    program_id: [u8; 32],
+    accounts: Vec<AccountMetaData>,
+    data: Vec<u8>,
+}
+
+/// InsertTransaction's fields
+#[derive(BorshSerialize)]
+struct InsertTransaction {
+    option_index: u8,
+    index: u16,
+    /// hold_up_time before spl-governance 3.1, ignored since
+    legacy: u32,
+    instructions: Vec<InstructionData>,
+}
+
+/// The accounts of a draft proposal to add transactions to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Proposal {
+    /// The spl-governance program the realm runs on, usually `governance::id()`
+    pub governance_program_id: Pubkey,
+    /// The governance account the proposal is under
+    pub governance: Pubkey,
+    /// The proposal account
+    pub proposal: Pubkey,
+    /// The token owner record of the proposal's owner
+    pub token_owner_record: Pubkey,
+    /// The proposal's owner or their delegate, who signs
+    pub governance_authority: Pubkey,
+}
+
+/// Address and bump seed of the native treasury of `governance`, the key to make the
+/// config authority
+pub fn native_treasury_address(
+    governance_program_id: &Pubkey,
+    governance: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[NATIVE_TREASURY_SEED, governance.as_ref()],
+        governance_program_id,
+    )
+}
+
+/// Address of the `index`th transaction of option `option_index` of `proposal`
+pub fn proposal_transaction_address(
+    governance_program_id: &Pubkey,
+    proposal: &Pubkey,
+    option_index: u8,
+    index: u16,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            GOVERNANCE_SEED,
+            proposal.as_ref(),
+            &[option_index],
+            &index.to_le_bytes(),
+        ],
+        governance_program_id,
+    )
+    .0
+}
+
+/// Build an InsertTransaction instruction adding `instructions` to the proposal, as
+/// its `index`th transaction for option `option_index` (0 for a yes/no proposal).
+/// `payer` pays for the proposal transaction account.
+pub fn insert_transaction(
+    proposal: &Proposal,
+    payer: &Pubkey,
+    option_index: u8,
+    index: u16,
+    instructions: &[Instruction],
+) -> Instruction {
+    let instructions = instructions
+        .iter()
+        .map(|instruction| InstructionData {
+            program_id: instruction.program_id.to_bytes(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| AccountMetaData {
+                    pubkey: meta.pubkey.to_bytes(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        })
+        .collect();
+    let mut data = vec![INSERT_TRANSACTION];
+    InsertTransaction {
+        option_index,
+        index,
+        legacy: 0,
+        instructions,
+    }
+    .serialize(&mut data)
+    .unwrap();
+    let transaction = proposal_transaction_address(
+        &proposal.governance_program_id,
+        &proposal.proposal,
+        option_index,
+        index,
+    );
+    Instruction::new_with_bytes(
+        proposal.governance_program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(proposal.governance, false),
+            AccountMeta::new(proposal.proposal, false),
+            AccountMeta::new_readonly(proposal.token_owner_record, false),
+            AccountMeta::new_readonly(proposal.governance_authority, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::instruction;
+
+    #[test]
+    fn test_insert_transaction() {
+        let proposal = Proposal {
+            governance_program_id: id(),
+            governance: Pubkey::new_unique(),
+            proposal: Pubkey::new_unique(),
+            token_owner_record: Pubkey::new_unique(),
+            governance_authority: Pubkey::new_unique(),
+        };
+        let program_id = Pubkey::new_unique();
+        let (treasury, _) =
+            native_treasury_address(&proposal.governance_program_id, &proposal.governance);
+        let set_decay = instruction::set_decay(&program_id, &treasury, 10);
+        let ix = insert_transaction(
+            &proposal,
+            &proposal.governance_authority,
+            0,
+            3,
+            std::slice::from_ref(&set_decay),
+        );
+
+        assert_eq!(ix.program_id, id());
+        assert_eq!(ix.accounts.len(), 8);
+        assert_eq!(
+            ix.accounts[4].pubkey,
+            proposal_transaction_address(&id(), &proposal.proposal, 0, 3)
+        );
+        // Tag, option, index, legacy hold-up time, then one instruction
+        assert_eq!(&ix.data[..8], &[9, 0, 3, 0, 0, 0, 0, 0]);
+        assert_eq!(&ix.data[8..12], &1u32.to_le_bytes());
+        assert_eq!(&ix.data[12..44], program_id.as_ref());
+        assert_eq!(&ix.data[44..48], &2u32.to_le_bytes());
+        // The treasury is stored as the signer the governance program signs for
+        assert_eq!(
+            &ix.data[82..116],
+            &[&treasury.to_bytes()[..], &[1, 0]].concat()[..]
+        );
+        assert_eq!(
+            &ix.data[ix.data.len() - set_decay.data.len() - 4..],
+            &[
+                &(set_decay.data.len() as u32).to_le_bytes()[..],
+                &set_decay.data
+            ]
+            .concat()[..]
+        );
+    }
+}
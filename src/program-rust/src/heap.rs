@@ -0,0 +1,163 @@
+//! The program's heap allocator, with the "custom-heap" feature
+// NOTE solana-program's default allocator bumps down from the end of the 32KB heap and
+// never frees: every Vec that grows is copied into a new block and the old one is lost.
+// The program allocates in a stack-like pattern. Greet decodes the instruction's
+// String, encodes the account layouts to try into Vecs that grow as they're written
+// and drops them right after, and formats log lines that are freed as soon as they're
+// logged. So this one bumps up, frees the block on top when it's dropped and grows
+// the block on top in place. A program as short-lived as an instruction needs nothing
+// more: other blocks are only freed when the instruction ends.
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    mem::size_of,
+    ptr::null_mut,
+};
+
+/// A bump allocator over `len` bytes from `start` that frees and grows the last block
+/// it handed out in place. The first word holds the address of the next free byte.
+pub struct StackAllocator {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl StackAllocator {
+    /// Where the next block goes, the first word of the heap when nothing's allocated
+    unsafe fn top(&self) -> *mut usize {
+        let top = self.start as *mut usize;
+        if *top == 0 {
+            *top = self.start + size_of::<usize>();
+        }
+        top
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+unsafe impl GlobalAlloc for StackAllocator {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let top = self.top();
+        let begin = match (*top).checked_add(layout.align() - 1) {
+            Some(begin) => begin & !(layout.align() - 1),
+            None => return null_mut(),
+        };
+        match begin.checked_add(layout.size()) {
+            Some(end) if end <= self.end() => {
+                *top = end;
+                begin as *mut u8
+            }
+            _ => null_mut(),
+        }
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let top = self.top();
+        if ptr as usize + layout.size() == *top {
+            *top = ptr as usize;
+        }
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let top = self.top();
+        if ptr as usize + layout.size() == *top {
+            return match (ptr as usize).checked_add(new_size) {
+                Some(end) if end <= self.end() => {
+                    *top = end;
+                    ptr
+                }
+                _ => null_mut(),
+            };
+        }
+        if new_size <= layout.size() {
+            return ptr;
+        }
+        let new = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if !new.is_null() {
+            new.copy_from_nonoverlapping(ptr, layout.size());
+        }
+        new
+    }
+}
+
+// NOTE Only in the program's own build: solana_program::entrypoint!() leaves its
+// allocator out when the "custom-heap" feature is on.
+#[cfg(all(
+    feature = "program",
+    feature = "custom-heap",
+    not(feature = "no-entrypoint"),
+    any(target_arch = "bpf", target_os = "solana")
+))]
+#[global_allocator]
+static ALLOCATOR: StackAllocator = StackAllocator {
+    start: crate::sdk::HEAP_START,
+    len: solana_program::entrypoint::HEAP_LENGTH,
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A heap of `words` usizes, aligned like the one the runtime maps in
+    fn heap(words: usize) -> (Vec<usize>, StackAllocator) {
+        let mut memory = vec![0usize; words];
+        let allocator = StackAllocator {
+            start: memory.as_mut_ptr() as usize,
+            len: words * size_of::<usize>(),
+        };
+        (memory, allocator)
+    }
+
+    #[test]
+    fn test_frees_and_grows_the_top_block() {
+        let (_memory, allocator) = heap(16);
+        let first = allocator.start + size_of::<usize>();
+        unsafe {
+            let a = allocator.alloc(Layout::from_size_align(3, 1).unwrap());
+            assert_eq!(a as usize, first);
+            // Aligned past the 3 bytes of `a`
+            let b = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(b as usize, first + 8);
+            b.write_bytes(7, 8);
+
+            // The top block grows in place
+            let b = allocator.realloc(b, Layout::from_size_align(8, 8).unwrap(), 24);
+            assert_eq!(b as usize, first + 8);
+            assert_eq!(*b.add(7), 7);
+
+            // Freeing it makes room again, freeing one below it doesn't
+            allocator.dealloc(b, Layout::from_size_align(24, 8).unwrap());
+            let c = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(c as usize, first + 8);
+            allocator.dealloc(a, Layout::from_size_align(3, 1).unwrap());
+            let d = allocator.alloc(Layout::from_size_align(1, 1).unwrap());
+            assert_eq!(d as usize, first + 16);
+
+            // A block below the top moves when it grows, keeping its bytes
+            c.write_bytes(9, 8);
+            let e = allocator.realloc(c, Layout::from_size_align(8, 8).unwrap(), 16);
+            assert_eq!(e as usize, first + 24);
+            assert_eq!(*e.add(7), 9);
+        }
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let (_memory, allocator) = heap(4);
+        unsafe {
+            let a = allocator.alloc(Layout::from_size_align(16, 8).unwrap());
+            assert!(!a.is_null());
+            assert!(allocator
+                .alloc(Layout::from_size_align(16, 8).unwrap())
+                .is_null());
+            assert!(allocator
+                .realloc(a, Layout::from_size_align(16, 8).unwrap(), 32)
+                .is_null());
+            let b = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert!(!b.is_null());
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Counting a greeting in the greeting account's data, without decoding it
+// NOTE Greet decodes the account into a GreetingAccount, with its message on the heap,
+// and encodes it back. Increment leaves the message alone, so it reads and writes the
+// fixed-width fields after it where they are: the counter, the calendar, the epoch of
+// the last update and the origin counters. That only works for the current schema in the Borsh layout;
+// older and compact accounts are migrated by a Greet first.
+// NOTE The fields are borrowed as fixed-size arrays with arrayref's macros, so reading
+// or writing one of them touches only its bytes: no Vec, no bounds check per byte, and
+// the compiler sees the sizes. See `npm run bench:fields` for what that saves over a
+// Greet, which decodes and re-encodes the whole account.
+use crate::{
+    calendar::{self, DayBitmap, DAY_BITMAP_SIZE},
+    compact, decay, migrations,
+    origin::Origin,
+    STATE_VERSION,
+};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+
+/// Bytes of the fields after the message: counter, calendar year, day bitmap, the
+/// epoch of the last update and the direct and CPI greeting counters
+pub const FIELDS_SIZE: usize = 8 + 2 + DAY_BITMAP_SIZE + 8 + 8 + 8;
+
+/// Where the fields after the message start in `data`, or None if it isn't an account
+/// on STATE_VERSION in the Borsh layout
+pub fn fields_offset(data: &[u8]) -> Option<usize> {
+    if compact::encoding(data) != compact::Encoding::Borsh
+        || migrations::stored_version(data).ok() != Some(STATE_VERSION)
+    {
+        return None;
+    }
+    // NOTE The header is whatever Borsh would skip, not the discriminator's length: data
+    // opening with STATE_MAGIC and the current version has the shorter header
+    let header = data.len() - migrations::state_body(data).len();
+    let mut len = [0; 4];
+    len.copy_from_slice(data.get(header..header + 4)?);
+    let offset = header + 4 + u32::from_le_bytes(len) as usize;
+    if data.len() < offset.checked_add(FIELDS_SIZE)? {
+        return None;
+    }
+    Some(offset)
+}
+
+/// The fields after the message, borrowed in place out of the account data
+pub struct FieldsMut<'a> {
+    pub counter: &'a mut [u8; 8],
+    pub calendar_year: &'a mut [u8; 2],
+    pub greeted_days: &'a mut [u8; DAY_BITMAP_SIZE],
+    pub last_update_epoch: &'a mut [u8; 8],
+    pub direct_greets: &'a mut [u8; 8],
+    pub cpi_greets: &'a mut [u8; 8],
+}
+
+/// The fields after the message in `data`, or None if fields_offset() can't find them
+pub fn fields_mut(data: &mut [u8]) -> Option<FieldsMut<'_>> {
+    let offset = fields_offset(data)?;
+    let fields = array_mut_ref![data, offset, FIELDS_SIZE];
+    let (counter, calendar_year, greeted_days, last_update_epoch, direct_greets, cpi_greets) =
+        mut_array_refs![fields, 8, 2, DAY_BITMAP_SIZE, 8, 8, 8];
+    Some(FieldsMut {
+        counter,
+        calendar_year,
+        greeted_days,
+        last_update_epoch,
+        direct_greets,
+        cpi_greets,
+    })
+}
+
+/// The counter stored in `data`, read without decoding the rest of the account
+pub fn counter(data: &[u8]) -> Option<u64> {
+    let offset = fields_offset(data)?;
+    let fields = array_ref![data, offset, FIELDS_SIZE];
+    let (counter, _, _, _, _, _) = array_refs![fields, 8, 2, DAY_BITMAP_SIZE, 8, 8, 8];
+    Some(u64::from_le_bytes(*counter))
+}
+
+/// The calendar stored in `data`, its year and greeted days, read without decoding
+/// the rest of the account
+pub fn calendar(data: &[u8]) -> Option<(u16, DayBitmap)> {
+    let offset = fields_offset(data)?;
+    let fields = array_ref![data, offset, FIELDS_SIZE];
+    let (_, calendar_year, greeted_days, _, _, _) =
+        array_refs![fields, 8, 2, DAY_BITMAP_SIZE, 8, 8, 8];
+    Some((u16::from_le_bytes(*calendar_year), DayBitmap(*greeted_days)))
+}
+
+/// Count a greeting of `weight` at `epoch` and `unix_timestamp` in the account data,
+/// decaying the counter under `half_life_epochs` first, like a Greet of the same message. A greeting
+/// of known `origin` is counted in its origin counter too. Returns the new counter, or
+/// None if the data isn't an account fields_offset() can read.
+pub fn increment(
+    data: &mut [u8],
+    weight: u64,
+    half_life_epochs: u64,
+    epoch: u64,
+    unix_timestamp: i64,
+    origin: Option<Origin>,
+) -> Option<u64> {
+    let fields = fields_mut(data)?;
+    let (value, last_update_epoch) = decay::touch(
+        u64::from_le_bytes(*fields.counter),
+        u64::from_le_bytes(*fields.last_update_epoch),
+        half_life_epochs,
+        epoch,
+    );
+    let value = value.saturating_add(weight);
+    let mut calendar_year = u16::from_le_bytes(*fields.calendar_year);
+    let mut greeted_days = DayBitmap(*fields.greeted_days);
+    calendar::mark_greeted(&mut calendar_year, &mut greeted_days, unix_timestamp);
+
+    *fields.counter = value.to_le_bytes();
+    *fields.calendar_year = calendar_year.to_le_bytes();
+    *fields.greeted_days = greeted_days.0;
+    *fields.last_update_epoch = last_update_epoch.to_le_bytes();
+    if let Some(origin) = origin {
+        let mut direct_greets = u64::from_le_bytes(*fields.direct_greets);
+        let mut cpi_greets = u64::from_le_bytes(*fields.cpi_greets);
+        origin.count(&mut direct_greets, &mut cpi_greets);
+        *fields.direct_greets = direct_greets.to_le_bytes();
+        *fields.cpi_greets = cpi_greets.to_le_bytes();
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::Rng;
+    use crate::GreetingAccount;
+
+    // 2024-03-01T12:00:00Z, and an epoch to decay to
+    const NOW: i64 = 1_709_294_400;
+    const EPOCH: u64 = 500;
+
+    #[test]
+    fn test_increment_matches_greet() {
+        let mut greeting = GreetingAccount {
+            txt: "Hello1234567".to_string(),
+            counter: 8,
+            calendar_year: 2023,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: EPOCH - 4,
+            direct_greets: 2,
+            cpi_greets: 0,
+        };
+        let mut data = migrations::encode_as(STATE_VERSION, &greeting).unwrap();
+        // Room to spare after the fields is left alone
+        data.extend_from_slice(&[7; 4]);
+        assert_eq!(
+            fields_offset(&data),
+            Some(migrations::GREETING_DISCRIMINATOR.len() + 4 + 12)
+        );
+
+        // Halved once, then counted
+        assert_eq!(counter(&data), Some(8));
+        assert_eq!(
+            increment(&mut data, 1, 4, EPOCH, NOW, Some(Origin::Cpi)),
+            Some(5)
+        );
+        assert_eq!(counter(&data), Some(5));
+        greeting.counter = 5;
+        greeting.last_update_epoch = EPOCH;
+        greeting.cpi_greets = 1;
+        calendar::mark_greeted(&mut greeting.calendar_year, &mut greeting.greeted_days, NOW);
+        assert_eq!(GreetingAccount::unpack(&data).unwrap(), greeting);
+        assert_eq!(data[data.len() - 4..], [7; 4]);
+
+        // Without the origin only the counter counts it
+        assert_eq!(increment(&mut data, 1, 4, EPOCH, NOW, None), Some(6));
+        greeting.counter = 6;
+        assert_eq!(GreetingAccount::unpack(&data).unwrap(), greeting);
+
+        // One field written on its own leaves the others be
+        *fields_mut(&mut data).unwrap().counter = 9u64.to_le_bytes();
+        greeting.counter = 9;
+        assert_eq!(GreetingAccount::unpack(&data).unwrap(), greeting);
+    }
+
+    #[test]
+    fn test_increment_needs_current_borsh_layout() {
+        let greeting = GreetingAccount {
+            txt: "Hello1234567".to_string(),
+            counter: 1,
+            calendar_year: 0,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: 0,
+            direct_greets: 0,
+            cpi_greets: 0,
+        };
+        let mut compact = compact::encode(&greeting);
+        assert_eq!(increment(&mut compact, 1, 0, EPOCH, NOW, None), None);
+        let mut old = migrations::encode_as(4, &greeting).unwrap();
+        assert_eq!(increment(&mut old, 1, 0, EPOCH, NOW, None), None);
+        let mut fresh = vec![0; 100];
+        assert_eq!(increment(&mut fresh, 1, 0, EPOCH, NOW, None), None);
+        assert_eq!(counter(&fresh), None);
+        // The message length runs past the data
+        let mut data = migrations::encode_as(STATE_VERSION, &greeting).unwrap();
+        let header = migrations::GREETING_DISCRIMINATOR.len();
+        data[header..header + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(increment(&mut data, 1, 0, EPOCH, NOW, None), None);
+        // STATE_MAGIC with the current version: the fields are where Borsh finds them
+        let body = borsh::BorshSerialize::try_to_vec(&greeting).unwrap();
+        let data = [&migrations::STATE_MAGIC[..], &[STATE_VERSION], &body].concat();
+        assert_eq!(fields_offset(&data), Some(migrations::HEADER_SIZE + 4 + 12));
+        assert_eq!(counter(&data), Some(1));
+    }
+
+    /// Account data to try both paths on: a greeting account in any layout, mangled
+    /// or not, or bytes that only open like one
+    fn arbitrary_data(rng: &mut Rng) -> Vec<u8> {
+        let mut greeted_days = DayBitmap::default();
+        for _ in 0..rng.below(4) {
+            greeted_days.set(rng.below(400) as u16);
+        }
+        let len = rng.below(24) as usize;
+        let greeting = GreetingAccount {
+            txt: (0..len)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect(),
+            counter: rng.next_u64() >> rng.below(64),
+            calendar_year: rng.below(3_000) as u16,
+            greeted_days,
+            last_update_epoch: rng.below(1_000),
+            direct_greets: rng.below(1_000),
+            cpi_greets: rng.below(1_000),
+        };
+        let mut data = match rng.below(10) {
+            0 => compact::encode(&greeting),
+            1 => {
+                migrations::encode_as(1 + rng.below(STATE_VERSION as u64) as u8, &greeting).unwrap()
+            }
+            2 => {
+                let len = rng.below(120) as usize;
+                rng.bytes(len)
+            }
+            _ => migrations::encode_as(STATE_VERSION, &greeting).unwrap(),
+        };
+        // Other headers in front of the same bytes
+        if rng.below(8) == 0 && data.len() >= 8 {
+            let version = rng.below(8) as u8;
+            match rng.below(3) {
+                0 => data[..8].copy_from_slice(&migrations::GREETING_DISCRIMINATOR),
+                1 => data[..4].copy_from_slice(&[b'H', b'W', b'S', version]),
+                _ => data[..4].copy_from_slice(&[b'H', b'W', b'C', version]),
+            }
+        }
+        // Flipped bytes (a message length, a non-UTF-8 message), truncated data, room
+        // to spare
+        for _ in 0..rng.below(3) {
+            if !data.is_empty() {
+                let at = rng.below(data.len() as u64) as usize;
+                data[at] ^= 1 << rng.below(8);
+            }
+        }
+        if rng.below(6) == 0 {
+            data.truncate(rng.below(data.len() as u64 + 1) as usize);
+        }
+        if rng.below(4) == 0 {
+            let extra = rng.below(16) as usize;
+            data.extend(rng.bytes(extra));
+        }
+        data
+    }
+
+    /// Whether `data` is current Borsh-layout state whose only fault is a message
+    /// that isn't UTF-8, which the in-place path never reads
+    fn only_the_message_is_invalid(data: &[u8], offset: usize) -> bool {
+        let header = data.len() - migrations::state_body(data).len();
+        std::str::from_utf8(&data[header + 4..offset]).is_err()
+    }
+
+    #[test]
+    fn test_in_place_matches_borsh_on_arbitrary_data() {
+        // NOTE Greet reads accounts with Borsh and Increment in place: on any data they
+        // must agree on the fields, or both refuse it. Data fields_offset() turns down
+        // for being another layout (older, compact) is Greet's alone and left out.
+        let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+        for case in 0..20_000 {
+            let data = arbitrary_data(&mut rng);
+            let current_borsh = compact::encoding(&data) == compact::Encoding::Borsh
+                && migrations::stored_version(&data).ok() == Some(STATE_VERSION);
+            let decoded = GreetingAccount::unpack(&data);
+            let offset = match fields_offset(&data) {
+                Some(offset) => offset,
+                None => {
+                    assert!(
+                        !current_borsh || decoded.is_err(),
+                        "case {}: only Borsh reads {:02x?}",
+                        case,
+                        data
+                    );
+                    continue;
+                }
+            };
+            let decoded = match decoded {
+                Ok(decoded) => decoded,
+                Err(_) => {
+                    assert!(
+                        only_the_message_is_invalid(&data, offset),
+                        "case {}: only the in-place path reads {:02x?}",
+                        case,
+                        data
+                    );
+                    continue;
+                }
+            };
+            assert_eq!(counter(&data), Some(decoded.counter), "case {}", case);
+            assert_eq!(
+                calendar(&data),
+                Some((decoded.calendar_year, decoded.greeted_days.clone())),
+                "case {}",
+                case
+            );
+
+            // Counting a greeting in place is decoding, counting and encoding
+            let (weight, half_life_epochs) = (rng.below(3), rng.below(4));
+            let epoch = decoded.last_update_epoch + rng.below(10);
+            let now = NOW + rng.below(3 * 365 * 86_400) as i64;
+            let origin = [None, Some(Origin::Direct), Some(Origin::Cpi)][rng.below(3) as usize];
+            let mut expected = decoded;
+            let (value, last_update_epoch) = decay::touch(
+                expected.counter,
+                expected.last_update_epoch,
+                half_life_epochs,
+                epoch,
+            );
+            expected.counter = value.saturating_add(weight);
+            expected.last_update_epoch = last_update_epoch;
+            calendar::mark_greeted(&mut expected.calendar_year, &mut expected.greeted_days, now);
+            if let Some(origin) = origin {
+                origin.count(&mut expected.direct_greets, &mut expected.cpi_greets);
+            }
+            let mut incremented = data.clone();
+            assert_eq!(
+                increment(
+                    &mut incremented,
+                    weight,
+                    half_life_epochs,
+                    epoch,
+                    now,
+                    origin
+                ),
+                Some(expected.counter),
+                "case {}",
+                case
+            );
+            assert_eq!(
+                GreetingAccount::unpack(&incremented).unwrap(),
+                expected,
+                "case {}",
+                case
+            );
+            // Nothing but the fields is written
+            assert_eq!(incremented[..offset], data[..offset], "case {}", case);
+            assert_eq!(
+                incremented[offset + FIELDS_SIZE..],
+                data[offset + FIELDS_SIZE..],
+                "case {}",
+                case
+            );
+        }
+    }
+}
@@ -0,0 +1,2704 @@
+//! Instructions the hello world program understands
+// NOTE The BorshSchema derive builds a private helper struct per enum variant whose
+// fields are never read, which newer rustc flags as dead code. Lint attributes on the
+// enum itself don't reach those generated items, so the allow has to be module-wide.
+#![allow(dead_code)]
+// NOTE The first byte of instruction_data is the enum variant index (Borsh encodes
+// enums as a u8 tag followed by the variant's fields), so the client must prepend
+// that tag before the serialized fields. See sayHello() in src/client/hello_world.ts.
+use crate::config::ConfigPatch;
+#[cfg(feature = "program")]
+use crate::{
+    challenge::challenge_address,
+    claim::{claim_vault_address, claimed_address},
+    compression::{self, log_authority_address},
+    config::config_address,
+    luck::{ticket_address, vault_address},
+    milestone::snapshot_address,
+    receipt::receipt_address,
+    session::session_address,
+    stake::{position_address, stake_authority_address},
+    tags::tag_counters_address,
+    target::greeting_address,
+    token,
+    wormhole::foreign_greeter_address,
+};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+// NOTE SetConfig's patch is boxed: unboxed it made the instruction more than twice the
+// size of any other variant, and every processor's frame held one. Borsh encodes a Box
+// as what's in it, so the bytes on the wire are the same.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub enum HelloWorldInstruction {
+    /// Save a message into the greeting account
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The greeting account, owned by this program
+    /// 1. `[]` The config account, see `config::config_address()`
+    /// 2. `[signer, writable]` The payer of the fee (only when the config sets one)
+    /// 3. `[]` The config's Pyth SOL/USD price account (only with a fee)
+    /// 4. `[writable]` The config's fee recipient (only with a fee)
+    /// 5. `[]` The system program (only with a fee)
+    /// 6. `[writable]` The config's greeting log (only when the config sets one)
+    /// 7. `[]` The log authority, see `compression::log_authority_address()` (with the
+    ///    greeting log)
+    /// 8. `[]` The spl-account-compression program (with the greeting log)
+    /// 9. `[]` The spl-noop program (with the greeting log)
+    /// 10. `[writable]` The greeting's receipt, see `receipt::receipt_address()` (only
+    ///     when the config sets greeting receipts; right after whichever fee and log
+    ///     accounts the config asks for)
+    /// 11. `[signer, writable]` The receipt's signer, which pays its rent (with the
+    ///     receipt)
+    /// 12. `[]` The system program (with the receipt)
+    /// 13. `[]` The greeter's token account of the config's balance mint (only when the
+    ///     config sets one, the greeting counts for its balance; right after whichever
+    ///     fee, log and receipt accounts the config asks for)
+    /// 14. `[signer]` The token account's owner (with the token account)
+    /// 15. `[]` The greeter's stake position, see `stake::position_address()` (only to
+    ///     weight the greeting by stake, when the config sets no balance mint; right
+    ///     after whichever fee, log and receipt accounts the config asks for)
+    /// 16. `[signer]` The staker, or a session key of the staker's (with the stake
+    ///     position)
+    /// 17. `[]` The session token, see `session::session_address()` (only when a
+    ///     session key signs for the staker)
+    /// 18. `[]` The instructions sysvar (optional, always last: counts the greeting as
+    ///     direct or CPI, see `origin`)
+    Greet {
+        /// Message to store
+        txt: String,
+    },
+
+    /// Report the deployed program's crate version and state-schema version
+    ///
+    /// solana-program 1.6 has no return data, so the versions are written to the
+    /// program log as `helloworld-version: <semver> schema: <u8>`. Simulate the
+    /// transaction and read the log line to branch on the deployed version.
+    ///
+    /// Accounts expected: none
+    Version,
+
+    /// Log a greeting account's data, so a migration leaves an on-chain record of the
+    /// state it moved. Written as `helloworld-export: <account> <hex data>`.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The greeting account, owned by this program
+    ExportState,
+
+    /// Copy the greeting held by an account of another deployment (e.g. this program
+    /// under its old program id) into a greeting account of this one. The counter
+    /// comes along, so only the config authority can import.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The greeting account to import into, owned by this program
+    /// 1. `[]` The greeting account to import from
+    /// 2. `[]` The config account
+    /// 3. `[signer]` The config authority
+    ImportState,
+
+    /// Create the config account, with the signer as its authority
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account, see `config::config_address()`
+    /// 1. `[signer, writable]` The authority, which pays for the account
+    /// 2. `[]` The system program
+    InitConfig {
+        /// Epochs it takes a greeting counter to halve, 0 for no decay
+        decay_half_life_epochs: u64,
+    },
+
+    /// Change how fast greeting counters decay. Applies to the decay greeting accounts
+    /// haven't had folded in yet too.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetDecay {
+        /// Epochs it takes a greeting counter to halve, 0 for no decay
+        half_life_epochs: u64,
+    },
+
+    /// Name the key allowed to Tick
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetCrankAuthority {
+        /// The crank authority's key, all zeros for none
+        crank_authority: [u8; 32],
+    },
+
+    /// Do the maintenance greeting accounts need without anyone greeting them: fold
+    /// the decay into each counter. Idempotent, an account already up to date for the
+    /// current epoch is left alone, so a keeper bot can retry or overlap freely.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[signer]` The crank authority
+    /// 2. `[writable]` The greeting accounts to maintain, any number of them
+    Tick,
+
+    /// Charge a fee for every Greet, set in USD and paid in lamports at the price of
+    /// a Pyth SOL/USD feed. A fee of 0 makes greetings free again.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetFee {
+        /// Fee per Greet in millionths of a dollar, 0 for none
+        fee_usd_micros: u64,
+        /// Slots the price may be behind the clock
+        max_price_age_slots: u64,
+        /// Widest confidence interval accepted, in basis points of the price
+        max_confidence_bps: u16,
+        /// Key of the Pyth SOL/USD price account
+        price_feed: [u8; 32],
+        /// Key the fees are paid to
+        fee_recipient: [u8; 32],
+    },
+
+    /// Enter the greeting account's latest greeting in the lucky-greeter draw, bound
+    /// to Switchboard randomness seeded in the previous slot. See luck.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The greeting account, owned by this program
+    /// 1. `[writable]` Its ticket, see `luck::ticket_address()`
+    /// 2. `[]` The Switchboard randomness account
+    /// 3. `[signer, writable]` The greeter, paid if the draw wins and paying for the
+    ///    ticket the first time
+    /// 4. `[]` The system program
+    CommitLuck,
+
+    /// Settle the greeting account's draw with the revealed randomness, paying the
+    /// tip vault out to the greeter if it wins
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The greeting account
+    /// 1. `[writable]` Its ticket
+    /// 2. `[]` The Switchboard randomness account committed to
+    /// 3. `[writable]` The tip vault, see `luck::vault_address()`
+    /// 4. `[writable]` The greeter who committed
+    /// 5. `[]` The system program
+    RevealLuck,
+
+    /// Hand the config to a new authority, such as an spl-governance native treasury
+    /// so a DAO runs the deployment. See governance.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetAuthority {
+        /// The new config authority's key
+        new_authority: [u8; 32],
+    },
+
+    /// Let greeters stake tokens of a mint, each `stake_unit` of them adding 1 to what
+    /// their greetings count for. An all-zero mint turns staking off for new deposits.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetStake {
+        /// Key of the mint to stake
+        stake_mint: [u8; 32],
+        /// Base units of stake that add 1 to the weight of a greeting
+        stake_unit: u64,
+    },
+
+    /// Lock tokens of the stake mint in the stake vault. See stake.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[writable]` The staker's stake position, see `stake::position_address()`
+    /// 2. `[signer, writable]` The staker, paying for the position the first time
+    /// 3. `[writable]` The token account to take the tokens from, owned by the staker
+    /// 4. `[writable]` The stake vault, a token account owned by the stake authority
+    /// 5. `[]` The SPL Token program
+    /// 6. `[]` The system program
+    Deposit {
+        /// Tokens to deposit, in the mint's base units
+        amount: u64,
+    },
+
+    /// Take deposited tokens back out of the stake vault
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The staker's stake position
+    /// 1. `[signer]` The staker
+    /// 2. `[writable]` The stake vault
+    /// 3. `[writable]` The token account to pay the tokens to
+    /// 4. `[]` The stake authority, see `stake::stake_authority_address()`
+    /// 5. `[]` The SPL Token program
+    WithdrawStake {
+        /// Tokens to withdraw, in the mint's base units
+        amount: u64,
+    },
+
+    /// Escrow lamports daring the challengee to get the greeting account's counter up by
+    /// `greet_count` before `deadline_slot`. See challenge.rs.
+    ///
+    /// Anyone can help: Greet takes no signer, so every greeting of the account counts
+    /// toward the target, whoever sends it, and a third party can meet (or overshoot)
+    /// the challenge for the challengee. Only the challengee can claim it.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The challenge, see `challenge::challenge_address()`
+    /// 1. `[signer, writable]` The challenger, paying the escrow and the challenge's rent
+    /// 2. `[]` The challengee
+    /// 3. `[]` The greeting account to greet
+    /// 4. `[]` The system program
+    CreateChallenge {
+        /// Greetings on top of the account's current counter
+        greet_count: u64,
+        /// First slot the challenge can no longer be claimed in
+        deadline_slot: u64,
+        /// Lamports to escrow
+        amount: u64,
+    },
+
+    /// Pay a met challenge out to the challengee, closing it
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The challenge
+    /// 1. `[signer, writable]` The challengee
+    /// 2. `[]` The challenge's greeting account
+    ClaimChallenge,
+
+    /// Refund an expired challenge to the challenger, closing it
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The challenge
+    /// 1. `[signer, writable]` The challenger
+    RefundChallenge,
+
+    /// Count a greeting sent from another chain as a Wormhole message. See wormhole.rs.
+    /// The emitter has to be registered with RegisterForeignGreeter, and the payload
+    /// has to be a greeting, see `wormhole::decode_greeting()`.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The posted VAA, owned by the Wormhole core bridge
+    /// 1. `[writable]` The emitter's foreign greeter, see
+    ///    `wormhole::foreign_greeter_address()`
+    ReceiveCrossChainGreet,
+
+    /// Start logging every greeting to a merkle tree. See compression.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    /// 2. `[writable]` The merkle tree, allocated for `max_depth` and `max_buffer_size`
+    ///    and owned by the spl-account-compression program
+    /// 3. `[]` The log authority, see `compression::log_authority_address()`
+    /// 4. `[]` The spl-account-compression program
+    /// 5. `[]` The spl-noop program
+    InitGreetingLog {
+        /// Depth of the tree, which holds up to 2^max_depth greetings
+        max_depth: u32,
+        /// Changes the tree keeps to let concurrent appends and proofs through
+        max_buffer_size: u32,
+    },
+
+    /// Let a session key greet for the authority until the session runs out, or
+    /// extend the session it already has. See session.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The session token, see `session::session_address()`
+    /// 1. `[signer, writable]` The authority, paying for the session token the first
+    ///    time and for the top-up
+    /// 2. `[signer, writable]` The session key
+    /// 3. `[]` The system program
+    CreateSession {
+        /// Seconds from now the session lasts, at most `session::MAX_SESSION_SECONDS`
+        duration_seconds: u64,
+        /// Lamports to transfer to the session key for its transaction fees
+        top_up_lamports: u64,
+    },
+
+    /// End a session before it runs out, closing its session token
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The session token
+    /// 1. `[signer, writable]` The authority, refunded the session token's rent
+    RevokeSession,
+
+    /// Count a greeting without changing the message, updating the account in place
+    /// without allocating. Only for deployments with no fee, greeting log, greeting
+    /// receipts or balance mint, and accounts already on the current schema in the
+    /// Borsh layout (Greet one first).
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The greeting account, owned by this program
+    /// 1. `[]` The config account, see `config::config_address()`
+    /// 2. `[]` The instructions sysvar (optional, counts the greeting as direct or
+    ///    CPI)
+    Increment,
+
+    /// Save the same message into each of the greeting accounts passed, counting a
+    /// greeting of each, for airdrop-style scripts. Takes at most
+    /// MAX_GREET_MANY_ACCOUNTS of them. An account that can't be greeted is skipped,
+    /// with a `GreetMany skipped <index> <pubkey>: <error>` log line, and the rest are
+    /// still greeted; the instruction ends with `GreetMany greeted <n> of <m>`. Only
+    /// for deployments with no fee, greeting log, greeting receipts or balance mint.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account, see `config::config_address()`
+    /// 1. `[writable]` The greeting accounts, owned by this program, 1 to
+    ///    MAX_GREET_MANY_ACCOUNTS of them
+    /// 2. `[]` The instructions sysvar (optional, after the greeting accounts: counts
+    ///    the greetings as direct or CPI)
+    GreetMany {
+        /// Message to store in every account
+        txt: String,
+    },
+
+    /// Save a message into the target's greeting account, paid for by someone else: a
+    /// backend can greet for its users. The payer signs and pays the fee and, the
+    /// first time, the rent of the target's greeting account, which it creates. The
+    /// target doesn't sign. Weighted 1, stake doesn't count. Refused while the config
+    /// sets greeting receipts or a balance mint: Greet leaves the one and weighs the
+    /// other.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The target's greeting account, see `target::greeting_address()`
+    /// 1. `[]` The config account, see `config::config_address()`
+    /// 2. `[signer, writable]` The payer
+    /// 3. `[]` The target, credited with the greeting
+    /// 4. `[]` The system program
+    /// 5. `[]` The config's Pyth SOL/USD price account (only when the config sets a
+    ///    fee)
+    /// 6. `[writable]` The config's fee recipient (only with a fee)
+    /// 7. `[writable]` The config's greeting log and the accounts after it, as for
+    ///    Greet (only when the config sets one)
+    /// 8. `[]` The instructions sysvar (optional, always last: counts the greeting as
+    ///    direct or CPI)
+    GreetFor {
+        /// Message to store, at most target::MAX_TARGET_MESSAGE_LEN bytes
+        txt: String,
+    },
+
+    /// Change any of the config's tunables at once: decay, crank authority, fee,
+    /// stake unit and the limits on greeting. Fields left None keep their value, and
+    /// the whole patch is refused with InvalidConfigValue if one is out of range.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The config account
+    /// 1. `[signer]` The config authority
+    SetConfig {
+        /// The fields to change
+        patch: Box<ConfigPatch>,
+    },
+
+    /// Start a counter snapshot, a merkle tree over the counters of greeting accounts.
+    /// See counter_snapshot.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[signer]` The config authority
+    /// 2. `[writable]` The snapshot, allocated with
+    ///    `counter_snapshot::account_size(depth)` bytes and owned by this program
+    InitCounterSnapshot {
+        /// Depth of the tree, which holds up to 2^depth greeting accounts, at most
+        /// counter_snapshot::MAX_SNAPSHOT_DEPTH
+        depth: u8,
+    },
+
+    /// Put the counters of greeting accounts into a counter snapshot and update its
+    /// root. Idempotent, like Tick: a leaf already holding the counter is left alone.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[signer]` The crank authority
+    /// 2. `[writable]` The counter snapshot
+    /// 3. `[]` The greeting accounts, one per index, on the current schema (an older
+    ///    one can't be migrated read-only, Greet it first)
+    SnapshotCounters {
+        /// The leaf of each greeting account: the one holding it already, or the next
+        /// free one
+        indices: Vec<u32>,
+    },
+
+    /// Claim a leaf of the config's claim root, paying its amount out of the claim
+    /// vault to the claimant. Each leaf can be claimed once. See claim.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[signer, writable]` The claimant, paying for the claimed bitmap if it's the
+    ///    first claim in its range
+    /// 2. `[writable]` The claimed bitmap of the leaf, see `claim::claimed_address()`
+    /// 3. `[writable]` The claim vault, see `claim::claim_vault_address()`
+    /// 4. `[]` The system program
+    Claim {
+        /// Index of the leaf in the list
+        index: u32,
+        /// Lamports the leaf pays out
+        amount: u64,
+        /// The siblings on the path from the leaf to the root, from the leaf up
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Record a greeting account's counter and the time under a label, a milestone
+    /// that stays readable after the counter moves on. Each recorder can use a label
+    /// once per account. See milestone.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account, for the counter's decay
+    /// 1. `[]` The greeting account, owned by this program
+    /// 2. `[signer, writable]` The recorder, paying for the snapshot
+    /// 3. `[writable]` The snapshot, see `milestone::snapshot_address()`
+    /// 4. `[]` The system program
+    Snapshot {
+        /// What the snapshot marks, at most `milestone::MAX_LABEL_LEN` bytes
+        label: String,
+    },
+
+    /// Count a greeting of a greeting account under a tag, one of up to
+    /// `tags::MAX_TAGS` per account. The first one creates the account's tag counters.
+    /// See tags.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[]` The greeting account, owned by this program
+    /// 2. `[signer, writable]` The payer, paying for the tag counters if they're new
+    /// 3. `[writable]` The tag counters, see `tags::tag_counters_address()`
+    /// 4. `[]` The system program
+    CountTag {
+        /// The tag's name padded with zeros, see `tags::tag()`
+        tag: [u8; 8],
+    },
+
+    /// Fail unless `slot_hash` is the hash of `slot` in the SlotHashes sysvar and `slot`
+    /// is at most `max_age_slots` behind the current one. Put it in a pre-signed Greet's
+    /// transaction so it can't be sent once it's stale. See recency.rs.
+    ///
+    /// Advisory only: it protects whoever signs the transaction it's in, with the bound
+    /// they chose, and is no rule of the program. Greet doesn't require it, and a
+    /// transaction built without it (or with `max_age_slots` of `u64::MAX`) greets as
+    /// usual. Once signed it can't be taken out or loosened, as that breaks the
+    /// signature.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The SlotHashes sysvar
+    CheckRecency {
+        /// A recent slot, at most `recency::MAX_ENTRIES` behind when it's sent
+        slot: u64,
+        /// The hash of `slot`
+        slot_hash: [u8; 32],
+        /// How far behind the current slot `slot` may be
+        max_age_slots: u64,
+    },
+
+    /// Let an emitter on another chain greet with ReceiveCrossChainGreet, creating its
+    /// foreign greeter. See wormhole.rs.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The config account
+    /// 1. `[signer, writable]` The config authority, paying for the foreign greeter
+    /// 2. `[writable]` The emitter's foreign greeter, see
+    ///    `wormhole::foreign_greeter_address()`
+    /// 3. `[]` The system program
+    RegisterForeignGreeter {
+        /// Wormhole chain id of the emitter
+        chain: u16,
+        /// Universal address of the emitter
+        sender: [u8; 32],
+    },
+}
+
+/// The most greeting accounts one GreetMany takes. About what fits in a transaction
+/// next to the payer, the config, the program and a short message.
+pub const MAX_GREET_MANY_ACCOUNTS: usize = 24;
+
+/// Each instruction's tag: the first byte of its data and its index in
+/// INSTRUCTION_ACCOUNTS
+pub mod tag {
+    pub const GREET: usize = 0;
+    pub const VERSION: usize = 1;
+    pub const EXPORT_STATE: usize = 2;
+    pub const IMPORT_STATE: usize = 3;
+    pub const INIT_CONFIG: usize = 4;
+    pub const SET_DECAY: usize = 5;
+    pub const SET_CRANK_AUTHORITY: usize = 6;
+    pub const TICK: usize = 7;
+    pub const SET_FEE: usize = 8;
+    pub const COMMIT_LUCK: usize = 9;
+    pub const REVEAL_LUCK: usize = 10;
+    pub const SET_AUTHORITY: usize = 11;
+    pub const SET_STAKE: usize = 12;
+    pub const DEPOSIT: usize = 13;
+    pub const WITHDRAW_STAKE: usize = 14;
+    pub const CREATE_CHALLENGE: usize = 15;
+    pub const CLAIM_CHALLENGE: usize = 16;
+    pub const REFUND_CHALLENGE: usize = 17;
+    pub const RECEIVE_CROSS_CHAIN_GREET: usize = 18;
+    pub const INIT_GREETING_LOG: usize = 19;
+    pub const CREATE_SESSION: usize = 20;
+    pub const REVOKE_SESSION: usize = 21;
+    pub const INCREMENT: usize = 22;
+    pub const GREET_MANY: usize = 23;
+    pub const GREET_FOR: usize = 24;
+    pub const SET_CONFIG: usize = 25;
+    pub const INIT_COUNTER_SNAPSHOT: usize = 26;
+    pub const SNAPSHOT_COUNTERS: usize = 27;
+    pub const CLAIM: usize = 28;
+    pub const SNAPSHOT: usize = 29;
+    pub const COUNT_TAG: usize = 30;
+    pub const CHECK_RECENCY: usize = 31;
+    pub const REGISTER_FOREIGN_GREETER: usize = 32;
+}
+
+/// One account an instruction expects, as listed in the generated IDL
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccountDescription {
+    pub name: &'static str,
+    pub writable: bool,
+    pub signer: bool,
+    /// Whether the instruction also runs without the account
+    pub optional: bool,
+    pub desc: &'static str,
+}
+
+/// The accounts each instruction expects, in order, indexed by the instruction's tag.
+/// NOTE Keep this in step with the "Accounts expected" docs above and the builders
+/// below; the IDL generator (`cargo run -p helloworld-schema --bin idl`) reads it.
+pub const INSTRUCTION_ACCOUNTS: &[&[AccountDescription]] = &[
+    // Greet
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "payer",
+            writable: true,
+            signer: true,
+            optional: true,
+            desc: "The payer of the fee, when the config sets one",
+        },
+        AccountDescription {
+            name: "priceFeed",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The config's Pyth SOL/USD price account, when there's a fee",
+        },
+        AccountDescription {
+            name: "feeRecipient",
+            writable: true,
+            signer: false,
+            optional: true,
+            desc: "The config's fee recipient, when there's a fee",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The system program, when there's a fee",
+        },
+        AccountDescription {
+            name: "greetingLog",
+            writable: true,
+            signer: false,
+            optional: true,
+            desc: "The config's greeting log, when it sets one",
+        },
+        AccountDescription {
+            name: "logAuthority",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The log authority, a PDA at [\"greeting-log\"], with the greeting log",
+        },
+        AccountDescription {
+            name: "compressionProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The spl-account-compression program, with the greeting log",
+        },
+        AccountDescription {
+            name: "noopProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The spl-noop program, with the greeting log",
+        },
+        AccountDescription {
+            name: "receipt",
+            writable: true,
+            signer: false,
+            optional: true,
+            desc: "The greeting's receipt, when the config sets greeting receipts",
+        },
+        AccountDescription {
+            name: "receiptSigner",
+            writable: true,
+            signer: true,
+            optional: true,
+            desc: "The receipt's signer, paying its rent, with the receipt",
+        },
+        AccountDescription {
+            name: "receiptSystemProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The system program, with the receipt",
+        },
+        AccountDescription {
+            name: "balanceTokenAccount",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The greeter's token account, when the config sets a balance mint",
+        },
+        AccountDescription {
+            name: "balanceOwner",
+            writable: false,
+            signer: true,
+            optional: true,
+            desc: "The token account's owner, with the token account",
+        },
+        AccountDescription {
+            name: "stakePosition",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The greeter's stake position, to weight the greeting by stake",
+        },
+        AccountDescription {
+            name: "staker",
+            writable: false,
+            signer: true,
+            optional: true,
+            desc: "The staker or a session key of theirs, with the stake position",
+        },
+        AccountDescription {
+            name: "sessionToken",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The session token, when a session key signs for the staker",
+        },
+        AccountDescription {
+            name: "instructions",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The instructions sysvar, last, to count the greeting as direct or CPI",
+        },
+    ],
+    // Version
+    &[],
+    // ExportState
+    &[AccountDescription {
+        name: "greetingAccount",
+        writable: false,
+        signer: false,
+        optional: false,
+        desc: "The greeting account, owned by this program",
+    }],
+    // ImportState
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeting account to import into, owned by this program",
+        },
+        AccountDescription {
+            name: "sourceAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account to import from",
+        },
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // InitConfig
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account, a PDA at seeds [\"config\"]",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The config authority, which pays for the account",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // SetDecay
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // SetCrankAuthority
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // Tick, followed by the greeting accounts to maintain
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "crankAuthority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The crank authority",
+        },
+    ],
+    // SetFee
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // CommitLuck
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "ticket",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeting account's ticket, a PDA at seeds [\"ticket\", greeting account]",
+        },
+        AccountDescription {
+            name: "randomness",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The Switchboard randomness account",
+        },
+        AccountDescription {
+            name: "greeter",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The greeter, paid if the draw wins",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // RevealLuck
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account",
+        },
+        AccountDescription {
+            name: "ticket",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeting account's ticket",
+        },
+        AccountDescription {
+            name: "randomness",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The Switchboard randomness account committed to",
+        },
+        AccountDescription {
+            name: "vault",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The tip vault, a PDA at seeds [\"vault\"]",
+        },
+        AccountDescription {
+            name: "greeter",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeter who committed",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // SetAuthority
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // SetStake
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // Deposit
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "stakePosition",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The staker's stake position, a PDA at seeds [\"stake\", staker]",
+        },
+        AccountDescription {
+            name: "staker",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The staker, paying for the position the first time",
+        },
+        AccountDescription {
+            name: "source",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The token account to take the tokens from, owned by the staker",
+        },
+        AccountDescription {
+            name: "vault",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The stake vault, a token account owned by the stake authority",
+        },
+        AccountDescription {
+            name: "tokenProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The SPL Token program",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // WithdrawStake
+    &[
+        AccountDescription {
+            name: "stakePosition",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The staker's stake position",
+        },
+        AccountDescription {
+            name: "staker",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The staker",
+        },
+        AccountDescription {
+            name: "vault",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The stake vault",
+        },
+        AccountDescription {
+            name: "destination",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The token account to pay the tokens to",
+        },
+        AccountDescription {
+            name: "stakeAuthority",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The stake authority, a PDA at seeds [\"stake-authority\"]",
+        },
+        AccountDescription {
+            name: "tokenProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The SPL Token program",
+        },
+    ],
+    // CreateChallenge
+    &[
+        AccountDescription {
+            name: "challenge",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The challenge, a PDA at seeds [\"challenge\", challenger, challengee]",
+        },
+        AccountDescription {
+            name: "challenger",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The challenger, escrowing the lamports",
+        },
+        AccountDescription {
+            name: "challengee",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The challengee",
+        },
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account to greet, owned by this program",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // ClaimChallenge
+    &[
+        AccountDescription {
+            name: "challenge",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The challenge",
+        },
+        AccountDescription {
+            name: "challengee",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The challengee, paid the escrow and the challenge's rent",
+        },
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The challenge's greeting account",
+        },
+    ],
+    // RefundChallenge
+    &[
+        AccountDescription {
+            name: "challenge",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The challenge",
+        },
+        AccountDescription {
+            name: "challenger",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The challenger, refunded the escrow and the challenge's rent",
+        },
+    ],
+    // ReceiveCrossChainGreet
+    &[
+        AccountDescription {
+            name: "postedVaa",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The posted VAA, owned by the Wormhole core bridge",
+        },
+        AccountDescription {
+            name: "foreignGreeter",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The emitter's foreign greeter, a PDA at [\"foreign-greeter\", chain, emitter]",
+        },
+    ],
+    // InitGreetingLog
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+        AccountDescription {
+            name: "merkleTree",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The merkle tree, allocated and owned by spl-account-compression",
+        },
+        AccountDescription {
+            name: "logAuthority",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The log authority, a PDA at [\"greeting-log\"]",
+        },
+        AccountDescription {
+            name: "compressionProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The spl-account-compression program",
+        },
+        AccountDescription {
+            name: "noopProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The spl-noop program",
+        },
+    ],
+    // CreateSession
+    &[
+        AccountDescription {
+            name: "sessionToken",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The session token, a PDA at [\"session\", authority, session key]",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The authority, paying for the session token and the top-up",
+        },
+        AccountDescription {
+            name: "sessionKey",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The session key",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // RevokeSession
+    &[
+        AccountDescription {
+            name: "sessionToken",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The session token",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The authority, refunded the session token's rent",
+        },
+    ],
+    // Increment
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "instructions",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The instructions sysvar, last, to count the greeting as direct or CPI",
+        },
+    ],
+    // GreetMany, followed by the rest of the greeting accounts
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "greetingAccount",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The first greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "instructions",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The instructions sysvar, last, to count the greeting as direct or CPI",
+        },
+    ],
+    // GreetFor
+    &[
+        AccountDescription {
+            name: "greetingAccount",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The target's greeting account, a PDA at [\"greeting\", target]",
+        },
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "payer",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The payer of the fee and the greeting account's rent",
+        },
+        AccountDescription {
+            name: "target",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The account credited with the greeting",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+        AccountDescription {
+            name: "priceFeed",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The config's Pyth SOL/USD price account, when there's a fee",
+        },
+        AccountDescription {
+            name: "feeRecipient",
+            writable: true,
+            signer: false,
+            optional: true,
+            desc: "The config's fee recipient, when there's a fee",
+        },
+        AccountDescription {
+            name: "greetingLog",
+            writable: true,
+            signer: false,
+            optional: true,
+            desc: "The config's greeting log, when it sets one",
+        },
+        AccountDescription {
+            name: "logAuthority",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The log authority, a PDA at [\"greeting-log\"], with the greeting log",
+        },
+        AccountDescription {
+            name: "compressionProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The spl-account-compression program, with the greeting log",
+        },
+        AccountDescription {
+            name: "noopProgram",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The spl-noop program, with the greeting log",
+        },
+        AccountDescription {
+            name: "instructions",
+            writable: false,
+            signer: false,
+            optional: true,
+            desc: "The instructions sysvar, last, to count the greeting as direct or CPI",
+        },
+    ],
+    // SetConfig
+    &[
+        AccountDescription {
+            name: "config",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+    ],
+    // InitCounterSnapshot
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The config authority",
+        },
+        AccountDescription {
+            name: "snapshot",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The counter snapshot, allocated and owned by this program",
+        },
+    ],
+    // SnapshotCounters, followed by the greeting accounts, one per index
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "crankAuthority",
+            writable: false,
+            signer: true,
+            optional: false,
+            desc: "The crank authority",
+        },
+        AccountDescription {
+            name: "snapshot",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The counter snapshot",
+        },
+    ],
+    // Claim
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "claimant",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The claimant, paying for a new claimed bitmap",
+        },
+        AccountDescription {
+            name: "claimedBitmap",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The claimed bitmap of the leaf",
+        },
+        AccountDescription {
+            name: "claimVault",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The claim vault",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // Snapshot
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "recorder",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The recorder, paying for the snapshot",
+        },
+        AccountDescription {
+            name: "snapshot",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The snapshot, a PDA at [\"snapshot\", greeting account, recorder, label]",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // CountTag
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "greetingAccount",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The greeting account, owned by this program",
+        },
+        AccountDescription {
+            name: "payer",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The payer, paying for new tag counters",
+        },
+        AccountDescription {
+            name: "tagCounters",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The tag counters, a PDA at [\"tags\", greeting account]",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+    // CheckRecency
+    &[AccountDescription {
+        name: "slotHashes",
+        writable: false,
+        signer: false,
+        optional: false,
+        desc: "The SlotHashes sysvar",
+    }],
+    // RegisterForeignGreeter
+    &[
+        AccountDescription {
+            name: "config",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The config account",
+        },
+        AccountDescription {
+            name: "authority",
+            writable: true,
+            signer: true,
+            optional: false,
+            desc: "The config authority, paying for the foreign greeter",
+        },
+        AccountDescription {
+            name: "foreignGreeter",
+            writable: true,
+            signer: false,
+            optional: false,
+            desc: "The emitter's foreign greeter, a PDA at [\"foreign-greeter\", chain, emitter]",
+        },
+        AccountDescription {
+            name: "systemProgram",
+            writable: false,
+            signer: false,
+            optional: false,
+            desc: "The system program",
+        },
+    ],
+];
+
+/// Build a `Greet` instruction, passing the config account so the counter decays
+#[cfg(feature = "program")]
+pub fn greet(program_id: &Pubkey, greeted_pubkey: &Pubkey, txt: &str) -> Instruction {
+    let data = HelloWorldInstruction::Greet {
+        txt: txt.to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*greeted_pubkey, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+    )
+}
+
+/// Build a `Greet` instruction for a deployment charging a fee, paid by `payer` to
+/// `fee_recipient` at the price in `price_feed`
+#[cfg(feature = "program")]
+pub fn greet_with_fee(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    payer: &Pubkey,
+    price_feed: &Pubkey,
+    fee_recipient: &Pubkey,
+    txt: &str,
+) -> Instruction {
+    let mut instruction = greet(program_id, greeted_pubkey, txt);
+    instruction.accounts.extend(vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*price_feed, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]);
+    instruction
+}
+
+/// Add the accounts of the greeting log `tree` to a `Greet` instruction, as a
+/// deployment logging greetings needs. Build `greet_with_fee()` first on a deployment
+/// with a fee.
+#[cfg(feature = "program")]
+pub fn add_greeting_log_accounts(
+    mut instruction: Instruction,
+    program_id: &Pubkey,
+    tree: &Pubkey,
+) -> Instruction {
+    let (log_authority, _) = log_authority_address(program_id);
+    instruction.accounts.extend(vec![
+        AccountMeta::new(*tree, false),
+        AccountMeta::new_readonly(log_authority, false),
+        AccountMeta::new_readonly(compression::account_compression::id(), false),
+        AccountMeta::new_readonly(compression::noop::id(), false),
+    ]);
+    instruction
+}
+
+/// Add the receipt accounts to a `Greet` instruction of `greeted_pubkey`, whose
+/// counter reads `counter` before the greeting, for a deployment with greeting
+/// receipts. `signer` signs for the receipt and pays its rent. Add the fee and
+/// greeting log accounts first when the deployment asks for them.
+#[cfg(feature = "program")]
+pub fn add_receipt_accounts(
+    mut instruction: Instruction,
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    counter: u64,
+    signer: &Pubkey,
+) -> Instruction {
+    let (receipt, _) = receipt_address(program_id, greeted_pubkey, counter);
+    instruction.accounts.extend(vec![
+        AccountMeta::new(receipt, false),
+        AccountMeta::new(*signer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]);
+    instruction
+}
+
+/// Add the instructions sysvar to a `Greet`, `Increment`, `GreetMany` or `GreetFor`
+/// instruction, so the program counts the greeting as direct or CPI (see `origin`).
+/// Add it last, after every other account.
+#[cfg(feature = "program")]
+pub fn add_instructions_sysvar(mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    instruction
+}
+
+/// Add `owner`'s token account of the config's balance mint to a `Greet` instruction,
+/// for a deployment weighing greetings by balance. Add the fee, greeting log and
+/// receipt accounts first when the deployment asks for them.
+#[cfg(feature = "program")]
+pub fn add_balance_accounts(
+    mut instruction: Instruction,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    instruction.accounts.extend(vec![
+        AccountMeta::new_readonly(*token_account, false),
+        AccountMeta::new_readonly(*owner, true),
+    ]);
+    instruction
+}
+
+/// Add the stake accounts of `staker` to a `Greet` instruction, so the greeting is
+/// weighted by their stake. Add the fee, greeting log and receipt accounts first when
+/// the deployment asks for them.
+#[cfg(feature = "program")]
+pub fn add_stake_accounts(
+    mut instruction: Instruction,
+    program_id: &Pubkey,
+    staker: &Pubkey,
+) -> Instruction {
+    let (position, _) = position_address(program_id, staker);
+    instruction.accounts.extend(vec![
+        AccountMeta::new_readonly(position, false),
+        AccountMeta::new_readonly(*staker, true),
+    ]);
+    instruction
+}
+
+/// Add the stake accounts of `staker` to a `Greet` instruction signed by
+/// `session_key` instead of the staker, under the session the staker approved with
+/// `create_session()`
+#[cfg(feature = "program")]
+pub fn add_session_stake_accounts(
+    mut instruction: Instruction,
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    session_key: &Pubkey,
+) -> Instruction {
+    let (position, _) = position_address(program_id, staker);
+    let (session_token, _) = session_address(program_id, staker, session_key);
+    instruction.accounts.extend(vec![
+        AccountMeta::new_readonly(position, false),
+        AccountMeta::new_readonly(*session_key, true),
+        AccountMeta::new_readonly(session_token, false),
+    ]);
+    instruction
+}
+
+/// Build a `Version` instruction
+#[cfg(feature = "program")]
+pub fn version(program_id: &Pubkey) -> Instruction {
+    let data = HelloWorldInstruction::Version.try_to_vec().unwrap();
+    Instruction::new_with_bytes(*program_id, &data, vec![])
+}
+
+/// Build an `ExportState` instruction
+#[cfg(feature = "program")]
+pub fn export_state(program_id: &Pubkey, greeted_pubkey: &Pubkey) -> Instruction {
+    let data = HelloWorldInstruction::ExportState.try_to_vec().unwrap();
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![AccountMeta::new_readonly(*greeted_pubkey, false)],
+    )
+}
+
+/// Build an `ImportState` instruction copying `source_pubkey`'s greeting into
+/// `greeted_pubkey`, signed by the config authority
+#[cfg(feature = "program")]
+pub fn import_state(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::ImportState.try_to_vec().unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*greeted_pubkey, false),
+            AccountMeta::new_readonly(*source_pubkey, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build an `InitConfig` instruction making `authority` the config authority
+#[cfg(feature = "program")]
+pub fn init_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    decay_half_life_epochs: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::InitConfig {
+        decay_half_life_epochs,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `SetDecay` instruction
+#[cfg(feature = "program")]
+pub fn set_decay(program_id: &Pubkey, authority: &Pubkey, half_life_epochs: u64) -> Instruction {
+    let data = HelloWorldInstruction::SetDecay { half_life_epochs }
+        .try_to_vec()
+        .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `SetConfig` instruction changing the fields set in `patch`
+#[cfg(feature = "program")]
+pub fn set_config(program_id: &Pubkey, authority: &Pubkey, patch: ConfigPatch) -> Instruction {
+    let data = HelloWorldInstruction::SetConfig {
+        patch: Box::new(patch),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `SetCrankAuthority` instruction
+#[cfg(feature = "program")]
+pub fn set_crank_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    crank_authority: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::SetCrankAuthority {
+        crank_authority: crank_authority.to_bytes(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `Tick` instruction maintaining `greeted_pubkeys`
+#[cfg(feature = "program")]
+pub fn tick(
+    program_id: &Pubkey,
+    crank_authority: &Pubkey,
+    greeted_pubkeys: &[Pubkey],
+) -> Instruction {
+    let data = HelloWorldInstruction::Tick.try_to_vec().unwrap();
+    let (config, _) = config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new_readonly(*crank_authority, true),
+    ];
+    accounts.extend(
+        greeted_pubkeys
+            .iter()
+            .map(|pubkey| AccountMeta::new(*pubkey, false)),
+    );
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Build a `SetFee` instruction
+#[cfg(feature = "program")]
+pub fn set_fee(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: &Pubkey,
+    fee_recipient: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::SetFee {
+        fee_usd_micros,
+        max_price_age_slots,
+        max_confidence_bps,
+        price_feed: price_feed.to_bytes(),
+        fee_recipient: fee_recipient.to_bytes(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `SetAuthority` instruction handing the config to `new_authority`
+#[cfg(feature = "program")]
+pub fn set_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::SetAuthority {
+        new_authority: new_authority.to_bytes(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `SetStake` instruction
+#[cfg(feature = "program")]
+pub fn set_stake(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    stake_mint: &Pubkey,
+    stake_unit: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::SetStake {
+        stake_mint: stake_mint.to_bytes(),
+        stake_unit,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a `Deposit` instruction staking `amount` tokens from `source`
+#[cfg(feature = "program")]
+pub fn deposit(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    source: &Pubkey,
+    vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::Deposit { amount }
+        .try_to_vec()
+        .unwrap();
+    let (config, _) = config_address(program_id);
+    let (position, _) = position_address(program_id, staker);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `WithdrawStake` instruction paying `amount` staked tokens to `destination`
+#[cfg(feature = "program")]
+pub fn withdraw_stake(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    vault: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::WithdrawStake { amount }
+        .try_to_vec()
+        .unwrap();
+    let (position, _) = position_address(program_id, staker);
+    let (stake_authority, _) = stake_authority_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(position, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(stake_authority, false),
+            AccountMeta::new_readonly(token::id(), false),
+        ],
+    )
+}
+
+/// Build a `CreateChallenge` instruction escrowing `amount` lamports for `challengee`
+/// to greet `greeted_pubkey` `greet_count` more times before `deadline_slot`
+#[cfg(feature = "program")]
+pub fn create_challenge(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    challengee: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    greet_count: u64,
+    deadline_slot: u64,
+    amount: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::CreateChallenge {
+        greet_count,
+        deadline_slot,
+        amount,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (challenge, _) = challenge_address(program_id, challenger, challengee);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(challenge, false),
+            AccountMeta::new(*challenger, true),
+            AccountMeta::new_readonly(*challengee, false),
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `ClaimChallenge` instruction paying `challenger`'s challenge out to
+/// `challengee`
+#[cfg(feature = "program")]
+pub fn claim_challenge(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    challengee: &Pubkey,
+    greeted_pubkey: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::ClaimChallenge.try_to_vec().unwrap();
+    let (challenge, _) = challenge_address(program_id, challenger, challengee);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(challenge, false),
+            AccountMeta::new(*challengee, true),
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+        ],
+    )
+}
+
+/// Build a `RefundChallenge` instruction giving `challenger` back their expired
+/// challenge to `challengee`
+#[cfg(feature = "program")]
+pub fn refund_challenge(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    challengee: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::RefundChallenge.try_to_vec().unwrap();
+    let (challenge, _) = challenge_address(program_id, challenger, challengee);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(challenge, false),
+            AccountMeta::new(*challenger, true),
+        ],
+    )
+}
+
+/// Build a `ReceiveCrossChainGreet` instruction counting the greeting in `posted_vaa`,
+/// a VAA whose emitter is `sender` on the Wormhole chain `chain`
+#[cfg(feature = "program")]
+pub fn receive_cross_chain_greet(
+    program_id: &Pubkey,
+    posted_vaa: &Pubkey,
+    chain: u16,
+    sender: &[u8; 32],
+) -> Instruction {
+    let data = HelloWorldInstruction::ReceiveCrossChainGreet
+        .try_to_vec()
+        .unwrap();
+    let (foreign_greeter, _) = foreign_greeter_address(program_id, chain, sender);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*posted_vaa, false),
+            AccountMeta::new(foreign_greeter, false),
+        ],
+    )
+}
+
+/// Build an `InitGreetingLog` instruction starting the greeting log in `tree`, an
+/// account already allocated with `compression::merkle_tree_account_size()` bytes and
+/// owned by the spl-account-compression program
+#[cfg(feature = "program")]
+pub fn init_greeting_log(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    tree: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    let data = HelloWorldInstruction::InitGreetingLog {
+        max_depth,
+        max_buffer_size,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    let (log_authority, _) = log_authority_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*tree, false),
+            AccountMeta::new_readonly(log_authority, false),
+            AccountMeta::new_readonly(compression::account_compression::id(), false),
+            AccountMeta::new_readonly(compression::noop::id(), false),
+        ],
+    )
+}
+
+/// Build a `CreateSession` instruction letting `session_key` greet for `authority`
+/// for `duration_seconds`, topping it up with `top_up_lamports`
+#[cfg(feature = "program")]
+pub fn create_session(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    session_key: &Pubkey,
+    duration_seconds: u64,
+    top_up_lamports: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::CreateSession {
+        duration_seconds,
+        top_up_lamports,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (session_token, _) = session_address(program_id, authority, session_key);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(session_token, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*session_key, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `RevokeSession` instruction ending `authority`'s session for `session_key`
+#[cfg(feature = "program")]
+pub fn revoke_session(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    session_key: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::RevokeSession.try_to_vec().unwrap();
+    let (session_token, _) = session_address(program_id, authority, session_key);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(session_token, false),
+            AccountMeta::new(*authority, true),
+        ],
+    )
+}
+
+/// Build an `Increment` instruction counting a greeting of `greeted_pubkey`
+#[cfg(feature = "program")]
+pub fn increment(program_id: &Pubkey, greeted_pubkey: &Pubkey) -> Instruction {
+    let data = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*greeted_pubkey, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+    )
+}
+
+/// Build a `GreetMany` instruction saving `txt` into every account of `greeted_pubkeys`
+#[cfg(feature = "program")]
+pub fn greet_many(program_id: &Pubkey, greeted_pubkeys: &[Pubkey], txt: &str) -> Instruction {
+    let data = HelloWorldInstruction::GreetMany {
+        txt: txt.to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    let mut accounts = vec![AccountMeta::new_readonly(config, false)];
+    accounts.extend(
+        greeted_pubkeys
+            .iter()
+            .map(|pubkey| AccountMeta::new(*pubkey, false)),
+    );
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Build a `GreetFor` instruction saving `txt` into `target`'s greeting account, paid
+/// for by `payer`
+#[cfg(feature = "program")]
+pub fn greet_for(program_id: &Pubkey, payer: &Pubkey, target: &Pubkey, txt: &str) -> Instruction {
+    let data = HelloWorldInstruction::GreetFor {
+        txt: txt.to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (greeting_account, _) = greeting_address(program_id, target);
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(greeting_account, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*target, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `GreetFor` instruction for a deployment charging a fee, paid by `payer` to
+/// `fee_recipient` at the price in `price_feed`
+#[cfg(feature = "program")]
+pub fn greet_for_with_fee(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    target: &Pubkey,
+    price_feed: &Pubkey,
+    fee_recipient: &Pubkey,
+    txt: &str,
+) -> Instruction {
+    let mut instruction = greet_for(program_id, payer, target, txt);
+    instruction.accounts.extend(vec![
+        AccountMeta::new_readonly(*price_feed, false),
+        AccountMeta::new(*fee_recipient, false),
+    ]);
+    instruction
+}
+
+/// Build a `CommitLuck` instruction entering `greeted_pubkey`'s latest greeting in
+/// the draw with the randomness account `randomness`
+#[cfg(feature = "program")]
+pub fn commit_luck(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    randomness: &Pubkey,
+    greeter: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::CommitLuck.try_to_vec().unwrap();
+    let (ticket, _) = ticket_address(program_id, greeted_pubkey);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+            AccountMeta::new(ticket, false),
+            AccountMeta::new_readonly(*randomness, false),
+            AccountMeta::new(*greeter, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `RevealLuck` instruction settling `greeted_pubkey`'s draw
+#[cfg(feature = "program")]
+pub fn reveal_luck(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    randomness: &Pubkey,
+    greeter: &Pubkey,
+) -> Instruction {
+    let data = HelloWorldInstruction::RevealLuck.try_to_vec().unwrap();
+    let (ticket, _) = ticket_address(program_id, greeted_pubkey);
+    let (vault, _) = vault_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+            AccountMeta::new(ticket, false),
+            AccountMeta::new_readonly(*randomness, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*greeter, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build an `InitCounterSnapshot` instruction starting a counter snapshot in
+/// `snapshot`, an account already allocated with `counter_snapshot::account_size()`
+/// bytes and owned by this program
+#[cfg(feature = "program")]
+pub fn init_counter_snapshot(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    snapshot: &Pubkey,
+    depth: u8,
+) -> Instruction {
+    let data = HelloWorldInstruction::InitCounterSnapshot { depth }
+        .try_to_vec()
+        .unwrap();
+    let (config, _) = config_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*snapshot, false),
+        ],
+    )
+}
+
+/// Build a `SnapshotCounters` instruction putting each of `greeted` (a greeting
+/// account and its leaf) into `snapshot`
+#[cfg(feature = "program")]
+pub fn snapshot_counters(
+    program_id: &Pubkey,
+    crank_authority: &Pubkey,
+    snapshot: &Pubkey,
+    greeted: &[(Pubkey, u32)],
+) -> Instruction {
+    let data = HelloWorldInstruction::SnapshotCounters {
+        indices: greeted.iter().map(|(_, index)| *index).collect(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new_readonly(*crank_authority, true),
+        AccountMeta::new(*snapshot, false),
+    ];
+    accounts.extend(
+        greeted
+            .iter()
+            .map(|(pubkey, _)| AccountMeta::new_readonly(*pubkey, false)),
+    );
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Build a `Claim` instruction paying `claimant` leaf `index` of the list with `root`
+#[cfg(feature = "program")]
+pub fn claim(
+    program_id: &Pubkey,
+    claimant: &Pubkey,
+    root: &[u8; 32],
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let data = HelloWorldInstruction::Claim {
+        index,
+        amount,
+        proof,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    let (claimed, _) = claimed_address(program_id, root, index);
+    let (vault, _) = claim_vault_address(program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(*claimant, true),
+            AccountMeta::new(claimed, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `Snapshot` instruction recording `greeted_pubkey`'s counter under `label`,
+/// paid for by `recorder`
+#[cfg(feature = "program")]
+pub fn snapshot(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    recorder: &Pubkey,
+    label: &str,
+) -> Instruction {
+    let data = HelloWorldInstruction::Snapshot {
+        label: label.to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let (config, _) = config_address(program_id);
+    let (snapshot, _) = snapshot_address(program_id, greeted_pubkey, recorder, label);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+            AccountMeta::new(*recorder, true),
+            AccountMeta::new(snapshot, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `CountTag` instruction counting a greeting of `greeted_pubkey` under `tag`,
+/// `payer` paying for the tag counters if they're new
+#[cfg(feature = "program")]
+pub fn count_tag(
+    program_id: &Pubkey,
+    greeted_pubkey: &Pubkey,
+    payer: &Pubkey,
+    tag: [u8; 8],
+) -> Instruction {
+    let data = HelloWorldInstruction::CountTag { tag }
+        .try_to_vec()
+        .unwrap();
+    let (config, _) = config_address(program_id);
+    let (tag_counters, _) = tag_counters_address(program_id, greeted_pubkey);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*greeted_pubkey, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(tag_counters, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Build a `CheckRecency` instruction failing unless `slot_hash` is the hash of
+/// `slot`, at most `max_age_slots` behind the current slot
+#[cfg(feature = "program")]
+pub fn check_recency(
+    program_id: &Pubkey,
+    slot: u64,
+    slot_hash: [u8; 32],
+    max_age_slots: u64,
+) -> Instruction {
+    let data = HelloWorldInstruction::CheckRecency {
+        slot,
+        slot_hash,
+        max_age_slots,
+    }
+    .try_to_vec()
+    .unwrap();
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![AccountMeta::new_readonly(sysvar::slot_hashes::id(), false)],
+    )
+}
+
+/// Build a `RegisterForeignGreeter` instruction letting the emitter `sender` on the
+/// Wormhole chain `chain` greet, signed by the config authority `authority`
+#[cfg(feature = "program")]
+pub fn register_foreign_greeter(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    chain: u16,
+    sender: &[u8; 32],
+) -> Instruction {
+    let data = HelloWorldInstruction::RegisterForeignGreeter {
+        chain,
+        sender: *sender,
+    }
+    .try_to_vec()
+    .unwrap();
+    let (foreign_greeter, _) = foreign_greeter_address(program_id, chain, sender);
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(foreign_greeter, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_accounts_match(instruction: &Instruction, tag: usize) {
+        let expected = INSTRUCTION_ACCOUNTS[tag];
+        let required = expected.iter().filter(|description| !description.optional);
+        assert!(instruction.accounts.len() >= required.count());
+        for (meta, description) in instruction.accounts.iter().zip(expected) {
+            assert_eq!(
+                meta.is_writable, description.writable,
+                "{}",
+                description.name
+            );
+            assert_eq!(meta.is_signer, description.signer, "{}", description.name);
+        }
+        assert_eq!(instruction.data[0] as usize, tag);
+    }
+
+    #[test]
+    fn test_builders_match_instruction_accounts() {
+        let program_id = Pubkey::new_unique();
+        assert_accounts_match(&greet(&program_id, &Pubkey::new_unique(), "hi"), tag::GREET);
+        let (payer, feed) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = greet_with_fee(
+            &program_id,
+            &Pubkey::new_unique(),
+            &payer,
+            &feed,
+            &payer,
+            "hi",
+        );
+        let ix = add_greeting_log_accounts(ix, &program_id, &Pubkey::new_unique());
+        let ix = add_receipt_accounts(ix, &program_id, &Pubkey::new_unique(), 7, &payer);
+        let ix = add_balance_accounts(ix, &Pubkey::new_unique(), &payer);
+        assert_accounts_match(
+            &add_stake_accounts(ix.clone(), &program_id, &payer),
+            tag::GREET,
+        );
+        let ix = add_session_stake_accounts(ix, &program_id, &payer, &Pubkey::new_unique());
+        let ix = add_instructions_sysvar(ix);
+        assert_eq!(ix.accounts.len(), INSTRUCTION_ACCOUNTS[tag::GREET].len());
+        assert_accounts_match(&ix, tag::GREET);
+        assert_accounts_match(&version(&program_id), tag::VERSION);
+        assert_accounts_match(
+            &export_state(&program_id, &Pubkey::new_unique()),
+            tag::EXPORT_STATE,
+        );
+        let (greeted, source) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let authority = Pubkey::new_unique();
+        assert_accounts_match(
+            &import_state(&program_id, &greeted, &source, &authority),
+            tag::IMPORT_STATE,
+        );
+        assert_accounts_match(&init_config(&program_id, &authority, 10), tag::INIT_CONFIG);
+        assert_accounts_match(&set_decay(&program_id, &authority, 0), tag::SET_DECAY);
+        assert_accounts_match(
+            &set_config(&program_id, &authority, ConfigPatch::default()),
+            tag::SET_CONFIG,
+        );
+        let crank = Pubkey::new_unique();
+        assert_accounts_match(
+            &set_crank_authority(&program_id, &authority, &crank),
+            tag::SET_CRANK_AUTHORITY,
+        );
+        let greeted = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = tick(&program_id, &crank, &greeted);
+        assert_accounts_match(&ix, tag::TICK);
+        // The greeting accounts follow the listed ones, writable
+        assert_eq!(ix.accounts.len(), 4);
+        assert!(ix.accounts[2..].iter().all(|meta| meta.is_writable));
+        let ix = set_fee(&program_id, &authority, 10_000, 25, 100, &feed, &payer);
+        assert_accounts_match(&ix, tag::SET_FEE);
+        let randomness = Pubkey::new_unique();
+        let ix = commit_luck(&program_id, &greeted[0], &randomness, &payer);
+        assert_accounts_match(&ix, tag::COMMIT_LUCK);
+        let ix = reveal_luck(&program_id, &greeted[0], &randomness, &payer);
+        assert_accounts_match(&ix, tag::REVEAL_LUCK);
+        let ix = set_authority(&program_id, &authority, &Pubkey::new_unique());
+        assert_accounts_match(&ix, tag::SET_AUTHORITY);
+        let mint = Pubkey::new_unique();
+        assert_accounts_match(
+            &set_stake(&program_id, &authority, &mint, 1_000),
+            tag::SET_STAKE,
+        );
+        let (source, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_accounts_match(
+            &deposit(&program_id, &payer, &source, &vault, 5),
+            tag::DEPOSIT,
+        );
+        let ix = withdraw_stake(&program_id, &payer, &vault, &source, 5);
+        assert_accounts_match(&ix, tag::WITHDRAW_STAKE);
+        let (challenger, challengee) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = create_challenge(
+            &program_id,
+            &challenger,
+            &challengee,
+            &greeted[0],
+            3,
+            1_000,
+            5,
+        );
+        assert_accounts_match(&ix, tag::CREATE_CHALLENGE);
+        let ix = claim_challenge(&program_id, &challenger, &challengee, &greeted[0]);
+        assert_accounts_match(&ix, tag::CLAIM_CHALLENGE);
+        assert_accounts_match(
+            &refund_challenge(&program_id, &challenger, &challengee),
+            tag::REFUND_CHALLENGE,
+        );
+        let ix = receive_cross_chain_greet(&program_id, &greeted[0], 2, &[7; 32]);
+        assert_accounts_match(&ix, tag::RECEIVE_CROSS_CHAIN_GREET);
+        let tree = Pubkey::new_unique();
+        assert_accounts_match(
+            &init_greeting_log(&program_id, &authority, &tree, 14, 64),
+            tag::INIT_GREETING_LOG,
+        );
+        let session_key = Pubkey::new_unique();
+        let ix = create_session(&program_id, &payer, &session_key, 3_600, 10_000_000);
+        assert_accounts_match(&ix, tag::CREATE_SESSION);
+        assert_accounts_match(
+            &revoke_session(&program_id, &payer, &session_key),
+            tag::REVOKE_SESSION,
+        );
+        let ix = add_instructions_sysvar(increment(&program_id, &greeted[0]));
+        assert_eq!(
+            ix.accounts.len(),
+            INSTRUCTION_ACCOUNTS[tag::INCREMENT].len()
+        );
+        assert_accounts_match(&ix, tag::INCREMENT);
+        let ix = add_instructions_sysvar(greet_many(&program_id, &greeted[..1], "hi"));
+        assert_accounts_match(&ix, tag::GREET_MANY);
+        let ix = greet_many(&program_id, &greeted, "hi");
+        assert_eq!(ix.accounts.len(), 1 + greeted.len());
+        assert!(ix.accounts[1..].iter().all(|meta| meta.is_writable));
+        let target = Pubkey::new_unique();
+        assert_accounts_match(
+            &greet_for(&program_id, &payer, &target, "hi"),
+            tag::GREET_FOR,
+        );
+        let ix = greet_for_with_fee(&program_id, &payer, &target, &feed, &payer, "hi");
+        let ix = add_greeting_log_accounts(ix, &program_id, &Pubkey::new_unique());
+        let ix = add_instructions_sysvar(ix);
+        assert_eq!(
+            ix.accounts.len(),
+            INSTRUCTION_ACCOUNTS[tag::GREET_FOR].len()
+        );
+        assert_accounts_match(&ix, tag::GREET_FOR);
+        let counters = Pubkey::new_unique();
+        assert_accounts_match(
+            &init_counter_snapshot(&program_id, &authority, &counters, 10),
+            tag::INIT_COUNTER_SNAPSHOT,
+        );
+        let ix = snapshot_counters(&program_id, &crank, &counters, &[(greeted[0], 0)]);
+        assert_accounts_match(&ix, tag::SNAPSHOT_COUNTERS);
+        assert_eq!(ix.accounts.len(), 4);
+        assert!(!ix.accounts[3].is_writable);
+        assert_accounts_match(
+            &claim(&program_id, &payer, &[1; 32], 0, 10, vec![[2; 32]]),
+            tag::CLAIM,
+        );
+        assert_accounts_match(
+            &snapshot(&program_id, &greeted[0], &payer, "v1"),
+            tag::SNAPSHOT,
+        );
+        assert_accounts_match(
+            &count_tag(&program_id, &greeted[0], &payer, *b"gm\0\0\0\0\0\0"),
+            tag::COUNT_TAG,
+        );
+        assert_accounts_match(
+            &check_recency(&program_id, 7, [7; 32], 150),
+            tag::CHECK_RECENCY,
+        );
+        assert_accounts_match(
+            &register_foreign_greeter(&program_id, &authority, 2, &[7; 32]),
+            tag::REGISTER_FOREIGN_GREETER,
+        );
+        // A session key signs in the staker's place, followed by the session token
+        let ix = greet(&program_id, &greeted[0], "hi");
+        let ix = add_session_stake_accounts(ix, &program_id, &payer, &session_key);
+        assert_eq!(ix.accounts[3].pubkey, session_key);
+        assert!(ix.accounts[3].is_signer);
+        assert_eq!(
+            ix.accounts[4].pubkey,
+            session_address(&program_id, &payer, &session_key).0
+        );
+    }
+}
@@ -27,37 +27,277 @@ to be marked/flagged as writable BEFORE you can actually make modifications to t
 If the account is read-only, then you can access it but only for adding lamports instead of
 deducting lamports (everyone doesn't mind receiving money).
 */
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+// NOTE Everything that touches solana-program sits behind the default "program"
+// feature. Building with default-features = false leaves just the Borsh types (state,
+// instructions, errors), which compile for wasm32-unknown-unknown. See src/wasm.
+#[cfg(feature = "program")]
 use solana_program::{
     log::sol_log_compute_units,
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
+    clock::Clock,
     entrypoint::ProgramResult,
+    epoch_schedule::EpochSchedule,
     msg,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::{self, Sysvar},
 };
 
+pub mod accounts;
+#[cfg(feature = "program")]
+pub mod bonus;
+pub mod calendar;
+pub mod challenge;
+#[cfg(feature = "program")]
+pub mod claim;
+pub mod compression;
+pub mod config;
+#[cfg(feature = "program")]
+pub mod counter_snapshot;
+#[cfg(feature = "program")]
+pub mod cpi;
+pub mod compact;
+pub mod decay;
+pub mod error;
+pub mod escrow;
+pub mod event;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod fixtures;
+#[cfg(test)]
+mod golden;
+#[cfg(feature = "program")]
+pub mod governance;
+pub mod heap;
+pub mod increment;
+pub mod instruction;
+#[cfg(feature = "pinocchio")]
+pub mod lite;
+pub mod luck;
+pub mod migrations;
+pub mod milestone;
+pub mod origin;
+pub mod profiling;
+pub mod pyth;
+pub mod receipt;
+pub mod recency;
+pub mod reentrancy;
+#[cfg(feature = "program")]
+pub mod sdk;
+pub mod session;
+pub mod stake;
+pub mod tags;
+#[cfg(feature = "program")]
+pub mod state_hash;
+pub mod switchboard;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod target;
+pub mod token;
+pub mod transfer_hook;
+pub mod wormhole;
+#[cfg(feature = "program")]
+use accounts::{admin, greet_for, greet_many, snapshot, Accounts};
+#[cfg(feature = "program")]
+use error::HelloWorldError;
+#[cfg(feature = "program")]
+use event::Event;
+#[cfg(feature = "program")]
+use instruction::{tag, HelloWorldInstruction, INSTRUCTION_ACCOUNTS};
+#[cfg(feature = "program")]
+use origin::Origin;
+
+/// `msg!` for the program's verbose logging, compiled out without the "log" feature
+// NOTE Every msg! costs compute units (more with formatting) and its format string
+// takes room in the binary. A build for mainnet can leave them out with
+// `--no-default-features --features program,custom-heap,custom-panic`. The lines
+// clients parse (the helloworld-version and helloworld-export lines, events, state
+// hashes, "Error: <name> (<code>)" and "Was sent message ...!") stay msg! so they're
+// there either way.
+#[cfg(all(feature = "program", feature = "log"))]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        msg!($($arg)*)
+    };
+}
+// NOTE Without the feature the arguments are still type checked (and count as used)
+// inside a closure that's never called, which the compiler drops.
+#[cfg(all(feature = "program", not(feature = "log")))]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        let _ = || msg!($($arg)*);
+    };
+}
+
+/// Mark the end of a processor stage with the compute units left, with the "profiling"
+/// feature. profiling::breakdown() turns the marks in the logs into a cost per stage.
+#[cfg(all(feature = "program", feature = "profiling"))]
+macro_rules! profile {
+    ($stage:literal) => {
+        msg!(concat!("profile: ", $stage));
+        sol_log_compute_units();
+    };
+}
+#[cfg(all(feature = "program", not(feature = "profiling")))]
+macro_rules! profile {
+    ($stage:literal) => {};
+}
+
+// The address this program is deployed at. Tooling (solana-verify, the verify helper,
+// other programs using this crate with no-entrypoint) reads it from here.
+// NOTE After your first deploy, replace this with the output of
+// `solana address -k dist/program/helloworld-keypair.json`.
+#[cfg(feature = "program")]
+solana_program::declare_id!("8PNFh5ugkVBAFT1Si8XsjXs4FgP3eDFuZSh8uTW6sDjc");
+
 // NOTE Rust has TRAITS that you can inherit from. So, below, the
 // "#[...]" annotation syntax is shorthand for inheriting functionality
 // from these other types (BorshSerialize, Debug, etc), without having to
 // manually write the code yourself. This means our new struct type GreetingAccount,
 // will have access to any prexisting methods from these other types.
 /// Define the type of state stored in accounts
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
 pub struct GreetingAccount {
+    /// message string
+    pub txt: String,
+    /// number of greetings
+    // NOTE A u32 could overflow under a long load test, a u64 never will
+    pub counter: u64,
+    /// year the greeted_days bitmap covers, 0 until the first greeting
+    pub calendar_year: u16,
+    /// days of calendar_year the account was greeted on, see calendar.rs
+    pub greeted_days: calendar::DayBitmap,
+    /// epoch the counter was last decayed to, 0 until stamped, see decay.rs
+    pub last_update_epoch: u64,
+    /// greetings sent in the transaction's own instructions, see origin.rs
+    pub direct_greets: u64,
+    /// greetings sent by other programs through CPI, see origin.rs
+    pub cpi_greets: u64,
+}
+
+/// Greeting account state at schema version 4: the message, the counter, the calendar
+/// and the epoch stamp
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct GreetingAccountV4 {
+    /// message string
+    pub txt: String,
+    /// number of greetings
+    pub counter: u64,
+    /// year the greeted_days bitmap covers, 0 until the first greeting
+    pub calendar_year: u16,
+    /// days of calendar_year the account was greeted on
+    pub greeted_days: calendar::DayBitmap,
+    /// epoch the counter was last decayed to, 0 until stamped
+    pub last_update_epoch: u64,
+}
+
+/// Greeting account state at schema version 3: the message, the counter and the
+/// calendar
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct GreetingAccountV3 {
+    /// message string
+    pub txt: String,
+    /// number of greetings
+    pub counter: u64,
+    /// year the greeted_days bitmap covers, 0 until the first greeting
+    pub calendar_year: u16,
+    /// days of calendar_year the account was greeted on
+    pub greeted_days: calendar::DayBitmap,
+}
+
+/// Greeting account state at schema version 2: the message and the counter
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct GreetingAccountV2 {
+    /// message string
+    pub txt: String,
     /// number of greetings
-    // pub counter: u32,
+    pub counter: u64,
+}
+
+/// Greeting account state up to schema version 1: just the message
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, PartialEq)]
+pub struct GreetingAccountV1 {
     /// message string
-    pub txt: String
+    pub txt: String,
+}
+
+impl GreetingAccount {
+    /// Decode a greeting account's data whatever its schema version and encoding.
+    /// Only the front of the data is read, like the program leaves it.
+    pub fn unpack(data: &[u8]) -> std::io::Result<Self> {
+        if compact::encoding(data) == compact::Encoding::Compact {
+            return compact::decode(data);
+        }
+        let state = migrations::current_state(data)?;
+        Self::deserialize(&mut &state[..])
+    }
+
+    /// The counter decayed to `epoch` under a half-life of `half_life_epochs`, the one
+    /// the program will build on at the next Greet. See decay.rs.
+    pub fn effective_counter(&self, half_life_epochs: u64, epoch: u64) -> u64 {
+        decay::decay(self.counter, self.last_update_epoch, half_life_epochs, epoch).0
+    }
+}
+
+/// Crate version of the deployed program, reported by the `Version` instruction
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the GreetingAccount data layout, reported by the `Version` instruction.
+/// Bump this whenever the shape of the stored state changes, and register the
+/// upgrade from the previous layout in `migrations::MIGRATIONS`.
+pub const STATE_VERSION: u8 = 6;
+
+// Contact info for security researchers, readable straight from the deployed binary
+// with `query-security-txt <program.so>`. Only compiled in with `--features security-txt`
+// and never when this crate is pulled in as a library by another program.
+#[cfg(all(
+    feature = "program",
+    feature = "security-txt",
+    not(feature = "no-entrypoint")
+))]
+solana_security_txt::security_txt! {
+    name: "Solana Rust Hello World",
+    project_url: "https://github.com/gaylonalfano/solana-rust-helloworld",
+    contacts: "link:https://github.com/gaylonalfano/solana-rust-helloworld/security/advisories/new",
+    policy: "https://github.com/gaylonalfano/solana-rust-helloworld/security/policy",
+    source_code: "https://github.com/gaylonalfano/solana-rust-helloworld"
 }
 
 // Declare and export the program's entrypoint
 // NOTE This entrypoint!() feature allows this particular smart contract (program)
 // to be entered into, and therefore controlled by, another program.
-entrypoint!(process_instruction);
+// NOTE Left out with no-entrypoint, so a program linking this crate (src/transfer-hook)
+// doesn't end up with two entrypoints.
+#[cfg(all(feature = "program", not(feature = "no-entrypoint")))]
+solana_program::entrypoint!(process_instruction);
+
+// With the custom-panic feature (on by default) a panic logs where it happened and
+// nothing else
+// NOTE solana-program's default handler logs the panic message through core::fmt,
+// which brings the whole formatting machinery into the binary for a path that should
+// never run. Code on chain also avoids unwrap() and expect() on Results for the same
+// reason: they format the error into the message. See tests/size.rs for the budget the
+// binary is held to.
+#[cfg(all(
+    feature = "program",
+    feature = "custom-panic",
+    not(feature = "no-entrypoint"),
+    any(target_arch = "bpf", target_os = "solana")
+))]
+#[no_mangle]
+fn custom_panic(info: &core::panic::PanicInfo<'_>) {
+    msg!("Program panicked");
+    if let Some(location) = info.location() {
+        msg!(location.file());
+        solana_program::log::sol_log_64(location.line() as u64, location.column() as u64, 0, 0, 0);
+    }
+}
 
 // Program entrypoint's implementation
+#[cfg(feature = "program")]
 pub fn process_instruction(
     // NOTE On the client-side we'll have a mirror of these params in JS as well
     // Remember that the program will always exist inside an account! It's not standalone.
@@ -67,65 +307,369 @@ pub fn process_instruction(
     instruction_data: &[u8], // NOTE _instruction_data is convention when not used
 ) -> ProgramResult {
     // Can log and view using command: solana logs -u localhost
-    // NOTE Apparently println!() isn't as performant as msg!()
+    // NOTE Apparently println!() isn't as performant as log!()
     // NOTE A "!" indicates a macro which is a shorthand that points to a
     // multiline set of code (so it will log all the lines below it)
-    msg!("Hello World Rust program entrypoint");
+    log!("Hello World Rust program entrypoint");
 
-    // Iterating accounts is safer then indexing
-    // NOTE You make array iterable using .iter() so you can call next()
-    // NOTE &mut means that we're getting a reference to a MUTABLE version of the
-    // accounts.iter() array, and I guess storing the reference inside accounts_iter.
-    let accounts_iter = &mut accounts.iter();
+    log!("Start instruction decode");
+    // The first byte picks which instruction to run, the rest are its fields.
+    // NOTE map_err() allows us to load a message if the decoding fails
+    let instruction = HelloWorldInstruction::try_from_slice(instruction_data).map_err(|err| {
+        log!("Decoding instruction failed, {:?}", err);
+        ProgramError::InvalidInstructionData
+    })?;
 
-    // Get the account to say hello to
-    // NOTE There is a built-in helper function instead of manually calling next(),next(),...
-    let account = next_account_info(accounts_iter)?;
+    let result = match instruction {
+        HelloWorldInstruction::Greet { txt } => process_greet(program_id, accounts, txt),
+        HelloWorldInstruction::Version => process_version(),
+        HelloWorldInstruction::ExportState => process_export_state(program_id, accounts),
+        HelloWorldInstruction::ImportState => process_import_state(program_id, accounts),
+        HelloWorldInstruction::InitConfig {
+            decay_half_life_epochs,
+        } => process_init_config(program_id, accounts, decay_half_life_epochs),
+        HelloWorldInstruction::SetDecay { half_life_epochs } => {
+            process_set_decay(program_id, accounts, half_life_epochs)
+        }
+        HelloWorldInstruction::SetCrankAuthority { crank_authority } => {
+            process_set_crank_authority(program_id, accounts, crank_authority)
+        }
+        HelloWorldInstruction::Tick => process_tick(program_id, accounts),
+        HelloWorldInstruction::SetFee {
+            fee_usd_micros,
+            max_price_age_slots,
+            max_confidence_bps,
+            price_feed,
+            fee_recipient,
+        } => process_set_fee(
+            program_id,
+            accounts,
+            fee_usd_micros,
+            max_price_age_slots,
+            max_confidence_bps,
+            price_feed,
+            fee_recipient,
+        ),
+        HelloWorldInstruction::CommitLuck => process_commit_luck(program_id, accounts),
+        HelloWorldInstruction::RevealLuck => process_reveal_luck(program_id, accounts),
+        HelloWorldInstruction::SetAuthority { new_authority } => {
+            process_set_authority(program_id, accounts, new_authority)
+        }
+        HelloWorldInstruction::SetStake {
+            stake_mint,
+            stake_unit,
+        } => process_set_stake(program_id, accounts, stake_mint, stake_unit),
+        HelloWorldInstruction::Deposit { amount } => process_deposit(program_id, accounts, amount),
+        HelloWorldInstruction::WithdrawStake { amount } => {
+            process_withdraw_stake(program_id, accounts, amount)
+        }
+        HelloWorldInstruction::CreateChallenge {
+            greet_count,
+            deadline_slot,
+            amount,
+        } => process_create_challenge(program_id, accounts, greet_count, deadline_slot, amount),
+        HelloWorldInstruction::ClaimChallenge => process_claim_challenge(program_id, accounts),
+        HelloWorldInstruction::RefundChallenge => process_refund_challenge(program_id, accounts),
+        HelloWorldInstruction::ReceiveCrossChainGreet => {
+            process_receive_cross_chain_greet(program_id, accounts)
+        }
+        HelloWorldInstruction::InitGreetingLog {
+            max_depth,
+            max_buffer_size,
+        } => process_init_greeting_log(program_id, accounts, max_depth, max_buffer_size),
+        HelloWorldInstruction::CreateSession {
+            duration_seconds,
+            top_up_lamports,
+        } => process_create_session(program_id, accounts, duration_seconds, top_up_lamports),
+        HelloWorldInstruction::RevokeSession => process_revoke_session(program_id, accounts),
+        HelloWorldInstruction::Increment => process_increment(program_id, accounts),
+        HelloWorldInstruction::GreetMany { txt } => process_greet_many(program_id, accounts, txt),
+        HelloWorldInstruction::GreetFor { txt } => process_greet_for(program_id, accounts, txt),
+        HelloWorldInstruction::SetConfig { patch } => {
+            process_set_config(program_id, accounts, &patch)
+        }
+        HelloWorldInstruction::InitCounterSnapshot { depth } => {
+            process_init_counter_snapshot(program_id, accounts, depth)
+        }
+        HelloWorldInstruction::SnapshotCounters { indices } => {
+            process_snapshot_counters(program_id, accounts, indices)
+        }
+        HelloWorldInstruction::Claim {
+            index,
+            amount,
+            proof,
+        } => process_claim(program_id, accounts, index, amount, proof),
+        HelloWorldInstruction::Snapshot { label } => process_snapshot(program_id, accounts, label),
+        HelloWorldInstruction::CountTag { tag } => process_count_tag(program_id, accounts, tag),
+        HelloWorldInstruction::CheckRecency {
+            slot,
+            slot_hash,
+            max_age_slots,
+        } => process_check_recency(program_id, accounts, slot, slot_hash, max_age_slots),
+        HelloWorldInstruction::RegisterForeignGreeter { chain, sender } => {
+            process_register_foreign_greeter(program_id, accounts, chain, sender)
+        }
+    };
+    // One line for every error of ours, whichever processor returned it
+    if let Err(ProgramError::Custom(code)) = result {
+        if let Some(error) = HelloWorldError::from_code(code) {
+            error.log();
+        }
+    }
+    result
+}
 
-    // The account must be owned by the program in order to modify its data
-    // NOTE The account is the account that we want to do something to. So,
-    // we're confirming that its owner is the program_id. Otherwise, throw error.
-    // NOTE If this check fails at any point, then the entire transaction this
-    // occurs within/lives within, will be exited and unroll itself.
-    // NOTE "owner" is NOT the human owner. It is the programmatic controller of the account!
-    if account.owner != program_id {
-        // Log the error message
-        msg!("Greeted account does not have the correct program id");
-        // Return the specific Error Type
-        return Err(ProgramError::IncorrectProgramId);
+/// An instruction's accounts, checked in one pass before its processor does anything
+/// else: each processor's business logic gets `accounts` already checked (roles, PDAs
+/// and their bumps, the config loaded) and the `remaining` accounts after them
+// NOTE The checks are the same helpers the processors called themselves, in the same
+// order, so each failure still returns its error. The accounts the config decides on
+// (the fee's, the greeting log's) are checked here too, taken off the list at the
+// position the config puts them.
+#[cfg(feature = "program")]
+struct Context<'a, 'b, T> {
+    accounts: T,
+    remaining: std::slice::Iter<'a, AccountInfo<'b>>,
+}
+
+/// The checked accounts of one or more instructions, for Context::load()
+#[cfg(feature = "program")]
+trait InstructionAccounts<'a, 'b>: Sized {
+    /// What the checks need from the instruction data, like a PDA's seeds
+    type Args: ?Sized;
+    /// Where the accounts the processor takes as it goes start, none by default
+    const REMAINING_IDX: usize = usize::MAX;
+
+    /// Check and capture the accounts at their positions in `accounts`
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        args: &Self::Args,
+    ) -> Result<Self, ProgramError>;
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b, T: InstructionAccounts<'a, 'b>> Context<'a, 'b, T> {
+    /// Check `accounts` are all there for the instruction of `tag`, then check them
+    fn load(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+        tag: usize,
+        args: &T::Args,
+    ) -> Result<Self, ProgramError> {
+        let accounts = Accounts::load(accounts, INSTRUCTION_ACCOUNTS[tag])?;
+        Ok(Self {
+            accounts: T::check(program_id, &accounts, args)?,
+            remaining: accounts.iter_from(T::REMAINING_IDX),
+        })
+    }
+}
+
+/// The greeting account and config of Greet and Increment
+#[cfg(feature = "program")]
+struct GreetAccounts<'a, 'b> {
+    greeting_account: &'a AccountInfo<'b>,
+    config: config::Config,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for GreetAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        // Get the account to say hello to
+        let greeting_account = accounts.get(accounts::GREETING_ACCOUNT_IDX)?;
+        // NOTE The config account used to be optional. With fees it can't be: a Greet
+        // leaving it out would skip the fee.
+        let config = greet_config(program_id, accounts.get(accounts::CONFIG_IDX)?)?;
+        check_greeting_account(program_id, greeting_account)?;
+        Ok(Self {
+            greeting_account,
+            config,
+        })
+    }
+}
+
+/// Greet's greeting account and config, then the accounts the config asks for: the
+/// fee's, the greeting log's, the receipt's and the ones the Greet is weighed by
+#[cfg(feature = "program")]
+struct ConfiguredGreetAccounts<'a, 'b> {
+    greeting_account: &'a AccountInfo<'b>,
+    config: config::Config,
+    fee: Option<FeeAccounts<'a, 'b>>,
+    log: Option<GreetingLog<'a, 'b>>,
+    receipt: Option<ReceiptAccounts<'a, 'b>>,
+    weight: WeightAccounts<'a, 'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ConfiguredGreetAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let GreetAccounts {
+            greeting_account,
+            config,
+        } = GreetAccounts::check(program_id, accounts, &())?;
+        let fee = if config.charges_fee() {
+            Some(FeeAccounts::check(
+                &config,
+                accounts.get(accounts::PAYER_IDX)?,
+                accounts.get(accounts::PRICE_FEED_IDX)?,
+                accounts.get(accounts::FEE_RECIPIENT_IDX)?,
+                accounts.get(accounts::SYSTEM_PROGRAM_IDX)?,
+            )?)
+        } else {
+            None
+        };
+        // The greeting log and weight accounts follow whichever fee accounts there are
+        let accounts_iter = &mut accounts.iter_from(if fee.is_some() {
+            accounts::FEE_ACCOUNTS_END
+        } else {
+            accounts::PAYER_IDX
+        });
+        let log = if config.has_greeting_log() {
+            let log = next_greeting_log(program_id, accounts_iter)?;
+            if log.tree.key.to_bytes() != config.greeting_log {
+                log!("{} is not the greeting log", log.tree.key);
+                return Err(HelloWorldError::InvalidGreetingLog.into());
+            }
+            Some(log)
+        } else {
+            None
+        };
+        let receipt = if config.greeting_receipts {
+            Some(next_receipt_accounts(accounts_iter)?)
+        } else {
+            None
+        };
+        let weight = WeightAccounts::next(&config, accounts_iter)?;
+        Ok(Self {
+            greeting_account,
+            config,
+            fee,
+            log,
+            receipt,
+            weight,
+        })
+    }
+}
+
+/// Save the passed message into the greeting account
+#[cfg(feature = "program")]
+pub fn process_greet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    txt: String,
+) -> ProgramResult {
+    profile!("start");
+    let (accounts, instructions) = origin::split_instructions_sysvar(accounts);
+    let Context { accounts, .. } =
+        Context::<ConfiguredGreetAccounts>::load(program_id, accounts, tag::GREET, &())?;
+    let account = accounts.greeting_account;
+    let config = accounts.config;
+    check_greeting_allowed(&config, txt.len())?;
+    let clock = Clock::get()?;
+
+    // Marked while the fee transfer runs, see reentrancy.rs
+    let lock = reentrancy::lock(account)?;
+    if let Some(fee) = accounts.fee {
+        fee.charge(&config, clock.slot)?;
+    }
+    let log = accounts.log;
+    let receipt = accounts.receipt;
+    let base = accounts.weight.weight(program_id, &config, &clock)?;
+    let weight = epoch_bonus(&config, &clock, base)?;
+    let origin = origin::origin(program_id, instructions)?;
+    drop(lock);
+    profile!("validation");
+
+    // NOTE Loaded after the checks above so the profile's stages don't overlap
+    let (version, greeting) = load_greeting(account)?;
+    check_cooldown(&config, &greeting, clock.unix_timestamp)?;
+    profile!("deserialize");
+    // NOTE Seeded by the counter as stored, so it's created before the counter moves
+    if let Some(receipt) = receipt {
+        receipt.create(program_id, account, greeting.counter, &clock)?;
     }
 
+    // Decay the counter to the current epoch before counting this greeting
+    let (counter, last_update_epoch) = decay::touch(
+        greeting.counter,
+        greeting.last_update_epoch,
+        config.decay_half_life_epochs,
+        clock.epoch,
+    );
+
     // ===== Sending string messages instead of just counting =====
     // https://youtu.be/gA7hFdq2h9Q?t=5270
-    msg!("Start instruction decode");
-    // We're passing in the data as a binary instance of the GreetingAccount type
-    // from the client side. Taking the data as an array of bytes and trying to
-    // decode it using the try_from_slice() method.
-    // NOTE map_err() allows us to load a message if the decoding fails
-    // NOTE Technically we don't have to instantiate a GreetingAccount (message).
-    // The reason he did this is to ensure we're getting the correct data values.
-    // This is highlighting that you can pass ANY type of data (instruction_data),
-    // not just &[u8], but also objects, etc. &[u8] is really flexible so allows
-    // more types of data to be sent.
-    let message = GreetingAccount::try_from_slice(instruction_data).map_err(|err| {
-        msg!("Receiving message as string utf8 failed, {:?}", err);
-        ProgramError::InvalidInstructionData
-    })?;
-    msg!("Greeting passed to program is {:?}", message);
+    // The message arrives already decoded from the Greet instruction. Wrapping it in
+    // a GreetingAccount ensures we store exactly the layout the client reads back.
+    let mut message = GreetingAccount {
+        txt,
+        counter: counter.saturating_add(weight),
+        calendar_year: greeting.calendar_year,
+        greeted_days: greeting.greeted_days,
+        last_update_epoch,
+        direct_greets: greeting.direct_greets,
+        cpi_greets: greeting.cpi_greets,
+    };
+    log!("Greeting passed to program is {:?}", message.txt);
+    if let Some(origin) = origin {
+        origin.count(&mut message.direct_greets, &mut message.cpi_greets);
+    }
+
+    // Mark today in the calendar of greeted days
+    let now = clock.unix_timestamp;
+    if !calendar::mark_greeted(&mut message.calendar_year, &mut message.greeted_days, now) {
+        log!("Clock is behind calendar year {}, day not recorded", message.calendar_year);
+    }
+    profile!("mutate");
+
+    let stored = store_greeting(account, version, &message)?;
+    profile!("serialize");
+    if let Some(log) = log {
+        let lock = reentrancy::lock(account)?;
+        log.append(&compression::GreetingLeaf {
+            greeting_account: account.key.to_bytes(),
+            counter: message.counter,
+            slot: clock.slot,
+            txt: message.txt.clone(),
+        })?;
+        drop(lock);
+    }
 
-    // Take the same instance of data from the account we need to actually update
-    let data = &mut &mut account.data.borrow_mut();
-    msg!("Account data: {:?}", data);
-    msg!("Start save instruction into data");
-    // Then we find a range inside the data that is the same size as instruction_data
-    // so we can copy the parameter data into the actual account data.
-    // It seems like we're essentially inserting the parameter data
-    // NOTE This is Rust array slicing syntax i.e. Python: data[:len(instruction_data)]
-    data[..instruction_data.len()].copy_from_slice(&instruction_data);
-    
     sol_log_compute_units();
     msg!("Was sent message {}!", message.txt);
-
+    if stored >= 2 {
+        log!("Greeted {} time(s)!", message.counter);
+    }
+    if stored >= 3 {
+        log!(
+            "Greeted on {} day(s) of {}",
+            message.greeted_days.count(),
+            message.calendar_year
+        );
+    }
+    if config.decay_half_life_epochs > 0 {
+        log!("Counter halves every {} epoch(s)", config.decay_half_life_epochs);
+    }
+    if config.weighs_balance() {
+        log!("Greeting counted {} times for the greeter's balance", base);
+    } else if base > 1 {
+        log!("Greeting counted {} times for the greeter's stake", base);
+    }
+    if weight != base {
+        log!("Counted double in the first {} slot(s) of the epoch", config.epoch_bonus_slots);
+    }
 
     // // ===== helloworld default =====
     // // Now we get to what we actually want to do for this smart contract
@@ -146,62 +690,6089 @@ pub fn process_instruction(
     // // greeting_account.serialize(data)?;
 
     // // Finally wrap it all up with a logging message.
-    // msg!("Greeted {} time(s)!", greeting_account.counter);
+    // log!("Greeted {} time(s)!", greeting_account.counter);
     // // ================================
 
     Ok(())
 }
 
-// Sanity tests
-#[cfg(test)]
-mod test {
-    use super::*;
-    use solana_program::clock::Epoch;
-    use std::mem;
+/// Check that `account` is a greeting account this program may write to
+#[cfg(feature = "program")]
+fn check_greeting_account(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    // The account must be owned by the program in order to modify its data
+    // NOTE The account is the account that we want to do something to. So,
+    // we're confirming that its owner is the program_id. Otherwise, throw error.
+    // NOTE If this check fails at any point, then the entire transaction this
+    // occurs within/lives within, will be exited and unroll itself.
+    // NOTE "owner" is NOT the human owner. It is the programmatic controller of the account!
+    if account.owner != program_id {
+        // Log the error message
+        log!("Greeted account does not have the correct program id");
+        // Return the specific Error Type
+        return Err(ProgramError::IncorrectProgramId);
+    }
 
-    #[test]
-    fn test_sanity() {
-        let program_id = Pubkey::default();
-        let key = Pubkey::default();
-        let mut lamports = 0;
-        let mut data = vec![0; mem::size_of::<u32>()];
-        let owner = Pubkey::default();
-        let account = AccountInfo::new(
-            &key,
-            false,
-            true,
-            &mut lamports,
-            &mut data,
-            &owner,
-            false,
-            Epoch::default(),
-        );
-        let instruction_data: Vec<u8> = Vec::new();
+    // NOTE Program accounts are marked executable by the BPF Loader and can never be
+    // unset. A state account should only ever hold data, so refuse anything executable
+    // rather than letting someone pass in a program and corrupt our assumptions.
+    if account.executable {
+        log!("Greeted account must not be executable");
+        return Err(HelloWorldError::ExecutableAccount.into());
+    }
+    // NOTE A nested invocation greeting the account before the caller's greeting is done
+    // would count it twice, see reentrancy.rs
+    reentrancy::check_unlocked(account)?;
+    Ok(())
+}
 
-        let accounts = vec![account];
+/// Refuse a greeting with a message of `txt_len` bytes if the config pauses greetings
+/// or caps messages shorter
+#[cfg(feature = "program")]
+fn check_greeting_allowed(config: &config::Config, txt_len: usize) -> ProgramResult {
+    if config.paused {
+        log!("Greetings are paused");
+        return Err(HelloWorldError::Paused.into());
+    }
+    if !config.allows_message_len(txt_len) {
+        log!("Messages are at most {} bytes", config.max_message_len);
+        return Err(HelloWorldError::MessageTooLong.into());
+    }
+    Ok(())
+}
 
-        assert_eq!(
-            GreetingAccount::try_from_slice(&accounts[0].data.borrow())
-                .unwrap()
-                // .counter,
-                .txt,
-            0
-        );
-        process_instruction(&program_id, &accounts, &instruction_data).unwrap();
-        assert_eq!(
-            GreetingAccount::try_from_slice(&accounts[0].data.borrow())
-                .unwrap()
-                // .counter,
-                .txt,
-            1
+/// Refuse a greeting of an account greeted within the config's cooldown
+#[cfg(feature = "program")]
+fn check_cooldown(
+    config: &config::Config,
+    greeting: &GreetingAccount,
+    unix_timestamp: i64,
+) -> ProgramResult {
+    if config.on_cooldown(greeting.calendar_year, &greeting.greeted_days, unix_timestamp) {
+        log!("Greeted within the last {} day(s)", config.cooldown_days);
+        return Err(HelloWorldError::OnCooldown.into());
+    }
+    Ok(())
+}
+
+/// What a greeting of `weight` counts for at `clock`, doubled in the config's epoch
+/// bonus slots (see bonus.rs)
+#[cfg(feature = "program")]
+fn epoch_bonus(config: &config::Config, clock: &Clock, weight: u64) -> Result<u64, ProgramError> {
+    if config.epoch_bonus_slots == 0 {
+        return Ok(weight);
+    }
+    let schedule = EpochSchedule::get()?;
+    Ok(bonus::apply(&schedule, clock.slot, config.epoch_bonus_slots, weight))
+}
+
+/// Read the greeting account's state at STATE_VERSION, along with the schema version
+/// it's stored as
+// NOTE Older schema versions are upgraded in memory and written back in the current
+// layout by store_greeting(). A read-only account can't be written back, so one still
+// on an old version is refused rather than silently read through a stale layout.
+#[cfg(feature = "program")]
+fn load_greeting(account: &AccountInfo) -> Result<(u8, GreetingAccount), ProgramError> {
+    let data = account.data.borrow();
+    let version = migrations::stored_version(&data).map_err(|err| {
+        log!("{} holds no greeting, {}", account.key, err);
+        ProgramError::InvalidAccountData
+    })?;
+    if version < STATE_VERSION && !account.is_writable {
+        log!(
+            "Greeting account is on schema {} and must be writable to migrate it to {}",
+            version,
+            STATE_VERSION
         );
-        process_instruction(&program_id, &accounts, &instruction_data).unwrap();
-        assert_eq!(
-            GreetingAccount::try_from_slice(&accounts[0].data.borrow())
-                .unwrap()
-                // .counter,
-                .txt,
-            2
+        return Err(HelloWorldError::MigrationRequired.into());
+    }
+    let greeting = GreetingAccount::unpack(&data).map_err(|err| {
+        log!("Can't read the greeting account, {}", err);
+        ProgramError::InvalidAccountData
+    })?;
+    Ok((version, greeting))
+}
+
+/// Encode `message` into the front of the greeting account's data, migrating the
+/// account from schema `version` if it's older. Returns the schema version written.
+#[cfg(feature = "program")]
+fn store_greeting(
+    account: &AccountInfo,
+    version: u8,
+    message: &GreetingAccount,
+) -> Result<u8, ProgramError> {
+    // Take the same instance of data from the account we need to actually update
+    let data = &mut &mut account.data.borrow_mut();
+    log!("Account data: {:?}", data);
+    log!("Start save instruction into data");
+    // Encode the message and copy it into the front of the account data.
+    // NOTE The account's size is fixed when the client creates it (see GREETING_SIZE
+    // in hello_world.ts), so a longer message than that simply doesn't fit.
+    // Layouts to try as (schema version of the fields kept, data), the first that fits
+    // is written.
+    let fresh = data.iter().all(|byte| *byte == 0);
+    let compact = compact::encoding(data) == compact::Encoding::Compact;
+    let mut layouts = vec![];
+    if !compact {
+        // NOTE solana-program 1.6 can't grow an account, and accounts from before a
+        // schema change were sized for the older layout. Those move to the newest
+        // schema that fits, or stay on theirs and keep only the fields it has. A new
+        // (still zeroed) account always gets the current layout.
+        let oldest = if fresh { STATE_VERSION } else { version };
+        for version in (oldest..=STATE_VERSION).rev() {
+            layouts.push((version, migrations::encode_as(version, message)?));
+        }
+    }
+    // NOTE A compact account stays compact, and a new account the Borsh layout doesn't
+    // fit in is created compact (the client saves rent by allocating less). Without
+    // room for the origin counters they're left out, like in a schema 4 account, then
+    // the calendar, like in a schema 2 account, and then the epoch stamp too. See
+    // compact.rs.
+    if compact || fresh {
+        layouts.push((STATE_VERSION, compact::encode(message)));
+        let mut undated = GreetingAccount {
+            txt: message.txt.clone(),
+            counter: message.counter,
+            calendar_year: message.calendar_year,
+            greeted_days: message.greeted_days.clone(),
+            last_update_epoch: message.last_update_epoch,
+            direct_greets: 0,
+            cpi_greets: 0,
+        };
+        layouts.push((4, compact::encode(&undated)));
+        undated.calendar_year = 0;
+        undated.greeted_days = calendar::DayBitmap::default();
+        layouts.push((2, compact::encode(&undated)));
+        undated.last_update_epoch = 0;
+        layouts.push((2, compact::encode(&undated)));
+    }
+    let (stored, message_data) = match layouts.iter().find(|(_, layout)| layout.len() <= data.len()) {
+        Some(layout) => layout,
+        None => {
+            log!(
+                "Message needs {} bytes but the account only has {}",
+                layouts.last().map_or(0, |(_, layout)| layout.len()),
+                data.len()
+            );
+            return Err(HelloWorldError::MessageTooLong.into());
+        }
+    };
+    // NOTE This is Rust array slicing syntax i.e. Python: data[:len(message_data)]
+    data[..message_data.len()].copy_from_slice(message_data);
+    if *stored > version && !fresh {
+        log!("Migrated greeting account from schema {} to {}", version, stored);
+    }
+    state_hash::log(account.key, data);
+    Ok(*stored)
+}
+
+/// ExportState's greeting account
+#[cfg(feature = "program")]
+struct ExportStateAccounts<'a, 'b> {
+    account: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ExportStateAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let account = accounts.get(accounts::state::GREETING_ACCOUNT_IDX)?;
+        if account.owner != program_id {
+            log!("Exported account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(Self { account })
+    }
+}
+
+/// Log a greeting account's data for the record of a migration
+#[cfg(feature = "program")]
+pub fn process_export_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<ExportStateAccounts>::load(program_id, accounts, tag::EXPORT_STATE, &())?;
+    let account = accounts.account;
+    let data = account.data.borrow();
+    let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+    // NOTE Keep this format stable, the migration tool reads it back
+    msg!("helloworld-export: {} {}", account.key, hex);
+    Ok(())
+}
+
+/// ImportState's greeting account, with the schema it's stored at, and its source,
+/// imported by the config authority
+#[cfg(feature = "program")]
+struct ImportStateAccounts<'a, 'b> {
+    account: &'a AccountInfo<'b>,
+    version: u8,
+    source: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ImportStateAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let account = accounts.get(accounts::state::GREETING_ACCOUNT_IDX)?;
+        let source = accounts.get(accounts::state::SOURCE_IDX)?;
+        let config_account = accounts.get(accounts::state::CONFIG_IDX)?;
+        let authority = accounts.get(accounts::state::AUTHORITY_IDX)?;
+        check_greeting_account(program_id, account)?;
+        let (version, _) = load_greeting(account)?;
+        load_config_as_authority(program_id, config_account, authority)?;
+        Ok(Self {
+            account,
+            version,
+            source,
+        })
+    }
+}
+
+/// Copy the greeting of an account from another deployment into one of ours
+#[cfg(feature = "program")]
+pub fn process_import_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<ImportStateAccounts>::load(program_id, accounts, tag::IMPORT_STATE, &())?;
+    let source = accounts.source;
+
+    // NOTE The source's counter comes along, so no greetings are lost. The source may
+    // be on an older schema, unpack() reads it through an upgraded copy.
+    let message = GreetingAccount::unpack(&source.data.borrow()).map_err(|err| {
+        log!("Source account {} holds no greeting, {}", source.key, err);
+        ProgramError::InvalidAccountData
+    })?;
+    store_greeting(accounts.account, accounts.version, &message)?;
+    log!(
+        "Imported message {} from {} (owned by {})",
+        message.txt,
+        source.key,
+        source.owner
+    );
+    Ok(())
+}
+
+/// The config a Greet runs under. A config account that hasn't been created yet reads
+/// as the defaults: no decay and no fee.
+#[cfg(feature = "program")]
+fn greet_config(program_id: &Pubkey, config: &AccountInfo) -> Result<config::Config, ProgramError> {
+    if *config.key != config::config_address(program_id).0 {
+        log!("{} is not the config account", config.key);
+        return Err(HelloWorldError::InvalidConfig.into());
+    }
+    if config.owner != program_id || config.data_is_empty() {
+        return Ok(config::Config::default());
+    }
+    read_config(program_id, config)
+}
+
+/// The accounts a fee is paid through, checked against the config
+#[cfg(feature = "program")]
+struct FeeAccounts<'a, 'b> {
+    payer: &'a AccountInfo<'b>,
+    price_feed: &'a AccountInfo<'b>,
+    fee_recipient: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> FeeAccounts<'a, 'b> {
+    /// Check the payer signs and the price feed and fee recipient are the config's
+    fn check(
+        config: &config::Config,
+        payer: &'a AccountInfo<'b>,
+        price_feed: &'a AccountInfo<'b>,
+        fee_recipient: &'a AccountInfo<'b>,
+        system_program: &'a AccountInfo<'b>,
+    ) -> Result<Self, ProgramError> {
+        if !payer.is_signer {
+            log!("The fee payer must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if price_feed.key.to_bytes() != config.price_feed {
+            log!("{} is not the configured price feed", price_feed.key);
+            return Err(HelloWorldError::InvalidPriceFeed.into());
+        }
+        if fee_recipient.key.to_bytes() != config.fee_recipient {
+            log!("{} is not the fee recipient", fee_recipient.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            payer,
+            price_feed,
+            fee_recipient,
+            system_program,
+        })
+    }
+
+    /// Take the config's fee from the payer, in lamports at the price feed's SOL/USD
+    /// price
+    // NOTE The price has to be fresh and certain enough for the config, or the Greet
+    // fails. A stale or wide price would let greeters pay far less (or more) than the
+    // fee.
+    fn charge(&self, config: &config::Config, slot: u64) -> ProgramResult {
+        let price = pyth::Price::parse(&self.price_feed.data.borrow())
+            .and_then(|price| {
+                price.checked(slot, config.max_price_age_slots, config.max_confidence_bps)
+            })
+            .map_err(|err| {
+                log!("Can't use the price feed, {:?}", err);
+                match err {
+                    pyth::PriceError::InvalidAccount => HelloWorldError::InvalidPriceFeed,
+                    pyth::PriceError::Stale => HelloWorldError::StalePrice,
+                    pyth::PriceError::Uncertain => HelloWorldError::UncertainPrice,
+                }
+            })?;
+        let lamports = price.lamports_for_usd(config.fee_usd_micros).ok_or_else(|| {
+            log!("The fee doesn't fit in a u64 of lamports");
+            ProgramError::InvalidArgument
+        })?;
+        invoke(
+            &system_instruction::transfer(self.payer.key, self.fee_recipient.key, lamports),
+            &[
+                self.payer.clone(),
+                self.fee_recipient.clone(),
+                self.system_program.clone(),
+            ],
+        )?;
+        log!(
+            "Charged a fee of {} lamports at {}e{} USD/SOL",
+            lamports,
+            price.price,
+            price.expo
         );
+        Ok(())
+    }
+}
+
+/// The accounts a Greet is weighed by, passed after the fee, log and receipt accounts
+#[cfg(feature = "program")]
+enum WeightAccounts<'a, 'b> {
+    /// None passed, the Greet counts once
+    Unweighted,
+    /// A stake position and its staker, signing themselves or through a session key
+    /// with its session token
+    Stake {
+        position: &'a AccountInfo<'b>,
+        signer: &'a AccountInfo<'b>,
+        session_token: Option<&'a AccountInfo<'b>>,
+    },
+    /// With a balance mint in the config, the greeter's token account of it and its
+    /// owner, signing
+    Balance {
+        token_account: &'a AccountInfo<'b>,
+        owner: &'a AccountInfo<'b>,
+    },
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> WeightAccounts<'a, 'b> {
+    /// Take the weight accounts the config asks for off `accounts_iter`, checking their
+    /// signer signs
+    fn next<I: Iterator<Item = &'a AccountInfo<'b>>>(
+        config: &config::Config,
+        accounts_iter: &mut I,
+    ) -> Result<Self, ProgramError> {
+        if config.weighs_balance() {
+            let token_account = next_account_info(accounts_iter)?;
+            let owner = next_account_info(accounts_iter)?;
+            if !owner.is_signer {
+                log!("The token account's owner must sign");
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            return Ok(Self::Balance {
+                token_account,
+                owner,
+            });
+        }
+        let position = match accounts_iter.next() {
+            Some(position) => position,
+            None => return Ok(Self::Unweighted),
+        };
+        let signer = next_account_info(accounts_iter)?;
+        if !signer.is_signer {
+            log!("The staker must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(Self::Stake {
+            position,
+            signer,
+            session_token: accounts_iter.next(),
+        })
+    }
+
+    /// How much the Greet counts for: 1, the staker's stake or the greeter's balance
+    fn weight(
+        &self,
+        program_id: &Pubkey,
+        config: &config::Config,
+        clock: &Clock,
+    ) -> Result<u64, ProgramError> {
+        match *self {
+            Self::Unweighted => Ok(1),
+            Self::Stake {
+                position,
+                signer,
+                session_token,
+            } => {
+                let staker = match session_token {
+                    Some(session_token) => {
+                        let session = load_session(program_id, session_token)?;
+                        if !session.is_valid(&signer.key.to_bytes(), clock.unix_timestamp) {
+                            log!(
+                                "{} has no running session, it ended at {}",
+                                signer.key,
+                                session.valid_until
+                            );
+                            return Err(HelloWorldError::InvalidSession.into());
+                        }
+                        Pubkey::new_from_array(session.authority)
+                    }
+                    None => *signer.key,
+                };
+                let position = load_position(program_id, position, &staker)?;
+                Ok(position.greet_weight(&config.stake_mint, config.stake_unit, clock.slot))
+            }
+            Self::Balance {
+                token_account,
+                owner,
+            } => balance_weight(config, token_account, owner),
+        }
+    }
+}
+
+/// How much a Greet counts for by the balance of the config's balance mint in the token
+/// account of `owner`
+// NOTE The balance is read as it is at greet time, tokens borrowed earlier in the same
+// transaction included. Stake weighting (see stake.rs) is the one that holds up against
+// flash loans.
+#[cfg(feature = "program")]
+fn balance_weight(
+    config: &config::Config,
+    token_account: &AccountInfo,
+    owner: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let account = if *token_account.owner == token::id() {
+        token::TokenAccount::parse(&token_account.data.borrow())
+    } else {
+        None
+    };
+    match account {
+        Some(account)
+            if account.mint == config.balance_mint && account.owner == owner.key.to_bytes() =>
+        {
+            Ok(config.balance_weight(account.amount))
+        }
+        _ => {
+            log!("{} is not {}'s token account of the balance mint", token_account.key, owner.key);
+            Err(HelloWorldError::InvalidBalanceAccount.into())
+        }
+    }
+}
+
+/// Read `staker`'s stake position
+#[cfg(feature = "program")]
+fn load_position(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+    staker: &Pubkey,
+) -> Result<stake::StakePosition, ProgramError> {
+    if *account.key != stake::position_address(program_id, staker).0
+        || account.owner != program_id
+    {
+        log!("{} is not the stake position of {}", account.key, staker);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    stake::StakePosition::try_from_slice(&account.data.borrow()).map_err(|err| {
+        log!("Can't read the stake position, {}", err);
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Write `position` back into its account
+#[cfg(feature = "program")]
+fn store_position(account: &AccountInfo, position: &stake::StakePosition) -> ProgramResult {
+    position
+        .serialize(&mut &mut account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the stake position, {}", err);
+            ProgramError::InvalidAccountData
+        })
+}
+
+/// Check `vault` is a token account of `mint` owned by the stake authority, returning
+/// the stake authority's address and bump seed
+#[cfg(feature = "program")]
+fn check_stake_vault(
+    program_id: &Pubkey,
+    vault: &AccountInfo,
+    mint: &[u8; 32],
+) -> Result<(Pubkey, u8), ProgramError> {
+    let (stake_authority, bump) = stake::stake_authority_address(program_id);
+    let account = if *vault.owner == token::id() {
+        token::TokenAccount::parse(&vault.data.borrow())
+    } else {
+        None
+    };
+    match account {
+        Some(account) if account.mint == *mint && account.owner == stake_authority.to_bytes() => {
+            Ok((stake_authority, bump))
+        }
+        _ => {
+            log!("{} is not the stake vault", vault.key);
+            Err(HelloWorldError::InvalidStakeVault.into())
+        }
+    }
+}
+
+/// Read the config account, checking it's the one of this program
+// NOTE Boxed: the admin processors hold the config before and after a change, and both
+// end up in the event, which would take most of a 4 KiB BPF stack frame
+#[cfg(feature = "program")]
+fn load_config(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<Box<config::Config>, ProgramError> {
+    read_config(program_id, account).map(Box::new)
+}
+
+/// load_config() without the box, for Increment, which doesn't allocate
+#[cfg(feature = "program")]
+fn read_config(program_id: &Pubkey, account: &AccountInfo) -> Result<config::Config, ProgramError> {
+    if *account.key != config::config_address(program_id).0 || account.owner != program_id {
+        log!("{} is not the config account", account.key);
+        return Err(HelloWorldError::InvalidConfig.into());
+    }
+    config::Config::unpack(&account.data.borrow()).map_err(|err| {
+        log!("Can't read the config account, {}", err);
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Write `config` back into the config account
+#[cfg(feature = "program")]
+fn store_config(account: &AccountInfo, config: &config::Config) -> ProgramResult {
+    config.pack(&mut account.data.borrow_mut()).map_err(|err| {
+        log!("Can't write the config account, {}", err);
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Read the config account and check `authority` is its authority and signed
+#[cfg(feature = "program")]
+fn load_config_as_authority(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    authority: &AccountInfo,
+) -> Result<Box<config::Config>, ProgramError> {
+    let config = load_config(program_id, config_account)?;
+    if !authority.is_signer || authority.key.to_bytes() != config.authority {
+        log!("{} is not the config authority or didn't sign", authority.key);
+        return Err(HelloWorldError::Unauthorized.into());
+    }
+    Ok(config)
+}
+
+/// InitConfig's accounts, with the bump of the config account
+#[cfg(feature = "program")]
+struct InitConfigAccounts<'a, 'b> {
+    config_account: &'a AccountInfo<'b>,
+    config_bump: u8,
+    authority: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for InitConfigAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(admin::CONFIG_IDX)?;
+        let authority = accounts.get(admin::AUTHORITY_IDX)?;
+        let system_program = accounts.get(admin::SYSTEM_PROGRAM_IDX)?;
+        if !authority.is_signer {
+            log!("The config authority must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let (address, config_bump) = config::config_address(program_id);
+        if *config_account.key != address {
+            log!("{} is not the config account", config_account.key);
+            return Err(HelloWorldError::InvalidConfig.into());
+        }
+        if config_account.owner == program_id {
+            log!("The config account already exists");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            config_account,
+            config_bump,
+            authority,
+            system_program,
+        })
+    }
+}
+
+/// The accounts of the config authority's instructions: the config, loaded, and the
+/// authority, checked to be its authority and to have signed
+#[cfg(feature = "program")]
+struct AdminAccounts<'a, 'b> {
+    config_account: &'a AccountInfo<'b>,
+    config: Box<config::Config>,
+    authority: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for AdminAccounts<'a, 'b> {
+    type Args = ();
+    // NOTE InitGreetingLog's log accounts follow, the Set* instructions have none
+    const REMAINING_IDX: usize = admin::GREETING_LOG_IDX;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(admin::CONFIG_IDX)?;
+        let authority = accounts.get(admin::AUTHORITY_IDX)?;
+        let config = load_config_as_authority(program_id, config_account, authority)?;
+        Ok(Self {
+            config_account,
+            config,
+            authority,
+        })
+    }
+}
+
+/// Create the config account with the signer as its authority
+#[cfg(feature = "program")]
+pub fn process_init_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    decay_half_life_epochs: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<InitConfigAccounts>::load(program_id, accounts, tag::INIT_CONFIG, &())?;
+    let config_account = accounts.config_account;
+    let authority = accounts.authority;
+    let system_program = accounts.system_program;
+
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    let lamports = Rent::get()?.minimum_balance(config::CONFIG_SIZE);
+    create_pda_account(
+        authority,
+        config_account,
+        system_program,
+        lamports,
+        config::CONFIG_SIZE,
+        program_id,
+        &[config::CONFIG_SEED, &[accounts.config_bump]],
+    )?;
+    let config = config::Config {
+        authority: authority.key.to_bytes(),
+        decay_half_life_epochs,
+        ..config::Config::default()
+    };
+    store_config(config_account, &config)?;
+    log!(
+        "Created config {} with authority {}, counters halve every {} epoch(s)",
+        config_account.key,
+        authority.key,
+        decay_half_life_epochs
+    );
+    Ok(())
+}
+
+/// Change the decay half-life in the config account
+#[cfg(feature = "program")]
+pub fn process_set_decay(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    half_life_epochs: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_DECAY, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    let old = config.clone();
+    log!(
+        "Decay half-life changed from {} to {} epoch(s)",
+        config.decay_half_life_epochs,
+        half_life_epochs
+    );
+    if config.greeting_receipts && half_life_epochs > 0 {
+        log!("Greeting receipts are on, turn them off before decaying counters");
+        return Err(HelloWorldError::InvalidConfigValue.into());
+    }
+    config.decay_half_life_epochs = half_life_epochs;
+    store_config(config_account, &config)?;
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Change the config's tunables set in `patch`, checking each is in range and that
+/// they fit the rest of the config
+#[cfg(feature = "program")]
+pub fn process_set_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    patch: &config::ConfigPatch,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_CONFIG, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    if let Err(field) = patch.validate_for(&config) {
+        log!("SetConfig's {} is out of range or conflicts with the config", field);
+        return Err(HelloWorldError::InvalidConfigValue.into());
+    }
+    let old = config.clone();
+    patch.apply(&mut config);
+    store_config(config_account, &config)?;
+    log!("Config updated: {:?}", patch);
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Change the crank authority in the config account
+#[cfg(feature = "program")]
+pub fn process_set_crank_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    crank_authority: [u8; 32],
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_CRANK_AUTHORITY, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    let old = config.clone();
+    config.crank_authority = crank_authority;
+    store_config(config_account, &config)?;
+    log!("Crank authority is now {}", Pubkey::new_from_array(crank_authority));
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Set the fee charged for every Greet
+#[cfg(feature = "program")]
+pub fn process_set_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: [u8; 32],
+    fee_recipient: [u8; 32],
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_FEE, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    let old = config.clone();
+    config.fee_usd_micros = fee_usd_micros;
+    config.max_price_age_slots = max_price_age_slots;
+    config.max_confidence_bps = max_confidence_bps;
+    config.price_feed = price_feed;
+    config.fee_recipient = fee_recipient;
+    store_config(config_account, &config)?;
+    log!(
+        "Fee is now {} micro-USD per greeting, paid to {}",
+        config.fee_usd_micros,
+        Pubkey::new_from_array(config.fee_recipient)
+    );
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Hand the config account to a new authority
+// NOTE The new authority doesn't sign: a governance PDA can only sign through its
+// program, once a proposal passes, so it couldn't accept the handover itself. Check the
+// key before sending, a wrong one locks the config for good.
+#[cfg(feature = "program")]
+pub fn process_set_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: [u8; 32],
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_AUTHORITY, &())?;
+    let config_account = accounts.config_account;
+    let authority = accounts.authority;
+    let mut config = accounts.config;
+    config.authority = new_authority;
+    store_config(config_account, &config)?;
+    log!(
+        "Config authority changed from {} to {}",
+        authority.key,
+        Pubkey::new_from_array(new_authority)
+    );
+    Event::authority_changed(authority.key.to_bytes(), new_authority).emit();
+    Ok(())
+}
+
+/// Set the mint greeters stake and how much of it adds 1 to a greeting
+#[cfg(feature = "program")]
+pub fn process_set_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    stake_mint: [u8; 32],
+    stake_unit: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<AdminAccounts>::load(program_id, accounts, tag::SET_STAKE, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    let old = config.clone();
+    config.stake_mint = stake_mint;
+    config.stake_unit = stake_unit;
+    store_config(config_account, &config)?;
+    log!(
+        "Stake mint is now {}, every {} staked adds 1 to a greeting",
+        Pubkey::new_from_array(stake_mint),
+        stake_unit
+    );
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Deposit's accounts, with the config's stake mint and the bump of the stake position
+#[cfg(feature = "program")]
+struct DepositAccounts<'a, 'b> {
+    stake_mint: [u8; 32],
+    position_account: &'a AccountInfo<'b>,
+    position_bump: u8,
+    staker: &'a AccountInfo<'b>,
+    source: &'a AccountInfo<'b>,
+    vault: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for DepositAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(accounts::deposit::CONFIG_IDX)?;
+        let position_account = accounts.get(accounts::deposit::POSITION_IDX)?;
+        let staker = accounts.get(accounts::deposit::STAKER_IDX)?;
+        let source = accounts.get(accounts::deposit::SOURCE_IDX)?;
+        let vault = accounts.get(accounts::deposit::VAULT_IDX)?;
+        let token_program = accounts.get(accounts::deposit::TOKEN_PROGRAM_IDX)?;
+        let system_program = accounts.get(accounts::deposit::SYSTEM_PROGRAM_IDX)?;
+        if !staker.is_signer {
+            log!("The staker must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let config = load_config(program_id, config_account)?;
+        if !config.has_staking() {
+            log!("Staking is off");
+            return Err(HelloWorldError::InvalidConfig.into());
+        }
+        check_stake_vault(program_id, vault, &config.stake_mint)?;
+        validate_program_account(token_program, &token::id())?;
+        let (address, position_bump) = stake::position_address(program_id, staker.key);
+        if *position_account.key != address {
+            log!("{} is not the stake position of {}", position_account.key, staker.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(Self {
+            stake_mint: config.stake_mint,
+            position_account,
+            position_bump,
+            staker,
+            source,
+            vault,
+            token_program,
+            system_program,
+        })
+    }
+}
+
+/// Lock tokens in the stake vault, adding them to the staker's position
+#[cfg(feature = "program")]
+pub fn process_deposit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<DepositAccounts>::load(program_id, accounts, tag::DEPOSIT, &())?;
+    let position_account = accounts.position_account;
+    let staker = accounts.staker;
+    let source = accounts.source;
+    let vault = accounts.vault;
+    let token_program = accounts.token_program;
+    let system_program = accounts.system_program;
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    if position_account.owner != program_id {
+        validate_program_account(system_program, &system_program::id())?;
+        let lamports = Rent::get()?.minimum_balance(stake::STAKE_POSITION_SIZE);
+        create_pda_account(
+            staker,
+            position_account,
+            system_program,
+            lamports,
+            stake::STAKE_POSITION_SIZE,
+            program_id,
+            &[stake::STAKE_SEED, staker.key.as_ref(), &[accounts.position_bump]],
+        )?;
+    }
+    let mut position = load_position(program_id, position_account, staker.key)?;
+    if position.amount > 0 && position.mint != accounts.stake_mint {
+        log!("Withdraw the stake of the previous mint first");
+        return Err(ProgramError::InvalidArgument);
+    }
+    position.owner = staker.key.to_bytes();
+    position.mint = accounts.stake_mint;
+    position.amount = position.amount.checked_add(amount).ok_or_else(|| {
+        log!("Stake doesn't fit in a u64");
+        ProgramError::InvalidArgument
+    })?;
+    position.last_deposit_slot = Clock::get()?.slot;
+    store_position(position_account, &position)?;
+    invoke(
+        &token::transfer(source.key, vault.key, staker.key, amount),
+        &[source.clone(), vault.clone(), staker.clone(), token_program.clone()],
+    )?;
+    log!("Deposited {}, {} staked", amount, position.amount);
+    Ok(())
+}
+
+/// WithdrawStake's accounts, with the staker's position and the stake authority's bump
+#[cfg(feature = "program")]
+struct WithdrawStakeAccounts<'a, 'b> {
+    position_account: &'a AccountInfo<'b>,
+    position: stake::StakePosition,
+    vault: &'a AccountInfo<'b>,
+    destination: &'a AccountInfo<'b>,
+    stake_authority: &'a AccountInfo<'b>,
+    stake_authority_bump: u8,
+    token_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for WithdrawStakeAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let position_account = accounts.get(accounts::withdraw_stake::POSITION_IDX)?;
+        let staker = accounts.get(accounts::withdraw_stake::STAKER_IDX)?;
+        let vault = accounts.get(accounts::withdraw_stake::VAULT_IDX)?;
+        let destination = accounts.get(accounts::withdraw_stake::DESTINATION_IDX)?;
+        let stake_authority = accounts.get(accounts::withdraw_stake::STAKE_AUTHORITY_IDX)?;
+        let token_program = accounts.get(accounts::withdraw_stake::TOKEN_PROGRAM_IDX)?;
+        if !staker.is_signer {
+            log!("The staker must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let position = load_position(program_id, position_account, staker.key)?;
+        let (address, stake_authority_bump) = check_stake_vault(program_id, vault, &position.mint)?;
+        if *stake_authority.key != address {
+            log!("{} is not the stake authority", stake_authority.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(token_program, &token::id())?;
+        Ok(Self {
+            position_account,
+            position,
+            vault,
+            destination,
+            stake_authority,
+            stake_authority_bump,
+            token_program,
+        })
+    }
+}
+
+/// Pay staked tokens back out of the stake vault
+#[cfg(feature = "program")]
+pub fn process_withdraw_stake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<WithdrawStakeAccounts>::load(program_id, accounts, tag::WITHDRAW_STAKE, &())?;
+    let vault = accounts.vault;
+    let stake_authority = accounts.stake_authority;
+    let mut position = accounts.position;
+    if amount > position.amount {
+        log!("Can't withdraw {}, {} staked", amount, position.amount);
+        return Err(HelloWorldError::InsufficientStake.into());
+    }
+
+    position.amount -= amount;
+    store_position(accounts.position_account, &position)?;
+    invoke_signed(
+        &token::transfer(vault.key, accounts.destination.key, stake_authority.key, amount),
+        &[
+            vault.clone(),
+            accounts.destination.clone(),
+            stake_authority.clone(),
+            accounts.token_program.clone(),
+        ],
+        &[&[stake::STAKE_AUTHORITY_SEED, &[accounts.stake_authority_bump]]],
+    )?;
+    log!("Withdrew {}, {} staked", amount, position.amount);
+    Ok(())
+}
+
+/// Tick's config, updated by the crank authority. The greeting accounts follow.
+#[cfg(feature = "program")]
+struct TickAccounts<'a, 'b> {
+    config_account: &'a AccountInfo<'b>,
+    config: Box<config::Config>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for TickAccounts<'a, 'b> {
+    type Args = ();
+    const REMAINING_IDX: usize = accounts::tick::GREETING_ACCOUNTS_IDX;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(accounts::tick::CONFIG_IDX)?;
+        let crank_authority = accounts.get(accounts::tick::CRANK_AUTHORITY_IDX)?;
+        let config = load_config(program_id, config_account)?;
+        if !crank_authority.is_signer || !config.is_crank_authority(&crank_authority.key.to_bytes())
+        {
+            log!("{} is not the crank authority or didn't sign", crank_authority.key);
+            return Err(HelloWorldError::UnauthorizedCrank.into());
+        }
+        Ok(Self {
+            config_account,
+            config,
+        })
+    }
+}
+
+/// Bring the passed greeting accounts up to date for the current epoch
+// NOTE Keeper bots resend on timeouts and batches can overlap, so Tick must be safe to
+// repeat: an account whose counter and stamp wouldn't change is skipped, not rewritten.
+// The same account passed twice in one Tick is thereby only updated once.
+#[cfg(feature = "program")]
+pub fn process_tick(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context {
+        accounts,
+        remaining,
+    } = Context::<TickAccounts>::load(program_id, accounts, tag::TICK, &())?;
+    let config = accounts.config;
+    let epoch = Clock::get()?.epoch;
+    let mut total = 0;
+    let mut updated = 0;
+    for account in remaining {
+        total += 1;
+        if account.key == accounts.config_account.key {
+            log!("The config account is not a greeting account");
+            return Err(HelloWorldError::InvalidConfig.into());
+        }
+        check_greeting_account(program_id, account)?;
+        let (version, greeting) = load_greeting(account)?;
+        let (counter, last_update_epoch) = decay::touch(
+            greeting.counter,
+            greeting.last_update_epoch,
+            config.decay_half_life_epochs,
+            epoch,
+        );
+        if counter == greeting.counter && last_update_epoch == greeting.last_update_epoch {
+            continue;
+        }
+        let greeting = GreetingAccount {
+            counter,
+            last_update_epoch,
+            ..greeting
+        };
+        store_greeting(account, version, &greeting)?;
+        updated += 1;
+    }
+    log!(
+        "Ticked at epoch {}, {} of {} greeting account(s) updated",
+        epoch,
+        updated,
+        total
+    );
+    Ok(())
+}
+
+/// Check `account` is a counter snapshot account this program owns and can write
+#[cfg(feature = "program")]
+fn check_snapshot_account(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    if account.owner != program_id || !account.is_writable {
+        log!("{} is not a writable account of this program", account.key);
+        return Err(HelloWorldError::InvalidSnapshot.into());
+    }
+    Ok(())
+}
+
+/// InitCounterSnapshot's snapshot, started by the config authority
+#[cfg(feature = "program")]
+struct InitCounterSnapshotAccounts<'a, 'b> {
+    snapshot_account: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for InitCounterSnapshotAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(snapshot::CONFIG_IDX)?;
+        let authority = accounts.get(snapshot::SIGNER_IDX)?;
+        let snapshot_account = accounts.get(snapshot::SNAPSHOT_IDX)?;
+        load_config_as_authority(program_id, config_account, authority)?;
+        check_snapshot_account(program_id, snapshot_account)?;
+        Ok(Self { snapshot_account })
+    }
+}
+
+/// SnapshotCounters' config and snapshot, updated by the crank authority. The greeting
+/// accounts follow.
+#[cfg(feature = "program")]
+struct SnapshotCountersAccounts<'a, 'b> {
+    config: Box<config::Config>,
+    snapshot_account: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for SnapshotCountersAccounts<'a, 'b> {
+    type Args = ();
+    const REMAINING_IDX: usize = snapshot::GREETING_ACCOUNTS_IDX;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(snapshot::CONFIG_IDX)?;
+        let crank_authority = accounts.get(snapshot::SIGNER_IDX)?;
+        let snapshot_account = accounts.get(snapshot::SNAPSHOT_IDX)?;
+        let config = load_config(program_id, config_account)?;
+        if !crank_authority.is_signer || !config.is_crank_authority(&crank_authority.key.to_bytes())
+        {
+            log!("{} is not the crank authority or didn't sign", crank_authority.key);
+            return Err(HelloWorldError::UnauthorizedCrank.into());
+        }
+        check_snapshot_account(program_id, snapshot_account)?;
+        Ok(Self {
+            config,
+            snapshot_account,
+        })
+    }
+}
+
+/// Start a counter snapshot in an account the client allocated
+#[cfg(feature = "program")]
+pub fn process_init_counter_snapshot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    depth: u8,
+) -> ProgramResult {
+    let Context { accounts, .. } = Context::<InitCounterSnapshotAccounts>::load(
+        program_id,
+        accounts,
+        tag::INIT_COUNTER_SNAPSHOT,
+        &(),
+    )?;
+    let snapshot_account = accounts.snapshot_account;
+    if depth == 0 || depth > counter_snapshot::MAX_SNAPSHOT_DEPTH {
+        log!("Snapshot depth {} is not 1 to {}", depth, counter_snapshot::MAX_SNAPSHOT_DEPTH);
+        return Err(HelloWorldError::InvalidSnapshot.into());
+    }
+    if let Err(err) = counter_snapshot::init(&mut snapshot_account.data.borrow_mut(), depth) {
+        log!(
+            "{} must be {} zeroed bytes",
+            snapshot_account.key,
+            counter_snapshot::account_size(depth)
+        );
+        return Err(err.into());
+    }
+    log!("Counter snapshot started in {}", snapshot_account.key);
+    Ok(())
+}
+
+/// Put the counters of the passed greeting accounts into a counter snapshot
+// NOTE Like Tick, safe to repeat: a leaf already holding the counter isn't rewritten.
+// The counters are the effective ones at the current epoch, so a snapshot of
+// decaying counters can be refreshed without a Tick first.
+#[cfg(feature = "program")]
+pub fn process_snapshot_counters(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    indices: Vec<u32>,
+) -> ProgramResult {
+    let Context {
+        accounts,
+        remaining: greeting_accounts,
+    } = Context::<SnapshotCountersAccounts>::load(
+        program_id,
+        accounts,
+        tag::SNAPSHOT_COUNTERS,
+        &(),
+    )?;
+    let config = accounts.config;
+    let snapshot_account = accounts.snapshot_account;
+    let mut data = snapshot_account.data.borrow_mut();
+    let depth = counter_snapshot::CounterSnapshot::unpack(&data)
+        .ok_or(HelloWorldError::InvalidSnapshot)?
+        .depth;
+    if greeting_accounts.len() != indices.len() {
+        log!(
+            "{} greeting account(s) for {} index(es)",
+            greeting_accounts.len(),
+            indices.len()
+        );
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let clock = Clock::get()?;
+    let empty = counter_snapshot::empty_hashes(depth);
+    let mut updated = 0;
+    for (account, index) in greeting_accounts.zip(indices) {
+        check_greeting_account(program_id, account)?;
+        let (_, greeting) = load_greeting(account)?;
+        let (counter, _) = decay::touch(
+            greeting.counter,
+            greeting.last_update_epoch,
+            config.decay_half_life_epochs,
+            clock.epoch,
+        );
+        let key = account.key.to_bytes();
+        if counter_snapshot::set_counter(&mut data, &empty, index, &key, counter)? {
+            updated += 1;
+        }
+    }
+    let mut header =
+        counter_snapshot::CounterSnapshot::unpack(&data).ok_or(HelloWorldError::InvalidSnapshot)?;
+    header.slot = clock.slot;
+    header.pack(&mut data);
+    log!(
+        "Snapshot {} holds {} account(s), {} updated",
+        snapshot_account.key,
+        header.leaf_count,
+        updated
+    );
+    Ok(())
+}
+
+/// Claim's accounts for the leaf at the index passed, with the config's claim root and
+/// the bumps of the claimed bitmap and the claim vault
+#[cfg(feature = "program")]
+struct ClaimAccounts<'a, 'b> {
+    root: [u8; 32],
+    claimant: &'a AccountInfo<'b>,
+    claimed: &'a AccountInfo<'b>,
+    claimed_bump: u8,
+    vault: &'a AccountInfo<'b>,
+    vault_bump: u8,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ClaimAccounts<'a, 'b> {
+    /// The leaf's index
+    type Args = u32;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        index: &u32,
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(accounts::claim::CONFIG_IDX)?;
+        let claimant = accounts.get(accounts::claim::CLAIMANT_IDX)?;
+        let claimed = accounts.get(accounts::claim::CLAIMED_IDX)?;
+        let vault = accounts.get(accounts::claim::VAULT_IDX)?;
+        let system_program = accounts.get(accounts::claim::SYSTEM_PROGRAM_IDX)?;
+        if !claimant.is_signer {
+            log!("The claimant must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let root = load_config(program_id, config_account)?.claim_root;
+        let (address, claimed_bump) = claim::claimed_address(program_id, &root, *index);
+        if *claimed.key != address {
+            log!("{} is not the claimed bitmap of leaf {}", claimed.key, index);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let (address, vault_bump) = claim::claim_vault_address(program_id);
+        if *vault.key != address {
+            log!("{} is not the claim vault", vault.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            root,
+            claimant,
+            claimed,
+            claimed_bump,
+            vault,
+            vault_bump,
+            system_program,
+        })
+    }
+}
+
+/// Pay a leaf of the config's claim root out to its claimant, once
+#[cfg(feature = "program")]
+pub fn process_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<ClaimAccounts>::load(program_id, accounts, tag::CLAIM, &index)?;
+    let root = accounts.root;
+    let claimant = accounts.claimant;
+    let claimed = accounts.claimed;
+    let system_program = accounts.system_program;
+    if !claim::verify(&root, index, &claimant.key.to_bytes(), amount, &proof) {
+        log!("Leaf {} paying {} to {} is not under the claim root", index, amount, claimant.key);
+        return Err(HelloWorldError::InvalidClaimProof.into());
+    }
+    if claimed.owner != program_id {
+        let lamports = Rent::get()?.minimum_balance(claim::CLAIMED_BITMAP_SIZE);
+        let number = claim::bitmap_number(index).to_le_bytes();
+        create_pda_account(
+            claimant,
+            claimed,
+            system_program,
+            lamports,
+            claim::CLAIMED_BITMAP_SIZE,
+            program_id,
+            &[claim::CLAIMED_SEED, &root, &number, &[accounts.claimed_bump]],
+        )?;
+        claim::init_bitmap(&mut claimed.data.borrow_mut());
+    }
+    {
+        let mut bitmap = claimed.data.borrow_mut();
+        if !claim::is_bitmap(&bitmap) {
+            log!("{} holds no claimed bitmap", claimed.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if claim::is_claimed(&bitmap, index) {
+            log!("Leaf {} was claimed already", index);
+            return Err(HelloWorldError::AlreadyClaimed.into());
+        }
+        claim::set_claimed(&mut bitmap, index);
+    }
+    if amount > 0 {
+        let vault = accounts.vault;
+        invoke_signed(
+            &system_instruction::transfer(vault.key, claimant.key, amount),
+            &[vault.clone(), claimant.clone(), system_program.clone()],
+            &[&[claim::CLAIM_VAULT_SEED, &[accounts.vault_bump]]],
+        )?;
+    }
+    log!("Leaf {} paid {} lamports to {}", index, amount, claimant.key);
+    Ok(())
+}
+
+/// Snapshot's accounts for the label passed, with the bump of the snapshot
+#[cfg(feature = "program")]
+struct SnapshotAccounts<'a, 'b> {
+    config: config::Config,
+    greeting_account: &'a AccountInfo<'b>,
+    recorder: &'a AccountInfo<'b>,
+    snapshot: &'a AccountInfo<'b>,
+    snapshot_bump: u8,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for SnapshotAccounts<'a, 'b> {
+    /// The snapshot's label, one of its seeds
+    type Args = str;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        label: &str,
+    ) -> Result<Self, ProgramError> {
+        let config = greet_config(program_id, accounts.get(accounts::milestone::CONFIG_IDX)?)?;
+        let greeting_account = accounts.get(accounts::milestone::GREETING_ACCOUNT_IDX)?;
+        let recorder = accounts.get(accounts::milestone::RECORDER_IDX)?;
+        let snapshot = accounts.get(accounts::milestone::SNAPSHOT_IDX)?;
+        let system_program = accounts.get(accounts::milestone::SYSTEM_PROGRAM_IDX)?;
+        if !recorder.is_signer {
+            log!("The recorder must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // NOTE Before deriving the address, a seed can't be longer than a label
+        if !milestone::is_valid_label(label) {
+            log!("A label is 1 to {} bytes, got {}", milestone::MAX_LABEL_LEN, label.len());
+            return Err(ProgramError::InvalidArgument);
+        }
+        check_greeting_account(program_id, greeting_account)?;
+        let (address, snapshot_bump) =
+            milestone::snapshot_address(program_id, greeting_account.key, recorder.key, label);
+        if *snapshot.key != address {
+            log!(
+                "{} is not the snapshot of {} labeled {:?}",
+                snapshot.key,
+                greeting_account.key,
+                label
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            config,
+            greeting_account,
+            recorder,
+            snapshot,
+            snapshot_bump,
+            system_program,
+        })
+    }
+}
+
+/// Record a greeting account's counter and the time under a label
+#[cfg(feature = "program")]
+pub fn process_snapshot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    label: String,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<SnapshotAccounts>::load(program_id, accounts, tag::SNAPSHOT, &label)?;
+    let config = accounts.config;
+    let account = accounts.greeting_account;
+    let recorder = accounts.recorder;
+    let snapshot = accounts.snapshot;
+    if snapshot.owner != program_id {
+        let size = milestone::snapshot_size(&label);
+        let lamports = Rent::get()?.minimum_balance(size);
+        create_pda_account(
+            recorder,
+            snapshot,
+            accounts.system_program,
+            lamports,
+            size,
+            program_id,
+            &[
+                milestone::SNAPSHOT_SEED,
+                account.key.as_ref(),
+                recorder.key.as_ref(),
+                label.as_bytes(),
+                &[accounts.snapshot_bump],
+            ],
+        )?;
+    } else if milestone::GreetingSnapshot::unpack(&snapshot.data.borrow()).is_some() {
+        log!("{} took a snapshot labeled {:?} already", recorder.key, label);
+        return Err(HelloWorldError::LabelTaken.into());
+    }
+
+    let (_, greeting) = load_greeting(account)?;
+    let clock = Clock::get()?;
+    let (counter, _) = decay::touch(
+        greeting.counter,
+        greeting.last_update_epoch,
+        config.decay_half_life_epochs,
+        clock.epoch,
+    );
+    let record = milestone::GreetingSnapshot {
+        greeting_account: account.key.to_bytes(),
+        recorder: recorder.key.to_bytes(),
+        counter,
+        slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp.max(0) as u64,
+        label,
+    };
+    record.pack(&mut snapshot.data.borrow_mut()).map_err(|err| {
+        log!("Can't write the snapshot, {}", err);
+        ProgramError::InvalidAccountData
+    })?;
+    log!("Snapshot {:?} of {} holds counter {}", record.label, account.key, counter);
+    Ok(())
+}
+
+/// CountTag's accounts, with the bump of the tag counters
+#[cfg(feature = "program")]
+struct CountTagAccounts<'a, 'b> {
+    config: config::Config,
+    greeting_account: &'a AccountInfo<'b>,
+    payer: &'a AccountInfo<'b>,
+    tag_counters: &'a AccountInfo<'b>,
+    tag_counters_bump: u8,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for CountTagAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config = greet_config(program_id, accounts.get(accounts::tags::CONFIG_IDX)?)?;
+        let greeting_account = accounts.get(accounts::tags::GREETING_ACCOUNT_IDX)?;
+        let payer = accounts.get(accounts::tags::PAYER_IDX)?;
+        let tag_counters = accounts.get(accounts::tags::TAG_COUNTERS_IDX)?;
+        let system_program = accounts.get(accounts::tags::SYSTEM_PROGRAM_IDX)?;
+        if !payer.is_signer {
+            log!("The payer must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_greeting_account(program_id, greeting_account)?;
+        let (address, tag_counters_bump) =
+            tags::tag_counters_address(program_id, greeting_account.key);
+        if *tag_counters.key != address {
+            log!("{} is not the tag counters of {}", tag_counters.key, greeting_account.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            config,
+            greeting_account,
+            payer,
+            tag_counters,
+            tag_counters_bump,
+            system_program,
+        })
+    }
+}
+
+/// Count a greeting of the greeting account under `tag`, creating its tag counters on
+/// the first one
+#[cfg(feature = "program")]
+pub fn process_count_tag(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tag: [u8; 8],
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<CountTagAccounts>::load(program_id, accounts, tag::COUNT_TAG, &())?;
+    let account = accounts.greeting_account;
+    let tag_counters = accounts.tag_counters;
+    if tag[0] == 0 {
+        log!("A tag is named by 1 to 8 bytes padded with zeros");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_greeting_allowed(&accounts.config, 0)?;
+    if tag_counters.owner != program_id {
+        let lamports = Rent::get()?.minimum_balance(tags::TAG_COUNTERS_SIZE);
+        create_pda_account(
+            accounts.payer,
+            tag_counters,
+            accounts.system_program,
+            lamports,
+            tags::TAG_COUNTERS_SIZE,
+            program_id,
+            &[tags::TAGS_SEED, account.key.as_ref(), &[accounts.tag_counters_bump]],
+        )?;
+    }
+
+    let mut counters =
+        tags::TagCounters::unpack(&tag_counters.data.borrow()).unwrap_or_else(|| tags::TagCounters {
+            greeting_account: account.key.to_bytes(),
+            entries: vec![],
+        });
+    let count = match counters.increment(&tag) {
+        Some(count) => count,
+        None => {
+            log!("{} counts {} tags already", account.key, tags::MAX_TAGS);
+            return Err(HelloWorldError::TagsFull.into());
+        }
+    };
+    counters.pack(&mut tag_counters.data.borrow_mut()).map_err(|err| {
+        log!("Can't write the tag counters, {}", err);
+        ProgramError::InvalidAccountData
+    })?;
+    log!("{} counts {} under {:?}", account.key, count, tags::tag_name(&tag));
+    Ok(())
+}
+
+/// CheckRecency's SlotHashes sysvar
+#[cfg(feature = "program")]
+struct CheckRecencyAccounts<'a, 'b> {
+    slot_hashes: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for CheckRecencyAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(_: &Pubkey, accounts: &Accounts<'a, 'b>, _: &()) -> Result<Self, ProgramError> {
+        let slot_hashes = accounts.get(accounts::recency::SLOT_HASHES_IDX)?;
+        if !sysvar::slot_hashes::check_id(slot_hashes.key) {
+            log!("{} is not the SlotHashes sysvar", slot_hashes.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Self { slot_hashes })
+    }
+}
+
+/// Fail unless `slot_hash` is the hash of `slot` in the SlotHashes sysvar, with `slot`
+/// at most `max_age_slots` old
+#[cfg(feature = "program")]
+pub fn process_check_recency(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slot: u64,
+    slot_hash: [u8; 32],
+    max_age_slots: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<CheckRecencyAccounts>::load(program_id, accounts, tag::CHECK_RECENCY, &())?;
+    let slot_hashes = accounts.slot_hashes;
+    let age = Clock::get()?.slot.saturating_sub(slot);
+    if age > max_age_slots {
+        log!("Slot {} is {} slot(s) old, more than {}", slot, age, max_age_slots);
+        return Err(HelloWorldError::StaleSlotHash.into());
+    }
+    match recency::find_slot_hash(&slot_hashes.data.borrow(), slot) {
+        Some(hash) if *hash == slot_hash => {}
+        Some(_) => {
+            log!("Slot {} has another hash", slot);
+            return Err(HelloWorldError::StaleSlotHash.into());
+        }
+        None => {
+            log!("Slot {} is not in the SlotHashes sysvar", slot);
+            return Err(HelloWorldError::StaleSlotHash.into());
+        }
+    }
+    log!("Slot {} is {} slot(s) old", slot, age);
+    Ok(())
+}
+
+/// Read a Switchboard randomness account
+#[cfg(feature = "program")]
+fn load_randomness(account: &AccountInfo) -> Result<switchboard::Randomness, ProgramError> {
+    let randomness = if *account.owner == switchboard::mainnet::id()
+        || *account.owner == switchboard::devnet::id()
+    {
+        switchboard::Randomness::parse(&account.data.borrow())
+    } else {
+        None
+    };
+    randomness.ok_or_else(|| {
+        log!("{} is not a Switchboard randomness account", account.key);
+        HelloWorldError::InvalidRandomness.into()
+    })
+}
+
+/// Check `ticket` is the ticket of `greeting_account`, returning its bump seed
+#[cfg(feature = "program")]
+fn check_ticket_address(
+    program_id: &Pubkey,
+    greeting_account: &AccountInfo,
+    ticket: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (address, bump) = luck::ticket_address(program_id, greeting_account.key);
+    if *ticket.key != address {
+        log!("{} is not the ticket of {}", ticket.key, greeting_account.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump)
+}
+
+/// Read a ticket account
+#[cfg(feature = "program")]
+fn load_ticket(account: &AccountInfo) -> Result<luck::LuckTicket, ProgramError> {
+    luck::LuckTicket::try_from_slice(&account.data.borrow()).map_err(|err| {
+        log!("Can't read the ticket, {}", err);
+        ProgramError::InvalidAccountData
+    })
+}
+
+/// Write `ticket` back into its account
+#[cfg(feature = "program")]
+fn store_ticket(account: &AccountInfo, ticket: &luck::LuckTicket) -> ProgramResult {
+    ticket
+        .serialize(&mut &mut account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the ticket, {}", err);
+            ProgramError::InvalidAccountData
+        })
+}
+
+/// CommitLuck's accounts, with the greeting drawn for and the bump of the ticket
+#[cfg(feature = "program")]
+struct CommitLuckAccounts<'a, 'b> {
+    greeting_account: &'a AccountInfo<'b>,
+    greeting: GreetingAccount,
+    ticket_account: &'a AccountInfo<'b>,
+    ticket_bump: u8,
+    randomness_account: &'a AccountInfo<'b>,
+    greeter: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for CommitLuckAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let greeting_account = accounts.get(accounts::luck::GREETING_ACCOUNT_IDX)?;
+        let ticket_account = accounts.get(accounts::luck::TICKET_IDX)?;
+        let randomness_account = accounts.get(accounts::luck::RANDOMNESS_IDX)?;
+        let greeter = accounts.get(accounts::luck::COMMIT_GREETER_IDX)?;
+        let system_program = accounts.get(accounts::luck::COMMIT_SYSTEM_PROGRAM_IDX)?;
+        if !greeter.is_signer {
+            log!("The greeter must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_greeting_account(program_id, greeting_account)?;
+        let (_, greeting) = load_greeting(greeting_account)?;
+        let ticket_bump = check_ticket_address(program_id, greeting_account, ticket_account)?;
+        Ok(Self {
+            greeting_account,
+            greeting,
+            ticket_account,
+            ticket_bump,
+            randomness_account,
+            greeter,
+            system_program,
+        })
+    }
+}
+
+/// RevealLuck's accounts, with the pending ticket and the bump of the vault
+#[cfg(feature = "program")]
+struct RevealLuckAccounts<'a, 'b> {
+    greeting_account: &'a AccountInfo<'b>,
+    ticket_account: &'a AccountInfo<'b>,
+    ticket: luck::LuckTicket,
+    randomness_account: &'a AccountInfo<'b>,
+    vault: &'a AccountInfo<'b>,
+    vault_bump: u8,
+    greeter: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for RevealLuckAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let greeting_account = accounts.get(accounts::luck::GREETING_ACCOUNT_IDX)?;
+        let ticket_account = accounts.get(accounts::luck::TICKET_IDX)?;
+        let randomness_account = accounts.get(accounts::luck::RANDOMNESS_IDX)?;
+        let vault = accounts.get(accounts::luck::VAULT_IDX)?;
+        let greeter = accounts.get(accounts::luck::REVEAL_GREETER_IDX)?;
+        let system_program = accounts.get(accounts::luck::REVEAL_SYSTEM_PROGRAM_IDX)?;
+        check_ticket_address(program_id, greeting_account, ticket_account)?;
+        if ticket_account.owner != program_id {
+            log!("{} has no ticket", greeting_account.key);
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let ticket = load_ticket(ticket_account)?;
+        if !ticket.is_pending() || randomness_account.key.to_bytes() != ticket.randomness {
+            log!("{} is not the randomness committed to", randomness_account.key);
+            return Err(HelloWorldError::InvalidRandomness.into());
+        }
+        if greeter.key.to_bytes() != ticket.greeter {
+            log!("{} is not the greeter who committed", greeter.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (address, vault_bump) = luck::vault_address(program_id);
+        if *vault.key != address {
+            log!("{} is not the vault", vault.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(Self {
+            greeting_account,
+            ticket_account,
+            ticket,
+            randomness_account,
+            vault,
+            vault_bump,
+            greeter,
+            system_program,
+        })
+    }
+}
+
+/// Enter the greeting account's latest greeting in the draw, committing to randomness
+/// seeded in the previous slot
+#[cfg(feature = "program")]
+pub fn process_commit_luck(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<CommitLuckAccounts>::load(program_id, accounts, tag::COMMIT_LUCK, &())?;
+    let greeting_account = accounts.greeting_account;
+    let greeting = accounts.greeting;
+    let ticket_account = accounts.ticket_account;
+    let randomness_account = accounts.randomness_account;
+    let greeter = accounts.greeter;
+    let system_program = accounts.system_program;
+    let clock = Clock::get()?;
+    let randomness = load_randomness(randomness_account)?;
+    if !randomness.is_fresh(clock.slot) {
+        log!(
+            "Randomness was seeded in slot {}, not the one before {}",
+            randomness.seed_slot,
+            clock.slot
+        );
+        return Err(HelloWorldError::InvalidRandomness.into());
+    }
+
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    if ticket_account.owner != program_id {
+        validate_program_account(system_program, &system_program::id())?;
+        let lamports = Rent::get()?.minimum_balance(luck::TICKET_SIZE);
+        create_pda_account(
+            greeter,
+            ticket_account,
+            system_program,
+            lamports,
+            luck::TICKET_SIZE,
+            program_id,
+            &[luck::TICKET_SEED, greeting_account.key.as_ref(), &[accounts.ticket_bump]],
+        )?;
+    }
+    let mut ticket = load_ticket(ticket_account)?;
+    // NOTE Whoever commits can learn the value from the oracle before revealing it, and
+    // simply not reveal a losing draw. Each greeting only ever gets one draw, so that
+    // doesn't buy a second chance: the next one takes another Greet.
+    if ticket.counter == greeting.counter && ticket.last_update_epoch == greeting.last_update_epoch
+    {
+        log!("{} was already drawn for since its last greeting", greeting_account.key);
+        return Err(HelloWorldError::NoNewGreeting.into());
+    }
+    ticket.greeter = greeter.key.to_bytes();
+    ticket.randomness = randomness_account.key.to_bytes();
+    ticket.seed_slot = randomness.seed_slot;
+    ticket.counter = greeting.counter;
+    ticket.last_update_epoch = greeting.last_update_epoch;
+    store_ticket(ticket_account, &ticket)?;
+    log!(
+        "Committed greeting {} of {} to randomness {}",
+        greeting.counter,
+        greeting_account.key,
+        randomness_account.key
+    );
+    Ok(())
+}
+
+/// Settle the greeting account's draw, paying the vault out to the greeter on a win
+#[cfg(feature = "program")]
+pub fn process_reveal_luck(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<RevealLuckAccounts>::load(program_id, accounts, tag::REVEAL_LUCK, &())?;
+    let greeting_account = accounts.greeting_account;
+    let ticket_account = accounts.ticket_account;
+    let randomness_account = accounts.randomness_account;
+    let vault = accounts.vault;
+    let greeter = accounts.greeter;
+    let mut ticket = accounts.ticket;
+    let clock = Clock::get()?;
+    let randomness = load_randomness(randomness_account)?;
+    let value = if randomness.seed_slot == ticket.seed_slot {
+        randomness.value(clock.slot)
+    } else {
+        None
+    };
+    let value = value.ok_or_else(|| {
+        log!("Randomness {} is not revealed in slot {}", randomness_account.key, clock.slot);
+        ProgramError::from(HelloWorldError::RandomnessNotRevealed)
+    })?;
+
+    ticket.randomness = [0; 32];
+    store_ticket(ticket_account, &ticket)?;
+    if !luck::is_winner(&value) {
+        log!("Greeting {} of {} didn't win", ticket.counter, greeting_account.key);
+        return Ok(());
+    }
+    let lamports = vault.lamports();
+    validate_program_account(accounts.system_program, &system_program::id())?;
+    invoke_signed(
+        &system_instruction::transfer(vault.key, greeter.key, lamports),
+        &[vault.clone(), greeter.clone(), accounts.system_program.clone()],
+        &[&[luck::VAULT_SEED, &[accounts.vault_bump]]],
+    )?;
+    log!(
+        "Greeting {} of {} won {} lamports",
+        ticket.counter,
+        greeting_account.key,
+        lamports
+    );
+    Ok(())
+}
+
+/// Read a challenge account, checking it's a challenge of this program
+#[cfg(feature = "program")]
+fn load_challenge(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<challenge::Challenge, ProgramError> {
+    if account.owner != program_id {
+        log!("{} is not an open challenge", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let challenge = challenge::Challenge::try_from_slice(&account.data.borrow()).map_err(|err| {
+        log!("Can't read the challenge, {}", err);
+        ProgramError::InvalidAccountData
+    })?;
+    // NOTE Other accounts of this program can decode as a challenge too (a greeting
+    // account's message is up to whoever greets), so only trust the one at the address
+    // its own challenger and challengee derive
+    let (address, _) = challenge::challenge_address(
+        program_id,
+        &Pubkey::new_from_array(challenge.challenger),
+        &Pubkey::new_from_array(challenge.challengee),
+    );
+    if *account.key != address {
+        log!("{} is not a challenge", account.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(challenge)
+}
+
+/// Close a program account, moving all its lamports to `recipient`
+#[cfg(feature = "program")]
+pub fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **recipient.lamports.borrow_mut() = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **account.lamports.borrow_mut() = 0;
+    account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+/// CreateChallenge's accounts, with the challengee's greeting and the bump of the
+/// challenge
+#[cfg(feature = "program")]
+struct CreateChallengeAccounts<'a, 'b> {
+    challenge_account: &'a AccountInfo<'b>,
+    challenge_bump: u8,
+    challenger: &'a AccountInfo<'b>,
+    challengee: &'a AccountInfo<'b>,
+    greeting_account: &'a AccountInfo<'b>,
+    greeting: GreetingAccount,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for CreateChallengeAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let challenge_account = accounts.get(accounts::challenge::CHALLENGE_IDX)?;
+        let challenger = accounts.get(accounts::challenge::CHALLENGER_IDX)?;
+        let challengee = accounts.get(accounts::challenge::CREATE_CHALLENGEE_IDX)?;
+        let greeting_account = accounts.get(accounts::challenge::CREATE_GREETING_ACCOUNT_IDX)?;
+        let system_program = accounts.get(accounts::challenge::SYSTEM_PROGRAM_IDX)?;
+        if !challenger.is_signer {
+            log!("The challenger must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        check_greeting_account(program_id, greeting_account)?;
+        let (_, greeting) = load_greeting(greeting_account)?;
+        let (address, challenge_bump) =
+            challenge::challenge_address(program_id, challenger.key, challengee.key);
+        if *challenge_account.key != address {
+            log!(
+                "{} is not the challenge of {} to {}",
+                challenge_account.key,
+                challenger.key,
+                challengee.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if challenge_account.owner == program_id {
+            log!("{} already challenges {}", challenger.key, challengee.key);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            challenge_account,
+            challenge_bump,
+            challenger,
+            challengee,
+            greeting_account,
+            greeting,
+            system_program,
+        })
+    }
+}
+
+/// ClaimChallenge's challenge, its challengee signing and the greeting they were
+/// challenged to
+#[cfg(feature = "program")]
+struct ClaimChallengeAccounts<'a, 'b> {
+    challenge_account: &'a AccountInfo<'b>,
+    challenge: challenge::Challenge,
+    challengee: &'a AccountInfo<'b>,
+    greeting: GreetingAccount,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ClaimChallengeAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let challenge_account = accounts.get(accounts::challenge::CHALLENGE_IDX)?;
+        let challengee = accounts.get(accounts::challenge::CLAIM_CHALLENGEE_IDX)?;
+        let greeting_account = accounts.get(accounts::challenge::CLAIM_GREETING_ACCOUNT_IDX)?;
+        if !challengee.is_signer {
+            log!("The challengee must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let challenge = load_challenge(program_id, challenge_account)?;
+        if challengee.key.to_bytes() != challenge.challengee {
+            log!("{} is not the challengee", challengee.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if greeting_account.key.to_bytes() != challenge.greeting_account {
+            log!("{} is not the challenge's greeting account", greeting_account.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        check_greeting_account(program_id, greeting_account)?;
+        let (_, greeting) = load_greeting(greeting_account)?;
+        Ok(Self {
+            challenge_account,
+            challenge,
+            challengee,
+            greeting,
+        })
+    }
+}
+
+/// RefundChallenge's challenge and its challenger, signing
+#[cfg(feature = "program")]
+struct RefundChallengeAccounts<'a, 'b> {
+    challenge_account: &'a AccountInfo<'b>,
+    challenge: challenge::Challenge,
+    challenger: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for RefundChallengeAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let challenge_account = accounts.get(accounts::challenge::CHALLENGE_IDX)?;
+        let challenger = accounts.get(accounts::challenge::CHALLENGER_IDX)?;
+        if !challenger.is_signer {
+            log!("The challenger must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let challenge = load_challenge(program_id, challenge_account)?;
+        if challenger.key.to_bytes() != challenge.challenger {
+            log!("{} is not the challenger", challenger.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Self {
+            challenge_account,
+            challenge,
+            challenger,
+        })
+    }
+}
+
+/// Escrow lamports challenging the challengee to greet the greeting account
+#[cfg(feature = "program")]
+pub fn process_create_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    greet_count: u64,
+    deadline_slot: u64,
+    amount: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } = Context::<CreateChallengeAccounts>::load(
+        program_id,
+        accounts,
+        tag::CREATE_CHALLENGE,
+        &(),
+    )?;
+    let challenge_account = accounts.challenge_account;
+    let challenger = accounts.challenger;
+    let challengee = accounts.challengee;
+    let greeting_account = accounts.greeting_account;
+    let system_program = accounts.system_program;
+    if greet_count == 0 || amount == 0 {
+        log!("A challenge takes at least one greeting and one lamport");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let clock = Clock::get()?;
+    if deadline_slot <= clock.slot {
+        log!("Deadline {} is not after slot {}", deadline_slot, clock.slot);
+        return Err(HelloWorldError::ChallengeExpired.into());
+    }
+    let counter = accounts.greeting.counter;
+    let target_counter = counter.checked_add(greet_count).ok_or_else(|| {
+        log!("Counter {} can't go up by {}", counter, greet_count);
+        ProgramError::InvalidArgument
+    })?;
+
+    let lamports = Rent::get()?
+        .minimum_balance(challenge::CHALLENGE_SIZE)
+        .checked_add(amount)
+        .ok_or(ProgramError::InvalidArgument)?;
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    create_pda_account(
+        challenger,
+        challenge_account,
+        system_program,
+        lamports,
+        challenge::CHALLENGE_SIZE,
+        program_id,
+        &[
+            challenge::CHALLENGE_SEED,
+            challenger.key.as_ref(),
+            challengee.key.as_ref(),
+            &[accounts.challenge_bump],
+        ],
+    )?;
+    let challenge = challenge::Challenge {
+        challenger: challenger.key.to_bytes(),
+        challengee: challengee.key.to_bytes(),
+        greeting_account: greeting_account.key.to_bytes(),
+        target_counter,
+        deadline_slot,
+        amount,
+    };
+    challenge
+        .serialize(&mut &mut challenge_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the challenge, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    log!(
+        "Escrowed {} lamports for {} to greet up to {}",
+        amount,
+        challengee.key,
+        target_counter
+    );
+    Ok(())
+}
+
+/// Pay a met challenge out to the challengee
+#[cfg(feature = "program")]
+pub fn process_claim_challenge(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<ClaimChallengeAccounts>::load(program_id, accounts, tag::CLAIM_CHALLENGE, &())?;
+    let challengee = accounts.challengee;
+    let challenge = accounts.challenge;
+    let clock = Clock::get()?;
+    if challenge.is_expired(clock.slot) {
+        log!("The deadline was slot {}", challenge.deadline_slot);
+        return Err(HelloWorldError::ChallengeExpired.into());
+    }
+    let counter = accounts.greeting.counter;
+    if !challenge.is_met(counter) {
+        log!("Counter is {} of {}", counter, challenge.target_counter);
+        return Err(HelloWorldError::ChallengeNotMet.into());
+    }
+
+    close_account(accounts.challenge_account, challengee)?;
+    log!("{} won {} lamports", challengee.key, challenge.amount);
+    Ok(())
+}
+
+/// Refund an expired challenge to the challenger
+#[cfg(feature = "program")]
+pub fn process_refund_challenge(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<RefundChallengeAccounts>::load(program_id, accounts, tag::REFUND_CHALLENGE, &())?;
+    let challenger = accounts.challenger;
+    let challenge = accounts.challenge;
+    let clock = Clock::get()?;
+    if !challenge.is_expired(clock.slot) {
+        log!("The deadline is slot {}", challenge.deadline_slot);
+        return Err(HelloWorldError::ChallengeNotExpired.into());
+    }
+
+    close_account(accounts.challenge_account, challenger)?;
+    log!("Refunded {} lamports to {}", challenge.amount, challenger.key);
+    Ok(())
+}
+
+/// Read a VAA posted to the Wormhole core bridge
+#[cfg(feature = "program")]
+fn load_posted_vaa(account: &AccountInfo) -> Result<wormhole::PostedVaa, ProgramError> {
+    let vaa = if *account.owner == wormhole::mainnet::id()
+        || *account.owner == wormhole::devnet::id()
+    {
+        wormhole::PostedVaa::parse(&account.data.borrow())
+    } else {
+        None
+    };
+    vaa.ok_or_else(|| {
+        log!("{} is not a posted Wormhole VAA", account.key);
+        HelloWorldError::InvalidVaa.into()
+    })
+}
+
+/// RegisterForeignGreeter's accounts, with the config checked to be the authority's
+/// and the bump of the foreign greeter
+#[cfg(feature = "program")]
+struct RegisterForeignGreeterAccounts<'a, 'b> {
+    authority: &'a AccountInfo<'b>,
+    greeter_account: &'a AccountInfo<'b>,
+    greeter_bump: u8,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for RegisterForeignGreeterAccounts<'a, 'b> {
+    type Args = (u16, [u8; 32]);
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        (chain, sender): &(u16, [u8; 32]),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(accounts::cross_chain::REGISTER_CONFIG_IDX)?;
+        let authority = accounts.get(accounts::cross_chain::REGISTER_AUTHORITY_IDX)?;
+        let greeter_account = accounts.get(accounts::cross_chain::REGISTER_FOREIGN_GREETER_IDX)?;
+        let system_program = accounts.get(accounts::cross_chain::REGISTER_SYSTEM_PROGRAM_IDX)?;
+        load_config_as_authority(program_id, config_account, authority)?;
+        let (address, greeter_bump) = wormhole::foreign_greeter_address(program_id, *chain, sender);
+        if *greeter_account.key != address {
+            log!("{} is not the foreign greeter of the emitter", greeter_account.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if greeter_account.owner == program_id {
+            let sender = Pubkey::new_from_array(*sender);
+            log!("Chain {} sender {} is already registered", chain, sender);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            authority,
+            greeter_account,
+            greeter_bump,
+            system_program,
+        })
+    }
+}
+
+/// Let the emitter `sender` on the Wormhole chain `chain` greet, creating its foreign
+/// greeter
+#[cfg(feature = "program")]
+pub fn process_register_foreign_greeter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    chain: u16,
+    sender: [u8; 32],
+) -> ProgramResult {
+    let Context { accounts, .. } = Context::<RegisterForeignGreeterAccounts>::load(
+        program_id,
+        accounts,
+        tag::REGISTER_FOREIGN_GREETER,
+        &(chain, sender),
+    )?;
+    let greeter_account = accounts.greeter_account;
+
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    let lamports = Rent::get()?.minimum_balance(wormhole::FOREIGN_GREETER_SIZE);
+    create_pda_account(
+        accounts.authority,
+        greeter_account,
+        accounts.system_program,
+        lamports,
+        wormhole::FOREIGN_GREETER_SIZE,
+        program_id,
+        &[
+            wormhole::FOREIGN_GREETER_SEED,
+            &chain.to_be_bytes(),
+            &sender,
+            &[accounts.greeter_bump],
+        ],
+    )?;
+    let greeter = wormhole::ForeignGreeter {
+        chain,
+        sender,
+        ..wormhole::ForeignGreeter::default()
+    };
+    greeter
+        .serialize(&mut &mut greeter_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the foreign greeter, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    log!("Chain {} sender {} can greet", chain, Pubkey::new_from_array(sender));
+    Ok(())
+}
+
+/// ReceiveCrossChainGreet's accounts, with the VAA posted and the foreign greeter its
+/// emitter was registered with
+#[cfg(feature = "program")]
+struct ReceiveCrossChainGreetAccounts<'a, 'b> {
+    vaa: wormhole::PostedVaa,
+    greeter_account: &'a AccountInfo<'b>,
+    greeter: wormhole::ForeignGreeter,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for ReceiveCrossChainGreetAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let vaa_account = accounts.get(accounts::cross_chain::POSTED_VAA_IDX)?;
+        let greeter_account = accounts.get(accounts::cross_chain::FOREIGN_GREETER_IDX)?;
+        let vaa = load_posted_vaa(vaa_account)?;
+        let (address, _) =
+            wormhole::foreign_greeter_address(program_id, vaa.emitter_chain, &vaa.emitter_address);
+        if *greeter_account.key != address {
+            log!("{} is not the foreign greeter of the VAA's emitter", greeter_account.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let greeter = if greeter_account.owner == program_id {
+            wormhole::ForeignGreeter::try_from_slice(&greeter_account.data.borrow()).ok()
+        } else {
+            None
+        };
+        let greeter = greeter
+            .filter(|greeter| {
+                greeter.chain == vaa.emitter_chain && greeter.sender == vaa.emitter_address
+            })
+            .ok_or_else(|| {
+                log!(
+                    "Chain {} sender {} is not registered",
+                    vaa.emitter_chain,
+                    Pubkey::new_from_array(vaa.emitter_address)
+                );
+                ProgramError::from(HelloWorldError::UnregisteredEmitter)
+            })?;
+        Ok(Self {
+            vaa,
+            greeter_account,
+            greeter,
+        })
+    }
+}
+
+/// Count a greeting from another chain, sent as a Wormhole message
+#[cfg(feature = "program")]
+pub fn process_receive_cross_chain_greet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let Context { accounts, .. } = Context::<ReceiveCrossChainGreetAccounts>::load(
+        program_id,
+        accounts,
+        tag::RECEIVE_CROSS_CHAIN_GREET,
+        &(),
+    )?;
+    let vaa = accounts.vaa;
+    let greeter_account = accounts.greeter_account;
+    let mut greeter = accounts.greeter;
+
+    let txt = wormhole::decode_greeting(&vaa.payload).ok_or_else(|| {
+        log!("VAA {} carries no greeting", vaa.sequence);
+        ProgramError::from(HelloWorldError::NotAGreeting)
+    })?;
+    if !greeter.is_new(vaa.sequence) {
+        log!("VAA {} is not past {}", vaa.sequence, greeter.last_sequence);
+        return Err(HelloWorldError::VaaAlreadyReceived.into());
+    }
+    greeter.counter = greeter.counter.saturating_add(1);
+    greeter.last_sequence = vaa.sequence;
+    greeter
+        .serialize(&mut &mut greeter_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the foreign greeter, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    log!("Foreign greeting: {}", txt);
+    log!(
+        "Chain {} sender {} greeted {} time(s)",
+        vaa.emitter_chain,
+        Pubkey::new_from_array(vaa.emitter_address),
+        greeter.counter
+    );
+    Ok(())
+}
+
+/// The accounts a greeting is logged with
+#[cfg(feature = "program")]
+struct GreetingLog<'a, 'b> {
+    tree: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    authority_bump: u8,
+    compression_program: &'a AccountInfo<'b>,
+    noop_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> GreetingLog<'a, 'b> {
+    /// Log `leaf` through the noop program and append its hash to the tree
+    fn append(&self, leaf: &compression::GreetingLeaf) -> ProgramResult {
+        invoke(&compression::log_leaf(leaf), std::slice::from_ref(self.noop_program))?;
+        // NOTE The log authority owns the tree, so only this program can append to it
+        invoke_signed(
+            &compression::append(self.tree.key, self.authority.key, &leaf.hash()),
+            &[
+                self.tree.clone(),
+                self.authority.clone(),
+                self.noop_program.clone(),
+                self.compression_program.clone(),
+            ],
+            &[&[compression::LOG_AUTHORITY_SEED, &[self.authority_bump]]],
+        )
+    }
+}
+
+/// The accounts a greeting's receipt is created with
+#[cfg(feature = "program")]
+struct ReceiptAccounts<'a, 'b> {
+    receipt: &'a AccountInfo<'b>,
+    signer: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> ReceiptAccounts<'a, 'b> {
+    /// Create the receipt of the greeting of `account` whose counter read `counter`
+    fn create(
+        &self,
+        program_id: &Pubkey,
+        account: &AccountInfo,
+        counter: u64,
+        clock: &Clock,
+    ) -> ProgramResult {
+        let (address, bump) = receipt::receipt_address(program_id, account.key, counter);
+        if *self.receipt.key != address {
+            log!("{} is not the receipt of {} at {}", self.receipt.key, account.key, counter);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if self.receipt.owner != program_id {
+            let lamports = Rent::get()?.minimum_balance(receipt::RECEIPT_SIZE);
+            create_pda_account(
+                self.signer,
+                self.receipt,
+                self.system_program,
+                lamports,
+                receipt::RECEIPT_SIZE,
+                program_id,
+                &[receipt::RECEIPT_SEED, account.key.as_ref(), &counter.to_le_bytes(), &[bump]],
+            )?;
+        } else if receipt::GreetingReceipt::unpack(&self.receipt.data.borrow()).is_some() {
+            log!("The receipt of {} at {} exists already", account.key, counter);
+            return Err(HelloWorldError::ReceiptExists.into());
+        }
+        let receipt = receipt::GreetingReceipt {
+            signer: self.signer.key.to_bytes(),
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp.max(0) as u64,
+        };
+        receipt.pack(&mut self.receipt.data.borrow_mut());
+        log!("Left receipt {}", self.receipt.key);
+        Ok(())
+    }
+}
+
+/// Take the receipt, its signer and the system program off `accounts_iter`
+#[cfg(feature = "program")]
+fn next_receipt_accounts<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    accounts_iter: &mut I,
+) -> Result<ReceiptAccounts<'a, 'b>, ProgramError> {
+    let receipt = next_account_info(accounts_iter)?;
+    let signer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    if !signer.is_signer {
+        log!("The receipt's signer must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    validate_program_account(system_program, &system_program::id())?;
+    Ok(ReceiptAccounts {
+        receipt,
+        signer,
+        system_program,
+    })
+}
+
+/// Take the greeting log's tree, log authority and programs off `accounts_iter`
+#[cfg(feature = "program")]
+fn next_greeting_log<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+    program_id: &Pubkey,
+    accounts_iter: &mut I,
+) -> Result<GreetingLog<'a, 'b>, ProgramError> {
+    let tree = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let compression_program = next_account_info(accounts_iter)?;
+    let noop_program = next_account_info(accounts_iter)?;
+    let (address, authority_bump) = compression::log_authority_address(program_id);
+    if *authority.key != address {
+        log!("{} is not the log authority", authority.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    validate_program_account(compression_program, &compression::account_compression::id())?;
+    validate_program_account(noop_program, &compression::noop::id())?;
+    Ok(GreetingLog {
+        tree,
+        authority,
+        authority_bump,
+        compression_program,
+        noop_program,
+    })
+}
+
+/// Initialize a merkle tree and log every greeting to it from now on
+#[cfg(feature = "program")]
+pub fn process_init_greeting_log(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> ProgramResult {
+    let Context {
+        accounts,
+        mut remaining,
+    } = Context::<AdminAccounts>::load(program_id, accounts, tag::INIT_GREETING_LOG, &())?;
+    let config_account = accounts.config_account;
+    let mut config = accounts.config;
+    let old = config.clone();
+    let log = next_greeting_log(program_id, &mut remaining)?;
+    if *log.tree.owner != compression::account_compression::id() {
+        log!("{} is not owned by spl-account-compression", log.tree.key);
+        return Err(HelloWorldError::InvalidGreetingLog.into());
+    }
+    invoke_signed(
+        &compression::init_empty_merkle_tree(
+            log.tree.key,
+            log.authority.key,
+            max_depth,
+            max_buffer_size,
+        ),
+        &[
+            log.tree.clone(),
+            log.authority.clone(),
+            log.noop_program.clone(),
+            log.compression_program.clone(),
+        ],
+        &[&[compression::LOG_AUTHORITY_SEED, &[log.authority_bump]]],
+    )?;
+    config.greeting_log = log.tree.key.to_bytes();
+    store_config(config_account, &config)?;
+    log!("Greetings are now logged to {}", log.tree.key);
+    Event::config_changed(&old, &config).emit();
+    Ok(())
+}
+
+/// Read a session token
+#[cfg(feature = "program")]
+fn load_session(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<session::SessionToken, ProgramError> {
+    let session = if account.owner == program_id {
+        session::SessionToken::try_from_slice(&account.data.borrow()).ok()
+    } else {
+        None
+    };
+    // NOTE The owner check alone doesn't tell a session token from the program's other
+    // accounts of the same size, the address derived from its own fields does
+    match session {
+        Some(session)
+            if *account.key
+                == session::session_address(
+                    program_id,
+                    &Pubkey::new_from_array(session.authority),
+                    &Pubkey::new_from_array(session.session_key),
+                )
+                .0 =>
+        {
+            Ok(session)
+        }
+        _ => {
+            log!("{} is not a session token", account.key);
+            Err(HelloWorldError::InvalidSession.into())
+        }
+    }
+}
+
+/// CreateSession's accounts, with the bump of the session token
+#[cfg(feature = "program")]
+struct CreateSessionAccounts<'a, 'b> {
+    session_account: &'a AccountInfo<'b>,
+    session_bump: u8,
+    authority: &'a AccountInfo<'b>,
+    session_key: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for CreateSessionAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let session_account = accounts.get(accounts::session::SESSION_TOKEN_IDX)?;
+        let authority = accounts.get(accounts::session::AUTHORITY_IDX)?;
+        let session_key = accounts.get(accounts::session::SESSION_KEY_IDX)?;
+        let system_program = accounts.get(accounts::session::SYSTEM_PROGRAM_IDX)?;
+        // NOTE The session key signs too, so nobody can hand out a session to a key they
+        // don't hold
+        if !authority.is_signer || !session_key.is_signer {
+            log!("The authority and the session key must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let (address, session_bump) =
+            session::session_address(program_id, authority.key, session_key.key);
+        if *session_account.key != address {
+            log!(
+                "{} is not the session of {} for {}",
+                session_account.key,
+                authority.key,
+                session_key.key
+            );
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        Ok(Self {
+            session_account,
+            session_bump,
+            authority,
+            session_key,
+            system_program,
+        })
+    }
+}
+
+/// RevokeSession's session token and its authority, signing
+#[cfg(feature = "program")]
+struct RevokeSessionAccounts<'a, 'b> {
+    session_account: &'a AccountInfo<'b>,
+    session: session::SessionToken,
+    authority: &'a AccountInfo<'b>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for RevokeSessionAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let session_account = accounts.get(accounts::session::SESSION_TOKEN_IDX)?;
+        let authority = accounts.get(accounts::session::AUTHORITY_IDX)?;
+        if !authority.is_signer {
+            log!("The authority must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let session = load_session(program_id, session_account)?;
+        if authority.key.to_bytes() != session.authority {
+            log!("{} is not the session's authority", authority.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Self {
+            session_account,
+            session,
+            authority,
+        })
+    }
+}
+
+/// Let the session key greet for the authority until the session runs out
+#[cfg(feature = "program")]
+pub fn process_create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    duration_seconds: u64,
+    top_up_lamports: u64,
+) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<CreateSessionAccounts>::load(program_id, accounts, tag::CREATE_SESSION, &())?;
+    let session_account = accounts.session_account;
+    let authority = accounts.authority;
+    let session_key = accounts.session_key;
+    let system_program = accounts.system_program;
+    if duration_seconds == 0 || duration_seconds > session::MAX_SESSION_SECONDS {
+        log!("A session lasts 1 to {} seconds", session::MAX_SESSION_SECONDS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Creating the session again extends it
+    if session_account.owner != program_id {
+        create_pda_account(
+            authority,
+            session_account,
+            system_program,
+            Rent::get()?.minimum_balance(session::SESSION_TOKEN_SIZE),
+            session::SESSION_TOKEN_SIZE,
+            program_id,
+            &[
+                session::SESSION_SEED,
+                authority.key.as_ref(),
+                session_key.key.as_ref(),
+                &[accounts.session_bump],
+            ],
+        )?;
+    }
+    if top_up_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(authority.key, session_key.key, top_up_lamports),
+            &[authority.clone(), session_key.clone(), system_program.clone()],
+        )?;
+    }
+    let session = session::SessionToken {
+        authority: authority.key.to_bytes(),
+        session_key: session_key.key.to_bytes(),
+        valid_until: (Clock::get()?.unix_timestamp.max(0) as u64).saturating_add(duration_seconds),
+    };
+    session
+        .serialize(&mut &mut session_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            log!("Can't write the session token, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    log!("{} greets for {} until {}", session_key.key, authority.key, session.valid_until);
+    Ok(())
+}
+
+/// End a session before it runs out
+#[cfg(feature = "program")]
+pub fn process_revoke_session(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let Context { accounts, .. } =
+        Context::<RevokeSessionAccounts>::load(program_id, accounts, tag::REVOKE_SESSION, &())?;
+    close_account(accounts.session_account, accounts.authority)?;
+    log!(
+        "Revoked the session of {}",
+        Pubkey::new_from_array(accounts.session.session_key)
+    );
+    Ok(())
+}
+
+/// Count a greeting of the greeting account without changing its message
+// NOTE A successful Increment doesn't touch the heap (the entrypoint's deserializing of
+// the accounts aside): the account is updated in place (see increment.rs) and every
+// line it logs is a plain string, as formatting one allocates it. That's also why the
+// fee and the greeting log, whose CPIs build their instructions on the heap, are left
+// to Greet.
+#[cfg(feature = "program")]
+pub fn process_increment(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let (accounts, instructions) = origin::split_instructions_sysvar(accounts);
+    let Context { accounts, .. } =
+        Context::<GreetAccounts>::load(program_id, accounts, tag::INCREMENT, &())?;
+    let account = accounts.greeting_account;
+    let config = accounts.config;
+    if config.charges_fee()
+        || config.has_greeting_log()
+        || config.greeting_receipts
+        || config.weighs_balance()
+    {
+        log!("The config needs Greet's fee, log, receipt or balance accounts, Greet instead");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_greeting_allowed(&config, 0)?;
+    let clock = Clock::get()?;
+    if let Some((year, days)) = increment::calendar(&account.data.borrow()) {
+        if config.on_cooldown(year, &days, clock.unix_timestamp) {
+            log!("Greeted within the cooldown");
+            return Err(HelloWorldError::OnCooldown.into());
+        }
+    }
+    let origin = origin::origin(program_id, instructions)?;
+    let weight = epoch_bonus(&config, &clock, 1)?;
+    let counted = increment::increment(
+        &mut account.data.borrow_mut(),
+        weight,
+        config.decay_half_life_epochs,
+        clock.epoch,
+        clock.unix_timestamp,
+        origin,
+    );
+    if counted.is_none() {
+        if migrations::stored_version(&account.data.borrow()).is_err() {
+            log!("{} holds no greeting", account.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        log!("Increment needs an account on the current schema, Greet it first");
+        return Err(HelloWorldError::MigrationRequired.into());
+    }
+    state_hash::log(account.key, &account.data.borrow());
+    log!("Counted a greeting");
+    Ok(())
+}
+
+/// GreetMany's config, the greeting accounts follow it
+#[cfg(feature = "program")]
+struct GreetManyAccounts<'a, 'b> {
+    config_account: &'a AccountInfo<'b>,
+    config: config::Config,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for GreetManyAccounts<'a, 'b> {
+    type Args = ();
+    // NOTE Each greeting account is checked as it's greeted, see check_one_of_many()
+    const REMAINING_IDX: usize = greet_many::GREETING_ACCOUNTS_IDX;
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let config_account = accounts.get(greet_many::CONFIG_IDX)?;
+        let config = greet_config(program_id, config_account)?;
+        Ok(Self {
+            config_account,
+            config,
+        })
+    }
+}
+
+/// Save the same message into every greeting account passed, skipping the ones that
+/// can't be greeted
+// NOTE The per-account and summary lines are msg!, not log!: scripts read them to
+// know which accounts to retry. Only when no account could be greeted does the
+// instruction fail, with the last account's error.
+#[cfg(feature = "program")]
+pub fn process_greet_many(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    txt: String,
+) -> ProgramResult {
+    let (accounts, instructions) = origin::split_instructions_sysvar(accounts);
+    let Context {
+        accounts,
+        remaining: greeting_accounts,
+    } = Context::<GreetManyAccounts>::load(program_id, accounts, tag::GREET_MANY, &())?;
+    let config_account = accounts.config_account;
+    let config = accounts.config;
+    if config.charges_fee()
+        || config.has_greeting_log()
+        || config.greeting_receipts
+        || config.weighs_balance()
+    {
+        log!("The config needs Greet's fee, log, receipt or balance accounts, Greet each instead");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_greeting_allowed(&config, txt.len())?;
+    let total = greeting_accounts.len();
+    if total > config.greet_many_limit() {
+        log!("GreetMany takes at most {} accounts, got {}", config.greet_many_limit(), total);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let clock = Clock::get()?;
+    let weight = epoch_bonus(&config, &clock, 1)?;
+    let origin = origin::origin(program_id, instructions)?;
+    let mut greeted = 0;
+    let mut last_error = ProgramError::NotEnoughAccountKeys;
+    for (index, account) in greeting_accounts.enumerate() {
+        let greeted_one = check_one_of_many(program_id, config_account, account)
+            .and_then(|_| save_greeting(account, &config, &clock, &txt, weight, origin));
+        match greeted_one {
+            Ok(_) => greeted += 1,
+            Err(err) => {
+                msg!("GreetMany skipped {} {}: {:?}", index, account.key, err);
+                last_error = err;
+            }
+        }
+    }
+    msg!("GreetMany greeted {} of {}", greeted, total);
+    if greeted == 0 {
+        return Err(last_error);
+    }
+    Ok(())
+}
+
+/// Check `account` can be greeted by a GreetMany
+#[cfg(feature = "program")]
+fn check_one_of_many(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    account: &AccountInfo,
+) -> ProgramResult {
+    if account.key == config_account.key {
+        log!("The config account is not a greeting account");
+        return Err(HelloWorldError::InvalidConfig.into());
+    }
+    // NOTE A read-only account would only fail the whole transaction once the
+    // instruction returns, so it's skipped here instead
+    if !account.is_writable {
+        log!("Greeting account {} is read-only", account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_greeting_account(program_id, account)
+}
+
+/// Save `txt` into the greeting account, counting a greeting of `weight` (and of
+/// `origin`, if known) after decaying the counter, and return what was stored
+#[cfg(feature = "program")]
+fn save_greeting(
+    account: &AccountInfo,
+    config: &config::Config,
+    clock: &Clock,
+    txt: &str,
+    weight: u64,
+    origin: Option<Origin>,
+) -> Result<GreetingAccount, ProgramError> {
+    let (version, greeting) = load_greeting(account)?;
+    check_cooldown(config, &greeting, clock.unix_timestamp)?;
+    let (counter, last_update_epoch) = decay::touch(
+        greeting.counter,
+        greeting.last_update_epoch,
+        config.decay_half_life_epochs,
+        clock.epoch,
+    );
+    let mut message = GreetingAccount {
+        txt: txt.to_string(),
+        counter: counter.saturating_add(weight),
+        calendar_year: greeting.calendar_year,
+        greeted_days: greeting.greeted_days,
+        last_update_epoch,
+        direct_greets: greeting.direct_greets,
+        cpi_greets: greeting.cpi_greets,
+    };
+    if let Some(origin) = origin {
+        origin.count(&mut message.direct_greets, &mut message.cpi_greets);
+    }
+    let now = clock.unix_timestamp;
+    calendar::mark_greeted(&mut message.calendar_year, &mut message.greeted_days, now);
+    store_greeting(account, version, &message)?;
+    Ok(message)
+}
+
+/// GreetFor's accounts, with the bump of the target's greeting account, then the fee's
+/// and the greeting log's when the config asks for them
+#[cfg(feature = "program")]
+struct GreetForAccounts<'a, 'b> {
+    greeting_account: &'a AccountInfo<'b>,
+    greeting_bump: u8,
+    config: config::Config,
+    payer: &'a AccountInfo<'b>,
+    target: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+    fee: Option<FeeAccounts<'a, 'b>>,
+    log: Option<GreetingLog<'a, 'b>>,
+}
+
+#[cfg(feature = "program")]
+impl<'a, 'b> InstructionAccounts<'a, 'b> for GreetForAccounts<'a, 'b> {
+    type Args = ();
+
+    fn check(
+        program_id: &Pubkey,
+        accounts: &Accounts<'a, 'b>,
+        _: &(),
+    ) -> Result<Self, ProgramError> {
+        let greeting_account = accounts.get(greet_for::GREETING_ACCOUNT_IDX)?;
+        let config = greet_config(program_id, accounts.get(greet_for::CONFIG_IDX)?)?;
+        let payer = accounts.get(greet_for::PAYER_IDX)?;
+        let target = accounts.get(greet_for::TARGET_IDX)?;
+        let system_program = accounts.get(greet_for::SYSTEM_PROGRAM_IDX)?;
+        if !payer.is_signer {
+            log!("The payer must sign");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let (address, greeting_bump) = target::greeting_address(program_id, target.key);
+        if *greeting_account.key != address {
+            log!("{} is not the greeting account of {}", greeting_account.key, target.key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        validate_program_account(system_program, &system_program::id())?;
+        let fee = if config.charges_fee() {
+            Some(FeeAccounts::check(
+                &config,
+                payer,
+                accounts.get(greet_for::PRICE_FEED_IDX)?,
+                accounts.get(greet_for::FEE_RECIPIENT_IDX)?,
+                system_program,
+            )?)
+        } else {
+            None
+        };
+        let log = if config.has_greeting_log() {
+            let log = next_greeting_log(
+                program_id,
+                &mut accounts.iter_from(if fee.is_some() {
+                    greet_for::FEE_ACCOUNTS_END
+                } else {
+                    greet_for::PRICE_FEED_IDX
+                }),
+            )?;
+            if log.tree.key.to_bytes() != config.greeting_log {
+                log!("{} is not the greeting log", log.tree.key);
+                return Err(HelloWorldError::InvalidGreetingLog.into());
+            }
+            Some(log)
+        } else {
+            None
+        };
+        Ok(Self {
+            greeting_account,
+            greeting_bump,
+            config,
+            payer,
+            target,
+            system_program,
+            fee,
+            log,
+        })
+    }
+}
+
+/// Save the passed message into the target's greeting account, paid for by the payer
+// NOTE The payer and the target are checked apart: the payer by its signature, the
+// target by the greeting account being the PDA of its key. Neither stands in for the
+// other, so a payer can't credit an account that isn't the named target's.
+#[cfg(feature = "program")]
+pub fn process_greet_for(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    txt: String,
+) -> ProgramResult {
+    let (accounts, instructions) = origin::split_instructions_sysvar(accounts);
+    let Context { accounts, .. } =
+        Context::<GreetForAccounts>::load(program_id, accounts, tag::GREET_FOR, &())?;
+    let account = accounts.greeting_account;
+    let config = accounts.config;
+    let payer = accounts.payer;
+    let target_account = accounts.target;
+    let system_program = accounts.system_program;
+    if config.greeting_receipts || config.weighs_balance() {
+        log!("The config keeps greeting receipts or weighs balances, Greet instead");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if txt.len() > target::MAX_TARGET_MESSAGE_LEN {
+        log!("GreetFor takes at most {} bytes of message", target::MAX_TARGET_MESSAGE_LEN);
+        return Err(HelloWorldError::MessageTooLong.into());
+    }
+    check_greeting_allowed(&config, txt.len())?;
+    let origin = origin::origin(program_id, instructions)?;
+    let clock = Clock::get()?;
+
+    // NOTE Only this program can sign for its PDA, with the seeds and bump that derive it
+    if account.owner != program_id {
+        let lamports = Rent::get()?.minimum_balance(target::TARGET_GREETING_SIZE);
+        create_pda_account(
+            payer,
+            account,
+            system_program,
+            lamports,
+            target::TARGET_GREETING_SIZE,
+            program_id,
+            &[
+                target::TARGET_GREETING_SEED,
+                target_account.key.as_ref(),
+                &[accounts.greeting_bump],
+            ],
+        )?;
+        log!("Created the greeting account of {}", target_account.key);
+    }
+    check_greeting_account(program_id, account)?;
+
+    // Marked while the fee transfer runs, see reentrancy.rs
+    let lock = reentrancy::lock(account)?;
+    if let Some(fee) = accounts.fee {
+        fee.charge(&config, clock.slot)?;
+    }
+    drop(lock);
+
+    let weight = epoch_bonus(&config, &clock, 1)?;
+    let message = save_greeting(account, &config, &clock, &txt, weight, origin)?;
+    if let Some(log) = accounts.log {
+        let lock = reentrancy::lock(account)?;
+        log.append(&compression::GreetingLeaf {
+            greeting_account: account.key.to_bytes(),
+            counter: message.counter,
+            slot: clock.slot,
+            txt: message.txt.clone(),
+        })?;
+        drop(lock);
+    }
+    log!("Greeted {} for {}, paid by {}", account.key, target_account.key, payer.key);
+    msg!("Was sent message {}!", message.txt);
+    Ok(())
+}
+
+/// Log the deployed crate version and state-schema version
+#[cfg(feature = "program")]
+pub fn process_version() -> ProgramResult {
+    // NOTE Keep this format stable, clients parse it out of the simulated logs
+    msg!(
+        "helloworld-version: {} schema: {}",
+        VERSION,
+        STATE_VERSION
+    );
+    Ok(())
+}
+
+/// Check that an account passed in as a program (system, token, ...) really is
+/// that program before we `invoke` it
+// NOTE Any account in the list can be swapped out by whoever builds the transaction.
+// If we CPI into whatever was passed as "the system program" without checking its key,
+// an attacker can substitute their own program and have it run with our signer seeds.
+// Every CPI path must call this on the program account it is about to invoke.
+// NOTE Sysvar accounts aren't executable, so don't use this for them. Reading a sysvar
+// through Sysvar::from_account_info() already checks its id for us.
+#[cfg(feature = "program")]
+pub fn validate_program_account(
+    program_account: &AccountInfo,
+    expected_program_id: &Pubkey,
+) -> ProgramResult {
+    if program_account.key != expected_program_id {
+        log!(
+            "Expected program {} but was passed {}",
+            expected_program_id,
+            program_account.key
+        );
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !program_account.executable {
+        log!("Program account {} is not executable", program_account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Create the PDA `account` at `seeds`, `size` bytes owned by the program and holding
+/// at least `lamports`, paid for by `payer`
+// NOTE create_account refuses an address that holds lamports already, so anyone could
+// keep a PDA from ever being created by sending it a lamport first. An address that
+// holds some is topped up to `lamports` instead, then allocated and assigned, which
+// only this program can sign for.
+#[cfg(feature = "program")]
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    lamports: u64,
+    size: usize,
+    program_id: &Pubkey,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    if account.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                lamports,
+                size as u64,
+                program_id,
+            ),
+            &[payer.clone(), account.clone(), system_program.clone()],
+            &[seeds],
+        );
+    }
+    let top_up = lamports.saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, size as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+// Sanity tests
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+    use crate::instruction::MAX_GREET_MANY_ACCOUNTS;
+    use crate::test_utils::{use_test_sysvars, EPOCH, NOW, SLOT};
+
+    // NOTE Borsh encodes a String as a u32 length prefix followed by the bytes,
+    // so a 12 character message needs 4 + 12 bytes of account data, plus the schema
+    // discriminator, the u64 counter, the calendar (u16 year, 46 byte day bitmap), the
+    // u64 epoch of the last update and the two u64 origin counters.
+    const MESSAGE_SIZE: usize =
+        migrations::GREETING_DISCRIMINATOR.len() + 4 + 12 + 8 + 2 + 46 + 8 + 16;
+
+    // Size of the accounts created at schema 2, before the calendar
+    const SCHEMA_2_SIZE: usize = migrations::HEADER_SIZE + 4 + 12 + 8;
+
+    // Size of the accounts created before schema 2, which only hold the message
+    const LEGACY_SIZE: usize = 4 + 12;
+
+    // The compact encoding of a 12 character message greeted fewer than 128 times:
+    // header, flags, 1 byte of length, the message and 1 byte of counter
+    const COMPACT_SIZE: usize = migrations::HEADER_SIZE + 1 + 1 + 12 + 1;
+
+    // The state of an account greeted `counter` times, last at NOW (in EPOCH) if
+    // `dated`
+    fn greeting(txt: &str, counter: u64, dated: bool) -> GreetingAccount {
+        let mut greeting = GreetingAccount {
+            txt: txt.to_string(),
+            counter,
+            calendar_year: 0,
+            greeted_days: calendar::DayBitmap::default(),
+            last_update_epoch: 0,
+            direct_greets: 0,
+            cpi_greets: 0,
+        };
+        if dated {
+            calendar::mark_greeted(&mut greeting.calendar_year, &mut greeting.greeted_days, NOW);
+            greeting.last_update_epoch = EPOCH;
+        }
+        greeting
+    }
+
+    // The config account before anyone created it: no decay and no fee
+    fn missing_config(program_id: &Pubkey) -> AccountInfo<'static> {
+        let key = config::config_address(program_id).0;
+        fixtures::read_only(fixtures::account(key, false, 0, vec![], system_program::id()))
+    }
+
+    // The config account, created with `authority` as its authority, and the
+    // authority signing
+    fn config_and_authority(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> (AccountInfo<'static>, AccountInfo<'static>) {
+        let config_data = config::Config {
+            authority: authority.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let config_key = config::config_address(program_id).0;
+        let config = fixtures::account(config_key, false, 0, config_data, *program_id);
+        let authority = fixtures::account(*authority, true, 0, vec![], system_program::id());
+        (fixtures::read_only(config), fixtures::read_only(authority))
+    }
+
+    fn message(txt: &str) -> Vec<u8> {
+        HelloWorldInstruction::Greet {
+            txt: txt.to_string(),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sanity() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], Pubkey::default());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello1234567", 1, true)
+        );
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        let greeted = GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap();
+        assert_eq!(greeted, greeting("World7654321", 2, true));
+        assert_eq!(greeted.calendar_year, 2024);
+        assert!(greeted.greeted_days.is_set(60));
+        assert_eq!(greeted.greeted_days.count(), 1);
+    }
+
+    #[test]
+    fn test_schema_1_message_in_another_size_is_rejected() {
+        let program_id = fixtures::program_id();
+        let v1 = GreetingAccountV1 {
+            txt: "Hello1234567".to_string(),
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(v1.len(), LEGACY_SIZE);
+
+        // No schema 1 account was created at any other size, so this is another
+        // account of the program's, not a greeting to overwrite
+        use_test_sysvars();
+        for size in [SCHEMA_2_SIZE, MESSAGE_SIZE] {
+            let account = fixtures::greeting_account("sized", size);
+            account.data.borrow_mut()[..LEGACY_SIZE].copy_from_slice(&v1);
+            let accounts = [account, missing_config(&program_id)];
+            assert_eq!(
+                process_instruction(&program_id, &accounts, &message("World7654321")),
+                Err(ProgramError::InvalidAccountData)
+            );
+            assert_eq!(accounts[0].data.borrow()[..LEGACY_SIZE], v1[..]);
+        }
+    }
+
+    #[test]
+    fn test_schema_2_account_keeps_its_counter() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        // Greeted before schema 3, with no room for the calendar
+        let data = migrations::encode_as(2, &greeting("Hello1234567", 5, false)).unwrap();
+        assert_eq!(data.len(), SCHEMA_2_SIZE);
+        let account = fixtures::account(key, false, 0, data, Pubkey::default());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        assert_eq!(migrations::stored_version(&accounts[0].data.borrow()).unwrap(), 2);
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("World7654321", 6, false)
+        );
+    }
+
+    #[test]
+    fn test_legacy_account_stays_on_schema_1() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        // Greeted before schema 2, with no room for the counter
+        let data = GreetingAccountV1 {
+            txt: "Hello1234567".to_string(),
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(data.len(), LEGACY_SIZE);
+        let owner = Pubkey::default();
+        let account = fixtures::account(key, false, 0, data, owner);
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        assert_eq!(migrations::stored_version(&accounts[0].data.borrow()).unwrap(), 1);
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("World7654321", 0, false)
+        );
+
+        // A new account has to be sized for the counter
+        let account = fixtures::account(key, false, 0, vec![0; LEGACY_SIZE], owner);
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[account, missing_config(&program_id)],
+                &message("Hello1234567")
+            ),
+            Err(HelloWorldError::MessageTooLong.into())
+        );
+    }
+
+    #[test]
+    fn test_small_new_account_is_compact() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        // Too small for the Borsh layout, big enough for the compact one without the
+        // calendar
+        let account = fixtures::account(key, false, 0, vec![0; COMPACT_SIZE], Pubkey::default());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        let data = accounts[0].data.borrow();
+        assert_eq!(compact::encoding(&data), compact::Encoding::Compact);
+        assert_eq!(migrations::stored_version(&data).unwrap(), STATE_VERSION);
+        assert_eq!(
+            GreetingAccount::unpack(&data).unwrap(),
+            greeting("World7654321", 2, false)
+        );
+    }
+
+    #[test]
+    fn test_compact_account_stays_compact() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        // Room for either encoding
+        let mut data = compact::encode(&greeting("Hello1234567", 126, false));
+        data.resize(MESSAGE_SIZE, 0);
+        let account = fixtures::account(key, false, 0, data, Pubkey::default());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        // 128 takes a second varint byte
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        let data = accounts[0].data.borrow();
+        assert_eq!(compact::encoding(&data), compact::Encoding::Compact);
+        assert_eq!(
+            GreetingAccount::unpack(&data).unwrap(),
+            greeting("World7654321", 128, true)
+        );
+    }
+
+    #[test]
+    fn test_counter_decays_with_config() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        // Greeted 8 times by 25 epochs ago, halving every 10 epochs
+        let mut stamped = greeting("Hello1234567", 8, false);
+        stamped.last_update_epoch = EPOCH - 25;
+        let data = migrations::encode_as(STATE_VERSION, &stamped).unwrap();
+        let config_data = config::Config {
+            authority: [1; 32],
+            decay_half_life_epochs: 10,
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let account = fixtures::account(key, false, 0, data, program_id);
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, config_data, program_id));
+        let accounts = vec![account, config];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("World7654321")).unwrap();
+        let greeted = GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap();
+        // Halved twice, then counted this greeting. The 5 epochs into the next
+        // half-life still count.
+        assert_eq!((greeted.counter, greeted.last_update_epoch), (3, EPOCH - 5));
+        assert_eq!(greeted.effective_counter(10, EPOCH + 5), 1);
+
+        // The config account is required
+        assert_eq!(
+            process_instruction(&program_id, &accounts[..1], &message("World7654321")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+
+        // Nor can some other account take its place
+        let other_key = Pubkey::new_unique();
+        let other = fixtures::read_only(fixtures::account(other_key, false, 0, vec![], program_id));
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[accounts[0].clone(), other],
+                &message("World7654321")
+            ),
+            Err(HelloWorldError::InvalidConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_greet_stamps_account_with_epoch() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        // Not created yet, so decay is off
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config = fixtures::read_only(fixtures::account(
+            config_key,
+            false,
+            0,
+            vec![],
+            system_program::id(),
+        ));
+        let accounts = vec![account, config];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        let greeted = GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap();
+        assert_eq!((greeted.counter, greeted.last_update_epoch), (1, EPOCH));
+    }
+
+    // NOTE Off chain only logging costs compute units (see capture_logs()), so this
+    // checks the marks are there and in order rather than what the stages cost. For
+    // real numbers, run the BPF build with the feature and read the logs with
+    // profiling::breakdown().
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn test_greet_profile() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let accounts = vec![account, missing_config(&program_id)];
+
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &accounts, &message("Hello1234567"))
+        });
+        result.unwrap();
+        let costs = profiling::breakdown(&logs);
+        println!("{}", profiling::report(&costs));
+        let stages: Vec<_> = costs.iter().map(|cost| cost.stage.as_str()).collect();
+        assert_eq!(stages, vec!["validation", "deserialize", "mutate", "serialize"]);
+        assert!(costs
+            .iter()
+            .all(|cost| cost.units >= crate::test_utils::LOG_UNITS));
+    }
+
+    // Counts the heap allocations of each thread, for test_increment_does_not_allocate
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn test_increment() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let accounts = vec![account, missing_config(&program_id)];
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+
+        use_test_sysvars();
+        // A new account needs a Greet first
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &increment),
+            Err(HelloWorldError::MigrationRequired.into())
+        );
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &accounts, &increment).unwrap();
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello1234567", 2, true)
+        );
+
+        // Nor does it allocate on the way, but for finding the config's address: off
+        // chain find_program_address() allocates, where the syscall doesn't
+        let allocations = |f: &dyn Fn()| {
+            let before = ALLOCATIONS.with(|count| count.get());
+            f();
+            ALLOCATIONS.with(|count| count.get()) - before
+        };
+        let config_address = allocations(&|| {
+            config::config_address(&program_id);
+        });
+        let incremented = allocations(&|| {
+            crate::test_utils::without_logs(|| {
+                process_instruction(&program_id, &accounts, &increment).unwrap()
+            })
+        });
+        assert!(config_address > 0);
+        assert_eq!(incremented, config_address);
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello1234567", 3, true)
+        );
+    }
+
+    #[test]
+    fn test_writes_log_state_hashes() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let accounts = vec![account, missing_config(&program_id)];
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+
+        use_test_sysvars();
+        for instruction in [message("Hello1234567"), increment].iter() {
+            let (result, logs) = crate::test_utils::capture_logs(|| {
+                process_instruction(&program_id, &accounts, instruction)
+            });
+            result.unwrap();
+            let data = accounts[0].data.borrow();
+            assert_eq!(state_hash::verify(&logs, &key, &data), Some(true));
+            assert_eq!(state_hash::verify(&logs, &key, &data[1..]), Some(false));
+        }
+    }
+
+    #[test]
+    fn test_counts_greetings_by_origin() {
+        let program_id = Pubkey::new_unique();
+        let wrapper = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let data = vec![0; MESSAGE_SIZE];
+        let account = fixtures::account(key, false, 0, data, program_id);
+        let sysvar_key = solana_program::sysvar::instructions::id();
+        let greet = instruction::greet(&program_id, &key, "Hello1234567");
+        let wrapped = solana_program::instruction::Instruction::new_with_bytes(
+            wrapper,
+            &[],
+            greet.accounts.clone(),
+        );
+        // The wrapper's instruction runs at index 0, ours at index 1
+        let instructions = [wrapped, greet];
+        let sysvar_data = [
+            crate::test_utils::instructions_sysvar_data(&instructions, 1),
+            crate::test_utils::instructions_sysvar_data(&instructions, 0),
+        ];
+        let sysvar_owner = solana_program::sysvar::id();
+        let mut sysvars = sysvar_data.iter().map(|data| {
+            let sysvar = fixtures::account(sysvar_key, false, 0, data.clone(), sysvar_owner);
+            fixtures::read_only(sysvar)
+        });
+        let (direct, cpi) = (sysvars.next().unwrap(), sysvars.next().unwrap());
+        let accounts = vec![account, missing_config(&program_id)];
+        let with_direct = [accounts.clone(), vec![direct.clone()]].concat();
+        let with_cpi = [accounts.clone(), vec![cpi]].concat();
+        let origins = || {
+            let greeting = GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap();
+            (greeting.counter, greeting.direct_greets, greeting.cpi_greets)
+        };
+
+        use_test_sysvars();
+        process_instruction(&program_id, &with_direct, &message("Hello1234567")).unwrap();
+        assert_eq!(origins(), (1, 1, 0));
+        process_instruction(&program_id, &with_cpi, &message("Hello1234567")).unwrap();
+        assert_eq!(origins(), (2, 1, 1));
+        // Without the sysvar the greeting is only in the counter
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        assert_eq!(origins(), (3, 1, 1));
+
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+        process_instruction(&program_id, &with_cpi, &increment).unwrap();
+        assert_eq!(origins(), (4, 1, 2));
+
+        // GreetMany doesn't take the sysvar for one of the greeting accounts
+        let greet_many = HelloWorldInstruction::GreetMany {
+            txt: "Hello1234567".to_string(),
+        };
+        let many = vec![accounts[1].clone(), accounts[0].clone(), direct];
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &many, &greet_many.try_to_vec().unwrap())
+        });
+        result.unwrap();
+        assert!(logs.contains(&"Program log: GreetMany greeted 1 of 1".to_string()));
+        assert_eq!(origins(), (5, 2, 2));
+    }
+
+    #[test]
+    fn test_greet_many() {
+        let program_id = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let owners = [program_id, other_program, program_id];
+        let mut accounts = vec![missing_config(&program_id)];
+        for (key, owner) in keys.iter().zip(&owners) {
+            accounts.push(fixtures::account(*key, false, 0, vec![0; MESSAGE_SIZE], *owner));
+        }
+        let greet_many = |txt: &str| {
+            HelloWorldInstruction::GreetMany {
+                txt: txt.to_string(),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+
+        // The account owned by another program is skipped, the others are greeted
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &accounts, &greet_many("Hello1234567"))
+        });
+        result.unwrap();
+        assert!(logs.contains(&format!(
+            "Program log: GreetMany skipped 1 {}: IncorrectProgramId",
+            keys[1]
+        )));
+        assert!(logs.contains(&"Program log: GreetMany greeted 2 of 3".to_string()));
+        for index in [1, 3] {
+            assert_eq!(
+                GreetingAccount::unpack(&accounts[index].data.borrow()).unwrap(),
+                greeting("Hello1234567", 1, true)
+            );
+        }
+        assert!(accounts[2].data.borrow().iter().all(|byte| *byte == 0));
+
+        // Nothing greeted fails with the last account's error
+        accounts[3].is_writable = false;
+        assert_eq!(
+            process_instruction(&program_id, &accounts[..1], &greet_many("Hi")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let unowned = [accounts[0].clone(), accounts[2].clone()];
+        assert_eq!(
+            process_instruction(&program_id, &unowned, &greet_many("Hi")),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        let too_many = vec![accounts[1].clone(); MAX_GREET_MANY_ACCOUNTS + 1];
+        let too_many = [vec![accounts[0].clone()], too_many].concat();
+        assert_eq!(
+            process_instruction(&program_id, &too_many, &greet_many("Hi")),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &accounts, &greet_many("Hello7654321")).unwrap();
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[1].data.borrow()).unwrap(),
+            greeting("Hello7654321", 2, true)
+        );
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[3].data.borrow()).unwrap(),
+            greeting("Hello1234567", 1, true)
+        );
+    }
+
+    #[test]
+    fn test_greet_for() {
+        let program_id = Pubkey::new_unique();
+        let (payer_key, target_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (greeting_key, _) = target::greeting_address(&program_id, &target_key);
+        let system_program_id = system_program::id();
+        // NOTE The create_account CPI is a no-op in unit tests, so the greeting account
+        // starts out created
+        let account = fixtures::account(
+            greeting_key,
+            false,
+            0,
+            vec![0; target::TARGET_GREETING_SIZE],
+            program_id,
+        );
+        let payer = fixtures::account(payer_key, true, 0, vec![], system_program_id);
+        let target =
+            fixtures::read_only(fixtures::account(target_key, false, 0, vec![], system_program_id));
+        let system_program = fixtures::program(system_program_id);
+        let accounts = vec![
+            account,
+            missing_config(&program_id),
+            payer,
+            target,
+            system_program,
+        ];
+        let greet_for = |txt: &str| {
+            HelloWorldInstruction::GreetFor {
+                txt: txt.to_string(),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &greet_for("Hello1234567")).unwrap();
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello1234567", 1, true)
+        );
+
+        // The payer has to sign, the target doesn't
+        let mut unsigned = accounts.clone();
+        unsigned[greet_for::PAYER_IDX].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &greet_for("Hi")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        // Only the target's own greeting account is credited
+        let mut other_target = accounts.clone();
+        let other_key = Pubkey::new_unique();
+        other_target[greet_for::TARGET_IDX] =
+            fixtures::with_key(other_target[greet_for::TARGET_IDX].clone(), other_key);
+        assert_eq!(
+            process_instruction(&program_id, &other_target, &greet_for("Hi")),
+            Err(ProgramError::InvalidSeeds)
+        );
+        let long = "x".repeat(target::MAX_TARGET_MESSAGE_LEN + 1);
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &greet_for(&long)),
+            Err(HelloWorldError::MessageTooLong.into())
+        );
+        let longest = "x".repeat(target::MAX_TARGET_MESSAGE_LEN);
+        process_instruction(&program_id, &accounts, &greet_for(&longest)).unwrap();
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting(&longest, 2, true)
+        );
+    }
+
+    #[test]
+    fn test_config_instructions() {
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let authority_key = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        // NOTE The system program's create_account can't run off chain, so the config
+        // account is already sized and InitConfig only writes it
+        let config = fixtures::account(
+            config_key,
+            false,
+            0,
+            vec![0; config::CONFIG_SIZE],
+            system_program_id,
+        );
+        let authority = fixtures::account(authority_key, true, 0, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+        let init = HelloWorldInstruction::InitConfig {
+            decay_half_life_epochs: 10,
+        }
+        .try_to_vec()
+        .unwrap();
+
+        use_test_sysvars();
+        let mut unsigned = authority.clone();
+        unsigned.is_signer = false;
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[config.clone(), unsigned, system_program.clone()],
+                &init
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        process_instruction(
+            &program_id,
+            &[config.clone(), authority.clone(), system_program],
+            &init,
+        )
+        .unwrap();
+        assert_eq!(
+            config::Config::try_from_slice(&config.data.borrow()).unwrap(),
+            config::Config {
+                authority: authority_key.to_bytes(),
+                decay_half_life_epochs: 10,
+                ..config::Config::default()
+            }
+        );
+
+        // Only the authority can change the half-life, once the config belongs to us
+        let set_decay = HelloWorldInstruction::SetDecay {
+            half_life_epochs: 20,
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), authority.clone()], &set_decay),
+            Err(HelloWorldError::InvalidConfig.into())
+        );
+        let config = fixtures::with_owner(config, program_id);
+        let mut unsigned = authority.clone();
+        unsigned.is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), unsigned], &set_decay),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        let impostor_key = Pubkey::new_unique();
+        let impostor = fixtures::with_key(authority.clone(), impostor_key);
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), impostor.clone()], &set_decay),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        process_instruction(&program_id, &[config.clone(), authority.clone()], &set_decay)
+            .unwrap();
+        assert_eq!(
+            config::Config::try_from_slice(&config.data.borrow())
+                .unwrap()
+                .decay_half_life_epochs,
+            20
+        );
+
+        // Once handed over, say to a DAO's treasury, only the new authority can
+        let set_authority = HelloWorldInstruction::SetAuthority {
+            new_authority: impostor_key.to_bytes(),
+        }
+        .try_to_vec()
+        .unwrap();
+        process_instruction(&program_id, &[config.clone(), authority.clone()], &set_authority)
+            .unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), authority], &set_decay),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        process_instruction(&program_id, &[config.clone(), impostor], &set_decay).unwrap();
+    }
+
+    #[test]
+    fn test_set_config_limits_greetings() {
+        let program_id = Pubkey::new_unique();
+        let (key, authority_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let config_data = config::Config {
+            authority: authority_key.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config = fixtures::account(config_key, false, 0, config_data, program_id);
+        let authority =
+            fixtures::read_only(fixtures::account(authority_key, true, 0, vec![], program_id));
+        let accounts = vec![account, config.clone()];
+        let admin = vec![config.clone(), authority];
+        let set_config = |patch: config::ConfigPatch| {
+            process_instruction(
+                &program_id,
+                &admin,
+                &HelloWorldInstruction::SetConfig {
+                    patch: Box::new(patch),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        };
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+
+        use_test_sysvars();
+        // A patch with a field out of range changes nothing
+        let before = config.data.borrow().to_vec();
+        assert_eq!(
+            set_config(config::ConfigPatch {
+                paused: Some(true),
+                max_confidence_bps: Some(10_001),
+                ..config::ConfigPatch::default()
+            }),
+            Err(HelloWorldError::InvalidConfigValue.into())
+        );
+        assert_eq!(*config.data.borrow(), before);
+        // And so does one turning receipts on under decay, or decay on under receipts
+        let set_decay = |half_life_epochs| {
+            process_instruction(
+                &program_id,
+                &admin,
+                &HelloWorldInstruction::SetDecay { half_life_epochs }
+                    .try_to_vec()
+                    .unwrap(),
+            )
+        };
+        assert_eq!(
+            set_config(config::ConfigPatch {
+                decay_half_life_epochs: Some(10),
+                greeting_receipts: Some(true),
+                ..config::ConfigPatch::default()
+            }),
+            Err(HelloWorldError::InvalidConfigValue.into())
+        );
+        set_config(config::ConfigPatch {
+            greeting_receipts: Some(true),
+            ..config::ConfigPatch::default()
+        })
+        .unwrap();
+        assert_eq!(set_decay(10), Err(HelloWorldError::InvalidConfigValue.into()));
+        set_config(config::ConfigPatch {
+            greeting_receipts: Some(false),
+            ..config::ConfigPatch::default()
+        })
+        .unwrap();
+        set_decay(0).unwrap();
+        assert_eq!(*config.data.borrow(), before);
+
+        set_config(config::ConfigPatch {
+            paused: Some(true),
+            ..config::ConfigPatch::default()
+        })
+        .unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(HelloWorldError::Paused.into())
+        );
+
+        set_config(config::ConfigPatch {
+            paused: Some(false),
+            max_message_len: Some(5),
+            cooldown_days: Some(1),
+            ..config::ConfigPatch::default()
+        })
+        .unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(HelloWorldError::MessageTooLong.into())
+        );
+        process_instruction(&program_id, &accounts, &message("Hello")).unwrap();
+        // Greeted today, so not again until tomorrow
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello")),
+            Err(HelloWorldError::OnCooldown.into())
+        );
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &increment),
+            Err(HelloWorldError::OnCooldown.into())
+        );
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello", 1, true)
+        );
+    }
+
+    #[test]
+    fn test_admin_changes_log_events() {
+        let program_id = Pubkey::new_unique();
+        let (authority_key, new_authority_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let old = config::Config {
+            authority: authority_key.to_bytes(),
+            ..config::Config::default()
+        };
+        let config = fixtures::account(config_key, false, 0, old.try_to_vec().unwrap(), program_id);
+        let authority =
+            fixtures::read_only(fixtures::account(authority_key, true, 0, vec![], program_id));
+        let admin = vec![config, authority];
+        let set_config = HelloWorldInstruction::SetConfig {
+            patch: Box::new(config::ConfigPatch {
+                cooldown_days: Some(7),
+                ..config::ConfigPatch::default()
+            }),
+        };
+        let set_authority = HelloWorldInstruction::SetAuthority {
+            new_authority: new_authority_key.to_bytes(),
+        };
+
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &admin, &set_config.try_to_vec().unwrap())?;
+            process_instruction(&program_id, &admin, &set_authority.try_to_vec().unwrap())
+        });
+        result.unwrap();
+        let events: Vec<Event> = event::events(&logs)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                Event::config_changed(
+                    &old,
+                    &config::Config {
+                        cooldown_days: 7,
+                        ..old
+                    },
+                ),
+                Event::authority_changed(authority_key.to_bytes(), new_authority_key.to_bytes()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_greet_charges_fee() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (authority_key, payer_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (feed_key, recipient_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let system_program_id = system_program::id();
+        let loader = solana_program::bpf_loader::id();
+        let config_data = config::Config {
+            authority: authority_key.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        // $150.00 ± $0.10, published 10 slots ago
+        let feed_data = pyth::price_account(15_000_000_000, 10_000_000, -8, SLOT - 10);
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config = fixtures::account(config_key, false, 0, config_data, program_id);
+        let authority = fixtures::read_only(fixtures::account(
+            authority_key,
+            true,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let payer = fixtures::account(payer_key, true, 0, vec![], system_program_id);
+        let feed = fixtures::read_only(fixtures::with_executable(
+            fixtures::account(feed_key, false, 0, feed_data, loader),
+            false,
+        ));
+        let recipient = fixtures::account(recipient_key, false, 0, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+
+        use_test_sysvars();
+        // One cent a greeting, from a price at most 25 slots old and 10 basis points
+        // uncertain
+        let set_fee = |max_confidence_bps| {
+            HelloWorldInstruction::SetFee {
+                fee_usd_micros: 10_000,
+                max_price_age_slots: 25,
+                max_confidence_bps,
+                price_feed: feed_key.to_bytes(),
+                fee_recipient: recipient_key.to_bytes(),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), payer.clone()], &set_fee(10)),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        process_instruction(&program_id, &[config.clone(), authority.clone()], &set_fee(10))
+            .unwrap();
+        assert!(config::Config::unpack(&config.data.borrow()).unwrap().charges_fee());
+
+        let accounts = vec![
+            account,
+            config.clone(),
+            payer.clone(),
+            feed.clone(),
+            recipient.clone(),
+            system_program,
+        ];
+        // Leaving out the fee accounts doesn't skip the fee
+        assert_eq!(
+            process_instruction(&program_id, &accounts[..2], &message("Hello1234567")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let mut unsigned = accounts.clone();
+        unsigned[2].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &message("Hello1234567")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut wrong_feed = accounts.clone();
+        wrong_feed[3] = payer.clone();
+        assert_eq!(
+            process_instruction(&program_id, &wrong_feed, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidPriceFeed.into())
+        );
+        let mut wrong_recipient = accounts.clone();
+        wrong_recipient[4] = payer;
+        assert_eq!(
+            process_instruction(&program_id, &wrong_recipient, &message("Hello1234567")),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+        let greeted = GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap();
+        assert_eq!(greeted.counter, 1);
+
+        // About 6.7 basis points of uncertainty is too much for a 5 basis point limit
+        process_instruction(&program_id, &[config.clone(), authority], &set_fee(5)).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(HelloWorldError::UncertainPrice.into())
+        );
+
+        // Nor can a price from 30 slots ago be used
+        feed.data.borrow_mut()[232..240].copy_from_slice(&(SLOT - 30).to_le_bytes());
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(HelloWorldError::StalePrice.into())
+        );
+    }
+
+    #[test]
+    fn test_tick() {
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (authority_key, crank_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (stamped_key, unstamped_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Halving every 10 epochs, in a config account created before the crank
+        // authority and in a current one
+        let settings = config::Config {
+            authority: authority_key.to_bytes(),
+            decay_half_life_epochs: 10,
+            ..config::Config::default()
+        };
+        let mut legacy_data = vec![0; config::CONFIG_SIZE_V1];
+        settings.pack(&mut legacy_data).unwrap();
+        let mut stamped = greeting("Hello1234567", 8, false);
+        stamped.last_update_epoch = EPOCH - 25;
+        let stamped_data = migrations::encode_as(STATE_VERSION, &stamped).unwrap();
+        let unstamped_data =
+            migrations::encode_as(STATE_VERSION, &greeting("Hello1234567", 8, false)).unwrap();
+        let system_program_id = system_program::id();
+        let legacy = fixtures::account(config_key, false, 0, legacy_data, program_id);
+        let config =
+            fixtures::account(config_key, false, 0, settings.try_to_vec().unwrap(), program_id);
+        let authority = fixtures::read_only(fixtures::account(
+            authority_key,
+            true,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let crank =
+            fixtures::read_only(fixtures::account(crank_key, true, 0, vec![], system_program_id));
+        let stamped = fixtures::account(stamped_key, false, 0, stamped_data, program_id);
+        let unstamped = fixtures::account(unstamped_key, false, 0, unstamped_data, program_id);
+        let tick = HelloWorldInstruction::Tick.try_to_vec().unwrap();
+        let accounts = vec![config.clone(), crank.clone(), stamped, unstamped];
+        let mut legacy_accounts = accounts.clone();
+        legacy_accounts[0] = legacy.clone();
+
+        use_test_sysvars();
+        // Nobody can Tick before a crank authority is set
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &tick),
+            Err(HelloWorldError::UnauthorizedCrank.into())
+        );
+        assert_eq!(
+            process_instruction(&program_id, &legacy_accounts, &tick),
+            Err(HelloWorldError::UnauthorizedCrank.into())
+        );
+        // Nor can the crank authority be set in a config account without room for it
+        let set_crank = HelloWorldInstruction::SetCrankAuthority {
+            crank_authority: crank_key.to_bytes(),
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &[legacy, authority.clone()], &set_crank),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &[config.clone(), crank.clone()], &set_crank),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        process_instruction(&program_id, &[config.clone(), authority], &set_crank).unwrap();
+
+        let mut unsigned = crank;
+        unsigned.is_signer = false;
+        let mut unsigned_accounts = accounts.clone();
+        unsigned_accounts[1] = unsigned;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned_accounts, &tick),
+            Err(HelloWorldError::UnauthorizedCrank.into())
+        );
+        process_instruction(&program_id, &accounts, &tick).unwrap();
+        let read = |account: &AccountInfo| {
+            let greeted = GreetingAccount::unpack(&account.data.borrow()).unwrap();
+            (greeted.counter, greeted.last_update_epoch)
+        };
+        // The decay is folded in, and the unstamped account stamped
+        assert_eq!(read(&accounts[2]), (2, EPOCH - 5));
+        assert_eq!(read(&accounts[3]), (8, EPOCH));
+
+        // Ticking again, or passing an account twice, changes nothing
+        let mut twice = accounts.clone();
+        twice.push(accounts[2].clone());
+        process_instruction(&program_id, &twice, &tick).unwrap();
+        assert_eq!(read(&accounts[2]), (2, EPOCH - 5));
+        assert_eq!(read(&accounts[3]), (8, EPOCH));
+
+        // The config account isn't a greeting account
+        let mut with_config = accounts.clone();
+        with_config.push(config);
+        assert_eq!(
+            process_instruction(&program_id, &with_config, &tick),
+            Err(HelloWorldError::InvalidConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_counter_snapshot() {
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (authority_key, crank_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (snapshot_key, first_key, second_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let config_data = config::Config {
+            authority: authority_key.to_bytes(),
+            crank_authority: crank_key.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let first_data =
+            migrations::encode_as(STATE_VERSION, &greeting("Hello1234567", 3, false)).unwrap();
+        let second_data =
+            migrations::encode_as(STATE_VERSION, &greeting("Hello1234567", 5, false)).unwrap();
+        let system_program_id = system_program::id();
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, config_data, program_id));
+        let authority = fixtures::read_only(fixtures::account(
+            authority_key,
+            true,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let crank =
+            fixtures::read_only(fixtures::account(crank_key, true, 0, vec![], system_program_id));
+        let snapshot = fixtures::account(
+            snapshot_key,
+            false,
+            0,
+            vec![0; counter_snapshot::account_size(2)],
+            program_id,
+        );
+        let first =
+            fixtures::read_only(fixtures::account(first_key, false, 0, first_data, program_id));
+        let second =
+            fixtures::read_only(fixtures::account(second_key, false, 0, second_data, program_id));
+        let init = HelloWorldInstruction::InitCounterSnapshot { depth: 2 }
+            .try_to_vec()
+            .unwrap();
+        let snapshot_counters = |indices: Vec<u32>| {
+            HelloWorldInstruction::SnapshotCounters { indices }
+                .try_to_vec()
+                .unwrap()
+        };
+
+        use_test_sysvars();
+        // Only the config authority starts one
+        let by_crank = vec![config.clone(), crank.clone(), snapshot.clone()];
+        assert_eq!(
+            process_instruction(&program_id, &by_crank, &init),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        let accounts = vec![config.clone(), authority.clone(), snapshot.clone()];
+        process_instruction(&program_id, &accounts, &init).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &init),
+            Err(HelloWorldError::InvalidSnapshot.into())
+        );
+
+        // Only the crank authority updates it
+        let mut accounts = vec![config, crank, snapshot, first, second];
+        let mut by_authority = accounts.clone();
+        by_authority[1] = authority;
+        assert_eq!(
+            process_instruction(&program_id, &by_authority, &snapshot_counters(vec![0, 1])),
+            Err(HelloWorldError::UnauthorizedCrank.into())
+        );
+        process_instruction(&program_id, &accounts, &snapshot_counters(vec![0, 1])).unwrap();
+        let data = accounts[2].data.borrow().to_vec();
+        let header = counter_snapshot::CounterSnapshot::unpack(&data).unwrap();
+        assert_eq!((header.leaf_count, header.slot), (2, SLOT));
+        assert_eq!(counter_snapshot::leaf(&data, 1), Some((second_key.to_bytes(), 5)));
+        let proof = counter_snapshot::proof(&data, 1).unwrap();
+        assert!(counter_snapshot::verify_proof(
+            &header.root,
+            &second_key.to_bytes(),
+            5,
+            1,
+            &proof
+        ));
+
+        // An account keeps its leaf, and a leaf its account
+        accounts.pop();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &snapshot_counters(vec![1])),
+            Err(HelloWorldError::InvalidSnapshotIndex.into())
+        );
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &snapshot_counters(vec![0, 1])),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        process_instruction(&program_id, &accounts, &snapshot_counters(vec![0])).unwrap();
+        assert_eq!(accounts[2].data.borrow().to_vec(), data);
+    }
+
+    #[test]
+    fn test_claim() {
+        let program_id = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (claimant_key, other_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let list = [(other_key.to_bytes(), 10), (claimant_key.to_bytes(), 500)];
+        let root = claim::claim_root(&list);
+        let (claimed_key, _) = claim::claimed_address(&program_id, &root, 1);
+        let (vault_key, _) = claim::claim_vault_address(&program_id);
+        let system_program_id = system_program::id();
+        let config_data = config::Config {
+            claim_root: root,
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        // NOTE The create_account CPI is a no-op in unit tests, so the bitmap starts out
+        // allocated
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, config_data, program_id));
+        let claimant = fixtures::account(claimant_key, true, 0, vec![], system_program_id);
+        let claimed = fixtures::account(
+            claimed_key,
+            false,
+            0,
+            vec![0; claim::CLAIMED_BITMAP_SIZE],
+            program_id,
+        );
+        let vault = fixtures::account(vault_key, false, 1_000_000, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+        let claim = |index: u32, amount: u64| {
+            HelloWorldInstruction::Claim {
+                index,
+                amount,
+                proof: claim::claim_proof(&list, 1).unwrap(),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let accounts = vec![config, claimant, claimed, vault, system_program];
+
+        use_test_sysvars();
+        // The proof must be of the claimant's own leaf and amount
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &claim(1, 501)),
+            Err(HelloWorldError::InvalidClaimProof.into())
+        );
+        let mut by_other = accounts.clone();
+        by_other[1] = fixtures::with_key(by_other[1].clone(), other_key);
+        assert_eq!(
+            process_instruction(&program_id, &by_other, &claim(1, 500)),
+            Err(HelloWorldError::InvalidClaimProof.into())
+        );
+        let mut unsigned = accounts.clone();
+        unsigned[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &claim(1, 500)),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut wrong_bitmap = accounts.clone();
+        wrong_bitmap[2] = fixtures::with_key(wrong_bitmap[2].clone(), other_key);
+        assert_eq!(
+            process_instruction(&program_id, &wrong_bitmap, &claim(1, 500)),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        // An account of ours at the bitmap's address that isn't a bitmap
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &claim(1, 500)),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // Not created yet, so still the system program's
+        let mut unopened = accounts.clone();
+        unopened[2] = fixtures::with_owner(unopened[2].clone(), system_program_id);
+        process_instruction(&program_id, &unopened, &claim(1, 500)).unwrap();
+        assert!(claim::is_bitmap(&accounts[2].data.borrow()));
+        assert!(claim::is_claimed(&accounts[2].data.borrow(), 1));
+        assert!(!claim::is_claimed(&accounts[2].data.borrow(), 0));
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &claim(1, 500)),
+            Err(HelloWorldError::AlreadyClaimed.into())
+        );
+        // Greeting the bitmap would write a greeting over the claimed bits
+        let greet = vec![accounts[2].clone(), missing_config(&program_id)];
+        assert_eq!(
+            process_instruction(&program_id, &greet, &message("Hello")),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert!(claim::is_claimed(&accounts[2].data.borrow(), 1));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let program_id = Pubkey::new_unique();
+        let (key, recorder_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let (snapshot_key, _) =
+            milestone::snapshot_address(&program_id, &key, &recorder_key, "launch");
+        let system_program_id = system_program::id();
+        // NOTE The create_account CPI is a no-op in unit tests, so the snapshot starts out
+        // created
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, vec![], system_program_id));
+        let recorder = fixtures::account(recorder_key, true, 0, vec![], system_program_id);
+        let snapshot = fixtures::account(
+            snapshot_key,
+            false,
+            0,
+            vec![0; milestone::snapshot_size("launch")],
+            program_id,
+        );
+        let system_program = fixtures::program(system_program_id);
+        let take = |label: &str| {
+            HelloWorldInstruction::Snapshot {
+                label: label.to_string(),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let accounts = vec![config.clone(), account.clone(), recorder, snapshot, system_program];
+        use_test_sysvars();
+        let greet = [account.clone(), config];
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+
+        // The snapshot's address names the label and the recorder, who signs
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &take("launch2")),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &take("")),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut unsigned = accounts.clone();
+        unsigned[2].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &take("launch")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        process_instruction(&program_id, &accounts, &take("launch")).unwrap();
+        let record = milestone::GreetingSnapshot::unpack(&accounts[3].data.borrow()).unwrap();
+        assert_eq!(record.counter, 2);
+        assert_eq!(record.greeting_account, key.to_bytes());
+        assert_eq!(record.recorder, recorder_key.to_bytes());
+        assert_eq!(record.label, "launch");
+
+        // The counter moves on, the snapshot doesn't, and the label is used up
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &take("launch")),
+            Err(HelloWorldError::LabelTaken.into())
+        );
+        let record = milestone::GreetingSnapshot::unpack(&accounts[3].data.borrow()).unwrap();
+        assert_eq!(record.counter, 2);
+    }
+
+    #[test]
+    fn test_count_tag() {
+        let program_id = Pubkey::new_unique();
+        let (key, payer_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let (tags_key, _) = tags::tag_counters_address(&program_id, &key);
+        let system_program_id = system_program::id();
+        // NOTE The create_account CPI is a no-op in unit tests, so the tag counters start
+        // out created
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, vec![], system_program_id));
+        let payer = fixtures::account(payer_key, true, 0, vec![], system_program_id);
+        let tag_counters =
+            fixtures::account(tags_key, false, 0, vec![0; tags::TAG_COUNTERS_SIZE], program_id);
+        let system_program = fixtures::program(system_program_id);
+        let count = |name: &str| {
+            let tag = tags::tag(name).unwrap_or_default();
+            HelloWorldInstruction::CountTag { tag }.try_to_vec().unwrap()
+        };
+        let accounts = vec![config.clone(), account.clone(), payer, tag_counters, system_program];
+        use_test_sysvars();
+        process_instruction(&program_id, &[account, config], &message("Hello1234567")).unwrap();
+
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &count("")),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut wrong = accounts.clone();
+        wrong[3] = wrong[1].clone();
+        assert_eq!(
+            process_instruction(&program_id, &wrong, &count("gm")),
+            Err(ProgramError::InvalidSeeds)
+        );
+        for name in &["gm", "gn", "gm", "hello"] {
+            process_instruction(&program_id, &accounts, &count(name)).unwrap();
+        }
+        let counters = tags::TagCounters::unpack(&accounts[3].data.borrow()).unwrap();
+        assert_eq!(counters.greeting_account, key.to_bytes());
+        let counts: Vec<_> = ["gm", "gn", "hello", "gg"]
+            .iter()
+            .map(|name| counters.count(&tags::tag(name).unwrap()))
+            .collect();
+        assert_eq!(counts, [2, 1, 1, 0]);
+        // The account's own counter only counts the Greet
+        let (_, greeting) = load_greeting(&accounts[1]).unwrap();
+        assert_eq!(greeting.counter, 1);
+
+        // Once every entry is taken only the tags already counted go on
+        for i in 3..tags::MAX_TAGS {
+            process_instruction(&program_id, &accounts, &count(&i.to_string())).unwrap();
+        }
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &count("gg")),
+            Err(HelloWorldError::TagsFull.into())
+        );
+        process_instruction(&program_id, &accounts, &count("gn")).unwrap();
+    }
+
+    #[test]
+    fn test_check_recency() {
+        use solana_program::{hash::Hash, slot_hashes::SlotHashes};
+        let program_id = Pubkey::new_unique();
+        let key = sysvar::slot_hashes::id();
+        let mut slot_hashes = fixtures::read_only(fixtures::account(
+            key,
+            false,
+            0,
+            vec![0; SlotHashes::size_of()],
+            sysvar::id(),
+        ));
+        let recent = (SLOT - 2, Hash::new(&[2; 32]));
+        let old = (SLOT - 200, Hash::new(&[200; 32]));
+        SlotHashes::new(&[recent, old])
+            .to_account_info(&mut slot_hashes)
+            .unwrap();
+        let accounts = vec![slot_hashes];
+        let check = |slot: u64, slot_hash: [u8; 32], max_age_slots: u64| {
+            let data = HelloWorldInstruction::CheckRecency {
+                slot,
+                slot_hash,
+                max_age_slots,
+            };
+            process_instruction(&program_id, &accounts, &data.try_to_vec().unwrap())
+        };
+        use_test_sysvars();
+
+        check(SLOT - 2, [2; 32], 150).unwrap();
+        check(SLOT - 2, [2; 32], 2).unwrap();
+        check(SLOT - 200, [200; 32], 200).unwrap();
+        // Older than allowed, with another hash or not kept: all stale
+        assert_eq!(check(SLOT - 2, [2; 32], 1), Err(HelloWorldError::StaleSlotHash.into()));
+        assert_eq!(check(SLOT - 200, [200; 32], 150), Err(HelloWorldError::StaleSlotHash.into()));
+        assert_eq!(check(SLOT - 2, [3; 32], 150), Err(HelloWorldError::StaleSlotHash.into()));
+        assert_eq!(check(SLOT - 3, [2; 32], 150), Err(HelloWorldError::StaleSlotHash.into()));
+
+        // Only the sysvar's data is trusted
+        let mut forged = accounts.clone();
+        let forged_key = Pubkey::new_unique();
+        forged[0] = fixtures::with_key(forged[0].clone(), forged_key);
+        let data = HelloWorldInstruction::CheckRecency {
+            slot: SLOT - 2,
+            slot_hash: [2; 32],
+            max_age_slots: 150,
+        };
+        assert_eq!(
+            process_instruction(&program_id, &forged, &data.try_to_vec().unwrap()),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_lucky_draw() {
+        let program_id = Pubkey::new_unique();
+        let (key, randomness_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (ticket_key, _) = luck::ticket_address(&program_id, &key);
+        let (vault_key, _) = luck::vault_address(&program_id);
+        let greeter_key = Pubkey::new_unique();
+        let system_program_id = system_program::id();
+        // NOTE The create_account CPI is a no-op in unit tests, so the ticket starts out
+        // created
+        let randomness_data = switchboard::randomness_account(SLOT - 1, 0, [0; 32]);
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let ticket =
+            fixtures::account(ticket_key, false, 0, vec![0; luck::TICKET_SIZE], program_id);
+        let randomness = fixtures::read_only(fixtures::account(
+            randomness_key,
+            false,
+            0,
+            randomness_data,
+            switchboard::devnet::id(),
+        ));
+        let vault = fixtures::account(vault_key, false, 1_000_000, vec![], system_program_id);
+        let greeter = fixtures::account(greeter_key, true, 0, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+
+        use_test_sysvars();
+        let greet = vec![account.clone(), missing_config(&program_id)];
+        let commit = HelloWorldInstruction::CommitLuck.try_to_vec().unwrap();
+        let reveal = HelloWorldInstruction::RevealLuck.try_to_vec().unwrap();
+        let commit_accounts = vec![
+            account.clone(),
+            ticket.clone(),
+            randomness.clone(),
+            greeter.clone(),
+            system_program.clone(),
+        ];
+        let reveal_accounts = vec![
+            account,
+            ticket.clone(),
+            randomness.clone(),
+            vault,
+            greeter.clone(),
+            system_program,
+        ];
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+
+        let mut unsigned = commit_accounts.clone();
+        unsigned[3].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &commit),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        // Only randomness owned by Switchboard counts
+        let mut not_switchboard = commit_accounts.clone();
+        not_switchboard[2] = greeter.clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_switchboard, &commit),
+            Err(HelloWorldError::InvalidRandomness.into())
+        );
+        process_instruction(&program_id, &commit_accounts, &commit).unwrap();
+        let committed = luck::LuckTicket::try_from_slice(&ticket.data.borrow()).unwrap();
+        assert_eq!(
+            committed,
+            luck::LuckTicket {
+                greeter: greeter_key.to_bytes(),
+                randomness: randomness_key.to_bytes(),
+                seed_slot: SLOT - 1,
+                counter: 1,
+                last_update_epoch: EPOCH,
+            }
+        );
+        // One draw per greeting
+        assert_eq!(
+            process_instruction(&program_id, &commit_accounts, &commit),
+            Err(HelloWorldError::NoNewGreeting.into())
+        );
+
+        // Not before the oracle reveals
+        assert_eq!(
+            process_instruction(&program_id, &reveal_accounts, &reveal),
+            Err(HelloWorldError::RandomnessNotRevealed.into())
+        );
+        let reveal_value = |value| {
+            randomness
+                .data
+                .borrow_mut()
+                .copy_from_slice(&switchboard::randomness_account(SLOT - 1, SLOT, value));
+        };
+        reveal_value([1; 32]);
+        process_instruction(&program_id, &reveal_accounts, &reveal).unwrap();
+        assert!(!luck::LuckTicket::try_from_slice(&ticket.data.borrow())
+            .unwrap()
+            .is_pending());
+        // Nor twice
+        assert_eq!(
+            process_instruction(&program_id, &reveal_accounts, &reveal),
+            Err(HelloWorldError::InvalidRandomness.into())
+        );
+
+        // The next greeting gets another draw
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        reveal_value([0; 32]);
+        process_instruction(&program_id, &commit_accounts, &commit).unwrap();
+        let mut wrong_greeter = reveal_accounts.clone();
+        wrong_greeter[4] = randomness.clone();
+        assert_eq!(
+            process_instruction(&program_id, &wrong_greeter, &reveal),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &reveal_accounts, &reveal).unwrap();
+        assert!(luck::is_winner(&[0; 32]));
+    }
+    #[test]
+    fn test_stake_weights_greetings() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (authority_key, staker_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mint_key, source_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (vault_key, destination_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (position_key, _) = stake::position_address(&program_id, &staker_key);
+        let (stake_authority_key, _) = stake::stake_authority_address(&program_id);
+        let (system_program_id, token_program_id) = (system_program::id(), token::id());
+        let config_data = config::Config {
+            authority: authority_key.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        // NOTE The create_account and token CPIs are no-ops in unit tests, so the position
+        // starts out created and no tokens move
+        let vault_data =
+            token::token_account(mint_key.to_bytes(), stake_authority_key.to_bytes(), 0);
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config = fixtures::account(config_key, false, 0, config_data, program_id);
+        let authority = fixtures::read_only(fixtures::account(
+            authority_key,
+            true,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let position = fixtures::account(
+            position_key,
+            false,
+            0,
+            vec![0; stake::STAKE_POSITION_SIZE],
+            program_id,
+        );
+        let staker = fixtures::account(staker_key, true, 0, vec![], system_program_id);
+        let source = fixtures::account(source_key, false, 0, vec![], token_program_id);
+        let vault = fixtures::account(vault_key, false, 0, vault_data, token_program_id);
+        let destination = fixtures::account(destination_key, false, 0, vec![], token_program_id);
+        let stake_authority = fixtures::read_only(fixtures::account(
+            stake_authority_key,
+            false,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let token_program = fixtures::program(token_program_id);
+        let system_program = fixtures::program(system_program_id);
+
+        use_test_sysvars();
+        let deposit = |amount| {
+            HelloWorldInstruction::Deposit { amount }
+                .try_to_vec()
+                .unwrap()
+        };
+        let withdraw = |amount| {
+            HelloWorldInstruction::WithdrawStake { amount }
+                .try_to_vec()
+                .unwrap()
+        };
+        let deposit_accounts = vec![
+            config.clone(),
+            position.clone(),
+            staker.clone(),
+            source,
+            vault.clone(),
+            token_program.clone(),
+            system_program,
+        ];
+        let withdraw_accounts = vec![
+            position.clone(),
+            staker.clone(),
+            vault.clone(),
+            destination,
+            stake_authority,
+            token_program,
+        ];
+        let greet = vec![account.clone(), config.clone(), position.clone(), staker.clone()];
+        assert_eq!(
+            process_instruction(&program_id, &deposit_accounts, &deposit(2_500)),
+            Err(HelloWorldError::InvalidConfig.into())
+        );
+
+        // Every 1000 staked adds 1 to a greeting
+        let set_stake = HelloWorldInstruction::SetStake {
+            stake_mint: mint_key.to_bytes(),
+            stake_unit: 1_000,
+        }
+        .try_to_vec()
+        .unwrap();
+        process_instruction(&program_id, &[config.clone(), authority], &set_stake).unwrap();
+        assert!(config::Config::unpack(&config.data.borrow()).unwrap().has_staking());
+        let mut not_vault = deposit_accounts.clone();
+        not_vault[4] = fixtures::with_owner(vault.clone(), program_id);
+        assert_eq!(
+            process_instruction(&program_id, &not_vault, &deposit(2_500)),
+            Err(HelloWorldError::InvalidStakeVault.into())
+        );
+        process_instruction(&program_id, &deposit_accounts, &deposit(2_500)).unwrap();
+        let load = || stake::StakePosition::try_from_slice(&position.data.borrow()).unwrap();
+        assert_eq!(
+            load(),
+            stake::StakePosition {
+                owner: staker_key.to_bytes(),
+                mint: mint_key.to_bytes(),
+                amount: 2_500,
+                last_deposit_slot: SLOT,
+            }
+        );
+
+        // Stake deposited this slot doesn't count yet
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 1);
+        let mut earlier = load();
+        earlier.last_deposit_slot = SLOT - 1;
+        store_position(&position, &earlier).unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 4);
+        let mut unsigned = greet.clone();
+        unsigned[3].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &message("Hello1234567")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+
+        assert_eq!(
+            process_instruction(&program_id, &withdraw_accounts, &withdraw(2_501)),
+            Err(HelloWorldError::InsufficientStake.into())
+        );
+        let mut wrong_authority = withdraw_accounts.clone();
+        wrong_authority[4] = staker.clone();
+        assert_eq!(
+            process_instruction(&program_id, &wrong_authority, &withdraw(2_000)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        process_instruction(&program_id, &withdraw_accounts, &withdraw(2_000)).unwrap();
+        assert_eq!(load().amount, 500);
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 5);
+    }
+
+    #[test]
+    fn test_session_keys() {
+        let program_id = Pubkey::new_unique();
+        let (staker_key, session_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (position_key, _) = stake::position_address(&program_id, &staker_key);
+        let (token_key, _) = session::session_address(&program_id, &staker_key, &session_key);
+        let mint = [3; 32];
+        let system_program_id = system_program::id();
+        let loader = solana_program::bpf_loader::id();
+        let new_account = |key: Pubkey, is_signer, data: Vec<u8>, owner: &Pubkey| {
+            fixtures::account(key, is_signer, 0, data, *owner)
+        };
+        let config_data = config::Config {
+            stake_mint: mint,
+            stake_unit: 1_000,
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let position_data = stake::StakePosition {
+            owner: staker_key.to_bytes(),
+            mint,
+            amount: 2_500,
+            last_deposit_slot: SLOT - 1,
+        }
+        .try_to_vec()
+        .unwrap();
+        let account = new_account(Pubkey::new_unique(), false, vec![0; MESSAGE_SIZE], &program_id);
+        let config_key = config::config_address(&program_id).0;
+        let config = new_account(config_key, false, config_data, &program_id);
+        let position = new_account(position_key, false, position_data, &program_id);
+        let staker = new_account(staker_key, true, vec![], &system_program_id);
+        let session_signer = new_account(session_key, true, vec![], &system_program_id);
+        // NOTE The create_account CPI is a no-op in unit tests, so the token starts out
+        // allocated
+        let token = new_account(
+            token_key,
+            false,
+            vec![0; session::SESSION_TOKEN_SIZE],
+            &system_program_id,
+        );
+        let system_program = new_account(system_program_id, false, vec![], &loader);
+
+        use_test_sysvars();
+        let create = |duration_seconds| {
+            HelloWorldInstruction::CreateSession {
+                duration_seconds,
+                top_up_lamports: 10_000_000,
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let create_accounts = vec![
+            token.clone(),
+            staker.clone(),
+            session_signer.clone(),
+            system_program,
+        ];
+        assert_eq!(
+            process_instruction(&program_id, &create_accounts, &create(0)),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut unsigned = create_accounts.clone();
+        unsigned[2].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &create(3_600)),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        process_instruction(&program_id, &create_accounts, &create(3_600)).unwrap();
+        let token_data = token.data.clone();
+        let load = || session::SessionToken::try_from_slice(&token_data.borrow()).unwrap();
+        assert_eq!(
+            load(),
+            session::SessionToken {
+                authority: staker_key.to_bytes(),
+                session_key: session_key.to_bytes(),
+                valid_until: NOW as u64 + 3_600,
+            }
+        );
+
+        // The session key greets with the staker's stake, without the staker signing
+        let token = fixtures::with_owner(token, program_id);
+        let greet = vec![
+            account.clone(),
+            config,
+            position,
+            session_signer.clone(),
+            token.clone(),
+        ];
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 3);
+        let mut other_signer = greet.clone();
+        other_signer[3] = new_account(Pubkey::new_unique(), true, vec![], &system_program_id);
+        assert_eq!(
+            process_instruction(&program_id, &other_signer, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidSession.into())
+        );
+        let mut not_token = greet.clone();
+        not_token[4] = account.clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_token, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidSession.into())
+        );
+        let mut expired = load();
+        expired.valid_until = NOW as u64;
+        expired.serialize(&mut &mut token.data.borrow_mut()[..]).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &greet, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidSession.into())
+        );
+
+        // Only the authority revokes, getting the rent back
+        let revoke = HelloWorldInstruction::RevokeSession.try_to_vec().unwrap();
+        **token.lamports.borrow_mut() = 1_000;
+        assert_eq!(
+            process_instruction(&program_id, &[token.clone(), session_signer], &revoke),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &[token.clone(), staker.clone()], &revoke).unwrap();
+        assert_eq!((token.lamports(), staker.lamports()), (0, 1_000));
+    }
+
+    #[test]
+    fn test_greeting_challenge() {
+        let program_id = Pubkey::new_unique();
+        let (key, other_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (challenger_key, challengee_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (challenge_key, _) =
+            challenge::challenge_address(&program_id, &challenger_key, &challengee_key);
+        let system_program_id = system_program::id();
+        let data = vec![0; MESSAGE_SIZE];
+        // NOTE The create_account CPI is a no-op in unit tests, so the challenge starts
+        // out with its data
+        let account = fixtures::account(key, false, 0, data, program_id);
+        let challenge = fixtures::account(
+            challenge_key,
+            false,
+            0,
+            vec![0; challenge::CHALLENGE_SIZE],
+            program_id,
+        );
+        let challenger = fixtures::account(challenger_key, true, 0, vec![], system_program_id);
+        let challengee = fixtures::account(challengee_key, true, 0, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+
+        use_test_sysvars();
+        let greet = vec![account.clone(), missing_config(&program_id)];
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        let create = |greet_count, deadline_slot| {
+            HelloWorldInstruction::CreateChallenge {
+                greet_count,
+                deadline_slot,
+                amount: 5_000,
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+        let claim = HelloWorldInstruction::ClaimChallenge.try_to_vec().unwrap();
+        let refund = HelloWorldInstruction::RefundChallenge.try_to_vec().unwrap();
+        // Not created yet, so still the system program's
+        let unopened = fixtures::with_owner(challenge.clone(), system_program_id);
+        let create_accounts = vec![
+            unopened,
+            challenger.clone(),
+            challengee.clone(),
+            account.clone(),
+            system_program,
+        ];
+        let claim_accounts = vec![challenge.clone(), challengee.clone(), account.clone()];
+        let refund_accounts = vec![challenge.clone(), challenger.clone()];
+        let open = || {
+            process_instruction(&program_id, &create_accounts, &create(2, SLOT + 10)).unwrap();
+            // What create_account would have funded it with
+            **challenge.lamports.borrow_mut() = 7_000;
+        };
+
+        let mut unsigned = create_accounts.clone();
+        unsigned[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &create(2, SLOT + 10)),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &create_accounts, &create(0, SLOT + 10)),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &create_accounts, &create(2, SLOT)),
+            Err(HelloWorldError::ChallengeExpired.into())
+        );
+        let mut other_challengee = create_accounts.clone();
+        other_challengee[2] = challenger.clone();
+        assert_eq!(
+            process_instruction(&program_id, &other_challengee, &create(2, SLOT + 10)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        open();
+        assert_eq!(
+            challenge::Challenge::try_from_slice(&challenge.data.borrow()).unwrap(),
+            challenge::Challenge {
+                challenger: challenger_key.to_bytes(),
+                challengee: challengee_key.to_bytes(),
+                greeting_account: key.to_bytes(),
+                target_counter: 3,
+                deadline_slot: SLOT + 10,
+                amount: 5_000,
+            }
+        );
+        let mut opened = create_accounts.clone();
+        opened[0] = challenge.clone();
+        assert_eq!(
+            process_instruction(&program_id, &opened, &create(2, SLOT + 10)),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        // Only the challengee claims, only for the challenge's greeting account, and
+        // only once the counter is there
+        let mut unsigned = claim_accounts.clone();
+        unsigned[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &claim),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut not_challengee = claim_accounts.clone();
+        not_challengee[1] = challenger.clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_challengee, &claim),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut other_account = claim_accounts.clone();
+        other_account[2] = challenge.clone();
+        assert_eq!(
+            process_instruction(&program_id, &other_account, &claim),
+            Err(ProgramError::InvalidArgument)
+        );
+        let mut impostor = claim_accounts.clone();
+        impostor[0] = fixtures::with_key(impostor[0].clone(), other_key);
+        assert_eq!(
+            process_instruction(&program_id, &impostor, &claim),
+            Err(ProgramError::InvalidSeeds)
+        );
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &claim_accounts, &claim),
+            Err(HelloWorldError::ChallengeNotMet.into())
+        );
+        // Nor can the challengee import a counter of their making
+        let forged_data =
+            migrations::encode_as(STATE_VERSION, &greeting("Hello1234567", 100, true)).unwrap();
+        let forged_key = Pubkey::new_unique();
+        let forged = fixtures::read_only(fixtures::account(
+            forged_key,
+            false,
+            0,
+            forged_data,
+            challengee_key,
+        ));
+        let (config, _) = config_and_authority(&program_id, &Pubkey::new_unique());
+        let import = vec![account.clone(), forged, config, challengee.clone()];
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &import,
+                &HelloWorldInstruction::ImportState.try_to_vec().unwrap()
+            ),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        assert_eq!(
+            process_instruction(&program_id, &claim_accounts, &claim),
+            Err(HelloWorldError::ChallengeNotMet.into())
+        );
+        // Nor can the challenger take it back before the deadline
+        assert_eq!(
+            process_instruction(&program_id, &refund_accounts, &refund),
+            Err(HelloWorldError::ChallengeNotExpired.into())
+        );
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &claim_accounts, &claim).unwrap();
+        assert_eq!((challengee.lamports(), challenge.lamports()), (7_000, 0));
+        assert!(challenge.data.borrow().iter().all(|byte| *byte == 0));
+
+        // From the deadline on it's the challenger's again, even if met
+        open();
+        let mut expired = challenge::Challenge::try_from_slice(&challenge.data.borrow()).unwrap();
+        expired.deadline_slot = SLOT;
+        expired
+            .serialize(&mut &mut challenge.data.borrow_mut()[..])
+            .unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &claim_accounts, &claim),
+            Err(HelloWorldError::ChallengeExpired.into())
+        );
+        let mut unsigned = refund_accounts.clone();
+        unsigned[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &refund),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut not_challenger = refund_accounts.clone();
+        not_challenger[1] = challengee.clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_challenger, &refund),
+            Err(ProgramError::InvalidArgument)
+        );
+        process_instruction(&program_id, &refund_accounts, &refund).unwrap();
+        assert_eq!((challenger.lamports(), challenge.lamports()), (7_000, 0));
+        // Closed, there's nothing left to pay out
+        assert_eq!(
+            process_instruction(&program_id, &refund_accounts, &refund),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_challenge_counts_anyones_greetings() {
+        let program_id = fixtures::program_id();
+        let account = fixtures::greeting_account("challenged", fixtures::GREETING_SIZE);
+        let challenger = fixtures::signer("challenger");
+        let challengee = fixtures::signer("challengee");
+        let challenge_key =
+            challenge::challenge_address(&program_id, challenger.key, challengee.key).0;
+        let challenge = fixtures::account(
+            challenge_key,
+            false,
+            0,
+            vec![0; challenge::CHALLENGE_SIZE],
+            program_id,
+        );
+        // Not created yet, so still the system program's (create_account is a no-op in
+        // unit tests)
+        let mut unopened = challenge.clone();
+        unopened.owner = challenger.owner;
+        let create = HelloWorldInstruction::CreateChallenge {
+            greet_count: 2,
+            deadline_slot: SLOT + 10,
+            amount: 5_000,
+        };
+        let greet = [account.clone(), missing_config(&program_id)];
+        use_test_sysvars();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        let create_accounts = [
+            unopened,
+            challenger,
+            challengee.clone(),
+            account.clone(),
+            fixtures::program(system_program::id()),
+        ];
+        process_instruction(&program_id, &create_accounts, &create.try_to_vec().unwrap())
+            .unwrap();
+
+        // Greet takes no signer, so greetings the challengee didn't send (nor sign) move
+        // the counter to the target too, and the challengee claims
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        let before = challengee.lamports();
+        **challenge.lamports.borrow_mut() = 7_000;
+        process_instruction(
+            &program_id,
+            &[challenge.clone(), challengee.clone(), account],
+            &HelloWorldInstruction::ClaimChallenge.try_to_vec().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(challengee.lamports(), before + 7_000);
+    }
+    #[test]
+    fn test_register_foreign_greeter() {
+        let program_id = fixtures::program_id();
+        let authority = fixtures::signer("authority");
+        let config = fixtures::config_account(&config::Config {
+            authority: authority.key.to_bytes(),
+            ..config::Config::default()
+        });
+        // An emitter on Ethereum, Wormhole chain 2
+        let sender = [7; 32];
+        let (greeter_key, _) = wormhole::foreign_greeter_address(&program_id, 2, &sender);
+        // NOTE The create_account CPI is a no-op in unit tests, so the foreign greeter
+        // starts out allocated
+        let greeter = fixtures::account(
+            greeter_key,
+            false,
+            0,
+            vec![0; wormhole::FOREIGN_GREETER_SIZE],
+            system_program::id(),
+        );
+        let system_program = fixtures::program(system_program::id());
+        let accounts = vec![config, authority, greeter, system_program];
+        let register = |chain| {
+            HelloWorldInstruction::RegisterForeignGreeter { chain, sender }
+                .try_to_vec()
+                .unwrap()
+        };
+        use_test_sysvars();
+
+        // Only the config authority registers emitters
+        let mut unsigned = accounts.clone();
+        unsigned[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &register(2)),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &register(3)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        process_instruction(&program_id, &accounts, &register(2)).unwrap();
+        let registered = accounts[2].data.borrow().to_vec();
+        assert_eq!(
+            wormhole::ForeignGreeter::try_from_slice(&registered).unwrap(),
+            wormhole::ForeignGreeter {
+                chain: 2,
+                sender,
+                counter: 0,
+                last_sequence: 0,
+            }
+        );
+        // Once
+        let mut again = accounts.clone();
+        again[2] = fixtures::account(greeter_key, false, 0, registered, program_id);
+        assert_eq!(
+            process_instruction(&program_id, &again, &register(2)),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn test_receive_cross_chain_greet() {
+        let program_id = fixtures::program_id();
+        // An emitter on Ethereum, Wormhole chain 2, registered
+        let sender = [7; 32];
+        let (greeter_key, _) = wormhole::foreign_greeter_address(&program_id, 2, &sender);
+        let registered = wormhole::ForeignGreeter {
+            chain: 2,
+            sender,
+            ..wormhole::ForeignGreeter::default()
+        };
+        let greeter = fixtures::account(
+            greeter_key,
+            false,
+            0,
+            registered.try_to_vec().unwrap(),
+            program_id,
+        );
+        let hello = "Hello".to_string().try_to_vec().unwrap();
+        let posted = |chain, sender, sequence, payload: &[u8]| {
+            let data = wormhole::posted_vaa(chain, sender, sequence, payload);
+            fixtures::account(fixtures::key("vaa"), false, 0, data, wormhole::devnet::id())
+        };
+        let receive = |vaa, greeter| {
+            let data = HelloWorldInstruction::ReceiveCrossChainGreet
+                .try_to_vec()
+                .unwrap();
+            process_instruction(&program_id, &[vaa, greeter], &data)
+        };
+        let load = || wormhole::ForeignGreeter::try_from_slice(&greeter.data.borrow()).unwrap();
+        use_test_sysvars();
+
+        // Only VAAs the core bridge verified count
+        let vaa = posted(2, sender, 5, &hello);
+        let not_bridge =
+            fixtures::account(*vaa.key, false, 0, vaa.data.borrow().to_vec(), program_id);
+        assert_eq!(
+            receive(not_bridge, greeter.clone()),
+            Err(HelloWorldError::InvalidVaa.into())
+        );
+        let message = posted(2, sender, 5, &hello);
+        message.data.borrow_mut()[..3].copy_from_slice(b"msg");
+        assert_eq!(
+            receive(message, greeter.clone()),
+            Err(HelloWorldError::InvalidVaa.into())
+        );
+        assert_eq!(receive(vaa.clone(), vaa.clone()), Err(ProgramError::InvalidSeeds));
+        // Only from registered emitters: anyone can publish a message
+        let stranger = [8; 32];
+        let (stranger_key, _) = wormhole::foreign_greeter_address(&program_id, 2, &stranger);
+        let unregistered = fixtures::account(stranger_key, false, 0, vec![], system_program::id());
+        assert_eq!(
+            receive(posted(2, stranger, 1, &hello), unregistered),
+            Err(HelloWorldError::UnregisteredEmitter.into())
+        );
+        // And only greetings
+        for payload in [&b"Hello"[..], &[0; 4], &[&hello[..], &[0]].concat()] {
+            assert_eq!(
+                receive(posted(2, sender, 5, payload), greeter.clone()),
+                Err(HelloWorldError::NotAGreeting.into())
+            );
+        }
+        assert_eq!(load(), registered);
+
+        receive(vaa.clone(), greeter.clone()).unwrap();
+        assert_eq!(
+            load(),
+            wormhole::ForeignGreeter {
+                chain: 2,
+                sender,
+                counter: 1,
+                last_sequence: 5,
+            }
+        );
+        // Each VAA counts once, and only after the last one received
+        assert_eq!(
+            receive(vaa, greeter.clone()),
+            Err(HelloWorldError::VaaAlreadyReceived.into())
+        );
+        receive(posted(2, sender, 6, &hello), greeter.clone()).unwrap();
+        assert_eq!((load().counter, load().last_sequence), (2, 6));
+        assert_eq!(
+            receive(posted(2, sender, 3, &hello), greeter.clone()),
+            Err(HelloWorldError::VaaAlreadyReceived.into())
+        );
+    }
+
+    #[test]
+    fn test_greeting_log() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let (config_key, _) = config::config_address(&program_id);
+        let (authority_key, tree_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (log_authority_key, _) = compression::log_authority_address(&program_id);
+        let compression_id = compression::account_compression::id();
+        let (noop_id, system_program_id) = (compression::noop::id(), system_program::id());
+        let config_data = config::Config {
+            authority: authority_key.to_bytes(),
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        // NOTE The compression CPIs are no-ops in unit tests, so the tree stays empty
+        let tree_data = vec![0; compression::merkle_tree_account_size(3, 8)];
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config = fixtures::account(config_key, false, 0, config_data, program_id);
+        let authority = fixtures::read_only(fixtures::account(
+            authority_key,
+            true,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let tree = fixtures::account(tree_key, false, 0, tree_data, compression_id);
+        let log_authority = fixtures::read_only(fixtures::account(
+            log_authority_key,
+            false,
+            0,
+            vec![],
+            system_program_id,
+        ));
+        let compression_program = fixtures::program(compression_id);
+        let noop_program = fixtures::program(noop_id);
+
+        let init = HelloWorldInstruction::InitGreetingLog {
+            max_depth: 3,
+            max_buffer_size: 8,
+        }
+        .try_to_vec()
+        .unwrap();
+        let log = vec![tree.clone(), log_authority, compression_program, noop_program.clone()];
+        let init_accounts = [&[config.clone(), authority.clone()][..], &log].concat();
+        let greet = [&[account.clone(), config.clone()][..], &log].concat();
+        use_test_sysvars();
+
+        // Greetings aren't logged until the authority starts the log
+        process_instruction(&program_id, &greet[..2], &message("Hello1234567")).unwrap();
+        let mut not_authority = init_accounts.clone();
+        not_authority[1].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &not_authority, &init),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        // The tree has to be allocated to the compression program first
+        let mut not_allocated = init_accounts.clone();
+        not_allocated[2] = fixtures::with_owner(not_allocated[2].clone(), system_program_id);
+        assert_eq!(
+            process_instruction(&program_id, &not_allocated, &init),
+            Err(HelloWorldError::InvalidGreetingLog.into())
+        );
+        let mut not_log_authority = init_accounts.clone();
+        not_log_authority[3] = authority.clone();
+        assert_eq!(
+            process_instruction(&program_id, &not_log_authority, &init),
+            Err(ProgramError::InvalidSeeds)
+        );
+        process_instruction(&program_id, &init_accounts, &init).unwrap();
+        let stored = config::Config::unpack(&config.data.borrow()).unwrap();
+        assert_eq!(stored.greeting_log, tree_key.to_bytes());
+
+        // From then on every Greet has to log to it
+        assert_eq!(
+            process_instruction(&program_id, &greet[..2], &message("Hello1234567")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let mut other_tree = greet.clone();
+        other_tree[2] = noop_program.clone();
+        assert_eq!(
+            process_instruction(&program_id, &other_tree, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidGreetingLog.into())
+        );
+        let mut other_noop = greet.clone();
+        other_noop[5] = tree;
+        assert_eq!(
+            process_instruction(&program_id, &other_noop, &message("Hello1234567")),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 2);
+    }
+
+    #[test]
+    fn test_greeting_receipts() {
+        let program_id = Pubkey::new_unique();
+        let (key, signer_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let (receipt_key, _) = receipt::receipt_address(&program_id, &key, 0);
+        let (next_receipt_key, _) = receipt::receipt_address(&program_id, &key, 1);
+        let system_program_id = system_program::id();
+        let config_data = config::Config {
+            greeting_receipts: true,
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        // NOTE The create_account CPI is a no-op in unit tests, so the receipt starts out
+        // created
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, config_data, program_id));
+        let receipt =
+            fixtures::account(receipt_key, false, 0, vec![0; receipt::RECEIPT_SIZE], program_id);
+        let signer = fixtures::account(signer_key, true, 0, vec![], system_program_id);
+        let system_program = fixtures::program(system_program_id);
+        let greet = vec![account.clone(), config.clone(), receipt, signer, system_program];
+        use_test_sysvars();
+
+        // Every Greet has to leave its receipt, signed for
+        assert_eq!(
+            process_instruction(&program_id, &greet[..2], &message("Hello1234567")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let mut unsigned = greet.clone();
+        unsigned[3].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &message("Hello1234567")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        let stored = receipt::GreetingReceipt::unpack(&greet[2].data.borrow()).unwrap();
+        assert_eq!(stored.signer, signer_key.to_bytes());
+
+        // The next greeting's receipt is at the next counter, and can't be made twice
+        assert_eq!(
+            process_instruction(&program_id, &greet, &message("Hello1234567")),
+            Err(ProgramError::InvalidSeeds)
+        );
+        let mut taken = greet.clone();
+        taken[2] = fixtures::with_key(taken[2].clone(), next_receipt_key);
+        assert_eq!(
+            process_instruction(&program_id, &taken, &message("Hello1234567")),
+            Err(HelloWorldError::ReceiptExists.into())
+        );
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 1);
+
+        // Increment leaves no receipt, so it's refused
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &greet[..2],
+                &HelloWorldInstruction::Increment.try_to_vec().unwrap()
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_balance_weight() {
+        let program_id = Pubkey::new_unique();
+        let (key, owner_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config_key, _) = config::config_address(&program_id);
+        let token_key = Pubkey::new_unique();
+        let config_data = config::Config {
+            balance_mint: [5; 32],
+            balance_unit: 100,
+            ..config::Config::default()
+        }
+        .try_to_vec()
+        .unwrap();
+        let token_data = token::token_account([5; 32], owner_key.to_bytes(), 250);
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let config =
+            fixtures::read_only(fixtures::account(config_key, false, 0, config_data, program_id));
+        let token_account =
+            fixtures::read_only(fixtures::account(token_key, false, 0, token_data, token::id()));
+        let owner = fixtures::read_only(fixtures::account(owner_key, true, 0, vec![], program_id));
+        let greet = vec![account.clone(), config.clone(), token_account, owner];
+        use_test_sysvars();
+
+        // The balance is read from the owner's token account of the balance mint
+        assert_eq!(
+            process_instruction(&program_id, &greet[..2], &message("Hello1234567")),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let mut unsigned = greet.clone();
+        unsigned[3].is_signer = false;
+        assert_eq!(
+            process_instruction(&program_id, &unsigned, &message("Hello1234567")),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut not_the_owner = greet.clone();
+        not_the_owner[3] = not_the_owner[1].clone();
+        not_the_owner[3].is_signer = true;
+        assert_eq!(
+            process_instruction(&program_id, &not_the_owner, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidBalanceAccount.into())
+        );
+        greet[2].data.borrow_mut()[..32].copy_from_slice(&[6; 32]);
+        assert_eq!(
+            process_instruction(&program_id, &greet, &message("Hello1234567")),
+            Err(HelloWorldError::InvalidBalanceAccount.into())
+        );
+
+        // 250 base units are 2 whole units of 100
+        greet[2].data.borrow_mut()[..32].copy_from_slice(&[5; 32]);
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(GreetingAccount::unpack(&account.data.borrow()).unwrap().counter, 2);
+
+        // Increment and GreetMany count 1 whatever the balance, so they're refused
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &greet[..2],
+                &HelloWorldInstruction::Increment.try_to_vec().unwrap()
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[config, account],
+                &HelloWorldInstruction::GreetMany {
+                    txt: "Hello1234567".to_string()
+                }
+                .try_to_vec()
+                .unwrap()
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_epoch_bonus() {
+        let program_id = fixtures::program_id();
+        let account = fixtures::greeting_account("greeted", MESSAGE_SIZE);
+        // SLOT is the first slot of EPOCH, so it's in the bonus
+        let config = fixtures::config_account(&config::Config {
+            epoch_bonus_slots: 10,
+            ..config::Config::default()
+        });
+        let greet = vec![account.clone(), config.clone()];
+        let counter = || GreetingAccount::unpack(&account.data.borrow()).unwrap().counter;
+
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &greet, &message("Hello1234567"))
+        });
+        result.unwrap();
+        assert_eq!(counter(), 2);
+        assert!(logs.contains(
+            &"Program log: Counted double in the first 10 slot(s) of the epoch".to_string()
+        ));
+
+        // Increment and GreetMany get the bonus too
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+        process_instruction(&program_id, &greet, &increment).unwrap();
+        assert_eq!(counter(), 4);
+        let greet_many = HelloWorldInstruction::GreetMany {
+            txt: "Hello1234567".to_string(),
+        };
+        let many = vec![config.clone(), account.clone()];
+        process_instruction(&program_id, &many, &greet_many.try_to_vec().unwrap()).unwrap();
+        assert_eq!(counter(), 6);
+
+        // Without it a greeting counts once
+        config::Config::default()
+            .pack(&mut config.data.borrow_mut())
+            .unwrap();
+        process_instruction(&program_id, &greet, &message("Hello1234567")).unwrap();
+        assert_eq!(counter(), 7);
+    }
+
+    #[test]
+    fn test_read_only_account_requires_migration() {
+        let key = Pubkey::default();
+        let account = fixtures::read_only(fixtures::account(
+            key,
+            false,
+            0,
+            vec![0; MESSAGE_SIZE],
+            Pubkey::default(),
+        ));
+        assert_eq!(
+            load_greeting(&account),
+            Err(HelloWorldError::MigrationRequired.into())
+        );
+    }
+
+    #[test]
+    fn test_rejects_wrong_owner() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::default();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], Pubkey::new_unique());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_rejects_executable_account() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        let account = fixtures::with_executable(
+            fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], Pubkey::default()),
+            true,
+        );
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello1234567")),
+            Err(HelloWorldError::ExecutableAccount.into())
+        );
+        // Nothing should have been written into the account
+        assert_eq!(*accounts[0].data.borrow(), vec![0; MESSAGE_SIZE]);
+    }
+
+    #[test]
+    fn test_rejects_nested_greeting() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let data = vec![0; MESSAGE_SIZE];
+        let account = fixtures::account(key, false, 0, data, program_id);
+        let accounts = vec![account, missing_config(&program_id)];
+        let greet_many = HelloWorldInstruction::GreetMany {
+            txt: "Hi".to_string(),
+        };
+        let greet_many = greet_many.try_to_vec().unwrap();
+        let many_accounts = [accounts[1].clone(), accounts[0].clone()];
+
+        use_test_sysvars();
+        process_instruction(&program_id, &accounts, &message("Hello1234567")).unwrap();
+
+        // NOTE A wrapper re-entering us while our Greet is calling out would hand the
+        // nested Greet the account as the outer one left it, marked. Off chain the
+        // CPIs are no-ops, so mark it the way the outer Greet does.
+        let lock = reentrancy::lock(&accounts[0]).unwrap();
+        let increment = HelloWorldInstruction::Increment.try_to_vec().unwrap();
+        for (accounts, data) in [
+            (&accounts[..], message("Hello7654321")),
+            (&accounts[..], increment),
+            (&many_accounts[..], greet_many),
+        ] {
+            assert_eq!(
+                process_instruction(&program_id, accounts, &data),
+                Err(HelloWorldError::Reentrancy.into())
+            );
+        }
+        drop(lock);
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello1234567", 1, true)
+        );
+
+        // Each Greet lifts its own mark, so greetings one after the other still count
+        process_instruction(&program_id, &accounts, &message("Hello7654321")).unwrap();
+        assert!(!reentrancy::is_locked(&accounts[0].data.borrow()));
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello7654321", 2, true)
+        );
+    }
+
+    #[test]
+    fn test_rejects_message_too_long() {
+        let program_id = Pubkey::default();
+        let key = Pubkey::default();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], Pubkey::default());
+
+        let accounts = vec![account, missing_config(&program_id)];
+
+        // Too long for the compact encoding as well
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&program_id, &accounts, &message(&"Hello1234567".repeat(9)))
+        });
+        assert_eq!(result, Err(ProgramError::Custom(6001)));
+        assert_eq!(result, Err(HelloWorldError::MessageTooLong.into()));
+        assert_eq!(logs.last().unwrap(), "Program log: Error: MessageTooLong (6001)");
+        assert_eq!(*accounts[0].data.borrow(), vec![0; MESSAGE_SIZE]);
+    }
+
+    #[test]
+    fn test_version() {
+        let program_id = Pubkey::default();
+        let instruction_data = HelloWorldInstruction::Version.try_to_vec().unwrap();
+        assert_eq!(instruction_data, vec![1]);
+        assert_eq!(process_instruction(&program_id, &[], &instruction_data), Ok(()));
+    }
+
+    #[test]
+    fn test_export_state() {
+        let account = fixtures::greeting_account("exported", 4);
+        account.data.borrow_mut().copy_from_slice(&[0, 1, 0xab, 0xff]);
+        let export = HelloWorldInstruction::ExportState.try_to_vec().unwrap();
+
+        // NOTE The line is the export's whole record, so it's logged without the "log"
+        // feature too (cargo test --no-default-features --features program)
+        let (result, logs) = crate::test_utils::capture_logs(|| {
+            process_instruction(&fixtures::program_id(), std::slice::from_ref(&account), &export)
+        });
+        result.unwrap();
+        assert!(logs.contains(&format!(
+            "Program log: helloworld-export: {} 0001abff",
+            account.key
+        )));
+        let other = fixtures::account(fixtures::key("other"), false, 0, vec![1], Pubkey::default());
+        assert_eq!(
+            process_instruction(&fixtures::program_id(), &[other], &export),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_import_state() {
+        let program_id = Pubkey::new_unique();
+        let old_program_id = Pubkey::new_unique();
+        let (key, source_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // NOTE A shorter message than the account holds leaves trailing bytes behind
+        let mut source_data = migrations::encode_as(STATE_VERSION, &greeting("Hello", 41, true)).unwrap();
+        source_data.extend_from_slice(&[7; 4]);
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let source = fixtures::read_only(fixtures::account(
+            source_key,
+            false,
+            0,
+            source_data,
+            old_program_id,
+        ));
+        let (config, authority) = config_and_authority(&program_id, &Pubkey::new_unique());
+        let accounts = vec![account, source, config, authority];
+
+        let export = HelloWorldInstruction::ExportState.try_to_vec().unwrap();
+        let import = HelloWorldInstruction::ImportState.try_to_vec().unwrap();
+        assert_eq!(import, vec![3]);
+        // The source belongs to the old deployment, only it can export the account
+        assert_eq!(
+            process_instruction(&program_id, &accounts[1..2], &export),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            process_instruction(&old_program_id, &accounts[1..2], &export),
+            Ok(())
+        );
+
+        process_instruction(&program_id, &accounts, &import).unwrap();
+        // The counter and calendar move along with the message
+        assert_eq!(
+            GreetingAccount::unpack(&accounts[0].data.borrow()).unwrap(),
+            greeting("Hello", 41, true)
+        );
+        // Importing into an account of the old deployment is refused
+        assert_eq!(
+            process_instruction(&old_program_id, &accounts, &import),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_import_state_needs_the_config_authority() {
+        let program_id = Pubkey::new_unique();
+        let (key, source_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // A greeting anyone can write, claiming a million greetings
+        let source_data =
+            migrations::encode_as(STATE_VERSION, &greeting("Forged", 1_000_000, true)).unwrap();
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let source = fixtures::read_only(fixtures::account(
+            source_key,
+            false,
+            0,
+            source_data,
+            Pubkey::new_unique(),
+        ));
+        let authority_key = Pubkey::new_unique();
+        let (config, authority) = config_and_authority(&program_id, &authority_key);
+        let import = HelloWorldInstruction::ImportState.try_to_vec().unwrap();
+
+        // Signed by someone else
+        let (_, impostor) = config_and_authority(&program_id, &Pubkey::new_unique());
+        let accounts = vec![account.clone(), source.clone(), config.clone(), impostor];
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &import),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        // The authority, without its signature
+        let mut unsigned = authority;
+        unsigned.is_signer = false;
+        let accounts = vec![account.clone(), source.clone(), config, unsigned];
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &import),
+            Err(HelloWorldError::Unauthorized.into())
+        );
+        // A config account of our own making, naming the forger as authority
+        let (config, forger) = config_and_authority(&program_id, &Pubkey::new_unique());
+        let forged_config = fixtures::account(
+            Pubkey::new_unique(),
+            false,
+            0,
+            config.data.borrow().to_vec(),
+            program_id,
+        );
+        let accounts = vec![account.clone(), source, forged_config, forger];
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &import),
+            Err(HelloWorldError::InvalidConfig.into())
+        );
+        assert_eq!(*account.data.borrow(), vec![0; MESSAGE_SIZE]);
+    }
+
+    #[test]
+    fn test_import_state_rejects_source_without_greeting() {
+        let program_id = Pubkey::new_unique();
+        let (key, source_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // A length prefix claiming more bytes than there are
+        let account = fixtures::account(key, false, 0, vec![0; MESSAGE_SIZE], program_id);
+        let source =
+            fixtures::read_only(fixtures::account(source_key, false, 0, vec![0xff; 4], program_id));
+        let (config, authority) = config_and_authority(&program_id, &Pubkey::new_unique());
+        let accounts = vec![account, source, config, authority];
+
+        let import = HelloWorldInstruction::ImportState.try_to_vec().unwrap();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &import),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(*accounts[0].data.borrow(), vec![0; MESSAGE_SIZE]);
+    }
+
+    #[test]
+    fn test_greetings_refuse_other_accounts() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        // A foreign greeter for an Ethereum address, whose chain id and address padding
+        // read as a 2 character schema 1 message
+        let mut sender = [0; 32];
+        sender[12..].copy_from_slice(&[0xab; 20]);
+        let data = wormhole::ForeignGreeter {
+            chain: 2,
+            sender,
+            counter: 3,
+            last_sequence: 9,
+        }
+        .try_to_vec()
+        .unwrap();
+        let before = data.clone();
+        let account = fixtures::account(key, false, 0, data, program_id);
+        let accounts = vec![account, missing_config(&program_id)];
+
+        use_test_sysvars();
+        assert_eq!(
+            process_instruction(&program_id, &accounts, &message("Hello")),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &accounts,
+                &HelloWorldInstruction::Increment.try_to_vec().unwrap()
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(*accounts[0].data.borrow(), before);
+    }
+
+    #[test]
+    fn test_rejects_unknown_instruction() {
+        let program_id = Pubkey::default();
+        assert_eq!(
+            process_instruction(&program_id, &[], &[42]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &[], &[]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_validate_program_account() {
+        let system_program_id = solana_program::system_program::id();
+        let system_program = fixtures::program(system_program_id);
+        assert_eq!(
+            validate_program_account(&system_program, &system_program_id),
+            Ok(())
+        );
+
+        // Right executable, wrong key
+        let token_program_id = Pubkey::new_unique();
+        assert_eq!(
+            validate_program_account(&system_program, &token_program_id),
+            Err(ProgramError::IncorrectProgramId)
+        );
+
+        // Right key, but not actually a program
+        let impostor = fixtures::read_only(fixtures::with_executable(
+            fixtures::program(system_program_id),
+            false,
+        ));
+        assert_eq!(
+            validate_program_account(&impostor, &system_program_id),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_context() {
+        let program_id = fixtures::program_id();
+        let greeted = fixtures::greeting_account("greeted", 100);
+        let (tag_counters_key, bump) = tags::tag_counters_address(&program_id, greeted.key);
+        let tag_counters =
+            fixtures::account(tag_counters_key, false, 0, vec![], system_program::id());
+        let count_tag = vec![
+            fixtures::config_account(&config::Config::default()),
+            greeted.clone(),
+            fixtures::signer("payer"),
+            tag_counters,
+            fixtures::program(system_program::id()),
+        ];
+
+        // Every account checked, the PDA's bump kept for the processor
+        let context =
+            Context::<CountTagAccounts>::load(&program_id, &count_tag, tag::COUNT_TAG, &())
+                .unwrap();
+        assert_eq!(context.accounts.tag_counters.key, &tag_counters_key);
+        assert_eq!(context.accounts.tag_counters_bump, bump);
+        assert_eq!(context.remaining.len(), 0);
+        let load = |accounts: &[AccountInfo]| {
+            Context::<CountTagAccounts>::load(&program_id, accounts, tag::COUNT_TAG, &()).err()
+        };
+        let mut unsigned = count_tag.clone();
+        unsigned[accounts::tags::PAYER_IDX].is_signer = false;
+        assert_eq!(load(&unsigned), Some(ProgramError::MissingRequiredSignature));
+        let mut wrong_pda = count_tag.clone();
+        wrong_pda[accounts::tags::TAG_COUNTERS_IDX] = fixtures::signer("tag-counters");
+        assert_eq!(load(&wrong_pda), Some(ProgramError::InvalidSeeds));
+        assert_eq!(load(&count_tag[..4]), Some(ProgramError::NotEnoughAccountKeys));
+
+        // The accounts after the fixed ones are left to the processor
+        let config = config::Config {
+            crank_authority: fixtures::key("crank").to_bytes(),
+            ..config::Config::default()
+        };
+        let snapshot_counters = vec![
+            fixtures::config_account(&config),
+            fixtures::signer("crank"),
+            fixtures::account(fixtures::key("snapshot"), false, 0, vec![], program_id),
+            greeted.clone(),
+            greeted,
+        ];
+        let context = Context::<SnapshotCountersAccounts>::load(
+            &program_id,
+            &snapshot_counters,
+            tag::SNAPSHOT_COUNTERS,
+            &(),
+        )
+        .unwrap();
+        assert_eq!(context.remaining.len(), 2);
+        assert_eq!(context.accounts.config.crank_authority, config.crank_authority);
     }
 }
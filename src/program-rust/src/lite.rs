@@ -0,0 +1,117 @@
+//! Increment on pinocchio instead of solana-program, to measure what the monolithic
+//! SDK costs a program in compute units and binary size
+// NOTE The "pinocchio" feature builds this instead of the program: only the SDK-free
+// modules (increment, config, migrations, ...) compile next to it, and the entrypoint
+// here takes Increment and refuses every other instruction. It's the instruction that
+// already avoids the heap (see process_increment() in lib.rs), so the difference left
+// is the SDK's: the entrypoint's account deserializing, the sysvar and PDA syscalls and
+// what they pull into the binary. `cargo xtask bench-sdk` builds both and compares.
+//
+// It's a benchmark, not a second deployment. It checks what process_increment() checks
+// and writes the same bytes, with two gaps: it doesn't read the instructions sysvar, so
+// the direct and CPI counters aren't counted, and it refuses a config with an epoch
+// bonus rather than reading the epoch schedule. Nor does it log the state hash.
+use crate::{
+    accounts,
+    config::{self, Config},
+    error::HelloWorldError,
+    increment,
+    instruction::tag,
+    migrations, reentrancy,
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    log::sol_log,
+    program_error::ProgramError,
+    pubkey::{self, Pubkey},
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
+
+/// Run an Increment, the only instruction this build takes
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data != [tag::INCREMENT as u8] {
+        sol_log("Only Increment is built on pinocchio");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let greeting_account = accounts
+        .get(accounts::GREETING_ACCOUNT_IDX)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let config = read_config(
+        program_id,
+        accounts
+            .get(accounts::CONFIG_IDX)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?,
+    )?;
+    if !greeting_account.is_owned_by(program_id) {
+        sol_log("Greeted account does not have the correct program id");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if greeting_account.executable() {
+        return Err(error(HelloWorldError::ExecutableAccount));
+    }
+    if reentrancy::is_locked(&greeting_account.try_borrow_data()?) {
+        return Err(error(HelloWorldError::Reentrancy));
+    }
+    if config.charges_fee()
+        || config.has_greeting_log()
+        || config.greeting_receipts
+        || config.weighs_balance()
+        || config.epoch_bonus_slots != 0
+    {
+        sol_log("The config needs what this build leaves out, use the program");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if config.paused {
+        return Err(error(HelloWorldError::Paused));
+    }
+
+    let clock = Clock::get()?;
+    let mut data = greeting_account.try_borrow_mut_data()?;
+    if let Some((year, days)) = increment::calendar(&data) {
+        if config.on_cooldown(year, &days, clock.unix_timestamp) {
+            return Err(error(HelloWorldError::OnCooldown));
+        }
+    }
+    let counted = increment::increment(
+        &mut data,
+        1,
+        config.decay_half_life_epochs,
+        clock.epoch,
+        clock.unix_timestamp,
+        None,
+    );
+    if counted.is_none() {
+        if migrations::stored_version(&data).is_err() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        return Err(error(HelloWorldError::MigrationRequired));
+    }
+    sol_log("Counted a greeting");
+    Ok(())
+}
+
+/// The config, or the default one while it's not initialized, like greet_config()
+fn read_config(program_id: &Pubkey, account: &AccountInfo) -> Result<Config, ProgramError> {
+    let (address, _) = pubkey::find_program_address(&[config::CONFIG_SEED], program_id);
+    if *account.key() != address {
+        return Err(error(HelloWorldError::InvalidConfig));
+    }
+    if !account.is_owned_by(program_id) || account.data_is_empty() {
+        return Ok(Config::default());
+    }
+    Config::unpack(&account.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// `err` as the program returns it, with its message logged
+fn error(err: HelloWorldError) -> ProgramError {
+    sol_log(err.message());
+    ProgramError::Custom(err.code())
+}
@@ -0,0 +1,95 @@
+//! The lucky-greeter draw: every greeting gets one chance to win the tip vault
+// NOTE The vault is a PDA of this program at seeds [VAULT_SEED] that nobody holds the
+// key to. It's left owned by the system program, so tipping is a plain SOL transfer
+// to its address (or pointing the config's fee recipient at it), and the program pays
+// it out with a transfer it signs for with the seeds.
+//
+// A draw is a commit and a reveal of Switchboard randomness (see switchboard.rs):
+// CommitLuck binds the greeting account's ticket to a freshly seeded randomness
+// account, and RevealLuck settles it once the oracle has revealed the value. The
+// ticket (a PDA at [TICKET_SEED, greeting account]) also remembers the greeting the
+// last draw was for, so a greeting can't be drawn for twice.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of the tip vault's address
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Seed of a greeting account's ticket, followed by the greeting account's key
+pub const TICKET_SEED: &[u8] = b"ticket";
+
+/// One chance in LUCK_ODDS to win
+pub const LUCK_ODDS: u64 = 100;
+
+/// A greeting account's draw
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct LuckTicket {
+    /// key of whoever committed to the pending draw, paid if it wins
+    pub greeter: [u8; 32],
+    /// key of the randomness account of the pending draw, all zeros for none
+    pub randomness: [u8; 32],
+    /// slot that randomness was seeded in
+    pub seed_slot: u64,
+    /// counter of the greeting last drawn for
+    pub counter: u64,
+    /// last_update_epoch of the greeting last drawn for
+    pub last_update_epoch: u64,
+}
+
+/// Size of a ticket account's data
+pub const TICKET_SIZE: usize = 32 + 32 + 8 + 8 + 8;
+
+impl LuckTicket {
+    /// Whether a draw is committed and not revealed yet
+    pub fn is_pending(&self) -> bool {
+        self.randomness != [0; 32]
+    }
+}
+
+/// Whether the revealed `value` wins
+pub fn is_winner(value: &[u8; 32]) -> bool {
+    let mut low = [0; 8];
+    low.copy_from_slice(&value[..8]);
+    u64::from_le_bytes(low) % LUCK_ODDS == 0
+}
+
+/// Address and bump seed of the tip vault of the program at `program_id`
+#[cfg(feature = "program")]
+pub fn vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED], program_id)
+}
+
+/// Address and bump seed of the ticket of `greeting_account`
+#[cfg(feature = "program")]
+pub fn ticket_address(program_id: &Pubkey, greeting_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TICKET_SEED, greeting_account.as_ref()], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ticket_size() {
+        assert_eq!(
+            LuckTicket::default().try_to_vec().unwrap().len(),
+            TICKET_SIZE
+        );
+        assert!(!LuckTicket::default().is_pending());
+    }
+
+    #[test]
+    fn test_odds() {
+        let value = |n: u64| {
+            let mut value = [0xff; 32];
+            value[..8].copy_from_slice(&n.to_le_bytes());
+            value
+        };
+        assert!(is_winner(&value(0)));
+        assert!(is_winner(&value(LUCK_ODDS * 7)));
+        assert!(!is_winner(&value(LUCK_ODDS * 7 + 1)));
+        let wins = (0..10_000).filter(|n| is_winner(&value(*n))).count();
+        assert_eq!(wins as u64, 10_000 / LUCK_ODDS);
+    }
+}
@@ -0,0 +1,471 @@
+//! Upgrades of greeting account data from one schema version to the next
+// NOTE Every change to the GreetingAccount layout bumps STATE_VERSION and registers the
+// function turning the previous layout into the new one in MIGRATIONS. Accounts are
+// upgraded lazily: the processor runs the chain when it next uses an account it may
+// write, and refuses a read-only account still on an old version with
+// HelloWorldError::MigrationRequired.
+//
+// Version 1 accounts hold the bare Borsh GreetingAccount. From version 2 on the data
+// opens with a header, STATE_MAGIC followed by the version byte. A version 1 account
+// can't look like that: its first 4 bytes are the message length, and a length whose
+// top byte isn't zero (16 MiB or more) is bigger than any account can be. Compactly
+// encoded accounts (see `compact`) carry the same header under COMPACT_MAGIC.
+//
+// From version 6 on the Borsh layout opens with Anchor's 8-byte account discriminator,
+// GREETING_DISCRIMINATOR, instead, so explorers and parsers that read Anchor IDLs
+// recognize a greeting account without a plugin (see `--anchor` in the schema crate's
+// idl binary). Its fourth byte isn't zero either. The discriminator names the struct,
+// not a version: the next layout change has to give the new one a name (and so a
+// discriminator) of its own. Compact accounts keep their header, they aren't Borsh.
+//
+// Having neither doesn't make data a version 1 greeting: the program owns accounts
+// of other types without a discriminator, and some of them decode as a Borsh String
+// (a foreign greeter's chain id reads as a short length). So version 1 is only read
+// where it was written: an account of exactly V1_ACCOUNT_SIZE, the size every client
+// created before schema 2, holding a message that fits it. Any other size fails closed,
+// whatever type it is, so a new account type needs no entry here. All zeros is a new
+// account, which holds nothing a greeting could overwrite.
+use crate::{
+    calendar::DayBitmap, compact::COMPACT_MAGIC, GreetingAccount, GreetingAccountV1,
+    GreetingAccountV2, GreetingAccountV3, GreetingAccountV4, STATE_VERSION,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::io;
+
+/// First bytes of the data of every account at schema version 2 or later
+pub const STATE_MAGIC: [u8; 3] = *b"HWS";
+
+/// Size of the STATE_MAGIC + version header
+pub const HEADER_SIZE: usize = STATE_MAGIC.len() + 1;
+
+/// Anchor discriminator of GreetingAccount: sha256("account:GreetingAccount")[..8]
+pub const GREETING_DISCRIMINATOR: [u8; 8] = [190, 16, 56, 57, 246, 26, 112, 24];
+
+/// First schema version opening with GREETING_DISCRIMINATOR rather than STATE_MAGIC
+pub const DISCRIMINATOR_VERSION: u8 = 6;
+
+/// Size of every schema 1 account: the Borsh String of the original client's 12
+/// character message
+pub const V1_ACCOUNT_SIZE: usize = 4 + 12;
+
+/// Turns the state of one schema version (without header) into the next one's
+pub type Migration = fn(&[u8]) -> io::Result<Vec<u8>>;
+
+/// `MIGRATIONS[i]` upgrades schema version `i + 1` to `i + 2`
+pub const MIGRATIONS: &[Migration] = &[
+    add_counter,
+    add_calendar,
+    add_last_update,
+    add_origin_counters,
+    add_discriminator,
+];
+
+/// Schema 1 -> 2: count greetings. Accounts migrated from schema 1 start at 0.
+fn add_counter(body: &[u8]) -> io::Result<Vec<u8>> {
+    let greeting = GreetingAccountV1::deserialize(&mut &body[..])?;
+    GreetingAccountV2 {
+        txt: greeting.txt,
+        counter: 0,
+    }
+    .try_to_vec()
+}
+
+/// Schema 2 -> 3: record the days greeted on, starting with an empty calendar
+fn add_calendar(body: &[u8]) -> io::Result<Vec<u8>> {
+    let greeting = GreetingAccountV2::deserialize(&mut &body[..])?;
+    GreetingAccountV3 {
+        txt: greeting.txt,
+        counter: greeting.counter,
+        calendar_year: 0,
+        greeted_days: DayBitmap::default(),
+    }
+    .try_to_vec()
+}
+
+/// Schema 3 -> 4: decay the counter over time. Migrated accounts aren't stamped with
+/// an epoch, so they don't decay until their next greeting (see decay.rs).
+fn add_last_update(body: &[u8]) -> io::Result<Vec<u8>> {
+    let greeting = GreetingAccountV3::deserialize(&mut &body[..])?;
+    GreetingAccountV4 {
+        txt: greeting.txt,
+        counter: greeting.counter,
+        calendar_year: greeting.calendar_year,
+        greeted_days: greeting.greeted_days,
+        last_update_epoch: 0,
+    }
+    .try_to_vec()
+}
+
+/// Schema 4 -> 5: count direct and CPI greetings apart. The greetings before the
+/// migration aren't known to be either, so both start at 0 (see origin.rs).
+fn add_origin_counters(body: &[u8]) -> io::Result<Vec<u8>> {
+    let greeting = GreetingAccountV4::deserialize(&mut &body[..])?;
+    GreetingAccount {
+        txt: greeting.txt,
+        counter: greeting.counter,
+        calendar_year: greeting.calendar_year,
+        greeted_days: greeting.greeted_days,
+        last_update_epoch: greeting.last_update_epoch,
+        direct_greets: 0,
+        cpi_greets: 0,
+    }
+    .try_to_vec()
+}
+
+/// Schema 5 -> 6: open with Anchor's account discriminator, the fields are the same
+fn add_discriminator(body: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(body.to_vec())
+}
+
+/// Account data holding `account` as schema `version`, dropping the fields older
+/// versions don't have. For accounts too small to be migrated, see store_greeting().
+pub fn encode_as(version: u8, account: &GreetingAccount) -> io::Result<Vec<u8>> {
+    let body = match version {
+        1 => GreetingAccountV1 {
+            txt: account.txt.clone(),
+        }
+        .try_to_vec()?,
+        2 => GreetingAccountV2 {
+            txt: account.txt.clone(),
+            counter: account.counter,
+        }
+        .try_to_vec()?,
+        3 => GreetingAccountV3 {
+            txt: account.txt.clone(),
+            counter: account.counter,
+            calendar_year: account.calendar_year,
+            greeted_days: account.greeted_days.clone(),
+        }
+        .try_to_vec()?,
+        4 => GreetingAccountV4 {
+            txt: account.txt.clone(),
+            counter: account.counter,
+            calendar_year: account.calendar_year,
+            greeted_days: account.greeted_days.clone(),
+            last_update_epoch: account.last_update_epoch,
+        }
+        .try_to_vec()?,
+        5..=STATE_VERSION => account.try_to_vec()?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown schema version {}", version),
+            ))
+        }
+    };
+    Ok(encode(version, &body))
+}
+
+/// Schema version in the header `data` opens with, if it opens with one
+fn header_version(data: &[u8]) -> Option<u8> {
+    let magic = data.get(..STATE_MAGIC.len())?;
+    match data.get(STATE_MAGIC.len()) {
+        Some(&version) if (magic == STATE_MAGIC || magic == COMPACT_MAGIC) && version >= 2 => {
+            Some(version)
+        }
+        _ => None,
+    }
+}
+
+/// Whether `data` is a schema 1 account: V1_ACCOUNT_SIZE bytes opening with a Borsh
+/// String that fits them (a shorter message leaves the rest as it was)
+fn is_version_1(data: &[u8]) -> bool {
+    if data.len() != V1_ACCOUNT_SIZE {
+        return false;
+    }
+    let mut len = [0; 4];
+    len.copy_from_slice(&data[..4]);
+    let len = u32::from_le_bytes(len) as usize;
+    match data.get(4..4usize.saturating_add(len)) {
+        Some(txt) => std::str::from_utf8(txt).is_ok(),
+        None => false,
+    }
+}
+
+/// Schema version of the state in `data`, or an error if it isn't a greeting account's
+pub fn stored_version(data: &[u8]) -> io::Result<u8> {
+    if data.starts_with(&GREETING_DISCRIMINATOR) {
+        return Ok(DISCRIMINATOR_VERSION);
+    }
+    if let Some(version) = header_version(data) {
+        return Ok(version);
+    }
+    if is_version_1(data) || data.iter().all(|byte| *byte == 0) {
+        return Ok(1);
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not a greeting account",
+    ))
+}
+
+/// The state in `data`, without its header
+pub fn state_body(data: &[u8]) -> &[u8] {
+    if data.starts_with(&GREETING_DISCRIMINATOR) {
+        &data[GREETING_DISCRIMINATOR.len()..]
+    } else if header_version(data).is_some() {
+        &data[HEADER_SIZE..]
+    } else {
+        data
+    }
+}
+
+/// Account data holding `body` as schema `version`
+pub fn encode(version: u8, body: &[u8]) -> Vec<u8> {
+    if version == 1 {
+        return body.to_vec();
+    }
+    if version >= DISCRIMINATOR_VERSION {
+        return [&GREETING_DISCRIMINATOR[..], body].concat();
+    }
+    let mut data = Vec::with_capacity(HEADER_SIZE + body.len());
+    data.extend_from_slice(&STATE_MAGIC);
+    data.push(version);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Run `migrations` over `data` up to schema `target`. Returns the version found and
+/// the upgraded account data, or None when `data` is already at `target`.
+pub fn migrate_with(
+    migrations: &[Migration],
+    target: u8,
+    data: &[u8],
+) -> io::Result<Option<(u8, Vec<u8>)>> {
+    let version = stored_version(data)?;
+    if version > target {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "schema version {} is newer than this program's {}",
+                version, target
+            ),
+        ));
+    }
+    if version == target {
+        return Ok(None);
+    }
+    let mut body = state_body(data).to_vec();
+    for from in version..target {
+        let migration = migrations.get(from as usize - 1).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no migration registered from schema version {}", from),
+            )
+        })?;
+        body = migration(&body)?;
+    }
+    Ok(Some((version, encode(target, &body))))
+}
+
+/// Upgrade `data` to STATE_VERSION, see `migrate_with()`
+pub fn migrate(data: &[u8]) -> io::Result<Option<(u8, Vec<u8>)>> {
+    migrate_with(MIGRATIONS, STATE_VERSION, data)
+}
+
+/// The state in `data` at STATE_VERSION, without its header, upgrading a copy of it
+/// if it's older. For reading accounts that can't be migrated in place.
+pub fn current_state(data: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(match migrate(data)? {
+        Some((_, migrated)) => state_body(&migrated).to_vec(),
+        None => state_body(data).to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn append_counter(body: &[u8]) -> io::Result<Vec<u8>> {
+        let mut body = body.to_vec();
+        body.extend_from_slice(&[0; 4]);
+        Ok(body)
+    }
+
+    fn append_flags(body: &[u8]) -> io::Result<Vec<u8>> {
+        let mut body = body.to_vec();
+        body.push(0xaa);
+        Ok(body)
+    }
+
+    #[test]
+    fn test_every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len(), STATE_VERSION as usize - 1);
+    }
+
+    #[test]
+    fn test_add_counter_calendar_last_update_origin_counters_and_discriminator() {
+        let mut v1 = GreetingAccountV1 {
+            txt: "Hello".to_string(),
+        }
+        .try_to_vec()
+        .unwrap();
+        // Trailing bytes are left alone, like the program leaves them
+        v1.resize(V1_ACCOUNT_SIZE, 0);
+
+        let (from, v6) = migrate(&v1).unwrap().unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(stored_version(&v6).unwrap(), 6);
+        assert_eq!(v6[..8], GREETING_DISCRIMINATOR);
+        let expected = GreetingAccount {
+            txt: "Hello".to_string(),
+            counter: 0,
+            calendar_year: 0,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: 0,
+            direct_greets: 0,
+            cpi_greets: 0,
+        };
+        assert_eq!(GreetingAccount::unpack(&v6).unwrap(), expected);
+        assert_eq!(GreetingAccount::unpack(&v1).unwrap(), expected);
+        assert_eq!(migrate(&v6).unwrap(), None);
+
+        // Schema 2 keeps its counter
+        let v2 = encode_as(
+            2,
+            &GreetingAccount {
+                counter: 7,
+                ..expected
+            },
+        )
+        .unwrap();
+        assert_eq!(stored_version(&v2).unwrap(), 2);
+        assert_eq!(GreetingAccount::unpack(&v2).unwrap().counter, 7);
+        assert_eq!(migrate(&v2).unwrap().unwrap().0, 2);
+
+        // Schema 3 keeps its calendar, and isn't stamped with an epoch
+        let mut dated = GreetingAccount {
+            txt: "Hello1234567".to_string(),
+            counter: 7,
+            calendar_year: 2024,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: 500,
+            direct_greets: 4,
+            cpi_greets: 3,
+        };
+        dated.greeted_days.set(59);
+        // Schema 5 only swaps its header for the discriminator
+        let v5 = encode_as(5, &dated).unwrap();
+        assert_eq!(stored_version(&v5).unwrap(), 5);
+        let (from, migrated) = migrate(&v5).unwrap().unwrap();
+        assert_eq!((from, &migrated[8..]), (5, &v5[HEADER_SIZE..]));
+        assert_eq!(GreetingAccount::unpack(&v5).unwrap(), dated);
+        let v3 = encode_as(3, &dated).unwrap();
+        assert_eq!(stored_version(&v3).unwrap(), 3);
+        let v4 = encode_as(4, &dated).unwrap();
+        assert_eq!(stored_version(&v4).unwrap(), 4);
+
+        // Schema 4 keeps its epoch stamp, and doesn't know how it was greeted
+        dated.direct_greets = 0;
+        dated.cpi_greets = 0;
+        assert_eq!(GreetingAccount::unpack(&v4).unwrap(), dated);
+        dated.last_update_epoch = 0;
+        assert_eq!(GreetingAccount::unpack(&v3).unwrap(), dated);
+    }
+
+    #[test]
+    fn test_encode_as_older_versions() {
+        let account = GreetingAccount {
+            txt: "Hello1234567".to_string(),
+            counter: 3,
+            calendar_year: 2024,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: 500,
+            direct_greets: 2,
+            cpi_greets: 1,
+        };
+        assert_eq!(encode_as(1, &account).unwrap().len(), 4 + 12);
+        assert_eq!(
+            encode_as(2, &account).unwrap().len(),
+            HEADER_SIZE + 4 + 12 + 8
+        );
+        assert_eq!(
+            encode_as(3, &account).unwrap().len(),
+            HEADER_SIZE + 4 + 12 + 8 + 2 + 46
+        );
+        assert_eq!(
+            encode_as(4, &account).unwrap().len(),
+            HEADER_SIZE + 4 + 12 + 8 + 2 + 46 + 8
+        );
+        assert_eq!(
+            encode_as(5, &account).unwrap().len(),
+            HEADER_SIZE + 4 + 12 + 8 + 2 + 46 + 8 + 8 + 8
+        );
+        assert_eq!(
+            encode_as(STATE_VERSION, &account).unwrap().len(),
+            GREETING_DISCRIMINATOR.len() + 4 + 12 + 8 + 2 + 46 + 8 + 8 + 8
+        );
+        assert!(encode_as(STATE_VERSION + 1, &account).is_err());
+    }
+
+    #[test]
+    fn test_greeting_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:GreetingAccount");
+        assert_eq!(hash.to_bytes()[..8], GREETING_DISCRIMINATOR);
+        // Not a schema 1 message length, whose top byte is zero
+        assert_ne!(GREETING_DISCRIMINATOR[3], 0);
+    }
+
+    #[test]
+    fn test_version_1_accounts_have_no_header() {
+        // A 5 character message in a 16 byte account
+        let data = [
+            5, 0, 0, 0, b'H', b'e', b'l', b'l', b'o', 0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(stored_version(&data).unwrap(), 1);
+        assert_eq!(state_body(&data), &data[..]);
+        assert_eq!(encode(1, &data), data.to_vec());
+        // Even a message starting with the magic bytes
+        let mut magic = [0; V1_ACCOUNT_SIZE];
+        magic[..7].copy_from_slice(b"\x03\0\0\0HWS");
+        assert_eq!(stored_version(&magic).unwrap(), 1);
+        // A new account
+        assert_eq!(stored_version(&[]).unwrap(), 1);
+        assert_eq!(stored_version(&[0; 16]).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_other_accounts_are_not_version_1() {
+        // A length running past the data, and a message that isn't UTF-8
+        assert!(stored_version(&[0xff; 16]).is_err());
+        assert!(stored_version(&[2, 0, 0, 0, 0xc3, 0x28]).is_err());
+        // A valid message in an account of any other size, like a foreign greeter on
+        // chain 2 (Ethereum), whose chain id and zero padding read as "\0\0"
+        for size in [V1_ACCOUNT_SIZE - 1, V1_ACCOUNT_SIZE + 1, 40] {
+            let mut other = vec![0; size];
+            other[0] = 2;
+            other[14..].iter_mut().for_each(|byte| *byte = 0xab);
+            assert!(stored_version(&other).is_err(), "{} bytes", size);
+            assert!(migrate(&other).is_err());
+            assert!(GreetingAccount::unpack(&other).is_err());
+        }
+        // Anything opening with the Anchor discriminator of another account
+        let receipt = [&crate::receipt::RECEIPT_DISCRIMINATOR[..], &[0; 16]].concat();
+        assert!(stored_version(&receipt).is_err());
+    }
+
+    #[test]
+    fn test_migrate_with_runs_the_chain() {
+        let migrations: &[Migration] = &[append_counter, append_flags];
+        let mut v1 = vec![0; V1_ACCOUNT_SIZE];
+        v1[..5].copy_from_slice(&[1, 0, 0, 0, b'a']);
+
+        let (from, v3) = migrate_with(migrations, 3, &v1).unwrap().unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(stored_version(&v3).unwrap(), 3);
+        assert_eq!(state_body(&v3), [&v1[..], &[0, 0, 0, 0, 0xaa]].concat());
+
+        // Part way
+        let (from, v2) = migrate_with(migrations, 2, &v1).unwrap().unwrap();
+        assert_eq!((from, stored_version(&v2).unwrap()), (1, 2));
+        let (from, upgraded) = migrate_with(migrations, 3, &v2).unwrap().unwrap();
+        assert_eq!((from, upgraded), (2, v3.clone()));
+
+        // Up to date
+        assert_eq!(migrate_with(migrations, 3, &v3).unwrap(), None);
+        // Written by a newer program
+        assert!(migrate_with(migrations, 2, &v3).is_err());
+        // Missing a migration
+        assert!(migrate_with(&migrations[..1], 3, &v1).is_err());
+    }
+}
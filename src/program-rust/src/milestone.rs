@@ -0,0 +1,127 @@
+//! Point-in-time records of a greeting account's counter, kept under a label
+// NOTE Snapshot copies a greeting account's counter (the effective one, decayed to the
+// current epoch, see decay.rs) and the clock into a GreetingSnapshot at
+// [SNAPSHOT_SEED, greeting account, recorder, label], so a milestone like "before
+// mainnet launch" stays readable after the live counter moves on. The recorder is in
+// the seeds so nobody can take a label for somebody else's records, and it pays the
+// rent. A label is recorded once: Snapshot again under the same one fails with
+// LabelTaken, pick a new label instead.
+//
+// The label is a seed, so it's at most MAX_LABEL_LEN bytes. It's kept in the account
+// as well, so the snapshots of an account can be listed with getProgramAccounts.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of a snapshot's address, followed by the greeting account's and the
+/// recorder's keys and the label
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+/// Anchor discriminator of GreetingSnapshot: sha256("account:GreetingSnapshot")[..8]
+pub const SNAPSHOT_DISCRIMINATOR: [u8; 8] = [234, 107, 107, 176, 155, 117, 216, 16];
+
+/// Longest label in bytes, the most a seed holds
+pub const MAX_LABEL_LEN: usize = 32;
+
+/// A greeting account's counter as it was at one point
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct GreetingSnapshot {
+    /// key of the greeting account
+    pub greeting_account: [u8; 32],
+    /// key that recorded the snapshot and paid its rent
+    pub recorder: [u8; 32],
+    /// the account's effective counter
+    pub counter: u64,
+    /// slot the snapshot was taken at
+    pub slot: u64,
+    /// unix timestamp the snapshot was taken at
+    // NOTE Unsigned, as borsh-js has no i64
+    pub unix_timestamp: u64,
+    /// what the snapshot marks, at most MAX_LABEL_LEN bytes
+    pub label: String,
+}
+
+/// Size of the data of a snapshot labeled `label`, the discriminator included
+pub fn snapshot_size(label: &str) -> usize {
+    8 + 32 + 32 + 8 + 8 + 8 + 4 + label.len()
+}
+
+/// Whether `label` can name a snapshot
+pub fn is_valid_label(label: &str) -> bool {
+    !label.is_empty() && label.len() <= MAX_LABEL_LEN
+}
+
+impl GreetingSnapshot {
+    /// Read a snapshot account's data, None if it holds no snapshot
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[..8] != SNAPSHOT_DISCRIMINATOR {
+            return None;
+        }
+        Self::try_from_slice(&data[8..]).ok()
+    }
+
+    /// Write the snapshot into a snapshot account's data, of snapshot_size(label) bytes
+    pub fn pack(&self, data: &mut [u8]) -> std::io::Result<()> {
+        data[..8].copy_from_slice(&SNAPSHOT_DISCRIMINATOR);
+        self.serialize(&mut &mut data[8..])
+    }
+}
+
+/// Address and bump seed of `recorder`'s snapshot of `greeting_account` labeled
+/// `label`
+#[cfg(feature = "program")]
+pub fn snapshot_address(
+    program_id: &Pubkey,
+    greeting_account: &Pubkey,
+    recorder: &Pubkey,
+    label: &str,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SNAPSHOT_SEED,
+            greeting_account.as_ref(),
+            recorder.as_ref(),
+            label.as_bytes(),
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = GreetingSnapshot {
+            greeting_account: [1; 32],
+            recorder: [2; 32],
+            counter: 3,
+            slot: 4,
+            unix_timestamp: 5,
+            label: "before mainnet launch".to_string(),
+        };
+        let mut data = vec![0; snapshot_size(&snapshot.label)];
+        assert_eq!(GreetingSnapshot::unpack(&data), None);
+        snapshot.pack(&mut data).unwrap();
+        assert_eq!(GreetingSnapshot::unpack(&data), Some(snapshot));
+
+        assert!(is_valid_label("v1"));
+        assert!(!is_valid_label(""));
+        assert!(!is_valid_label(&"a".repeat(MAX_LABEL_LEN + 1)));
+    }
+
+    #[cfg(feature = "program")]
+    #[test]
+    fn test_snapshot_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:GreetingSnapshot");
+        assert_eq!(hash.to_bytes()[..8], SNAPSHOT_DISCRIMINATOR);
+
+        let (program_id, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let recorder = Pubkey::new_unique();
+        assert_ne!(
+            snapshot_address(&program_id, &account, &recorder, "v1"),
+            snapshot_address(&program_id, &account, &Pubkey::new_unique(), "v1")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Whether a greeting came straight from the transaction or from another program
+// NOTE solana-program 1.6 has no stack height syscall (sol_get_stack_height came with
+// 1.14), so the origin is read from the instructions sysvar instead. It holds the
+// transaction's own instructions and the index of the one running: if that one is
+// ours we were called directly, otherwise a program it called CPI'd into us. (Ours
+// calling us again would look direct, but the program never invokes itself.)
+// The sysvar is optional and goes last in the account list, after whatever optional
+// accounts the instruction takes. Without it a greeting is only in `counter`, which
+// is how the greetings before schema 5 are counted too, so `counter` minus both
+// origin counters is the greetings of unknown origin.
+#[cfg(feature = "program")]
+use solana_program::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvar,
+};
+
+/// How a greeting reached the program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Origin {
+    /// One of the transaction's own instructions
+    Direct,
+    /// A cross-program invocation by another program
+    Cpi,
+}
+
+impl Origin {
+    /// Count a greeting of this origin in the greeting account's origin counters
+    pub fn count(self, direct_greets: &mut u64, cpi_greets: &mut u64) {
+        let counter = match self {
+            Origin::Direct => direct_greets,
+            Origin::Cpi => cpi_greets,
+        };
+        *counter = counter.saturating_add(1);
+    }
+}
+
+/// The program id of the transaction instruction running, read out of the
+/// instructions sysvar's data without decoding the instructions
+// NOTE The data opens with the instruction count and a u16 offset per instruction, and
+// ends with the index of the one running. An instruction is its account count, 33
+// bytes per account (meta byte, key), then the program id.
+pub fn running_program_id(data: &[u8]) -> Option<&[u8]> {
+    let read_u16 = |at: usize| -> Option<usize> {
+        let bytes = data.get(at..at.checked_add(2)?)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let current = read_u16(data.len().checked_sub(2)?)?;
+    if current >= read_u16(0)? {
+        return None;
+    }
+    let start = read_u16(2 + 2 * current)?;
+    let program_id = start + 2 + read_u16(start)? * 33;
+    data.get(program_id..program_id + 32)
+}
+
+/// `accounts` without the instructions sysvar, and the sysvar if it was passed last
+#[cfg(feature = "program")]
+pub fn split_instructions_sysvar<'a, 'b>(
+    accounts: &'a [AccountInfo<'b>],
+) -> (&'a [AccountInfo<'b>], Option<&'a AccountInfo<'b>>) {
+    match accounts.split_last() {
+        Some((last, rest)) if sysvar::instructions::check_id(last.key) => (rest, Some(last)),
+        _ => (accounts, None),
+    }
+}
+
+/// The origin of the greeting being run, or None without the instructions sysvar
+#[cfg(feature = "program")]
+pub fn origin(
+    program_id: &Pubkey,
+    instructions: Option<&AccountInfo>,
+) -> Result<Option<Origin>, ProgramError> {
+    let instructions = match instructions {
+        Some(instructions) => instructions,
+        None => return Ok(None),
+    };
+    let data = instructions.try_borrow_data()?;
+    let running = running_program_id(&data).ok_or(ProgramError::InvalidAccountData)?;
+    Ok(Some(if running == program_id.as_ref() {
+        Origin::Direct
+    } else {
+        Origin::Cpi
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{fixtures, test_utils::instructions_sysvar_data as instructions_data};
+    use solana_program::instruction::{AccountMeta, Instruction};
+
+    #[test]
+    fn test_origin() {
+        let (helloworld, wrapper) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let payer = Pubkey::new_unique();
+        let accounts = vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+        ];
+        let instructions = [
+            Instruction::new_with_bytes(wrapper, &[1, 2, 3], accounts.clone()),
+            Instruction::new_with_bytes(helloworld, &[0], accounts),
+        ];
+        let direct = instructions_data(&instructions, 1);
+        let composed = instructions_data(&instructions, 0);
+        assert_eq!(running_program_id(&direct), Some(helloworld.as_ref()));
+        assert_eq!(running_program_id(&composed), Some(wrapper.as_ref()));
+        assert_eq!(
+            running_program_id(&instructions_data(&instructions, 2)),
+            None
+        );
+        assert_eq!(running_program_id(&direct[..10]), None);
+        assert_eq!(running_program_id(&[]), None);
+
+        let key = sysvar::instructions::id();
+        let sysvar_account =
+            fixtures::read_only(fixtures::account(key, false, 0, direct, sysvar::id()));
+        assert_eq!(
+            origin(&helloworld, Some(&sysvar_account)),
+            Ok(Some(Origin::Direct))
+        );
+        assert_eq!(
+            origin(&wrapper, Some(&sysvar_account)),
+            Ok(Some(Origin::Cpi))
+        );
+        assert_eq!(origin(&helloworld, None), Ok(None));
+
+        let accounts = [sysvar_account];
+        let (rest, found) = split_instructions_sysvar(&accounts);
+        assert!(rest.is_empty());
+        assert_eq!(found.map(|account| account.key), Some(&key));
+        let (rest, found) = split_instructions_sysvar(&accounts[..0]);
+        assert!(rest.is_empty() && found.is_none());
+
+        let (mut direct_greets, mut cpi_greets) = (0, u64::MAX);
+        Origin::Direct.count(&mut direct_greets, &mut cpi_greets);
+        Origin::Cpi.count(&mut direct_greets, &mut cpi_greets);
+        assert_eq!((direct_greets, cpi_greets), (1, u64::MAX));
+    }
+}
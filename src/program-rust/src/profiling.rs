@@ -0,0 +1,116 @@
+//! Compute units per processor stage, from the logs of a build with the "profiling"
+//! feature
+// NOTE process_greet() marks the end of each stage with profile!(), which logs
+// "profile: <stage>" followed by sol_log_compute_units()'s "consumption: <N> units
+// remaining". A stage costs the units between its mark and the one before, the
+// mark's own "profile:" line included: about 100 units a stage are the profiling's.
+// Works on the logs of a transaction (simulateTransaction, getTransaction) or of
+// solana-program-test.
+
+/// Prefix of the lines marking the end of a stage
+pub const STAGE_PREFIX: &str = "profile: ";
+
+/// What a stage cost, summed over every time it ran in the logs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageCost {
+    pub stage: String,
+    pub units: u64,
+}
+
+/// The units left, out of a sol_log_compute_units() line
+fn units_remaining(line: &str) -> Option<u64> {
+    let (_, rest) = line.split_once("consumption: ")?;
+    rest.strip_suffix(" units remaining")?.parse().ok()
+}
+
+/// Cost per stage of the profile marks in `logs`, in the order the stages first ran.
+/// A "start" mark begins a new instruction, the cost of whatever ran before it isn't
+/// counted.
+pub fn breakdown<S: AsRef<str>>(logs: &[S]) -> Vec<StageCost> {
+    let mut costs: Vec<StageCost> = Vec::new();
+    let mut stage: Option<&str> = None;
+    let mut last: Option<u64> = None;
+    for line in logs {
+        let line = line.as_ref();
+        let line = line.strip_prefix("Program log: ").unwrap_or(line);
+        if let Some(name) = line.strip_prefix(STAGE_PREFIX) {
+            stage = Some(name);
+            continue;
+        }
+        let (name, remaining) = match (stage.take(), units_remaining(line)) {
+            (Some(name), Some(remaining)) => (name, remaining),
+            _ => continue,
+        };
+        if name != "start" {
+            if let Some(before) = last {
+                let units = before.saturating_sub(remaining);
+                match costs.iter_mut().find(|cost| cost.stage == name) {
+                    Some(cost) => cost.units += units,
+                    None => costs.push(StageCost {
+                        stage: name.to_string(),
+                        units,
+                    }),
+                }
+            }
+        }
+        last = Some(remaining);
+    }
+    costs
+}
+
+/// `costs` as a table, one stage a line and the total at the bottom
+pub fn report(costs: &[StageCost]) -> String {
+    let total: u64 = costs.iter().map(|cost| cost.units).sum();
+    let mut report = String::new();
+    for cost in costs {
+        report.push_str(&format!("{:<12} {:>8}\n", cost.stage, cost.units));
+    }
+    report.push_str(&format!("{:<12} {:>8}\n", "total", total));
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_breakdown() {
+        let logs = [
+            "Program 4uQe invoke [1]",
+            "Program log: profile: start",
+            "Program consumption: 199000 units remaining",
+            "Program log: profile: validation",
+            "Program consumption: 197500 units remaining",
+            "Program log: Greeting passed to program is \"hi\"",
+            "Program log: profile: deserialize",
+            "Program consumption: 197000 units remaining",
+            // A sol_log_compute_units() that isn't a mark is skipped
+            "Program consumption: 196900 units remaining",
+            "Program log: profile: mutate",
+            "Program consumption: 196000 units remaining",
+            "Program log: profile: serialize",
+            "Program consumption: 195200 units remaining",
+            // A second Greet in the same transaction
+            "Program log: profile: start",
+            "Program consumption: 190000 units remaining",
+            "Program log: profile: validation",
+            "Program consumption: 189000 units remaining",
+        ];
+        let costs = breakdown(&logs);
+        let units: Vec<_> = costs
+            .iter()
+            .map(|cost| (cost.stage.as_str(), cost.units))
+            .collect();
+        assert_eq!(
+            units,
+            vec![
+                ("validation", 2500),
+                ("deserialize", 500),
+                ("mutate", 1000),
+                ("serialize", 800)
+            ]
+        );
+        assert!(report(&costs).ends_with(&format!("{:<12} {:>8}\n", "total", 4800)));
+        assert!(breakdown::<&str>(&[]).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Reading Pyth price accounts, for fees set in USD
+// NOTE pyth-client needs a newer solana-program than the one pinned here, so the few
+// fields the fee needs are read straight out of the price account (Pyth's v2 layout,
+// see pyth-client's PriceAccount). The layout is fixed: a u32 magic, version and
+// account type up front, the exponent at 20, and the aggregate price, confidence,
+// status and publish slot at 208.
+//
+// Which account is the SOL/USD feed is up to the config authority (see SetFee), so
+// nothing here checks the owner: the program only reads the account whose key is in
+// the config.
+use std::convert::TryInto;
+
+/// First bytes of every Pyth account
+pub const MAGIC: u32 = 0xa1b2_c3d4;
+
+/// Version of the account layout read here
+pub const VERSION: u32 = 2;
+
+/// Account type of a price account
+pub const ACCOUNT_TYPE_PRICE: u32 = 3;
+
+/// Aggregate price status while publishers are trading
+pub const STATUS_TRADING: u32 = 1;
+
+/// Bytes of the price account read here
+pub const PRICE_ACCOUNT_SIZE: usize = 240;
+
+const EXPO_OFFSET: usize = 20;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const AGG_PUB_SLOT_OFFSET: usize = 232;
+
+/// Aggregate price of a Pyth price account: `price` ± `conf`, times 10^`expo`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Price {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub status: u32,
+    pub publish_slot: u64,
+}
+
+/// Why a price can't be used
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PriceError {
+    /// The account isn't a Pyth v2 price account
+    InvalidAccount,
+    /// The price isn't trading, or was published too long ago
+    Stale,
+    /// The confidence interval is too wide relative to the price
+    Uncertain,
+}
+
+// NOTE Copied rather than try_into().unwrap(), which panics through the Debug
+// formatting of the error, see custom_panic in lib.rs
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&data[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+impl Price {
+    /// Read the aggregate price out of a Pyth price account's data
+    pub fn parse(data: &[u8]) -> Result<Self, PriceError> {
+        if data.len() < PRICE_ACCOUNT_SIZE
+            || u32_at(data, 0) != MAGIC
+            || u32_at(data, 4) != VERSION
+            || u32_at(data, 8) != ACCOUNT_TYPE_PRICE
+        {
+            return Err(PriceError::InvalidAccount);
+        }
+        Ok(Self {
+            price: u64_at(data, AGG_PRICE_OFFSET) as i64,
+            conf: u64_at(data, AGG_CONF_OFFSET),
+            expo: u32_at(data, EXPO_OFFSET) as i32,
+            status: u32_at(data, AGG_STATUS_OFFSET),
+            publish_slot: u64_at(data, AGG_PUB_SLOT_OFFSET),
+        })
+    }
+
+    /// The price, if it's trading, at most `max_age_slots` old at `slot` and its
+    /// confidence interval at most `max_confidence_bps` basis points of it
+    pub fn checked(
+        self,
+        slot: u64,
+        max_age_slots: u64,
+        max_confidence_bps: u16,
+    ) -> Result<Self, PriceError> {
+        if self.status != STATUS_TRADING
+            || self.price <= 0
+            || slot.saturating_sub(self.publish_slot) > max_age_slots
+        {
+            return Err(PriceError::Stale);
+        }
+        if self.conf as u128 * 10_000 > self.price as u128 * max_confidence_bps as u128 {
+            return Err(PriceError::Uncertain);
+        }
+        Ok(self)
+    }
+
+    /// Lamports worth `usd_micros` millionths of a dollar at this SOL/USD price,
+    /// rounded up, or None if it doesn't fit in a u64
+    pub fn lamports_for_usd(&self, usd_micros: u64) -> Option<u64> {
+        // lamports = usd_micros / 10^6 / (price * 10^expo) * 10^9
+        //          = usd_micros * 10^(3 - expo) / price
+        let (mut numerator, mut denominator) = (usd_micros as u128, self.price as u128);
+        let shift = 3 - self.expo as i64;
+        if shift >= 0 {
+            numerator = numerator.checked_mul(10u128.checked_pow(shift.try_into().ok()?)?)?;
+        } else {
+            denominator =
+                denominator.checked_mul(10u128.checked_pow((-shift).try_into().ok()?)?)?;
+        }
+        (numerator.checked_add(denominator - 1)? / denominator)
+            .try_into()
+            .ok()
+    }
+}
+
+/// Data of a Pyth price account holding the given aggregate price, for tests
+#[cfg(test)]
+pub(crate) fn price_account(price: i64, conf: u64, expo: i32, publish_slot: u64) -> Vec<u8> {
+    let mut data = vec![0; PRICE_ACCOUNT_SIZE];
+    data[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    data[8..12].copy_from_slice(&ACCOUNT_TYPE_PRICE.to_le_bytes());
+    data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+    data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+    data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+    data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&STATUS_TRADING.to_le_bytes());
+    data[AGG_PUB_SLOT_OFFSET..AGG_PUB_SLOT_OFFSET + 8].copy_from_slice(&publish_slot.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_price_account() {
+        // $150.00 ± $0.10, with Pyth's usual exponent for SOL/USD
+        let data = price_account(15_000_000_000, 10_000_000, -8, 1_000);
+        let price = Price::parse(&data).unwrap();
+        assert_eq!(
+            price,
+            Price {
+                price: 15_000_000_000,
+                conf: 10_000_000,
+                expo: -8,
+                status: STATUS_TRADING,
+                publish_slot: 1_000,
+            }
+        );
+        assert_eq!(Price::parse(&data[..200]), Err(PriceError::InvalidAccount));
+        let mut product = data;
+        product[8] = 2;
+        assert_eq!(Price::parse(&product), Err(PriceError::InvalidAccount));
+    }
+
+    #[test]
+    fn test_price_checks() {
+        let price = Price::parse(&price_account(15_000_000_000, 10_000_000, -8, 1_000)).unwrap();
+        assert!(price.checked(1_025, 25, 10).is_ok());
+        assert_eq!(price.checked(1_026, 25, 10), Err(PriceError::Stale));
+        // 0.1 / 150 is about 6.7 basis points
+        assert_eq!(price.checked(1_000, 25, 6), Err(PriceError::Uncertain));
+        let halted = Price { status: 0, ..price };
+        assert_eq!(halted.checked(1_000, 25, 10), Err(PriceError::Stale));
+    }
+
+    #[test]
+    fn test_lamports_for_usd() {
+        let price = Price::parse(&price_account(15_000_000_000, 0, -8, 0)).unwrap();
+        // One cent at $150 is 1/15000 SOL
+        assert_eq!(price.lamports_for_usd(10_000), Some(66_667));
+        assert_eq!(price.lamports_for_usd(150_000_000), Some(1_000_000_000));
+        let whole_dollars = Price {
+            price: 150,
+            expo: 0,
+            ..price
+        };
+        assert_eq!(whole_dollars.lamports_for_usd(10_000), Some(66_667));
+        let tiny = Price {
+            price: 1,
+            expo: -30,
+            ..price
+        };
+        assert_eq!(tiny.lamports_for_usd(u64::MAX), None);
+    }
+}
@@ -0,0 +1,105 @@
+//! Greeting receipts: a PDA per greeting, so any one greeting can be proven on chain
+//! later
+// NOTE With the config's greeting_receipts set, every Greet creates a receipt at
+// [RECEIPT_SEED, greeting account, counter as u64 LE] recording who signed for it and
+// when. The counter is the one the greeting account held before the greeting, as
+// stored, so the client finds the address by reading the account: receipt N is the
+// greeting that took the counter past N. Another program proves a greeting happened
+// by deriving the address and checking the account is this program's and holds a
+// receipt.
+//
+// Receipts cost rent, paid by their signer, which is why they're off by default. A
+// counter that decays can come back to a value it held, whose receipt is taken, and
+// that Greet would fail with ReceiptExists: SetConfig and SetDecay refuse to turn
+// receipts and decay on together.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of a receipt's address, followed by the greeting account's key and counter
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Anchor discriminator of GreetingReceipt: sha256("account:GreetingReceipt")[..8]
+pub const RECEIPT_DISCRIMINATOR: [u8; 8] = [131, 162, 12, 227, 214, 137, 135, 68];
+
+/// Size of a receipt's data, the discriminator included
+pub const RECEIPT_SIZE: usize = 8 + 32 + 8 + 8;
+
+/// The record of one greeting
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct GreetingReceipt {
+    /// key that signed for the receipt and paid its rent
+    pub signer: [u8; 32],
+    /// slot of the greeting
+    pub slot: u64,
+    /// unix timestamp of the greeting
+    // NOTE Unsigned, as borsh-js has no i64. No greeting happened before 1970.
+    pub unix_timestamp: u64,
+}
+
+impl GreetingReceipt {
+    /// Read a receipt account's data, None if it holds no receipt
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() != RECEIPT_SIZE || data[..8] != RECEIPT_DISCRIMINATOR {
+            return None;
+        }
+        Self::try_from_slice(&data[8..]).ok()
+    }
+
+    /// Write the receipt into a receipt account's data
+    pub fn pack(&self, data: &mut [u8]) {
+        data[..8].copy_from_slice(&RECEIPT_DISCRIMINATOR);
+        // NOTE Encoding into a slice of the exact size can't fail
+        let _ = self.serialize(&mut &mut data[8..RECEIPT_SIZE]);
+    }
+}
+
+/// Address and bump seed of the receipt of the greeting of `greeting_account` at
+/// `counter`
+#[cfg(feature = "program")]
+pub fn receipt_address(
+    program_id: &Pubkey,
+    greeting_account: &Pubkey,
+    counter: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            RECEIPT_SEED,
+            greeting_account.as_ref(),
+            &counter.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_receipt() {
+        let receipt = GreetingReceipt {
+            signer: [1; 32],
+            slot: 2,
+            unix_timestamp: 3,
+        };
+        let mut data = [0; RECEIPT_SIZE];
+        assert_eq!(GreetingReceipt::unpack(&data), None);
+        receipt.pack(&mut data);
+        assert_eq!(GreetingReceipt::unpack(&data), Some(receipt));
+        assert_eq!(GreetingReceipt::unpack(&data[..RECEIPT_SIZE - 1]), None);
+    }
+
+    #[cfg(feature = "program")]
+    #[test]
+    fn test_receipt_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:GreetingReceipt");
+        assert_eq!(hash.to_bytes()[..8], RECEIPT_DISCRIMINATOR);
+
+        let (program_id, account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_ne!(
+            receipt_address(&program_id, &account, 1),
+            receipt_address(&program_id, &account, 2)
+        );
+    }
+}
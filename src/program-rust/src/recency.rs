@@ -0,0 +1,98 @@
+//! Proof that a transaction was signed recently, from the SlotHashes sysvar
+// NOTE A transaction signed against a durable nonce, or handed to somebody else to
+// send later, has no recent blockhash to expire it: a pre-signed Greet stays valid
+// until it's sent. CheckRecency closes that window. The client names a recent slot and
+// its hash, and the program looks the pair up in the SlotHashes sysvar, which holds
+// the hashes of the last MAX_ENTRIES slots: a pair that isn't there (too old, or never
+// the cluster's) or a slot more than max_age_slots behind the current one fails the
+// whole transaction with StaleSlotHash. Put it in the same transaction as the Greet.
+// It's the signer's safeguard, not the program's: Greet runs without it, and the age
+// bound is whatever the signer put in.
+//
+// The hash ties the proof to this cluster and fork, so a proof can't be made up ahead
+// of time for a future slot. The sysvar is read from its account rather than
+// deserialized (about 20KB), as only one entry is needed.
+use arrayref::array_ref;
+
+/// Slots the SlotHashes sysvar keeps, the oldest a proof can name
+pub const MAX_ENTRIES: u64 = 512;
+
+/// Bytes of one (slot, hash) entry of the sysvar's data
+const ENTRY_SIZE: usize = 8 + 32;
+
+/// The hash of `slot` in the SlotHashes sysvar's `data`, None if it isn't kept
+// NOTE The data is a bincode Vec<(Slot, Hash)>: a u64 length, then the entries newest
+// first
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Option<&[u8; 32]> {
+    if data.len() < 8 {
+        return None;
+    }
+    let len = u64::from_le_bytes(*array_ref![data, 0, 8]) as usize;
+    for entry in data[8..].chunks_exact(ENTRY_SIZE).take(len) {
+        let entry_slot = u64::from_le_bytes(*array_ref![entry, 0, 8]);
+        if entry_slot == slot {
+            return Some(array_ref![entry, 8, 32]);
+        }
+        if entry_slot < slot {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// SlotHashes data holding `entries`, newest first
+    fn slot_hashes(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+
+    #[test]
+    fn test_find_slot_hash() {
+        let data = slot_hashes(&[(105, [5; 32]), (103, [3; 32]), (100, [0; 32])]);
+        assert_eq!(find_slot_hash(&data, 105), Some(&[5; 32]));
+        assert_eq!(find_slot_hash(&data, 100), Some(&[0; 32]));
+        // Skipped slots and slots out of the window have no hash
+        assert_eq!(find_slot_hash(&data, 104), None);
+        assert_eq!(find_slot_hash(&data, 99), None);
+        assert_eq!(find_slot_hash(&data, 106), None);
+        assert_eq!(find_slot_hash(&[], 105), None);
+        // Only the entries the length counts are read
+        let mut short = data.clone();
+        short[..8].copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(find_slot_hash(&short, 103), None);
+    }
+
+    #[cfg(feature = "program")]
+    #[test]
+    fn test_matches_the_sysvar_layout() {
+        use crate::fixtures;
+        use solana_program::{
+            hash::Hash,
+            slot_hashes::SlotHashes,
+            sysvar::{self, Sysvar},
+        };
+        let hashes = SlotHashes::new(&[(7, Hash::new(&[7; 32])), (9, Hash::new(&[9; 32]))]);
+        let data = vec![0; SlotHashes::size_of()];
+        let mut account = fixtures::read_only(fixtures::account(
+            sysvar::slot_hashes::id(),
+            false,
+            0,
+            data,
+            sysvar::id(),
+        ));
+        hashes.to_account_info(&mut account).unwrap();
+        assert_eq!(SlotHashes::size_of(), 8 + MAX_ENTRIES as usize * ENTRY_SIZE);
+        let data = account.data.borrow();
+        assert_eq!(find_slot_hash(&data, 9), Some(&[9; 32]));
+        assert_eq!(find_slot_hash(&data, 7), Some(&[7; 32]));
+        assert_eq!(find_slot_hash(&data, 8), None);
+    }
+}
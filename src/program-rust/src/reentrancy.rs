@@ -0,0 +1,99 @@
+//! Refusing a greeting account while the instruction greeting it is calling out
+// NOTE The runtime only lets a program be re-entered by itself: A -> B -> A fails with
+// ReentrancyNotAllowed, A -> A doesn't. So a wrapper can't get back into hello world
+// through the programs Greet calls (they're all checked by key, see
+// validate_program_account()), but a nested hello world invocation would run against
+// the greeting account mid-greeting and count it twice. While Greet or GreetFor is
+// calling out (the fee transfer, the greeting log) the account's fourth byte (the
+// version byte, or a byte of the discriminator from schema 6 on) holds LOCKED. A callee
+// is handed the caller's account data, so any nested greeting of the account finds the
+// mark and fails with HelloWorldError::Reentrancy. No instruction ever ends with the
+// mark set: it's either lifted or the whole transaction fails.
+#[cfg(feature = "program")]
+use crate::error::HelloWorldError;
+#[cfg(feature = "program")]
+use solana_program::{account_info::AccountInfo, program_error::ProgramError};
+
+/// Where the mark goes: the header's version byte, the fourth byte of the account
+/// discriminator, the top byte of a schema 1 account's message length
+pub const LOCK_IDX: usize = 3;
+
+/// The mark. Not a schema version nor the discriminator's byte, and a schema 1 message
+/// length with this top byte would need an account of 4 GiB.
+pub const LOCKED: u8 = u8::MAX;
+
+/// Whether `data` is marked by an instruction still greeting it
+pub fn is_locked(data: &[u8]) -> bool {
+    data.get(LOCK_IDX) == Some(&LOCKED)
+}
+
+/// A greeting account marked while its greeting calls out. Drop it to lift the mark
+/// before reading or writing the account's state.
+#[cfg(feature = "program")]
+pub struct Lock<'a, 'b> {
+    account: &'a AccountInfo<'b>,
+    saved: u8,
+}
+
+/// Refuse `account` if it's marked, with HelloWorldError::Reentrancy
+#[cfg(feature = "program")]
+pub fn check_unlocked(account: &AccountInfo) -> Result<(), ProgramError> {
+    if is_locked(&account.data.borrow()) {
+        return Err(HelloWorldError::Reentrancy.into());
+    }
+    Ok(())
+}
+
+/// Mark `account` until the lock is released. An account too small to hold the mark
+/// has no state to count twice and is left as it is.
+#[cfg(feature = "program")]
+pub fn lock<'a, 'b>(account: &'a AccountInfo<'b>) -> Result<Lock<'a, 'b>, ProgramError> {
+    check_unlocked(account)?;
+    let mut data = account.try_borrow_mut_data()?;
+    let saved = data.get(LOCK_IDX).copied().unwrap_or_default();
+    if let Some(byte) = data.get_mut(LOCK_IDX) {
+        *byte = LOCKED;
+    }
+    Ok(Lock { account, saved })
+}
+
+// NOTE Also lifted when a processor bails out with `?`. On chain a failed instruction
+// doesn't keep its writes anyway, but the unit tests carry on with the same account.
+#[cfg(feature = "program")]
+impl Drop for Lock<'_, '_> {
+    fn drop(&mut self) {
+        if let Ok(mut data) = self.account.try_borrow_mut_data() {
+            if let Some(byte) = data.get_mut(LOCK_IDX) {
+                *byte = self.saved;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_lock() {
+        let data = b"HWS\x04rest".to_vec();
+        let account = fixtures::account(Pubkey::new_unique(), false, 0, data, Pubkey::new_unique());
+
+        let guard = lock(&account).unwrap();
+        assert!(is_locked(&account.data.borrow()));
+        assert_eq!(
+            check_unlocked(&account),
+            Err(HelloWorldError::Reentrancy.into())
+        );
+        assert!(lock(&account).is_err());
+        drop(guard);
+        assert_eq!(&account.data.borrow()[..], b"HWS\x04rest");
+        assert_eq!(check_unlocked(&account), Ok(()));
+
+        // A schema 1 account's message length is put back the same way
+        assert!(!is_locked(&[5, 0, 0, 0]));
+        assert!(!is_locked(&[0; 3]));
+    }
+}
@@ -0,0 +1,87 @@
+//! The solana-program APIs that differ between the SDK versions the program builds
+//! against
+// NOTE The program is pinned to solana-program 1.6, while the programs that CPI into
+// it or reuse this crate are split between 1.18 and 2.x. The "sdk-1-18" feature builds
+// the same sources against those: the manifests under sdk/ turn it on next to their own
+// solana-program, and `cargo xtask sdk-matrix` runs the unit tests against each. Every
+// call that differs between them goes through here, so the rest of the program is
+// written once:
+// - log_data(): sol_log_data() (the "Program data: <base64>" lines Anchor tooling
+//   reads) arrived in 1.8; on 1.6 the data goes out as a msg! line of hex, as
+//   event.rs's events always have. Parsers accept both.
+// - HEAP_START: a usize on 1.6 and a u64 from 1.8 on.
+// - instructions_sysvar_data(): Message::serialize_instructions() took a flag on 1.6
+//   and is private later; newer SDKs build the sysvar with construct_instructions_data().
+// Code built for the chain checks `any(target_arch = "bpf", target_os = "solana")`:
+// cargo build-bpf sets the first, build-sbf (1.18 on) the second.
+#[cfg(not(feature = "sdk-1-18"))]
+use solana_program::msg;
+
+/// Log `data` for off-chain parsers: through sol_log_data() on the SDKs that have it,
+/// as a msg! line of `prefix` and the data in hex on 1.6
+#[cfg(not(feature = "sdk-1-18"))]
+pub fn log_data(prefix: &str, data: &[u8]) {
+    let hex: String = data.iter().map(|byte| format!("{:02x}", byte)).collect();
+    msg!("{}{}", prefix, hex);
+}
+
+/// Log `data` for off-chain parsers: through sol_log_data() on the SDKs that have it,
+/// as a msg! line of `prefix` and the data in hex on 1.6
+#[cfg(feature = "sdk-1-18")]
+pub fn log_data(_prefix: &str, data: &[u8]) {
+    solana_program::log::sol_log_data(&[data]);
+}
+
+/// Address the program's heap starts at
+#[cfg(not(feature = "sdk-1-18"))]
+pub const HEAP_START: usize = solana_program::entrypoint::HEAP_START_ADDRESS;
+
+/// Address the program's heap starts at
+#[cfg(feature = "sdk-1-18")]
+pub const HEAP_START: usize = solana_program::entrypoint::HEAP_START_ADDRESS as usize;
+
+/// Instructions sysvar data for a transaction of `instructions`, running the one at
+/// `current`, for tests
+#[cfg(all(any(test, feature = "test-utils"), not(feature = "sdk-1-18")))]
+pub fn instructions_sysvar_data(
+    instructions: &[solana_program::instruction::Instruction],
+    current: u16,
+) -> Vec<u8> {
+    let message = solana_program::message::Message::new(instructions, None);
+    let mut data = message.serialize_instructions(false);
+    data.extend_from_slice(&current.to_le_bytes());
+    data
+}
+
+/// Instructions sysvar data for a transaction of `instructions`, running the one at
+/// `current`, for tests
+#[cfg(all(any(test, feature = "test-utils"), feature = "sdk-1-18"))]
+pub fn instructions_sysvar_data(
+    instructions: &[solana_program::instruction::Instruction],
+    current: u16,
+) -> Vec<u8> {
+    use solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction,
+    };
+    let borrowed: Vec<_> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+    // NOTE It leaves the last two bytes, the current instruction's index, zeroed
+    let mut data = construct_instructions_data(&borrowed);
+    let len = data.len();
+    data[len - 2..].copy_from_slice(&current.to_le_bytes());
+    data
+}
@@ -0,0 +1,78 @@
+//! Session keys: a temporary key greeting on behalf of a wallet for a while
+// NOTE The session-keys crate (gpl_session) is an Anchor program built on a newer
+// solana-program than the one pinned here, so this is the same pattern, native. The
+// wallet approves a session once, with CreateSession: it signs along with a key the
+// client just generated and keeps in memory, and the program records the pair in a
+// session token, a PDA at [SESSION_SEED, authority, session key], valid until
+// `valid_until`. CreateSession can also top the session key up with lamports for its
+// transaction fees. From then on the session key signs Greets by itself, standing in
+// for the wallet as the staker of a stake-weighted greeting when the session token
+// follows it, with no wallet prompt until the session runs out.
+//
+// A session only ever counts for Greet: depositing, withdrawing and everything else
+// still need the wallet. The wallet can end a session early with RevokeSession, which
+// closes the token and gets its rent back.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of a session token, followed by the authority's and the session key's keys
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Longest a session can last, a week
+pub const MAX_SESSION_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// A session key approved by a wallet
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct SessionToken {
+    /// key of the wallet that approved the session
+    pub authority: [u8; 32],
+    /// key of the session key
+    pub session_key: [u8; 32],
+    /// unix timestamp the session runs out at
+    // NOTE Unsigned, as borsh-js has no i64. No session is valid before 1970.
+    pub valid_until: u64,
+}
+
+/// Size of a session token's data
+pub const SESSION_TOKEN_SIZE: usize = 32 + 32 + 8;
+
+impl SessionToken {
+    /// Whether `signer` may act for the authority at unix timestamp `now`
+    pub fn is_valid(&self, signer: &[u8; 32], now: i64) -> bool {
+        self.session_key == *signer && now >= 0 && (now as u64) < self.valid_until
+    }
+}
+
+/// Address and bump seed of `authority`'s session token for `session_key`
+#[cfg(feature = "program")]
+pub fn session_address(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    session_key: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SESSION_SEED, authority.as_ref(), session_key.as_ref()],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_session_token() {
+        let token = SessionToken {
+            authority: [1; 32],
+            session_key: [2; 32],
+            valid_until: 1_000,
+        };
+        assert_eq!(token.try_to_vec().unwrap().len(), SESSION_TOKEN_SIZE);
+        assert!(token.is_valid(&[2; 32], 999));
+        // Not once it runs out, nor for another key, the authority's included
+        assert!(!token.is_valid(&[2; 32], 1_000));
+        assert!(!token.is_valid(&[1; 32], 999));
+        assert!(!token.is_valid(&[2; 32], -1));
+    }
+}
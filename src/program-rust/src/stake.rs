@@ -0,0 +1,86 @@
+//! Stake-to-greet: greetings count for more the more tokens the greeter has staked
+// NOTE Stakers deposit the config's stake mint into the stake vault, one token account
+// for everyone whose owner is the stake authority, a PDA of this program at seeds
+// [STAKE_AUTHORITY_SEED]. Anyone can create the vault; the program only checks its
+// mint and owner. What each staker put in is kept in their stake position, a PDA at
+// [STAKE_SEED, staker], and only the program can move tokens out of the vault, back to
+// whoever deposited them. A position remembers the mint it holds, so changing the
+// config's stake mint leaves existing stakers free to withdraw what they put in, while
+// only stake of the current mint weights greetings.
+//
+// Stake deposited in the current slot doesn't count yet, so tokens borrowed and paid
+// back within one transaction (a flash loan) can't weight a greeting.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of a staker's position, followed by the staker's key
+pub const STAKE_SEED: &[u8] = b"stake";
+
+/// Seed of the stake vault's owner
+pub const STAKE_AUTHORITY_SEED: &[u8] = b"stake-authority";
+
+/// What a staker has deposited
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct StakePosition {
+    /// key of the staker
+    pub owner: [u8; 32],
+    /// key of the mint deposited
+    pub mint: [u8; 32],
+    /// tokens deposited, in the mint's base units
+    pub amount: u64,
+    /// slot of the last deposit
+    pub last_deposit_slot: u64,
+}
+
+/// Size of a stake position's data
+pub const STAKE_POSITION_SIZE: usize = 32 + 32 + 8 + 8;
+
+impl StakePosition {
+    /// How much a greeting by the staker counts at `slot`: 1, plus 1 for every full
+    /// `stake_unit` of `stake_mint` staked before `slot`
+    pub fn greet_weight(&self, stake_mint: &[u8; 32], stake_unit: u64, slot: u64) -> u64 {
+        if stake_unit == 0 || self.mint != *stake_mint || self.last_deposit_slot >= slot {
+            return 1;
+        }
+        1 + self.amount / stake_unit
+    }
+}
+
+/// Address and bump seed of `staker`'s stake position
+#[cfg(feature = "program")]
+pub fn position_address(program_id: &Pubkey, staker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, staker.as_ref()], program_id)
+}
+
+/// Address and bump seed of the stake authority, the owner of the stake vault
+#[cfg(feature = "program")]
+pub fn stake_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_AUTHORITY_SEED], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_greet_weight() {
+        let position = StakePosition {
+            owner: [1; 32],
+            mint: [2; 32],
+            amount: 2_500,
+            last_deposit_slot: 10,
+        };
+        assert_eq!(position.try_to_vec().unwrap().len(), STAKE_POSITION_SIZE);
+        assert_eq!(position.greet_weight(&[2; 32], 1_000, 11), 3);
+        // Not in the slot it was deposited in
+        assert_eq!(position.greet_weight(&[2; 32], 1_000, 10), 1);
+        // Nor without a stake unit set, or once the config stakes another mint
+        assert_eq!(position.greet_weight(&[2; 32], 0, 11), 1);
+        assert_eq!(position.greet_weight(&[3; 32], 1_000, 11), 1);
+        assert_eq!(
+            StakePosition::default().greet_weight(&[0; 32], 1_000, 11),
+            1
+        );
+    }
+}
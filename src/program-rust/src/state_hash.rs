@@ -0,0 +1,141 @@
+//! A short hash of a greeting account's data, logged after every write to it, so an
+//! off-chain verifier can check the state it reconstructed against the chain's
+//! without fetching the account
+// NOTE The hash is sha256 over the whole account data as the instruction leaves it,
+// cut to STATE_HASH_SIZE bytes: the encoded state and whatever the account holds after
+// it (zeros, or the tail of a longer message from before), which is what an indexer
+// storing the account's bytes has. Logged as one "helloworld-state: <account> <hash>"
+// line per account written, both in hex, with or without the "log" feature, like
+// events (see event.rs). The line is put together in a stack buffer: Increment doesn't
+// allocate, and base58 would. A transaction writing an account twice logs it twice;
+// the last line is the state it ends with.
+use solana_program::{hash::hash, log::sol_log, pubkey::Pubkey};
+use std::convert::TryInto;
+
+/// Prefix of the lines carrying a state hash
+pub const STATE_HASH_PREFIX: &str = "helloworld-state: ";
+
+/// Bytes of the sha256 kept in a state hash
+pub const STATE_HASH_SIZE: usize = 8;
+
+/// Length of a state hash line
+pub const STATE_HASH_LOG_LEN: usize = STATE_HASH_PREFIX.len() + 64 + 1 + 2 * STATE_HASH_SIZE;
+
+/// The state hash of an account holding `data`
+pub fn state_hash(data: &[u8]) -> [u8; STATE_HASH_SIZE] {
+    let mut short = [0; STATE_HASH_SIZE];
+    short.copy_from_slice(&hash(data).to_bytes()[..STATE_HASH_SIZE]);
+    short
+}
+
+/// Write the line carrying the state hash of `account`, holding `data`, into `line`
+pub fn write_log<'a>(
+    account: &Pubkey,
+    data: &[u8],
+    line: &'a mut [u8; STATE_HASH_LOG_LEN],
+) -> &'a str {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let prefix = STATE_HASH_PREFIX.len();
+    line[..prefix].copy_from_slice(STATE_HASH_PREFIX.as_bytes());
+    let (key, hash) = line[prefix..].split_at_mut(64);
+    hash[0] = b' ';
+    let short = state_hash(data);
+    let key = account.as_ref().iter().zip(key.chunks_mut(2));
+    for (byte, hex) in key.chain(short.iter().zip(hash[1..].chunks_mut(2))) {
+        hex[0] = DIGITS[(byte >> 4) as usize];
+        hex[1] = DIGITS[(byte & 0xf) as usize];
+    }
+    // Only ASCII was written
+    std::str::from_utf8(line).unwrap_or_default()
+}
+
+/// The log line carrying the state hash of `account`, holding `data`
+pub fn to_log(account: &Pubkey, data: &[u8]) -> String {
+    write_log(account, data, &mut [0; STATE_HASH_LOG_LEN]).to_string()
+}
+
+/// The account and state hash a log line carries, None for any other line. Takes the
+/// line as the program logged it or as the runtime reports it, after "Program log: ".
+pub fn from_log(line: &str) -> Option<(Pubkey, [u8; STATE_HASH_SIZE])> {
+    let line = line.strip_prefix("Program log: ").unwrap_or(line);
+    let mut parts = line.strip_prefix(STATE_HASH_PREFIX)?.split(' ');
+    let key = crate::event::decode_hex(parts.next()?).ok()?;
+    let hex = crate::event::decode_hex(parts.next()?).ok()?;
+    if parts.next().is_some() || key.len() != 32 || hex.len() != STATE_HASH_SIZE {
+        return None;
+    }
+    let account = Pubkey::new_from_array(key.try_into().ok()?);
+    let mut short = [0; STATE_HASH_SIZE];
+    short.copy_from_slice(&hex);
+    Some((account, short))
+}
+
+/// The last state hash `logs` carry for `account`, None if the logs don't write it
+pub fn last_state_hash<S: AsRef<str>>(
+    logs: &[S],
+    account: &Pubkey,
+) -> Option<[u8; STATE_HASH_SIZE]> {
+    logs.iter()
+        .rev()
+        .filter_map(|line| from_log(line.as_ref()))
+        .find(|(key, _)| key == account)
+        .map(|(_, short)| short)
+}
+
+/// Whether `data` is what the transaction that logged `logs` left in `account`, None
+/// if the logs don't write it
+pub fn verify<S: AsRef<str>>(logs: &[S], account: &Pubkey, data: &[u8]) -> Option<bool> {
+    last_state_hash(logs, account).map(|short| short == state_hash(data))
+}
+
+/// Log the state hash of `account`, holding `data`
+pub fn log(account: &Pubkey, data: &[u8]) {
+    let mut line = [0; STATE_HASH_LOG_LEN];
+    sol_log(write_log(account, data, &mut line));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_state_hash_round_trips_through_a_log_line() {
+        let account = Pubkey::new_unique();
+        let data = [7; 64];
+        let line = format!("Program log: {}", to_log(&account, &data));
+        assert_eq!(from_log(&line), Some((account, state_hash(&data))));
+        assert_eq!(
+            state_hash(&data)[..],
+            solana_program::hash::hash(&data).to_bytes()[..STATE_HASH_SIZE]
+        );
+
+        assert_eq!(
+            &line[line.len() - 2 * STATE_HASH_SIZE - 65..][..64],
+            account
+                .as_ref()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        );
+
+        assert_eq!(from_log("Program log: helloworld-state: nope 00"), None);
+        let short = format!("{}{} 0011", STATE_HASH_PREFIX, "00".repeat(32));
+        assert_eq!(from_log(&short), None);
+    }
+
+    #[test]
+    fn test_verify_checks_the_last_hash_of_the_account() {
+        let account = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let logs = [
+            to_log(&account, b"before"),
+            to_log(&other, b"other"),
+            "Program log: Was sent message hi!".to_string(),
+            to_log(&account, b"after"),
+        ];
+        assert_eq!(verify(&logs, &account, b"after"), Some(true));
+        assert_eq!(verify(&logs, &account, b"before"), Some(false));
+        assert_eq!(verify(&logs, &other, b"other"), Some(true));
+        assert_eq!(verify(&logs, &Pubkey::new_unique(), b"after"), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Reading Switchboard On-Demand randomness accounts
+// NOTE switchboard-on-demand needs a newer solana-program than the one pinned here, so,
+// like pyth.rs, this reads the few fields it needs straight out of the account
+// (RandomnessAccountData, behind its 8-byte Anchor discriminator).
+//
+// Randomness is committed and revealed in two steps, so nobody can see the value
+// before they're bound to it:
+// 1. The requester asks the oracle to commit to the slot hash of the previous slot
+//    (`seed_slot`), and in the same transaction commits to the randomness account.
+//    Only an account seeded in the slot right before is accepted, so the value can't
+//    already be known.
+// 2. The oracle reveals the value derived from that slot hash (`reveal_slot` is the
+//    slot it did), and in the same transaction the requester settles. Only a value
+//    revealed in the current slot is accepted, as the Switchboard SDK does.
+use std::convert::TryInto;
+
+/// Anchor discriminator of RandomnessAccountData: sha256("account:RandomnessAccountData")
+pub const DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+
+/// Bytes of the randomness account read here
+pub const RANDOMNESS_ACCOUNT_SIZE: usize = 184;
+
+const SEED_SLOT_OFFSET: usize = 104;
+const REVEAL_SLOT_OFFSET: usize = 144;
+const VALUE_OFFSET: usize = 152;
+
+/// The Switchboard On-Demand program on mainnet-beta
+#[cfg(feature = "program")]
+pub mod mainnet {
+    solana_program::declare_id!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+}
+
+/// The Switchboard On-Demand program on devnet
+#[cfg(feature = "program")]
+pub mod devnet {
+    solana_program::declare_id!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2");
+}
+
+/// The commitment and reveal of a randomness account
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Randomness {
+    pub seed_slot: u64,
+    pub reveal_slot: u64,
+    pub value: [u8; 32],
+}
+
+impl Randomness {
+    /// Read a randomness account's data, or None if it isn't one
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < RANDOMNESS_ACCOUNT_SIZE || data[..8] != DISCRIMINATOR {
+            return None;
+        }
+        Some(Self {
+            seed_slot: u64::from_le_bytes(
+                data[SEED_SLOT_OFFSET..SEED_SLOT_OFFSET + 8]
+                    .try_into()
+                    .ok()?,
+            ),
+            reveal_slot: u64::from_le_bytes(
+                data[REVEAL_SLOT_OFFSET..REVEAL_SLOT_OFFSET + 8]
+                    .try_into()
+                    .ok()?,
+            ),
+            value: data[VALUE_OFFSET..VALUE_OFFSET + 32].try_into().ok()?,
+        })
+    }
+
+    /// Whether the randomness was seeded in the slot before `slot`, so it can be
+    /// committed to
+    pub fn is_fresh(&self, slot: u64) -> bool {
+        self.seed_slot.checked_add(1) == Some(slot)
+    }
+
+    /// The value, if it was revealed in `slot`
+    pub fn value(&self, slot: u64) -> Option<[u8; 32]> {
+        if self.reveal_slot == slot && self.reveal_slot > self.seed_slot {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+}
+
+/// Data of a randomness account, for tests
+#[cfg(test)]
+pub(crate) fn randomness_account(seed_slot: u64, reveal_slot: u64, value: [u8; 32]) -> Vec<u8> {
+    let mut data = vec![0; RANDOMNESS_ACCOUNT_SIZE];
+    data[..8].copy_from_slice(&DISCRIMINATOR);
+    data[SEED_SLOT_OFFSET..SEED_SLOT_OFFSET + 8].copy_from_slice(&seed_slot.to_le_bytes());
+    data[REVEAL_SLOT_OFFSET..REVEAL_SLOT_OFFSET + 8].copy_from_slice(&reveal_slot.to_le_bytes());
+    data[VALUE_OFFSET..VALUE_OFFSET + 32].copy_from_slice(&value);
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_commit_and_reveal() {
+        let committed = Randomness::parse(&randomness_account(99, 0, [0; 32])).unwrap();
+        assert!(committed.is_fresh(100));
+        assert!(!committed.is_fresh(101));
+        assert_eq!(committed.value(100), None);
+
+        let revealed = Randomness::parse(&randomness_account(99, 105, [7; 32])).unwrap();
+        assert_eq!(revealed.value(105), Some([7; 32]));
+        // Only in the slot it was revealed in
+        assert_eq!(revealed.value(106), None);
+    }
+
+    #[test]
+    fn test_rejects_other_accounts() {
+        let mut data = randomness_account(99, 0, [0; 32]);
+        assert!(Randomness::parse(&data[..100]).is_none());
+        data[0] = 0;
+        assert!(Randomness::parse(&data).is_none());
+    }
+}
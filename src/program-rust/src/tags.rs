@@ -0,0 +1,152 @@
+//! Named counters: one greeting account counting greetings in several categories
+// NOTE CountTag counts a greeting under a tag, an 8-byte name like "gm" or "hello"
+// padded with zeros, in the account's tag counters: a TagCounters at [TAGS_SEED,
+// greeting account] holding a (tag, count) entry per tag seen so far. They're kept
+// apart from the greeting account because those are sized by the client for their
+// layout (see store_greeting in lib.rs), and solana-program 1.6 can't grow an
+// account. For the same reason the first CountTag creates the tag counters with room
+// for MAX_TAGS entries, paid by its payer, and a tag past that fails with TagsFull.
+//
+// The counts are plain: they don't decay and don't count toward the account's own
+// counter, Greet still does that.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+/// Seed of the tag counters' address, followed by the greeting account's key
+pub const TAGS_SEED: &[u8] = b"tags";
+
+/// Anchor discriminator of TagCounters: sha256("account:TagCounters")[..8]
+pub const TAG_COUNTERS_DISCRIMINATOR: [u8; 8] = [235, 100, 236, 95, 73, 212, 182, 97];
+
+/// Most tags one greeting account counts
+pub const MAX_TAGS: usize = 16;
+
+/// Size of the tag counters' data, with room for MAX_TAGS entries
+pub const TAG_COUNTERS_SIZE: usize = 8 + 32 + 4 + MAX_TAGS * (8 + 8);
+
+/// Greetings counted under one tag
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct TagCount {
+    /// the tag's name, padded with zeros
+    pub tag: [u8; 8],
+    /// greetings counted under it
+    pub count: u64,
+}
+
+/// A greeting account's counts by tag
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct TagCounters {
+    /// key of the greeting account
+    pub greeting_account: [u8; 32],
+    /// one entry per tag, in the order they were first counted
+    pub entries: Vec<TagCount>,
+}
+
+/// The tag named `name`, None unless it's 1 to 8 bytes
+pub fn tag(name: &str) -> Option<[u8; 8]> {
+    if name.is_empty() || name.len() > 8 {
+        return None;
+    }
+    let mut tag = [0; 8];
+    tag[..name.len()].copy_from_slice(name.as_bytes());
+    Some(tag)
+}
+
+/// The name of `tag`, without its padding
+pub fn tag_name(tag: &[u8; 8]) -> String {
+    let len = tag.iter().position(|byte| *byte == 0).unwrap_or(tag.len());
+    String::from_utf8_lossy(&tag[..len]).into_owned()
+}
+
+impl TagCounters {
+    /// Read the tag counters' data, None if it holds none
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[..8] != TAG_COUNTERS_DISCRIMINATOR {
+            return None;
+        }
+        Self::deserialize(&mut &data[8..]).ok()
+    }
+
+    /// Write the tag counters into their data, of TAG_COUNTERS_SIZE bytes
+    pub fn pack(&self, data: &mut [u8]) -> std::io::Result<()> {
+        data[..8].copy_from_slice(&TAG_COUNTERS_DISCRIMINATOR);
+        self.serialize(&mut &mut data[8..])
+    }
+
+    /// Greetings counted under `tag`
+    pub fn count(&self, tag: &[u8; 8]) -> u64 {
+        self.entries
+            .iter()
+            .find(|entry| entry.tag == *tag)
+            .map_or(0, |entry| entry.count)
+    }
+
+    /// Count a greeting under `tag`, adding its entry if it's new. Returns the tag's
+    /// count, None when it's new and there's no room left.
+    pub fn increment(&mut self, tag: &[u8; 8]) -> Option<u64> {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.tag == *tag) {
+            entry.count = entry.count.saturating_add(1);
+            return Some(entry.count);
+        }
+        if self.entries.len() >= MAX_TAGS {
+            return None;
+        }
+        self.entries.push(TagCount {
+            tag: *tag,
+            count: 1,
+        });
+        Some(1)
+    }
+}
+
+/// Address and bump seed of `greeting_account`'s tag counters
+#[cfg(feature = "program")]
+pub fn tag_counters_address(program_id: &Pubkey, greeting_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TAGS_SEED, greeting_account.as_ref()], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tag_counters() {
+        let (gm, gn) = (tag("gm").unwrap(), tag("gn").unwrap());
+        let mut counters = TagCounters::default();
+        assert_eq!(counters.increment(&gm), Some(1));
+        assert_eq!(counters.increment(&gn), Some(1));
+        assert_eq!(counters.increment(&gm), Some(2));
+        assert_eq!(
+            (counters.count(&gm), counters.count(&tag("hello").unwrap())),
+            (2, 0)
+        );
+
+        // Packed at full capacity, they fit the account
+        for i in 2..MAX_TAGS {
+            assert_eq!(counters.increment(&tag(&i.to_string()).unwrap()), Some(1));
+        }
+        assert_eq!(counters.increment(&tag("one more").unwrap()), None);
+        assert_eq!(counters.increment(&gn), Some(2));
+        let mut data = [0; TAG_COUNTERS_SIZE];
+        assert_eq!(TagCounters::unpack(&data), None);
+        counters.pack(&mut data).unwrap();
+        assert_eq!(TagCounters::unpack(&data), Some(counters));
+    }
+
+    #[test]
+    fn test_tag_names() {
+        assert_eq!(tag("gm"), Some(*b"gm\0\0\0\0\0\0"));
+        assert_eq!(tag("hello123"), Some(*b"hello123"));
+        assert_eq!((tag(""), tag("too long!")), (None, None));
+        assert_eq!(tag_name(b"gm\0\0\0\0\0\0"), "gm");
+        assert_eq!(tag_name(b"hello123"), "hello123");
+    }
+
+    #[cfg(feature = "program")]
+    #[test]
+    fn test_tag_counters_discriminator_is_anchors() {
+        let hash = solana_program::hash::hash(b"account:TagCounters");
+        assert_eq!(hash.to_bytes()[..8], TAG_COUNTERS_DISCRIMINATOR);
+    }
+}
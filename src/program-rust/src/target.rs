@@ -0,0 +1,56 @@
+//! Greeting accounts credited to a user, at an address derived from the user's key
+// NOTE Greet leaves both roles to one client: whoever builds the transaction pays for
+// it and picks the greeting account. GreetFor splits them. The payer signs and pays
+// (the fee and, the first time, the account's rent), the target is whoever the
+// greeting counts for and doesn't sign. The target's greeting account is a PDA at
+// [TARGET_GREETING_SEED, target], so a backend paying for its users' greetings can
+// only ever credit the user it names, and a user finds their account from their own
+// key.
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+
+use crate::{increment::FIELDS_SIZE, migrations};
+
+/// Seed of a target's greeting account, followed by the target's key
+pub const TARGET_GREETING_SEED: &[u8] = b"greeting";
+
+/// Longest message a target's greeting account has room for, in bytes
+pub const MAX_TARGET_MESSAGE_LEN: usize = 32;
+
+/// Size of a target's greeting account: the current layout with the longest message
+pub const TARGET_GREETING_SIZE: usize =
+    migrations::GREETING_DISCRIMINATOR.len() + 4 + MAX_TARGET_MESSAGE_LEN + FIELDS_SIZE;
+
+/// Address and bump seed of `target`'s greeting account
+#[cfg(feature = "program")]
+pub fn greeting_address(program_id: &Pubkey, target: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TARGET_GREETING_SEED, target.as_ref()], program_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{calendar::DayBitmap, GreetingAccount, STATE_VERSION};
+
+    #[test]
+    fn test_target_greeting_size() {
+        let greeting = GreetingAccount {
+            txt: "x".repeat(MAX_TARGET_MESSAGE_LEN),
+            counter: u64::MAX,
+            calendar_year: 2024,
+            greeted_days: DayBitmap::default(),
+            last_update_epoch: u64::MAX,
+            direct_greets: u64::MAX,
+            cpi_greets: u64::MAX,
+        };
+        let data = migrations::encode_as(STATE_VERSION, &greeting).unwrap();
+        assert_eq!(data.len(), TARGET_GREETING_SIZE);
+
+        let program_id = Pubkey::new_unique();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_ne!(
+            greeting_address(&program_id, &alice).0,
+            greeting_address(&program_id, &bob).0
+        );
+    }
+}
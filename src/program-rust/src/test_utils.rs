@@ -0,0 +1,168 @@
+//! What unit tests need to run processors off-chain, shared with the other programs in
+//! the workspace through the `test-utils` feature
+// NOTE Unit tests call processors directly rather than through a validator, so
+// there's no runtime behind the syscalls: CPIs do nothing and the default stubs have
+// no sysvars. use_test_sysvars() serves Clock::get(), Rent::get() and
+// EpochSchedule::get() from here instead.
+use solana_program::{
+    clock::Clock,
+    entrypoint::SUCCESS,
+    epoch_schedule::EpochSchedule,
+    instruction::Instruction,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    rent::Rent,
+};
+use std::{
+    cell::{Cell, RefCell},
+    sync::Once,
+};
+
+/// The unix timestamp tests run at, 2024-03-01T12:00:00Z, day 60 of a leap year
+pub const NOW: i64 = 1_709_294_400;
+
+/// The epoch tests run in
+pub const EPOCH: u64 = 500;
+
+/// The slot tests run in, the first of EPOCH
+pub const SLOT: u64 = 200_000;
+
+/// Slots in an epoch of the schedule tests run under, without warmup so SLOT is in
+/// EPOCH
+pub const SLOTS_PER_EPOCH: u64 = SLOT / EPOCH;
+
+/// Compute units a sol_log() costs the meter capture_logs() fakes, the syscall's base
+/// cost on chain
+pub const LOG_UNITS: u64 = 100;
+
+/// Compute units the faked meter starts at, a transaction's default budget
+const BUDGET: u64 = 200_000;
+
+thread_local! {
+    /// The lines logged and the units left, while capture_logs() runs on this thread
+    static CAPTURE: RefCell<Option<(Vec<String>, u64)>> = const { RefCell::new(None) };
+    /// Whether logs are dropped, while without_logs() runs on this thread
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+struct TestSysvars;
+
+impl SyscallStubs for TestSysvars {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT,
+            unix_timestamp: NOW,
+            epoch: EPOCH,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let schedule = EpochSchedule::custom(SLOTS_PER_EPOCH, SLOTS_PER_EPOCH, false);
+        unsafe { *(var_addr as *mut EpochSchedule) = schedule };
+        SUCCESS
+    }
+
+    fn sol_log(&self, message: &str) {
+        if QUIET.with(Cell::get) {
+            return;
+        }
+        CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+            Some((logs, remaining)) => {
+                *remaining = remaining.saturating_sub(LOG_UNITS);
+                logs.push(format!("Program log: {}", message));
+            }
+            None => println!("{}", message),
+        });
+    }
+
+    #[cfg(feature = "sdk-1-18")]
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<_> = fields.iter().map(|field| encode_base64(field)).collect();
+        if QUIET.with(Cell::get) {
+            return;
+        }
+        CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+            Some((logs, remaining)) => {
+                *remaining = remaining.saturating_sub(LOG_UNITS);
+                logs.push(format!("Program data: {}", fields.join(" ")));
+            }
+            None => println!("data: {}", fields.join(" ")),
+        });
+    }
+
+    fn sol_log_compute_units(&self) {
+        if QUIET.with(Cell::get) {
+            return;
+        }
+        CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+            Some((logs, remaining)) => logs.push(format!(
+                "Program consumption: {} units remaining",
+                remaining
+            )),
+            None => println!("SyscallStubs: sol_log_compute_units() not available"),
+        });
+    }
+}
+
+/// `data` in base64, as sol_log_data() logs it
+pub fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let mut bytes = [0; 3];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Serve Clock::get() SLOT, EPOCH and NOW, Rent::get() the default rent and
+/// EpochSchedule::get() epochs of SLOTS_PER_EPOCH, for the rest of the test run
+pub fn use_test_sysvars() {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestSysvars));
+    });
+}
+
+/// Run `f` with the test sysvars, returning what it logged the way a validator would,
+/// with a compute meter where only logging costs (LOG_UNITS a line)
+// NOTE Captured per thread, since tests run in parallel with the one set of stubs
+pub fn capture_logs<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    use_test_sysvars();
+    CAPTURE.with(|capture| *capture.borrow_mut() = Some((Vec::new(), BUDGET)));
+    let result = f();
+    let (logs, _) = CAPTURE.with(|capture| capture.borrow_mut().take()).unwrap();
+    (result, logs)
+}
+
+/// Run `f` with the test sysvars, dropping what it logs rather than printing it
+// NOTE For tests counting what the program allocates: the test harness allocates to
+// capture printed output, where sol_log() on chain doesn't
+pub fn without_logs<R>(f: impl FnOnce() -> R) -> R {
+    use_test_sysvars();
+    QUIET.with(|quiet| quiet.set(true));
+    let result = f();
+    QUIET.with(|quiet| quiet.set(false));
+    result
+}
+
+/// Instructions sysvar data for a transaction of `instructions`, running the one at
+/// `current`, as the runtime lays it out (see origin.rs)
+pub fn instructions_sysvar_data(instructions: &[Instruction], current: u16) -> Vec<u8> {
+    crate::sdk::instructions_sysvar_data(instructions, current)
+}
@@ -0,0 +1,129 @@
+//! The bits of the SPL Token program the stake vault and the escrow program need
+// NOTE spl-token needs a newer solana-program than the one pinned here, so, like
+// pyth.rs, this reads token accounts and builds Transfer by hand. Token accounts have a
+// fixed layout: the mint, the owner (the key allowed to move the tokens) and the amount
+// up front, and the state byte at 108.
+use std::convert::TryInto;
+
+/// Size of a token account's data
+pub const TOKEN_ACCOUNT_SIZE: usize = 165;
+
+/// Token account state of an initialized, not frozen account
+pub const STATE_INITIALIZED: u8 = 1;
+
+const STATE_OFFSET: usize = 108;
+
+/// TokenInstruction tag of Transfer
+const TRANSFER: u8 = 3;
+
+/// TokenInstruction tag of CloseAccount
+#[cfg(feature = "program")]
+const CLOSE_ACCOUNT: u8 = 9;
+
+// The SPL Token program
+#[cfg(feature = "program")]
+solana_program::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// The fields of a token account read here
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenAccount {
+    pub mint: [u8; 32],
+    pub owner: [u8; 32],
+    pub amount: u64,
+}
+
+impl TokenAccount {
+    /// Read an initialized token account's data, or None if it isn't one
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() != TOKEN_ACCOUNT_SIZE || data[STATE_OFFSET] != STATE_INITIALIZED {
+            return None;
+        }
+        Some(Self {
+            mint: data[..32].try_into().ok()?,
+            owner: data[32..64].try_into().ok()?,
+            amount: u64::from_le_bytes(data[64..72].try_into().ok()?),
+        })
+    }
+}
+
+/// Instruction data of a Transfer of `amount` tokens
+pub fn transfer_data(amount: u64) -> Vec<u8> {
+    let mut data = vec![TRANSFER];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// Build a Transfer of `amount` tokens from `source` to `destination`, signed by
+/// `authority`, the source's owner
+#[cfg(feature = "program")]
+pub fn transfer(
+    source: &solana_program::pubkey::Pubkey,
+    destination: &solana_program::pubkey::Pubkey,
+    authority: &solana_program::pubkey::Pubkey,
+    amount: u64,
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    Instruction::new_with_bytes(
+        id(),
+        &transfer_data(amount),
+        vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Build a CloseAccount of the empty token account `account`, sending its rent to
+/// `destination`, signed by `authority`, its owner
+#[cfg(feature = "program")]
+pub fn close_account(
+    account: &solana_program::pubkey::Pubkey,
+    destination: &solana_program::pubkey::Pubkey,
+    authority: &solana_program::pubkey::Pubkey,
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    Instruction::new_with_bytes(
+        id(),
+        &[CLOSE_ACCOUNT],
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Data of an initialized token account, for tests
+#[cfg(any(test, feature = "test-utils"))]
+pub fn token_account(mint: [u8; 32], owner: [u8; 32], amount: u64) -> Vec<u8> {
+    let mut data = vec![0; TOKEN_ACCOUNT_SIZE];
+    data[..32].copy_from_slice(&mint);
+    data[32..64].copy_from_slice(&owner);
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[STATE_OFFSET] = STATE_INITIALIZED;
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_token_account() {
+        let data = token_account([1; 32], [2; 32], 500);
+        assert_eq!(
+            TokenAccount::parse(&data),
+            Some(TokenAccount {
+                mint: [1; 32],
+                owner: [2; 32],
+                amount: 500,
+            })
+        );
+        assert_eq!(TokenAccount::parse(&data[..82]), None);
+        let mut frozen = data;
+        frozen[STATE_OFFSET] = 2;
+        assert_eq!(TokenAccount::parse(&frozen), None);
+        assert_eq!(transfer_data(500), vec![3, 244, 1, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,304 @@
+//! The interface of the transfer-hook program (src/transfer-hook), which counts every
+//! transfer of a Token-2022 mint as a greeting
+// NOTE spl-transfer-hook-interface and spl-token-2022 need a newer solana-program than
+// the one pinned here, so, like token.rs, this encodes the interface's two instructions
+// and reads Token-2022 accounts by hand. The interface's instructions open with the first
+// 8 bytes of sha256("spl-transfer-hook-interface:<name>").
+//
+// A mint whose TransferHook extension names the hook program gets Execute called by
+// Token-2022 on every transfer, with the source, mint, destination and owner, then the
+// mint's extra account metas (a PDA of the hook program at [EXTRA_ACCOUNT_METAS_SEED,
+// mint]) and the extra accounts it lists. The one extra account here is the mint's
+// transfer greetings (a PDA at [TRANSFER_GREETINGS_SEED, mint]), the counter. The mint
+// authority creates both with InitializeExtraAccountMetaList.
+//
+// Anyone can call Execute directly too, so it only counts while Token-2022 has the
+// source account flagged as transferring.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Instruction discriminator of Execute
+pub const EXECUTE: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+/// Instruction discriminator of InitializeExtraAccountMetaList
+pub const INITIALIZE_EXTRA_ACCOUNT_METAS: [u8; 8] = [43, 34, 13, 49, 167, 88, 235, 235];
+
+/// Seed of a mint's extra account metas, followed by the mint's key
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// Seed of a mint's transfer greetings, followed by the mint's key
+pub const TRANSFER_GREETINGS_SEED: &[u8] = b"transfer-greetings";
+
+/// Size of the extra account metas' data: the TLV header, the count and one meta
+pub const EXTRA_ACCOUNT_METAS_SIZE: usize = 8 + 4 + 4 + EXTRA_ACCOUNT_META_SIZE;
+
+const EXTRA_ACCOUNT_META_SIZE: usize = 1 + 32 + 1 + 1;
+
+/// Index of the mint among Execute's accounts
+const MINT_INDEX: u8 = 1;
+
+/// Size of a token account's data without extensions, and so where the account type of
+/// one with extensions is
+const BASE_ACCOUNT_SIZE: usize = 165;
+
+/// Token-2022 AccountType of token accounts
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Token-2022 ExtensionType of TransferHookAccount
+const TRANSFER_HOOK_ACCOUNT: u16 = 15;
+
+// The Token-2022 program
+#[cfg(feature = "program")]
+pub mod token_2022 {
+    solana_program::declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+}
+
+/// Transfers of a mint counted as greetings
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct TransferGreetings {
+    /// key of the mint
+    pub mint: [u8; 32],
+    /// transfers counted
+    pub counter: u64,
+    /// base units transferred in them
+    pub volume: u64,
+}
+
+/// Size of a transfer greetings account's data
+pub const TRANSFER_GREETINGS_SIZE: usize = 32 + 8 + 8;
+
+/// An instruction of the transfer-hook interface
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransferHookInstruction {
+    /// Count a transfer of `amount` base units
+    Execute { amount: u64 },
+    /// Create the mint's extra account metas and transfer greetings. The metas the
+    /// instruction carries are ignored, the list is always the transfer greetings.
+    InitializeExtraAccountMetaList,
+}
+
+impl TransferHookInstruction {
+    /// Read an instruction's data, or None if it isn't one of the interface's
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 {
+            return None;
+        }
+        let (discriminator, rest) = data.split_at(8);
+        if discriminator == EXECUTE {
+            let amount = rest.get(..8)?.try_into().ok()?;
+            Some(Self::Execute {
+                amount: u64::from_le_bytes(amount),
+            })
+        } else if discriminator == INITIALIZE_EXTRA_ACCOUNT_METAS {
+            Some(Self::InitializeExtraAccountMetaList)
+        } else {
+            None
+        }
+    }
+
+    /// The instruction's data
+    pub fn pack(&self) -> Vec<u8> {
+        match self {
+            Self::Execute { amount } => [&EXECUTE[..], &amount.to_le_bytes()].concat(),
+            Self::InitializeExtraAccountMetaList => {
+                // An empty list of metas
+                [&INITIALIZE_EXTRA_ACCOUNT_METAS[..], &0u32.to_le_bytes()].concat()
+            }
+        }
+    }
+}
+
+/// Data of a mint's extra account metas: an ExtraAccountMetaList for Execute holding
+/// the one writable PDA at [TRANSFER_GREETINGS_SEED, mint]
+pub fn extra_account_metas_data() -> Vec<u8> {
+    // Seeds of the PDA: Seed::Literal (1, length, bytes), then Seed::AccountKey (3, index)
+    let mut address_config = vec![1, TRANSFER_GREETINGS_SEED.len() as u8];
+    address_config.extend_from_slice(TRANSFER_GREETINGS_SEED);
+    address_config.extend_from_slice(&[3, MINT_INDEX]);
+    address_config.resize(32, 0);
+
+    let mut data = EXECUTE.to_vec();
+    data.extend_from_slice(&((4 + EXTRA_ACCOUNT_META_SIZE) as u32).to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    // A PDA of the hook program, not a signer, writable
+    data.push(1);
+    data.extend_from_slice(&address_config);
+    data.extend_from_slice(&[0, 1]);
+    data
+}
+
+/// The mint authority of a Token-2022 mint's data, or None without one
+pub fn mint_authority(data: &[u8]) -> Option<[u8; 32]> {
+    if data.len() < 36 || data[..4] != [1, 0, 0, 0] {
+        return None;
+    }
+    data[4..36].try_into().ok()
+}
+
+/// The mint of a token account's data
+pub fn token_account_mint(data: &[u8]) -> Option<[u8; 32]> {
+    data.get(..32)?.try_into().ok()
+}
+
+/// Whether a Token-2022 token account's data has it in the middle of a transfer, as its
+/// TransferHookAccount extension says
+pub fn is_transferring(data: &[u8]) -> bool {
+    if data.len() <= BASE_ACCOUNT_SIZE || data[BASE_ACCOUNT_SIZE] != ACCOUNT_TYPE_ACCOUNT {
+        return false;
+    }
+    // The extensions are TLV entries: a u16 type, a u16 length and the value
+    let mut offset = BASE_ACCOUNT_SIZE + 1;
+    while offset + 4 <= data.len() {
+        let extension = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        let value = offset + 4;
+        if extension == 0 || value + length > data.len() {
+            return false;
+        }
+        if extension == TRANSFER_HOOK_ACCOUNT {
+            return length == 1 && data[value] == 1;
+        }
+        offset = value + length;
+    }
+    false
+}
+
+/// Address and bump seed of `mint`'s extra account metas
+#[cfg(feature = "program")]
+pub fn extra_account_metas_address(hook_program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], hook_program_id)
+}
+
+/// Address and bump seed of `mint`'s transfer greetings
+#[cfg(feature = "program")]
+pub fn transfer_greetings_address(hook_program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TRANSFER_GREETINGS_SEED, mint.as_ref()], hook_program_id)
+}
+
+/// Build an InitializeExtraAccountMetaList instruction for `mint`, signed by its mint
+/// authority. Both accounts it creates need their rent-exempt lamports transferred to
+/// them first (EXTRA_ACCOUNT_METAS_SIZE and TRANSFER_GREETINGS_SIZE bytes).
+#[cfg(feature = "program")]
+pub fn initialize_extra_account_meta_list(
+    hook_program_id: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    let (extra_account_metas, _) = extra_account_metas_address(hook_program_id, mint);
+    let (transfer_greetings, _) = transfer_greetings_address(hook_program_id, mint);
+    Instruction::new_with_bytes(
+        *hook_program_id,
+        &TransferHookInstruction::InitializeExtraAccountMetaList.pack(),
+        vec![
+            AccountMeta::new(extra_account_metas, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new(transfer_greetings, false),
+        ],
+    )
+}
+
+/// Build the Execute instruction Token-2022 sends for a transfer of `amount` of `mint`
+#[cfg(feature = "program")]
+pub fn execute(
+    hook_program_id: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> solana_program::instruction::Instruction {
+    use solana_program::instruction::{AccountMeta, Instruction};
+    let (extra_account_metas, _) = extra_account_metas_address(hook_program_id, mint);
+    let (transfer_greetings, _) = transfer_greetings_address(hook_program_id, mint);
+    Instruction::new_with_bytes(
+        *hook_program_id,
+        &TransferHookInstruction::Execute { amount }.pack(),
+        vec![
+            AccountMeta::new_readonly(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(extra_account_metas, false),
+            AccountMeta::new(transfer_greetings, false),
+        ],
+    )
+}
+
+/// Data of a Token-2022 mint with `authority` as its mint authority, for tests
+#[cfg(test)]
+pub(crate) fn mint(authority: [u8; 32]) -> Vec<u8> {
+    let mut data = vec![0; 82];
+    data[..4].copy_from_slice(&[1, 0, 0, 0]);
+    data[4..36].copy_from_slice(&authority);
+    data
+}
+
+/// Data of a Token-2022 token account of `mint` with the TransferHookAccount extension,
+/// for tests
+#[cfg(test)]
+pub(crate) fn hooked_token_account(mint: [u8; 32], transferring: bool) -> Vec<u8> {
+    let mut data = crate::token::token_account(mint, [2; 32], 500);
+    data.push(ACCOUNT_TYPE_ACCOUNT);
+    // ImmutableOwner, empty, then TransferHookAccount
+    data.extend_from_slice(&[7, 0, 0, 0]);
+    data.extend_from_slice(&[15, 0, 1, 0, transferring as u8]);
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instructions() {
+        let data = TransferHookInstruction::Execute { amount: 500 }.pack();
+        assert_eq!(
+            data,
+            vec![105, 37, 101, 197, 75, 251, 102, 26, 244, 1, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            TransferHookInstruction::unpack(&data),
+            Some(TransferHookInstruction::Execute { amount: 500 })
+        );
+        assert_eq!(TransferHookInstruction::unpack(&data[..12]), None);
+        let data = TransferHookInstruction::InitializeExtraAccountMetaList.pack();
+        assert_eq!(
+            TransferHookInstruction::unpack(&data),
+            Some(TransferHookInstruction::InitializeExtraAccountMetaList)
+        );
+        assert_eq!(TransferHookInstruction::unpack(&[0; 16]), None);
+    }
+
+    #[test]
+    fn test_extra_account_metas_data() {
+        let data = extra_account_metas_data();
+        assert_eq!(data.len(), EXTRA_ACCOUNT_METAS_SIZE);
+        assert_eq!(&data[..8], &EXECUTE);
+        assert_eq!(&data[8..16], &[39, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(&data[16..20], &[1, 1, 18, b't']);
+        assert_eq!(&data[37..40], &[3, 1, 0]);
+        assert_eq!(&data[49..], &[0, 1]);
+    }
+
+    #[test]
+    fn test_token_2022_accounts() {
+        assert_eq!(mint_authority(&mint([7; 32])), Some([7; 32]));
+        assert_eq!(mint_authority(&[0; 82]), None);
+        let data = hooked_token_account([1; 32], true);
+        assert_eq!(token_account_mint(&data), Some([1; 32]));
+        assert!(is_transferring(&data));
+        assert!(!is_transferring(&hooked_token_account([1; 32], false)));
+        // Nor a token account without extensions, or with a truncated one
+        assert!(!is_transferring(&data[..BASE_ACCOUNT_SIZE]));
+        assert!(!is_transferring(&data[..data.len() - 1]));
+        assert_eq!(
+            TransferGreetings::default().try_to_vec().unwrap().len(),
+            TRANSFER_GREETINGS_SIZE
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Greetings received from other chains through Wormhole
+// NOTE wormhole-anchor-sdk needs a newer solana-program than the one pinned here, so,
+// like switchboard.rs, this reads the posted VAA account straight out of its data.
+//
+// A contract on another chain greets by publishing a Wormhole message. Once the
+// guardians sign it, anyone can post the VAA to the core bridge on Solana, which checks
+// the signatures and stores the message in a posted VAA account it owns. Those are the
+// only accounts of the core bridge opening with the "vaa" magic (outgoing messages
+// open with "msg" and carry no signatures), so an account owned by the core bridge
+// with that magic is a verified message.
+//
+// The sender is the message's emitter, identified by its Wormhole chain id and 32-byte
+// universal address. Anyone can publish a message, so only the emitters the config
+// authority registered greet: RegisterForeignGreeter creates the emitter's foreign
+// greeter, a PDA of this program at [FOREIGN_GREETER_SEED, chain (big-endian), emitter
+// address], counting its greetings, and a VAA from an emitter without one is rejected.
+// The message has to be a greeting too, its payload the Borsh String of the message
+// (see `decode_greeting`), not whatever else the emitter publishes. Emitters number
+// their messages, so a VAA only counts if its sequence is past the last one received,
+// which stops the same VAA being received twice (and drops a message relayed after a
+// later one).
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+#[cfg(feature = "program")]
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+/// Magic a posted VAA account's data opens with
+pub const POSTED_VAA_MAGIC: &[u8] = b"vaa";
+
+const SEQUENCE_OFFSET: usize = 49;
+const EMITTER_CHAIN_OFFSET: usize = 57;
+const EMITTER_ADDRESS_OFFSET: usize = 59;
+const PAYLOAD_OFFSET: usize = 91;
+
+/// Seed of a foreign greeter, followed by its chain id and emitter address
+pub const FOREIGN_GREETER_SEED: &[u8] = b"foreign-greeter";
+
+/// The Wormhole core bridge on mainnet-beta
+#[cfg(feature = "program")]
+pub mod mainnet {
+    solana_program::declare_id!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+}
+
+/// The Wormhole core bridge on devnet
+#[cfg(feature = "program")]
+pub mod devnet {
+    solana_program::declare_id!("3u8hJUVTA4jH1wYAyUur7FFZVQ8H635K3tSHHF4ssjQ5");
+}
+
+/// The fields of a posted VAA read here
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostedVaa {
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+impl PostedVaa {
+    /// Read a posted VAA account's data, or None if it isn't one
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < PAYLOAD_OFFSET + 4 || !data.starts_with(POSTED_VAA_MAGIC) {
+            return None;
+        }
+        let payload_len =
+            u32::from_le_bytes(data[PAYLOAD_OFFSET..PAYLOAD_OFFSET + 4].try_into().ok()?) as usize;
+        let payload = data.get(PAYLOAD_OFFSET + 4..PAYLOAD_OFFSET + 4 + payload_len)?;
+        Some(Self {
+            sequence: u64::from_le_bytes(
+                data[SEQUENCE_OFFSET..SEQUENCE_OFFSET + 8].try_into().ok()?,
+            ),
+            emitter_chain: u16::from_le_bytes(
+                data[EMITTER_CHAIN_OFFSET..EMITTER_CHAIN_OFFSET + 2]
+                    .try_into()
+                    .ok()?,
+            ),
+            emitter_address: data[EMITTER_ADDRESS_OFFSET..EMITTER_ADDRESS_OFFSET + 32]
+                .try_into()
+                .ok()?,
+            payload: payload.to_vec(),
+        })
+    }
+}
+
+/// The greeting a VAA's payload carries: the Borsh String of a non-empty message and
+/// nothing after it. None if the payload is anything else.
+pub fn decode_greeting(payload: &[u8]) -> Option<String> {
+    String::try_from_slice(payload)
+        .ok()
+        .filter(|txt| !txt.is_empty())
+}
+
+/// Greetings received from one emitter on another chain
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, Default, PartialEq)]
+pub struct ForeignGreeter {
+    /// Wormhole chain id of the emitter
+    pub chain: u16,
+    /// universal address of the emitter
+    pub sender: [u8; 32],
+    /// greetings received
+    pub counter: u64,
+    /// sequence of the last VAA received
+    pub last_sequence: u64,
+}
+
+/// Size of a foreign greeter account's data
+pub const FOREIGN_GREETER_SIZE: usize = 2 + 32 + 8 + 8;
+
+impl ForeignGreeter {
+    /// Whether a VAA numbered `sequence` is new, so it can be received
+    pub fn is_new(&self, sequence: u64) -> bool {
+        self.counter == 0 || sequence > self.last_sequence
+    }
+}
+
+/// Address and bump seed of the foreign greeter of the emitter `sender` on `chain`
+#[cfg(feature = "program")]
+pub fn foreign_greeter_address(program_id: &Pubkey, chain: u16, sender: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FOREIGN_GREETER_SEED, &chain.to_be_bytes(), sender],
+        program_id,
+    )
+}
+
+/// Data of a posted VAA account, for tests
+#[cfg(test)]
+pub(crate) fn posted_vaa(chain: u16, sender: [u8; 32], sequence: u64, payload: &[u8]) -> Vec<u8> {
+    let mut data = vec![0; PAYLOAD_OFFSET];
+    data[..3].copy_from_slice(POSTED_VAA_MAGIC);
+    data[SEQUENCE_OFFSET..SEQUENCE_OFFSET + 8].copy_from_slice(&sequence.to_le_bytes());
+    data[EMITTER_CHAIN_OFFSET..EMITTER_CHAIN_OFFSET + 2].copy_from_slice(&chain.to_le_bytes());
+    data[EMITTER_ADDRESS_OFFSET..EMITTER_ADDRESS_OFFSET + 32].copy_from_slice(&sender);
+    data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_posted_vaa() {
+        let data = posted_vaa(2, [7; 32], 41, b"Hello");
+        assert_eq!(
+            PostedVaa::parse(&data),
+            Some(PostedVaa {
+                sequence: 41,
+                emitter_chain: 2,
+                emitter_address: [7; 32],
+                payload: b"Hello".to_vec(),
+            })
+        );
+        // Truncated, or an unsigned outgoing message
+        assert_eq!(PostedVaa::parse(&data[..data.len() - 1]), None);
+        let mut message = data;
+        message[..3].copy_from_slice(b"msg");
+        assert_eq!(PostedVaa::parse(&message), None);
+    }
+
+    #[test]
+    fn test_decode_greeting() {
+        let payload = "Hello".to_string().try_to_vec().unwrap();
+        assert_eq!(decode_greeting(&payload), Some("Hello".to_string()));
+        // Raw bytes, trailing bytes, an empty message and a message that isn't UTF-8
+        assert_eq!(decode_greeting(b"Hello"), None);
+        assert_eq!(decode_greeting(&[&payload[..], &[0]].concat()), None);
+        assert_eq!(decode_greeting(&[0; 4]), None);
+        assert_eq!(decode_greeting(&[2, 0, 0, 0, 0xc3, 0x28]), None);
+    }
+
+    #[test]
+    fn test_foreign_greeter() {
+        let greeter = ForeignGreeter {
+            counter: 1,
+            last_sequence: 41,
+            ..ForeignGreeter::default()
+        };
+        assert_eq!(greeter.try_to_vec().unwrap().len(), FOREIGN_GREETER_SIZE);
+        assert!(ForeignGreeter::default().is_new(0));
+        assert!(!greeter.is_new(41));
+        assert!(greeter.is_new(42));
+    }
+}
@@ -0,0 +1,210 @@
+// NOTE Greetings don't contend for a shared account: a Greet writes only its own
+// greeting account (the shard) and reads the config, so with every greeter paying its
+// own fee, the Greets of one slot lock disjoint accounts and the runtime can run them
+// side by side. The totals are summed off the hot path, by SnapshotCounters copying
+// every shard's counter into a counter snapshot.
+//
+// Here many greeters greet at once, round after round, in transactions signed with
+// the same blockhash and sent together. Their write locks have to be disjoint, every
+// Greet has to land, and the snapshot taken afterwards has to add up to all of them.
+use helloworld::{
+    config::config_address,
+    counter_snapshot::{self, CounterSnapshot},
+    fixtures::{self, GREETING_SIZE},
+    instruction, process_instruction, GreetingAccount,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{keypair_from_seed, Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use std::collections::HashSet;
+
+const GREETERS: usize = 16;
+const ROUNDS: u64 = 3;
+const SNAPSHOT_DEPTH: u8 = 5;
+
+/// Keys `transaction` locks for writing
+fn write_locks(transaction: &Transaction) -> HashSet<Pubkey> {
+    let message = transaction.message();
+    message
+        .account_keys
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| message.is_writable(*index))
+        .map(|(_, pubkey)| *pubkey)
+        .collect()
+}
+
+/// Root of a snapshot tree SNAPSHOT_DEPTH deep holding `leaves` from the left
+fn root(leaves: &[([u8; 32], u64)]) -> [u8; 32] {
+    let mut level = (0..1usize << SNAPSHOT_DEPTH)
+        .map(|index| match leaves.get(index) {
+            Some((key, counter)) => counter_snapshot::leaf_hash(key, *counter),
+            None => [0; 32],
+        })
+        .collect::<Vec<_>>();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| counter_snapshot::node_hash(&pair[0], &pair[1]))
+            .collect();
+    }
+    level[0]
+}
+
+#[tokio::test]
+async fn test_greets_in_one_slot_lock_disjoint_accounts() {
+    let program_id = fixtures::program_id();
+    let greeters = (0..GREETERS)
+        .map(|index| keypair_from_seed(&fixtures::seed(&format!("greeter-{}", index))).unwrap())
+        .collect::<Vec<Keypair>>();
+    let shards = (0..GREETERS)
+        .map(|index| fixtures::key(&format!("shard-{}", index)))
+        .collect::<Vec<_>>();
+    let snapshot = fixtures::key("snapshot");
+    let snapshot_size = counter_snapshot::account_size(SNAPSHOT_DEPTH);
+
+    let mut program_test =
+        ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    for (greeter, shard) in greeters.iter().zip(&shards) {
+        program_test.add_account(
+            greeter.pubkey(),
+            Account {
+                lamports: fixtures::FUNDED_LAMPORTS,
+                owner: system_program::id(),
+                ..Account::default()
+            },
+        );
+        program_test.add_account(
+            *shard,
+            Account {
+                lamports: Rent::default().minimum_balance(GREETING_SIZE),
+                data: vec![0_u8; GREETING_SIZE],
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+    program_test.add_account(
+        snapshot,
+        Account {
+            lamports: Rent::default().minimum_balance(snapshot_size),
+            data: vec![0_u8; snapshot_size],
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // The payer is the config authority and the crank authority
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            instruction::init_config(&program_id, &payer.pubkey(), 0),
+            instruction::set_crank_authority(&program_id, &payer.pubkey(), &payer.pubkey()),
+            instruction::init_counter_snapshot(
+                &program_id,
+                &payer.pubkey(),
+                &snapshot,
+                SNAPSHOT_DEPTH,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let (config, _) = config_address(&program_id);
+    for round in 0..ROUNDS {
+        // One message per round, so no round repeats an earlier round's transaction
+        let txt = format!("Round {}", round);
+        let transactions = greeters
+            .iter()
+            .zip(&shards)
+            .map(|(greeter, shard)| {
+                let mut transaction = Transaction::new_with_payer(
+                    &[instruction::greet(&program_id, shard, &txt)],
+                    Some(&greeter.pubkey()),
+                );
+                transaction.sign(&[greeter], recent_blockhash);
+                transaction
+            })
+            .collect::<Vec<_>>();
+
+        // Each Greet locks its greeter and its shard for writing and nothing else:
+        // the config is only read, so it doesn't serialize them
+        let mut locked = HashSet::new();
+        for (transaction, (greeter, shard)) in transactions.iter().zip(greeters.iter().zip(&shards))
+        {
+            let locks = write_locks(transaction);
+            assert_eq!(locks, [greeter.pubkey(), *shard].iter().copied().collect());
+            assert!(!locks.contains(&config));
+            assert!(locked.is_disjoint(&locks));
+            locked.extend(locks);
+        }
+        banks_client
+            .process_transactions(transactions)
+            .await
+            .unwrap_or_else(|err| panic!("round {}: {}", round, err));
+    }
+
+    // Two Greets of the same shard would contend: both write it
+    let greet = |greeter: &Keypair| {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::greet(&program_id, &shards[0], "Hello")],
+            Some(&greeter.pubkey()),
+        );
+        transaction.sign(&[greeter], recent_blockhash);
+        write_locks(&transaction)
+    };
+    assert!(!greet(&greeters[0]).is_disjoint(&greet(&greeters[1])));
+
+    let mut leaves = vec![];
+    for shard in &shards {
+        let account = banks_client
+            .get_account(*shard)
+            .await
+            .expect("get_account")
+            .expect("shard not found");
+        let greeting = GreetingAccount::unpack(&account.data).unwrap();
+        assert_eq!(greeting.counter, ROUNDS);
+        assert_eq!(greeting.txt, format!("Round {}", ROUNDS - 1));
+        leaves.push((shard.to_bytes(), greeting.counter));
+    }
+
+    // Sum the shards up into the snapshot, each at the next free leaf
+    let indexed = shards
+        .iter()
+        .enumerate()
+        .map(|(index, shard)| (*shard, index as u32))
+        .collect::<Vec<_>>();
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::snapshot_counters(
+            &program_id,
+            &payer.pubkey(),
+            &snapshot,
+            &indexed,
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let data = banks_client
+        .get_account(snapshot)
+        .await
+        .expect("get_account")
+        .expect("snapshot not found")
+        .data;
+    let header = CounterSnapshot::unpack(&data).unwrap();
+    assert_eq!(header.leaf_count, GREETERS as u32);
+    let total = (0..header.leaf_count)
+        .map(|index| counter_snapshot::leaf(&data, index).unwrap().1)
+        .sum::<u64>();
+    assert_eq!(total, GREETERS as u64 * ROUNDS);
+    assert_eq!(header.root, root(&leaves));
+}
@@ -0,0 +1,213 @@
+// NOTE Transactions a client gets wrong: a signature left off, accounts in the wrong
+// order, too few or too many of them, instruction data cut short and accounts owned
+// by another program. Each has to fail with the error naming what's wrong, not just
+// fail, and leave the accounts as they were.
+use helloworld::{
+    accounts,
+    error::HelloWorldError,
+    fixtures::{self, GREETING_SIZE},
+    instruction, process_instruction, tags, GreetingAccount,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{keypair_from_seed, Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+
+struct Setup {
+    banks_client: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    program_id: Pubkey,
+    /// A greeting account of the program's, not greeted yet
+    greeted: Pubkey,
+    /// An account like it but owned by the system program
+    foreign: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let program_id = fixtures::program_id();
+    let greeted = fixtures::key("greeted");
+    let foreign = fixtures::key("foreign");
+    let mut program_test =
+        ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    for (pubkey, owner) in [(greeted, program_id), (foreign, system_program::id())] {
+        program_test.add_account(
+            pubkey,
+            Account {
+                lamports: Rent::default().minimum_balance(GREETING_SIZE),
+                data: vec![0_u8; GREETING_SIZE],
+                owner,
+                ..Account::default()
+            },
+        );
+    }
+    let (banks_client, payer, blockhash) = program_test.start().await;
+    Setup {
+        banks_client,
+        payer,
+        blockhash,
+        program_id,
+        greeted,
+        foreign,
+    }
+}
+
+impl Setup {
+    /// Run `instruction` paid for and signed by the payer alone
+    async fn process(&mut self, instruction: Instruction) -> Result<(), TransportError> {
+        let mut transaction =
+            Transaction::new_with_payer(&[instruction], Some(&self.payer.pubkey()));
+        transaction.sign(&[&self.payer], self.blockhash);
+        self.banks_client.process_transaction(transaction).await
+    }
+
+    async fn data(&mut self, pubkey: Pubkey) -> Vec<u8> {
+        self.banks_client
+            .get_account(pubkey)
+            .await
+            .expect("get_account")
+            .expect("account not found")
+            .data
+    }
+
+    /// Fail unless the greeting account still has no greeting
+    async fn assert_not_greeted(&mut self) {
+        assert_eq!(self.data(self.greeted).await, vec![0_u8; GREETING_SIZE]);
+    }
+}
+
+/// Fail unless `result` is the transaction's only instruction failing with `expected`
+fn assert_instruction_error(result: Result<(), TransportError>, expected: InstructionError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, expected)
+    );
+}
+
+fn custom(error: HelloWorldError) -> InstructionError {
+    InstructionError::Custom(error.code())
+}
+
+#[tokio::test]
+async fn test_missing_signer() {
+    let mut setup = setup().await;
+    let tagger = keypair_from_seed(&fixtures::seed("tagger")).unwrap();
+    let tag = tags::tag("gm").unwrap();
+    let count_tag =
+        instruction::count_tag(&setup.program_id, &setup.greeted, &tagger.pubkey(), tag);
+
+    // Signed by the fee payer but not the tagger: the runtime refuses it before the
+    // program runs
+    let mut transaction =
+        Transaction::new_with_payer(&[count_tag.clone()], Some(&setup.payer.pubkey()));
+    transaction.partial_sign(&[&setup.payer], setup.blockhash);
+    let result = setup.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::SignatureFailure
+    );
+
+    // The tagger not marked as a signer at all: the program refuses it
+    let mut unsigned = count_tag;
+    unsigned.accounts[accounts::tags::PAYER_IDX].is_signer = false;
+    let result = setup.process(unsigned).await;
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    let (tag_counters, _) = tags::tag_counters_address(&setup.program_id, &setup.greeted);
+    assert!(setup
+        .banks_client
+        .get_account(tag_counters)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_wrong_account_order() {
+    let mut setup = setup().await;
+
+    // The config where the greeting account goes, and the other way around
+    let mut greet = instruction::greet(&setup.program_id, &setup.greeted, "Hello1234567");
+    greet
+        .accounts
+        .swap(accounts::GREETING_ACCOUNT_IDX, accounts::CONFIG_IDX);
+    let result = setup.process(greet).await;
+    assert_instruction_error(result, custom(HelloWorldError::InvalidConfig));
+
+    let mut increment = instruction::increment(&setup.program_id, &setup.greeted);
+    increment
+        .accounts
+        .swap(accounts::GREETING_ACCOUNT_IDX, accounts::CONFIG_IDX);
+    let result = setup.process(increment).await;
+    assert_instruction_error(result, custom(HelloWorldError::InvalidConfig));
+    setup.assert_not_greeted().await;
+}
+
+#[tokio::test]
+async fn test_missing_accounts() {
+    let mut setup = setup().await;
+    let mut greet = instruction::greet(&setup.program_id, &setup.greeted, "Hello1234567");
+    greet.accounts.truncate(accounts::CONFIG_IDX);
+    let result = setup.process(greet).await;
+    assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+    setup.assert_not_greeted().await;
+}
+
+#[tokio::test]
+async fn test_extra_accounts() {
+    let mut setup = setup().await;
+
+    // Accounts past the ones an instruction takes are left alone, not refused
+    let extra = AccountMeta::new_readonly(setup.foreign, false);
+    let mut greet = instruction::greet(&setup.program_id, &setup.greeted, "Hello1234567");
+    greet.accounts.push(extra.clone());
+    setup.process(greet).await.unwrap();
+    let mut increment = instruction::increment(&setup.program_id, &setup.greeted);
+    increment.accounts.push(extra);
+    setup.process(increment).await.unwrap();
+
+    let greeting = GreetingAccount::unpack(&setup.data(setup.greeted).await).unwrap();
+    assert_eq!(greeting.txt, "Hello1234567");
+    assert_eq!(greeting.counter, 2);
+    assert_eq!(setup.data(setup.foreign).await, vec![0_u8; GREETING_SIZE]);
+}
+
+#[tokio::test]
+async fn test_truncated_instruction_data() {
+    let mut setup = setup().await;
+    let greet = instruction::greet(&setup.program_id, &setup.greeted, "Hello1234567");
+
+    // Cut anywhere, from no data at all to one byte short of the message
+    for len in [0, 1, 4, greet.data.len() - 1] {
+        let mut truncated = greet.clone();
+        truncated.data.truncate(len);
+        let result = setup.process(truncated).await;
+        assert_instruction_error(result, InstructionError::InvalidInstructionData);
+    }
+    setup.assert_not_greeted().await;
+}
+
+#[tokio::test]
+async fn test_account_owned_by_another_program() {
+    let mut setup = setup().await;
+    let result = setup
+        .process(instruction::greet(
+            &setup.program_id,
+            &setup.foreign,
+            "Hello1234567",
+        ))
+        .await;
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+    let result = setup
+        .process(instruction::increment(&setup.program_id, &setup.foreign))
+        .await;
+    assert_instruction_error(result, InstructionError::IncorrectProgramId);
+    assert_eq!(setup.data(setup.foreign).await, vec![0_u8; GREETING_SIZE]);
+}
@@ -0,0 +1,244 @@
+// NOTE A stateful property test: random sequences of valid instructions against one
+// bank each, with the program's global invariants checked after every step rather
+// than the result of one call on its own. The sequences come from a seeded Rng, so a
+// failure names the seed and step to replay.
+//
+// The program keeps no totals of its own to check against, so a model of what the
+// sequence so far should have done stands in for them: every counter is the number of
+// greetings its account got, the tag counters add up to the CountTags that landed, and
+// no lamports appear or go missing besides the transaction fees.
+use helloworld::{
+    fixtures::{self, Rng},
+    increment::FIELDS_SIZE,
+    instruction,
+    migrations::GREETING_DISCRIMINATOR,
+    process_instruction,
+    tags::{self, tag_counters_address, TagCounters},
+    GreetingAccount,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, rent::Rent, signature::Signer, transaction::Transaction,
+};
+use std::collections::BTreeMap;
+
+const SEQUENCES: u64 = 6;
+const STEPS: usize = 20;
+const GREETING_ACCOUNTS: usize = 3;
+const TAGS: &[&str] = &["gm", "gn", "hi"];
+const MAX_TXT_LEN: usize = 12;
+
+// Current layout: discriminator + Borsh String (u32 length prefix + up to MAX_TXT_LEN
+// bytes of text) + the fixed-size fields after it
+const GREETING_SIZE: usize = GREETING_DISCRIMINATOR.len() + 4 + MAX_TXT_LEN + FIELDS_SIZE;
+
+#[derive(Debug)]
+enum Step {
+    Greet(usize, String),
+    GreetMany(Vec<usize>, String),
+    Increment(usize),
+    CountTag(usize, [u8; 8]),
+}
+
+/// What the sequence so far should have left in each greeting account
+#[derive(Clone, Default)]
+struct Greeting {
+    txt: String,
+    counter: u64,
+    tags: BTreeMap<[u8; 8], u64>,
+}
+
+fn random_txt(rng: &mut Rng) -> String {
+    let len = 1 + rng.below(MAX_TXT_LEN as u64) as usize;
+    (0..len)
+        .map(|_| (b'a' + rng.below(26) as u8) as char)
+        .collect()
+}
+
+/// A step valid in the state `model` describes: Increment needs a greeted account
+fn random_step(rng: &mut Rng, model: &[Greeting]) -> Step {
+    let index = rng.below(GREETING_ACCOUNTS as u64) as usize;
+    match rng.below(4) {
+        0 => Step::Greet(index, random_txt(rng)),
+        1 => {
+            let indices = (0..GREETING_ACCOUNTS)
+                .filter(|_| rng.below(2) == 0)
+                .collect::<Vec<_>>();
+            if indices.is_empty() {
+                Step::Greet(index, random_txt(rng))
+            } else {
+                Step::GreetMany(indices, random_txt(rng))
+            }
+        }
+        2 if model[index].counter > 0 => Step::Increment(index),
+        _ => {
+            let tag = TAGS[rng.below(TAGS.len() as u64) as usize];
+            Step::CountTag(index, tags::tag(tag).unwrap())
+        }
+    }
+}
+
+/// Apply `step` to the model
+fn expect(model: &mut [Greeting], step: &Step) {
+    match step {
+        Step::Greet(index, txt) => {
+            model[*index].txt = txt.clone();
+            model[*index].counter += 1;
+        }
+        Step::GreetMany(indices, txt) => {
+            for index in indices {
+                model[*index].txt = txt.clone();
+                model[*index].counter += 1;
+            }
+        }
+        Step::Increment(index) => model[*index].counter += 1,
+        Step::CountTag(index, tag) => *model[*index].tags.entry(*tag).or_default() += 1,
+    }
+}
+
+async fn lamports(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    banks_client
+        .get_account(pubkey)
+        .await
+        .expect("get_account")
+        .map_or(0, |account| account.lamports)
+}
+
+#[tokio::test]
+async fn test_invariants_hold_across_instruction_sequences() {
+    let program_id = fixtures::program_id();
+    let greeted = (0..GREETING_ACCOUNTS)
+        .map(|index| fixtures::key(&format!("greeted-{}", index)))
+        .collect::<Vec<_>>();
+    let tag_counters = greeted
+        .iter()
+        .map(|pubkey| tag_counters_address(&program_id, pubkey).0)
+        .collect::<Vec<_>>();
+
+    for seed in 1..=SEQUENCES {
+        let mut program_test =
+            ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+        for pubkey in &greeted {
+            program_test.add_account(
+                *pubkey,
+                Account {
+                    // Rent-exempt, so rent collection doesn't take from the total
+                    lamports: Rent::default().minimum_balance(GREETING_SIZE),
+                    data: vec![0_u8; GREETING_SIZE],
+                    owner: program_id,
+                    ..Account::default()
+                },
+            );
+        }
+        let (mut banks_client, payer, mut blockhash) = program_test.start().await;
+        // Every account a step can move lamports between
+        let mut watched = vec![payer.pubkey()];
+        watched.extend(&greeted);
+        watched.extend(&tag_counters);
+
+        let mut rng = Rng::new(seed);
+        let mut model = vec![Greeting::default(); GREETING_ACCOUNTS];
+        for step_index in 0..STEPS {
+            let step = random_step(&mut rng, &model);
+            let context = format!("seed {} step {}: {:?}", seed, step_index, step);
+            let instruction = match &step {
+                Step::Greet(index, txt) => instruction::greet(&program_id, &greeted[*index], txt),
+                Step::GreetMany(indices, txt) => {
+                    let pubkeys = indices
+                        .iter()
+                        .map(|index| greeted[*index])
+                        .collect::<Vec<_>>();
+                    instruction::greet_many(&program_id, &pubkeys, txt)
+                }
+                Step::Increment(index) => instruction::increment(&program_id, &greeted[*index]),
+                Step::CountTag(index, tag) => {
+                    instruction::count_tag(&program_id, &greeted[*index], &payer.pubkey(), *tag)
+                }
+            };
+
+            // A fresh blockhash every step, or a step repeating an earlier one would be
+            // refused as the same transaction
+            let (new_blockhash, fee_calculator) = banks_client
+                .get_new_blockhash(&blockhash)
+                .await
+                .expect("get_new_blockhash");
+            blockhash = new_blockhash;
+            let mut transaction =
+                Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+            transaction.sign(&[&payer], blockhash);
+            let fee = fee_calculator.calculate_fee(transaction.message());
+
+            let mut before = vec![];
+            for pubkey in &watched {
+                before.push(lamports(&mut banks_client, *pubkey).await);
+            }
+            banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_or_else(|err| panic!("{}: {}", context, err));
+            expect(&mut model, &step);
+            let mut after = vec![];
+            for pubkey in &watched {
+                after.push(lamports(&mut banks_client, *pubkey).await);
+            }
+
+            // No lamports made or lost, besides the fee
+            assert_eq!(
+                after.iter().sum::<u64>() + fee,
+                before.iter().sum::<u64>(),
+                "{}",
+                context
+            );
+            let mut counted_tags = 0;
+            for (index, pubkey) in greeted.iter().enumerate() {
+                let account = banks_client
+                    .get_account(*pubkey)
+                    .await
+                    .expect("get_account")
+                    .expect("greeting account not found");
+                let expected = &model[index];
+                // Greetings never move a greeting account's lamports
+                assert_eq!(account.lamports, before[1 + index], "{}", context);
+                if expected.counter == 0 {
+                    assert_eq!(account.data, vec![0_u8; GREETING_SIZE], "{}", context);
+                } else {
+                    let greeting = GreetingAccount::unpack(&account.data)
+                        .unwrap_or_else(|err| panic!("{}: {}", context, err));
+                    assert_eq!(greeting.counter, expected.counter, "{}", context);
+                    assert_eq!(greeting.txt, expected.txt, "{}", context);
+                    assert!(
+                        greeting.direct_greets + greeting.cpi_greets <= greeting.counter,
+                        "{}",
+                        context
+                    );
+                }
+
+                let counters = banks_client
+                    .get_account(tag_counters[index])
+                    .await
+                    .expect("get_account");
+                match counters {
+                    None => assert!(expected.tags.is_empty(), "{}", context),
+                    Some(counters) => {
+                        assert_eq!(counters.owner, program_id, "{}", context);
+                        let counters = TagCounters::unpack(&counters.data).expect(&context);
+                        assert_eq!(counters.greeting_account, pubkey.to_bytes(), "{}", context);
+                        let counted = counters
+                            .entries
+                            .iter()
+                            .map(|entry| (entry.tag, entry.count))
+                            .collect::<BTreeMap<_, _>>();
+                        assert_eq!(counted, expected.tags, "{}", context);
+                        counted_tags += counted.values().sum::<u64>();
+                    }
+                }
+            }
+            // The tag counters add up to the CountTags that landed, over all accounts
+            let count_tags = model
+                .iter()
+                .flat_map(|greeting| greeting.tags.values())
+                .sum::<u64>();
+            assert_eq!(counted_tags, count_tags, "{}", context);
+        }
+    }
+}
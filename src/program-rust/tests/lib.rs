@@ -1,19 +1,24 @@
-use borsh::BorshDeserialize;
-use helloworld::{process_instruction, GreetingAccount};
+use helloworld::{
+    config::{config_address, Config},
+    fixtures, instruction,
+    migrations::HEADER_SIZE,
+    process_instruction, GreetingAccount,
+};
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction},
-    pubkey::Pubkey,
-    signature::Signer,
+    signature::{keypair_from_seed, Signer},
     transaction::Transaction,
 };
-use std::mem;
+
+// Schema header + Borsh String (u32 length prefix + 12 bytes of text) + u64 counter
+// + calendar (u16 year, 46 byte day bitmap) + u64 epoch of the last update
+const GREETING_SIZE: usize = HEADER_SIZE + 4 + 12 + 8 + 2 + 46 + 8;
 
 #[tokio::test]
 async fn test_helloworld() {
-    let program_id = Pubkey::new_unique();
-    let greeted_pubkey = Pubkey::new_unique();
+    let program_id = fixtures::program_id();
+    let greeted_pubkey = fixtures::key("greeted");
 
     let mut program_test = ProgramTest::new(
         "helloworld", // Run the BPF version with `cargo test-bpf`
@@ -24,73 +29,151 @@ async fn test_helloworld() {
         greeted_pubkey,
         Account {
             lamports: 5,
-            data: vec![0_u8; mem::size_of::<u32>()],
+            data: vec![0_u8; GREETING_SIZE],
             owner: program_id,
             ..Account::default()
         },
     );
     let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-    // Verify account has zero greetings
+    // Verify account has no message yet
     let greeted_account = banks_client
         .get_account(greeted_pubkey)
         .await
         .expect("get_account")
         .expect("greeted_account not found");
-    assert_eq!(
-        GreetingAccount::try_from_slice(&greeted_account.data)
-            .unwrap()
-            .counter,
-        0
-    );
+    assert_eq!(greeted_account.data, vec![0_u8; GREETING_SIZE]);
 
     // Greet once
     let mut transaction = Transaction::new_with_payer(
-        &[Instruction::new_with_bincode(
-            program_id,
-            &[0], // ignored but makes the instruction unique in the slot
-            vec![AccountMeta::new(greeted_pubkey, false)],
+        &[instruction::greet(
+            &program_id,
+            &greeted_pubkey,
+            "Hello1234567",
         )],
         Some(&payer.pubkey()),
     );
     transaction.sign(&[&payer], recent_blockhash);
     banks_client.process_transaction(transaction).await.unwrap();
 
-    // Verify account has one greeting
+    // Verify account holds the first message
     let greeted_account = banks_client
         .get_account(greeted_pubkey)
         .await
         .expect("get_account")
         .expect("greeted_account not found");
-    assert_eq!(
-        GreetingAccount::try_from_slice(&greeted_account.data)
-            .unwrap()
-            .counter,
-        1
-    );
+    let greeting = GreetingAccount::unpack(&greeted_account.data).unwrap();
+    assert_eq!(greeting.txt, "Hello1234567");
+    assert_eq!(greeting.counter, 1);
 
     // Greet again
     let mut transaction = Transaction::new_with_payer(
-        &[Instruction::new_with_bincode(
-            program_id,
-            &[1], // ignored but makes the instruction unique in the slot
-            vec![AccountMeta::new(greeted_pubkey, false)],
+        &[instruction::greet(
+            &program_id,
+            &greeted_pubkey,
+            "World7654321",
         )],
         Some(&payer.pubkey()),
     );
     transaction.sign(&[&payer], recent_blockhash);
     banks_client.process_transaction(transaction).await.unwrap();
 
-    // Verify account has two greetings
+    // Verify account holds the second message
     let greeted_account = banks_client
         .get_account(greeted_pubkey)
         .await
         .expect("get_account")
         .expect("greeted_account not found");
+    let greeting = GreetingAccount::unpack(&greeted_account.data).unwrap();
+    assert_eq!(greeting.txt, "World7654321");
+    assert_eq!(greeting.counter, 2);
+    // The day of the bank's clock is marked
+    assert_ne!(greeting.calendar_year, 0);
+    assert_eq!(greeting.greeted_days.count(), 1);
+}
+
+#[tokio::test]
+async fn test_config() {
+    let program_id = fixtures::program_id();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            instruction::init_config(&program_id, &payer.pubkey(), 10),
+            instruction::set_decay(&program_id, &payer.pubkey(), 20),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let (config_pubkey, _) = config_address(&program_id);
+    let config_account = banks_client
+        .get_account(config_pubkey)
+        .await
+        .expect("get_account")
+        .expect("config account not found");
+    assert_eq!(config_account.owner, program_id);
     assert_eq!(
-        GreetingAccount::try_from_slice(&greeted_account.data)
-            .unwrap()
-            .counter,
-        2
+        Config::unpack(&config_account.data).unwrap(),
+        Config {
+            authority: payer.pubkey().to_bytes(),
+            decay_half_life_epochs: 20,
+            ..Config::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_tick() {
+    let program_id = fixtures::program_id();
+    let greeted_pubkey = fixtures::key("greeted");
+    let crank = keypair_from_seed(&fixtures::seed("crank")).unwrap();
+
+    let mut program_test =
+        ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    program_test.add_account(
+        greeted_pubkey,
+        Account {
+            lamports: 5,
+            data: vec![0_u8; GREETING_SIZE],
+            owner: program_id,
+            ..Account::default()
+        },
     );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            instruction::greet(&program_id, &greeted_pubkey, "Hello1234567"),
+            instruction::init_config(&program_id, &payer.pubkey(), 10),
+            instruction::set_crank_authority(&program_id, &payer.pubkey(), &crank.pubkey()),
+            instruction::tick(&program_id, &crank.pubkey(), &[greeted_pubkey]),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &crank], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let greeted_account = banks_client
+        .get_account(greeted_pubkey)
+        .await
+        .expect("get_account")
+        .expect("greeted_account not found");
+    let greeting = GreetingAccount::unpack(&greeted_account.data).unwrap();
+    assert_eq!(greeting.counter, 1);
+    assert_ne!(greeting.last_update_epoch, 0);
+}
+
+#[tokio::test]
+async fn test_version() {
+    let program_id = fixtures::program_id();
+    let program_test = ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction::version(&program_id)], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
 }
@@ -0,0 +1,197 @@
+// NOTE Rent at the edges. The client creates greeting accounts and funds them to the
+// rent-exempt minimum for their size (see hello_world.ts); the program doesn't check
+// that, and it never grows an account (solana-program 1.6 can't), so a greeting never
+// leaves an exempt account short. The PDAs the program creates itself are funded to
+// the minimum by the payer, and an address holding lamports already, below the minimum
+// or above it, is topped up rather than refused (see create_pda_account()).
+use helloworld::{
+    fixtures::{self, GREETING_SIZE},
+    instruction,
+    migrations::HEADER_SIZE,
+    process_instruction,
+    tags::{self, tag_counters_address, TagCounters, TAG_COUNTERS_SIZE},
+    GreetingAccount,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, rent::Rent, signature::Signer, system_program,
+    transaction::Transaction,
+};
+
+// Header + Borsh String + u64 counter, the size of a schema 2 account and too small
+// for the current layout
+const SCHEMA_2_SIZE: usize = HEADER_SIZE + 4 + 12 + 8;
+
+fn greeting_account(lamports: u64, size: usize) -> Account {
+    Account {
+        lamports,
+        data: vec![0_u8; size],
+        owner: fixtures::program_id(),
+        ..Account::default()
+    }
+}
+
+async fn get_account(context: &mut ProgramTestContext, pubkey: Pubkey) -> Option<Account> {
+    context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .expect("get_account")
+}
+
+async fn greet(context: &mut ProgramTestContext, greeted: &Pubkey, txt: &str) {
+    let (_, blockhash, _) = context.banks_client.get_fees().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::greet(&fixtures::program_id(), greeted, txt)],
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(&[&context.payer], blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_rent_exempt_greeting_account_keeps_its_lamports() {
+    let greeted = fixtures::key("greeted");
+    let minimum = Rent::default().minimum_balance(GREETING_SIZE);
+    // (128 bytes of account overhead + 104 of data) * 3480 lamports a byte-year * 2 years
+    assert_eq!(minimum, 1_614_720);
+    let mut program_test = ProgramTest::new(
+        "helloworld",
+        fixtures::program_id(),
+        processor!(process_instruction),
+    );
+    program_test.add_account(greeted, greeting_account(minimum, GREETING_SIZE));
+    let mut context = program_test.start_with_context().await;
+
+    greet(&mut context, &greeted, "Hello1234567").await;
+    // Epochs later, past when rent would have been collected from a paying account
+    let slot = context
+        .genesis_config()
+        .epoch_schedule
+        .get_first_slot_in_epoch(10);
+    context.warp_to_slot(slot).unwrap();
+    greet(&mut context, &greeted, "World7654321").await;
+
+    let account = get_account(&mut context, greeted).await.unwrap();
+    assert_eq!(account.lamports, minimum);
+    assert_eq!(GreetingAccount::unpack(&account.data).unwrap().counter, 2);
+}
+
+#[tokio::test]
+async fn test_greeting_account_below_rent_exempt_minimum() {
+    // The program leaves funding greeting accounts to the client: one short of the
+    // minimum, or far short, is greeted all the same and pays rent as it goes
+    let minimum = Rent::default().minimum_balance(GREETING_SIZE);
+    for lamports in [minimum - 1, 1] {
+        let greeted = fixtures::key(&format!("greeted-{}", lamports));
+        let mut program_test = ProgramTest::new(
+            "helloworld",
+            fixtures::program_id(),
+            processor!(process_instruction),
+        );
+        program_test.add_account(greeted, greeting_account(lamports, GREETING_SIZE));
+        let mut context = program_test.start_with_context().await;
+
+        greet(&mut context, &greeted, "Hello1234567").await;
+        let account = get_account(&mut context, greeted).await.unwrap();
+        assert!(account.lamports <= lamports);
+        assert!(!Rent::default().is_exempt(account.lamports, account.data.len()));
+        let greeting = GreetingAccount::unpack(&account.data).unwrap();
+        assert_eq!(greeting.txt, "Hello1234567");
+        assert_eq!(greeting.counter, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_greeting_never_grows_an_account() {
+    // An account too small for the current layout is written in the one that fits
+    // (compact here, see compact.rs) rather than grown, which would take more rent than
+    // it was funded for
+    let greeted = fixtures::key("greeted");
+    let minimum = Rent::default().minimum_balance(SCHEMA_2_SIZE);
+    // (128 + 28) * 3480 * 2
+    assert_eq!(minimum, 1_085_760);
+    let mut program_test = ProgramTest::new(
+        "helloworld",
+        fixtures::program_id(),
+        processor!(process_instruction),
+    );
+    program_test.add_account(greeted, greeting_account(minimum, SCHEMA_2_SIZE));
+    let mut context = program_test.start_with_context().await;
+
+    greet(&mut context, &greeted, "Hello1234567").await;
+    greet(&mut context, &greeted, "World7654321").await;
+
+    let account = get_account(&mut context, greeted).await.unwrap();
+    assert_eq!(account.data.len(), SCHEMA_2_SIZE);
+    assert_eq!(account.lamports, minimum);
+    assert!(Rent::default().is_exempt(account.lamports, account.data.len()));
+    assert_eq!(GreetingAccount::unpack(&account.data).unwrap().counter, 2);
+}
+
+#[tokio::test]
+async fn test_count_tag_tops_up_prefunded_tag_counters() {
+    let program_id = fixtures::program_id();
+    let greeted = fixtures::key("greeted");
+    let (tag_counters, _) = tag_counters_address(&program_id, &greeted);
+    let tag = tags::tag("gm").unwrap();
+    let minimum = Rent::default().minimum_balance(TAG_COUNTERS_SIZE);
+
+    // Nothing there yet, then lamports sent to the address ahead of time: far below the
+    // minimum, just below, exactly the minimum and above it
+    for prefunded in [0, 1, minimum - 1, minimum, 2 * minimum] {
+        let mut program_test =
+            ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+        program_test.add_account(
+            greeted,
+            greeting_account(
+                Rent::default().minimum_balance(GREETING_SIZE),
+                GREETING_SIZE,
+            ),
+        );
+        if prefunded > 0 {
+            program_test.add_account(
+                tag_counters,
+                Account {
+                    lamports: prefunded,
+                    owner: system_program::id(),
+                    ..Account::default()
+                },
+            );
+        }
+        let mut context = program_test.start_with_context().await;
+        let payer = context.payer.pubkey();
+        let payer_before = get_account(&mut context, payer).await.unwrap().lamports;
+
+        let (fee_calculator, blockhash, _) = context.banks_client.get_fees().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction::count_tag(&program_id, &greeted, &payer, tag)],
+            Some(&payer),
+        );
+        transaction.sign(&[&context.payer], blockhash);
+        let fee = fee_calculator.calculate_fee(transaction.message());
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_or_else(|err| panic!("prefunded with {}: {}", prefunded, err));
+
+        let account = get_account(&mut context, tag_counters).await.unwrap();
+        assert_eq!(account.owner, program_id, "prefunded with {}", prefunded);
+        assert_eq!(account.data.len(), TAG_COUNTERS_SIZE);
+        assert_eq!(account.lamports, prefunded.max(minimum));
+        assert_eq!(TagCounters::unpack(&account.data).unwrap().count(&tag), 1);
+        // The payer only makes up the difference
+        let payer_after = get_account(&mut context, payer).await.unwrap().lamports;
+        assert_eq!(
+            payer_before - payer_after,
+            fee + minimum.saturating_sub(prefunded),
+            "prefunded with {}",
+            prefunded
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// NOTE The binary's size is what deploying it costs: the program data account holds
+// the whole .so, rent-exempt. This holds the release build to a budget so growth shows
+// up in review. It reads the .so `cargo test-bpf` builds (BPF_OUT_DIR points at it), so
+// it's ignored under a plain `cargo test` and, run without that build, fails rather
+// than passing unchecked: `cargo test-bpf -- --include-ignored` runs it. If a change
+// needs more room, raise SIZE_BUDGET in the same PR and say why, and helloworld's in
+// SIZE_BUDGETS in src/xtask/src/main.rs with it: `cargo xtask check-size` holds every
+// program to one.
+use std::{env, fs, path::Path};
+
+/// Most bytes the program's .so may take
+const SIZE_BUDGET: u64 = 512 * 1024;
+
+#[test]
+#[ignore = "measures the .so of cargo test-bpf, run with -- --include-ignored"]
+fn test_program_size_budget() {
+    let out_dir = env::var("BPF_OUT_DIR").unwrap_or_else(|_| {
+        panic!("No BPF build to measure, run `cargo test-bpf -- --include-ignored`")
+    });
+    let program = Path::new(&out_dir).join("helloworld.so");
+    let size = fs::metadata(&program)
+        .unwrap_or_else(|err| panic!("Can't read {}: {}", program.display(), err))
+        .len();
+    println!("{} is {} bytes of {}", program.display(), size, SIZE_BUDGET);
+    assert!(
+        size <= SIZE_BUDGET,
+        "{} is {} bytes, over the budget of {}",
+        program.display(),
+        size,
+        SIZE_BUDGET
+    );
+}
@@ -0,0 +1,156 @@
+// NOTE A BPF stack frame is 4 KiB, and going past it isn't caught when building: the
+// build only warns, and the program traps with "Access violation in stack frame" when
+// the frame is entered. So this runs the instructions whose processors hold the most,
+// each with its largest input: SetConfig with every field of the patch, which holds the
+// config before and after and the event with both; GreetMany over as many accounts as
+// it takes; Greet and Snapshot, which hold the config, the greeting and the message.
+// Under `cargo test-bpf` they run on the BPF build (BPF_OUT_DIR points at it), where a
+// frame overflow fails the instruction; under a plain `cargo test` they run natively.
+use helloworld::{
+    config::{config_address, Config, ConfigPatch},
+    fixtures::{self, GREETING_SIZE},
+    instruction::{self, MAX_GREET_MANY_ACCOUNTS},
+    migrations::GREETING_DISCRIMINATOR,
+    milestone::MAX_LABEL_LEN,
+    process_instruction, GreetingAccount,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    blockhash: Hash,
+    name: &str,
+    instruction: Instruction,
+) {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[payer], blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_or_else(|err| panic!("{}: {}", name, err));
+}
+
+#[tokio::test]
+async fn test_largest_processors_fit_their_stack_frames() {
+    let program_id = fixtures::program_id();
+    let greeted = (0..MAX_GREET_MANY_ACCOUNTS)
+        .map(|index| fixtures::key(&format!("greeted-{}", index)))
+        .collect::<Vec<Pubkey>>();
+    let mut program_test =
+        ProgramTest::new("helloworld", program_id, processor!(process_instruction));
+    for pubkey in &greeted {
+        program_test.add_account(
+            *pubkey,
+            Account {
+                lamports: Rent::default().minimum_balance(GREETING_SIZE),
+                data: vec![0_u8; GREETING_SIZE],
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+    let (mut banks_client, payer, blockhash) = program_test.start().await;
+    let authority = payer.pubkey();
+
+    process(
+        &mut banks_client,
+        &payer,
+        blockhash,
+        "InitConfig",
+        instruction::init_config(&program_id, &authority, 0),
+    )
+    .await;
+    process(
+        &mut banks_client,
+        &payer,
+        blockhash,
+        "Greet",
+        instruction::greet(&program_id, &greeted[0], "Hello1234567"),
+    )
+    .await;
+    process(
+        &mut banks_client,
+        &payer,
+        blockhash,
+        "GreetMany",
+        instruction::greet_many(&program_id, &greeted, "World7654321"),
+    )
+    .await;
+    process(
+        &mut banks_client,
+        &payer,
+        blockhash,
+        "Snapshot",
+        instruction::snapshot(
+            &program_id,
+            &greeted[0],
+            &authority,
+            &"l".repeat(MAX_LABEL_LEN),
+        ),
+    )
+    .await;
+    for pubkey in &greeted {
+        let account = banks_client
+            .get_account(*pubkey)
+            .await
+            .expect("get_account")
+            .expect("greeting account not found");
+        // The Borsh layout, not the compact one a smaller account falls back to
+        assert!(account.data.starts_with(&GREETING_DISCRIMINATOR));
+        let greeting = GreetingAccount::unpack(&account.data).unwrap();
+        assert_eq!(greeting.txt, "World7654321");
+    }
+
+    // Last, as the fee and the balance weighting it turns on need more accounts than
+    // the greetings above pass
+    let patch = ConfigPatch {
+        // Receipts are refused with decay, so the half-life is set to 0
+        decay_half_life_epochs: Some(0),
+        crank_authority: Some([3; 32]),
+        fee_usd_micros: Some(4),
+        max_price_age_slots: Some(5),
+        max_confidence_bps: Some(6),
+        price_feed: Some([7; 32]),
+        fee_recipient: Some([8; 32]),
+        stake_unit: Some(10),
+        paused: Some(true),
+        max_message_len: Some(13),
+        cooldown_days: Some(14),
+        max_greet_many_accounts: Some(15),
+        claim_root: Some([16; 32]),
+        greeting_receipts: Some(true),
+        balance_mint: Some([18; 32]),
+        balance_unit: Some(19),
+        epoch_bonus_slots: Some(20),
+    };
+    process(
+        &mut banks_client,
+        &payer,
+        blockhash,
+        "SetConfig",
+        instruction::set_config(&program_id, &authority, patch.clone()),
+    )
+    .await;
+    let (config_key, _) = config_address(&program_id);
+    let account = banks_client
+        .get_account(config_key)
+        .await
+        .expect("get_account")
+        .expect("config not found");
+    let mut expected = Config {
+        authority: authority.to_bytes(),
+        ..Config::default()
+    };
+    patch.apply(&mut expected);
+    assert_eq!(Config::unpack(&account.data).unwrap(), expected);
+}
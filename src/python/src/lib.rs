@@ -0,0 +1,1254 @@
+/*
+NOTES:
+- Python bindings so data-science users can decode greeting accounts and build
+instructions without reimplementing the Borsh layouts by hand.
+- Build and install into the active virtualenv with `maturin develop` (or
+`pip install ./src/python`), then:
+    import helloworld_py
+    helloworld_py.decode_greeting_account(account_data)  # {'txt': 'Hello1234567', 'counter': 1, ...}
+- Everything here is a thin wrapper around the program crate (used as a library with
+no-entrypoint), so the layouts always match what's deployed.
+- The program doesn't emit events yet, so there is no event parsing.
+*/
+use borsh::BorshDeserialize;
+use helloworld::{
+    calendar::DAYS_IN_YEAR,
+    challenge::Challenge,
+    claim,
+    compression::GreetingLeaf,
+    config::{Config, ConfigPatch},
+    error::HelloWorldError,
+    escrow::Escrow,
+    governance, instruction,
+    luck::LuckTicket,
+    milestone::{self, GreetingSnapshot},
+    receipt::GreetingReceipt,
+    session::SessionToken,
+    stake::StakePosition,
+    tags::{self, TagCounters},
+    transfer_hook::TransferGreetings,
+    wormhole::ForeignGreeter,
+    GreetingAccount,
+};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyDict},
+    wrap_pyfunction,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::{convert::TryFrom, str::FromStr};
+
+fn parse_pubkey(name: &str, value: &str) -> PyResult<Pubkey> {
+    Pubkey::from_str(value)
+        .map_err(|err| PyValueError::new_err(format!("Invalid {} {}: {:?}", name, value, err)))
+}
+
+fn hash(name: &str, value: &[u8]) -> PyResult<[u8; 32]> {
+    <[u8; 32]>::try_from(value).map_err(|_| {
+        PyValueError::new_err(format!("The {} is 32 bytes, not {}", name, value.len()))
+    })
+}
+
+/// Turn an Instruction into {"program_id": str, "accounts": [...], "data": bytes}
+fn instruction_to_dict(py: Python, instruction: Instruction) -> PyResult<PyObject> {
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|meta| {
+            let account = PyDict::new(py);
+            account.set_item("pubkey", meta.pubkey.to_string())?;
+            account.set_item("is_signer", meta.is_signer)?;
+            account.set_item("is_writable", meta.is_writable)?;
+            Ok(account.to_object(py))
+        })
+        .collect::<PyResult<Vec<PyObject>>>()?;
+
+    let dict = PyDict::new(py);
+    dict.set_item("program_id", instruction.program_id.to_string())?;
+    dict.set_item("accounts", accounts)?;
+    dict.set_item("data", PyBytes::new(py, &instruction.data))?;
+    Ok(dict.to_object(py))
+}
+
+fn item<'a>(dict: &'a PyDict, key: &str) -> PyResult<&'a PyAny> {
+    dict.get_item(key)
+        .ok_or_else(|| PyValueError::new_err(format!("Instruction has no {}", key)))
+}
+
+/// The inverse of instruction_to_dict()
+fn dict_to_instruction(dict: &PyDict) -> PyResult<Instruction> {
+    let accounts = item(dict, "accounts")?
+        .extract::<Vec<&PyDict>>()?
+        .into_iter()
+        .map(|account| {
+            Ok(AccountMeta {
+                pubkey: parse_pubkey("account", item(account, "pubkey")?.extract()?)?,
+                is_signer: item(account, "is_signer")?.extract()?,
+                is_writable: item(account, "is_writable")?.extract()?,
+            })
+        })
+        .collect::<PyResult<Vec<AccountMeta>>>()?;
+    Ok(Instruction {
+        program_id: parse_pubkey("program id", item(dict, "program_id")?.extract()?)?,
+        accounts,
+        data: item(dict, "data")?.extract::<&[u8]>()?.to_vec(),
+    })
+}
+
+/// Decode a greeting account's data into {"txt": str, "counter": int,
+/// "calendar_year": int, "greeted_days": [int], "last_update_epoch": int,
+/// "direct_greets": int, "cpi_greets": int} (days of the year, 0 is January 1st)
+// NOTE Accounts are allocated at a fixed size, so the data can have zeroed bytes after
+// the message. unpack() (rather than try_from_slice()) ignores them, and reads accounts
+// of every schema version.
+#[pyfunction]
+fn decode_greeting_account(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let account = GreetingAccount::unpack(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid greeting account: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("txt", &account.txt)?;
+    dict.set_item("counter", account.counter)?;
+    dict.set_item("calendar_year", account.calendar_year)?;
+    let greeted_days: Vec<u16> = (0..DAYS_IN_YEAR as u16)
+        .filter(|day| account.greeted_days.is_set(*day))
+        .collect();
+    dict.set_item("greeted_days", greeted_days)?;
+    dict.set_item("last_update_epoch", account.last_update_epoch)?;
+    dict.set_item("direct_greets", account.direct_greets)?;
+    dict.set_item("cpi_greets", account.cpi_greets)?;
+    Ok(dict.to_object(py))
+}
+
+/// Build a Greet instruction saving `txt` into `greeted_pubkey`
+#[pyfunction]
+fn build_greet_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+    txt: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    instruction_to_dict(py, instruction::greet(&program_id, &greeted_pubkey, txt))
+}
+
+/// Add the receipt accounts to a Greet instruction (a dict like the builders return)
+/// of `greeted_pubkey`, whose counter reads `counter` before the greeting. `signer`
+/// signs for the receipt and pays its rent.
+#[pyfunction]
+fn add_receipt_accounts(
+    py: Python,
+    instruction: &PyDict,
+    program_id: &str,
+    greeted_pubkey: &str,
+    counter: u64,
+    signer: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    let signer = parse_pubkey("signer", signer)?;
+    instruction_to_dict(
+        py,
+        instruction::add_receipt_accounts(
+            dict_to_instruction(instruction)?,
+            &program_id,
+            &greeted_pubkey,
+            counter,
+            &signer,
+        ),
+    )
+}
+
+/// Add `owner`'s token account of the config's balance mint to a Greet instruction (a
+/// dict like the builders return), for a deployment weighing greetings by balance
+#[pyfunction]
+fn add_balance_accounts(
+    py: Python,
+    instruction: &PyDict,
+    token_account: &str,
+    owner: &str,
+) -> PyResult<PyObject> {
+    let token_account = parse_pubkey("token account", token_account)?;
+    let owner = parse_pubkey("owner", owner)?;
+    instruction_to_dict(
+        py,
+        instruction::add_balance_accounts(
+            dict_to_instruction(instruction)?,
+            &token_account,
+            &owner,
+        ),
+    )
+}
+
+/// Build a Greet instruction for a deployment charging a fee, paid by `payer` to
+/// `fee_recipient` at the price in `price_feed`
+#[pyfunction]
+fn build_greet_with_fee_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+    payer: &str,
+    price_feed: &str,
+    fee_recipient: &str,
+    txt: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    let payer = parse_pubkey("payer", payer)?;
+    let price_feed = parse_pubkey("price feed", price_feed)?;
+    let fee_recipient = parse_pubkey("fee recipient", fee_recipient)?;
+    instruction_to_dict(
+        py,
+        instruction::greet_with_fee(
+            &program_id,
+            &greeted_pubkey,
+            &payer,
+            &price_feed,
+            &fee_recipient,
+            txt,
+        ),
+    )
+}
+
+/// Decode the config account's data into {"authority": str,
+/// "decay_half_life_epochs": int, "crank_authority": str or None,
+/// "fee_usd_micros": int, "max_price_age_slots": int, "max_confidence_bps": int,
+/// "price_feed": str, "fee_recipient": str, "stake_mint": str, "stake_unit": int,
+/// "greeting_log": str or None, "paused": bool, "max_message_len": int,
+/// "cooldown_days": int, "max_greet_many_accounts": int, "claim_root": bytes or None,
+/// "greeting_receipts": bool, "balance_mint": str or None, "balance_unit": int,
+/// "epoch_bonus_slots": int}
+#[pyfunction]
+fn decode_config_account(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let config = Config::unpack(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid config account: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("authority", Pubkey::new(&config.authority).to_string())?;
+    dict.set_item("decay_half_life_epochs", config.decay_half_life_epochs)?;
+    let crank_authority = Some(config.crank_authority)
+        .filter(|key| *key != [0; 32])
+        .map(|key| Pubkey::new(&key).to_string());
+    dict.set_item("crank_authority", crank_authority)?;
+    dict.set_item("fee_usd_micros", config.fee_usd_micros)?;
+    dict.set_item("max_price_age_slots", config.max_price_age_slots)?;
+    dict.set_item("max_confidence_bps", config.max_confidence_bps)?;
+    dict.set_item("price_feed", Pubkey::new(&config.price_feed).to_string())?;
+    dict.set_item(
+        "fee_recipient",
+        Pubkey::new(&config.fee_recipient).to_string(),
+    )?;
+    dict.set_item("stake_mint", Pubkey::new(&config.stake_mint).to_string())?;
+    dict.set_item("stake_unit", config.stake_unit)?;
+    let greeting_log = Some(config.greeting_log)
+        .filter(|key| *key != [0; 32])
+        .map(|key| Pubkey::new(&key).to_string());
+    dict.set_item("greeting_log", greeting_log)?;
+    dict.set_item("paused", config.paused)?;
+    dict.set_item("max_message_len", config.max_message_len)?;
+    dict.set_item("cooldown_days", config.cooldown_days)?;
+    dict.set_item("max_greet_many_accounts", config.max_greet_many_accounts)?;
+    let claim_root = Some(config.claim_root)
+        .filter(|root| *root != [0; 32])
+        .map(|root| PyBytes::new(py, &root).to_object(py));
+    dict.set_item("claim_root", claim_root)?;
+    dict.set_item("greeting_receipts", config.greeting_receipts)?;
+    let balance_mint = Some(config.balance_mint)
+        .filter(|mint| *mint != [0; 32])
+        .map(|mint| Pubkey::new(&mint).to_string());
+    dict.set_item("balance_mint", balance_mint)?;
+    dict.set_item("balance_unit", config.balance_unit)?;
+    dict.set_item("epoch_bonus_slots", config.epoch_bonus_slots)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a stake position's data into {"owner": str, "mint": str, "amount": int,
+/// "last_deposit_slot": int}
+#[pyfunction]
+fn decode_stake_position(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let position = StakePosition::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid stake position: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("owner", Pubkey::new(&position.owner).to_string())?;
+    dict.set_item("mint", Pubkey::new(&position.mint).to_string())?;
+    dict.set_item("amount", position.amount)?;
+    dict.set_item("last_deposit_slot", position.last_deposit_slot)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a lucky-greeter ticket's data into {"greeter": str, "randomness": str or
+/// None, "seed_slot": int, "counter": int, "last_update_epoch": int}
+#[pyfunction]
+fn decode_luck_ticket(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let ticket = LuckTicket::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid ticket: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("greeter", Pubkey::new(&ticket.greeter).to_string())?;
+    let randomness = Some(ticket.randomness)
+        .filter(|_| ticket.is_pending())
+        .map(|key| Pubkey::new(&key).to_string());
+    dict.set_item("randomness", randomness)?;
+    dict.set_item("seed_slot", ticket.seed_slot)?;
+    dict.set_item("counter", ticket.counter)?;
+    dict.set_item("last_update_epoch", ticket.last_update_epoch)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a challenge's data into {"challenger": str, "challengee": str,
+/// "greeting_account": str, "target_counter": int, "deadline_slot": int, "amount": int}
+#[pyfunction]
+fn decode_challenge(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let challenge = Challenge::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid challenge: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("challenger", Pubkey::new(&challenge.challenger).to_string())?;
+    dict.set_item("challengee", Pubkey::new(&challenge.challengee).to_string())?;
+    dict.set_item(
+        "greeting_account",
+        Pubkey::new(&challenge.greeting_account).to_string(),
+    )?;
+    dict.set_item("target_counter", challenge.target_counter)?;
+    dict.set_item("deadline_slot", challenge.deadline_slot)?;
+    dict.set_item("amount", challenge.amount)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a foreign greeter's data into {"chain": int, "sender": bytes, "counter": int,
+/// "last_sequence": int}
+#[pyfunction]
+fn decode_foreign_greeter(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let greeter = ForeignGreeter::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid foreign greeter: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("chain", greeter.chain)?;
+    dict.set_item("sender", PyBytes::new(py, &greeter.sender))?;
+    dict.set_item("counter", greeter.counter)?;
+    dict.set_item("last_sequence", greeter.last_sequence)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a leaf of the greeting log, the data of a spl-noop instruction of a Greet,
+/// into {"greeting_account": str, "counter": int, "slot": int, "txt": str,
+/// "hash": bytes} (the hash being what's stored in the tree)
+#[pyfunction]
+fn decode_greeting_leaf(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let leaf = GreetingLeaf::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid greeting leaf: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item(
+        "greeting_account",
+        Pubkey::new(&leaf.greeting_account).to_string(),
+    )?;
+    dict.set_item("counter", leaf.counter)?;
+    dict.set_item("slot", leaf.slot)?;
+    dict.set_item("txt", &leaf.txt)?;
+    dict.set_item("hash", PyBytes::new(py, &leaf.hash()))?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a session token into {"authority": str, "session_key": str,
+/// "valid_until": int}
+#[pyfunction]
+fn decode_session_token(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let session = SessionToken::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid session token: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("authority", Pubkey::new(&session.authority).to_string())?;
+    dict.set_item("session_key", Pubkey::new(&session.session_key).to_string())?;
+    dict.set_item("valid_until", session.valid_until)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a greeting receipt into {"signer": str, "slot": int, "unix_timestamp": int}
+#[pyfunction]
+fn decode_greeting_receipt(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let receipt = GreetingReceipt::unpack(data)
+        .ok_or_else(|| PyValueError::new_err("Invalid greeting receipt"))?;
+    let dict = PyDict::new(py);
+    dict.set_item("signer", Pubkey::new(&receipt.signer).to_string())?;
+    dict.set_item("slot", receipt.slot)?;
+    dict.set_item("unix_timestamp", receipt.unix_timestamp)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a snapshot into {"greeting_account": str, "recorder": str, "counter": int,
+/// "slot": int, "unix_timestamp": int, "label": str}
+#[pyfunction]
+fn decode_greeting_snapshot(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let snapshot = GreetingSnapshot::unpack(data)
+        .ok_or_else(|| PyValueError::new_err("Invalid greeting snapshot"))?;
+    let dict = PyDict::new(py);
+    dict.set_item(
+        "greeting_account",
+        Pubkey::new(&snapshot.greeting_account).to_string(),
+    )?;
+    dict.set_item("recorder", Pubkey::new(&snapshot.recorder).to_string())?;
+    dict.set_item("counter", snapshot.counter)?;
+    dict.set_item("slot", snapshot.slot)?;
+    dict.set_item("unix_timestamp", snapshot.unix_timestamp)?;
+    dict.set_item("label", snapshot.label)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a greeting account's tag counters into {"greeting_account": str, "counts":
+/// {tag name: int}}
+#[pyfunction]
+fn decode_tag_counters(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let counters =
+        TagCounters::unpack(data).ok_or_else(|| PyValueError::new_err("Invalid tag counters"))?;
+    let counts = PyDict::new(py);
+    for entry in &counters.entries {
+        counts.set_item(tags::tag_name(&entry.tag), entry.count)?;
+    }
+    let dict = PyDict::new(py);
+    dict.set_item(
+        "greeting_account",
+        Pubkey::new(&counters.greeting_account).to_string(),
+    )?;
+    dict.set_item("counts", counts)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode a mint's transfer greetings, kept by the transfer-hook program, into
+/// {"mint": str, "counter": int, "volume": int}
+#[pyfunction]
+fn decode_transfer_greetings(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let greetings = TransferGreetings::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid transfer greetings: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("mint", Pubkey::new(&greetings.mint).to_string())?;
+    dict.set_item("counter", greetings.counter)?;
+    dict.set_item("volume", greetings.volume)?;
+    Ok(dict.to_object(py))
+}
+
+/// Decode an escrow program's open offer into {"initializer": str, "vault": str,
+/// "receive_account": str, "offered_amount": int, "expected_amount": int}
+#[pyfunction]
+fn decode_escrow(py: Python, data: &[u8]) -> PyResult<PyObject> {
+    let escrow = Escrow::try_from_slice(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid escrow: {}", err)))?;
+    let dict = PyDict::new(py);
+    dict.set_item("initializer", Pubkey::new(&escrow.initializer).to_string())?;
+    dict.set_item("vault", Pubkey::new(&escrow.vault).to_string())?;
+    dict.set_item(
+        "receive_account",
+        Pubkey::new(&escrow.receive_account).to_string(),
+    )?;
+    dict.set_item("offered_amount", escrow.offered_amount)?;
+    dict.set_item("expected_amount", escrow.expected_amount)?;
+    Ok(dict.to_object(py))
+}
+
+/// Effective counter of a greeting account's data at `epoch`, under a decay half-life
+/// of `half_life_epochs`
+#[pyfunction]
+fn effective_counter(data: &[u8], half_life_epochs: u64, epoch: u64) -> PyResult<u64> {
+    let account = GreetingAccount::unpack(data)
+        .map_err(|err| PyValueError::new_err(format!("Invalid greeting account: {}", err)))?;
+    Ok(account.effective_counter(half_life_epochs, epoch))
+}
+
+/// Build a Version instruction
+#[pyfunction]
+fn build_version_instruction(py: Python, program_id: &str) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    instruction_to_dict(py, instruction::version(&program_id))
+}
+
+/// Build an ExportState instruction logging `greeted_pubkey`'s data
+#[pyfunction]
+fn build_export_state_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    instruction_to_dict(py, instruction::export_state(&program_id, &greeted_pubkey))
+}
+
+/// Build an ImportState instruction copying `source_pubkey`'s greeting into
+/// `greeted_pubkey`, signed by the config authority
+#[pyfunction]
+fn build_import_state_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+    source_pubkey: &str,
+    authority: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    let source_pubkey = parse_pubkey("source pubkey", source_pubkey)?;
+    let authority = parse_pubkey("authority", authority)?;
+    instruction_to_dict(
+        py,
+        instruction::import_state(&program_id, &greeted_pubkey, &source_pubkey, &authority),
+    )
+}
+
+/// Build an InitConfig instruction making `authority` the config authority
+#[pyfunction]
+fn build_init_config_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    decay_half_life_epochs: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    instruction_to_dict(
+        py,
+        instruction::init_config(&program_id, &authority, decay_half_life_epochs),
+    )
+}
+
+/// Build a SetDecay instruction
+#[pyfunction]
+fn build_set_decay_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    half_life_epochs: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    instruction_to_dict(
+        py,
+        instruction::set_decay(&program_id, &authority, half_life_epochs),
+    )
+}
+
+/// Build a SetConfig instruction changing the fields in `patch`, a dict keyed by
+/// ConfigPatch's field names (keys as base58 strings, claim_root as 32 bytes). Fields
+/// left out keep their value.
+#[pyfunction]
+fn build_set_config_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    patch: &PyDict,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    fn field<'a, T: FromPyObject<'a>>(patch: &'a PyDict, name: &str) -> PyResult<Option<T>> {
+        patch
+            .get_item(name)
+            .map(|value| value.extract())
+            .transpose()
+    }
+    let key = |name: &str| -> PyResult<Option<[u8; 32]>> {
+        field::<&str>(patch, name)?
+            .map(|value| Ok(parse_pubkey(name, value)?.to_bytes()))
+            .transpose()
+    };
+    let patch = ConfigPatch {
+        decay_half_life_epochs: field(patch, "decay_half_life_epochs")?,
+        crank_authority: key("crank_authority")?,
+        fee_usd_micros: field(patch, "fee_usd_micros")?,
+        max_price_age_slots: field(patch, "max_price_age_slots")?,
+        max_confidence_bps: field(patch, "max_confidence_bps")?,
+        price_feed: key("price_feed")?,
+        fee_recipient: key("fee_recipient")?,
+        stake_unit: field(patch, "stake_unit")?,
+        paused: field(patch, "paused")?,
+        max_message_len: field(patch, "max_message_len")?,
+        cooldown_days: field(patch, "cooldown_days")?,
+        max_greet_many_accounts: field(patch, "max_greet_many_accounts")?,
+        claim_root: field::<&[u8]>(patch, "claim_root")?
+            .map(|root| hash("claim root", root))
+            .transpose()?,
+        greeting_receipts: field(patch, "greeting_receipts")?,
+        balance_mint: key("balance_mint")?,
+        balance_unit: field(patch, "balance_unit")?,
+        epoch_bonus_slots: field(patch, "epoch_bonus_slots")?,
+    };
+    patch
+        .validate()
+        .map_err(|name| PyValueError::new_err(format!("{} is out of range", name)))?;
+    instruction_to_dict(py, instruction::set_config(&program_id, &authority, patch))
+}
+
+/// Build a SetCrankAuthority instruction naming `crank_authority`
+#[pyfunction]
+fn build_set_crank_authority_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    crank_authority: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let crank_authority = parse_pubkey("crank authority", crank_authority)?;
+    instruction_to_dict(
+        py,
+        instruction::set_crank_authority(&program_id, &authority, &crank_authority),
+    )
+}
+
+/// Build a SetAuthority instruction handing the config to `new_authority`
+#[pyfunction]
+fn build_set_authority_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    new_authority: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let new_authority = parse_pubkey("new authority", new_authority)?;
+    instruction_to_dict(
+        py,
+        instruction::set_authority(&program_id, &authority, &new_authority),
+    )
+}
+
+/// Build an spl-governance InsertTransaction instruction adding `instruction` (a dict
+/// like the builders return) to a draft proposal as its `index`th transaction
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn build_insert_transaction_instruction(
+    py: Python,
+    governance_program_id: &str,
+    governance: &str,
+    proposal: &str,
+    token_owner_record: &str,
+    governance_authority: &str,
+    payer: &str,
+    index: u16,
+    instruction: &PyDict,
+) -> PyResult<PyObject> {
+    let proposal = governance::Proposal {
+        governance_program_id: parse_pubkey("governance program id", governance_program_id)?,
+        governance: parse_pubkey("governance", governance)?,
+        proposal: parse_pubkey("proposal", proposal)?,
+        token_owner_record: parse_pubkey("token owner record", token_owner_record)?,
+        governance_authority: parse_pubkey("governance authority", governance_authority)?,
+    };
+    let payer = parse_pubkey("payer", payer)?;
+    let instruction = dict_to_instruction(instruction)?;
+    instruction_to_dict(
+        py,
+        governance::insert_transaction(&proposal, &payer, 0, index, &[instruction]),
+    )
+}
+
+/// Build a Tick instruction maintaining the greeting accounts `greeted_pubkeys`
+#[pyfunction]
+fn build_tick_instruction(
+    py: Python,
+    program_id: &str,
+    crank_authority: &str,
+    greeted_pubkeys: Vec<String>,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let crank_authority = parse_pubkey("crank authority", crank_authority)?;
+    let greeted_pubkeys = greeted_pubkeys
+        .iter()
+        .map(|pubkey| parse_pubkey("greeted pubkey", pubkey))
+        .collect::<PyResult<Vec<Pubkey>>>()?;
+    instruction_to_dict(
+        py,
+        instruction::tick(&program_id, &crank_authority, &greeted_pubkeys),
+    )
+}
+
+/// Build a SetFee instruction charging `fee_usd_micros` millionths of a dollar per
+/// Greet
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn build_set_fee_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: &str,
+    fee_recipient: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let price_feed = parse_pubkey("price feed", price_feed)?;
+    let fee_recipient = parse_pubkey("fee recipient", fee_recipient)?;
+    instruction_to_dict(
+        py,
+        instruction::set_fee(
+            &program_id,
+            &authority,
+            fee_usd_micros,
+            max_price_age_slots,
+            max_confidence_bps,
+            &price_feed,
+            &fee_recipient,
+        ),
+    )
+}
+
+/// Build a CommitLuck instruction entering the latest greeting of `greeted_pubkey` in
+/// the draw with the Switchboard randomness account `randomness`
+#[pyfunction]
+fn build_commit_luck_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+    randomness: &str,
+    greeter: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    let randomness = parse_pubkey("randomness", randomness)?;
+    let greeter = parse_pubkey("greeter", greeter)?;
+    instruction_to_dict(
+        py,
+        instruction::commit_luck(&program_id, &greeted_pubkey, &randomness, &greeter),
+    )
+}
+
+/// Build a RevealLuck instruction settling the draw of `greeted_pubkey`
+#[pyfunction]
+fn build_reveal_luck_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+    randomness: &str,
+    greeter: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    let randomness = parse_pubkey("randomness", randomness)?;
+    let greeter = parse_pubkey("greeter", greeter)?;
+    instruction_to_dict(
+        py,
+        instruction::reveal_luck(&program_id, &greeted_pubkey, &randomness, &greeter),
+    )
+}
+
+/// Build a SetStake instruction weighting greetings by stake of `stake_mint`, 1 more
+/// for every `stake_unit` base units staked
+#[pyfunction]
+fn build_set_stake_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    stake_mint: &str,
+    stake_unit: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let stake_mint = parse_pubkey("stake mint", stake_mint)?;
+    instruction_to_dict(
+        py,
+        instruction::set_stake(&program_id, &authority, &stake_mint, stake_unit),
+    )
+}
+
+/// Build a Deposit instruction staking `amount` tokens from the token account
+/// `source` into the stake vault `vault`
+#[pyfunction]
+fn build_deposit_instruction(
+    py: Python,
+    program_id: &str,
+    staker: &str,
+    source: &str,
+    vault: &str,
+    amount: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let staker = parse_pubkey("staker", staker)?;
+    let source = parse_pubkey("source", source)?;
+    let vault = parse_pubkey("vault", vault)?;
+    instruction_to_dict(
+        py,
+        instruction::deposit(&program_id, &staker, &source, &vault, amount),
+    )
+}
+
+/// Build a WithdrawStake instruction paying `amount` staked tokens from the stake
+/// vault `vault` to the token account `destination`
+#[pyfunction]
+fn build_withdraw_stake_instruction(
+    py: Python,
+    program_id: &str,
+    staker: &str,
+    vault: &str,
+    destination: &str,
+    amount: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let staker = parse_pubkey("staker", staker)?;
+    let vault = parse_pubkey("vault", vault)?;
+    let destination = parse_pubkey("destination", destination)?;
+    instruction_to_dict(
+        py,
+        instruction::withdraw_stake(&program_id, &staker, &vault, &destination, amount),
+    )
+}
+
+/// Build a CreateChallenge instruction escrowing `amount` lamports for `challengee` to
+/// greet `greeted_pubkey` `greet_count` more times before `deadline_slot`
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn build_create_challenge_instruction(
+    py: Python,
+    program_id: &str,
+    challenger: &str,
+    challengee: &str,
+    greeted_pubkey: &str,
+    greet_count: u64,
+    deadline_slot: u64,
+    amount: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let challenger = parse_pubkey("challenger", challenger)?;
+    let challengee = parse_pubkey("challengee", challengee)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    instruction_to_dict(
+        py,
+        instruction::create_challenge(
+            &program_id,
+            &challenger,
+            &challengee,
+            &greeted_pubkey,
+            greet_count,
+            deadline_slot,
+            amount,
+        ),
+    )
+}
+
+/// Build a ClaimChallenge instruction paying `challenger`'s challenge out to
+/// `challengee`
+#[pyfunction]
+fn build_claim_challenge_instruction(
+    py: Python,
+    program_id: &str,
+    challenger: &str,
+    challengee: &str,
+    greeted_pubkey: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let challenger = parse_pubkey("challenger", challenger)?;
+    let challengee = parse_pubkey("challengee", challengee)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    instruction_to_dict(
+        py,
+        instruction::claim_challenge(&program_id, &challenger, &challengee, &greeted_pubkey),
+    )
+}
+
+/// Build a RefundChallenge instruction giving `challenger` back their expired
+/// challenge to `challengee`
+#[pyfunction]
+fn build_refund_challenge_instruction(
+    py: Python,
+    program_id: &str,
+    challenger: &str,
+    challengee: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let challenger = parse_pubkey("challenger", challenger)?;
+    let challengee = parse_pubkey("challengee", challengee)?;
+    instruction_to_dict(
+        py,
+        instruction::refund_challenge(&program_id, &challenger, &challengee),
+    )
+}
+
+/// Build a ReceiveCrossChainGreet instruction counting the greeting in `posted_vaa`, a
+/// VAA whose emitter is the 32-byte universal address `sender` on Wormhole chain
+/// `chain`
+#[pyfunction]
+fn build_receive_cross_chain_greet_instruction(
+    py: Python,
+    program_id: &str,
+    posted_vaa: &str,
+    chain: u16,
+    sender: &[u8],
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let posted_vaa = parse_pubkey("posted VAA", posted_vaa)?;
+    let sender = <[u8; 32]>::try_from(sender).map_err(|_| {
+        PyValueError::new_err(format!("The sender is 32 bytes, not {}", sender.len()))
+    })?;
+    instruction_to_dict(
+        py,
+        instruction::receive_cross_chain_greet(&program_id, &posted_vaa, chain, &sender),
+    )
+}
+
+/// Build an InitGreetingLog instruction starting the greeting log in `tree`, already
+/// allocated to the spl-account-compression program
+#[pyfunction]
+fn build_init_greeting_log_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    tree: &str,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let tree = parse_pubkey("tree", tree)?;
+    instruction_to_dict(
+        py,
+        instruction::init_greeting_log(&program_id, &authority, &tree, max_depth, max_buffer_size),
+    )
+}
+
+/// Build a CreateSession instruction letting `session_key` greet for `authority` for
+/// `duration_seconds`, topping it up with `top_up_lamports`
+#[pyfunction]
+fn build_create_session_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    session_key: &str,
+    duration_seconds: u64,
+    top_up_lamports: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let session_key = parse_pubkey("session key", session_key)?;
+    instruction_to_dict(
+        py,
+        instruction::create_session(
+            &program_id,
+            &authority,
+            &session_key,
+            duration_seconds,
+            top_up_lamports,
+        ),
+    )
+}
+
+/// Build a RevokeSession instruction ending `authority`'s session for `session_key`
+#[pyfunction]
+fn build_revoke_session_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    session_key: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let session_key = parse_pubkey("session key", session_key)?;
+    instruction_to_dict(
+        py,
+        instruction::revoke_session(&program_id, &authority, &session_key),
+    )
+}
+
+/// Build an Increment instruction counting a greeting of `greeted_pubkey` without
+/// changing its message
+#[pyfunction]
+fn build_increment_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkey: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkey = parse_pubkey("greeted pubkey", greeted_pubkey)?;
+    instruction_to_dict(py, instruction::increment(&program_id, &greeted_pubkey))
+}
+
+/// Build a GreetMany instruction saving `txt` into every account of `greeted_pubkeys`
+#[pyfunction]
+fn build_greet_many_instruction(
+    py: Python,
+    program_id: &str,
+    greeted_pubkeys: Vec<String>,
+    txt: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeted_pubkeys = greeted_pubkeys
+        .iter()
+        .map(|pubkey| parse_pubkey("greeted pubkey", pubkey))
+        .collect::<PyResult<Vec<Pubkey>>>()?;
+    instruction_to_dict(
+        py,
+        instruction::greet_many(&program_id, &greeted_pubkeys, txt),
+    )
+}
+
+/// Build a GreetFor instruction saving `txt` into `target`'s greeting account, paid for
+/// by `payer`
+#[pyfunction]
+fn build_greet_for_instruction(
+    py: Python,
+    program_id: &str,
+    payer: &str,
+    target: &str,
+    txt: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let payer = parse_pubkey("payer", payer)?;
+    let target = parse_pubkey("target", target)?;
+    instruction_to_dict(
+        py,
+        instruction::greet_for(&program_id, &payer, &target, txt),
+    )
+}
+
+/// Build an InitCounterSnapshot instruction initializing the client-allocated account
+/// `snapshot` as a counter snapshot of `depth` levels
+#[pyfunction]
+fn build_init_counter_snapshot_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    snapshot: &str,
+    depth: u8,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let snapshot = parse_pubkey("snapshot", snapshot)?;
+    instruction_to_dict(
+        py,
+        instruction::init_counter_snapshot(&program_id, &authority, &snapshot, depth),
+    )
+}
+
+/// Build a SnapshotCounters instruction writing the counters of `greeted`, pairs of a
+/// greeting account and its leaf index, into `snapshot`
+#[pyfunction]
+fn build_snapshot_counters_instruction(
+    py: Python,
+    program_id: &str,
+    crank_authority: &str,
+    snapshot: &str,
+    greeted: Vec<(String, u32)>,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let crank_authority = parse_pubkey("crank authority", crank_authority)?;
+    let snapshot = parse_pubkey("snapshot", snapshot)?;
+    let greeted = greeted
+        .iter()
+        .map(|(pubkey, index)| Ok((parse_pubkey("greeted pubkey", pubkey)?, *index)))
+        .collect::<PyResult<Vec<(Pubkey, u32)>>>()?;
+    instruction_to_dict(
+        py,
+        instruction::snapshot_counters(&program_id, &crank_authority, &snapshot, &greeted),
+    )
+}
+
+/// The claim root of the list `leaves` of (claimant, lamports), leaf i at index i
+#[pyfunction]
+fn claim_root(py: Python, leaves: Vec<(String, u64)>) -> PyResult<PyObject> {
+    let leaves = claim_leaves(leaves)?;
+    Ok(PyBytes::new(py, &claim::claim_root(&leaves)).to_object(py))
+}
+
+/// The proof of leaf `index` of the list `leaves`, a list of 32-byte siblings
+#[pyfunction]
+fn claim_proof(py: Python, leaves: Vec<(String, u64)>, index: u32) -> PyResult<PyObject> {
+    let leaves = claim_leaves(leaves)?;
+    let proof = claim::claim_proof(&leaves, index).ok_or_else(|| {
+        PyValueError::new_err(format!("No leaf {} in a list of {}", index, leaves.len()))
+    })?;
+    let proof: Vec<_> = proof
+        .iter()
+        .map(|sibling| PyBytes::new(py, sibling).to_object(py))
+        .collect();
+    Ok(proof.to_object(py))
+}
+
+fn claim_leaves(leaves: Vec<(String, u64)>) -> PyResult<Vec<([u8; 32], u64)>> {
+    leaves
+        .iter()
+        .map(|(claimant, amount)| Ok((parse_pubkey("claimant", claimant)?.to_bytes(), *amount)))
+        .collect()
+}
+
+/// Build a Claim instruction paying `claimant` leaf `index` of the list with `root`,
+/// proven by `proof`, the 32-byte siblings from the leaf up
+#[pyfunction]
+fn build_claim_instruction(
+    py: Python,
+    program_id: &str,
+    claimant: &str,
+    root: &[u8],
+    index: u32,
+    amount: u64,
+    proof: Vec<&[u8]>,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let claimant = parse_pubkey("claimant", claimant)?;
+    let root = hash("claim root", root)?;
+    let proof = proof
+        .iter()
+        .map(|sibling| hash("proof sibling", sibling))
+        .collect::<PyResult<Vec<_>>>()?;
+    instruction_to_dict(
+        py,
+        instruction::claim(&program_id, &claimant, &root, index, amount, proof),
+    )
+}
+
+/// Build a Snapshot instruction recording `greeting_account`'s counter under `label`,
+/// at `recorder`'s expense
+#[pyfunction]
+fn build_snapshot_instruction(
+    py: Python,
+    program_id: &str,
+    greeting_account: &str,
+    recorder: &str,
+    label: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeting_account = parse_pubkey("greeting account", greeting_account)?;
+    let recorder = parse_pubkey("recorder", recorder)?;
+    if !milestone::is_valid_label(label) {
+        return Err(PyValueError::new_err(format!(
+            "A label is 1 to {} bytes, not {}",
+            milestone::MAX_LABEL_LEN,
+            label.len()
+        )));
+    }
+    instruction_to_dict(
+        py,
+        instruction::snapshot(&program_id, &greeting_account, &recorder, label),
+    )
+}
+
+/// Build a CountTag instruction counting a greeting of `greeting_account` under the tag
+/// named `tag`, `payer` paying for the tag counters if they're new
+#[pyfunction]
+fn build_count_tag_instruction(
+    py: Python,
+    program_id: &str,
+    greeting_account: &str,
+    payer: &str,
+    tag: &str,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let greeting_account = parse_pubkey("greeting account", greeting_account)?;
+    let payer = parse_pubkey("payer", payer)?;
+    let tag = tags::tag(tag).ok_or_else(|| {
+        PyValueError::new_err(format!("A tag is 1 to 8 bytes, not {}", tag.len()))
+    })?;
+    instruction_to_dict(
+        py,
+        instruction::count_tag(&program_id, &greeting_account, &payer, tag),
+    )
+}
+
+/// Build a CheckRecency instruction failing unless `slot_hash` (32 bytes) is the hash of
+/// `slot` in the SlotHashes sysvar, at most `max_age_slots` old
+#[pyfunction]
+fn build_check_recency_instruction(
+    py: Python,
+    program_id: &str,
+    slot: u64,
+    slot_hash: &[u8],
+    max_age_slots: u64,
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let slot_hash = hash("slot hash", slot_hash)?;
+    instruction_to_dict(
+        py,
+        instruction::check_recency(&program_id, slot, slot_hash, max_age_slots),
+    )
+}
+
+/// Build a RegisterForeignGreeter instruction letting the emitter at the 32-byte
+/// universal address `sender` on Wormhole chain `chain` greet, signed by the config
+/// authority
+#[pyfunction]
+fn build_register_foreign_greeter_instruction(
+    py: Python,
+    program_id: &str,
+    authority: &str,
+    chain: u16,
+    sender: &[u8],
+) -> PyResult<PyObject> {
+    let program_id = parse_pubkey("program id", program_id)?;
+    let authority = parse_pubkey("authority", authority)?;
+    let sender = <[u8; 32]>::try_from(sender).map_err(|_| {
+        PyValueError::new_err(format!("The sender is 32 bytes, not {}", sender.len()))
+    })?;
+    instruction_to_dict(
+        py,
+        instruction::register_foreign_greeter(&program_id, &authority, chain, &sender),
+    )
+}
+
+/// Human-readable message for a `Custom(code)` program error, or None
+#[pyfunction]
+fn error_message(code: u32) -> Option<&'static str> {
+    HelloWorldError::from_code(code).map(HelloWorldError::message)
+}
+
+#[pymodule]
+fn helloworld_py(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("PROGRAM_ID", helloworld::id().to_string())?;
+    m.add_function(wrap_pyfunction!(decode_greeting_account, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_config_account, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_luck_ticket, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_stake_position, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_challenge, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_foreign_greeter, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_greeting_leaf, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_transfer_greetings, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_session_token, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_escrow, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_greeting_receipt, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_greeting_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_tag_counters, m)?)?;
+    m.add_function(wrap_pyfunction!(effective_counter, m)?)?;
+    m.add_function(wrap_pyfunction!(build_greet_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_greet_with_fee_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(add_receipt_accounts, m)?)?;
+    m.add_function(wrap_pyfunction!(add_balance_accounts, m)?)?;
+    m.add_function(wrap_pyfunction!(build_version_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_export_state_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_import_state_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_init_config_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_decay_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_config_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_crank_authority_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_tick_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_fee_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_authority_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_insert_transaction_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_commit_luck_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_reveal_luck_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_set_stake_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_deposit_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_withdraw_stake_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_create_challenge_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_claim_challenge_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_refund_challenge_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        build_receive_cross_chain_greet_instruction,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(build_init_greeting_log_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_create_session_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_revoke_session_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_increment_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_greet_many_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_greet_for_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        build_init_counter_snapshot_instruction,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(build_snapshot_counters_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(claim_root, m)?)?;
+    m.add_function(wrap_pyfunction!(claim_proof, m)?)?;
+    m.add_function(wrap_pyfunction!(build_claim_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_snapshot_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_count_tag_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(build_check_recency_instruction, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        build_register_foreign_greeter_instruction,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(error_message, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,489 @@
+/*
+NOTES:
+- Stamps out a new instruction so it starts out wired the way every other one is:
+`cargo run -p helloworld-scaffold -- <InstructionName> [--error <ErrorName>]...`
+- It adds, in src/program-rust/src:
+  - the HelloWorldInstruction variant, its tag constant and INSTRUCTION_ACCOUNTS
+  entry, a builder and its assert in test_builders_match_instruction_accounts
+  (instruction.rs)
+  - a module of its own with the processor and a test (<instruction_name>.rs), and
+  its `pub mod` and dispatch arm (lib.rs)
+  - each --error as a HelloWorldError variant with the next code, with its
+  ERROR_MESSAGES entry and from_code arm (error.rs)
+  - the variant to the schema's tag order test (src/schema/src/main.rs)
+- The stamped instruction takes no arguments and one signer, the authority; the
+TODOs mark what to fill in. Run with --dry-run to list the files it would change.
+- It edits the files as text, after the last entry of each list, so it stops with
+an error rather than guessing if one of them no longer looks the way it expects.
+*/
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+const USAGE: &str =
+    "Usage: scaffold <InstructionName> [--error <ErrorName>]... [--root <DIR>] [--dry-run]
+
+Options:
+  --error <ErrorName>  Also add a HelloWorldError variant (repeatable)
+  --root <DIR>         Repository to edit (defaults to this one)
+  --dry-run            List the files that would change without writing them";
+
+const VARIANT: &str = "
+    /// TODO Describe what __NAME__ does
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The authority
+    __NAME__,";
+
+const TAG: &str = "
+    pub const __UPPER__: usize = __TAG__;";
+
+const ACCOUNTS: &str = "
+    // __NAME__
+    &[AccountDescription {
+        name: \"authority\",
+        writable: false,
+        signer: true,
+        optional: false,
+        desc: \"The authority\",
+    }],";
+
+const BUILDER: &str = "
+/// Build a `__NAME__` instruction signed by `authority`
+#[cfg(feature = \"program\")]
+pub fn __SNAKE__(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    let data = HelloWorldInstruction::__NAME__.try_to_vec().unwrap();
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![AccountMeta::new_readonly(*authority, true)],
+    )
+}
+";
+
+const BUILDER_TEST: &str = "
+        assert_accounts_match(&__SNAKE__(&program_id, &Pubkey::new_unique()), tag::__UPPER__);";
+
+const MODULE: &str = "//! TODO What the __NAME__ instruction is for
+#[cfg(feature = \"program\")]
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// TODO Describe what __NAME__ does
+#[cfg(feature = \"program\")]
+pub fn process___SNAKE__(_program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    if !authority.is_signer {
+        msg!(\"The authority must sign\");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    msg!(\"__NAME__ by {}\", authority.key);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_program::clock::Epoch;
+
+    #[test]
+    fn test___SNAKE__() {
+        let program_id = Pubkey::new_unique();
+        let (key, owner) = (Pubkey::new_unique(), Pubkey::default());
+        let (mut lamports, mut data) = (0, vec![]);
+        let mut authority = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            Epoch::default(),
+        );
+        assert_eq!(
+            process___SNAKE__(&program_id, std::slice::from_ref(&authority)),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        authority.is_signer = true;
+        process___SNAKE__(&program_id, std::slice::from_ref(&authority)).unwrap();
+        // TODO Check what __NAME__ changed
+    }
+}
+";
+
+const ERROR_VARIANT: &str = "
+    /// TODO When __ERROR__ is returned
+    __ERROR__ = __CODE__,";
+
+const ERROR_MESSAGE: &str = "
+    (
+        HelloWorldError::__ERROR__.code(),
+        \"TODO __ERROR__\",
+    ),";
+
+const ERROR_CODE: &str = "
+            __CODE__ => Some(HelloWorldError::__ERROR__),";
+
+const SCHEMA_TEST: &str = "
+        assert_eq!(variants[__TAG__][0], \"__NAME__\");";
+
+struct Args {
+    name: String,
+    errors: Vec<String>,
+    root: PathBuf,
+    dry_run: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut name = None;
+    let mut args = Args {
+        name: String::new(),
+        errors: vec![],
+        root: Path::new(env!("CARGO_MANIFEST_DIR")).join("../.."),
+        dry_run: false,
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--error" => args.errors.push(check_name(value()?)?),
+            "--root" => args.root = PathBuf::from(value()?),
+            "--dry-run" => args.dry_run = true,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if name.is_none() && !arg.starts_with('-') => name = Some(check_name(arg)?),
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    args.name = name.ok_or_else(|| "Missing the instruction name".to_string())?;
+    Ok(args)
+}
+
+/// `name` if it's an UpperCamelCase identifier
+fn check_name(name: String) -> Result<String, String> {
+    let mut chars = name.chars();
+    let upper = chars.next().is_some_and(|c| c.is_ascii_uppercase());
+    if upper && chars.all(|c| c.is_ascii_alphanumeric()) {
+        Ok(name)
+    } else {
+        Err(format!("{} is not an UpperCamelCase name", name))
+    }
+}
+
+/// `SetGreetingLimit` to `set_greeting_limit`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Insert `insertion` right before the first `end` following `anchor`
+fn insert_before(
+    text: &str,
+    anchor: &str,
+    end: &str,
+    insertion: &str,
+    file: &str,
+) -> Result<String, String> {
+    let start = text
+        .find(anchor)
+        .ok_or_else(|| format!("Can't find `{}` in {}", anchor.trim(), file))?;
+    let at = text[start..]
+        .find(end)
+        .map(|offset| start + offset)
+        .ok_or_else(|| format!("Can't find the end of `{}` in {}", anchor.trim(), file))?;
+    Ok([&text[..at], insertion, &text[at..]].concat())
+}
+
+/// The lines between `anchor` and the next `end`
+fn block<'a>(text: &'a str, anchor: &str, end: &str) -> &'a str {
+    let start = text.find(anchor).unwrap_or(text.len());
+    let len = text[start..].find(end).unwrap_or(0);
+    &text[start..start + len]
+}
+
+/// Add `pub mod module;` in alphabetical order, ahead of any #[cfg] of the module
+/// after it
+fn insert_pub_mod(lib: &str, module: &str) -> Result<String, String> {
+    let lines: Vec<&str> = lib.lines().collect();
+    let mods: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].starts_with("pub mod "))
+        .collect();
+    let last = *mods.last().ok_or("Can't find the `pub mod`s in lib.rs")?;
+    let mut at = mods
+        .iter()
+        .copied()
+        .find(|&i| lines[i]["pub mod ".len()..].trim_end_matches(';') > module)
+        .unwrap_or(last + 1);
+    while at > 0 && lines[at - 1].starts_with("#[cfg") {
+        at -= 1;
+    }
+    let mut out: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    out.insert(at, format!("pub mod {};", module));
+    Ok(out.join("\n") + "\n")
+}
+
+/// The dispatch arm, on one line when it fits in lib.rs's 100 columns
+fn dispatch_arm(name: &str, snake: &str) -> String {
+    let call = format!("{}::process_{}(program_id, accounts)", snake, snake);
+    let line = format!("        HelloWorldInstruction::{} => {},", name, call);
+    if line.len() <= 100 {
+        format!("\n{}", line)
+    } else {
+        format!(
+            "\n        HelloWorldInstruction::{} => {{\n            {}\n        }}",
+            name, call
+        )
+    }
+}
+
+fn fill(template: &str, name: &str, snake: &str, tag: usize) -> String {
+    template
+        .replace("__NAME__", name)
+        .replace("__SNAKE__", snake)
+        .replace("__UPPER__", &snake.to_uppercase())
+        .replace("__TAG__", &tag.to_string())
+}
+
+fn fill_error(template: &str, error: &str, code: usize) -> String {
+    template
+        .replace("__ERROR__", error)
+        .replace("__CODE__", &code.to_string())
+}
+
+/// Every file to write, with its new contents
+fn scaffold(args: &Args) -> Result<Vec<(PathBuf, String)>, String> {
+    let src = args.root.join("src/program-rust/src");
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path.display(), err))
+    };
+    let (name, snake) = (args.name.as_str(), snake_case(&args.name));
+    let module_path = src.join(format!("{}.rs", snake));
+    if module_path.exists() {
+        return Err(format!("{} already exists", module_path.display()));
+    }
+
+    let instruction_rs = read(&src.join("instruction.rs"))?;
+    let variants = block(&instruction_rs, "pub enum HelloWorldInstruction {", "\n}\n");
+    if variants.contains(&format!("    {},", name))
+        || variants.contains(&format!("    {} {{", name))
+    {
+        return Err(format!("HelloWorldInstruction already has {}", name));
+    }
+    // NOTE INSTRUCTION_ACCOUNTS has one entry per variant, each headed by its name
+    let tag = block(&instruction_rs, "pub const INSTRUCTION_ACCOUNTS", "\n];")
+        .lines()
+        .filter(|line| line.starts_with("    // "))
+        .count();
+    let file = "instruction.rs";
+    let mut instruction_rs = insert_before(
+        &instruction_rs,
+        "pub enum HelloWorldInstruction {",
+        "\n}\n",
+        &fill(VARIANT, name, &snake, tag),
+        file,
+    )?;
+    instruction_rs = insert_before(
+        &instruction_rs,
+        "pub mod tag {",
+        "\n}\n",
+        &fill(TAG, name, &snake, tag),
+        file,
+    )?;
+    instruction_rs = insert_before(
+        &instruction_rs,
+        "pub const INSTRUCTION_ACCOUNTS",
+        "\n];",
+        &fill(ACCOUNTS, name, &snake, tag),
+        file,
+    )?;
+    instruction_rs = insert_before(
+        &instruction_rs,
+        "pub const INSTRUCTION_ACCOUNTS",
+        "\n#[cfg(test)]",
+        &fill(BUILDER, name, &snake, tag),
+        file,
+    )?;
+    instruction_rs = insert_before(
+        &instruction_rs,
+        "fn test_builders_match_instruction_accounts",
+        "\n    }\n",
+        &fill(BUILDER_TEST, name, &snake, tag),
+        file,
+    )?;
+
+    let mut lib_rs = insert_pub_mod(&read(&src.join("lib.rs"))?, &snake)?;
+    lib_rs = insert_before(
+        &lib_rs,
+        "let instruction = HelloWorldInstruction::try_from_slice",
+        "\n    }\n}\n",
+        &dispatch_arm(name, &snake),
+        "lib.rs",
+    )?;
+
+    let mut error_rs = read(&src.join("error.rs"))?;
+    for error in &args.errors {
+        let codes = block(&error_rs, "pub enum HelloWorldError {", "\n}\n");
+        if codes.contains(&format!("    {} =", error)) {
+            return Err(format!("HelloWorldError already has {}", error));
+        }
+        let code = codes.lines().filter(|line| line.contains(" = ")).count();
+        let file = "error.rs";
+        error_rs = insert_before(
+            &error_rs,
+            "pub enum HelloWorldError {",
+            "\n}\n",
+            &fill_error(ERROR_VARIANT, error, code),
+            file,
+        )?;
+        error_rs = insert_before(
+            &error_rs,
+            "pub const ERROR_MESSAGES",
+            "\n];",
+            &fill_error(ERROR_MESSAGE, error, code),
+            file,
+        )?;
+        error_rs = insert_before(
+            &error_rs,
+            "fn from_code",
+            "\n            _ => None,",
+            &fill_error(ERROR_CODE, error, code),
+            file,
+        )?;
+    }
+
+    let schema_path = args.root.join("src/schema/src/main.rs");
+    let schema_rs = insert_before(
+        &read(&schema_path)?,
+        "fn test_instruction_schema_lists_variants_in_tag_order",
+        "\n    }\n",
+        &fill(SCHEMA_TEST, name, &snake, tag),
+        "src/schema/src/main.rs",
+    )?;
+
+    let mut files = vec![
+        (module_path, fill(MODULE, name, &snake, tag)),
+        (src.join("instruction.rs"), instruction_rs),
+        (src.join("lib.rs"), lib_rs),
+        (schema_path, schema_rs),
+    ];
+    if !args.errors.is_empty() {
+        files.push((src.join("error.rs"), error_rs));
+    }
+    Ok(files)
+}
+
+/// Run rustfmt over what was written, since how an insertion lays out depends on the
+/// names in it
+// NOTE Except lib.rs, which is formatted by hand
+fn format(files: &[(PathBuf, String)]) {
+    let paths = files
+        .iter()
+        .map(|(path, _)| path)
+        .filter(|path| !path.ends_with("lib.rs"));
+    let status = process::Command::new("rustfmt")
+        .args(["--edition", "2018"])
+        .args(paths)
+        .status();
+    if !matches!(status, Ok(status) if status.success()) {
+        eprintln!("rustfmt failed, run `cargo fmt` on the files above");
+    }
+}
+
+fn main() {
+    let result = parse_args().and_then(|args| {
+        let files = scaffold(&args)?;
+        for (path, contents) in &files {
+            if !args.dry_run {
+                fs::write(path, contents)
+                    .map_err(|err| format!("Can't write {}: {}", path.display(), err))?;
+            }
+            println!("{}", path.display());
+        }
+        if !args.dry_run {
+            format(&files);
+        }
+        Ok(args)
+    });
+    match result {
+        Ok(args) if !args.dry_run => println!(
+            "\nAdded {}. Fill in the TODOs, then regenerate the client types with\n\
+             `cargo run -p helloworld-schema --bin ts -- src/client/generated.ts` and add \
+             any Python, wasm or ffi bindings and client code it needs.",
+            args.name
+        ),
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(snake_case("SetGreetingLimit"), "set_greeting_limit");
+        assert_eq!(snake_case("Version"), "version");
+        assert!(check_name("SetLimit".to_string()).is_ok());
+        assert!(check_name("set_limit".to_string()).is_err());
+        assert!(check_name("Set-Limit".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_insert_pub_mod() {
+        let lib = "use x;\npub mod config;\n#[cfg(feature = \"program\")]\npub mod cpi;\n\
+                   pub mod error;\n\nfn main() {}\n";
+        assert_eq!(
+            insert_pub_mod(lib, "counter").unwrap(),
+            "use x;\npub mod config;\npub mod counter;\n#[cfg(feature = \"program\")]\n\
+             pub mod cpi;\npub mod error;\n\nfn main() {}\n"
+        );
+        assert_eq!(
+            insert_pub_mod(lib, "zebra").unwrap(),
+            "use x;\npub mod config;\n#[cfg(feature = \"program\")]\npub mod cpi;\n\
+             pub mod error;\npub mod zebra;\n\nfn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_before() {
+        let text = "enum A {\n    X,\n}\n\nenum B {\n    Y,\n}\n";
+        assert_eq!(
+            insert_before(text, "enum B {", "\n}\n", "\n    Z,", "f").unwrap(),
+            "enum A {\n    X,\n}\n\nenum B {\n    Y,\n    Z,\n}\n"
+        );
+        assert!(insert_before(text, "enum C {", "\n}\n", "", "f").is_err());
+        assert_eq!(
+            dispatch_arm("Ping", "ping"),
+            "\n        HelloWorldInstruction::Ping => ping::process_ping(program_id, accounts),"
+        );
+        assert!(dispatch_arm(
+            "SetAVeryLongInstructionName",
+            "set_a_very_long_instruction_name"
+        )
+        .contains("=> {\n"));
+    }
+}
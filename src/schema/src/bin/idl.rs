@@ -0,0 +1,647 @@
+/*
+NOTES:
+- Emits an IDL for the program so clients can be generated instead of hand-maintained.
+Two flavours are supported:
+  - shank/solita JSON (default), for solita or codama's shank IDL importer.
+  - Anchor's IDL spec (`--anchor`). Drop the output into an Anchor workspace as
+  `idls/helloworld.json` and `declare_program!(helloworld)` generates typed CPI calls
+  into this program. Our instruction discriminators are the 1-byte Borsh enum tags
+  rather than Anchor's 8-byte hashes, which the spec allows. Greeting accounts (from
+  schema 6 on), counter snapshots, greeting receipts and events do carry Anchor's
+  8-byte discriminators, so they're listed under "accounts" and "events" for explorers
+  and parsers to recognize.
+- We don't use the shank derive macros themselves: they need newer proc-macro2/serde
+releases than the solana 1.6 BPF toolchain can build. Everything shank would read from
+annotations already exists in Rust: instruction args and account layouts come from
+BorshSchema, account lists from INSTRUCTION_ACCOUNTS, and errors from ERROR_MESSAGES.
+- Run with `cargo run -p helloworld-schema --bin idl -- [--anchor] [out.json]`.
+*/
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use helloworld::{
+    challenge::Challenge,
+    compression::GreetingLeaf,
+    config::{Config, ConfigPatch},
+    counter_snapshot::{CounterSnapshot, COUNTER_SNAPSHOT_DISCRIMINATOR},
+    error::{HelloWorldError, ERROR_MESSAGES},
+    event::{AuthorityChanged, ConfigChanged, AUTHORITY_CHANGED, CONFIG_CHANGED},
+    instruction::{HelloWorldInstruction, INSTRUCTION_ACCOUNTS},
+    luck::LuckTicket,
+    migrations::GREETING_DISCRIMINATOR,
+    milestone::{GreetingSnapshot, SNAPSHOT_DISCRIMINATOR},
+    receipt::{GreetingReceipt, RECEIPT_DISCRIMINATOR},
+    session::SessionToken,
+    stake::StakePosition,
+    tags::{TagCount, TagCounters, TAG_COUNTERS_DISCRIMINATOR},
+    wormhole::ForeignGreeter,
+    GreetingAccount,
+};
+use serde_json::{json, Value};
+use std::{env, fs, process};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Spec {
+    Shank,
+    Anchor,
+}
+
+/// "greeting_account" / "GreetingAccount" -> "greetingAccount"
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper_next = false;
+    for (i, c) in name.chars().enumerate() {
+        if c == '_' {
+            upper_next = true;
+        } else if i == 0 {
+            out.extend(c.to_lowercase());
+        } else if upper_next {
+            out.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// "greetingAccount" / "Greet" -> "greeting_account" / "greet"
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            out.push('_');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+fn field_name(name: &str, spec: Spec) -> String {
+    match spec {
+        Spec::Shank => camel_case(name),
+        Spec::Anchor => snake_case(name),
+    }
+}
+
+/// Translate a Borsh declaration into an IDL type
+fn idl_type(declaration: &str, container: &BorshSchemaContainer, spec: Spec) -> Value {
+    if declaration.starts_with("Option<") {
+        let inner = &declaration["Option<".len()..declaration.len() - 1];
+        return json!({ "option": idl_type(inner, container, spec) });
+    }
+    match container.definitions.get(declaration) {
+        Some(Definition::Sequence { elements }) => {
+            json!({ "vec": idl_type(elements, container, spec) })
+        }
+        Some(Definition::Array { length, elements }) => {
+            json!({ "array": [idl_type(elements, container, spec), length] })
+        }
+        Some(_) => match spec {
+            Spec::Shank => json!({ "defined": declaration }),
+            Spec::Anchor => json!({ "defined": { "name": declaration } }),
+        },
+        // Primitives (u8, u32, string, bool, ...) have no definition and keep their name
+        None => json!(declaration),
+    }
+}
+
+fn idl_fields(fields: &Fields, container: &BorshSchemaContainer, spec: Spec) -> Vec<Value> {
+    match fields {
+        Fields::NamedFields(fields) => fields
+            .iter()
+            .map(|(name, declaration)| {
+                json!({
+                    "name": field_name(name, spec),
+                    "type": idl_type(declaration, container, spec),
+                })
+            })
+            .collect(),
+        Fields::UnnamedFields(fields) => fields
+            .iter()
+            .enumerate()
+            .map(|(i, declaration)| {
+                json!({
+                    "name": format!("arg{}", i),
+                    "type": idl_type(declaration, container, spec),
+                })
+            })
+            .collect(),
+        Fields::Empty => vec![],
+    }
+}
+
+fn instructions(spec: Spec) -> Vec<Value> {
+    let container = HelloWorldInstruction::schema_container();
+    let variants = match &container.definitions[&container.declaration] {
+        Definition::Enum { variants } => variants,
+        _ => unreachable!("HelloWorldInstruction is an enum"),
+    };
+    variants
+        .iter()
+        .enumerate()
+        .map(|(tag, (name, declaration))| {
+            let args = match &container.definitions[declaration] {
+                Definition::Struct { fields } => idl_fields(fields, &container, spec),
+                _ => vec![],
+            };
+            let accounts: Vec<Value> = INSTRUCTION_ACCOUNTS[tag]
+                .iter()
+                .map(|account| {
+                    let mut value = match spec {
+                        Spec::Shank => json!({
+                            "name": account.name,
+                            "isMut": account.writable,
+                            "isSigner": account.signer,
+                            "desc": account.desc,
+                        }),
+                        Spec::Anchor => json!({
+                            "name": snake_case(account.name),
+                            "docs": [account.desc],
+                            "writable": account.writable,
+                            "signer": account.signer,
+                        }),
+                    };
+                    // NOTE Both specs leave "optional" out for required accounts
+                    if account.optional {
+                        value["optional"] = json!(true);
+                    }
+                    value
+                })
+                .collect();
+            match spec {
+                Spec::Shank => json!({
+                    "name": camel_case(name),
+                    "accounts": accounts,
+                    "args": args,
+                    "discriminant": { "type": "u8", "value": tag },
+                }),
+                Spec::Anchor => json!({
+                    "name": snake_case(name),
+                    "discriminator": [tag],
+                    "accounts": accounts,
+                    "args": args,
+                }),
+            }
+        })
+        .collect()
+}
+
+fn account(container: BorshSchemaContainer, spec: Spec) -> Value {
+    let fields = match &container.definitions[&container.declaration] {
+        Definition::Struct { fields } => idl_fields(fields, &container, spec),
+        _ => unreachable!("accounts are structs"),
+    };
+    json!({
+        "name": container.declaration,
+        "type": { "kind": "struct", "fields": fields },
+    })
+}
+
+fn errors() -> Vec<Value> {
+    ERROR_MESSAGES
+        .iter()
+        .map(|(code, message)| {
+            let error = HelloWorldError::from_code(*code).unwrap();
+            json!({ "code": code, "name": format!("{:?}", error), "msg": message })
+        })
+        .collect()
+}
+
+fn idl(spec: Spec) -> Value {
+    match spec {
+        Spec::Shank => json!({
+            "version": helloworld::VERSION,
+            "name": "helloworld",
+            "instructions": instructions(spec),
+            "accounts": [
+                account(GreetingAccount::schema_container(), spec),
+                account(Config::schema_container(), spec),
+                account(LuckTicket::schema_container(), spec),
+                account(StakePosition::schema_container(), spec),
+                account(Challenge::schema_container(), spec),
+                account(ForeignGreeter::schema_container(), spec),
+                account(SessionToken::schema_container(), spec),
+                account(CounterSnapshot::schema_container(), spec),
+                account(GreetingReceipt::schema_container(), spec),
+                account(GreetingSnapshot::schema_container(), spec),
+                account(TagCounters::schema_container(), spec),
+            ],
+            "types": [
+                account(GreetingLeaf::schema_container(), spec),
+                account(TagCount::schema_container(), spec),
+                account(ConfigPatch::schema_container(), spec),
+            ],
+            "errors": errors(),
+            "metadata": {
+                "origin": "shank",
+                "address": helloworld::id().to_string(),
+            },
+        }),
+        // NOTE Anchor's "accounts" section requires 8-byte account discriminators. Only
+        // greeting accounts, counter snapshots, greeting receipts, snapshots and tag
+        // counters have one: the config, tickets, positions and the rest are PDAs created
+        // before discriminators and solana-program 1.6 can't grow them to make room, so
+        // their layouts are listed under "types" only. Compact greeting accounts and those too small to migrate
+        // to schema 6 don't match it either. A counter snapshot's type is its header,
+        // the leaves and nodes after it aren't Borsh.
+        Spec::Anchor => json!({
+            "address": helloworld::id().to_string(),
+            "metadata": {
+                "name": "helloworld",
+                "version": helloworld::VERSION,
+                "spec": "0.1.0",
+            },
+            "instructions": instructions(spec),
+            "accounts": [
+                { "name": "GreetingAccount", "discriminator": GREETING_DISCRIMINATOR },
+                {
+                    "name": "CounterSnapshot",
+                    "discriminator": COUNTER_SNAPSHOT_DISCRIMINATOR,
+                },
+                { "name": "GreetingReceipt", "discriminator": RECEIPT_DISCRIMINATOR },
+                { "name": "GreetingSnapshot", "discriminator": SNAPSHOT_DISCRIMINATOR },
+                { "name": "TagCounters", "discriminator": TAG_COUNTERS_DISCRIMINATOR },
+            ],
+            "events": [
+                { "name": "ConfigChanged", "discriminator": CONFIG_CHANGED },
+                { "name": "AuthorityChanged", "discriminator": AUTHORITY_CHANGED },
+            ],
+            "errors": errors(),
+            "types": [
+                account(GreetingAccount::schema_container(), spec),
+                account(Config::schema_container(), spec),
+                account(LuckTicket::schema_container(), spec),
+                account(StakePosition::schema_container(), spec),
+                account(Challenge::schema_container(), spec),
+                account(ForeignGreeter::schema_container(), spec),
+                account(SessionToken::schema_container(), spec),
+                account(CounterSnapshot::schema_container(), spec),
+                account(GreetingReceipt::schema_container(), spec),
+                account(GreetingSnapshot::schema_container(), spec),
+                account(TagCounters::schema_container(), spec),
+                account(GreetingLeaf::schema_container(), spec),
+                account(TagCount::schema_container(), spec),
+                account(ConfigPatch::schema_container(), spec),
+                account(ConfigChanged::schema_container(), spec),
+                account(AuthorityChanged::schema_container(), spec),
+            ],
+        }),
+    }
+}
+
+fn main() {
+    let mut spec = Spec::Shank;
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--anchor" => spec = Spec::Anchor,
+            _ => path = Some(arg),
+        }
+    }
+
+    let output = serde_json::to_string_pretty(&idl(spec)).unwrap();
+    match path {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, output + "\n") {
+                eprintln!("Failed to write {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        None => println!("{}", output),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_name_cases() {
+        assert_eq!(camel_case("Greet"), "greet");
+        assert_eq!(camel_case("greeting_account"), "greetingAccount");
+        assert_eq!(camel_case("txt"), "txt");
+        assert_eq!(snake_case("greetingAccount"), "greeting_account");
+        assert_eq!(snake_case("Greet"), "greet");
+    }
+
+    #[test]
+    fn test_greet_instruction() {
+        let idl = idl(Spec::Shank);
+        assert_eq!(
+            idl["instructions"][0],
+            json!({
+                "name": "greet",
+                "accounts": [
+                    {
+                        "name": "greetingAccount",
+                        "isMut": true,
+                        "isSigner": false,
+                        "desc": "The greeting account, owned by this program",
+                    },
+                    {
+                        "name": "config",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The config account",
+                    },
+                    {
+                        "name": "payer",
+                        "isMut": true,
+                        "isSigner": true,
+                        "desc": "The payer of the fee, when the config sets one",
+                        "optional": true,
+                    },
+                    {
+                        "name": "priceFeed",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The config's Pyth SOL/USD price account, when there's a fee",
+                        "optional": true,
+                    },
+                    {
+                        "name": "feeRecipient",
+                        "isMut": true,
+                        "isSigner": false,
+                        "desc": "The config's fee recipient, when there's a fee",
+                        "optional": true,
+                    },
+                    {
+                        "name": "systemProgram",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The system program, when there's a fee",
+                        "optional": true,
+                    },
+                    {
+                        "name": "greetingLog",
+                        "isMut": true,
+                        "isSigner": false,
+                        "desc": "The config's greeting log, when it sets one",
+                        "optional": true,
+                    },
+                    {
+                        "name": "logAuthority",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The log authority, a PDA at [\"greeting-log\"], with the greeting log",
+                        "optional": true,
+                    },
+                    {
+                        "name": "compressionProgram",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The spl-account-compression program, with the greeting log",
+                        "optional": true,
+                    },
+                    {
+                        "name": "noopProgram",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The spl-noop program, with the greeting log",
+                        "optional": true,
+                    },
+                    {
+                        "name": "receipt",
+                        "isMut": true,
+                        "isSigner": false,
+                        "desc": "The greeting's receipt, when the config sets greeting receipts",
+                        "optional": true,
+                    },
+                    {
+                        "name": "receiptSigner",
+                        "isMut": true,
+                        "isSigner": true,
+                        "desc": "The receipt's signer, paying its rent, with the receipt",
+                        "optional": true,
+                    },
+                    {
+                        "name": "receiptSystemProgram",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The system program, with the receipt",
+                        "optional": true,
+                    },
+                    {
+                        "name": "balanceTokenAccount",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The greeter's token account, when the config sets a balance mint",
+                        "optional": true,
+                    },
+                    {
+                        "name": "balanceOwner",
+                        "isMut": false,
+                        "isSigner": true,
+                        "desc": "The token account's owner, with the token account",
+                        "optional": true,
+                    },
+                    {
+                        "name": "stakePosition",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The greeter's stake position, to weight the greeting by stake",
+                        "optional": true,
+                    },
+                    {
+                        "name": "staker",
+                        "isMut": false,
+                        "isSigner": true,
+                        "desc": "The staker or a session key of theirs, with the stake position",
+                        "optional": true,
+                    },
+                    {
+                        "name": "sessionToken",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The session token, when a session key signs for the staker",
+                        "optional": true,
+                    },
+                    {
+                        "name": "instructions",
+                        "isMut": false,
+                        "isSigner": false,
+                        "desc": "The instructions sysvar, last, to count the greeting as direct or CPI",
+                        "optional": true,
+                    },
+                ],
+                "args": [{ "name": "txt", "type": "string" }],
+                "discriminant": { "type": "u8", "value": 0 },
+            })
+        );
+        assert_eq!(idl["instructions"][1]["name"], "version");
+        assert_eq!(idl["errors"][1]["name"], "MessageTooLong");
+    }
+
+    #[test]
+    fn test_anchor_greet_instruction() {
+        let idl = idl(Spec::Anchor);
+        assert_eq!(
+            idl["instructions"][0],
+            json!({
+                "name": "greet",
+                "discriminator": [0],
+                "accounts": [
+                    {
+                        "name": "greeting_account",
+                        "docs": ["The greeting account, owned by this program"],
+                        "writable": true,
+                        "signer": false,
+                    },
+                    {
+                        "name": "config",
+                        "docs": ["The config account"],
+                        "writable": false,
+                        "signer": false,
+                    },
+                    {
+                        "name": "payer",
+                        "docs": ["The payer of the fee, when the config sets one"],
+                        "writable": true,
+                        "signer": true,
+                        "optional": true,
+                    },
+                    {
+                        "name": "price_feed",
+                        "docs": ["The config's Pyth SOL/USD price account, when there's a fee"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "fee_recipient",
+                        "docs": ["The config's fee recipient, when there's a fee"],
+                        "writable": true,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "system_program",
+                        "docs": ["The system program, when there's a fee"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "greeting_log",
+                        "docs": ["The config's greeting log, when it sets one"],
+                        "writable": true,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "log_authority",
+                        "docs": ["The log authority, a PDA at [\"greeting-log\"], with the greeting log"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "compression_program",
+                        "docs": ["The spl-account-compression program, with the greeting log"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "noop_program",
+                        "docs": ["The spl-noop program, with the greeting log"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "receipt",
+                        "docs": ["The greeting's receipt, when the config sets greeting receipts"],
+                        "writable": true,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "receipt_signer",
+                        "docs": ["The receipt's signer, paying its rent, with the receipt"],
+                        "writable": true,
+                        "signer": true,
+                        "optional": true,
+                    },
+                    {
+                        "name": "receipt_system_program",
+                        "docs": ["The system program, with the receipt"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "balance_token_account",
+                        "docs": ["The greeter's token account, when the config sets a balance mint"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "balance_owner",
+                        "docs": ["The token account's owner, with the token account"],
+                        "writable": false,
+                        "signer": true,
+                        "optional": true,
+                    },
+                    {
+                        "name": "stake_position",
+                        "docs": ["The greeter's stake position, to weight the greeting by stake"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "staker",
+                        "docs": ["The staker or a session key of theirs, with the stake position"],
+                        "writable": false,
+                        "signer": true,
+                        "optional": true,
+                    },
+                    {
+                        "name": "session_token",
+                        "docs": ["The session token, when a session key signs for the staker"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                    {
+                        "name": "instructions",
+                        "docs": ["The instructions sysvar, last, to count the greeting as direct or CPI"],
+                        "writable": false,
+                        "signer": false,
+                        "optional": true,
+                    },
+                ],
+                "args": [{ "name": "txt", "type": "string" }],
+            })
+        );
+        assert_eq!(idl["metadata"]["spec"], "0.1.0");
+        assert_eq!(idl["types"][0]["name"], "GreetingAccount");
+        assert_eq!(idl["types"][1]["name"], "Config");
+    }
+
+    #[test]
+    fn test_anchor_accounts_and_events_have_types() {
+        let idl = idl(Spec::Anchor);
+        let types: Vec<&Value> = idl["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ty| &ty["name"])
+            .collect();
+        for entry in idl["accounts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(idl["events"].as_array().unwrap())
+        {
+            assert!(types.contains(&&entry["name"]), "{}", entry["name"]);
+            assert_eq!(entry["discriminator"].as_array().unwrap().len(), 8);
+        }
+        assert_eq!(
+            idl["accounts"][0]["discriminator"],
+            json!([190, 16, 56, 57, 246, 26, 112, 24])
+        );
+    }
+}
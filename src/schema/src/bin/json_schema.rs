@@ -0,0 +1,223 @@
+/*
+NOTES:
+- Emits a JSON Schema (draft 2020-12) document per account type so explorers and
+generic account viewers can render helloworld accounts without custom code.
+- JSON Schema describes the decoded value, not the bytes, so each property also carries
+an "x-borsh" annotation with its Borsh type, byte offset and size. Sizes and offsets
+are null once a variable-length field (String, Vec, Option) makes them data-dependent;
+a viewer has to decode sequentially from that field on.
+- Run with `cargo run -p helloworld-schema --bin json-schema -- [out-dir]`. With an
+output directory one `<Account>.schema.json` file is written per account, otherwise
+all documents are printed as one object keyed by account name.
+- When adding a new account type, add it to accounts() below.
+*/
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use helloworld::{
+    challenge::Challenge, config::Config, escrow::Escrow, luck::LuckTicket, session::SessionToken,
+    stake::StakePosition, transfer_hook::TransferGreetings, wormhole::ForeignGreeter,
+    GreetingAccount,
+};
+use serde_json::{json, Map, Value};
+use std::{env, fs, path::Path, process};
+
+fn accounts() -> Vec<BorshSchemaContainer> {
+    vec![
+        GreetingAccount::schema_container(),
+        Config::schema_container(),
+        LuckTicket::schema_container(),
+        StakePosition::schema_container(),
+        Challenge::schema_container(),
+        ForeignGreeter::schema_container(),
+        TransferGreetings::schema_container(),
+        SessionToken::schema_container(),
+        Escrow::schema_container(),
+    ]
+}
+
+/// JSON Schema for a Borsh declaration and its encoded size, if fixed
+fn layout(declaration: &str, container: &BorshSchemaContainer) -> (Value, Option<usize>) {
+    if declaration.starts_with("Option<") {
+        let (inner, _) = layout(
+            &declaration["Option<".len()..declaration.len() - 1],
+            container,
+        );
+        // NOTE 1 tag byte, then the value only when present, so the size varies
+        return (json!({ "oneOf": [{ "type": "null" }, inner] }), None);
+    }
+    let integer = |bits: u32, signed: bool| {
+        let (min, max) = if signed {
+            (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1)
+        } else {
+            (0, (1i64 << bits) - 1)
+        };
+        (
+            json!({ "type": "integer", "minimum": min, "maximum": max }),
+            Some(bits as usize / 8),
+        )
+    };
+    match declaration {
+        "bool" => (json!({ "type": "boolean" }), Some(1)),
+        "u8" => integer(8, false),
+        "u16" => integer(16, false),
+        "u32" => integer(32, false),
+        "i8" => integer(8, true),
+        "i16" => integer(16, true),
+        "i32" => integer(32, true),
+        // NOTE These don't fit in a JS number, viewers should show them as decimal strings
+        "u64" | "i64" | "u128" | "i128" => {
+            let size = if declaration.ends_with("128") { 16 } else { 8 };
+            (
+                json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+                Some(size),
+            )
+        }
+        // u32 length prefix followed by the UTF-8 bytes
+        "string" => (json!({ "type": "string" }), None),
+        _ => match container.definitions.get(declaration) {
+            Some(Definition::Array { length, elements }) => {
+                let (items, size) = layout(elements, container);
+                let schema = json!({
+                    "type": "array",
+                    "items": items,
+                    "minItems": length,
+                    "maxItems": length,
+                });
+                (schema, size.map(|size| size * *length as usize))
+            }
+            Some(Definition::Sequence { elements }) => {
+                let (items, _) = layout(elements, container);
+                (json!({ "type": "array", "items": items }), None)
+            }
+            Some(Definition::Struct { fields }) => object(fields, container),
+            _ => panic!("no JSON Schema mapping for {}", declaration),
+        },
+    }
+}
+
+/// An object schema for a struct's fields, annotating each with its Borsh layout
+fn object(fields: &Fields, container: &BorshSchemaContainer) -> (Value, Option<usize>) {
+    let fields = match fields {
+        Fields::NamedFields(fields) => fields.clone(),
+        Fields::Empty => vec![],
+        Fields::UnnamedFields(_) => panic!("tuple structs have no field names"),
+    };
+    let mut properties = Map::new();
+    let mut offset = Some(0);
+    for (name, declaration) in &fields {
+        let (mut schema, size) = layout(declaration, container);
+        schema["x-borsh"] = json!({
+            "type": declaration,
+            "offset": offset,
+            "size": size,
+        });
+        properties.insert(name.clone(), schema);
+        offset = offset.and_then(|offset| size.map(|size| offset + size));
+    }
+    let required: Vec<&String> = fields.iter().map(|(name, _)| name).collect();
+    let schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    });
+    (schema, offset)
+}
+
+fn json_schema(container: &BorshSchemaContainer) -> Value {
+    let (mut schema, size) = match &container.definitions[&container.declaration] {
+        Definition::Struct { fields } => object(fields, container),
+        _ => unreachable!("accounts are structs"),
+    };
+    schema["$schema"] = json!("https://json-schema.org/draft/2020-12/schema");
+    schema["$id"] = json!(format!("helloworld/{}.schema.json", container.declaration));
+    schema["title"] = json!(container.declaration);
+    schema["description"] = json!(format!(
+        "Borsh-encoded helloworld account owned by program {}",
+        helloworld::id()
+    ));
+    schema["x-borsh"] = json!({ "size": size });
+    schema
+}
+
+fn main() {
+    match env::args().nth(1) {
+        Some(dir) => {
+            for container in accounts() {
+                let path = Path::new(&dir).join(format!("{}.schema.json", container.declaration));
+                let output = serde_json::to_string_pretty(&json_schema(&container)).unwrap();
+                if let Err(err) = fs::write(&path, output + "\n") {
+                    eprintln!("Failed to write {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+            }
+        }
+        None => {
+            let documents: Map<String, Value> = accounts()
+                .iter()
+                .map(|container| (container.declaration.clone(), json_schema(container)))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&documents).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_greeting_account_json_schema() {
+        let schema = json_schema(&GreetingAccount::schema_container());
+        assert_eq!(schema["title"], "GreetingAccount");
+        assert_eq!(
+            schema["required"],
+            json!([
+                "txt",
+                "counter",
+                "calendar_year",
+                "greeted_days",
+                "last_update_epoch",
+                "direct_greets",
+                "cpi_greets"
+            ])
+        );
+        assert_eq!(
+            schema["properties"]["txt"],
+            json!({
+                "type": "string",
+                "x-borsh": { "type": "string", "offset": 0, "size": null },
+            })
+        );
+        // A String makes the account variable-sized
+        assert_eq!(schema["x-borsh"]["size"], Value::Null);
+    }
+
+    #[test]
+    fn test_fixed_size_offsets() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Fixed {
+            flag: bool,
+            count: u64,
+            seed: [u8; 32],
+            note: String,
+            after: u8,
+        }
+        let (schema, size) = match &Fixed::schema_container().definitions["Fixed"] {
+            Definition::Struct { fields } => object(fields, &Fixed::schema_container()),
+            _ => unreachable!(),
+        };
+        let offsets: Vec<Value> = ["flag", "count", "seed", "note", "after"]
+            .iter()
+            .map(|name| schema["properties"][name]["x-borsh"]["offset"].clone())
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![json!(0), json!(1), json!(9), json!(41), Value::Null]
+        );
+        assert_eq!(schema["properties"]["seed"]["x-borsh"]["size"], 32);
+        assert_eq!(schema["properties"]["count"]["type"], "string");
+        assert_eq!(size, None);
+    }
+}
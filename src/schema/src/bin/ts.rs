@@ -0,0 +1,705 @@
+/*
+NOTES:
+- Generates src/client/generated.ts (TypeScript classes, the borsh-js schema, instruction
+tags and the error table) from the Rust types, so the JS client can't drift from the
+program's layouts. Regenerate after changing any state/instruction/error type with
+`cargo run -p helloworld-schema --bin ts -- src/client/generated.ts`.
+- The test at the bottom fails if the checked-in file is out of date.
+- Only the Borsh types borsh-js 0.4 understands are mapped; anything else panics so we
+notice while generating rather than at runtime in the client.
+*/
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use helloworld::{
+    calendar::DAY_BITMAP_SIZE,
+    challenge::{Challenge, CHALLENGE_SEED},
+    claim::{
+        CLAIMED_BITMAP_DISCRIMINATOR, CLAIMED_BITMAP_SIZE, CLAIMED_SEED, CLAIMS_PER_BITMAP,
+        CLAIM_VAULT_SEED,
+    },
+    compact::{COMPACT_MAGIC, FLAG_CALENDAR, FLAG_COUNTER, FLAG_LAST_UPDATE, FLAG_ORIGIN},
+    compression::{GreetingLeaf, LOG_AUTHORITY_SEED},
+    config::{Config, ConfigPatch, CONFIG_SEED},
+    counter_snapshot::{
+        CounterSnapshot, COUNTER_SNAPSHOT_DISCRIMINATOR, HEADER_SIZE as SNAPSHOT_HEADER_SIZE,
+        LEAF_SIZE as SNAPSHOT_LEAF_SIZE, MAX_SNAPSHOT_DEPTH,
+    },
+    error::{HelloWorldError, ERROR_MESSAGES},
+    escrow::{Escrow, ESCROW_SEED},
+    instruction::{HelloWorldInstruction, MAX_GREET_MANY_ACCOUNTS},
+    luck::{LuckTicket, TICKET_SEED, VAULT_SEED},
+    migrations::{DISCRIMINATOR_VERSION, GREETING_DISCRIMINATOR, STATE_MAGIC},
+    milestone::{GreetingSnapshot, MAX_LABEL_LEN, SNAPSHOT_DISCRIMINATOR, SNAPSHOT_SEED},
+    receipt::{GreetingReceipt, RECEIPT_DISCRIMINATOR, RECEIPT_SEED, RECEIPT_SIZE},
+    recency,
+    session::{SessionToken, SESSION_SEED},
+    stake::{StakePosition, STAKE_AUTHORITY_SEED, STAKE_SEED},
+    state_hash::{STATE_HASH_PREFIX, STATE_HASH_SIZE},
+    tags::{
+        TagCount, TagCounters, MAX_TAGS, TAGS_SEED, TAG_COUNTERS_DISCRIMINATOR, TAG_COUNTERS_SIZE,
+    },
+    target::{MAX_TARGET_MESSAGE_LEN, TARGET_GREETING_SEED, TARGET_GREETING_SIZE},
+    transfer_hook::{TransferGreetings, EXTRA_ACCOUNT_METAS_SEED, TRANSFER_GREETINGS_SEED},
+    wormhole::{ForeignGreeter, FOREIGN_GREETER_SEED},
+    GreetingAccount, GreetingAccountV1, GreetingAccountV2, GreetingAccountV3, GreetingAccountV4,
+    STATE_VERSION,
+};
+use std::{env, fmt::Write, fs, process};
+
+const HEADER: &str = "\
+// This file is generated from the Rust program by
+// `cargo run -p helloworld-schema --bin ts -- src/client/generated.ts`. Do not edit.
+";
+
+/// A struct to emit as a TS class: (class name, [(field name, borsh declaration)])
+struct TsClass {
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+/// TS type, default value and borsh-js schema type for a Borsh declaration
+struct TsType {
+    ts: String,
+    default: String,
+    borsh: String,
+}
+
+fn ts_type(declaration: &str, container: &BorshSchemaContainer) -> TsType {
+    let simple = |ts: &str, default: &str, borsh: &str| TsType {
+        ts: ts.to_string(),
+        default: default.to_string(),
+        borsh: borsh.to_string(),
+    };
+    if declaration.starts_with("Option<") {
+        let inner = ts_type(
+            &declaration["Option<".len()..declaration.len() - 1],
+            container,
+        );
+        return TsType {
+            ts: format!("{} | null", inner.ts),
+            default: "null".to_string(),
+            borsh: format!("{{kind: 'option', type: {}}}", inner.borsh),
+        };
+    }
+    match declaration {
+        "string" => simple("string", "''", "'string'"),
+        // NOTE borsh-js 0.4 has no bool, but a bool is one byte of 0 or 1 like a u8
+        "bool" => simple("number", "0", "'u8'"),
+        "u8" | "u16" | "u32" => simple("number", "0", &format!("'{}'", declaration)),
+        "u64" | "u128" => simple("BN", "new BN(0)", &format!("'{}'", declaration)),
+        _ => match container.definitions.get(declaration) {
+            Some(Definition::Array { length, elements }) if elements == "u8" => simple(
+                "Uint8Array",
+                &format!("new Uint8Array({})", length),
+                &format!("[{}]", length),
+            ),
+            Some(Definition::Sequence { elements }) => {
+                let inner = ts_type(elements, container);
+                TsType {
+                    ts: format!("{}[]", inner.ts),
+                    default: "[]".to_string(),
+                    borsh: format!("[{}]", inner.borsh),
+                }
+            }
+            Some(Definition::Struct { .. }) => {
+                simple(declaration, &format!("new {}()", declaration), declaration)
+            }
+            _ => panic!("borsh-js has no mapping for {}", declaration),
+        },
+    }
+}
+
+fn named_fields(fields: &Fields) -> Vec<(String, String)> {
+    match fields {
+        Fields::NamedFields(fields) => fields.clone(),
+        Fields::Empty => vec![],
+        Fields::UnnamedFields(_) => panic!("tuple structs can't be mapped to TS classes"),
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn write_class(out: &mut String, class: &TsClass, container: &BorshSchemaContainer) {
+    // NOTE borsh-js reads and writes the properties named after the Rust fields, so
+    // they keep their snake_case names
+    writeln!(out, "export class {} {{", class.name).unwrap();
+    for (name, declaration) in &class.fields {
+        let ty = ts_type(declaration, container);
+        // NOTE eslint's no-inferrable-types rejects annotating primitive initializers
+        if ty.ts == "string" || ty.ts == "number" {
+            writeln!(out, "  {} = {};", name, ty.default).unwrap();
+        } else {
+            writeln!(out, "  {}: {} = {};", name, ty.ts, ty.default).unwrap();
+        }
+    }
+    if !class.fields.is_empty() {
+        let params: Vec<String> = class
+            .fields
+            .iter()
+            .map(|(name, declaration)| format!("{}: {}", name, ts_type(declaration, container).ts))
+            .collect();
+        let one_line = format!(
+            "  constructor(fields: {{{}}} | undefined = undefined) {{",
+            params.join("; ")
+        );
+        if one_line.len() <= 80 {
+            writeln!(out, "{}", one_line).unwrap();
+        } else {
+            // Wrapped the way prettier breaks a union that doesn't fit
+            out.push_str("  constructor(\n    fields:\n      | {\n");
+            for param in &params {
+                writeln!(out, "          {};", param).unwrap();
+            }
+            out.push_str("        }\n      | undefined = undefined,\n  ) {\n");
+        }
+        writeln!(out, "    if (fields) {{").unwrap();
+        for (name, _) in &class.fields {
+            writeln!(out, "      this.{} = fields.{};", name, name).unwrap();
+        }
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "  }}").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn schema_entry(class: &TsClass, container: &BorshSchemaContainer) -> String {
+    // NOTE borsh-js takes the field names as they appear in the Rust struct
+    let fields: Vec<String> = class
+        .fields
+        .iter()
+        .map(|(name, declaration)| {
+            format!(
+                "[{}, {}]",
+                quote(name),
+                ts_type(declaration, container).borsh
+            )
+        })
+        .collect();
+    let one_line = format!(
+        "  [{}, {{kind: 'struct', fields: [{}]}}],",
+        class.name,
+        fields.join(", ")
+    );
+    if one_line.len() <= 80 {
+        return one_line;
+    }
+    // Prettier breaks the outer array first, then the struct if it still doesn't fit
+    let struct_line = format!("    {{kind: 'struct', fields: [{}]}},", fields.join(", "));
+    if struct_line.len() <= 80 {
+        return format!("  [\n    {},\n{}\n  ],", class.name, struct_line);
+    }
+    let mut entry = format!("  [\n    {},\n    {{\n      kind: 'struct',\n", class.name);
+    entry.push_str("      fields: [\n");
+    for field in fields {
+        writeln!(entry, "        {},", field).unwrap();
+    }
+    entry.push_str("      ],\n    },\n  ],");
+    entry
+}
+
+fn generate() -> String {
+    let mut out = String::from(HEADER);
+    let mut schema_entries = vec![];
+    let mut uses_bn = false;
+
+    // Account state, the current layout and the older ones still on chain, the
+    // config account, the lucky-greeter tickets, the stake positions, the challenges, the
+    // greeters on other chains, the leaves of the greeting log, the transfer-hook
+    // program's counters, the session tokens and the escrow program's offers,
+    // SetConfig's patch of the config, the header of the counter snapshots and the
+    // greeting receipts
+    let accounts = [
+        GreetingAccount::schema_container(),
+        GreetingAccountV4::schema_container(),
+        GreetingAccountV3::schema_container(),
+        GreetingAccountV2::schema_container(),
+        GreetingAccountV1::schema_container(),
+        Config::schema_container(),
+        LuckTicket::schema_container(),
+        StakePosition::schema_container(),
+        Challenge::schema_container(),
+        ForeignGreeter::schema_container(),
+        GreetingLeaf::schema_container(),
+        TransferGreetings::schema_container(),
+        SessionToken::schema_container(),
+        Escrow::schema_container(),
+        ConfigPatch::schema_container(),
+        CounterSnapshot::schema_container(),
+        GreetingReceipt::schema_container(),
+        GreetingSnapshot::schema_container(),
+        TagCount::schema_container(),
+        TagCounters::schema_container(),
+    ];
+    let mut classes = vec![];
+    for account in &accounts {
+        if let Definition::Struct { fields } = &account.definitions[&account.declaration] {
+            classes.push((
+                TsClass {
+                    name: account.declaration.clone(),
+                    fields: named_fields(fields),
+                },
+                account,
+            ));
+        }
+    }
+
+    // Instruction arguments, one class per variant that carries fields
+    let instructions = HelloWorldInstruction::schema_container();
+    let variants = match &instructions.definitions[&instructions.declaration] {
+        Definition::Enum { variants } => variants.clone(),
+        _ => unreachable!("HelloWorldInstruction is an enum"),
+    };
+    for (name, declaration) in &variants {
+        if let Definition::Struct { fields } = &instructions.definitions[declaration] {
+            let fields = named_fields(fields);
+            if !fields.is_empty() {
+                classes.push((
+                    TsClass {
+                        name: format!("{}Args", name),
+                        fields,
+                    },
+                    &instructions,
+                ));
+            }
+        }
+    }
+
+    let mut body = String::new();
+    for (class, container) in &classes {
+        uses_bn |= class
+            .fields
+            .iter()
+            .any(|(_, declaration)| ts_type(declaration, container).ts.contains("BN"));
+        write_class(&mut body, class, container);
+        schema_entries.push(schema_entry(class, container));
+    }
+
+    if uses_bn {
+        out.push_str("import BN from 'bn.js';\n");
+    }
+    out.push('\n');
+    out.push_str(&body);
+
+    out.push_str("/**\n * Borsh schema for every class above\n */\n");
+    out.push_str("export const HelloWorldSchema = new Map<any, any>([\n");
+    for entry in schema_entries {
+        writeln!(out, "{}", entry).unwrap();
+    }
+    out.push_str("]);\n\n");
+
+    out.push_str(
+        "/**\n * Schema version of GreetingAccount. From version 2 on, account data opens with\n",
+    );
+    out.push_str(" * STATE_MAGIC and the version byte; version 1 accounts have no header.\n */\n");
+    writeln!(out, "export const STATE_VERSION = {};", STATE_VERSION).unwrap();
+    writeln!(
+        out,
+        "export const STATE_MAGIC = {};\n",
+        quote(std::str::from_utf8(&STATE_MAGIC).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of GreetingAccount, sha256(\"account:GreetingAccount\")[..8].\n",
+    );
+    out.push_str(
+        " * From DISCRIMINATOR_VERSION on it opens the data in place of STATE_MAGIC and the\n",
+    );
+    out.push_str(" * version byte.\n */\n");
+    writeln!(
+        out,
+        "export const GREETING_DISCRIMINATOR = Buffer.from({:?});",
+        GREETING_DISCRIMINATOR
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "export const DISCRIMINATOR_VERSION = {};\n",
+        DISCRIMINATOR_VERSION
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Discriminator of compactly encoded accounts (varints, bitpacked flags), in place\n",
+    );
+    out.push_str(" * of STATE_MAGIC. See src/program-rust/src/compact.rs.\n */\n");
+    writeln!(
+        out,
+        "export const COMPACT_MAGIC = {};",
+        quote(std::str::from_utf8(&COMPACT_MAGIC).unwrap())
+    )
+    .unwrap();
+    writeln!(out, "export const COMPACT_FLAG_COUNTER = {};", FLAG_COUNTER).unwrap();
+    writeln!(
+        out,
+        "export const COMPACT_FLAG_CALENDAR = {};",
+        FLAG_CALENDAR
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "export const COMPACT_FLAG_LAST_UPDATE = {};",
+        FLAG_LAST_UPDATE
+    )
+    .unwrap();
+    writeln!(out, "export const COMPACT_FLAG_ORIGIN = {};\n", FLAG_ORIGIN).unwrap();
+    out.push_str("/**\n * Bytes of the greeted_days bitmap, one bit per day of the year\n */\n");
+    writeln!(out, "export const DAY_BITMAP_SIZE = {};\n", DAY_BITMAP_SIZE).unwrap();
+    out.push_str("/**\n * The most greeting accounts one GreetMany takes\n */\n");
+    writeln!(
+        out,
+        "export const MAX_GREET_MANY_ACCOUNTS = {};\n",
+        MAX_GREET_MANY_ACCOUNTS
+    )
+    .unwrap();
+    out.push_str("/**\n * Seed of the config account's address, a PDA of the program\n */\n");
+    writeln!(
+        out,
+        "export const CONFIG_SEED = {};\n",
+        quote(std::str::from_utf8(CONFIG_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str("/**\n * Seed of the tip vault's address, a PDA of the program\n */\n");
+    writeln!(
+        out,
+        "export const VAULT_SEED = {};\n",
+        quote(std::str::from_utf8(VAULT_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str("/**\n * Seed of the claim vault's address, a PDA of the program\n */\n");
+    writeln!(
+        out,
+        "export const CLAIM_VAULT_SEED = {};\n",
+        quote(std::str::from_utf8(CLAIM_VAULT_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a claimed bitmap, followed by the claim root and the bitmap's number\n */\n",
+    );
+    writeln!(
+        out,
+        "export const CLAIMED_SEED = {};\n",
+        quote(std::str::from_utf8(CLAIMED_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of a claimed bitmap, sha256(\"account:ClaimedBitmap\")[..8]\n */\n",
+    );
+    writeln!(
+        out,
+        "export const CLAIMED_BITMAP_DISCRIMINATOR = Buffer.from({:?});\n",
+        CLAIMED_BITMAP_DISCRIMINATOR
+    )
+    .unwrap();
+    out.push_str("/**\n * Size of a claimed bitmap's data, the discriminator included\n */\n");
+    writeln!(
+        out,
+        "export const CLAIMED_BITMAP_SIZE = {};\n",
+        CLAIMED_BITMAP_SIZE
+    )
+    .unwrap();
+    out.push_str("/**\n * Leaves one claimed bitmap covers\n */\n");
+    writeln!(
+        out,
+        "export const CLAIMS_PER_BITMAP = {};\n",
+        CLAIMS_PER_BITMAP
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a greeting account's ticket, followed by the greeting account's key\n */\n",
+    );
+    writeln!(
+        out,
+        "export const TICKET_SEED = {};\n",
+        quote(std::str::from_utf8(TICKET_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str("/**\n * Seed of a staker's stake position, followed by the staker's key\n */\n");
+    writeln!(
+        out,
+        "export const STAKE_SEED = {};\n",
+        quote(std::str::from_utf8(STAKE_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str("/**\n * Seed of the stake authority, the owner of the stake vault\n */\n");
+    writeln!(
+        out,
+        "export const STAKE_AUTHORITY_SEED = {};\n",
+        quote(std::str::from_utf8(STAKE_AUTHORITY_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a challenge, followed by the challenger's and the challengee's keys\n */\n",
+    );
+    writeln!(
+        out,
+        "export const CHALLENGE_SEED = {};\n",
+        quote(std::str::from_utf8(CHALLENGE_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a foreign greeter, followed by its Wormhole chain id (big-endian u16) and\n * emitter address\n */\n",
+    );
+    writeln!(
+        out,
+        "export const FOREIGN_GREETER_SEED = {};\n",
+        quote(std::str::from_utf8(FOREIGN_GREETER_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of the log authority, the authority of the greeting log's tree\n */\n",
+    );
+    writeln!(
+        out,
+        "export const LOG_AUTHORITY_SEED = {};\n",
+        quote(std::str::from_utf8(LOG_AUTHORITY_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a mint's extra account metas in the transfer-hook program, followed by\n * the mint's key\n */\n",
+    );
+    writeln!(
+        out,
+        "export const EXTRA_ACCOUNT_METAS_SEED = {};\n",
+        quote(std::str::from_utf8(EXTRA_ACCOUNT_METAS_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a mint's transfer greetings in the transfer-hook program, followed by the\n * mint's key\n */\n",
+    );
+    writeln!(
+        out,
+        "export const TRANSFER_GREETINGS_SEED = {};\n",
+        quote(std::str::from_utf8(TRANSFER_GREETINGS_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a session token, followed by the authority's and the session key's keys\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SESSION_SEED = {};\n",
+        quote(std::str::from_utf8(SESSION_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a target's greeting account (GreetFor), followed by the target's key\n */\n",
+    );
+    writeln!(
+        out,
+        "export const TARGET_GREETING_SEED = {};\n",
+        quote(std::str::from_utf8(TARGET_GREETING_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str("/**\n * Longest message GreetFor saves, in bytes\n */\n");
+    writeln!(
+        out,
+        "export const MAX_TARGET_MESSAGE_LEN = {};\n",
+        MAX_TARGET_MESSAGE_LEN
+    )
+    .unwrap();
+    out.push_str("/**\n * Size of a target's greeting account\n */\n");
+    writeln!(
+        out,
+        "export const TARGET_GREETING_SIZE = {};\n",
+        TARGET_GREETING_SIZE
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of an escrow in the escrow program, followed by the initializer's key\n */\n",
+    );
+    writeln!(
+        out,
+        "export const ESCROW_SEED = {};\n",
+        quote(std::str::from_utf8(ESCROW_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of CounterSnapshot, sha256(\"account:CounterSnapshot\")[..8]\n */\n",
+    );
+    writeln!(
+        out,
+        "export const COUNTER_SNAPSHOT_DISCRIMINATOR = Buffer.from({:?});\n",
+        COUNTER_SNAPSHOT_DISCRIMINATOR
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Bytes of a counter snapshot's discriminator and CounterSnapshot header\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SNAPSHOT_HEADER_SIZE = {};\n",
+        SNAPSHOT_HEADER_SIZE
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Bytes of a counter snapshot's leaf, a greeting account key and counter\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SNAPSHOT_LEAF_SIZE = {};\n",
+        SNAPSHOT_LEAF_SIZE
+    )
+    .unwrap();
+    out.push_str("/**\n * Deepest tree a counter snapshot can hold\n */\n");
+    writeln!(
+        out,
+        "export const MAX_SNAPSHOT_DEPTH = {};\n",
+        MAX_SNAPSHOT_DEPTH
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Seed of a greeting receipt, followed by the greeting account's key and counter\n */\n",
+    );
+    writeln!(
+        out,
+        "export const RECEIPT_SEED = {};\n",
+        quote(std::str::from_utf8(RECEIPT_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of GreetingReceipt, sha256(\"account:GreetingReceipt\")[..8]\n */\n",
+    );
+    writeln!(
+        out,
+        "export const RECEIPT_DISCRIMINATOR = Buffer.from({:?});\n",
+        RECEIPT_DISCRIMINATOR
+    )
+    .unwrap();
+    out.push_str("/**\n * Size of a greeting receipt's data, the discriminator included\n */\n");
+    writeln!(out, "export const RECEIPT_SIZE = {};\n", RECEIPT_SIZE).unwrap();
+    out.push_str(
+        "/**\n * Seed of a snapshot, followed by the greeting account's and the recorder's keys and the label\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SNAPSHOT_SEED = {};\n",
+        quote(std::str::from_utf8(SNAPSHOT_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of GreetingSnapshot, sha256(\"account:GreetingSnapshot\")[..8]\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SNAPSHOT_DISCRIMINATOR = Buffer.from({:?});\n",
+        SNAPSHOT_DISCRIMINATOR
+    )
+    .unwrap();
+    out.push_str("/**\n * Longest snapshot label in bytes\n */\n");
+    writeln!(out, "export const MAX_LABEL_LEN = {};\n", MAX_LABEL_LEN).unwrap();
+    out.push_str("/**\n * Seed of the tag counters, followed by the greeting account's key\n */\n");
+    writeln!(
+        out,
+        "export const TAGS_SEED = {};\n",
+        quote(std::str::from_utf8(TAGS_SEED).unwrap())
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Anchor discriminator of TagCounters, sha256(\"account:TagCounters\")[..8]\n */\n",
+    );
+    writeln!(
+        out,
+        "export const TAG_COUNTERS_DISCRIMINATOR = Buffer.from({:?});\n",
+        TAG_COUNTERS_DISCRIMINATOR
+    )
+    .unwrap();
+    out.push_str("/**\n * Most tags one greeting account counts\n */\n");
+    writeln!(out, "export const MAX_TAGS = {};\n", MAX_TAGS).unwrap();
+    out.push_str("/**\n * Size of the tag counters' data, the discriminator included\n */\n");
+    writeln!(
+        out,
+        "export const TAG_COUNTERS_SIZE = {};\n",
+        TAG_COUNTERS_SIZE
+    )
+    .unwrap();
+    out.push_str(
+        "/**\n * Slots the SlotHashes sysvar keeps, the oldest a CheckRecency can name\n */\n",
+    );
+    writeln!(
+        out,
+        "export const SLOT_HASHES_MAX_ENTRIES = {};\n",
+        recency::MAX_ENTRIES
+    )
+    .unwrap();
+    out.push_str("/**\n * Prefix of the log lines carrying a greeting account's state hash\n */\n");
+    writeln!(
+        out,
+        "export const STATE_HASH_PREFIX = {};\n",
+        quote(STATE_HASH_PREFIX)
+    )
+    .unwrap();
+    out.push_str("/**\n * Bytes of the sha256 of the account data kept in a state hash\n */\n");
+    writeln!(out, "export const STATE_HASH_SIZE = {};\n", STATE_HASH_SIZE).unwrap();
+
+    out.push_str("/**\n * Instruction tags, the first byte of every instruction's data\n */\n");
+    out.push_str("export enum HelloWorldInstruction {\n");
+    for (tag, (name, _)) in variants.iter().enumerate() {
+        writeln!(out, "  {} = {},", name, tag).unwrap();
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("/**\n * Program errors, numbered by their `Custom(code)` error code\n */\n");
+    out.push_str("export enum HelloWorldError {\n");
+    for (code, _) in ERROR_MESSAGES {
+        let name = format!("{:?}", HelloWorldError::from_code(*code).unwrap());
+        writeln!(out, "  {} = {},", name, code).unwrap();
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("/**\n * Program error codes, as returned in `Custom(code)` errors\n */\n");
+    out.push_str("export const HELLO_WORLD_ERRORS: {\n");
+    out.push_str("  [code: number]: {name: string; msg: string};\n} = {\n");
+    for (code, msg) in ERROR_MESSAGES {
+        let name = format!("{:?}", HelloWorldError::from_code(*code).unwrap());
+        writeln!(out, "  {}: {{", code).unwrap();
+        writeln!(out, "    name: {},", quote(&name)).unwrap();
+        writeln!(out, "    msg: {},", quote(msg)).unwrap();
+        out.push_str("  },\n");
+    }
+    out.push_str("};\n");
+    out
+}
+
+fn main() {
+    let output = generate();
+    match env::args().nth(1) {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, output) {
+                eprintln!("Failed to write {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", output),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generated_client_types_are_up_to_date() {
+        assert_eq!(
+            include_str!("../../../client/generated.ts"),
+            generate(),
+            "src/client/generated.ts is stale, regenerate it with \
+             `cargo run -p helloworld-schema --bin ts -- src/client/generated.ts`"
+        );
+    }
+
+    #[test]
+    fn test_error_enum_maps_every_code() {
+        let generated = generate();
+        for (code, msg) in ERROR_MESSAGES {
+            let name = format!("{:?}", HelloWorldError::from_code(*code).unwrap());
+            assert!(generated.contains(&format!("  {} = {},\n", name, code)));
+            assert!(generated.contains(&format!(
+                "  {}: {{\n    name: {},\n    msg: {},\n  }},\n",
+                code,
+                quote(&name),
+                quote(msg)
+            )));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+/*
+NOTES:
+- Borsh isn't self-describing: the bytes of a GreetingAccount don't say "this is a
+String". BorshSchema fixes that by letting each type describe its own layout, which
+this binary dumps as JSON so generic decoders and non-Rust clients can parse account
+and instruction data without hand-written layouts (like GreetingSchema in hello_world.ts).
+- Run with `cargo run -p helloworld-schema` (prints to stdout) or pass an output path.
+- When adding a new account/instruction/event type, derive BorshSchema on it and add it
+to schemas() below.
+*/
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use borsh::BorshSchema;
+use helloworld::{
+    challenge::Challenge, config::Config, counter_snapshot::CounterSnapshot, escrow::Escrow,
+    instruction::HelloWorldInstruction, luck::LuckTicket, milestone::GreetingSnapshot,
+    receipt::GreetingReceipt, session::SessionToken, stake::StakePosition, tags::TagCounters,
+    transfer_hook::TransferGreetings, wormhole::ForeignGreeter, GreetingAccount, GreetingAccountV1,
+    GreetingAccountV2, GreetingAccountV3, GreetingAccountV4,
+};
+use serde_json::{json, Map, Value};
+use std::{env, fs, process};
+
+/// Every program type a client may need to decode, grouped by what it is used for
+fn schemas() -> Value {
+    json!({
+        "accounts": {
+            "GreetingAccount": container_to_json(&GreetingAccount::schema_container()),
+            "GreetingAccountV4": container_to_json(&GreetingAccountV4::schema_container()),
+            "GreetingAccountV3": container_to_json(&GreetingAccountV3::schema_container()),
+            "GreetingAccountV2": container_to_json(&GreetingAccountV2::schema_container()),
+            "GreetingAccountV1": container_to_json(&GreetingAccountV1::schema_container()),
+            "Config": container_to_json(&Config::schema_container()),
+            "LuckTicket": container_to_json(&LuckTicket::schema_container()),
+            "StakePosition": container_to_json(&StakePosition::schema_container()),
+            "Challenge": container_to_json(&Challenge::schema_container()),
+            "ForeignGreeter": container_to_json(&ForeignGreeter::schema_container()),
+            "TransferGreetings": container_to_json(&TransferGreetings::schema_container()),
+            "SessionToken": container_to_json(&SessionToken::schema_container()),
+            "Escrow": container_to_json(&Escrow::schema_container()),
+            "CounterSnapshot": container_to_json(&CounterSnapshot::schema_container()),
+            "GreetingReceipt": container_to_json(&GreetingReceipt::schema_container()),
+            "GreetingSnapshot": container_to_json(&GreetingSnapshot::schema_container()),
+            "TagCounters": container_to_json(&TagCounters::schema_container()),
+        },
+        "instructions": container_to_json(&HelloWorldInstruction::schema_container()),
+    })
+}
+
+fn container_to_json(container: &BorshSchemaContainer) -> Value {
+    // NOTE definitions is a HashMap, sort it so the output is stable between runs
+    let mut declarations: Vec<_> = container.definitions.keys().collect();
+    declarations.sort();
+    let mut definitions = Map::new();
+    for declaration in declarations {
+        definitions.insert(
+            declaration.clone(),
+            definition_to_json(&container.definitions[declaration]),
+        );
+    }
+    json!({
+        "declaration": container.declaration,
+        "definitions": definitions,
+    })
+}
+
+fn definition_to_json(definition: &Definition) -> Value {
+    match definition {
+        Definition::Array { length, elements } => {
+            json!({ "kind": "array", "length": length, "elements": elements })
+        }
+        Definition::Sequence { elements } => json!({ "kind": "sequence", "elements": elements }),
+        Definition::Tuple { elements } => json!({ "kind": "tuple", "elements": elements }),
+        Definition::Enum { variants } => json!({ "kind": "enum", "variants": variants }),
+        Definition::Struct { fields } => match fields {
+            Fields::NamedFields(fields) => json!({ "kind": "struct", "fields": fields }),
+            Fields::UnnamedFields(fields) => json!({ "kind": "struct", "fields": fields }),
+            Fields::Empty => json!({ "kind": "struct", "fields": [] }),
+        },
+    }
+}
+
+fn main() {
+    let output = serde_json::to_string_pretty(&schemas()).unwrap();
+    match env::args().nth(1) {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, output + "\n") {
+                eprintln!("Failed to write {}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        None => println!("{}", output),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_greeting_account_schema() {
+        assert_eq!(
+            container_to_json(&GreetingAccount::schema_container()),
+            json!({
+                "declaration": "GreetingAccount",
+                "definitions": {
+                    "GreetingAccount": {
+                        "kind": "struct",
+                        "fields": [
+                            ["txt", "string"],
+                            ["counter", "u64"],
+                            ["calendar_year", "u16"],
+                            ["greeted_days", "Array<u8, 46>"],
+                            ["last_update_epoch", "u64"],
+                            ["direct_greets", "u64"],
+                            ["cpi_greets", "u64"],
+                        ],
+                    },
+                    "Array<u8, 46>": { "kind": "array", "length": 46, "elements": "u8" },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_instruction_schema_lists_variants_in_tag_order() {
+        let schema = container_to_json(&HelloWorldInstruction::schema_container());
+        let variants = &schema["definitions"]["HelloWorldInstruction"]["variants"];
+        assert_eq!(variants[0][0], "Greet");
+        assert_eq!(variants[1][0], "Version");
+        assert_eq!(variants[2][0], "ExportState");
+        assert_eq!(variants[3][0], "ImportState");
+        assert_eq!(variants[4][0], "InitConfig");
+        assert_eq!(variants[5][0], "SetDecay");
+        assert_eq!(variants[6][0], "SetCrankAuthority");
+        assert_eq!(variants[7][0], "Tick");
+        assert_eq!(variants[8][0], "SetFee");
+        assert_eq!(variants[9][0], "CommitLuck");
+        assert_eq!(variants[10][0], "RevealLuck");
+        assert_eq!(variants[11][0], "SetAuthority");
+        assert_eq!(variants[12][0], "SetStake");
+        assert_eq!(variants[13][0], "Deposit");
+        assert_eq!(variants[14][0], "WithdrawStake");
+        assert_eq!(variants[15][0], "CreateChallenge");
+        assert_eq!(variants[16][0], "ClaimChallenge");
+        assert_eq!(variants[17][0], "RefundChallenge");
+        assert_eq!(variants[18][0], "ReceiveCrossChainGreet");
+        assert_eq!(variants[19][0], "InitGreetingLog");
+        assert_eq!(variants[20][0], "CreateSession");
+        assert_eq!(variants[21][0], "RevokeSession");
+        assert_eq!(variants[22][0], "Increment");
+        assert_eq!(variants[23][0], "GreetMany");
+        assert_eq!(variants[24][0], "GreetFor");
+        assert_eq!(variants[25][0], "SetConfig");
+        assert_eq!(variants[26][0], "InitCounterSnapshot");
+        assert_eq!(variants[27][0], "SnapshotCounters");
+        assert_eq!(variants[28][0], "Claim");
+        assert_eq!(variants[29][0], "Snapshot");
+        assert_eq!(variants[30][0], "CountTag");
+        assert_eq!(variants[31][0], "CheckRecency");
+        assert_eq!(variants[32][0], "RegisterForeignGreeter");
+    }
+}
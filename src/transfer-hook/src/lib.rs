@@ -0,0 +1,364 @@
+/*
+NOTES:
+- A second program, implementing the SPL transfer-hook interface: point a Token-2022
+mint's TransferHook extension at it and every transfer of the mint counts as a
+greeting in the mint's transfer greetings account.
+- The layouts, seeds and instruction builders are in the helloworld crate
+(src/program-rust/src/transfer_hook.rs), so clients only need the one interface crate.
+- Build with `npm run build:transfer-hook`, deploy the .so from dist/program, then, as
+the mint authority, `npm run transfer-hook -- init <MINT>`.
+*/
+use borsh::{BorshDeserialize, BorshSerialize};
+use helloworld::transfer_hook::{
+    self, token_2022, TransferGreetings, TransferHookInstruction, EXTRA_ACCOUNT_METAS_SEED,
+    EXTRA_ACCOUNT_METAS_SIZE, TRANSFER_GREETINGS_SEED, TRANSFER_GREETINGS_SIZE,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+// The address the transfer hook is deployed at
+// NOTE After your first deploy, replace this with the output of
+// `solana address -k dist/program/helloworld_transfer_hook-keypair.json`.
+solana_program::declare_id!("5cgtpSM3bKxfqtM6NACrGYHgWrgpdT4ncecVP9XrefZp");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match TransferHookInstruction::unpack(instruction_data) {
+        Some(TransferHookInstruction::Execute { amount }) => {
+            process_execute(program_id, accounts, amount)
+        }
+        Some(TransferHookInstruction::InitializeExtraAccountMetaList) => {
+            process_initialize_extra_account_meta_list(program_id, accounts)
+        }
+        None => {
+            msg!("Not a transfer-hook interface instruction");
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+}
+
+/// Create `account`, already holding its rent, as a PDA of the program at `seeds`
+fn create_pda<'a>(
+    program_id: &Pubkey,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    size: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    // NOTE Allocate and assign rather than create_account, so there's no payer to sign:
+    // the interface passes the mint authority read-only
+    let accounts = [account.clone(), system_program.clone()];
+    invoke_signed(
+        &system_instruction::allocate(account.key, size as u64),
+        &accounts,
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, program_id),
+        &accounts,
+        &[seeds],
+    )
+}
+
+/// Create the mint's extra account metas and transfer greetings
+pub fn process_initialize_extra_account_meta_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let metas_account = next_account_info(accounts_iter)?;
+    let mint = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let greetings_account = next_account_info(accounts_iter)?;
+
+    if *mint.owner != token_2022::id() {
+        msg!("{} is not a Token-2022 mint", mint.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mint_authority = transfer_hook::mint_authority(&mint.data.borrow());
+    if !authority.is_signer || mint_authority != Some(authority.key.to_bytes()) {
+        msg!("{} is not the mint authority or didn't sign", authority.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program.key != system_program::id() {
+        msg!("Expected the system program, got {}", system_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (metas_address, metas_bump) =
+        transfer_hook::extra_account_metas_address(program_id, mint.key);
+    let (greetings_address, greetings_bump) =
+        transfer_hook::transfer_greetings_address(program_id, mint.key);
+    if *metas_account.key != metas_address || *greetings_account.key != greetings_address {
+        msg!(
+            "Not the extra account metas and transfer greetings of {}",
+            mint.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if metas_account.owner == program_id || greetings_account.owner == program_id {
+        msg!("The hook is already initialized for {}", mint.key);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_pda(
+        program_id,
+        metas_account,
+        system_program,
+        EXTRA_ACCOUNT_METAS_SIZE,
+        &[EXTRA_ACCOUNT_METAS_SEED, mint.key.as_ref(), &[metas_bump]],
+    )?;
+    create_pda(
+        program_id,
+        greetings_account,
+        system_program,
+        TRANSFER_GREETINGS_SIZE,
+        &[
+            TRANSFER_GREETINGS_SEED,
+            mint.key.as_ref(),
+            &[greetings_bump],
+        ],
+    )?;
+    metas_account.data.borrow_mut()[..EXTRA_ACCOUNT_METAS_SIZE]
+        .copy_from_slice(&transfer_hook::extra_account_metas_data());
+    TransferGreetings {
+        mint: mint.key.to_bytes(),
+        ..TransferGreetings::default()
+    }
+    .serialize(&mut &mut greetings_account.data.borrow_mut()[..])
+    .map_err(|err| {
+        msg!("Can't write the transfer greetings, {}", err);
+        ProgramError::InvalidAccountData
+    })?;
+    msg!("Transfers of {} now count as greetings", mint.key);
+    Ok(())
+}
+
+/// Count a transfer of `amount` as a greeting
+pub fn process_execute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let mint = next_account_info(accounts_iter)?;
+    let _destination = next_account_info(accounts_iter)?;
+    let _owner = next_account_info(accounts_iter)?;
+    let metas_account = next_account_info(accounts_iter)?;
+    let greetings_account = next_account_info(accounts_iter)?;
+
+    // NOTE Only Token-2022 sets the transferring flag, and only for the length of the
+    // transfer, so this can't be called on its own to count fake transfers
+    if *source.owner != token_2022::id()
+        || transfer_hook::token_account_mint(&source.data.borrow()) != Some(mint.key.to_bytes())
+        || !transfer_hook::is_transferring(&source.data.borrow())
+    {
+        msg!(
+            "{} is not a Token-2022 account being transferred from",
+            source.key
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (metas_address, _) = transfer_hook::extra_account_metas_address(program_id, mint.key);
+    let (greetings_address, _) = transfer_hook::transfer_greetings_address(program_id, mint.key);
+    if *metas_account.key != metas_address
+        || *greetings_account.key != greetings_address
+        || greetings_account.owner != program_id
+    {
+        msg!(
+            "Not the extra account metas and transfer greetings of {}",
+            mint.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mut greetings = TransferGreetings::try_from_slice(&greetings_account.data.borrow())
+        .map_err(|err| {
+            msg!("Can't read the transfer greetings, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    greetings.counter = greetings.counter.saturating_add(1);
+    greetings.volume = greetings.volume.saturating_add(amount);
+    greetings
+        .serialize(&mut &mut greetings_account.data.borrow_mut()[..])
+        .map_err(|err| {
+            msg!("Can't write the transfer greetings, {}", err);
+            ProgramError::InvalidAccountData
+        })?;
+    msg!(
+        "{} greeted {} time(s) by transfer",
+        mint.key,
+        greetings.counter
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_program::{bpf_loader, clock::Epoch};
+
+    fn account<'a>(
+        key: Pubkey,
+        is_signer: bool,
+        is_writable: bool,
+        data: Vec<u8>,
+        owner: Pubkey,
+    ) -> AccountInfo<'a> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            is_writable,
+            Box::leak(Box::new(0)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            owner == bpf_loader::id(),
+            Epoch::default(),
+        )
+    }
+
+    /// Data of a Token-2022 token account of `mint` with the TransferHookAccount
+    /// extension
+    fn token_account(mint: &Pubkey, transferring: bool) -> Vec<u8> {
+        let mut data = vec![0; 165];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[108] = 1;
+        data.push(2);
+        data.extend_from_slice(&[15, 0, 1, 0, transferring as u8]);
+        data
+    }
+
+    #[test]
+    fn test_counts_transfers() {
+        let program_id = id();
+        let (mint_key, authority_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (metas_key, _) = transfer_hook::extra_account_metas_address(&program_id, &mint_key);
+        let (greetings_key, _) = transfer_hook::transfer_greetings_address(&program_id, &mint_key);
+        let mut mint_data = vec![0; 82];
+        mint_data[..4].copy_from_slice(&[1, 0, 0, 0]);
+        mint_data[4..36].copy_from_slice(authority_key.as_ref());
+        let system = system_program::id();
+        // NOTE The allocate and assign CPIs are no-ops in unit tests, so the accounts start
+        // out allocated and the test hands them over to the program itself
+        let metas = account(
+            metas_key,
+            false,
+            true,
+            vec![0; EXTRA_ACCOUNT_METAS_SIZE],
+            system,
+        );
+        let greetings = account(
+            greetings_key,
+            false,
+            true,
+            vec![0; TRANSFER_GREETINGS_SIZE],
+            system,
+        );
+        let mint = account(mint_key, false, false, mint_data, token_2022::id());
+        let authority = account(authority_key, true, false, vec![], system);
+        let system_program = account(system, false, false, vec![], bpf_loader::id());
+        let source = account(
+            Pubkey::new_unique(),
+            false,
+            true,
+            token_account(&mint_key, true),
+            token_2022::id(),
+        );
+        let destination = account(
+            Pubkey::new_unique(),
+            false,
+            true,
+            token_account(&mint_key, false),
+            token_2022::id(),
+        );
+
+        let init = TransferHookInstruction::InitializeExtraAccountMetaList.pack();
+        let mut init_accounts = vec![
+            metas.clone(),
+            mint.clone(),
+            authority,
+            system_program,
+            greetings.clone(),
+        ];
+        let mut not_authority = init_accounts.clone();
+        not_authority[2] = destination.clone();
+        not_authority[2].is_signer = true;
+        assert_eq!(
+            process_instruction(&program_id, &not_authority, &init),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut not_token_2022 = init_accounts.clone();
+        not_token_2022[1].owner = &system;
+        assert_eq!(
+            process_instruction(&program_id, &not_token_2022, &init),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        process_instruction(&program_id, &init_accounts, &init).unwrap();
+        assert_eq!(
+            &metas.data.borrow()[..],
+            &transfer_hook::extra_account_metas_data()[..]
+        );
+
+        let (mut metas, mut greetings) = (metas, greetings);
+        metas.owner = &program_id;
+        greetings.owner = &program_id;
+        init_accounts[0] = metas.clone();
+        init_accounts[4] = greetings.clone();
+        assert_eq!(
+            process_instruction(&program_id, &init_accounts, &init),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        let execute = |amount| TransferHookInstruction::Execute { amount }.pack();
+        let accounts = vec![
+            source.clone(),
+            mint.clone(),
+            destination.clone(),
+            destination.clone(),
+            metas,
+            greetings.clone(),
+        ];
+        process_instruction(&program_id, &accounts, &execute(500)).unwrap();
+        process_instruction(&program_id, &accounts, &execute(250)).unwrap();
+        assert_eq!(
+            TransferGreetings::try_from_slice(&greetings.data.borrow()).unwrap(),
+            TransferGreetings {
+                mint: mint_key.to_bytes(),
+                counter: 2,
+                volume: 750,
+            }
+        );
+
+        // Calling the hook outside a transfer doesn't count
+        let mut not_transferring = accounts.clone();
+        not_transferring[0] = destination;
+        assert_eq!(
+            process_instruction(&program_id, &not_transferring, &execute(500)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut other_mint = accounts;
+        other_mint[1] = source;
+        assert_eq!(
+            process_instruction(&program_id, &other_mint, &execute(500)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            process_instruction(&program_id, &[], &[0; 16]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Compare a deployed program with a local build, the way solana-verify does
+// NOTE Shared by the `verify` binary and the deployer, which checks every deploy
+use solana_program::{hash::hash, pubkey::Pubkey};
+use std::{env, fs, process::Command};
+
+/// Hash program bytes the same way solana-verify does (trailing zero padding removed)
+pub fn executable_hash(bytes: &[u8]) -> String {
+    let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    hash(&bytes[..len]).to_string()
+}
+
+/// Fetch the deployed program bytes through the solana CLI
+pub fn dump_program(url: Option<&str>, program_id: &Pubkey) -> Result<Vec<u8>, String> {
+    let out_path = env::temp_dir().join(format!("helloworld-verify-{}.so", program_id));
+    let mut cmd = Command::new("solana");
+    cmd.args(["program", "dump"]);
+    if let Some(url) = url {
+        cmd.args(["--url", url]);
+    }
+    cmd.arg(program_id.to_string()).arg(&out_path);
+
+    let status = cmd
+        .status()
+        .map_err(|err| format!("Failed to run `solana program dump`: {}", err))?;
+    if !status.success() {
+        return Err(format!("`solana program dump` exited with {}", status));
+    }
+    let bytes = fs::read(&out_path)
+        .map_err(|err| format!("Failed to read {}: {}", out_path.display(), err))?;
+    let _ = fs::remove_file(&out_path);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_executable_hash_ignores_trailing_zeros() {
+        let so = vec![0x7f, b'E', b'L', b'F', 0, 1, 2];
+        let mut padded = so.clone();
+        padded.extend_from_slice(&[0; 64]);
+        assert_eq!(executable_hash(&so), executable_hash(&padded));
+        assert_ne!(executable_hash(&so), executable_hash(&so[..6]));
+    }
+}
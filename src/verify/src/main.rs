@@ -0,0 +1,90 @@
+/*
+NOTES:
+- This is a small helper to confirm that the program deployed on chain is byte-for-byte
+the program built from this source. It dumps the on-chain program with the solana CLI
+(`solana program dump`) and compares its sha256 hash with the local .so file.
+- The program data account is allocated bigger than the .so file (so it can be upgraded
+in place), and the extra space is zero-filled. solana-verify strips those trailing zeros
+before hashing, so we do the same here to get matching hashes.
+- For a fully reproducible comparison, build the local .so with `solana-verify build`
+(pinned docker image) rather than a plain `cargo build-bpf`.
+*/
+use helloworld_verify::{dump_program, executable_hash};
+use solana_program::pubkey::Pubkey;
+use std::{env, fs, path::PathBuf, process, str::FromStr};
+
+const USAGE: &str = "Usage: verify [--url <URL>] [--program-id <PUBKEY>] [--so <PATH>]
+
+Options:
+  --url <URL>           RPC URL or moniker passed to the solana CLI (defaults to the CLI config)
+  --program-id <PUBKEY> Deployed program to check (defaults to helloworld::id())
+  --so <PATH>           Local build to compare against (defaults to dist/program/helloworld.so)";
+
+struct Args {
+    url: Option<String>,
+    program_id: Pubkey,
+    so_path: PathBuf,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        url: None,
+        program_id: helloworld::id(),
+        so_path: PathBuf::from("dist/program/helloworld.so"),
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "--url" | "-u" => args.url = Some(value()?),
+            "--program-id" => {
+                let value = value()?;
+                args.program_id = Pubkey::from_str(&value)
+                    .map_err(|err| format!("Invalid program id {}: {:?}", value, err))?;
+            }
+            "--so" => args.so_path = PathBuf::from(value()?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument {}", flag)),
+        }
+    }
+    Ok(args)
+}
+
+fn run() -> Result<bool, String> {
+    let args = parse_args()?;
+
+    let local = fs::read(&args.so_path)
+        .map_err(|err| format!("Failed to read {}: {}", args.so_path.display(), err))?;
+    let on_chain = dump_program(args.url.as_deref(), &args.program_id)?;
+
+    let local_hash = executable_hash(&local);
+    let on_chain_hash = executable_hash(&on_chain);
+    println!("Program id:     {}", args.program_id);
+    println!(
+        "Local build:    {} ({})",
+        local_hash,
+        args.so_path.display()
+    );
+    println!("On-chain build: {}", on_chain_hash);
+    Ok(local_hash == on_chain_hash)
+}
+
+fn main() {
+    match run() {
+        Ok(true) => println!("Verified: on-chain program matches the local build"),
+        Ok(false) => {
+            eprintln!("Mismatch: on-chain program does NOT match the local build");
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    }
+}
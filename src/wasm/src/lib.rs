@@ -0,0 +1,553 @@
+/*
+NOTES:
+- Browser bindings over the same Rust types the program uses, so a web app can decode
+greeting accounts and build instruction data without a hand-written Borsh schema.
+- Build with wasm-pack (`wasm-pack build src/wasm --target web`) or plain cargo
+(`cargo build -p helloworld-wasm --target wasm32-unknown-unknown --release`) followed
+by wasm-bindgen. The program crate is pulled in without its "program" feature because
+solana-program 1.6 can't compile to wasm32.
+- Instruction builders return only the data bytes. Wrap them in a web3.js
+TransactionInstruction with the program id and the accounts listed in instruction.rs:
+    new TransactionInstruction({keys: [{pubkey, isSigner: false, isWritable: true}],
+      programId, data: Buffer.from(greetInstructionData('Hello1234567'))})
+*/
+use borsh::BorshSerialize;
+use helloworld::{
+    error::HelloWorldError,
+    instruction::HelloWorldInstruction,
+    milestone::{is_valid_label, MAX_LABEL_LEN},
+    tags, GreetingAccount,
+};
+use std::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+// NOTE JsValue can only be created inside a wasm runtime, so the logic lives in plain
+// Rust functions (which the unit tests call) and the exports just convert errors.
+fn decode_txt(data: &[u8]) -> Result<String, String> {
+    // Accounts are allocated at a fixed size, unpack() ignores the zeroed tail
+    GreetingAccount::unpack(data)
+        .map(|account| account.txt)
+        .map_err(|err| format!("Invalid greeting account: {}", err))
+}
+
+fn instruction_data(instruction: HelloWorldInstruction) -> Vec<u8> {
+    instruction.try_to_vec().unwrap()
+}
+
+/// Decode a greeting account's data and return its message
+#[wasm_bindgen(js_name = decodeGreetingAccount)]
+pub fn decode_greeting_account(data: &[u8]) -> Result<String, JsValue> {
+    decode_txt(data).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Instruction data for Greet. Accounts: 0. `[writable]` the greeting account,
+/// 1. `[]` the config account (optional, without it the counter doesn't decay)
+#[wasm_bindgen(js_name = greetInstructionData)]
+pub fn greet_instruction_data(txt: String) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::Greet { txt })
+}
+
+/// Instruction data for Version. Accounts: none
+#[wasm_bindgen(js_name = versionInstructionData)]
+pub fn version_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::Version)
+}
+
+/// Instruction data for ExportState. Accounts: 0. `[]` the greeting account
+#[wasm_bindgen(js_name = exportStateInstructionData)]
+pub fn export_state_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::ExportState)
+}
+
+/// Instruction data for ImportState. Accounts: 0. `[writable]` the greeting account,
+/// 1. `[]` the account to import from, 2. `[]` the config account, 3. `[signer]` the
+/// config authority
+#[wasm_bindgen(js_name = importStateInstructionData)]
+pub fn import_state_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::ImportState)
+}
+
+/// Instruction data for InitConfig. Accounts: 0. `[writable]` the config account,
+/// 1. `[signer, writable]` the authority, 2. `[]` the system program
+#[wasm_bindgen(js_name = initConfigInstructionData)]
+pub fn init_config_instruction_data(decay_half_life_epochs: u64) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::InitConfig {
+        decay_half_life_epochs,
+    })
+}
+
+/// Instruction data for SetDecay. Accounts: 0. `[writable]` the config account,
+/// 1. `[signer]` the config authority
+#[wasm_bindgen(js_name = setDecayInstructionData)]
+pub fn set_decay_instruction_data(half_life_epochs: u64) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::SetDecay { half_life_epochs })
+}
+
+fn crank_authority_data(crank_authority: &[u8]) -> Result<Vec<u8>, String> {
+    let crank_authority = <[u8; 32]>::try_from(crank_authority)
+        .map_err(|_| format!("A key is 32 bytes, not {}", crank_authority.len()))?;
+    Ok(instruction_data(HelloWorldInstruction::SetCrankAuthority {
+        crank_authority,
+    }))
+}
+
+/// Instruction data for SetCrankAuthority naming the 32-byte key `crankAuthority`
+/// (all zeros for none). Accounts: 0. `[writable]` the config account, 1. `[signer]`
+/// the config authority
+#[wasm_bindgen(js_name = setCrankAuthorityInstructionData)]
+pub fn set_crank_authority_instruction_data(crank_authority: &[u8]) -> Result<Vec<u8>, JsValue> {
+    crank_authority_data(crank_authority).map_err(|err| JsValue::from_str(&err))
+}
+
+fn authority_data(new_authority: &[u8]) -> Result<Vec<u8>, String> {
+    let new_authority = <[u8; 32]>::try_from(new_authority)
+        .map_err(|_| format!("A key is 32 bytes, not {}", new_authority.len()))?;
+    Ok(instruction_data(HelloWorldInstruction::SetAuthority {
+        new_authority,
+    }))
+}
+
+/// Instruction data for SetAuthority handing the config to the 32-byte key
+/// `newAuthority`. Accounts: 0. `[writable]` the config account, 1. `[signer]` the
+/// config authority
+#[wasm_bindgen(js_name = setAuthorityInstructionData)]
+pub fn set_authority_instruction_data(new_authority: &[u8]) -> Result<Vec<u8>, JsValue> {
+    authority_data(new_authority).map_err(|err| JsValue::from_str(&err))
+}
+
+fn fee_data(
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: &[u8],
+    fee_recipient: &[u8],
+) -> Result<Vec<u8>, String> {
+    let key = |name: &str, key: &[u8]| {
+        <[u8; 32]>::try_from(key)
+            .map_err(|_| format!("The {} is 32 bytes, not {}", name, key.len()))
+    };
+    Ok(instruction_data(HelloWorldInstruction::SetFee {
+        fee_usd_micros,
+        max_price_age_slots,
+        max_confidence_bps,
+        price_feed: key("price feed", price_feed)?,
+        fee_recipient: key("fee recipient", fee_recipient)?,
+    }))
+}
+
+/// Instruction data for SetFee, charging `feeUsdMicros` millionths of a dollar per
+/// Greet at the price of the Pyth SOL/USD account `priceFeed`, paid to
+/// `feeRecipient` (both 32-byte keys). Accounts: 0. `[writable]` the config account,
+/// 1. `[signer]` the config authority
+#[wasm_bindgen(js_name = setFeeInstructionData)]
+pub fn set_fee_instruction_data(
+    fee_usd_micros: u64,
+    max_price_age_slots: u64,
+    max_confidence_bps: u16,
+    price_feed: &[u8],
+    fee_recipient: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    fee_data(
+        fee_usd_micros,
+        max_price_age_slots,
+        max_confidence_bps,
+        price_feed,
+        fee_recipient,
+    )
+    .map_err(|err| JsValue::from_str(&err))
+}
+
+/// Instruction data for Tick. Accounts: 0. `[]` the config account, 1. `[signer]` the
+/// crank authority, then `[writable]` each greeting account to maintain
+#[wasm_bindgen(js_name = tickInstructionData)]
+pub fn tick_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::Tick)
+}
+
+/// Instruction data for CommitLuck. Accounts: 0. `[]` the greeting account, 1.
+/// `[writable]` its ticket, 2. `[]` the Switchboard randomness account, 3. `[signer,
+/// writable]` the greeter, 4. `[]` the system program
+#[wasm_bindgen(js_name = commitLuckInstructionData)]
+pub fn commit_luck_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::CommitLuck)
+}
+
+/// Instruction data for RevealLuck. Accounts: 0. `[]` the greeting account, 1.
+/// `[writable]` its ticket, 2. `[]` the randomness account committed to, 3.
+/// `[writable]` the tip vault, 4. `[writable]` the greeter, 5. `[]` the system program
+#[wasm_bindgen(js_name = revealLuckInstructionData)]
+pub fn reveal_luck_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::RevealLuck)
+}
+
+fn stake_data(stake_mint: &[u8], stake_unit: u64) -> Result<Vec<u8>, String> {
+    let stake_mint = <[u8; 32]>::try_from(stake_mint)
+        .map_err(|_| format!("A key is 32 bytes, not {}", stake_mint.len()))?;
+    Ok(instruction_data(HelloWorldInstruction::SetStake {
+        stake_mint,
+        stake_unit,
+    }))
+}
+
+/// Instruction data for SetStake, weighting greetings by stake of the 32-byte mint key
+/// `stakeMint`, 1 more for every `stakeUnit` base units. Accounts: 0. `[writable]` the
+/// config account, 1. `[signer]` the config authority
+#[wasm_bindgen(js_name = setStakeInstructionData)]
+pub fn set_stake_instruction_data(stake_mint: &[u8], stake_unit: u64) -> Result<Vec<u8>, JsValue> {
+    stake_data(stake_mint, stake_unit).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Instruction data for Deposit, staking `amount` tokens. Accounts: 0. `[]` the config
+/// account, 1. `[writable]` the stake position, 2. `[signer, writable]` the staker, 3.
+/// `[writable]` the token account to take from, 4. `[writable]` the stake vault, 5.
+/// `[]` the token program, 6. `[]` the system program
+#[wasm_bindgen(js_name = depositInstructionData)]
+pub fn deposit_instruction_data(amount: u64) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::Deposit { amount })
+}
+
+/// Instruction data for WithdrawStake, paying back `amount` staked tokens. Accounts:
+/// 0. `[writable]` the stake position, 1. `[signer]` the staker, 2. `[writable]` the
+/// stake vault, 3. `[writable]` the token account to pay to, 4. `[]` the stake
+/// authority, 5. `[]` the token program
+#[wasm_bindgen(js_name = withdrawStakeInstructionData)]
+pub fn withdraw_stake_instruction_data(amount: u64) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::WithdrawStake { amount })
+}
+
+/// Instruction data for CreateChallenge, escrowing `amount` lamports for the challengee
+/// to greet `greetCount` more times before `deadlineSlot`. Accounts: 0. `[writable]`
+/// the challenge, 1. `[signer, writable]` the challenger, 2. `[]` the challengee, 3.
+/// `[]` the greeting account, 4. `[]` the system program
+#[wasm_bindgen(js_name = createChallengeInstructionData)]
+pub fn create_challenge_instruction_data(
+    greet_count: u64,
+    deadline_slot: u64,
+    amount: u64,
+) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::CreateChallenge {
+        greet_count,
+        deadline_slot,
+        amount,
+    })
+}
+
+/// Instruction data for ClaimChallenge. Accounts: 0. `[writable]` the challenge, 1.
+/// `[signer, writable]` the challengee, 2. `[]` the challenge's greeting account
+#[wasm_bindgen(js_name = claimChallengeInstructionData)]
+pub fn claim_challenge_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::ClaimChallenge)
+}
+
+/// Instruction data for RefundChallenge. Accounts: 0. `[writable]` the challenge, 1.
+/// `[signer, writable]` the challenger
+#[wasm_bindgen(js_name = refundChallengeInstructionData)]
+pub fn refund_challenge_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::RefundChallenge)
+}
+
+/// Instruction data for ReceiveCrossChainGreet. Accounts: 0. `[]` the posted VAA, 1.
+/// `[writable]` the emitter's foreign greeter
+#[wasm_bindgen(js_name = receiveCrossChainGreetInstructionData)]
+pub fn receive_cross_chain_greet_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::ReceiveCrossChainGreet)
+}
+
+/// Instruction data for InitGreetingLog, starting a greeting log `maxDepth` deep that
+/// keeps `maxBufferSize` changes. Accounts: 0. `[writable]` the config account, 1.
+/// `[signer]` the config authority, 2. `[writable]` the merkle tree, 3. `[]` the log
+/// authority, 4. `[]` the spl-account-compression program, 5. `[]` the spl-noop program
+#[wasm_bindgen(js_name = initGreetingLogInstructionData)]
+pub fn init_greeting_log_instruction_data(max_depth: u32, max_buffer_size: u32) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::InitGreetingLog {
+        max_depth,
+        max_buffer_size,
+    })
+}
+
+/// Instruction data for CreateSession, letting a session key greet for the authority
+/// for `durationSeconds` and topping it up with `topUpLamports`. Accounts: 0.
+/// `[writable]` the session token, 1. `[signer, writable]` the authority, 2. `[signer,
+/// writable]` the session key, 3. `[]` the system program
+#[wasm_bindgen(js_name = createSessionInstructionData)]
+pub fn create_session_instruction_data(duration_seconds: u64, top_up_lamports: u64) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::CreateSession {
+        duration_seconds,
+        top_up_lamports,
+    })
+}
+
+/// Instruction data for RevokeSession. Accounts: 0. `[writable]` the session token, 1.
+/// `[signer, writable]` the authority
+#[wasm_bindgen(js_name = revokeSessionInstructionData)]
+pub fn revoke_session_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::RevokeSession)
+}
+
+/// Instruction data for Increment, counting a greeting without changing the message.
+/// Accounts: 0. `[writable]` the greeting account, 1. `[]` the config account
+#[wasm_bindgen(js_name = incrementInstructionData)]
+pub fn increment_instruction_data() -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::Increment)
+}
+
+/// Instruction data for GreetMany, saving `txt` into every greeting account passed.
+/// Accounts: 0. `[]` the config account, 1.. `[writable]` the greeting accounts, at
+/// most MAX_GREET_MANY_ACCOUNTS of them
+#[wasm_bindgen(js_name = greetManyInstructionData)]
+pub fn greet_many_instruction_data(txt: String) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::GreetMany { txt })
+}
+
+/// Instruction data for GreetFor, saving `txt` into the target's greeting account at
+/// the payer's expense. Accounts: 0. `[writable]` the target's greeting account,
+/// 1. `[]` the config account, 2. `[signer, writable]` the payer, 3. `[]` the target,
+/// 4. `[]` the system program
+#[wasm_bindgen(js_name = greetForInstructionData)]
+pub fn greet_for_instruction_data(txt: String) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::GreetFor { txt })
+}
+
+/// Instruction data for InitCounterSnapshot, making the client-allocated snapshot
+/// account a merkle tree `depth` levels deep. Accounts: 0. `[]` the config account, 1.
+/// `[signer]` the config authority, 2. `[writable]` the snapshot
+#[wasm_bindgen(js_name = initCounterSnapshotInstructionData)]
+pub fn init_counter_snapshot_instruction_data(depth: u8) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::InitCounterSnapshot { depth })
+}
+
+/// Instruction data for SnapshotCounters, writing the counter of each greeting account
+/// passed into the snapshot leaf at the same position in `indices`. Accounts: 0. `[]`
+/// the config account, 1. `[signer]` the crank authority, 2. `[writable]` the
+/// snapshot, 3.. `[]` the greeting accounts
+#[wasm_bindgen(js_name = snapshotCountersInstructionData)]
+pub fn snapshot_counters_instruction_data(indices: Vec<u32>) -> Vec<u8> {
+    instruction_data(HelloWorldInstruction::SnapshotCounters { indices })
+}
+
+fn claim_data(index: u32, amount: u64, proof: &[u8]) -> Result<Vec<u8>, String> {
+    let siblings = proof.chunks_exact(32);
+    if !siblings.remainder().is_empty() {
+        return Err(format!(
+            "A proof is 32-byte hashes, not {} bytes",
+            proof.len()
+        ));
+    }
+    let proof = siblings
+        .map(|sibling| {
+            let mut hash = [0; 32];
+            hash.copy_from_slice(sibling);
+            hash
+        })
+        .collect();
+    Ok(instruction_data(HelloWorldInstruction::Claim {
+        index,
+        amount,
+        proof,
+    }))
+}
+
+/// Instruction data for Claim, paying out leaf `index` of `amount` lamports of the
+/// config's claim root. `proof` is the siblings from the leaf up, 32 bytes each, one
+/// after the other. Accounts: 0. `[]` the config account, 1. `[signer, writable]` the
+/// claimant, 2. `[writable]` the leaf's claimed bitmap, 3. `[writable]` the claim vault,
+/// 4. `[]` the system program
+#[wasm_bindgen(js_name = claimInstructionData)]
+pub fn claim_instruction_data(index: u32, amount: u64, proof: &[u8]) -> Result<Vec<u8>, JsValue> {
+    claim_data(index, amount, proof).map_err(|err| JsValue::from_str(&err))
+}
+
+fn snapshot_data(label: String) -> Result<Vec<u8>, String> {
+    if !is_valid_label(&label) {
+        return Err(format!(
+            "A label is 1 to {} bytes, not {}",
+            MAX_LABEL_LEN,
+            label.len()
+        ));
+    }
+    Ok(instruction_data(HelloWorldInstruction::Snapshot { label }))
+}
+
+/// Instruction data for Snapshot, recording the greeting account's counter under
+/// `label`. Accounts: 0. `[]` the config account, 1. `[]` the greeting account,
+/// 2. `[signer, writable]` the recorder, 3. `[writable]` the snapshot, 4. `[]` the
+/// system program
+#[wasm_bindgen(js_name = snapshotInstructionData)]
+pub fn snapshot_instruction_data(label: String) -> Result<Vec<u8>, JsValue> {
+    snapshot_data(label).map_err(|err| JsValue::from_str(&err))
+}
+
+fn count_tag_data(tag: &str) -> Result<Vec<u8>, String> {
+    let tag = tags::tag(tag).ok_or_else(|| format!("A tag is 1 to 8 bytes, not {}", tag.len()))?;
+    Ok(instruction_data(HelloWorldInstruction::CountTag { tag }))
+}
+
+/// Instruction data for CountTag, counting a greeting under the tag named `tag`.
+/// Accounts: 0. `[]` the config account, 1. `[]` the greeting account, 2. `[signer,
+/// writable]` the payer, 3. `[writable]` the tag counters, 4. `[]` the system program
+#[wasm_bindgen(js_name = countTagInstructionData)]
+pub fn count_tag_instruction_data(tag: String) -> Result<Vec<u8>, JsValue> {
+    count_tag_data(&tag).map_err(|err| JsValue::from_str(&err))
+}
+
+fn check_recency_data(slot: u64, slot_hash: &[u8], max_age_slots: u64) -> Result<Vec<u8>, String> {
+    let slot_hash = <[u8; 32]>::try_from(slot_hash)
+        .map_err(|_| format!("A slot hash is 32 bytes, not {}", slot_hash.len()))?;
+    Ok(instruction_data(HelloWorldInstruction::CheckRecency {
+        slot,
+        slot_hash,
+        max_age_slots,
+    }))
+}
+
+/// Instruction data for CheckRecency, failing unless the 32-byte `slotHash` is the hash
+/// of `slot`, at most `maxAgeSlots` old. Accounts: 0. `[]` the SlotHashes sysvar
+#[wasm_bindgen(js_name = checkRecencyInstructionData)]
+pub fn check_recency_instruction_data(
+    slot: u64,
+    slot_hash: &[u8],
+    max_age_slots: u64,
+) -> Result<Vec<u8>, JsValue> {
+    check_recency_data(slot, slot_hash, max_age_slots).map_err(|err| JsValue::from_str(&err))
+}
+
+fn register_foreign_greeter_data(chain: u16, sender: &[u8]) -> Result<Vec<u8>, String> {
+    let sender = <[u8; 32]>::try_from(sender)
+        .map_err(|_| format!("A sender is 32 bytes, not {}", sender.len()))?;
+    Ok(instruction_data(
+        HelloWorldInstruction::RegisterForeignGreeter { chain, sender },
+    ))
+}
+
+/// Instruction data for RegisterForeignGreeter, letting the emitter at the 32-byte
+/// universal address `sender` on Wormhole chain `chain` greet. Accounts: 0. `[]` the
+/// config account, 1. `[signer, writable]` the config authority, 2. `[writable]` the
+/// emitter's foreign greeter, 3. `[]` the system program
+#[wasm_bindgen(js_name = registerForeignGreeterInstructionData)]
+pub fn register_foreign_greeter_instruction_data(
+    chain: u16,
+    sender: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    register_foreign_greeter_data(chain, sender).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Human-readable message for a `Custom(code)` program error, or undefined
+#[wasm_bindgen(js_name = errorMessage)]
+pub fn error_message(code: u32) -> Option<String> {
+    HelloWorldError::from_code(code).map(|error| error.message().to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_greet_data_decodes_as_account() {
+        let data = greet_instruction_data("Hello".to_string());
+        assert_eq!(data[0], 0);
+        // Pad like a real account to check the zeroed tail is ignored
+        let mut account = data[1..].to_vec();
+        account.resize(4 + 12, 0);
+        assert_eq!(decode_txt(&account).unwrap(), "Hello");
+        assert!(decode_txt(&[0xff; 4]).is_err());
+    }
+
+    #[test]
+    fn test_version_and_errors() {
+        assert_eq!(version_instruction_data(), vec![1]);
+        assert_eq!(export_state_instruction_data(), vec![2]);
+        assert_eq!(import_state_instruction_data(), vec![3]);
+        assert_eq!(
+            init_config_instruction_data(10),
+            vec![4, 10, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            set_decay_instruction_data(0),
+            vec![5, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        let data = crank_authority_data(&[7; 32]).unwrap();
+        assert_eq!(data[0], 6);
+        assert_eq!(&data[1..], &[7; 32][..]);
+        assert!(crank_authority_data(&[7; 31]).is_err());
+        assert_eq!(tick_instruction_data(), vec![7]);
+        let data = fee_data(10_000, 25, 100, &[7; 32], &[8; 32]).unwrap();
+        assert_eq!((data[0], data.len()), (8, 1 + 8 + 8 + 2 + 32 + 32));
+        assert!(fee_data(10_000, 25, 100, &[7; 32], &[8; 31]).is_err());
+        assert_eq!(commit_luck_instruction_data(), vec![9]);
+        assert_eq!(reveal_luck_instruction_data(), vec![10]);
+        let data = authority_data(&[7; 32]).unwrap();
+        assert_eq!((data[0], &data[1..]), (11, &[7; 32][..]));
+        assert!(authority_data(&[7; 33]).is_err());
+        let data = stake_data(&[7; 32], 1_000).unwrap();
+        assert_eq!((data[0], &data[1..33], data.len()), (12, &[7; 32][..], 41));
+        assert!(stake_data(&[7; 31], 1_000).is_err());
+        assert_eq!(
+            deposit_instruction_data(500),
+            vec![13, 244, 1, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            withdraw_stake_instruction_data(500),
+            vec![14, 244, 1, 0, 0, 0, 0, 0, 0]
+        );
+        let data = create_challenge_instruction_data(3, 1_000, 500);
+        assert_eq!((data[0], data.len()), (15, 1 + 8 + 8 + 8));
+        assert_eq!(&data[9..17], &1_000u64.to_le_bytes());
+        assert_eq!(claim_challenge_instruction_data(), vec![16]);
+        assert_eq!(refund_challenge_instruction_data(), vec![17]);
+        assert_eq!(receive_cross_chain_greet_instruction_data(), vec![18]);
+        assert_eq!(
+            init_greeting_log_instruction_data(14, 64),
+            vec![19, 14, 0, 0, 0, 64, 0, 0, 0]
+        );
+        let data = create_session_instruction_data(3_600, 500);
+        assert_eq!((data[0], data.len()), (20, 1 + 8 + 8));
+        assert_eq!(&data[1..9], &3_600u64.to_le_bytes());
+        assert_eq!(revoke_session_instruction_data(), vec![21]);
+        assert_eq!(increment_instruction_data(), vec![22]);
+        assert_eq!(
+            greet_many_instruction_data("hi".to_string()),
+            vec![23, 2, 0, 0, 0, b'h', b'i']
+        );
+        assert_eq!(
+            greet_for_instruction_data("hi".to_string()),
+            vec![24, 2, 0, 0, 0, b'h', b'i']
+        );
+        assert_eq!(init_counter_snapshot_instruction_data(4), vec![26, 4]);
+        assert_eq!(
+            snapshot_counters_instruction_data(vec![0, 1]),
+            vec![27, 2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]
+        );
+        let data = claim_data(3, 500, &[7; 64]).unwrap();
+        assert_eq!((data[0], data.len()), (28, 1 + 4 + 8 + 4 + 64));
+        assert_eq!(&data[13..17], &2u32.to_le_bytes());
+        assert!(claim_data(3, 500, &[7; 33]).is_err());
+        assert_eq!(
+            snapshot_data("v1".to_string()).unwrap(),
+            vec![29, 2, 0, 0, 0, b'v', b'1']
+        );
+        assert!(snapshot_data(String::new()).is_err());
+        assert_eq!(
+            count_tag_data("gm").unwrap(),
+            vec![30, b'g', b'm', 0, 0, 0, 0, 0, 0]
+        );
+        assert!(count_tag_data("too long!").is_err());
+        let data = check_recency_data(7, &[9; 32], 150).unwrap();
+        assert_eq!(
+            (data[0], &data[1..9], &data[9..41]),
+            (31, &7u64.to_le_bytes()[..], &[9; 32][..])
+        );
+        assert_eq!(&data[41..], &150u64.to_le_bytes());
+        assert!(check_recency_data(7, &[9; 31], 150).is_err());
+        let data = register_foreign_greeter_data(2, &[7; 32]).unwrap();
+        assert_eq!(
+            (data[0], &data[1..3], &data[3..]),
+            (32, &[2, 0][..], &[7; 32][..])
+        );
+        assert!(register_foreign_greeter_data(2, &[7; 31]).is_err());
+        assert_eq!(
+            error_message(6001).as_deref(),
+            Some(HelloWorldError::MessageTooLong.message())
+        );
+        assert_eq!(error_message(1), None);
+    }
+}
@@ -0,0 +1,463 @@
+/*
+NOTES:
+- One entry point for the commands otherwise spread over package.json, the READMEs and
+the CI config: `cargo xtask <command>` (the alias is in .cargo/config.toml).
+- Each command is a list of steps, printed before it runs and stopping at the first
+that fails, so `--dry-run` shows exactly what would be run.
+- The programs still build with `cargo build-bpf`: build-sbf is the name newer
+toolchains use, but solana 1.6 only ships the bpf one.
+- The crates with their own lockfiles (bench, geyser, ledger, wasm) are tested from their own
+manifests, since `cargo test --workspace` doesn't reach them.
+- sdk-matrix runs helloworld's unit tests against each solana-program it builds with:
+the pinned 1.6 and the manifests under src/program-rust/sdk/ (see src/sdk.rs there).
+bench-sdk builds Increment on pinocchio (src/lite.rs) and the program on 1.18 with
+build-sbf, which 1.18 ships, and prints what each costs.
+*/
+use std::{env, fs, path::Path, process};
+
+const USAGE: &str = "Usage: cargo xtask <command> [options] [--dry-run]
+
+Commands:
+  build-sbf [--program <NAME>] [--no-log]
+                                Build the on-chain programs into dist/program,
+                                --no-log without helloworld's verbose logging
+  check-size [--program <NAME>] [--no-log]
+                                Build-sbf, then fail if a .so is over its budget
+  test-all [--bpf]              Clippy and every test, --bpf also runs the program's
+                                integration tests against the BPF build
+  start-localnet [ARGS]...      Run solana-test-validator, passing ARGS through
+  deploy-local                  Build helloworld, fund the payer and deploy to localnet
+  sdk-matrix                    Clippy and helloworld's unit tests against each
+                                solana-program version it supports
+  bench-sdk                     Build Increment on pinocchio and on solana-program
+                                1.18 into dist/sdk, then compare their compute
+                                units and sizes
+
+Programs: helloworld, transfer-hook, escrow";
+
+/// The on-chain programs, by name, and their manifests
+const PROGRAMS: &[(&str, &str)] = &[
+    ("helloworld", "src/program-rust/Cargo.toml"),
+    ("transfer-hook", "src/transfer-hook/Cargo.toml"),
+    ("escrow", "src/escrow/Cargo.toml"),
+];
+
+/// Most bytes each program's .so in dist/program may take
+// NOTE helloworld's is SIZE_BUDGET in src/program-rust/tests/size.rs, which `cargo
+// test-bpf` checks; raise both together
+const SIZE_BUDGETS: &[(&str, &str, u64)] = &[
+    ("helloworld", "helloworld.so", 512 * 1024),
+    ("transfer-hook", "helloworld_transfer_hook.so", 256 * 1024),
+    ("escrow", "helloworld_escrow.so", 256 * 1024),
+];
+
+/// Crates outside the workspace, with their own lockfiles, and the flags they test with
+const OWN_LOCKFILES: &[(&str, &[&str])] = &[
+    // NOTE Runs each benchmark once, as a test, rather than timing it
+    ("src/bench/Cargo.toml", &["--benches"]),
+    ("src/geyser/Cargo.toml", &[]),
+    // NOTE USB access needs libudev, which CI and most contributors don't have
+    ("src/ledger/Cargo.toml", &["--no-default-features"]),
+    ("src/wasm/Cargo.toml", &[]),
+];
+
+/// The solana-program versions helloworld builds with and the manifests building it
+/// against each
+const SDKS: &[(&str, &str)] = &[
+    ("1.6", "src/program-rust/Cargo.toml"),
+    ("1.18", "src/program-rust/sdk/1.18/Cargo.toml"),
+    ("2.1", "src/program-rust/sdk/2.1/Cargo.toml"),
+];
+
+/// A command line to run from the repo root
+#[derive(Debug, PartialEq)]
+struct Step {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Step {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    fn run(&self, root: &Path) -> Result<(), String> {
+        let status = process::Command::new(&self.program)
+            .args(&self.args)
+            .current_dir(root)
+            .status()
+            .map_err(|err| format!("Failed to run {}: {}", self.program, err))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("`{}` exited with {}", self, status))
+        }
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+fn build_sbf(manifest: &str) -> Step {
+    Step::new(
+        "cargo",
+        &[
+            "build-bpf",
+            &format!("--manifest-path={}", manifest),
+            "--bpf-out-dir=dist/program",
+        ],
+    )
+}
+
+/// The programs `--program` picks out of `names`, all of them without it
+fn selected<'a>(names: &'a [&'a str], args: &[String]) -> Result<Vec<&'a str>, String> {
+    let only = match args.iter().position(|arg| arg == "--program") {
+        Some(i) => Some(args.get(i + 1).ok_or("Missing value for --program")?),
+        None => None,
+    };
+    let names: Vec<_> = names
+        .iter()
+        .copied()
+        .filter(|name| only.is_none_or(|only| only == name))
+        .collect();
+    if names.is_empty() {
+        return Err(format!("Unknown program {}", only.unwrap()));
+    }
+    Ok(names)
+}
+
+/// The .so files `check-size` measures and their budgets, given the arguments after it
+fn size_checks(args: &[String]) -> Result<Vec<(String, u64)>, String> {
+    let names: Vec<_> = SIZE_BUDGETS.iter().map(|(name, _, _)| *name).collect();
+    let names = selected(&names, args)?;
+    Ok(SIZE_BUDGETS
+        .iter()
+        .filter(|(name, _, _)| names.contains(name))
+        .map(|(_, library, budget)| (format!("dist/program/{}", library), *budget))
+        .collect())
+}
+
+/// Fails if the file at `path` is over `budget` bytes
+fn check_size(root: &Path, path: &str, budget: u64) -> Result<(), String> {
+    let size = fs::metadata(root.join(path))
+        .map_err(|err| format!("Can't read {}: {}", path, err))?
+        .len();
+    println!("{} is {} bytes of {}", path, size, budget);
+    if size > budget {
+        return Err(format!(
+            "{} is {} bytes, over the budget of {}",
+            path, size, budget
+        ));
+    }
+    Ok(())
+}
+
+/// The steps of `command`, given the arguments after it
+fn plan(command: &str, args: &[String]) -> Result<Vec<Step>, String> {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let steps = match command {
+        // NOTE check-size measures what build-sbf builds, then run() checks it
+        "build-sbf" | "check-size" => {
+            let names: Vec<_> = PROGRAMS.iter().map(|(name, _)| *name).collect();
+            let names = selected(&names, args)?;
+            PROGRAMS
+                .iter()
+                .filter(|(name, _)| names.contains(name))
+                .map(|(name, manifest)| {
+                    let mut step = build_sbf(manifest);
+                    // NOTE Only helloworld has the verbose logging to leave out
+                    if *name == "helloworld" && flag("--no-log") {
+                        step.args.extend(
+                            [
+                                "--no-default-features",
+                                "--features",
+                                "program,custom-heap,custom-panic",
+                            ]
+                            .iter()
+                            .map(|arg| arg.to_string()),
+                        );
+                    }
+                    step
+                })
+                .collect()
+        }
+        "test-all" => {
+            let mut steps = vec![
+                Step::new(
+                    "cargo",
+                    &[
+                        "clippy",
+                        "--workspace",
+                        "--all-targets",
+                        "--",
+                        "-D",
+                        "warnings",
+                    ],
+                ),
+                Step::new("cargo", &["test", "--workspace"]),
+            ];
+            for (manifest, flags) in OWN_LOCKFILES {
+                let manifest = format!("--manifest-path={}", manifest);
+                let mut args = vec!["test", manifest.as_str()];
+                args.extend_from_slice(flags);
+                steps.push(Step::new("cargo", &args));
+            }
+            if flag("--bpf") {
+                steps.push(Step::new(
+                    "cargo",
+                    &[
+                        "test-bpf",
+                        "--manifest-path=src/program-rust/Cargo.toml",
+                        "--",
+                        "--include-ignored",
+                    ],
+                ));
+            }
+            steps
+        }
+        "sdk-matrix" => SDKS
+            .iter()
+            .flat_map(|(_, manifest)| {
+                let manifest = format!("--manifest-path={}", manifest);
+                vec![
+                    Step::new(
+                        "cargo",
+                        &[
+                            "clippy",
+                            &manifest,
+                            "--lib",
+                            "--tests",
+                            "--features=test-utils",
+                            "--",
+                            "-D",
+                            "warnings",
+                        ],
+                    ),
+                    Step::new(
+                        "cargo",
+                        &["test", &manifest, "--lib", "--features=test-utils"],
+                    ),
+                ]
+            })
+            .collect(),
+        "bench-sdk" => vec![
+            Step::new(
+                "cargo",
+                &[
+                    "build-sbf",
+                    "--manifest-path=src/program-rust/sdk/1.18/Cargo.toml",
+                    "--sbf-out-dir=dist/sdk",
+                    "--no-default-features",
+                    "--features",
+                    "program,custom-heap,custom-panic",
+                ],
+            ),
+            Step::new(
+                "cargo",
+                &[
+                    "build-sbf",
+                    "--manifest-path=src/program-rust/sdk/pinocchio/Cargo.toml",
+                    "--sbf-out-dir=dist/sdk",
+                ],
+            ),
+            // NOTE The test runs in its manifest's directory, so dist/sdk is four up
+            Step::new(
+                "env",
+                &[
+                    "SBF_OUT_DIR=../../../../dist/sdk",
+                    "cargo",
+                    "test",
+                    "--manifest-path=src/program-rust/sdk/pinocchio/Cargo.toml",
+                    "--test",
+                    "bench",
+                    "--",
+                    "--include-ignored",
+                    "--nocapture",
+                ],
+            ),
+        ],
+        "start-localnet" => {
+            let mut step = Step::new("solana-test-validator", &["--reset", "--quiet"]);
+            step.args
+                .extend(args.iter().filter(|arg| *arg != "--dry-run").cloned());
+            vec![step]
+        }
+        "deploy-local" => vec![
+            build_sbf(PROGRAMS[0].1),
+            Step::new("solana", &["airdrop", "10", "--url", "localhost"]),
+            Step::new(
+                "cargo",
+                &[
+                    "run",
+                    "-q",
+                    "-p",
+                    "helloworld-deploy",
+                    "--",
+                    "--url",
+                    "localhost",
+                    "--skip-build",
+                ],
+            ),
+        ],
+        _ => return Err(format!("Unknown command {}", command)),
+    };
+    Ok(steps)
+}
+
+fn run() -> Result<(), String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = args.first().ok_or("Missing the command")?;
+    if command == "--help" || command == "-h" {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    for step in plan(command, &args[1..])? {
+        println!("$ {}", step);
+        if !dry_run {
+            step.run(&root)?;
+        }
+    }
+    if command == "check-size" {
+        for (path, budget) in size_checks(&args[1..])? {
+            println!("$ check {} <= {} bytes", path, budget);
+            if !dry_run {
+                check_size(&root, &path, budget)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}\n\n{}", err, USAGE);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn commands(command: &str, args: &[&str]) -> Vec<String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        plan(command, &args)
+            .unwrap()
+            .iter()
+            .map(|step| step.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_build_sbf() {
+        assert_eq!(commands("build-sbf", &[]).len(), PROGRAMS.len());
+        assert_eq!(
+            commands("build-sbf", &["--program", "escrow"]),
+            vec![
+                "cargo build-bpf --manifest-path=src/escrow/Cargo.toml \
+                 --bpf-out-dir=dist/program"
+            ]
+        );
+        assert!(
+            commands("build-sbf", &["--program", "helloworld", "--no-log"])[0]
+                .ends_with("--no-default-features --features program,custom-heap,custom-panic")
+        );
+        assert!(plan("build-sbf", &["--program".to_string()]).is_err());
+        assert!(plan("build-sbf", &["--program".to_string(), "nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_check_size() {
+        assert_eq!(commands("check-size", &[]), commands("build-sbf", &[]));
+        assert_eq!(
+            size_checks(&["--program".to_string(), "escrow".to_string()]).unwrap(),
+            vec![("dist/program/helloworld_escrow.so".to_string(), 256 * 1024)]
+        );
+        // Every program has a budget
+        assert!(PROGRAMS
+            .iter()
+            .zip(SIZE_BUDGETS)
+            .all(|((name, _), (budgeted, _, _))| name == budgeted));
+        assert_eq!(size_checks(&[]).unwrap().len(), PROGRAMS.len());
+        assert!(plan("check-size", &["--program".to_string(), "nope".to_string()]).is_err());
+
+        let root = env::temp_dir();
+        let path = format!("helloworld-xtask-size-{}.so", process::id());
+        fs::write(root.join(&path), [0; 100]).unwrap();
+        assert!(check_size(&root, &path, 100).is_ok());
+        assert!(check_size(&root, &path, 99).is_err());
+        fs::remove_file(root.join(&path)).unwrap();
+        assert!(check_size(&root, &path, 100).is_err());
+    }
+
+    #[test]
+    fn test_test_all() {
+        let steps = commands("test-all", &[]);
+        assert_eq!(steps.len(), 2 + OWN_LOCKFILES.len());
+        assert!(steps.contains(
+            &"cargo test --manifest-path=src/ledger/Cargo.toml --no-default-features".to_string()
+        ));
+        let bpf = commands("test-all", &["--bpf"]);
+        assert!(bpf.last().unwrap().starts_with("cargo test-bpf"));
+        // The size budget is ignored without a BPF build, so it only runs here
+        assert!(bpf.last().unwrap().ends_with("-- --include-ignored"));
+    }
+
+    #[test]
+    fn test_sdk_matrix() {
+        let steps = commands("sdk-matrix", &[]);
+        assert_eq!(steps.len(), 2 * SDKS.len());
+        // At least two majors besides the pinned one
+        assert!(SDKS.len() >= 3);
+        assert_eq!(
+            steps[3],
+            "cargo test --manifest-path=src/program-rust/sdk/1.18/Cargo.toml --lib \
+             --features=test-utils"
+        );
+        // Every manifest but the pinned one turns the shims on
+        for (version, manifest) in &SDKS[1..] {
+            let toml = fs::read_to_string(Path::new("../..").join(manifest)).unwrap();
+            assert!(toml.contains("\"sdk-1-18\""), "{}", manifest);
+            assert!(
+                toml.contains(&format!("version = \"~{}\"", version)),
+                "{}",
+                manifest
+            );
+        }
+    }
+
+    #[test]
+    fn test_bench_sdk() {
+        let steps = commands("bench-sdk", &[]);
+        assert_eq!(steps.len(), 3);
+        // Both without helloworld's verbose logging, which the pinocchio build has none of
+        assert!(steps[0].ends_with("--features program,custom-heap,custom-panic"));
+        assert!(steps[1].contains("sdk/pinocchio/Cargo.toml --sbf-out-dir=dist/sdk"));
+        assert!(steps[2].starts_with("env SBF_OUT_DIR=../../../../dist/sdk cargo test"));
+        assert!(Path::new("../program-rust/sdk/pinocchio/tests/bench.rs").is_file());
+    }
+
+    #[test]
+    fn test_localnet() {
+        assert_eq!(
+            commands("start-localnet", &["--limit-ledger-size", "--dry-run"]),
+            vec!["solana-test-validator --reset --quiet --limit-ledger-size"]
+        );
+        let steps = commands("deploy-local", &[]);
+        assert!(steps[0].contains("src/program-rust/Cargo.toml"));
+        assert!(steps[2].ends_with("--url localhost --skip-build"));
+        assert!(plan("deploy", &[]).is_err());
+    }
+}